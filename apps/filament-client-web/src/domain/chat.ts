@@ -37,7 +37,8 @@ export type PermissionName =
   | "create_message"
   | "publish_video"
   | "publish_screen_share"
-  | "subscribe_streams";
+  | "subscribe_streams"
+  | "mention_everyone";
 export type MediaPublishSource = "microphone" | "camera" | "screen_share";
 
 export type MarkdownToken =
@@ -524,7 +525,8 @@ export function permissionFromInput(input: string): PermissionName {
     input !== "create_message" &&
     input !== "publish_video" &&
     input !== "publish_screen_share" &&
-    input !== "subscribe_streams"
+    input !== "subscribe_streams" &&
+    input !== "mention_everyone"
   ) {
     throw new DomainValidationError("Invalid permission.");
   }
@@ -54,6 +54,7 @@ export const KNOWN_PERMISSIONS: readonly PermissionName[] = [
   "publish_video",
   "publish_screen_share",
   "subscribe_streams",
+  "mention_everyone",
 ];
 
 const PERMISSION_BITS: Record<PermissionName, PermissionBits> = {
@@ -69,6 +70,7 @@ const PERMISSION_BITS: Record<PermissionName, PermissionBits> = {
   publish_video: 1 << 9,
   publish_screen_share: 1 << 10,
   subscribe_streams: 1 << 11,
+  mention_everyone: 1 << 12,
 };
 
 const KNOWN_PERMISSION_MASK = KNOWN_PERMISSIONS.reduce<PermissionBits>(
@@ -19,6 +19,12 @@ export const PERMISSION_MATRIX: readonly PermissionMatrixEntry[] = [
     summary: "Send messages and participate in channels.",
     category: "workspace",
   },
+  {
+    permission: "mention_everyone",
+    label: "Mention Everyone",
+    summary: "Notify the whole workspace with @everyone and @here.",
+    category: "workspace",
+  },
   {
     permission: "delete_message",
     label: "Delete Messages",
@@ -19,6 +19,8 @@ import {
   type PresenceUpdatePayload,
 } from "./gateway-presence-events";
 import {
+  type HistoryPayload,
+  type MessageCreateAckPayload,
   type ReadyPayload,
 } from "./gateway-ready-dispatch";
 
@@ -95,6 +97,15 @@ export interface ChannelCreatePayload {
   channel: ChannelRecord;
 }
 
+export interface ChannelUpdatePayload {
+  guildId: GuildId;
+  channelId: ChannelId;
+  updatedFields: {
+    locked?: boolean;
+  };
+  updatedAtUnix: number;
+}
+
 export interface WorkspaceUpdatePayload {
   guildId: GuildId;
   updatedFields: {
@@ -280,6 +291,13 @@ export interface MessageReactionPayload {
   actorUserId?: UserId;
 }
 
+export interface MessageMentionPayload {
+  guildId: GuildId;
+  channelId: ChannelId;
+  messageId: MessageId;
+  scope: "everyone" | "here";
+}
+
 export interface MessageUpdatePayload {
   guildId: GuildId;
   channelId: ChannelId;
@@ -300,11 +318,15 @@ export interface MessageDeletePayload {
 
 export interface GatewayHandlers {
   onReady?: (payload: ReadyPayload) => void;
+  onHistory?: (payload: HistoryPayload) => void;
   onMessageCreate?: (message: MessageRecord) => void;
+  onMessageCreateAck?: (payload: MessageCreateAckPayload) => void;
   onMessageUpdate?: (payload: MessageUpdatePayload) => void;
   onMessageDelete?: (payload: MessageDeletePayload) => void;
   onMessageReaction?: (payload: MessageReactionPayload) => void;
+  onMessageMention?: (payload: MessageMentionPayload) => void;
   onChannelCreate?: (payload: ChannelCreatePayload) => void;
+  onChannelUpdate?: (payload: ChannelUpdatePayload) => void;
   onWorkspaceUpdate?: (payload: WorkspaceUpdatePayload) => void;
   onWorkspaceMemberAdd?: (payload: WorkspaceMemberAddPayload) => void;
   onWorkspaceMemberUpdate?: (payload: WorkspaceMemberUpdatePayload) => void;
@@ -1,5 +1,6 @@
 import type {
   MessageDeletePayload,
+  MessageMentionPayload,
   MessageReactionPayload,
   MessageUpdatePayload,
 } from "./gateway-contracts";
@@ -19,6 +20,7 @@ export interface MessageGatewayDispatchHandlers {
   onMessageUpdate?: (payload: MessageUpdatePayload) => void;
   onMessageDelete?: (payload: MessageDeletePayload) => void;
   onMessageReaction?: (payload: MessageReactionPayload) => void;
+  onMessageMention?: (payload: MessageMentionPayload) => void;
 }
 
 export const MESSAGE_GATEWAY_DISPATCH_EVENT_TYPES: readonly string[] = [
@@ -26,6 +28,7 @@ export const MESSAGE_GATEWAY_DISPATCH_EVENT_TYPES: readonly string[] = [
   "message_update",
   "message_delete",
   "message_reaction",
+  "message_mention",
 ];
 
 const MESSAGE_GATEWAY_EVENT_TYPE_SET = new Set<string>(
@@ -52,6 +55,9 @@ const MESSAGE_DISPATCH_TABLE: GatewayDispatchTable<
   message_reaction: (eventPayload, eventHandlers) => {
     eventHandlers.onMessageReaction?.(eventPayload);
   },
+  message_mention: (eventPayload, eventHandlers) => {
+    eventHandlers.onMessageMention?.(eventPayload);
+  },
 };
 
 export function dispatchMessageGatewayEvent(
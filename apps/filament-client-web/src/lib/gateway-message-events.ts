@@ -17,6 +17,7 @@ import {
 } from "../domain/chat";
 import type {
   MessageDeletePayload,
+  MessageMentionPayload,
   MessageReactionPayload,
   MessageUpdatePayload,
 } from "./gateway-contracts";
@@ -37,6 +38,10 @@ type MessageGatewayEvent =
   | {
       type: "message_reaction";
       payload: MessageReactionPayload;
+    }
+  | {
+      type: "message_mention";
+      payload: MessageMentionPayload;
     };
 
 type MessageGatewayEventType = MessageGatewayEvent["type"];
@@ -115,6 +120,32 @@ function parseMessageReactionPayload(payload: unknown): MessageReactionPayload |
   return next;
 }
 
+function parseMessageMentionPayload(payload: unknown): MessageMentionPayload | null {
+  if (!payload || typeof payload !== "object") {
+    return null;
+  }
+  const value = payload as Record<string, unknown>;
+  if (
+    typeof value.guild_id !== "string" ||
+    typeof value.channel_id !== "string" ||
+    typeof value.message_id !== "string" ||
+    (value.scope !== "everyone" && value.scope !== "here")
+  ) {
+    return null;
+  }
+
+  try {
+    return {
+      guildId: guildIdFromInput(value.guild_id),
+      channelId: channelIdFromInput(value.channel_id),
+      messageId: messageIdFromInput(value.message_id),
+      scope: value.scope,
+    };
+  } catch {
+    return null;
+  }
+}
+
 function parseMessageUpdatePayload(payload: unknown): MessageUpdatePayload | null {
   if (!payload || typeof payload !== "object") {
     return null;
@@ -222,6 +253,7 @@ const MESSAGE_EVENT_DECODERS: {
   message_update: parseMessageUpdatePayload,
   message_delete: parseMessageDeletePayload,
   message_reaction: parseMessageReactionPayload,
+  message_mention: parseMessageMentionPayload,
 };
 
 function isMessageGatewayEventType(value: string): value is MessageGatewayEventType {
@@ -269,6 +301,17 @@ export function decodeMessageGatewayEvent(
     };
   }
 
+  if (type === "message_mention") {
+    const parsedPayload = MESSAGE_EVENT_DECODERS.message_mention(payload);
+    if (!parsedPayload) {
+      return null;
+    }
+    return {
+      type,
+      payload: parsedPayload,
+    };
+  }
+
   const parsedPayload = MESSAGE_EVENT_DECODERS.message_reaction(payload);
   if (!parsedPayload) {
     return null;
@@ -1,7 +1,13 @@
 import {
   channelIdFromInput,
   guildIdFromInput,
+  messageFromResponse,
+  messageHistoryFromResponse,
   userIdFromInput,
+  type ChannelId,
+  type GuildId,
+  type MessageHistory,
+  type MessageRecord,
 } from "../domain/chat";
 
 export interface ReadyPayload {
@@ -13,14 +19,28 @@ export interface SubscribedPayload {
   channelId: string;
 }
 
+export interface HistoryPayload extends MessageHistory {
+  guildId: GuildId;
+  channelId: ChannelId;
+}
+
+export interface MessageCreateAckPayload {
+  nonce: string | null;
+  message: MessageRecord;
+}
+
 export interface ReadyGatewayDispatchHandlers {
   onReady?: (payload: ReadyPayload) => void;
   onSubscribed?: (payload: SubscribedPayload) => void;
+  onHistory?: (payload: HistoryPayload) => void;
+  onMessageCreateAck?: (payload: MessageCreateAckPayload) => void;
 }
 
 export const READY_GATEWAY_DISPATCH_EVENT_TYPES: readonly string[] = [
   "ready",
   "subscribed",
+  "history",
+  "message_create_ack",
 ];
 
 function parseReadyPayload(payload: unknown): ReadyPayload | null {
@@ -67,6 +87,47 @@ function parseSubscribedPayload(payload: unknown): SubscribedPayload | null {
   return { guildId, channelId };
 }
 
+function parseHistoryPayload(payload: unknown): HistoryPayload | null {
+  const scope = parseSubscribedPayload(payload);
+  if (!scope) {
+    return null;
+  }
+
+  let history: MessageHistory;
+  try {
+    history = messageHistoryFromResponse(payload);
+  } catch {
+    return null;
+  }
+
+  return { ...scope, ...history };
+}
+
+function parseMessageCreateAckPayload(payload: unknown): MessageCreateAckPayload | null {
+  if (!payload || typeof payload !== "object") {
+    return null;
+  }
+
+  const value = payload as Record<string, unknown>;
+  let nonce: string | null;
+  if (value.nonce === null) {
+    nonce = null;
+  } else if (typeof value.nonce === "string") {
+    nonce = value.nonce;
+  } else {
+    return null;
+  }
+
+  let message: MessageRecord;
+  try {
+    message = messageFromResponse(payload);
+  } catch {
+    return null;
+  }
+
+  return { nonce, message };
+}
+
 export function dispatchReadyGatewayEvent(
   type: string,
   payload: unknown,
@@ -102,3 +163,39 @@ export function dispatchSubscribedGatewayEvent(
   handlers.onSubscribed?.(subscribedPayload);
   return true;
 }
+
+export function dispatchHistoryGatewayEvent(
+  type: string,
+  payload: unknown,
+  handlers: ReadyGatewayDispatchHandlers,
+): boolean {
+  if (type !== "history") {
+    return false;
+  }
+
+  const historyPayload = parseHistoryPayload(payload);
+  if (!historyPayload) {
+    return true;
+  }
+
+  handlers.onHistory?.(historyPayload);
+  return true;
+}
+
+export function dispatchMessageCreateAckGatewayEvent(
+  type: string,
+  payload: unknown,
+  handlers: ReadyGatewayDispatchHandlers,
+): boolean {
+  if (type !== "message_create_ack") {
+    return false;
+  }
+
+  const ackPayload = parseMessageCreateAckPayload(payload);
+  if (!ackPayload) {
+    return true;
+  }
+
+  handlers.onMessageCreateAck?.(ackPayload);
+  return true;
+}
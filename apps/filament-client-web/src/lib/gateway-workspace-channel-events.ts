@@ -1,17 +1,28 @@
 import {
   channelFromResponse,
+  channelIdFromInput,
   guildIdFromInput,
+  type ChannelId,
   type GuildId,
 } from "../domain/chat";
-import type { ChannelCreatePayload } from "./gateway-contracts";
+import type { ChannelCreatePayload, ChannelUpdatePayload } from "./gateway-contracts";
 
-export type WorkspaceChannelGatewayEvent = {
-  type: "channel_create";
-  payload: ChannelCreatePayload;
-};
+type WorkspaceChannelGatewayEventType = "channel_create" | "channel_update";
 
-export function isWorkspaceChannelGatewayEventType(value: string): value is "channel_create" {
-  return value === "channel_create";
+export type WorkspaceChannelGatewayEvent =
+  | {
+      type: "channel_create";
+      payload: ChannelCreatePayload;
+    }
+  | {
+      type: "channel_update";
+      payload: ChannelUpdatePayload;
+    };
+
+export function isWorkspaceChannelGatewayEventType(
+  value: string,
+): value is WorkspaceChannelGatewayEventType {
+  return value === "channel_create" || value === "channel_update";
 }
 
 function parseChannelCreatePayload(payload: unknown): ChannelCreatePayload | null {
@@ -38,6 +49,48 @@ function parseChannelCreatePayload(payload: unknown): ChannelCreatePayload | nul
   };
 }
 
+function parseChannelUpdatePayload(payload: unknown): ChannelUpdatePayload | null {
+  if (!payload || typeof payload !== "object") {
+    return null;
+  }
+  const value = payload as Record<string, unknown>;
+  if (
+    typeof value.guild_id !== "string" ||
+    typeof value.channel_id !== "string" ||
+    !value.updated_fields ||
+    typeof value.updated_fields !== "object" ||
+    typeof value.updated_at_unix !== "number" ||
+    !Number.isSafeInteger(value.updated_at_unix) ||
+    value.updated_at_unix < 1
+  ) {
+    return null;
+  }
+
+  const updatedFieldsDto = value.updated_fields as Record<string, unknown>;
+  if (typeof updatedFieldsDto.locked !== "boolean") {
+    return null;
+  }
+  const updatedFields: ChannelUpdatePayload["updatedFields"] = {
+    locked: updatedFieldsDto.locked,
+  };
+
+  let guildId: GuildId;
+  let channelId: ChannelId;
+  try {
+    guildId = guildIdFromInput(value.guild_id);
+    channelId = channelIdFromInput(value.channel_id);
+  } catch {
+    return null;
+  }
+
+  return {
+    guildId,
+    channelId,
+    updatedFields,
+    updatedAtUnix: value.updated_at_unix,
+  };
+}
+
 export function decodeWorkspaceChannelGatewayEvent(
   type: string,
   payload: unknown,
@@ -46,6 +99,17 @@ export function decodeWorkspaceChannelGatewayEvent(
     return null;
   }
 
+  if (type === "channel_update") {
+    const parsedPayload = parseChannelUpdatePayload(payload);
+    if (!parsedPayload) {
+      return null;
+    }
+    return {
+      type,
+      payload: parsedPayload,
+    };
+  }
+
   const parsedPayload = parseChannelCreatePayload(payload);
   if (!parsedPayload) {
     return null;
@@ -55,4 +119,4 @@ export function decodeWorkspaceChannelGatewayEvent(
     type,
     payload: parsedPayload,
   };
-}
\ No newline at end of file
+}
@@ -1,5 +1,6 @@
 import type {
   ChannelCreatePayload,
+  ChannelUpdatePayload,
   WorkspaceChannelPermissionOverrideUpdatePayload,
   WorkspaceChannelOverrideUpdatePayload,
   WorkspaceIpBanSyncPayload,
@@ -26,6 +27,7 @@ import {
 
 export interface WorkspaceGatewayDispatchHandlers {
   onChannelCreate?: (payload: ChannelCreatePayload) => void;
+  onChannelUpdate?: (payload: ChannelUpdatePayload) => void;
   onWorkspaceUpdate?: (payload: WorkspaceUpdatePayload) => void;
   onWorkspaceMemberAdd?: (payload: WorkspaceMemberAddPayload) => void;
   onWorkspaceMemberUpdate?: (payload: WorkspaceMemberUpdatePayload) => void;
@@ -50,6 +52,7 @@ export interface WorkspaceGatewayDispatchHandlers {
 
 export const WORKSPACE_GATEWAY_DISPATCH_EVENT_TYPES: readonly string[] = [
   "channel_create",
+  "channel_update",
   "workspace_update",
   "workspace_member_add",
   "workspace_member_update",
@@ -77,6 +80,9 @@ const WORKSPACE_DISPATCH_TABLE: GatewayDispatchTable<
   channel_create: (eventPayload, eventHandlers) => {
     eventHandlers.onChannelCreate?.(eventPayload);
   },
+  channel_update: (eventPayload, eventHandlers) => {
+    eventHandlers.onChannelUpdate?.(eventPayload);
+  },
   workspace_update: (eventPayload, eventHandlers) => {
     eventHandlers.onWorkspaceUpdate?.(eventPayload);
   },
@@ -2,6 +2,7 @@ import { type AccessToken } from "../domain/auth";
 import {
   type ChannelId,
   type GuildId,
+  type MessageId,
 } from "../domain/chat";
 import {
   parseGatewayEventEnvelope,
@@ -10,6 +11,8 @@ import {
   dispatchGatewayDomainEvent,
 } from "./gateway-domain-dispatch";
 import {
+  dispatchHistoryGatewayEvent,
+  dispatchMessageCreateAckGatewayEvent,
   dispatchSubscribedGatewayEvent,
   dispatchReadyGatewayEvent,
 } from "./gateway-ready-dispatch";
@@ -28,12 +31,24 @@ type GatewayEnvelopeDispatcher = (
 const GATEWAY_ENVELOPE_DISPATCHERS: readonly GatewayEnvelopeDispatcher[] = [
   dispatchReadyGatewayEvent,
   dispatchSubscribedGatewayEvent,
+  dispatchHistoryGatewayEvent,
+  dispatchMessageCreateAckGatewayEvent,
   dispatchGatewayDomainEvent,
 ];
 
+export interface GatewayHistoryRequest {
+  limit?: number;
+  before?: MessageId;
+}
+
 interface GatewayClient {
   updateSubscription: (guildId: GuildId, channelId: ChannelId) => void;
   setSubscribedChannels: (guildId: GuildId, channelIds: ReadonlyArray<ChannelId>) => void;
+  requestHistory: (
+    guildId: GuildId,
+    channelId: ChannelId,
+    request?: GatewayHistoryRequest,
+  ) => boolean;
   close: () => void;
 }
 
@@ -52,13 +67,20 @@ function normalizeGatewayBaseUrl(): string {
   return "";
 }
 
-export function resolveGatewayUrl(accessToken: AccessToken): string {
+export const GATEWAY_WS_PROTOCOL = "filament.gateway.v1";
+const GATEWAY_WS_TOKEN_PROTOCOL_PREFIX = "filament.access_token.";
+
+export function resolveGatewayUrl(): string {
   const base = normalizeGatewayBaseUrl();
-  const query = `access_token=${encodeURIComponent(accessToken)}`;
   if (base.length > 0) {
-    return `${base}/gateway/ws?${query}`;
+    return `${base}/gateway/ws`;
   }
-  return `/gateway/ws?${query}`;
+  return "/gateway/ws";
+}
+
+// The token rides in Sec-WebSocket-Protocol so it never lands in URLs or proxy logs.
+export function gatewayProtocols(accessToken: AccessToken): string[] {
+  return [GATEWAY_WS_PROTOCOL, `${GATEWAY_WS_TOKEN_PROTOCOL_PREFIX}${accessToken}`];
 }
 
 function sendEnvelope(socket: WebSocket, type: string, data: unknown): void {
@@ -120,6 +142,7 @@ export function connectGateway(
     return {
       updateSubscription: () => {},
       setSubscribedChannels: () => {},
+      requestHistory: () => false,
       close: () => {},
     };
   }
@@ -169,7 +192,7 @@ export function connectGateway(
   const connect = () => {
     if (isClosed) return;
 
-    socket = new WebSocket(resolveGatewayUrl(accessToken));
+    socket = new WebSocket(resolveGatewayUrl(), gatewayProtocols(accessToken));
 
     socket.onopen = () => {
       retryDelay = 1000;
@@ -223,6 +246,18 @@ export function connectGateway(
         );
       }
     },
+    requestHistory: (historyGuildId, historyChannelId, request = {}) => {
+      if (!socket || socket.readyState !== WebSocket.OPEN) {
+        return false;
+      }
+      sendEnvelope(socket, "history_request", {
+        guild_id: historyGuildId,
+        channel_id: historyChannelId,
+        ...(typeof request.limit === "number" ? { limit: request.limit } : {}),
+        ...(request.before ? { before: request.before } : {}),
+      });
+      return true;
+    },
     close: () => {
       isClosed = true;
       if (reconnectTimer) {
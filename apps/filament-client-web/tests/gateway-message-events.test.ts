@@ -73,6 +73,28 @@ describe("decodeMessageGatewayEvent", () => {
     expect(result).toBeNull();
   });
 
+  it("decodes message_mention payload and rejects unknown scopes", () => {
+    const payload = {
+      guild_id: DEFAULT_GUILD_ID,
+      channel_id: DEFAULT_CHANNEL_ID,
+      message_id: DEFAULT_MESSAGE_ID,
+      scope: "here",
+    };
+
+    expect(decodeMessageGatewayEvent("message_mention", payload)).toEqual({
+      type: "message_mention",
+      payload: {
+        guildId: DEFAULT_GUILD_ID,
+        channelId: DEFAULT_CHANNEL_ID,
+        messageId: DEFAULT_MESSAGE_ID,
+        scope: "here",
+      },
+    });
+    expect(
+      decodeMessageGatewayEvent("message_mention", { ...payload, scope: "role" }),
+    ).toBeNull();
+  });
+
   it("fails closed for invalid message_reaction payload", () => {
     const result = decodeMessageGatewayEvent("message_reaction", {
       guild_id: DEFAULT_GUILD_ID,
@@ -1,4 +1,6 @@
 import {
+  dispatchHistoryGatewayEvent,
+  dispatchMessageCreateAckGatewayEvent,
   dispatchSubscribedGatewayEvent,
   dispatchReadyGatewayEvent,
 } from "../src/lib/gateway-ready-dispatch";
@@ -6,6 +8,7 @@ import {
 const DEFAULT_USER_ID = "01ARZ3NDEKTSV4RRFFQ69G5FAV";
 const DEFAULT_GUILD_ID = "01ARZ3NDEKTSV4RRFFQ69G5FAV";
 const DEFAULT_CHANNEL_ID = "01ARZ3NDEKTSV4RRFFQ69G5FAW";
+const DEFAULT_MESSAGE_ID = "01ARZ3NDEKTSV4RRFFQ69G5FAX";
 
 describe("dispatchReadyGatewayEvent", () => {
   it("dispatches valid ready payloads", () => {
@@ -108,3 +111,119 @@ describe("dispatchSubscribedGatewayEvent", () => {
     expect(onSubscribed).not.toHaveBeenCalled();
   });
 });
+
+describe("dispatchHistoryGatewayEvent", () => {
+  it("dispatches valid history pages", () => {
+    const onHistory = vi.fn();
+
+    const handled = dispatchHistoryGatewayEvent(
+      "history",
+      {
+        guild_id: DEFAULT_GUILD_ID,
+        channel_id: DEFAULT_CHANNEL_ID,
+        messages: [
+          {
+            message_id: DEFAULT_MESSAGE_ID,
+            guild_id: DEFAULT_GUILD_ID,
+            channel_id: DEFAULT_CHANNEL_ID,
+            author_id: DEFAULT_USER_ID,
+            content: "hello",
+            markdown_tokens: [{ type: "text", text: "hello" }],
+            attachments: [],
+            created_at_unix: 1710000001,
+          },
+        ],
+        next_before: DEFAULT_MESSAGE_ID,
+      },
+      { onHistory },
+    );
+
+    expect(handled).toBe(true);
+    expect(onHistory).toHaveBeenCalledTimes(1);
+    const payload = onHistory.mock.calls[0][0];
+    expect(payload.guildId).toBe(DEFAULT_GUILD_ID);
+    expect(payload.channelId).toBe(DEFAULT_CHANNEL_ID);
+    expect(payload.messages).toHaveLength(1);
+    expect(payload.messages[0].content).toBe("hello");
+    expect(payload.nextBefore).toBe(DEFAULT_MESSAGE_ID);
+  });
+
+  it("fails closed for invalid history payloads", () => {
+    const onHistory = vi.fn();
+
+    const handled = dispatchHistoryGatewayEvent(
+      "history",
+      {
+        guild_id: DEFAULT_GUILD_ID,
+        channel_id: DEFAULT_CHANNEL_ID,
+        messages: [{ message_id: "invalid" }],
+        next_before: null,
+      },
+      { onHistory },
+    );
+
+    expect(handled).toBe(true);
+    expect(onHistory).not.toHaveBeenCalled();
+  });
+
+  it("returns false for non-history event types", () => {
+    const onHistory = vi.fn();
+
+    const handled = dispatchHistoryGatewayEvent(
+      "subscribed",
+      {
+        guild_id: DEFAULT_GUILD_ID,
+        channel_id: DEFAULT_CHANNEL_ID,
+      },
+      { onHistory },
+    );
+
+    expect(handled).toBe(false);
+    expect(onHistory).not.toHaveBeenCalled();
+  });
+});
+
+describe("dispatchMessageCreateAckGatewayEvent", () => {
+  it("dispatches the created message with its nonce", () => {
+    const onMessageCreateAck = vi.fn();
+
+    const handled = dispatchMessageCreateAckGatewayEvent(
+      "message_create_ack",
+      {
+        nonce: "draft-1",
+        message_id: DEFAULT_MESSAGE_ID,
+        guild_id: DEFAULT_GUILD_ID,
+        channel_id: DEFAULT_CHANNEL_ID,
+        author_id: DEFAULT_USER_ID,
+        content: "hello",
+        markdown_tokens: [{ type: "text", text: "hello" }],
+        attachments: [],
+        created_at_unix: 1710000001,
+      },
+      { onMessageCreateAck },
+    );
+
+    expect(handled).toBe(true);
+    expect(onMessageCreateAck).toHaveBeenCalledTimes(1);
+    const payload = onMessageCreateAck.mock.calls[0][0];
+    expect(payload.nonce).toBe("draft-1");
+    expect(payload.message.messageId).toBe(DEFAULT_MESSAGE_ID);
+    expect(payload.message.createdAtUnix).toBe(1710000001);
+  });
+
+  it("fails closed for invalid acks", () => {
+    const onMessageCreateAck = vi.fn();
+
+    const handled = dispatchMessageCreateAckGatewayEvent(
+      "message_create_ack",
+      {
+        nonce: 7,
+        message_id: DEFAULT_MESSAGE_ID,
+      },
+      { onMessageCreateAck },
+    );
+
+    expect(handled).toBe(true);
+    expect(onMessageCreateAck).not.toHaveBeenCalled();
+  });
+});
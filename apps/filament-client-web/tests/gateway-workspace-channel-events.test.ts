@@ -9,6 +9,7 @@ const DEFAULT_CHANNEL_ID = "01ARZ3NDEKTSV4RRFFQ69G5FAX";
 describe("decodeWorkspaceChannelGatewayEvent", () => {
   it("exposes strict workspace channel event type guard", () => {
     expect(isWorkspaceChannelGatewayEventType("channel_create")).toBe(true);
+    expect(isWorkspaceChannelGatewayEventType("channel_update")).toBe(true);
     expect(isWorkspaceChannelGatewayEventType("workspace_update")).toBe(false);
   });
 
@@ -48,6 +49,37 @@ describe("decodeWorkspaceChannelGatewayEvent", () => {
     expect(result).toBeNull();
   });
 
+  it("decodes valid channel_update payload", () => {
+    const result = decodeWorkspaceChannelGatewayEvent("channel_update", {
+      guild_id: DEFAULT_GUILD_ID,
+      channel_id: DEFAULT_CHANNEL_ID,
+      updated_fields: { locked: true },
+      updated_at_unix: 1710000000,
+      actor_user_id: "01ARZ3NDEKTSV4RRFFQ69G5FAY",
+    });
+
+    expect(result).toEqual({
+      type: "channel_update",
+      payload: {
+        guildId: DEFAULT_GUILD_ID,
+        channelId: DEFAULT_CHANNEL_ID,
+        updatedFields: { locked: true },
+        updatedAtUnix: 1710000000,
+      },
+    });
+  });
+
+  it("fails closed for invalid channel_update payload", () => {
+    const result = decodeWorkspaceChannelGatewayEvent("channel_update", {
+      guild_id: DEFAULT_GUILD_ID,
+      channel_id: DEFAULT_CHANNEL_ID,
+      updated_fields: { locked: "yes" },
+      updated_at_unix: 1710000000,
+    });
+
+    expect(result).toBeNull();
+  });
+
   it("returns null for unknown event type", () => {
     const result = decodeWorkspaceChannelGatewayEvent("workspace_unknown", {
       guild_id: DEFAULT_GUILD_ID,
@@ -1,6 +1,6 @@
 import { accessTokenFromInput } from "../src/domain/auth";
-import { channelIdFromInput, guildIdFromInput } from "../src/domain/chat";
-import { connectGateway, resolveGatewayUrl } from "../src/lib/gateway";
+import { channelIdFromInput, guildIdFromInput, messageIdFromInput } from "../src/domain/chat";
+import { connectGateway, gatewayProtocols, resolveGatewayUrl } from "../src/lib/gateway";
 
 describe("gateway URL resolution", () => {
   const token = accessTokenFromInput("A".repeat(64));
@@ -12,17 +12,24 @@ describe("gateway URL resolution", () => {
   it("uses explicit gateway env URL", () => {
     vi.stubEnv("VITE_FILAMENT_GATEWAY_WS_URL", "wss://chat.example.com");
     vi.stubEnv("VITE_FILAMENT_API_BASE_URL", "https://api.example.com");
-    expect(resolveGatewayUrl(token)).toBe(`wss://chat.example.com/gateway/ws?access_token=${encodeURIComponent(token)}`);
+    expect(resolveGatewayUrl()).toBe("wss://chat.example.com/gateway/ws");
   });
 
   it("derives ws URL from API base URL", () => {
     vi.stubEnv("VITE_FILAMENT_API_BASE_URL", "https://api.filament.example/api");
-    expect(resolveGatewayUrl(token)).toBe(`wss://api.filament.example/gateway/ws?access_token=${encodeURIComponent(token)}`);
+    expect(resolveGatewayUrl()).toBe("wss://api.filament.example/gateway/ws");
   });
 
   it("falls back to relative gateway path", () => {
     vi.stubEnv("VITE_FILAMENT_API_BASE_URL", "/api");
-    expect(resolveGatewayUrl(token)).toBe(`/gateway/ws?access_token=${encodeURIComponent(token)}`);
+    expect(resolveGatewayUrl()).toBe("/gateway/ws");
+  });
+
+  it("carries the access token in websocket subprotocols", () => {
+    expect(gatewayProtocols(token)).toEqual([
+      "filament.gateway.v1",
+      `filament.access_token.${token}`,
+    ]);
   });
 });
 
@@ -34,14 +41,16 @@ class MockWebSocket {
   static instances: MockWebSocket[] = [];
 
   readonly url: string;
+  readonly protocols: string[];
   readyState = MockWebSocket.CONNECTING;
   sent: string[] = [];
   onopen: ((event: Event) => void) | null = null;
   onmessage: ((event: MessageEvent) => void) | null = null;
   onclose: ((event: CloseEvent) => void) | null = null;
 
-  constructor(url: string) {
+  constructor(url: string, protocols: string[] = []) {
     this.url = url;
+    this.protocols = protocols;
     MockWebSocket.instances.push(this);
   }
 
@@ -1515,6 +1524,34 @@ describe("gateway payload parsing", () => {
     expect(socket.readyState).toBe(MockWebSocket.CLOSED);
   });
 
+  it("sends history_request envelopes while open", () => {
+    const { socket, client } = createOpenGateway();
+    const before = messageIdFromInput("01ARZ3NDEKTSV4RRFFQ69G5FAZ");
+
+    const sent = client.requestHistory(DEFAULT_GUILD_ID, DEFAULT_CHANNEL_ID, {
+      limit: 50,
+      before,
+    });
+
+    expect(sent).toBe(true);
+
+    expect(socket.sent[socket.sent.length - 1]).toBe(
+      JSON.stringify({
+        v: 1,
+        t: "history_request",
+        d: {
+          guild_id: DEFAULT_GUILD_ID,
+          channel_id: DEFAULT_CHANNEL_ID,
+          limit: 50,
+          before,
+        },
+      }),
+    );
+
+    client.close();
+    expect(client.requestHistory(DEFAULT_GUILD_ID, DEFAULT_CHANNEL_ID)).toBe(false);
+  });
+
   it("sends subscribe events for all configured channel subscriptions", () => {
     const { socket, client } = createOpenGateway();
     const voiceChannelId = channelIdFromInput("01ARZ3NDEKTSV4RRFFQ69G5FAZ");
@@ -4,6 +4,6 @@ mod server;
 
 pub use server::directory_contract;
 pub use server::{
-    build_router, build_router_with_db_bootstrap, init_tracing, AppConfig,
+    build_router, build_router_with_db_bootstrap, init_tracing, AppConfig, MessageContentPolicy,
     MAX_LIVEKIT_TOKEN_TTL_SECS,
 };
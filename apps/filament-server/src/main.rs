@@ -1,13 +1,16 @@
 #![forbid(unsafe_code)]
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
+use axum_server::tls_rustls::RustlsConfig;
 use filament_core::UserId;
 use filament_server::{
     build_router_with_db_bootstrap, directory_contract::IpNetwork, init_tracing, AppConfig,
+    MessageContentPolicy,
 };
 use tokio::net::TcpListener;
 
@@ -44,6 +47,17 @@ fn parse_u64_env_or_default(var_name: &str, default: u64) -> anyhow::Result<u64>
     )
 }
 
+fn parse_bool_env_or_default(var_name: &str, default: bool) -> anyhow::Result<bool> {
+    std::env::var(var_name).map_or_else(
+        |_| Ok(default),
+        |value| match value.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(true),
+            "0" | "false" | "no" | "off" => Ok(false),
+            _ => Err(anyhow::anyhow!("invalid {var_name} value {value:?}")),
+        },
+    )
+}
+
 fn parse_rate_limit_requests_per_minute_from_env(defaults: &AppConfig) -> anyhow::Result<u32> {
     parse_u32_env_or_default(
         "FILAMENT_RATE_LIMIT_REQUESTS_PER_MINUTE",
@@ -110,6 +124,110 @@ fn parse_directory_runtime_limits_from_env(
     ))
 }
 
+fn parse_audit_log_limits_from_env(defaults: &AppConfig) -> anyhow::Result<(Duration, usize)> {
+    let retention = Duration::from_secs(parse_u64_env_or_default(
+        "FILAMENT_AUDIT_LOG_RETENTION_SECS",
+        defaults.audit_log_retention.as_secs(),
+    )?);
+    let memory_max_entries = parse_usize_env_or_default(
+        "FILAMENT_AUDIT_LOG_MEMORY_MAX_ENTRIES",
+        defaults.audit_log_memory_max_entries,
+    )?;
+    Ok((retention, memory_max_entries))
+}
+
+fn parse_search_runtime_limits_from_env(
+    defaults: &AppConfig,
+) -> anyhow::Result<(u32, usize, usize, usize, Duration)> {
+    let requests_per_minute = parse_u32_env_or_default(
+        "FILAMENT_SEARCH_REQUESTS_PER_MINUTE",
+        defaults.search_requests_per_minute,
+    )?;
+    let max_concurrent_queries = parse_usize_env_or_default(
+        "FILAMENT_SEARCH_MAX_CONCURRENT_QUERIES",
+        defaults.search_max_concurrent_queries,
+    )?;
+    let reconcile_batch_docs = parse_usize_env_or_default(
+        "FILAMENT_SEARCH_RECONCILE_BATCH_DOCS",
+        defaults.search_reconcile_batch_docs,
+    )?;
+    let writer_heap_bytes = parse_usize_env_or_default(
+        "FILAMENT_SEARCH_WRITER_HEAP_BYTES",
+        defaults.search_writer_heap_bytes,
+    )?;
+    let query_timeout_max = Duration::from_millis(parse_u64_env_or_default(
+        "FILAMENT_SEARCH_QUERY_TIMEOUT_MAX_MILLIS",
+        u64::try_from(defaults.search_query_timeout_max.as_millis()).unwrap_or(u64::MAX),
+    )?);
+    Ok((
+        requests_per_minute,
+        max_concurrent_queries,
+        reconcile_batch_docs,
+        writer_heap_bytes,
+        query_timeout_max,
+    ))
+}
+
+fn parse_content_runtime_limits_from_env(
+    defaults: &AppConfig,
+) -> anyhow::Result<(bool, Duration, usize)> {
+    let link_previews_enabled = parse_bool_env_or_default(
+        "FILAMENT_LINK_PREVIEWS_ENABLED",
+        defaults.link_previews_enabled,
+    )?;
+    let deleted_message_retention = Duration::from_secs(parse_u64_env_or_default(
+        "FILAMENT_DELETED_MESSAGE_RETENTION_SECS",
+        defaults.deleted_message_retention.as_secs(),
+    )?);
+    let mime_sniff_bytes =
+        parse_usize_env_or_default("FILAMENT_MIME_SNIFF_BYTES", defaults.mime_sniff_bytes)?;
+    Ok((
+        link_previews_enabled,
+        deleted_message_retention,
+        mime_sniff_bytes,
+    ))
+}
+
+fn parse_attachment_key_layout_from_env(defaults: &AppConfig) -> anyhow::Result<(String, usize)> {
+    let attachment_key_prefix = std::env::var("FILAMENT_ATTACHMENT_KEY_PREFIX")
+        .unwrap_or_else(|_| defaults.attachment_key_prefix.clone());
+    let attachment_key_shard_chars = parse_usize_env_or_default(
+        "FILAMENT_ATTACHMENT_KEY_SHARD_CHARS",
+        defaults.attachment_key_shard_chars,
+    )?;
+    Ok((attachment_key_prefix, attachment_key_shard_chars))
+}
+
+fn parse_message_content_policy_from_env(
+    defaults: &AppConfig,
+) -> anyhow::Result<MessageContentPolicy> {
+    let defaults = defaults.message_content_policy;
+    Ok(MessageContentPolicy {
+        normalize_nfc: parse_bool_env_or_default(
+            "FILAMENT_MESSAGE_CONTENT_NORMALIZE_NFC",
+            defaults.normalize_nfc,
+        )?,
+        reject_invisible: parse_bool_env_or_default(
+            "FILAMENT_MESSAGE_CONTENT_REJECT_INVISIBLE",
+            defaults.reject_invisible,
+        )?,
+        max_combining_mark_percent: parse_u32_env_or_default(
+            "FILAMENT_MESSAGE_CONTENT_MAX_COMBINING_MARK_PERCENT",
+            defaults.max_combining_mark_percent,
+        )?,
+    })
+}
+
+fn parse_history_limits_from_env(defaults: &AppConfig) -> anyhow::Result<(usize, usize)> {
+    let history_default_limit = parse_usize_env_or_default(
+        "FILAMENT_HISTORY_DEFAULT_LIMIT",
+        defaults.history_default_limit,
+    )?;
+    let history_max_limit =
+        parse_usize_env_or_default("FILAMENT_HISTORY_MAX_LIMIT", defaults.history_max_limit)?;
+    Ok((history_default_limit, history_max_limit))
+}
+
 fn parse_trusted_proxy_cidrs_from_env(defaults: &AppConfig) -> anyhow::Result<Vec<IpNetwork>> {
     std::env::var("FILAMENT_TRUSTED_PROXY_CIDRS").map_or_else(
         |_| Ok(defaults.trusted_proxy_cidrs.clone()),
@@ -139,6 +257,66 @@ fn parse_trusted_proxy_cidrs_from_env(defaults: &AppConfig) -> anyhow::Result<Ve
     )
 }
 
+fn parse_route_rate_limits_from_env(defaults: &AppConfig) -> anyhow::Result<HashMap<String, u32>> {
+    std::env::var("FILAMENT_ROUTE_RATE_LIMITS").map_or_else(
+        |_| Ok(defaults.route_rate_limits.clone()),
+        |raw| {
+            let mut overrides = HashMap::new();
+            for (index, entry) in raw.split(',').enumerate() {
+                let entry = entry.trim();
+                if entry.is_empty() {
+                    continue;
+                }
+                let (route, limit) = entry.rsplit_once('=').ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "invalid FILAMENT_ROUTE_RATE_LIMITS entry at position {}: expected <route>=<requests_per_minute>",
+                        index + 1
+                    )
+                })?;
+                let limit = limit.trim().parse::<u32>().map_err(|e| {
+                    anyhow::anyhow!(
+                        "invalid FILAMENT_ROUTE_RATE_LIMITS limit at position {}: {e}",
+                        index + 1
+                    )
+                })?;
+                overrides.insert(route.trim().to_owned(), limit);
+            }
+            Ok(overrides)
+        },
+    )
+}
+
+fn parse_route_body_limits_from_env(
+    defaults: &AppConfig,
+) -> anyhow::Result<HashMap<String, usize>> {
+    std::env::var("FILAMENT_ROUTE_BODY_LIMITS").map_or_else(
+        |_| Ok(defaults.route_body_limits.clone()),
+        |raw| {
+            let mut overrides = HashMap::new();
+            for (index, entry) in raw.split(',').enumerate() {
+                let entry = entry.trim();
+                if entry.is_empty() {
+                    continue;
+                }
+                let (route, limit) = entry.rsplit_once('=').ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "invalid FILAMENT_ROUTE_BODY_LIMITS entry at position {}: expected <route>=<bytes>",
+                        index + 1
+                    )
+                })?;
+                let limit = limit.trim().parse::<usize>().map_err(|e| {
+                    anyhow::anyhow!(
+                        "invalid FILAMENT_ROUTE_BODY_LIMITS limit at position {}: {e}",
+                        index + 1
+                    )
+                })?;
+                overrides.insert(route.trim().to_owned(), limit);
+            }
+            Ok(overrides)
+        },
+    )
+}
+
 fn parse_server_owner_user_id_from_env(defaults: &AppConfig) -> anyhow::Result<Option<UserId>> {
     std::env::var("FILAMENT_SERVER_OWNER_USER_ID").map_or_else(
         |_| Ok(defaults.server_owner_user_id),
@@ -154,6 +332,28 @@ fn parse_server_owner_user_id_from_env(defaults: &AppConfig) -> anyhow::Result<O
     )
 }
 
+fn parse_tls_paths_from_env(defaults: &AppConfig) -> (Option<PathBuf>, Option<PathBuf>) {
+    let tls_cert_path = parse_optional_nonempty_env("FILAMENT_TLS_CERT_PATH")
+        .map(PathBuf::from)
+        .or_else(|| defaults.tls_cert_path.clone());
+    let tls_key_path = parse_optional_nonempty_env("FILAMENT_TLS_KEY_PATH")
+        .map(PathBuf::from)
+        .or_else(|| defaults.tls_key_path.clone());
+    (tls_cert_path, tls_key_path)
+}
+
+fn parse_host_list_env(var_name: &str) -> Vec<String> {
+    std::env::var(var_name)
+        .map(|raw| {
+            raw.split(',')
+                .map(str::trim)
+                .filter(|host| !host.is_empty())
+                .map(str::to_ascii_lowercase)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn parse_optional_nonempty_env(var_name: &str) -> Option<String> {
     std::env::var(var_name).ok().and_then(|value| {
         let trimmed = value.trim();
@@ -166,6 +366,7 @@ fn parse_optional_nonempty_env(var_name: &str) -> Option<String> {
 }
 
 #[tokio::main]
+#[allow(clippy::too_many_lines)]
 async fn main() -> anyhow::Result<()> {
     init_tracing();
 
@@ -177,6 +378,8 @@ async fn main() -> anyhow::Result<()> {
         .map_err(|_| anyhow::anyhow!("FILAMENT_LIVEKIT_API_SECRET is required for runtime"))?;
     let defaults = AppConfig::default();
     let rate_limit_requests_per_minute = parse_rate_limit_requests_per_minute_from_env(&defaults)?;
+    let route_rate_limits = parse_route_rate_limits_from_env(&defaults)?;
+    let route_body_limits = parse_route_body_limits_from_env(&defaults)?;
     let (
         auth_route_requests_per_minute,
         gateway_ingress_events_per_window,
@@ -188,40 +391,167 @@ async fn main() -> anyhow::Result<()> {
         "FILAMENT_MAX_CREATED_GUILDS_PER_USER",
         defaults.max_created_guilds_per_user,
     )?;
+    let max_friends_per_user = parse_usize_env_or_default(
+        "FILAMENT_MAX_FRIENDS_PER_USER",
+        defaults.max_friends_per_user,
+    )?;
+    let max_incoming_friend_requests_per_user = parse_usize_env_or_default(
+        "FILAMENT_MAX_INCOMING_FRIEND_REQUESTS_PER_USER",
+        defaults.max_incoming_friend_requests_per_user,
+    )?;
+    let min_account_age_for_guild_create = Duration::from_secs(parse_u64_env_or_default(
+        "FILAMENT_MIN_ACCOUNT_AGE_FOR_GUILD_CREATE_SECS",
+        defaults.min_account_age_for_guild_create.as_secs(),
+    )?);
+    let guild_create_cooldown = Duration::from_secs(parse_u64_env_or_default(
+        "FILAMENT_GUILD_CREATE_COOLDOWN_SECS",
+        defaults.guild_create_cooldown.as_secs(),
+    )?);
+    let refresh_token_reuse_grace = Duration::from_secs(parse_u64_env_or_default(
+        "FILAMENT_REFRESH_TOKEN_REUSE_GRACE_SECS",
+        defaults.refresh_token_reuse_grace.as_secs(),
+    )?);
+    let gateway_outbound_buffer_max_bytes = parse_usize_env_or_default(
+        "FILAMENT_GATEWAY_OUTBOUND_BUFFER_MAX_BYTES",
+        defaults.gateway_outbound_buffer_max_bytes,
+    )?;
+    let gateway_slow_consumer_max_strikes = parse_u32_env_or_default(
+        "FILAMENT_GATEWAY_SLOW_CONSUMER_MAX_STRIKES",
+        defaults.gateway_slow_consumer_max_strikes,
+    )?;
+    let gateway_max_connection_lifetime = Duration::from_secs(parse_u64_env_or_default(
+        "FILAMENT_GATEWAY_MAX_CONNECTION_LIFETIME_SECS",
+        defaults.gateway_max_connection_lifetime.as_secs(),
+    )?);
+    let gateway_max_connections_per_user = parse_usize_env_or_default(
+        "FILAMENT_GATEWAY_MAX_CONNECTIONS_PER_USER",
+        defaults.gateway_max_connections_per_user,
+    )?;
+    let gateway_subscription_revalidate_interval = Duration::from_secs(parse_u64_env_or_default(
+        "FILAMENT_GATEWAY_SUBSCRIPTION_REVALIDATE_SECS",
+        defaults.gateway_subscription_revalidate_interval.as_secs(),
+    )?);
+    let (
+        search_requests_per_minute,
+        search_max_concurrent_queries,
+        search_reconcile_batch_docs,
+        search_writer_heap_bytes,
+        search_query_timeout_max,
+    ) = parse_search_runtime_limits_from_env(&defaults)?;
     let (
         directory_join_requests_per_minute_per_ip,
         directory_join_requests_per_minute_per_user,
         audit_list_limit_max,
         guild_ip_ban_max_entries,
     ) = parse_directory_runtime_limits_from_env(&defaults)?;
+    let (audit_log_retention, audit_log_memory_max_entries) =
+        parse_audit_log_limits_from_env(&defaults)?;
     let trusted_proxy_cidrs = parse_trusted_proxy_cidrs_from_env(&defaults)?;
     let server_owner_user_id = parse_server_owner_user_id_from_env(&defaults)?;
+    let log_redact_pii =
+        parse_bool_env_or_default("FILAMENT_LOG_REDACT_PII", defaults.log_redact_pii)?;
+    let message_content_compression = parse_bool_env_or_default(
+        "FILAMENT_MESSAGE_CONTENT_COMPRESSION",
+        defaults.message_content_compression,
+    )?;
+    let (link_previews_enabled, deleted_message_retention, mime_sniff_bytes) =
+        parse_content_runtime_limits_from_env(&defaults)?;
+    let (history_default_limit, history_max_limit) = parse_history_limits_from_env(&defaults)?;
+    let (attachment_key_prefix, attachment_key_shard_chars) =
+        parse_attachment_key_layout_from_env(&defaults)?;
+    let max_message_attachment_total_bytes = parse_u64_env_or_default(
+        "FILAMENT_MAX_MESSAGE_ATTACHMENT_TOTAL_BYTES",
+        defaults.max_message_attachment_total_bytes,
+    )?;
+    let search_enabled =
+        parse_bool_env_or_default("FILAMENT_SEARCH_ENABLED", defaults.search_enabled)?;
+    let search_sync_index_writes = parse_bool_env_or_default(
+        "FILAMENT_SEARCH_SYNC_INDEX_WRITES",
+        defaults.search_sync_index_writes,
+    )?;
+    let attachment_verify_integrity = parse_bool_env_or_default(
+        "FILAMENT_ATTACHMENT_VERIFY_INTEGRITY",
+        defaults.attachment_verify_integrity,
+    )?;
+    let (tls_cert_path, tls_key_path) = parse_tls_paths_from_env(&defaults);
     let captcha_hcaptcha_site_key = parse_optional_nonempty_env("FILAMENT_HCAPTCHA_SITE_KEY");
     let captcha_hcaptcha_secret = parse_optional_nonempty_env("FILAMENT_HCAPTCHA_SECRET");
     let app_config = AppConfig {
         attachment_root: std::env::var("FILAMENT_ATTACHMENT_ROOT")
             .map_or_else(|_| PathBuf::from("./data/attachments"), PathBuf::from),
+        attachment_key_prefix,
+        attachment_key_shard_chars,
+        attachment_verify_integrity,
+        max_message_attachment_total_bytes,
+        tls_cert_path,
+        tls_key_path,
+        admin_api_secret: parse_optional_nonempty_env("FILAMENT_ADMIN_API_SECRET"),
+        enable_debug_routes: parse_bool_env_or_default(
+            "FILAMENT_ENABLE_DEBUG_ROUTES",
+            defaults.enable_debug_routes,
+        )?,
         livekit_url: std::env::var("FILAMENT_LIVEKIT_URL")
             .unwrap_or_else(|_| String::from("ws://127.0.0.1:7880")),
         livekit_api_key: Some(livekit_api_key),
         livekit_api_secret: Some(livekit_api_secret),
         rate_limit_requests_per_minute,
+        route_rate_limits,
+        route_body_limits,
         auth_route_requests_per_minute,
         gateway_ingress_events_per_window,
         gateway_ingress_window,
+        gateway_outbound_buffer_max_bytes,
+        gateway_slow_consumer_max_strikes,
+        gateway_max_connection_lifetime,
+        gateway_max_connections_per_user,
+        gateway_subscription_revalidate_interval,
         media_token_requests_per_minute,
         media_publish_requests_per_minute,
         max_created_guilds_per_user,
+        create_default_guild_channels: parse_bool_env_or_default(
+            "FILAMENT_CREATE_DEFAULT_GUILD_CHANNELS",
+            defaults.create_default_guild_channels,
+        )?,
+        create_default_guild_voice_channel: parse_bool_env_or_default(
+            "FILAMENT_CREATE_DEFAULT_GUILD_VOICE_CHANNEL",
+            defaults.create_default_guild_voice_channel,
+        )?,
+        max_friends_per_user,
+        max_incoming_friend_requests_per_user,
+        min_account_age_for_guild_create,
+        guild_create_cooldown,
+        refresh_token_reuse_grace,
+        search_requests_per_minute,
+        search_max_concurrent_queries,
+        search_reconcile_batch_docs,
+        search_writer_heap_bytes,
+        search_enabled,
+        search_sync_index_writes,
+        search_query_timeout_max,
         directory_join_requests_per_minute_per_ip,
         directory_join_requests_per_minute_per_user,
         audit_list_limit_max,
         guild_ip_ban_max_entries,
+        audit_log_retention,
+        audit_log_memory_max_entries,
         trusted_proxy_cidrs,
         server_owner_user_id,
+        log_redact_pii,
+        link_previews_enabled,
+        message_content_compression,
+        message_content_encryption_key: parse_optional_nonempty_env(
+            "FILAMENT_MESSAGE_CONTENT_ENCRYPTION_KEY",
+        ),
+        message_content_policy: parse_message_content_policy_from_env(&defaults)?,
+        deleted_message_retention,
+        mime_sniff_bytes,
+        history_default_limit,
+        history_max_limit,
         captcha_hcaptcha_site_key,
         captcha_hcaptcha_secret,
         captcha_verify_url: std::env::var("FILAMENT_HCAPTCHA_VERIFY_URL")
             .unwrap_or_else(|_| String::from("https://api.hcaptcha.com/siteverify")),
+        captcha_verify_allowed_hosts: parse_host_list_env("FILAMENT_HCAPTCHA_VERIFY_ALLOWED_HOSTS"),
         database_url: Some(database_url),
         ..AppConfig::default()
     };
@@ -230,24 +560,38 @@ async fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|_| String::from("0.0.0.0:3000"))
         .parse::<SocketAddr>()
         .map_err(|e| anyhow::anyhow!("invalid FILAMENT_BIND_ADDR: {e}"))?;
+    let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    if let (Some(cert_path), Some(key_path)) = (&app_config.tls_cert_path, &app_config.tls_key_path)
+    {
+        // Both ring and aws-lc-rs are linked through other dependencies, so
+        // rustls cannot pick a provider on its own.
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+        let tls_config = RustlsConfig::from_pem_file(cert_path, key_path)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to load TLS certificate or key: {e}"))?;
+        tracing::info!(%addr, "filament-server listening with TLS");
+        axum_server::bind_rustls(addr, tls_config)
+            .serve(make_service)
+            .await?;
+        return Ok(());
+    }
+
     let listener = TcpListener::bind(addr).await?;
     tracing::info!(%addr, "filament-server listening");
-
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .await?;
+    axum::serve(listener, make_service).await?;
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        parse_directory_runtime_limits_from_env, parse_optional_nonempty_env,
-        parse_rate_limit_requests_per_minute_from_env, parse_rate_runtime_limits_from_env,
-        parse_server_owner_user_id_from_env, parse_trusted_proxy_cidrs_from_env,
-        parse_u32_env_or_default, parse_u64_env_or_default, parse_usize_env_or_default,
+        parse_bool_env_or_default, parse_directory_runtime_limits_from_env,
+        parse_optional_nonempty_env, parse_rate_limit_requests_per_minute_from_env,
+        parse_rate_runtime_limits_from_env, parse_route_body_limits_from_env,
+        parse_route_rate_limits_from_env, parse_server_owner_user_id_from_env,
+        parse_tls_paths_from_env, parse_trusted_proxy_cidrs_from_env, parse_u32_env_or_default,
+        parse_u64_env_or_default, parse_usize_env_or_default,
     };
     use filament_core::UserId;
     use filament_server::{directory_contract::IpNetwork, AppConfig};
@@ -295,6 +639,93 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn parse_bool_env_or_default_accepts_flags_and_rejects_garbage() {
+        let _guard = lock_env();
+        let key = "FILAMENT_TEST_PARSE_BOOL";
+        std::env::set_var(key, "TRUE");
+        let enabled = parse_bool_env_or_default(key, false);
+        std::env::set_var(key, "0");
+        let disabled = parse_bool_env_or_default(key, true);
+        std::env::set_var(key, "maybe");
+        let invalid = parse_bool_env_or_default(key, false);
+        std::env::remove_var(key);
+        let fallback = parse_bool_env_or_default(key, true);
+
+        assert!(enabled.expect("true should parse"));
+        assert!(!disabled.expect("0 should parse"));
+        assert!(invalid.is_err());
+        assert!(fallback.expect("missing var should use default"));
+    }
+
+    #[test]
+    fn route_rate_limits_env_is_parsed() {
+        let _guard = lock_env();
+        std::env::set_var(
+            "FILAMENT_ROUTE_RATE_LIMITS",
+            "/guilds/{guild_id}/search=20, /users/me/profile/avatar=5,",
+        );
+
+        let parsed = parse_route_rate_limits_from_env(&AppConfig::default())
+            .expect("route rate limits should parse");
+
+        std::env::remove_var("FILAMENT_ROUTE_RATE_LIMITS");
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed.get("/guilds/{guild_id}/search"), Some(&20));
+        assert_eq!(parsed.get("/users/me/profile/avatar"), Some(&5));
+    }
+
+    #[test]
+    fn route_rate_limits_env_rejects_malformed_entries() {
+        let _guard = lock_env();
+        std::env::set_var("FILAMENT_ROUTE_RATE_LIMITS", "/health");
+        let missing_limit = parse_route_rate_limits_from_env(&AppConfig::default());
+        std::env::set_var("FILAMENT_ROUTE_RATE_LIMITS", "/health=lots");
+        let invalid_limit = parse_route_rate_limits_from_env(&AppConfig::default());
+        std::env::remove_var("FILAMENT_ROUTE_RATE_LIMITS");
+
+        assert!(missing_limit.is_err());
+        assert!(invalid_limit.is_err());
+    }
+
+    #[test]
+    fn route_body_limits_env_is_parsed() {
+        let _guard = lock_env();
+        std::env::set_var(
+            "FILAMENT_ROUTE_BODY_LIMITS",
+            "/guilds/{guild_id}/search/reconcile=4194304, /auth/login=4096,",
+        );
+        let parsed = parse_route_body_limits_from_env(&AppConfig::default())
+            .expect("route body limits should parse");
+        std::env::set_var("FILAMENT_ROUTE_BODY_LIMITS", "/auth/login=small");
+        let invalid_limit = parse_route_body_limits_from_env(&AppConfig::default());
+        std::env::remove_var("FILAMENT_ROUTE_BODY_LIMITS");
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(
+            parsed.get("/guilds/{guild_id}/search/reconcile"),
+            Some(&4_194_304)
+        );
+        assert_eq!(parsed.get("/auth/login"), Some(&4096));
+        assert!(invalid_limit.is_err());
+    }
+
+    #[test]
+    fn tls_paths_env_is_parsed_and_blank_values_are_ignored() {
+        let _guard = lock_env();
+        std::env::set_var("FILAMENT_TLS_CERT_PATH", "/etc/filament/tls/cert.pem");
+        std::env::set_var("FILAMENT_TLS_KEY_PATH", "  ");
+        let (cert_path, key_path) = parse_tls_paths_from_env(&AppConfig::default());
+        std::env::remove_var("FILAMENT_TLS_CERT_PATH");
+        std::env::remove_var("FILAMENT_TLS_KEY_PATH");
+
+        assert_eq!(
+            cert_path.as_deref(),
+            Some(std::path::Path::new("/etc/filament/tls/cert.pem"))
+        );
+        assert!(key_path.is_none());
+    }
+
     #[test]
     fn rate_limit_env_override_is_parsed() {
         let _guard = lock_env();
@@ -24,20 +24,26 @@ use pasetors::{
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use sqlx::Row;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
 
 use super::{
     core::{
-        AppConfig, AppState, AuthContext, CaptchaConfig, LiveKitConfig, ACCESS_TOKEN_TTL_SECS,
-        RATE_LIMIT_SWEEP_INTERVAL_SECS,
+        AppConfig, AppState, AuthContext, CaptchaConfig, ChannelKey, LiveKitConfig,
+        RuntimeSecurityConfig, ACCESS_TOKEN_TTL_SECS, CHANNEL_EXPORT_REQUESTS_PER_MINUTE,
+        MESSAGE_RATE_WINDOW_SECS, RATE_LIMIT_SWEEP_INTERVAL_SECS, REPORT_REQUESTS_PER_WINDOW,
+        REPORT_WINDOW_SECS, USER_EXPORT_REQUESTS_PER_WINDOW, USER_EXPORT_WINDOW_SECS,
     },
     directory_contract::IpNetwork,
     errors::AuthFailure,
+    metrics::record_message_created,
     types::{ChannelPath, MediaPublishSource},
 };
 
 const MAX_X_FORWARDED_FOR_HEADER_CHARS: usize = 512;
 const MAX_X_FORWARDED_FOR_ENTRY_CHARS: usize = 64;
 const UNKNOWN_CLIENT_IP: &str = "unknown";
+const REDACTED_LOG_VALUE: &str = "redacted";
 const RATE_LIMIT_WINDOW_SECS: i64 = 60;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -94,22 +100,95 @@ impl ClientIp {
     }
 }
 
+pub(crate) const MIN_PASSWORD_BYTES: usize = 12;
+pub(crate) const MAX_PASSWORD_BYTES: usize = 128;
+
 pub(crate) fn validate_password(value: &str) -> Result<(), AuthFailure> {
     let len = value.len();
-    if (12..=128).contains(&len) {
-        Ok(())
+    if len < MIN_PASSWORD_BYTES {
+        Err(AuthFailure::PasswordTooShort {
+            limit: MIN_PASSWORD_BYTES,
+        })
+    } else if len > MAX_PASSWORD_BYTES {
+        Err(AuthFailure::PasswordTooLong {
+            limit: MAX_PASSWORD_BYTES,
+        })
     } else {
-        Err(AuthFailure::InvalidRequest)
+        Ok(())
     }
 }
 
+pub(crate) const MAX_MESSAGE_CONTENT_BYTES: usize = 2000;
+
 pub(crate) fn validate_message_content(content: &str) -> Result<(), AuthFailure> {
-    let len = content.len();
-    if (1..=2000).contains(&len) {
+    if content.is_empty() {
+        Err(AuthFailure::ContentEmpty)
+    } else if content.len() > MAX_MESSAGE_CONTENT_BYTES {
+        Err(AuthFailure::ContentTooLong {
+            limit: MAX_MESSAGE_CONTENT_BYTES,
+        })
+    } else {
         Ok(())
+    }
+}
+
+/// Characters that carry no visible glyph, so content made only of them
+/// renders as a blank message.
+fn is_invisible_message_char(ch: char) -> bool {
+    ch.is_whitespace()
+        || ch.is_control()
+        || matches!(
+            ch,
+            '\u{00ad}'
+                | '\u{034f}'
+                | '\u{061c}'
+                | '\u{115f}'
+                | '\u{1160}'
+                | '\u{17b4}'
+                | '\u{17b5}'
+                | '\u{180e}'
+                | '\u{200b}'..='\u{200f}'
+                | '\u{202a}'..='\u{202e}'
+                | '\u{2060}'..='\u{206f}'
+                | '\u{2800}'
+                | '\u{3164}'
+                | '\u{fe00}'..='\u{fe0f}'
+                | '\u{feff}'
+                | '\u{ffa0}'
+        )
+}
+
+/// Applies the opt-in message content policy: NFC normalization, rejection
+/// of content with no visible characters, and a cap on the share of
+/// combining marks. Empty content passes through untouched.
+pub(crate) fn apply_message_content_policy(
+    runtime: &RuntimeSecurityConfig,
+    content: String,
+) -> Result<String, AuthFailure> {
+    let policy = runtime.message_content_policy;
+    if content.is_empty() {
+        return Ok(content);
+    }
+    let content = if policy.normalize_nfc {
+        let normalized: String = content.nfc().collect();
+        validate_message_content(&normalized)?;
+        normalized
     } else {
-        Err(AuthFailure::InvalidRequest)
+        content
+    };
+    if policy.reject_invisible && content.chars().all(is_invisible_message_char) {
+        return Err(AuthFailure::ContentEmpty);
     }
+    let max_percent = u64::from(policy.max_combining_mark_percent);
+    if max_percent > 0 {
+        let (chars, marks) = content.chars().fold((0_u64, 0_u64), |(chars, marks), ch| {
+            (chars + 1, marks + u64::from(is_combining_mark(ch)))
+        });
+        if marks * 100 > chars * max_percent {
+            return Err(AuthFailure::InvalidRequest);
+        }
+    }
+    Ok(content)
 }
 
 pub(crate) fn hash_password(password: &str) -> anyhow::Result<String> {
@@ -169,6 +248,14 @@ pub(crate) fn verify_access_token(state: &AppState, token: &str) -> anyhow::Resu
         .ok_or_else(|| anyhow!("token claims missing"))
 }
 
+/// Reads the `exp` claim of verified access token claims as unix seconds.
+pub(crate) fn access_token_expires_at_unix(claims: &Claims) -> Option<i64> {
+    let expiration = claims.get_claim("exp")?.as_str()?;
+    OffsetDateTime::parse(expiration, &Rfc3339)
+        .ok()
+        .map(OffsetDateTime::unix_timestamp)
+}
+
 pub(crate) async fn authenticate(
     state: &AppState,
     headers: &HeaderMap,
@@ -254,6 +341,18 @@ pub(crate) fn now_unix() -> i64 {
     i64::try_from(seconds).unwrap_or(i64::MAX)
 }
 
+/// Seconds until the oldest hit in a full sliding window expires and frees a
+/// slot, clamped to at least one second.
+fn rate_limit_retry_after_secs(route_hits: &[i64], now: i64) -> u64 {
+    rate_limit_retry_after_secs_for_window(route_hits, now, RATE_LIMIT_WINDOW_SECS)
+}
+
+fn rate_limit_retry_after_secs_for_window(route_hits: &[i64], now: i64, window_secs: i64) -> u64 {
+    let oldest = route_hits.iter().min().copied().unwrap_or(now);
+    let remaining = window_secs.saturating_sub(now.saturating_sub(oldest));
+    u64::try_from(remaining.max(1)).unwrap_or(1)
+}
+
 async fn maybe_sweep_rate_limit_state(state: &AppState, now: i64) {
     let last = state.rate_limit_last_sweep_unix.load(Ordering::Relaxed);
     if now.saturating_sub(last) < RATE_LIMIT_SWEEP_INTERVAL_SECS {
@@ -302,6 +401,50 @@ async fn maybe_sweep_rate_limit_state(state: &AppState, now: i64) {
             !route_hits.is_empty()
         });
     }
+    {
+        let mut hits = state.search_query_hits.write().await;
+        hits.retain(|_, route_hits| {
+            route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < RATE_LIMIT_WINDOW_SECS);
+            !route_hits.is_empty()
+        });
+    }
+    {
+        let mut hits = state.channel_export_hits.write().await;
+        hits.retain(|_, route_hits| {
+            route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < RATE_LIMIT_WINDOW_SECS);
+            !route_hits.is_empty()
+        });
+    }
+    {
+        let mut hits = state.user_export_hits.write().await;
+        hits.retain(|_, route_hits| {
+            route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < USER_EXPORT_WINDOW_SECS);
+            !route_hits.is_empty()
+        });
+    }
+    {
+        let mut hits = state.report_hits.write().await;
+        hits.retain(|_, route_hits| {
+            route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < REPORT_WINDOW_SECS);
+            !route_hits.is_empty()
+        });
+    }
+    {
+        let mut hits = state.message_create_hits.write().await;
+        hits.retain(|_, user_hits| {
+            user_hits.retain(|timestamp| now.saturating_sub(*timestamp) < MESSAGE_RATE_WINDOW_SECS);
+            !user_hits.is_empty()
+        });
+    }
+    {
+        let mut hits = state.message_create_guild_hits.write().await;
+        hits.retain(|_, guild_hits| {
+            guild_hits
+                .retain(|timestamp| now.saturating_sub(*timestamp) < MESSAGE_RATE_WINDOW_SECS);
+            !guild_hits.is_empty()
+        });
+    }
+    state.message_idempotency.write().await.prune_expired(now);
     {
         let mut leases = state.media_subscribe_leases.write().await;
         leases.retain(|_, channel_leases| {
@@ -325,8 +468,8 @@ pub(crate) fn outbound_event<T: Serialize>(event_type: &str, data: T) -> anyhow:
         .map_err(|error| anyhow!("failed to encode outbound event envelope {event_type}: {error}"))
 }
 
-pub(crate) fn channel_key(guild_id: &str, channel_id: &str) -> String {
-    format!("{guild_id}:{channel_id}")
+pub(crate) fn channel_key(guild_id: &str, channel_id: &str) -> ChannelKey {
+    ChannelKey::new(guild_id, channel_id)
 }
 
 pub(crate) fn build_livekit_config(config: &AppConfig) -> anyhow::Result<Option<LiveKitConfig>> {
@@ -367,6 +510,7 @@ pub(crate) fn build_captcha_config(config: &AppConfig) -> anyhow::Result<Option<
                 return Err(anyhow!("hcaptcha site key and secret cannot be empty"));
             }
             let verify_url = validate_captcha_verify_url(&config.captcha_verify_url)?;
+            ensure_captcha_verify_host_allowed(&verify_url, &config.captcha_verify_allowed_hosts)?;
             if config.captcha_verify_timeout.is_zero()
                 || config.captcha_verify_timeout > Duration::from_secs(10)
             {
@@ -411,6 +555,45 @@ pub(crate) fn validate_captcha_verify_url(value: &str) -> anyhow::Result<String>
     ))
 }
 
+fn ensure_captcha_verify_host_allowed(
+    verify_url: &str,
+    allowed_hosts: &[String],
+) -> anyhow::Result<()> {
+    if allowed_hosts.is_empty() {
+        return Ok(());
+    }
+    let host = reqwest::Url::parse(verify_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        .ok_or_else(|| anyhow!("captcha verify url is invalid"))?;
+    if allowed_hosts
+        .iter()
+        .any(|allowed| allowed.trim().eq_ignore_ascii_case(&host))
+    {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "captcha verify url host {host:?} is not in the allowed host list"
+    ))
+}
+
+/// Counts a created message in the aggregate metric and in the author and
+/// guild rolling windows behind `GET /admin/message-rates`. Never rejects.
+pub(crate) async fn track_message_created(state: &AppState, guild_id: &str, author_id: &str) {
+    record_message_created();
+    let now = now_unix();
+    maybe_sweep_rate_limit_state(state, now).await;
+    for (windows, key) in [
+        (&state.message_create_hits, author_id),
+        (&state.message_create_guild_hits, guild_id),
+    ] {
+        let mut hits = windows.write().await;
+        let key_hits = hits.entry(key.to_owned()).or_default();
+        key_hits.retain(|timestamp| now.saturating_sub(*timestamp) < MESSAGE_RATE_WINDOW_SECS);
+        key_hits.push(now);
+    }
+}
+
 pub(crate) async fn enforce_auth_route_rate_limit(
     state: &AppState,
     client_ip: ClientIp,
@@ -430,10 +613,12 @@ pub(crate) async fn enforce_auth_route_rate_limit(
         tracing::warn!(
             event = "auth.rate_limit",
             route = %route,
-            client_ip = %ip,
+            client_ip = %loggable_client_ip(state, &ip),
             client_ip_source = client_ip.source().as_str()
         );
-        return Err(AuthFailure::RateLimited);
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs(route_hits, now),
+        ));
     }
     route_hits.push(now);
     Ok(())
@@ -457,10 +642,12 @@ pub(crate) async fn enforce_directory_join_rate_limit(
             tracing::warn!(
                 event = "directory.join.rate_limit",
                 limiter = "ip",
-                client_ip = %ip,
+                client_ip = %loggable_client_ip(state, &ip),
                 client_ip_source = client_ip.source().as_str()
             );
-            return Err(AuthFailure::RateLimited);
+            return Err(AuthFailure::RateLimitedRetryAfter(
+                rate_limit_retry_after_secs(route_hits, now),
+            ));
         }
         route_hits.push(now);
     }
@@ -476,10 +663,12 @@ pub(crate) async fn enforce_directory_join_rate_limit(
             event = "directory.join.rate_limit",
             limiter = "user",
             user_id = %user_id,
-            client_ip = %ip,
+            client_ip = %loggable_client_ip(state, &ip),
             client_ip_source = client_ip.source().as_str()
         );
-        return Err(AuthFailure::RateLimited);
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs(route_hits, now),
+        ));
     }
     route_hits.push(now);
     Ok(())
@@ -504,13 +693,15 @@ pub(crate) async fn enforce_media_token_rate_limit(
     if route_hits.len() >= max_hits {
         tracing::warn!(
             event = "media.token.rate_limit",
-            client_ip = %ip,
+            client_ip = %loggable_client_ip(state, &ip),
             client_ip_source = client_ip.source().as_str(),
             user_id = %user_id,
             guild_id = %path.guild_id,
             channel_id = %path.channel_id
         );
-        return Err(AuthFailure::RateLimited);
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs(route_hits, now),
+        ));
     }
     route_hits.push(now);
     Ok(())
@@ -535,13 +726,133 @@ pub(crate) async fn enforce_media_publish_rate_limit(
     if route_hits.len() >= max_hits {
         tracing::warn!(
             event = "media.publish.rate_limit",
-            client_ip = %ip,
+            client_ip = %loggable_client_ip(state, &ip),
             client_ip_source = client_ip.source().as_str(),
             user_id = %user_id,
             guild_id = %path.guild_id,
             channel_id = %path.channel_id
         );
-        return Err(AuthFailure::RateLimited);
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs(route_hits, now),
+        ));
+    }
+    route_hits.push(now);
+    Ok(())
+}
+
+pub(crate) async fn enforce_search_rate_limit(
+    state: &AppState,
+    client_ip: ClientIp,
+    user_id: UserId,
+    guild_id: &str,
+) -> Result<(), AuthFailure> {
+    let ip = client_ip.normalized();
+    let key = format!("{ip}:{user_id}:{guild_id}");
+    let now = now_unix();
+    maybe_sweep_rate_limit_state(state, now).await;
+
+    let mut hits = state.search_query_hits.write().await;
+    let route_hits = hits.entry(key).or_default();
+    route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < RATE_LIMIT_WINDOW_SECS);
+    let max_hits = usize::try_from(state.runtime.search_requests_per_minute).unwrap_or(usize::MAX);
+    if route_hits.len() >= max_hits {
+        tracing::warn!(
+            event = "search.rate_limit",
+            client_ip = %loggable_client_ip(state, &ip),
+            client_ip_source = client_ip.source().as_str(),
+            user_id = %user_id,
+            guild_id = %guild_id
+        );
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs(route_hits, now),
+        ));
+    }
+    route_hits.push(now);
+    Ok(())
+}
+
+pub(crate) async fn enforce_channel_export_rate_limit(
+    state: &AppState,
+    client_ip: ClientIp,
+    user_id: UserId,
+    path: &ChannelPath,
+) -> Result<(), AuthFailure> {
+    let ip = client_ip.normalized();
+    let key = format!("{user_id}:{}", path.guild_id);
+    let now = now_unix();
+    maybe_sweep_rate_limit_state(state, now).await;
+
+    let mut hits = state.channel_export_hits.write().await;
+    let route_hits = hits.entry(key).or_default();
+    route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < RATE_LIMIT_WINDOW_SECS);
+    if route_hits.len() >= CHANNEL_EXPORT_REQUESTS_PER_MINUTE {
+        tracing::warn!(
+            event = "messages.export.rate_limit",
+            client_ip = %loggable_client_ip(state, &ip),
+            client_ip_source = client_ip.source().as_str(),
+            user_id = %user_id,
+            guild_id = %path.guild_id,
+            channel_id = %path.channel_id
+        );
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs(route_hits, now),
+        ));
+    }
+    route_hits.push(now);
+    Ok(())
+}
+
+pub(crate) async fn enforce_user_export_rate_limit(
+    state: &AppState,
+    client_ip: ClientIp,
+    user_id: UserId,
+) -> Result<(), AuthFailure> {
+    let ip = client_ip.normalized();
+    let key = user_id.to_string();
+    let now = now_unix();
+    maybe_sweep_rate_limit_state(state, now).await;
+
+    let mut hits = state.user_export_hits.write().await;
+    let route_hits = hits.entry(key).or_default();
+    route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < USER_EXPORT_WINDOW_SECS);
+    if route_hits.len() >= USER_EXPORT_REQUESTS_PER_WINDOW {
+        tracing::warn!(
+            event = "auth.me.export.rate_limit",
+            client_ip = %loggable_client_ip(state, &ip),
+            client_ip_source = client_ip.source().as_str(),
+            user_id = %user_id
+        );
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs_for_window(route_hits, now, USER_EXPORT_WINDOW_SECS),
+        ));
+    }
+    route_hits.push(now);
+    Ok(())
+}
+
+pub(crate) async fn enforce_report_rate_limit(
+    state: &AppState,
+    client_ip: ClientIp,
+    user_id: UserId,
+) -> Result<(), AuthFailure> {
+    let ip = client_ip.normalized();
+    let key = user_id.to_string();
+    let now = now_unix();
+    maybe_sweep_rate_limit_state(state, now).await;
+
+    let mut hits = state.report_hits.write().await;
+    let route_hits = hits.entry(key).or_default();
+    route_hits.retain(|timestamp| now.saturating_sub(*timestamp) < REPORT_WINDOW_SECS);
+    if route_hits.len() >= REPORT_REQUESTS_PER_WINDOW {
+        tracing::warn!(
+            event = "reports.rate_limit",
+            client_ip = %loggable_client_ip(state, &ip),
+            client_ip_source = client_ip.source().as_str(),
+            user_id = %user_id
+        );
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            rate_limit_retry_after_secs_for_window(route_hits, now, REPORT_WINDOW_SECS),
+        ));
     }
     route_hits.push(now);
     Ok(())
@@ -573,7 +884,10 @@ pub(crate) async fn enforce_media_subscribe_cap(
             guild_id = %path.guild_id,
             channel_id = %path.channel_id
         );
-        return Err(AuthFailure::RateLimited);
+        let next_expiry = channel_leases.iter().min().copied().unwrap_or(now);
+        return Err(AuthFailure::RateLimitedRetryAfter(
+            u64::try_from(next_expiry.saturating_sub(now)).unwrap_or(1),
+        ));
     }
     channel_leases.push(expires_at);
     Ok(())
@@ -623,6 +937,17 @@ pub(crate) fn allowed_publish_sources(permissions: PermissionSet) -> Vec<MediaPu
     sources
 }
 
+/// Returns the client IP as it may appear in logs. When `log_redact_pii` is
+/// enabled the address is omitted entirely: hashing is not used because the
+/// IPv4 space is small enough to reverse an unsalted digest by enumeration.
+pub(crate) fn loggable_client_ip<'a>(state: &AppState, ip: &'a str) -> &'a str {
+    if state.runtime.log_redact_pii {
+        REDACTED_LOG_VALUE
+    } else {
+        ip
+    }
+}
+
 pub(crate) fn resolve_client_ip(
     headers: &HeaderMap,
     peer_ip: Option<IpAddr>,
@@ -683,11 +1008,14 @@ fn parse_header_ip(value: Option<&HeaderValue>, max_len: usize) -> Option<IpAddr
 #[cfg(test)]
 mod tests {
     use super::{
-        build_captcha_config, enforce_auth_route_rate_limit, outbound_event, resolve_client_ip,
-        ClientIp, ClientIpSource,
+        apply_message_content_policy, build_captcha_config, enforce_auth_route_rate_limit,
+        loggable_client_ip, outbound_event, rate_limit_retry_after_secs, resolve_client_ip,
+        validate_message_content, validate_password, ClientIp, ClientIpSource,
+        MAX_MESSAGE_CONTENT_BYTES, MAX_PASSWORD_BYTES, MIN_PASSWORD_BYTES,
     };
-    use crate::server::core::{AppConfig, AppState};
+    use crate::server::core::{AppConfig, AppState, MessageContentPolicy};
     use crate::server::directory_contract::IpNetwork;
+    use crate::server::errors::AuthFailure;
     use axum::http::HeaderMap;
     use serde::Serialize;
     use serde_json::Value;
@@ -743,6 +1071,34 @@ mod tests {
         assert_eq!(captcha.verify_url, "https://api.hcaptcha.com/siteverify");
     }
 
+    #[test]
+    fn captcha_config_enforces_verify_host_allowlist() {
+        let config = AppConfig {
+            captcha_hcaptcha_site_key: Some(String::from("10000000-ffff-ffff-ffff-000000000001")),
+            captcha_hcaptcha_secret: Some(String::from(
+                "0x0000000000000000000000000000000000000000",
+            )),
+            captcha_verify_url: String::from("https://captcha.attacker.example/siteverify"),
+            captcha_verify_allowed_hosts: vec![String::from("API.hcaptcha.com")],
+            ..AppConfig::default()
+        };
+        let Err(error) = build_captcha_config(&config) else {
+            panic!("unlisted host should be rejected");
+        };
+        assert!(
+            error.to_string().contains("not in the allowed host list"),
+            "unexpected error: {error}"
+        );
+
+        let allowed = AppConfig {
+            captcha_verify_url: String::from("https://api.hcaptcha.com/siteverify"),
+            ..config
+        };
+        assert!(build_captcha_config(&allowed)
+            .expect("listed host should be accepted")
+            .is_some());
+    }
+
     #[test]
     fn client_ip_defaults_to_peer_when_proxy_is_untrusted() {
         let mut headers = HeaderMap::new();
@@ -995,4 +1351,128 @@ mod tests {
             "expired subscribe leases should be swept"
         );
     }
+
+    #[test]
+    fn loggable_client_ip_omits_address_only_when_redaction_is_enabled() {
+        let plain = AppState::new(&AppConfig::default()).expect("state should initialize");
+        assert_eq!(loggable_client_ip(&plain, "198.51.100.7"), "198.51.100.7");
+
+        let redacted = AppState::new(&AppConfig {
+            log_redact_pii: true,
+            ..AppConfig::default()
+        })
+        .expect("state should initialize");
+        assert_eq!(loggable_client_ip(&redacted, "198.51.100.7"), "redacted");
+    }
+
+    #[test]
+    fn rate_limit_retry_after_tracks_oldest_hit_in_window() {
+        assert_eq!(rate_limit_retry_after_secs(&[100, 130, 150], 150), 10);
+        assert_eq!(rate_limit_retry_after_secs(&[100], 159), 1);
+        assert_eq!(rate_limit_retry_after_secs(&[100], 200), 1);
+        assert_eq!(rate_limit_retry_after_secs(&[], 100), 60);
+    }
+
+    #[test]
+    fn message_content_policy_is_a_no_op_by_default() {
+        let state = AppState::new(&AppConfig::default()).expect("state should build");
+        let decomposed = String::from("e\u{0301}\u{200b}");
+
+        let content = apply_message_content_policy(&state.runtime, decomposed.clone())
+            .expect("default policy should accept content");
+
+        assert_eq!(content, decomposed);
+    }
+
+    #[test]
+    fn message_content_policy_normalizes_to_nfc() {
+        let state = AppState::new(&AppConfig {
+            message_content_policy: MessageContentPolicy {
+                normalize_nfc: true,
+                ..MessageContentPolicy::default()
+            },
+            ..AppConfig::default()
+        })
+        .expect("state should build");
+
+        let content = apply_message_content_policy(&state.runtime, String::from("cafe\u{0301}"))
+            .expect("content should normalize");
+
+        assert_eq!(content, "caf\u{e9}");
+    }
+
+    #[test]
+    fn message_content_policy_rejects_invisible_only_content() {
+        let state = AppState::new(&AppConfig {
+            message_content_policy: MessageContentPolicy {
+                reject_invisible: true,
+                ..MessageContentPolicy::default()
+            },
+            ..AppConfig::default()
+        })
+        .expect("state should build");
+
+        for blank in [" \n ", "\u{200b}\u{200d}", "\u{202e}\u{2800}\u{3164}"] {
+            assert!(matches!(
+                apply_message_content_policy(&state.runtime, blank.to_owned()),
+                Err(AuthFailure::ContentEmpty)
+            ));
+        }
+        assert!(apply_message_content_policy(&state.runtime, String::from("\u{200b}hi")).is_ok());
+        assert!(apply_message_content_policy(&state.runtime, String::new()).is_ok());
+    }
+
+    #[test]
+    fn message_content_policy_caps_combining_mark_share() {
+        let state = AppState::new(&AppConfig {
+            message_content_policy: MessageContentPolicy {
+                normalize_nfc: true,
+                reject_invisible: false,
+                max_combining_mark_percent: 30,
+            },
+            ..AppConfig::default()
+        })
+        .expect("state should build");
+        let zalgo = format!("h{}i", "\u{0336}\u{0351}\u{0354}".repeat(4));
+
+        assert!(apply_message_content_policy(&state.runtime, zalgo).is_err());
+        assert!(apply_message_content_policy(
+            &state.runtime,
+            String::from("Cafe\u{0301} na\u{0308}ive")
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn message_content_validation_distinguishes_empty_from_too_long() {
+        assert!(matches!(
+            validate_message_content(""),
+            Err(AuthFailure::ContentEmpty)
+        ));
+        assert!(validate_message_content(&"a".repeat(MAX_MESSAGE_CONTENT_BYTES)).is_ok());
+        assert!(matches!(
+            validate_message_content(&"a".repeat(MAX_MESSAGE_CONTENT_BYTES + 1)),
+            Err(AuthFailure::ContentTooLong {
+                limit: MAX_MESSAGE_CONTENT_BYTES
+            })
+        ));
+    }
+
+    #[test]
+    fn password_validation_reports_the_violated_bound() {
+        assert!(matches!(
+            validate_password(&"p".repeat(MIN_PASSWORD_BYTES - 1)),
+            Err(AuthFailure::PasswordTooShort {
+                limit: MIN_PASSWORD_BYTES
+            })
+        ));
+        assert!(validate_password(&"p".repeat(MIN_PASSWORD_BYTES)).is_ok());
+        assert!(validate_password(&"p".repeat(MAX_PASSWORD_BYTES)).is_ok());
+        assert!(matches!(
+            validate_password(&"p".repeat(MAX_PASSWORD_BYTES + 1)),
+            Err(AuthFailure::PasswordTooLong {
+                limit: MAX_PASSWORD_BYTES
+            })
+        ));
+    }
 }
@@ -6,7 +6,7 @@ use sqlx::{PgPool, Row};
 use filament_core::{UserId, Username};
 
 use crate::server::{
-    auth::{hash_refresh_token, verify_password},
+    auth::{hash_refresh_token, now_unix, verify_password},
     core::{
         AppState, SessionRecord, AUTH_SESSION_SWEEP_INTERVAL_SECS, LOGIN_LOCK_SECS,
         LOGIN_LOCK_THRESHOLD, REFRESH_REPLAY_RETENTION_SECS, REFRESH_TOKEN_TTL_SECS,
@@ -18,7 +18,10 @@ use crate::server::{
 pub(crate) struct RefreshCheck {
     pub(crate) session_id: String,
     pub(crate) user_id: UserId,
+    /// Hash the rotation must replace. For a grace retry this is the
+    /// session's current hash rather than the hash of the presented token.
     pub(crate) presented_hash: [u8; 32],
+    pub(crate) grace_retry: bool,
 }
 
 pub(crate) enum RefreshCheckError {
@@ -56,6 +59,9 @@ pub(crate) trait AuthPersistence {
         now_unix: i64,
     ) -> Result<RefreshCheck, RefreshCheckError>;
 
+    /// Moves the session onto `next_hash` and records `presented_hash` as
+    /// used. When `grace_retry` is set, the record has no successor, so the
+    /// token a retry superseded is always treated as a replay.
     async fn rotate_refresh_token(
         &self,
         session_id: &str,
@@ -63,6 +69,7 @@ pub(crate) trait AuthPersistence {
         next_hash: [u8; 32],
         now_unix: i64,
         next_expires_at_unix: i64,
+        grace_retry: bool,
     ) -> Result<(), AuthFailure>;
 
     async fn revoke_session_with_token(
@@ -75,9 +82,14 @@ pub(crate) trait AuthPersistence {
         &self,
         user_id: UserId,
         username: &Username,
-    ) -> Result<Option<(String, String, i64, i64)>, AuthFailure>;
+    ) -> Result<Option<(String, String, i64, i64, i64)>, AuthFailure>;
 
     async fn lookup_users(&self, user_ids: &[UserId]) -> Result<Vec<UserLookupItem>, AuthFailure>;
+
+    async fn lookup_users_by_username(
+        &self,
+        usernames: &[Username],
+    ) -> Result<Vec<UserLookupItem>, AuthFailure>;
 }
 
 pub(crate) struct PostgresAuthRepository<'a> {
@@ -112,6 +124,50 @@ impl<'a> PostgresAuthRepository<'a> {
 
         Ok(())
     }
+
+    /// Accepts a reused refresh token when the session still holds the hash
+    /// it was rotated into, i.e. nothing has refreshed since. Only hashes are
+    /// stored, so the caller rotates again and the retry gets a new pair; that
+    /// pair belongs to the same session and is revoked with it.
+    async fn grace_retry(
+        &self,
+        session_id: &str,
+        successor_hash: &[u8],
+        now_unix: i64,
+    ) -> Result<Option<RefreshCheck>, RefreshCheckError> {
+        let Some(row) = sqlx::query(
+            "SELECT user_id FROM sessions
+             WHERE session_id = $1
+               AND refresh_token_hash = $2
+               AND NOT revoked
+               AND expires_at_unix >= $3",
+        )
+        .bind(session_id)
+        .bind(successor_hash)
+        .bind(now_unix)
+        .fetch_optional(self.pool)
+        .await
+        .map_err(|_| RefreshCheckError::Internal)?
+        else {
+            return Ok(None);
+        };
+        let user_id: String = row
+            .try_get("user_id")
+            .map_err(|_| RefreshCheckError::Internal)?;
+        let user_id = UserId::try_from(user_id).map_err(|_| RefreshCheckError::Internal)?;
+        let presented_hash =
+            <[u8; 32]>::try_from(successor_hash).map_err(|_| RefreshCheckError::Internal)?;
+        Ok(Some(RefreshCheck {
+            session_id: session_id.to_owned(),
+            user_id,
+            presented_hash,
+            grace_retry: true,
+        }))
+    }
+}
+
+fn refresh_reuse_grace_secs(state: &AppState) -> i64 {
+    i64::try_from(state.runtime.refresh_token_reuse_grace.as_secs()).unwrap_or(i64::MAX)
 }
 
 async fn maybe_sweep_auth_state(repo_state: &AppState, now_unix: i64) -> Result<(), AuthFailure> {
@@ -150,13 +206,14 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
     ) -> Result<bool, AuthFailure> {
         let user_id = UserId::new();
         let insert_result = sqlx::query(
-            "INSERT INTO users (user_id, username, password_hash, failed_logins, locked_until_unix)
-             VALUES ($1, $2, $3, 0, NULL)
+            "INSERT INTO users (user_id, username, password_hash, failed_logins, locked_until_unix, created_at_unix)
+             VALUES ($1, $2, $3, 0, NULL, $4)
              ON CONFLICT (username) DO NOTHING",
         )
         .bind(user_id.to_string())
         .bind(username.as_str())
         .bind(password_hash)
+        .bind(now_unix())
         .execute(self.pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
@@ -262,16 +319,35 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
             .map_err(|_| RefreshCheckError::Internal)?;
 
         let presented_hash = hash_refresh_token(refresh_token);
-        if let Some(row) =
-            sqlx::query("SELECT session_id FROM used_refresh_tokens WHERE token_hash = $1")
-                .bind(presented_hash.as_slice())
-                .fetch_optional(self.pool)
-                .await
-                .map_err(|_| RefreshCheckError::Internal)?
+        if let Some(row) = sqlx::query(
+            "SELECT session_id, used_at_unix, successor_hash
+             FROM used_refresh_tokens WHERE token_hash = $1",
+        )
+        .bind(presented_hash.as_slice())
+        .fetch_optional(self.pool)
+        .await
+        .map_err(|_| RefreshCheckError::Internal)?
         {
             let replay_session_id: String = row
                 .try_get("session_id")
                 .map_err(|_| RefreshCheckError::Internal)?;
+            let used_at_unix: i64 = row
+                .try_get("used_at_unix")
+                .map_err(|_| RefreshCheckError::Internal)?;
+            let successor_hash: Option<Vec<u8>> = row
+                .try_get("successor_hash")
+                .map_err(|_| RefreshCheckError::Internal)?;
+            let grace_secs = refresh_reuse_grace_secs(self.state);
+            if let Some(successor_hash) = successor_hash
+                .filter(|_| grace_secs > 0 && used_at_unix >= now_unix.saturating_sub(grace_secs))
+            {
+                if let Some(check) = self
+                    .grace_retry(&replay_session_id, &successor_hash, now_unix)
+                    .await?
+                {
+                    return Ok(check);
+                }
+            }
             sqlx::query("UPDATE sessions SET revoked = TRUE WHERE session_id = $1")
                 .bind(&replay_session_id)
                 .execute(self.pool)
@@ -323,6 +399,7 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
             session_id,
             user_id,
             presented_hash,
+            grace_retry: false,
         })
     }
 
@@ -333,6 +410,7 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
         next_hash: [u8; 32],
         now_unix: i64,
         next_expires_at_unix: i64,
+        grace_retry: bool,
     ) -> Result<(), AuthFailure> {
         maybe_sweep_auth_state(self.state, now_unix).await?;
 
@@ -347,12 +425,14 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
         .map_err(|_| AuthFailure::Internal)?;
 
         sqlx::query(
-            "INSERT INTO used_refresh_tokens (token_hash, session_id, used_at_unix) VALUES ($1, $2, $3)
+            "INSERT INTO used_refresh_tokens (token_hash, session_id, used_at_unix, successor_hash)
+             VALUES ($1, $2, $3, $4)
              ON CONFLICT (token_hash) DO NOTHING",
         )
         .bind(presented_hash.as_slice())
         .bind(session_id)
         .bind(now_unix)
+        .bind((!grace_retry).then_some(next_hash.as_slice()))
         .execute(self.pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
@@ -390,9 +470,9 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
         &self,
         user_id: UserId,
         _username: &Username,
-    ) -> Result<Option<(String, String, i64, i64)>, AuthFailure> {
+    ) -> Result<Option<(String, String, i64, i64, i64)>, AuthFailure> {
         let row = sqlx::query(
-            "SELECT username, about_markdown, avatar_version, banner_version
+            "SELECT username, about_markdown, avatar_version, banner_version, created_at_unix
              FROM users
              WHERE user_id = $1",
         )
@@ -413,11 +493,15 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
                 let banner_version: i64 = r
                     .try_get("banner_version")
                     .map_err(|_| AuthFailure::Internal)?;
+                let created_at_unix: i64 = r
+                    .try_get("created_at_unix")
+                    .map_err(|_| AuthFailure::Internal)?;
                 Ok(Some((
                     username,
                     about_markdown,
                     avatar_version,
                     banner_version,
+                    created_at_unix,
                 )))
             }
             None => Ok(None),
@@ -427,7 +511,7 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
     async fn lookup_users(&self, user_ids: &[UserId]) -> Result<Vec<UserLookupItem>, AuthFailure> {
         let user_ids: Vec<String> = user_ids.iter().map(ToString::to_string).collect();
         let rows = sqlx::query(
-            "SELECT user_id, username, avatar_version
+            "SELECT user_id, username, avatar_version, created_at_unix
              FROM users
              WHERE user_id = ANY($1)",
         )
@@ -443,7 +527,10 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
             let avatar_version: i64 = row
                 .try_get("avatar_version")
                 .map_err(|_| AuthFailure::Internal)?;
-            users_by_id.insert(user_id, (username, avatar_version));
+            let created_at_unix: i64 = row
+                .try_get("created_at_unix")
+                .map_err(|_| AuthFailure::Internal)?;
+            users_by_id.insert(user_id, (username, avatar_version, created_at_unix));
         }
 
         let users = user_ids
@@ -451,16 +538,68 @@ impl AuthPersistence for PostgresAuthRepository<'_> {
             .filter_map(|user_id| {
                 users_by_id
                     .get(&user_id)
-                    .map(|(username, avatar_version)| UserLookupItem {
-                        user_id: user_id.clone(),
-                        username: username.clone(),
-                        avatar_version: *avatar_version,
-                    })
+                    .map(
+                        |(username, avatar_version, created_at_unix)| UserLookupItem {
+                            user_id: user_id.clone(),
+                            username: username.clone(),
+                            avatar_version: *avatar_version,
+                            created_at_unix: *created_at_unix,
+                        },
+                    )
             })
             .collect();
 
         Ok(users)
     }
+
+    async fn lookup_users_by_username(
+        &self,
+        usernames: &[Username],
+    ) -> Result<Vec<UserLookupItem>, AuthFailure> {
+        let usernames: Vec<String> = usernames
+            .iter()
+            .map(|username| username.as_str().to_owned())
+            .collect();
+        let rows = sqlx::query(
+            "SELECT user_id, username, avatar_version, created_at_unix
+             FROM users
+             WHERE username = ANY($1)",
+        )
+        .bind(&usernames)
+        .fetch_all(self.pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+
+        let mut users_by_name = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let user_id: String = row.try_get("user_id").map_err(|_| AuthFailure::Internal)?;
+            let username: String = row.try_get("username").map_err(|_| AuthFailure::Internal)?;
+            let avatar_version: i64 = row
+                .try_get("avatar_version")
+                .map_err(|_| AuthFailure::Internal)?;
+            let created_at_unix: i64 = row
+                .try_get("created_at_unix")
+                .map_err(|_| AuthFailure::Internal)?;
+            users_by_name.insert(username, (user_id, avatar_version, created_at_unix));
+        }
+
+        let users =
+            usernames
+                .into_iter()
+                .filter_map(|username| {
+                    users_by_name.remove(&username).map(
+                        |(user_id, avatar_version, created_at_unix)| UserLookupItem {
+                            user_id,
+                            username,
+                            avatar_version,
+                            created_at_unix,
+                        },
+                    )
+                })
+                .collect();
+
+        Ok(users)
+    }
 }
 
 pub(crate) struct InMemoryAuthRepository<'a> {
@@ -498,6 +637,7 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
                 password_hash: password_hash.to_owned(),
                 failed_logins: 0,
                 locked_until_unix: None,
+                created_at_unix: now_unix(),
             },
         );
         drop(users);
@@ -586,6 +726,23 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
             .map_err(|_| RefreshCheckError::Internal)?;
 
         let presented_hash = hash_refresh_token(refresh_token);
+        let grace_secs = refresh_reuse_grace_secs(self.state);
+        let grace_retry = if grace_secs > 0 {
+            self.state
+                .session_store
+                .grace_retry_for_token(presented_hash, now_unix, grace_secs)
+                .await
+        } else {
+            None
+        };
+        if let Some((session_id, user_id, current_hash)) = grace_retry {
+            return Ok(RefreshCheck {
+                session_id,
+                user_id,
+                presented_hash: current_hash,
+                grace_retry: true,
+            });
+        }
         if let Some(session_id) = self
             .state
             .session_store
@@ -614,6 +771,7 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
             session_id,
             user_id,
             presented_hash,
+            grace_retry: false,
         })
     }
 
@@ -624,6 +782,7 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
         next_hash: [u8; 32],
         now_unix: i64,
         next_expires_at_unix: i64,
+        grace_retry: bool,
     ) -> Result<(), AuthFailure> {
         maybe_sweep_auth_state(self.state, now_unix).await?;
 
@@ -635,6 +794,7 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
                 next_hash,
                 now_unix,
                 next_expires_at_unix,
+                grace_retry,
             )
             .await
             .map_err(|()| AuthFailure::Unauthorized)?;
@@ -657,7 +817,7 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
         &self,
         _user_id: UserId,
         username: &Username,
-    ) -> Result<Option<(String, String, i64, i64)>, AuthFailure> {
+    ) -> Result<Option<(String, String, i64, i64, i64)>, AuthFailure> {
         let users = self.state.users.read().await;
         if let Some(user) = users.get(username.as_str()) {
             Ok(Some((
@@ -665,6 +825,7 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
                 user.about_markdown.clone(),
                 user.avatar_version,
                 user.banner_version,
+                user.created_at_unix,
             )))
         } else {
             Ok(None)
@@ -673,6 +834,7 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
 
     async fn lookup_users(&self, user_ids: &[UserId]) -> Result<Vec<UserLookupItem>, AuthFailure> {
         let user_ids_map = self.state.user_ids.read().await;
+        let users_map = self.state.users.read().await;
         let users = user_ids
             .iter()
             .filter_map(|user_id| {
@@ -681,6 +843,9 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
                     .get(&user_id_text)
                     .cloned()
                     .map(|username| UserLookupItem {
+                        created_at_unix: users_map
+                            .get(&username)
+                            .map_or(0, |record| record.created_at_unix),
                         user_id: user_id_text,
                         username,
                         avatar_version: 0,
@@ -689,6 +854,27 @@ impl AuthPersistence for InMemoryAuthRepository<'_> {
             .collect();
         Ok(users)
     }
+
+    async fn lookup_users_by_username(
+        &self,
+        usernames: &[Username],
+    ) -> Result<Vec<UserLookupItem>, AuthFailure> {
+        let users_map = self.state.users.read().await;
+        let users = usernames
+            .iter()
+            .filter_map(|username| {
+                users_map
+                    .get(username.as_str())
+                    .map(|record| UserLookupItem {
+                        user_id: record.id.to_string(),
+                        username: record.username.as_str().to_owned(),
+                        avatar_version: record.avatar_version,
+                        created_at_unix: record.created_at_unix,
+                    })
+            })
+            .collect();
+        Ok(users)
+    }
 }
 
 pub(crate) enum AuthRepository<'a> {
@@ -774,6 +960,7 @@ impl AuthPersistence for AuthRepository<'_> {
         next_hash: [u8; 32],
         now_unix: i64,
         next_expires_at_unix: i64,
+        grace_retry: bool,
     ) -> Result<(), AuthFailure> {
         match self {
             Self::Postgres(repo) => {
@@ -783,6 +970,7 @@ impl AuthPersistence for AuthRepository<'_> {
                     next_hash,
                     now_unix,
                     next_expires_at_unix,
+                    grace_retry,
                 )
                 .await
             }
@@ -793,6 +981,7 @@ impl AuthPersistence for AuthRepository<'_> {
                     next_hash,
                     now_unix,
                     next_expires_at_unix,
+                    grace_retry,
                 )
                 .await
             }
@@ -814,7 +1003,7 @@ impl AuthPersistence for AuthRepository<'_> {
         &self,
         user_id: UserId,
         username: &Username,
-    ) -> Result<Option<(String, String, i64, i64)>, AuthFailure> {
+    ) -> Result<Option<(String, String, i64, i64, i64)>, AuthFailure> {
         match self {
             Self::Postgres(repo) => repo.get_user_profile(user_id, username).await,
             Self::InMemory(repo) => repo.get_user_profile(user_id, username).await,
@@ -827,6 +1016,16 @@ impl AuthPersistence for AuthRepository<'_> {
             Self::InMemory(repo) => repo.lookup_users(user_ids).await,
         }
     }
+
+    async fn lookup_users_by_username(
+        &self,
+        usernames: &[Username],
+    ) -> Result<Vec<UserLookupItem>, AuthFailure> {
+        match self {
+            Self::Postgres(repo) => repo.lookup_users_by_username(usernames).await,
+            Self::InMemory(repo) => repo.lookup_users_by_username(usernames).await,
+        }
+    }
 }
 
 pub(crate) fn refresh_session_ttl_unix(now_unix: i64) -> i64 {
@@ -1,7 +1,7 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     path::PathBuf,
-    sync::atomic::AtomicI64,
+    sync::atomic::{AtomicI64, AtomicU64, AtomicUsize},
     sync::{Arc, Mutex, OnceLock},
     time::Duration,
 };
@@ -16,7 +16,7 @@ use pasetors::{keys::SymmetricKey, version4::V4};
 use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use tantivy::schema::Field;
-use tokio::sync::{mpsc, oneshot, watch, OnceCell, RwLock};
+use tokio::sync::{mpsc, oneshot, watch, OnceCell, RwLock, Semaphore};
 use uuid::Uuid;
 
 use super::{
@@ -25,62 +25,194 @@ use super::{
         IpNetwork, DEFAULT_AUDIT_LIST_LIMIT_MAX, DEFAULT_DIRECTORY_JOIN_REQUESTS_PER_MINUTE_PER_IP,
         DEFAULT_DIRECTORY_JOIN_REQUESTS_PER_MINUTE_PER_USER, DEFAULT_GUILD_IP_BAN_MAX_ENTRIES,
     },
+    domain::{build_message_content_cipher, MessageContentCipher},
     errors::AuthFailure,
-    realtime::init_search_service,
+    realtime::{init_search_service, OutboundBufferBudget, OutboundSender},
+    types::{EmbedResponse, MessageResponse},
 };
 
-pub(crate) type ChannelSubscriptions = HashMap<Uuid, mpsc::Sender<String>>;
-pub(crate) type Subscriptions = HashMap<String, ChannelSubscriptions>;
+pub(crate) type ChannelSubscriptions = HashMap<Uuid, OutboundSender>;
+pub(crate) type Subscriptions = HashMap<ChannelKey, ChannelSubscriptions>;
 pub(crate) type GuildConnectionIndex = HashMap<String, HashSet<Uuid>>;
+pub(crate) type ConnectionEventFilters = HashMap<Uuid, HashSet<&'static str>>;
 pub(crate) type UserConnectionIndex = HashMap<UserId, HashSet<Uuid>>;
 pub(crate) type GuildIpBanMap = HashMap<String, Vec<GuildIpBanRecord>>;
+pub(crate) type GuildMuteMap = HashMap<String, HashMap<UserId, GuildMuteRecord>>;
+pub(crate) type GuildTagMap = HashMap<String, Vec<String>>;
 pub(crate) type GuildRoleMap = HashMap<String, HashMap<String, WorkspaceRoleRecord>>;
 pub(crate) type GuildRoleAssignmentMap = HashMap<String, HashMap<UserId, HashSet<String>>>;
 pub(crate) type GuildChannelPermissionOverrideMap =
     HashMap<String, HashMap<String, ChannelPermissionOverrideRecord>>;
 pub(crate) type VoiceParticipantsByChannel = HashMap<String, HashMap<UserId, VoiceParticipant>>;
 
+/// Channel subscription key. Kept structured so guild fan-out and cleanup are
+/// exact field comparisons rather than parsing a joined `guild:channel` string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ChannelKey {
+    guild_id: String,
+    channel_id: String,
+}
+
+impl ChannelKey {
+    pub(crate) fn new(guild_id: impl Into<String>, channel_id: impl Into<String>) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            channel_id: channel_id.into(),
+        }
+    }
+
+    pub(crate) fn guild_id(&self) -> &str {
+        &self.guild_id
+    }
+
+    pub(crate) fn channel_id(&self) -> &str {
+        &self.channel_id
+    }
+}
+
+impl std::fmt::Display for ChannelKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.guild_id, self.channel_id)
+    }
+}
+
 pub const DEFAULT_JSON_BODY_LIMIT_BYTES: usize = 1_048_576;
 pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 10;
 pub const DEFAULT_RATE_LIMIT_REQUESTS_PER_MINUTE: u32 = 600;
 pub const DEFAULT_AUTH_ROUTE_REQUESTS_PER_MINUTE: u32 = 60;
+pub(crate) const MAX_ROUTE_RATE_LIMIT_OVERRIDES: usize = 64;
+pub(crate) const MAX_ROUTE_RATE_LIMIT_REQUESTS_PER_MINUTE: u32 = 6_000;
+pub(crate) const MAX_ROUTE_BODY_LIMIT_OVERRIDES: usize = 64;
+pub(crate) const MAX_ROUTE_BODY_LIMIT_BYTES: usize = 16 * 1024 * 1024;
+pub(crate) const MAX_ROUTE_BODY_LIMIT_PATH_CHARS: usize = 256;
 pub const ACCESS_TOKEN_TTL_SECS: i64 = 15 * 60;
 pub const REFRESH_TOKEN_TTL_SECS: i64 = 30 * 24 * 60 * 60;
 pub const DEFAULT_GATEWAY_INGRESS_EVENTS_PER_WINDOW: u32 = 60;
 pub const DEFAULT_GATEWAY_INGRESS_WINDOW_SECS: u64 = 10;
 pub const DEFAULT_GATEWAY_OUTBOUND_QUEUE: usize = 256;
+pub const DEFAULT_GATEWAY_OUTBOUND_BUFFER_MAX_BYTES: usize = 64 * 1024 * 1024;
+pub const DEFAULT_GATEWAY_SLOW_CONSUMER_MAX_STRIKES: u32 = 3;
+pub(crate) const MAX_GATEWAY_SLOW_CONSUMER_MAX_STRIKES: u32 = 64;
+pub const DEFAULT_GATEWAY_MAX_CONNECTION_LIFETIME_SECS: u64 = 60 * 60;
+pub(crate) const MAX_GATEWAY_MAX_CONNECTION_LIFETIME_SECS: u64 = 7 * 24 * 60 * 60;
+pub const DEFAULT_GATEWAY_MAX_CONNECTIONS_PER_USER: usize = 10;
+pub(crate) const MAX_GATEWAY_MAX_CONNECTIONS_PER_USER: usize = 256;
+pub(crate) const MIN_ADMIN_API_SECRET_CHARS: usize = 32;
+pub const DEFAULT_GATEWAY_SUBSCRIPTION_REVALIDATE_SECS: u64 = 30;
+pub(crate) const MAX_GATEWAY_SUBSCRIPTION_REVALIDATE_SECS: u64 = 60 * 60;
 pub const DEFAULT_MAX_GATEWAY_EVENT_BYTES: usize = filament_protocol::MAX_EVENT_BYTES;
 pub const DEFAULT_MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
 pub const DEFAULT_MAX_PROFILE_AVATAR_BYTES: usize = 2 * 1024 * 1024;
 pub const DEFAULT_MAX_PROFILE_BANNER_BYTES: usize = 6 * 1024 * 1024;
 pub const DEFAULT_USER_ATTACHMENT_QUOTA_BYTES: u64 = 250 * 1024 * 1024;
+pub const DEFAULT_MAX_MESSAGE_ATTACHMENT_TOTAL_BYTES: u64 = 100 * 1024 * 1024;
+pub const DEFAULT_ATTACHMENT_KEY_PREFIX: &str = "attachments";
+pub(crate) const MAX_ATTACHMENT_KEY_PREFIX_CHARS: usize = 64;
+pub const DEFAULT_ATTACHMENT_KEY_SHARD_CHARS: usize = 2;
+pub(crate) const MAX_ATTACHMENT_KEY_SHARD_CHARS: usize = 4;
 pub const DEFAULT_SEARCH_QUERY_MAX_CHARS: usize = 256;
 pub const DEFAULT_SEARCH_RESULT_LIMIT: usize = 20;
 pub const DEFAULT_SEARCH_RESULT_LIMIT_MAX: usize = 50;
 pub const DEFAULT_SEARCH_QUERY_TIMEOUT_MILLIS: u64 = 200;
+pub const DEFAULT_SEARCH_QUERY_TIMEOUT_MAX_MILLIS: u64 = 2_000;
+pub const DEFAULT_SEARCH_REQUESTS_PER_MINUTE: u32 = 30;
+pub const DEFAULT_SEARCH_MAX_CONCURRENT_QUERIES: usize = 8;
+pub const DEFAULT_SEARCH_RECONCILE_BATCH_DOCS: usize = 1_000;
+pub const DEFAULT_SEARCH_WRITER_HEAP_BYTES: usize = 50_000_000;
 pub const DEFAULT_MEDIA_TOKEN_REQUESTS_PER_MINUTE: u32 = 60;
 pub const DEFAULT_MEDIA_PUBLISH_REQUESTS_PER_MINUTE: u32 = 60;
 pub const DEFAULT_LIVEKIT_TOKEN_TTL_SECS: u64 = 5 * 60;
 pub const DEFAULT_MEDIA_SUBSCRIBE_TOKEN_CAP_PER_CHANNEL: usize = 6;
 pub const DEFAULT_MAX_CREATED_GUILDS_PER_USER: usize = 5;
+pub const DEFAULT_MAX_FRIENDS_PER_USER: usize = 1_000;
+pub const DEFAULT_MAX_INCOMING_FRIEND_REQUESTS_PER_USER: usize = 200;
+pub const DEFAULT_AUDIT_LOG_MEMORY_MAX_ENTRIES: usize = 10_000;
+pub const DEFAULT_IN_MEMORY_MAX_GUILDS: usize = 10_000;
+pub const DEFAULT_IN_MEMORY_MAX_CHANNEL_MESSAGES: usize = 10_000;
+pub const DEFAULT_IN_MEMORY_MAX_FRIEND_REQUESTS: usize = 200;
+pub const DEFAULT_IN_MEMORY_MAX_TOTAL_FRIEND_REQUESTS: usize = 100_000;
+pub const DEFAULT_IN_MEMORY_MAX_REBUILD_DOCS: usize = 100_000;
 pub const DEFAULT_CAPTCHA_VERIFY_TIMEOUT_SECS: u64 = 3;
 pub const MAX_LIVEKIT_TOKEN_TTL_SECS: u64 = 5 * 60;
+pub const DEFAULT_DELETED_MESSAGE_RETENTION_SECS: u64 = 0;
+pub const DEFAULT_MIME_SNIFF_BYTES: usize = 8192;
+pub(crate) const MIN_MIME_SNIFF_BYTES: usize = 512;
+pub(crate) const MAX_MIME_SNIFF_BYTES: usize = 1024 * 1024;
+pub const DEFAULT_HISTORY_DEFAULT_LIMIT: usize = 20;
+pub const DEFAULT_HISTORY_MAX_LIMIT: usize = 100;
+pub(crate) const MAX_DELETED_MESSAGE_RETENTION_SECS: u64 = 90 * 24 * 60 * 60;
 pub(crate) const RATE_LIMIT_SWEEP_INTERVAL_SECS: i64 = 30;
+pub(crate) const MESSAGE_RATE_WINDOW_SECS: i64 = 60;
+pub(crate) const DEFAULT_ADMIN_MESSAGE_RATES_LIMIT: usize = 20;
+pub(crate) const MAX_ADMIN_MESSAGE_RATES_LIMIT: usize = 100;
 pub(crate) const AUTH_SESSION_SWEEP_INTERVAL_SECS: i64 = 60;
+pub(crate) const DELETED_MESSAGE_PURGE_INTERVAL_SECS: u64 = 60;
+pub(crate) const DELETED_MESSAGE_PURGE_BATCH: usize = 500;
+pub(crate) const MIN_CHANNEL_RETENTION_SECS: u64 = 60 * 60;
+pub(crate) const MAX_CHANNEL_RETENTION_SECS: u64 = 10 * 365 * 24 * 60 * 60;
+pub(crate) const CHANNEL_RETENTION_PRUNE_INTERVAL_SECS: u64 = 60;
+pub(crate) const CHANNEL_RETENTION_PRUNE_BATCH: usize = 500;
+pub(crate) const AUDIT_LOG_PURGE_INTERVAL_SECS: u64 = 60;
+pub(crate) const AUDIT_LOG_PURGE_BATCH: usize = 1000;
+pub(crate) const MESSAGE_IDEMPOTENCY_TTL_SECS: i64 = 10 * 60;
+pub(crate) const MAX_MESSAGE_IDEMPOTENCY_RECORDS: usize = 100_000;
+pub(crate) const MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER: usize = 100;
+pub(crate) const MAX_IDEMPOTENCY_KEY_CHARS: usize = 64;
 pub(crate) const REFRESH_REPLAY_RETENTION_SECS: i64 = REFRESH_TOKEN_TTL_SECS + 60 * 60;
+pub(crate) const MAX_REFRESH_TOKEN_REUSE_GRACE_SECS: u64 = 60;
 pub(crate) const MAX_CAPTCHA_TOKEN_CHARS: usize = 4096;
 pub(crate) const MIN_CAPTCHA_TOKEN_CHARS: usize = 20;
 pub(crate) const LOGIN_LOCK_THRESHOLD: u8 = 5;
 pub(crate) const LOGIN_LOCK_SECS: i64 = 30;
-pub(crate) const MAX_HISTORY_LIMIT: usize = 100;
-pub(crate) const MAX_MIME_SNIFF_BYTES: usize = 8192;
+pub(crate) const MAX_HISTORY_LIMIT: usize = 500;
+pub(crate) const MAX_RECENT_MESSAGES_CHANNELS: usize = 50;
+pub(crate) const DEFAULT_RECENT_MESSAGES_PER_CHANNEL: usize = 5;
+pub(crate) const MAX_RECENT_MESSAGES_PER_CHANNEL: usize = 20;
+pub(crate) const CHANNEL_EXPORT_REQUESTS_PER_MINUTE: usize = 2;
+pub(crate) const USER_EXPORT_REQUESTS_PER_WINDOW: usize = 1;
+pub(crate) const USER_EXPORT_WINDOW_SECS: i64 = 60 * 60;
+pub(crate) const REPORT_REQUESTS_PER_WINDOW: usize = 5;
+pub(crate) const REPORT_WINDOW_SECS: i64 = 10 * 60;
+pub(crate) const MAX_REPORT_REASON_CHARS: usize = 512;
+pub(crate) const DEFAULT_REPORT_LIST_LIMIT: usize = 50;
+pub(crate) const MAX_REPORT_LIST_LIMIT: usize = 100;
+pub(crate) const DEFAULT_NOTIFICATION_LIST_LIMIT: usize = 50;
+pub(crate) const MAX_NOTIFICATION_LIST_LIMIT: usize = 100;
+/// Oldest entries are pruned once a user's inbox grows past this.
+pub(crate) const MAX_NOTIFICATIONS_PER_USER: usize = 1_000;
+pub(crate) const MAX_NOTIFICATION_MARK_READ_IDS: usize = 100;
+/// Upper bound on users one `@everyone`/`@here` notifies. Each candidate costs
+/// a channel permission lookup on the message write path.
+pub(crate) const MAX_MENTION_RECIPIENTS: usize = 500;
+pub(crate) const MAX_PUSH_SUBSCRIPTIONS_PER_USER: usize = 10;
+pub(crate) const MAX_PUSH_ENDPOINT_CHARS: usize = 2048;
+pub(crate) const PUSH_DELIVERY_QUEUE_CAPACITY: usize = 1024;
+pub(crate) const MAX_PUSH_CONCURRENT_DELIVERIES: usize = 16;
+pub(crate) const PUSH_DELIVERY_MAX_ATTEMPTS: u32 = 3;
+/// Doubled after every retryable failure.
+pub(crate) const PUSH_DELIVERY_RETRY_BASE_MILLIS: u64 = 1_000;
+pub(crate) const MIN_GUILD_MUTE_DURATION_SECS: u64 = 60;
+pub(crate) const MAX_GUILD_MUTE_DURATION_SECS: u64 = 28 * 24 * 60 * 60;
+pub(crate) const GUILD_MUTE_PURGE_INTERVAL_SECS: u64 = 60;
+pub(crate) const MAX_GUILD_DESCRIPTION_CHARS: usize = 300;
+pub(crate) const MAX_GUILD_TAGS: usize = 5;
+pub(crate) const MAX_GUILD_TAG_CHARS: usize = 24;
 pub(crate) const MAX_SEARCH_TERMS: usize = 20;
 pub(crate) const MAX_SEARCH_WILDCARDS: usize = 4;
 pub(crate) const MAX_SEARCH_FUZZY: usize = 2;
 pub(crate) const SEARCH_INDEX_QUEUE_CAPACITY: usize = 1024;
-pub(crate) const MAX_SEARCH_RECONCILE_DOCS: usize = 10_000;
+pub(crate) const MAX_SEARCH_RECONCILE_DOCS: usize = 1_000_000;
+pub(crate) const GUILD_DIRECTORY_WRITER_HEAP_BYTES: usize = 15_000_000;
+/// Tantivy refuses writer budgets below 15 MB.
+pub(crate) const MIN_SEARCH_WRITER_HEAP_BYTES: usize = 15_000_000;
+pub(crate) const MAX_SEARCH_WRITER_HEAP_BYTES: usize = 1_000_000_000;
+/// Ceiling for the configurable per-guild search timeout bound.
+pub(crate) const MAX_SEARCH_QUERY_TIMEOUT_MILLIS: u64 = 30_000;
 pub(crate) const MAX_REACTION_EMOJI_CHARS: usize = 32;
 pub(crate) const MAX_REACTIONS_PER_MESSAGE: usize = 64;
+pub(crate) const VOTE_UP_EMOJI: &str = "\u{1f44d}";
+pub(crate) const VOTE_DOWN_EMOJI: &str = "\u{1f44e}";
+pub(crate) const WELCOME_MESSAGE_CONTENT: &str = "joined the guild";
 pub(crate) const MAX_REACTOR_USER_IDS_PER_REACTION: usize = 32;
 pub(crate) const MAX_USER_LOOKUP_IDS: usize = 64;
 pub(crate) const MAX_ATTACHMENTS_PER_MESSAGE: usize = 5;
@@ -90,6 +222,15 @@ pub(crate) const MAX_PROFILE_BANNER_MIME_CHARS: usize = 64;
 pub(crate) const MAX_PROFILE_BANNER_OBJECT_KEY_CHARS: usize = 128;
 pub(crate) const MAX_TRACKED_VOICE_CHANNELS: usize = 1024;
 pub(crate) const MAX_TRACKED_VOICE_PARTICIPANTS_PER_CHANNEL: usize = 512;
+pub(crate) const MAX_LINK_PREVIEWS_PER_MESSAGE: usize = 3;
+pub(crate) const MAX_LINK_PREVIEW_URL_CHARS: usize = 2048;
+pub(crate) const MAX_LINK_PREVIEW_RESPONSE_BYTES: usize = 256 * 1024;
+pub(crate) const MAX_LINK_PREVIEW_CONCURRENT_FETCHES: usize = 4;
+pub(crate) const MAX_IN_MEMORY_LINK_PREVIEWS: usize = 1024;
+pub(crate) const LINK_PREVIEW_FETCH_TIMEOUT_SECS: u64 = 3;
+pub(crate) const LINK_PREVIEW_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+pub(crate) const LINK_PREVIEW_PRUNE_INTERVAL_SECS: u64 = 10 * 60;
+pub(crate) const LINK_PREVIEW_PRUNE_BATCH: usize = 500;
 pub(crate) const METRICS_TEXT_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
 
 pub(crate) static METRICS_STATE: OnceLock<MetricsState> = OnceLock::new();
@@ -104,25 +245,54 @@ pub(crate) struct MetricsState {
     pub(crate) gateway_events_unknown_received: Mutex<HashMap<(String, String), u64>>,
     pub(crate) gateway_events_parse_rejected: Mutex<HashMap<(String, String), u64>>,
     pub(crate) voice_sync_repairs: Mutex<HashMap<String, u64>>,
+    pub(crate) push_deliveries: Mutex<HashMap<&'static str, u64>>,
+    pub(crate) channel_retention_pruned: Mutex<HashMap<&'static str, u64>>,
+    pub(crate) search_worker_restarts: Mutex<HashMap<&'static str, u64>>,
+    pub(crate) search_index_ops_dropped: Mutex<HashMap<&'static str, u64>>,
+    pub(crate) messages_created: AtomicU64,
+    pub(crate) audit_log_entries_written: Mutex<HashMap<String, u64>>,
+    pub(crate) audit_log_entries_pruned: Mutex<HashMap<&'static str, u64>>,
+    pub(crate) gateway_outbound_buffered_bytes: AtomicUsize,
 }
 
 #[derive(Clone, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct AppConfig {
     pub max_body_bytes: usize,
     pub request_timeout: Duration,
     pub rate_limit_requests_per_minute: u32,
+    pub route_rate_limits: HashMap<String, u32>,
+    pub route_body_limits: HashMap<String, usize>,
     pub auth_route_requests_per_minute: u32,
     pub gateway_ingress_events_per_window: u32,
     pub gateway_ingress_window: Duration,
     pub gateway_outbound_queue: usize,
+    pub gateway_outbound_buffer_max_bytes: usize,
+    pub gateway_slow_consumer_max_strikes: u32,
+    pub gateway_max_connection_lifetime: Duration,
+    pub gateway_max_connections_per_user: usize,
+    pub gateway_subscription_revalidate_interval: Duration,
     pub max_gateway_event_bytes: usize,
     pub max_attachment_bytes: usize,
+    pub max_message_attachment_total_bytes: u64,
     pub max_profile_avatar_bytes: usize,
     pub max_profile_banner_bytes: usize,
     pub user_attachment_quota_bytes: u64,
     pub search_query_max_chars: usize,
     pub search_result_limit_max: usize,
     pub search_query_timeout: Duration,
+    pub search_query_timeout_max: Duration,
+    pub search_requests_per_minute: u32,
+    pub search_max_concurrent_queries: usize,
+    pub search_reconcile_batch_docs: usize,
+    pub search_writer_heap_bytes: usize,
+    /// When false no search index or worker thread is created, search routes
+    /// answer `404`, and message writes skip indexing.
+    pub search_enabled: bool,
+    /// Make message creates, edits, deletes and restores wait until the search
+    /// index has applied them. Off by default: writes queue the index update
+    /// and return, and `reconcile` repairs anything the queue lost.
+    pub search_sync_index_writes: bool,
     pub media_token_requests_per_minute: u32,
     pub media_publish_requests_per_minute: u32,
     pub directory_join_requests_per_minute_per_ip: u32,
@@ -131,17 +301,82 @@ pub struct AppConfig {
     pub guild_ip_ban_max_entries: usize,
     pub media_subscribe_token_cap_per_channel: usize,
     pub max_created_guilds_per_user: usize,
+    /// Give new guilds a `general` text channel unless the create request
+    /// says otherwise.
+    pub create_default_guild_channels: bool,
+    /// Also add a `voice` channel when default channels are created.
+    pub create_default_guild_voice_channel: bool,
+    pub max_friends_per_user: usize,
+    /// Pending friend requests a user may have waiting for them; further
+    /// requests to that user are rejected until some are resolved.
+    pub max_incoming_friend_requests_per_user: usize,
+    pub min_account_age_for_guild_create: Duration,
+    /// Minimum time between two guilds created by the same user. Zero (the
+    /// default) disables the check.
+    pub guild_create_cooldown: Duration,
+    /// How long after a refresh the previous refresh token may be presented
+    /// again without revoking the session. A retry inside the window gets a
+    /// fresh token pair and supersedes the pair it replaces. Zero (the
+    /// default) treats every reuse as a replay.
+    pub refresh_token_reuse_grace: Duration,
     pub trusted_proxy_cidrs: Vec<IpNetwork>,
     pub livekit_token_ttl: Duration,
+    pub deleted_message_retention: Duration,
+    /// Audit entries older than this are deleted by a background task. Zero
+    /// (the default) keeps them forever.
+    pub audit_log_retention: Duration,
+    /// Newest audit entries kept by the in-memory backend; older ones are
+    /// dropped as new ones arrive.
+    pub audit_log_memory_max_entries: usize,
+    /// Guilds the in-memory backend will hold; creates beyond it fail with
+    /// `capacity_reached`. Ignored when a database is configured.
+    pub in_memory_max_guilds: usize,
+    /// Messages, tombstones included, the in-memory backend keeps per channel.
+    pub in_memory_max_channel_messages: usize,
+    /// Pending friend requests each user may have sent on the in-memory
+    /// backend.
+    pub in_memory_max_friend_requests: usize,
+    /// Pending friend requests the in-memory backend will hold across all
+    /// users; creates beyond it fail with `capacity_reached`.
+    pub in_memory_max_total_friend_requests: usize,
+    /// Live messages a search rebuild may copy out of the in-memory backend;
+    /// larger rebuilds fail with `capacity_reached`.
+    pub in_memory_max_rebuild_docs: usize,
+    pub mime_sniff_bytes: usize,
+    pub history_default_limit: usize,
+    pub history_max_limit: usize,
     pub captcha_hcaptcha_site_key: Option<String>,
     pub captcha_hcaptcha_secret: Option<String>,
     pub captcha_verify_url: String,
+    /// Hosts `captcha_verify_url` may point at, compared case-insensitively.
+    /// Empty allows any host that passes the scheme check.
+    pub captcha_verify_allowed_hosts: Vec<String>,
     pub captcha_verify_timeout: Duration,
     pub livekit_url: String,
     pub livekit_api_key: Option<String>,
     pub livekit_api_secret: Option<String>,
     pub server_owner_user_id: Option<UserId>,
+    pub log_redact_pii: bool,
+    pub link_previews_enabled: bool,
+    pub message_content_compression: bool,
+    /// Base64url 32-byte key that seals new and edited message content at
+    /// rest. Rows sealed with it stay unreadable once it is removed.
+    pub message_content_encryption_key: Option<String>,
+    pub message_content_policy: MessageContentPolicy,
     pub attachment_root: PathBuf,
+    /// PEM certificate chain for serving HTTPS directly. Set together with
+    /// `tls_key_path`; when both are unset the binary serves plain HTTP.
+    pub tls_cert_path: Option<PathBuf>,
+    pub tls_key_path: Option<PathBuf>,
+    /// Shared secret for the `/admin/connections` endpoints, sent in the
+    /// `x-filament-admin-secret` header. The endpoints answer `404` when unset.
+    pub admin_api_secret: Option<String>,
+    /// Mount the `/echo` and `/slow` test routes. Off by default so
+    /// production deployments do not expose them.
+    pub enable_debug_routes: bool,
+    pub attachment_key_prefix: String,
+    pub attachment_key_shard_chars: usize,
+    pub attachment_verify_integrity: bool,
     pub database_url: Option<String>,
 }
 
@@ -151,18 +386,39 @@ impl Default for AppConfig {
             max_body_bytes: DEFAULT_JSON_BODY_LIMIT_BYTES,
             request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
             rate_limit_requests_per_minute: DEFAULT_RATE_LIMIT_REQUESTS_PER_MINUTE,
+            route_rate_limits: HashMap::new(),
+            route_body_limits: HashMap::new(),
             auth_route_requests_per_minute: DEFAULT_AUTH_ROUTE_REQUESTS_PER_MINUTE,
             gateway_ingress_events_per_window: DEFAULT_GATEWAY_INGRESS_EVENTS_PER_WINDOW,
             gateway_ingress_window: Duration::from_secs(DEFAULT_GATEWAY_INGRESS_WINDOW_SECS),
             gateway_outbound_queue: DEFAULT_GATEWAY_OUTBOUND_QUEUE,
+            gateway_outbound_buffer_max_bytes: DEFAULT_GATEWAY_OUTBOUND_BUFFER_MAX_BYTES,
+            gateway_slow_consumer_max_strikes: DEFAULT_GATEWAY_SLOW_CONSUMER_MAX_STRIKES,
+            gateway_max_connection_lifetime: Duration::from_secs(
+                DEFAULT_GATEWAY_MAX_CONNECTION_LIFETIME_SECS,
+            ),
+            gateway_max_connections_per_user: DEFAULT_GATEWAY_MAX_CONNECTIONS_PER_USER,
+            gateway_subscription_revalidate_interval: Duration::from_secs(
+                DEFAULT_GATEWAY_SUBSCRIPTION_REVALIDATE_SECS,
+            ),
             max_gateway_event_bytes: DEFAULT_MAX_GATEWAY_EVENT_BYTES,
             max_attachment_bytes: DEFAULT_MAX_ATTACHMENT_BYTES,
+            max_message_attachment_total_bytes: DEFAULT_MAX_MESSAGE_ATTACHMENT_TOTAL_BYTES,
             max_profile_avatar_bytes: DEFAULT_MAX_PROFILE_AVATAR_BYTES,
             max_profile_banner_bytes: DEFAULT_MAX_PROFILE_BANNER_BYTES,
             user_attachment_quota_bytes: DEFAULT_USER_ATTACHMENT_QUOTA_BYTES,
             search_query_max_chars: DEFAULT_SEARCH_QUERY_MAX_CHARS,
             search_result_limit_max: DEFAULT_SEARCH_RESULT_LIMIT_MAX,
             search_query_timeout: Duration::from_millis(DEFAULT_SEARCH_QUERY_TIMEOUT_MILLIS),
+            search_query_timeout_max: Duration::from_millis(
+                DEFAULT_SEARCH_QUERY_TIMEOUT_MAX_MILLIS,
+            ),
+            search_requests_per_minute: DEFAULT_SEARCH_REQUESTS_PER_MINUTE,
+            search_max_concurrent_queries: DEFAULT_SEARCH_MAX_CONCURRENT_QUERIES,
+            search_reconcile_batch_docs: DEFAULT_SEARCH_RECONCILE_BATCH_DOCS,
+            search_writer_heap_bytes: DEFAULT_SEARCH_WRITER_HEAP_BYTES,
+            search_enabled: true,
+            search_sync_index_writes: false,
             media_token_requests_per_minute: DEFAULT_MEDIA_TOKEN_REQUESTS_PER_MINUTE,
             media_publish_requests_per_minute: DEFAULT_MEDIA_PUBLISH_REQUESTS_PER_MINUTE,
             directory_join_requests_per_minute_per_ip:
@@ -173,23 +429,66 @@ impl Default for AppConfig {
             guild_ip_ban_max_entries: DEFAULT_GUILD_IP_BAN_MAX_ENTRIES,
             media_subscribe_token_cap_per_channel: DEFAULT_MEDIA_SUBSCRIBE_TOKEN_CAP_PER_CHANNEL,
             max_created_guilds_per_user: DEFAULT_MAX_CREATED_GUILDS_PER_USER,
+            create_default_guild_channels: false,
+            create_default_guild_voice_channel: false,
+            max_friends_per_user: DEFAULT_MAX_FRIENDS_PER_USER,
+            max_incoming_friend_requests_per_user: DEFAULT_MAX_INCOMING_FRIEND_REQUESTS_PER_USER,
+            min_account_age_for_guild_create: Duration::ZERO,
+            guild_create_cooldown: Duration::ZERO,
+            refresh_token_reuse_grace: Duration::ZERO,
             trusted_proxy_cidrs: Vec::new(),
             livekit_token_ttl: Duration::from_secs(DEFAULT_LIVEKIT_TOKEN_TTL_SECS),
+            deleted_message_retention: Duration::from_secs(DEFAULT_DELETED_MESSAGE_RETENTION_SECS),
+            audit_log_retention: Duration::ZERO,
+            audit_log_memory_max_entries: DEFAULT_AUDIT_LOG_MEMORY_MAX_ENTRIES,
+            in_memory_max_guilds: DEFAULT_IN_MEMORY_MAX_GUILDS,
+            in_memory_max_channel_messages: DEFAULT_IN_MEMORY_MAX_CHANNEL_MESSAGES,
+            in_memory_max_friend_requests: DEFAULT_IN_MEMORY_MAX_FRIEND_REQUESTS,
+            in_memory_max_total_friend_requests: DEFAULT_IN_MEMORY_MAX_TOTAL_FRIEND_REQUESTS,
+            in_memory_max_rebuild_docs: DEFAULT_IN_MEMORY_MAX_REBUILD_DOCS,
+            mime_sniff_bytes: DEFAULT_MIME_SNIFF_BYTES,
+            history_default_limit: DEFAULT_HISTORY_DEFAULT_LIMIT,
+            history_max_limit: DEFAULT_HISTORY_MAX_LIMIT,
             captcha_hcaptcha_site_key: None,
             captcha_hcaptcha_secret: None,
             captcha_verify_url: String::from("https://api.hcaptcha.com/siteverify"),
+            captcha_verify_allowed_hosts: Vec::new(),
             captcha_verify_timeout: Duration::from_secs(DEFAULT_CAPTCHA_VERIFY_TIMEOUT_SECS),
             livekit_url: String::from("ws://127.0.0.1:7880"),
             livekit_api_key: None,
             livekit_api_secret: None,
             server_owner_user_id: None,
+            log_redact_pii: false,
+            link_previews_enabled: false,
+            message_content_compression: false,
+            message_content_encryption_key: None,
+            message_content_policy: MessageContentPolicy::default(),
             attachment_root: PathBuf::from("./data/attachments"),
+            tls_cert_path: None,
+            tls_key_path: None,
+            admin_api_secret: None,
+            enable_debug_routes: false,
+            attachment_key_prefix: String::from(DEFAULT_ATTACHMENT_KEY_PREFIX),
+            attachment_key_shard_chars: DEFAULT_ATTACHMENT_KEY_SHARD_CHARS,
+            attachment_verify_integrity: false,
             database_url: None,
         }
     }
 }
 
+/// Opt-in checks applied to created and edited message content. Everything
+/// is off by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessageContentPolicy {
+    pub normalize_nfc: bool,
+    pub reject_invisible: bool,
+    /// Largest share of combining marks allowed, in percent; `0` disables
+    /// the check.
+    pub max_combining_mark_percent: u32,
+}
+
 #[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct RuntimeSecurityConfig {
     pub(crate) auth_route_requests_per_minute: u32,
     pub(crate) directory_join_requests_per_minute_per_ip: u32,
@@ -199,21 +498,56 @@ pub(crate) struct RuntimeSecurityConfig {
     pub(crate) gateway_ingress_events_per_window: u32,
     pub(crate) gateway_ingress_window: Duration,
     pub(crate) gateway_outbound_queue: usize,
+    pub(crate) gateway_slow_consumer_max_strikes: u32,
+    pub(crate) gateway_max_connection_lifetime: Duration,
+    pub(crate) gateway_max_connections_per_user: usize,
+    pub(crate) admin_api_secret: Option<String>,
+    pub(crate) gateway_subscription_revalidate_interval: Duration,
     pub(crate) max_gateway_event_bytes: usize,
     pub(crate) max_attachment_bytes: usize,
+    pub(crate) max_message_attachment_total_bytes: u64,
     pub(crate) max_profile_avatar_bytes: usize,
     pub(crate) max_profile_banner_bytes: usize,
     pub(crate) user_attachment_quota_bytes: u64,
+    pub(crate) attachment_key_prefix: String,
+    pub(crate) attachment_key_shard_chars: usize,
+    pub(crate) attachment_verify_integrity: bool,
     pub(crate) search_query_max_chars: usize,
     pub(crate) search_result_limit_max: usize,
     pub(crate) search_query_timeout: Duration,
+    pub(crate) search_query_timeout_max: Duration,
+    pub(crate) search_requests_per_minute: u32,
+    pub(crate) search_reconcile_batch_docs: usize,
+    pub(crate) search_sync_index_writes: bool,
     pub(crate) media_token_requests_per_minute: u32,
     pub(crate) media_publish_requests_per_minute: u32,
     pub(crate) media_subscribe_token_cap_per_channel: usize,
     pub(crate) max_created_guilds_per_user: usize,
+    pub(crate) create_default_guild_channels: bool,
+    pub(crate) create_default_guild_voice_channel: bool,
+    pub(crate) max_friends_per_user: usize,
+    pub(crate) max_incoming_friend_requests_per_user: usize,
+    pub(crate) min_account_age_for_guild_create: Duration,
+    pub(crate) guild_create_cooldown: Duration,
+    pub(crate) refresh_token_reuse_grace: Duration,
     pub(crate) trusted_proxy_cidrs: Arc<Vec<IpNetwork>>,
     pub(crate) server_owner_user_id: Option<UserId>,
+    pub(crate) log_redact_pii: bool,
+    pub(crate) link_previews_enabled: bool,
+    pub(crate) message_content_compression: bool,
+    pub(crate) message_content_policy: MessageContentPolicy,
     pub(crate) livekit_token_ttl: Duration,
+    pub(crate) deleted_message_retention: Duration,
+    pub(crate) audit_log_retention: Duration,
+    pub(crate) audit_log_memory_max_entries: usize,
+    pub(crate) in_memory_max_guilds: usize,
+    pub(crate) in_memory_max_channel_messages: usize,
+    pub(crate) in_memory_max_friend_requests: usize,
+    pub(crate) in_memory_max_total_friend_requests: usize,
+    pub(crate) in_memory_max_rebuild_docs: usize,
+    pub(crate) mime_sniff_bytes: usize,
+    pub(crate) history_default_limit: usize,
+    pub(crate) history_max_limit: usize,
     pub(crate) captcha: Option<Arc<CaptchaConfig>>,
 }
 
@@ -236,6 +570,7 @@ pub(crate) struct CaptchaConfig {
 pub(crate) struct SearchService {
     pub(crate) tx: mpsc::Sender<SearchCommand>,
     pub(crate) state: Arc<SearchIndexState>,
+    pub(crate) guild_directory: Arc<GuildDirectoryIndexState>,
 }
 
 #[derive(Clone)]
@@ -243,6 +578,7 @@ pub(crate) struct SearchIndexState {
     pub(crate) index: tantivy::Index,
     pub(crate) reader: tantivy::IndexReader,
     pub(crate) fields: SearchFields,
+    pub(crate) writer_heap_bytes: usize,
 }
 
 #[derive(Clone, Copy)]
@@ -255,6 +591,36 @@ pub(crate) struct SearchFields {
     pub(crate) content: Field,
 }
 
+/// Separate index of public guild names and descriptions for discovery, so
+/// guild documents never mix with message queries.
+pub(crate) struct GuildDirectoryIndexState {
+    pub(crate) index: tantivy::Index,
+    pub(crate) reader: tantivy::IndexReader,
+    pub(crate) fields: GuildDirectoryFields,
+    /// Tantivy allows one writer per index at a time.
+    pub(crate) write_lock: std::sync::Mutex<()>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct GuildDirectoryFields {
+    pub(crate) guild_id: Field,
+    pub(crate) name: Field,
+    pub(crate) description: Field,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct IndexedGuild {
+    pub(crate) guild_id: String,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CachedLinkPreview {
+    pub(crate) embed: EmbedResponse,
+    pub(crate) fetched_at_unix: i64,
+}
+
 #[derive(Clone)]
 pub(crate) struct IndexedMessage {
     pub(crate) message_id: String,
@@ -279,6 +645,13 @@ pub(crate) enum SearchOperation {
     },
 }
 
+/// Push delivery for users who were offline when a notification was written.
+/// Subscriptions are resolved by the worker, not at enqueue time.
+pub(crate) struct PushJob {
+    pub(crate) user_ids: Vec<UserId>,
+    pub(crate) payload: Vec<u8>,
+}
+
 pub(crate) struct SearchCommand {
     pub(crate) op: SearchOperation,
     pub(crate) ack: Option<oneshot::Sender<Result<(), AuthFailure>>>,
@@ -299,23 +672,54 @@ pub struct AppState {
     pub(crate) user_ip_observation_writes: Arc<RwLock<HashMap<String, i64>>>,
     pub(crate) media_token_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
     pub(crate) media_publish_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+    pub(crate) search_query_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+    pub(crate) channel_export_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+    pub(crate) user_export_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+    pub(crate) report_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+    /// Message create times per author over `MESSAGE_RATE_WINDOW_SECS`.
+    pub(crate) message_create_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+    /// Message create times per guild over `MESSAGE_RATE_WINDOW_SECS`.
+    pub(crate) message_create_guild_hits: Arc<RwLock<HashMap<String, Vec<i64>>>>,
+    pub(crate) message_idempotency: Arc<RwLock<MessageIdempotencyStore>>,
     pub(crate) media_subscribe_leases: Arc<RwLock<HashMap<String, Vec<i64>>>>,
     pub(crate) rate_limit_last_sweep_unix: Arc<AtomicI64>,
     pub(crate) auth_session_last_sweep_unix: Arc<AtomicI64>,
     pub(crate) membership_store: MembershipStore,
     pub(crate) user_ip_observations: Arc<RwLock<HashMap<(UserId, IpNetwork), i64>>>,
     pub(crate) guild_ip_bans: Arc<RwLock<GuildIpBanMap>>,
+    pub(crate) guild_mutes: Arc<RwLock<GuildMuteMap>>,
+    /// Sorted discovery tags per guild; guilds without tags have no entry.
+    pub(crate) guild_tags: Arc<RwLock<GuildTagMap>>,
     pub(crate) realtime_registry: RealtimeRegistry,
     pub(crate) attachment_store: Arc<LocalFileSystem>,
     pub(crate) attachments: Arc<RwLock<HashMap<String, AttachmentRecord>>>,
     pub(crate) friendship_requests: Arc<RwLock<HashMap<String, FriendshipRequestRecord>>>,
     pub(crate) friendships: Arc<RwLock<HashSet<(String, String)>>>,
-    pub(crate) audit_logs: Arc<RwLock<Vec<serde_json::Value>>>,
-    pub(crate) search: SearchService,
-    pub(crate) search_bootstrapped: Arc<OnceCell<()>>,
+    /// Oldest first, capped at `audit_log_memory_max_entries`.
+    pub(crate) audit_logs: Arc<RwLock<VecDeque<serde_json::Value>>>,
+    pub(crate) reports: Arc<RwLock<Vec<ReportRecord>>>,
+    pub(crate) notifications: Arc<RwLock<HashMap<UserId, VecDeque<NotificationRecord>>>>,
+    pub(crate) push_subscriptions: Arc<RwLock<HashMap<UserId, Vec<PushSubscriptionRecord>>>>,
+    /// `None` stores message content unsealed.
+    pub(crate) message_content_cipher: Option<Arc<MessageContentCipher>>,
+    /// Set once a push sender's delivery worker is running; `None` until one
+    /// is configured.
+    pub(crate) push_queue: Option<mpsc::Sender<PushJob>>,
+    /// `None` when search is disabled by config.
+    pub(crate) search: Option<SearchService>,
+    /// Guilds whose stored messages have been loaded into the in-memory
+    /// search index, each initialized on its first search.
+    pub(crate) search_bootstrapped_guilds: Arc<RwLock<HashMap<String, Arc<OnceCell<()>>>>>,
+    pub(crate) guild_directory_bootstrapped: Arc<OnceCell<()>>,
+    pub(crate) search_query_permits: Arc<Semaphore>,
+    pub(crate) gateway_outbound_budget: Arc<OutboundBufferBudget>,
+    pub(crate) link_previews: Arc<RwLock<HashMap<String, CachedLinkPreview>>>,
+    pub(crate) link_preview_fetch_permits: Arc<Semaphore>,
     pub(crate) runtime: Arc<RuntimeSecurityConfig>,
     pub(crate) livekit: Option<Arc<LiveKitConfig>>,
     pub(crate) livekit_room: Option<Arc<livekit_api::services::room::RoomClient>>,
+    /// Only reaches the localhost captcha verify URL allowed for tests; other
+    /// outbound calls go through `pinned_public_client`.
     pub(crate) http_client: Arc<reqwest::Client>,
 }
 
@@ -329,6 +733,7 @@ impl AppState {
         let dummy_password_hash = hash_password("filament-dummy-password")?;
         let livekit = build_livekit_config(config)?;
         let captcha = build_captcha_config(config)?;
+        let message_content_cipher = build_message_content_cipher(config)?;
         let db_pool = if let Some(database_url) = &config.database_url {
             Some(
                 PgPoolOptions::new()
@@ -344,7 +749,14 @@ impl AppState {
             .map_err(|e| anyhow!("attachment root init failed: {e}"))?;
         let attachment_store = LocalFileSystem::new_with_prefix(&config.attachment_root)
             .map_err(|e| anyhow!("attachment store init failed: {e}"))?;
-        let search = init_search_service().map_err(|e| anyhow!("search init failed: {e}"))?;
+        let search = if config.search_enabled {
+            Some(
+                init_search_service(config.search_writer_heap_bytes)
+                    .map_err(|e| anyhow!("search init failed: {e}"))?,
+            )
+        } else {
+            None
+        };
         let http_client = reqwest::Client::builder()
             .build()
             .map_err(|e| anyhow!("http client init failed: {e}"))?;
@@ -389,20 +801,43 @@ impl AppState {
             user_ip_observation_writes: Arc::new(RwLock::new(HashMap::new())),
             media_token_hits: Arc::new(RwLock::new(HashMap::new())),
             media_publish_hits: Arc::new(RwLock::new(HashMap::new())),
+            search_query_hits: Arc::new(RwLock::new(HashMap::new())),
+            channel_export_hits: Arc::new(RwLock::new(HashMap::new())),
+            user_export_hits: Arc::new(RwLock::new(HashMap::new())),
+            report_hits: Arc::new(RwLock::new(HashMap::new())),
+            message_create_hits: Arc::new(RwLock::new(HashMap::new())),
+            message_create_guild_hits: Arc::new(RwLock::new(HashMap::new())),
+            message_idempotency: Arc::new(RwLock::new(MessageIdempotencyStore::default())),
             media_subscribe_leases: Arc::new(RwLock::new(HashMap::new())),
             rate_limit_last_sweep_unix: Arc::new(AtomicI64::new(0)),
             auth_session_last_sweep_unix: Arc::new(AtomicI64::new(0)),
             membership_store,
             user_ip_observations: Arc::new(RwLock::new(HashMap::new())),
             guild_ip_bans: Arc::new(RwLock::new(HashMap::new())),
+            guild_mutes: Arc::new(RwLock::new(HashMap::new())),
+            guild_tags: Arc::new(RwLock::new(HashMap::new())),
             realtime_registry,
             attachment_store: Arc::new(attachment_store),
             attachments: Arc::new(RwLock::new(HashMap::new())),
             friendship_requests: Arc::new(RwLock::new(HashMap::new())),
             friendships: Arc::new(RwLock::new(HashSet::new())),
-            audit_logs: Arc::new(RwLock::new(Vec::new())),
+            audit_logs: Arc::new(RwLock::new(VecDeque::new())),
+            reports: Arc::new(RwLock::new(Vec::new())),
+            notifications: Arc::new(RwLock::new(HashMap::new())),
+            push_subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            message_content_cipher: message_content_cipher.map(Arc::new),
+            push_queue: None,
             search,
-            search_bootstrapped: Arc::new(OnceCell::new()),
+            search_bootstrapped_guilds: Arc::new(RwLock::new(HashMap::new())),
+            guild_directory_bootstrapped: Arc::new(OnceCell::new()),
+            search_query_permits: Arc::new(Semaphore::new(config.search_max_concurrent_queries)),
+            gateway_outbound_budget: Arc::new(OutboundBufferBudget::new(
+                config.gateway_outbound_buffer_max_bytes,
+            )),
+            link_previews: Arc::new(RwLock::new(HashMap::new())),
+            link_preview_fetch_permits: Arc::new(Semaphore::new(
+                MAX_LINK_PREVIEW_CONCURRENT_FETCHES,
+            )),
             runtime: Arc::new(RuntimeSecurityConfig {
                 auth_route_requests_per_minute: config.auth_route_requests_per_minute,
                 directory_join_requests_per_minute_per_ip: config
@@ -414,21 +849,60 @@ impl AppState {
                 gateway_ingress_events_per_window: config.gateway_ingress_events_per_window,
                 gateway_ingress_window: config.gateway_ingress_window,
                 gateway_outbound_queue: config.gateway_outbound_queue,
+                gateway_slow_consumer_max_strikes: config.gateway_slow_consumer_max_strikes,
+                gateway_max_connection_lifetime: config.gateway_max_connection_lifetime,
+                gateway_max_connections_per_user: config.gateway_max_connections_per_user,
+                admin_api_secret: config
+                    .admin_api_secret
+                    .as_deref()
+                    .map(|secret| secret.trim().to_owned()),
+                gateway_subscription_revalidate_interval: config
+                    .gateway_subscription_revalidate_interval,
                 max_gateway_event_bytes: config.max_gateway_event_bytes,
                 max_attachment_bytes: config.max_attachment_bytes,
+                max_message_attachment_total_bytes: config.max_message_attachment_total_bytes,
                 max_profile_avatar_bytes: config.max_profile_avatar_bytes,
                 max_profile_banner_bytes: config.max_profile_banner_bytes,
                 user_attachment_quota_bytes: config.user_attachment_quota_bytes,
+                attachment_key_prefix: config.attachment_key_prefix.clone(),
+                attachment_key_shard_chars: config.attachment_key_shard_chars,
+                attachment_verify_integrity: config.attachment_verify_integrity,
                 search_query_max_chars: config.search_query_max_chars,
                 search_result_limit_max: config.search_result_limit_max,
                 search_query_timeout: config.search_query_timeout,
+                search_query_timeout_max: config.search_query_timeout_max,
+                search_requests_per_minute: config.search_requests_per_minute,
+                search_reconcile_batch_docs: config.search_reconcile_batch_docs,
+                search_sync_index_writes: config.search_sync_index_writes,
                 media_token_requests_per_minute: config.media_token_requests_per_minute,
                 media_publish_requests_per_minute: config.media_publish_requests_per_minute,
                 media_subscribe_token_cap_per_channel: config.media_subscribe_token_cap_per_channel,
                 max_created_guilds_per_user: config.max_created_guilds_per_user,
+                create_default_guild_channels: config.create_default_guild_channels,
+                create_default_guild_voice_channel: config.create_default_guild_voice_channel,
+                max_friends_per_user: config.max_friends_per_user,
+                max_incoming_friend_requests_per_user: config.max_incoming_friend_requests_per_user,
+                min_account_age_for_guild_create: config.min_account_age_for_guild_create,
+                guild_create_cooldown: config.guild_create_cooldown,
+                refresh_token_reuse_grace: config.refresh_token_reuse_grace,
                 trusted_proxy_cidrs: Arc::new(config.trusted_proxy_cidrs.clone()),
                 server_owner_user_id: config.server_owner_user_id,
+                log_redact_pii: config.log_redact_pii,
+                link_previews_enabled: config.link_previews_enabled,
+                message_content_compression: config.message_content_compression,
+                message_content_policy: config.message_content_policy,
                 livekit_token_ttl: config.livekit_token_ttl,
+                deleted_message_retention: config.deleted_message_retention,
+                audit_log_retention: config.audit_log_retention,
+                audit_log_memory_max_entries: config.audit_log_memory_max_entries,
+                in_memory_max_guilds: config.in_memory_max_guilds,
+                in_memory_max_channel_messages: config.in_memory_max_channel_messages,
+                in_memory_max_friend_requests: config.in_memory_max_friend_requests,
+                in_memory_max_total_friend_requests: config.in_memory_max_total_friend_requests,
+                in_memory_max_rebuild_docs: config.in_memory_max_rebuild_docs,
+                mime_sniff_bytes: config.mime_sniff_bytes,
+                history_default_limit: config.history_default_limit,
+                history_max_limit: config.history_max_limit,
                 captcha: captcha.map(Arc::new),
             }),
             livekit: livekit.clone().map(Arc::new),
@@ -491,10 +965,12 @@ pub(crate) struct RealtimeRegistry {
     subscriptions: Arc<RwLock<Subscriptions>>,
     guild_connections: Arc<RwLock<GuildConnectionIndex>>,
     user_connections: Arc<RwLock<UserConnectionIndex>>,
-    connection_senders: Arc<RwLock<HashMap<Uuid, mpsc::Sender<String>>>>,
+    connection_senders: Arc<RwLock<HashMap<Uuid, OutboundSender>>>,
     connection_controls: Arc<RwLock<HashMap<Uuid, watch::Sender<ConnectionControl>>>>,
     connection_presence: Arc<RwLock<HashMap<Uuid, ConnectionPresence>>>,
     voice_participants: Arc<RwLock<VoiceParticipantsByChannel>>,
+    slow_consumer_strikes: Arc<RwLock<HashMap<Uuid, u32>>>,
+    connection_event_filters: Arc<RwLock<ConnectionEventFilters>>,
 }
 
 impl RealtimeRegistry {
@@ -502,7 +978,7 @@ impl RealtimeRegistry {
         subscriptions: Arc<RwLock<Subscriptions>>,
         guild_connections: Arc<RwLock<GuildConnectionIndex>>,
         user_connections: Arc<RwLock<UserConnectionIndex>>,
-        connection_senders: Arc<RwLock<HashMap<Uuid, mpsc::Sender<String>>>>,
+        connection_senders: Arc<RwLock<HashMap<Uuid, OutboundSender>>>,
         connection_controls: Arc<RwLock<HashMap<Uuid, watch::Sender<ConnectionControl>>>>,
         connection_presence: Arc<RwLock<HashMap<Uuid, ConnectionPresence>>>,
         voice_participants: Arc<RwLock<VoiceParticipantsByChannel>>,
@@ -515,6 +991,8 @@ impl RealtimeRegistry {
             connection_controls,
             connection_presence,
             voice_participants,
+            slow_consumer_strikes: Arc::new(RwLock::new(HashMap::new())),
+            connection_event_filters: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -530,7 +1008,7 @@ impl RealtimeRegistry {
         &self.user_connections
     }
 
-    pub(crate) fn connection_senders(&self) -> &Arc<RwLock<HashMap<Uuid, mpsc::Sender<String>>>> {
+    pub(crate) fn connection_senders(&self) -> &Arc<RwLock<HashMap<Uuid, OutboundSender>>> {
         &self.connection_senders
     }
 
@@ -547,6 +1025,14 @@ impl RealtimeRegistry {
     pub(crate) fn voice_participants(&self) -> &Arc<RwLock<VoiceParticipantsByChannel>> {
         &self.voice_participants
     }
+
+    pub(crate) fn slow_consumer_strikes(&self) -> &Arc<RwLock<HashMap<Uuid, u32>>> {
+        &self.slow_consumer_strikes
+    }
+
+    pub(crate) fn connection_event_filters(&self) -> &Arc<RwLock<ConnectionEventFilters>> {
+        &self.connection_event_filters
+    }
 }
 
 #[derive(Clone, Default)]
@@ -559,6 +1045,9 @@ pub(crate) struct SessionStore {
 struct UsedRefreshTokenRecord {
     session_id: String,
     used_at_unix: i64,
+    /// Hash of the refresh token this one was rotated into, or `None` when a
+    /// grace retry rotated it out and it must never be retried itself.
+    successor_hash: Option<[u8; 32]>,
 }
 
 impl SessionStore {
@@ -585,6 +1074,38 @@ impl SessionStore {
         replayed_session_id
     }
 
+    /// Returns the session id, user and current refresh hash when
+    /// `token_hash` was rotated out at most `grace_secs` ago and the session
+    /// has not rotated again since, so presenting it is a retry rather than a
+    /// replay. The successor pair cannot be returned because only its hash is
+    /// kept; the caller rotates from it to a new pair in the same session.
+    pub(crate) async fn grace_retry_for_token(
+        &self,
+        token_hash: [u8; 32],
+        now_unix: i64,
+        grace_secs: i64,
+    ) -> Option<(String, UserId, [u8; 32])> {
+        let record = self
+            .used_refresh_tokens
+            .read()
+            .await
+            .get(&token_hash)
+            .cloned()?;
+        let successor_hash = record.successor_hash?;
+        if record.used_at_unix < now_unix.saturating_sub(grace_secs) {
+            return None;
+        }
+        let sessions = self.sessions.read().await;
+        let session = sessions.get(&record.session_id)?;
+        if session.revoked
+            || session.expires_at_unix < now_unix
+            || session.refresh_token_hash != successor_hash
+        {
+            return None;
+        }
+        Some((record.session_id, session.user_id, successor_hash))
+    }
+
     pub(crate) async fn rotate_refresh_hash(
         &self,
         session_id: &str,
@@ -592,6 +1113,7 @@ impl SessionStore {
         next_hash: [u8; 32],
         now_unix: i64,
         next_expires_at_unix: i64,
+        grace_retry: bool,
     ) -> Result<UserId, ()> {
         let previous_hash = {
             let mut sessions = self.sessions.write().await;
@@ -613,6 +1135,7 @@ impl SessionStore {
             UsedRefreshTokenRecord {
                 session_id: session_id.to_owned(),
                 used_at_unix: now_unix,
+                successor_hash: (!grace_retry).then_some(next_hash),
             },
         );
         Ok(previous_hash.0)
@@ -690,6 +1213,7 @@ pub(crate) struct UserRecord {
     pub(crate) password_hash: String,
     pub(crate) failed_logins: u8,
     pub(crate) locked_until_unix: Option<i64>,
+    pub(crate) created_at_unix: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -729,6 +1253,13 @@ pub(crate) struct GuildRecord {
     pub(crate) visibility: GuildVisibility,
     pub(crate) created_by_user_id: UserId,
     pub(crate) default_join_role_id: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) icon_attachment_id: Option<String>,
+    pub(crate) search_query_timeout_ms: Option<u64>,
+    /// Channel that receives server-posted messages such as welcomes.
+    pub(crate) system_channel_id: Option<String>,
+    /// Whether joins post a welcome message to `system_channel_id`.
+    pub(crate) welcome_messages: bool,
     pub(crate) members: HashMap<UserId, Role>,
     pub(crate) banned_members: HashSet<UserId>,
     pub(crate) channels: HashMap<String, ChannelRecord>,
@@ -738,6 +1269,15 @@ pub(crate) struct GuildRecord {
 pub(crate) struct ChannelRecord {
     pub(crate) name: String,
     pub(crate) kind: ChannelKind,
+    /// Locked channels only accept posts from members who can moderate them.
+    pub(crate) locked: bool,
+    /// Messages older than this many seconds are pruned; `None` keeps history.
+    pub(crate) retention_secs: Option<u64>,
+    /// Voting channels score messages by their up/down vote reactions.
+    pub(crate) voting: bool,
+    /// The category-kind channel this channel is grouped under, if any.
+    pub(crate) category_id: Option<String>,
+    /// Kept in ascending message id order so history cursors can bisect.
     pub(crate) messages: Vec<MessageRecord>,
     pub(crate) role_overrides: HashMap<Role, ChannelPermissionOverwrite>,
 }
@@ -770,6 +1310,56 @@ pub(crate) struct GuildIpBanRecord {
     pub(crate) expires_at_unix: Option<i64>,
 }
 
+/// A temporary mute. Muted members keep read access but cannot post or
+/// react until `expires_at_unix`.
+#[derive(Debug, Clone)]
+pub(crate) struct GuildMuteRecord {
+    pub(crate) muted_by_user_id: UserId,
+    pub(crate) created_at_unix: i64,
+    pub(crate) expires_at_unix: i64,
+}
+
+/// A member's report of another member or one of their messages. Message
+/// reports keep a copy of the content as it read when reported.
+#[derive(Debug, Clone)]
+pub(crate) struct ReportRecord {
+    pub(crate) report_id: String,
+    pub(crate) guild_id: String,
+    pub(crate) reporter_user_id: UserId,
+    pub(crate) target_user_id: UserId,
+    pub(crate) channel_id: Option<String>,
+    pub(crate) message_id: Option<String>,
+    pub(crate) message_content: Option<String>,
+    pub(crate) reason: String,
+    pub(crate) created_at_unix: i64,
+}
+
+/// An inbox entry for a mention that targeted `user_id`, kept whether or not
+/// the user was connected when it was sent.
+#[derive(Debug, Clone)]
+pub(crate) struct NotificationRecord {
+    pub(crate) notification_id: String,
+    pub(crate) guild_id: String,
+    pub(crate) channel_id: String,
+    pub(crate) message_id: String,
+    pub(crate) author_id: UserId,
+    pub(crate) scope: String,
+    pub(crate) created_at_unix: i64,
+    pub(crate) read_at_unix: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PushSubscriptionRecord {
+    pub(crate) subscription_id: String,
+    pub(crate) user_id: UserId,
+    pub(crate) endpoint: String,
+    /// Base64url client public key and auth secret from the browser's
+    /// `PushSubscription`, used to encrypt payloads per RFC 8291.
+    pub(crate) p256dh: String,
+    pub(crate) auth: String,
+    pub(crate) created_at_unix: i64,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct MessageRecord {
     pub(crate) id: String,
@@ -779,6 +1369,29 @@ pub(crate) struct MessageRecord {
     pub(crate) attachment_ids: Vec<String>,
     pub(crate) created_at_unix: i64,
     pub(crate) reactions: HashMap<String, HashSet<UserId>>,
+    pub(crate) deleted_at_unix: Option<i64>,
+    /// Last edit time; never decreases, so it doubles as the edit version.
+    pub(crate) edited_at_unix: Option<i64>,
+    /// Posted by the server on the author's behalf; never editable.
+    pub(crate) system: bool,
+}
+
+/// A message create keyed by the author's idempotency key.
+#[derive(Debug, Clone)]
+pub(crate) struct MessageIdempotencyRecord {
+    pub(crate) guild_id: String,
+    pub(crate) channel_id: String,
+    pub(crate) created_at_unix: i64,
+    /// `None` while the first request is still creating the message.
+    pub(crate) response: Option<MessageResponse>,
+}
+
+/// Idempotency records grouped by author, so one user filling their share
+/// only evicts their own keys. `total` tracks the records across all users.
+#[derive(Debug, Default)]
+pub(crate) struct MessageIdempotencyStore {
+    pub(crate) by_user: HashMap<UserId, HashMap<String, MessageIdempotencyRecord>>,
+    pub(crate) total: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -793,6 +1406,8 @@ pub(crate) struct AttachmentRecord {
     pub(crate) sha256_hex: String,
     pub(crate) object_key: String,
     pub(crate) message_id: Option<String>,
+    /// Index in the message's `attachment_ids` as the author listed them.
+    pub(crate) message_position: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -811,7 +1426,10 @@ pub(crate) struct AuthContext {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ConnectionControl {
     Open,
+    /// Closes a slow consumer.
     Close,
+    /// Closes the connection on an operator's request.
+    AdminClose,
 }
 
 #[derive(Debug, Clone)]
@@ -867,18 +1485,65 @@ mod tests {
             )
             .await;
         let _ = store
-            .rotate_refresh_hash(&session_id, initial_hash, replay_hash, 0, i64::MAX)
+            .rotate_refresh_hash(&session_id, initial_hash, replay_hash, 0, i64::MAX, false)
             .await
             .expect("rotation should succeed");
 
         let replay = store.revoke_if_replayed_token(initial_hash).await;
         assert_eq!(replay.as_deref(), Some(session_id.as_str()));
         let second_rotate = store
-            .rotate_refresh_hash(&session_id, replay_hash, [2_u8; 32], 0, i64::MAX)
+            .rotate_refresh_hash(&session_id, replay_hash, [2_u8; 32], 0, i64::MAX, false)
             .await;
         assert!(second_rotate.is_err());
     }
 
+    #[tokio::test]
+    async fn session_store_grace_retry_only_accepts_the_latest_rotation_inside_window() {
+        let store = SessionStore::new();
+        let user_id = UserId::new();
+        let session_id = String::from("session-grace");
+        store
+            .insert(
+                session_id.clone(),
+                SessionRecord {
+                    user_id,
+                    refresh_token_hash: [1_u8; 32],
+                    expires_at_unix: i64::MAX,
+                    revoked: false,
+                },
+            )
+            .await;
+        store
+            .rotate_refresh_hash(&session_id, [1_u8; 32], [2_u8; 32], 100, i64::MAX, false)
+            .await
+            .expect("rotation should succeed");
+
+        let retry = store.grace_retry_for_token([1_u8; 32], 110, 10).await;
+        assert_eq!(retry, Some((session_id.clone(), user_id, [2_u8; 32])));
+        assert!(store
+            .grace_retry_for_token([1_u8; 32], 111, 10)
+            .await
+            .is_none());
+
+        store
+            .rotate_refresh_hash(&session_id, [2_u8; 32], [3_u8; 32], 105, i64::MAX, false)
+            .await
+            .expect("rotation should succeed");
+        assert!(store
+            .grace_retry_for_token([1_u8; 32], 105, 10)
+            .await
+            .is_none());
+
+        store
+            .rotate_refresh_hash(&session_id, [3_u8; 32], [4_u8; 32], 106, i64::MAX, true)
+            .await
+            .expect("grace rotation should succeed");
+        assert!(store
+            .grace_retry_for_token([3_u8; 32], 106, 10)
+            .await
+            .is_none());
+    }
+
     #[tokio::test]
     async fn session_store_revoke_with_token_rejects_hash_mismatch() {
         let store = SessionStore::new();
@@ -926,7 +1591,7 @@ mod tests {
         assert_eq!(validated, user_id);
 
         let rotated = store
-            .rotate_refresh_hash(&session_id, initial_hash, rotated_hash, 50, 200)
+            .rotate_refresh_hash(&session_id, initial_hash, rotated_hash, 50, 200, false)
             .await
             .expect("rotation should succeed");
         assert_eq!(rotated, user_id);
@@ -974,6 +1639,7 @@ mod tests {
                 [11_u8; 32],
                 950,
                 2_000,
+                false,
             )
             .await
             .expect("active rotation should succeed");
@@ -984,6 +1650,7 @@ mod tests {
                 [21_u8; 32],
                 800,
                 900,
+                false,
             )
             .await
             .expect("expired-session rotation should succeed before expiry");
@@ -1018,7 +1685,7 @@ mod tests {
             .await;
 
         store
-            .rotate_refresh_hash(&session_id, initial_hash, [31_u8; 32], 100, 5_000)
+            .rotate_refresh_hash(&session_id, initial_hash, [31_u8; 32], 100, 5_000, false)
             .await
             .expect("rotation should succeed");
 
@@ -1048,7 +1715,7 @@ mod tests {
             .await;
 
         store
-            .rotate_refresh_hash(&session_id, initial_hash, [41_u8; 32], 100, 10_000)
+            .rotate_refresh_hash(&session_id, initial_hash, [41_u8; 32], 100, 10_000, false)
             .await
             .expect("rotation should succeed");
 
@@ -1080,6 +1747,11 @@ mod tests {
                 visibility: GuildVisibility::Private,
                 created_by_user_id: UserId::new(),
                 default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
                 members: HashMap::new(),
                 banned_members: HashSet::new(),
                 channels: HashMap::new(),
@@ -4,9 +4,31 @@ use filament_core::{ChannelKind, Permission, PermissionSet, Role};
 
 use self::migrations::v10_role_color_schema::apply_role_color_schema;
 use self::migrations::v11_profile_banner_schema::apply_profile_banner_schema;
+use self::migrations::v12_link_preview_schema::apply_link_preview_schema;
+use self::migrations::v13_message_author_index_schema::apply_message_author_index_schema;
+use self::migrations::v14_message_tombstone_schema::apply_message_tombstone_schema;
+use self::migrations::v15_guild_search_timeout_schema::apply_guild_search_timeout_schema;
+use self::migrations::v16_report_schema::apply_report_schema;
+use self::migrations::v17_guild_mute_schema::apply_guild_mute_schema;
+use self::migrations::v18_channel_lock_schema::apply_channel_lock_schema;
+use self::migrations::v19_attachment_position_schema::apply_attachment_position_schema;
 use self::migrations::v1_hierarchical_permissions::backfill_hierarchical_permission_schema;
 pub(crate) use self::migrations::v1_hierarchical_permissions::seed_hierarchical_permissions_for_new_guild;
+use self::migrations::v20_guild_profile_schema::apply_guild_profile_schema;
+use self::migrations::v21_user_created_at_schema::apply_user_created_at_schema;
+use self::migrations::v22_message_content_compression_schema::apply_message_content_compression_schema;
+use self::migrations::v23_notification_schema::apply_notification_schema;
+use self::migrations::v24_push_subscription_schema::apply_push_subscription_schema;
+use self::migrations::v25_channel_retention_schema::apply_channel_retention_schema;
+use self::migrations::v26_message_edit_version_schema::apply_message_edit_version_schema;
+use self::migrations::v27_channel_voting_schema::apply_channel_voting_schema;
+use self::migrations::v28_channel_category_schema::apply_channel_category_schema;
+use self::migrations::v29_guild_system_channel_schema::apply_guild_system_channel_schema;
 use self::migrations::v2_attachment_schema::apply_attachment_schema;
+use self::migrations::v30_refresh_token_successor_schema::apply_refresh_token_successor_schema;
+use self::migrations::v31_message_content_sealed_schema::apply_message_content_sealed_schema;
+use self::migrations::v32_guild_tag_schema::apply_guild_tag_schema;
+use self::migrations::v33_audit_log_retention_schema::apply_audit_log_retention_schema;
 use self::migrations::v3_social_graph_schema::apply_social_graph_schema;
 use self::migrations::v4_moderation_audit_schema::apply_moderation_audit_schema;
 use self::migrations::v5_identity_schema::apply_identity_schema;
@@ -50,6 +72,28 @@ pub(crate) async fn ensure_db_schema(state: &AppState) -> Result<(), AuthFailure
             apply_default_join_role_schema(&mut tx).await?;
             apply_role_color_schema(&mut tx).await?;
             apply_profile_banner_schema(&mut tx).await?;
+            apply_link_preview_schema(&mut tx).await?;
+            apply_message_author_index_schema(&mut tx).await?;
+            apply_message_tombstone_schema(&mut tx).await?;
+            apply_guild_search_timeout_schema(&mut tx).await?;
+            apply_report_schema(&mut tx).await?;
+            apply_guild_mute_schema(&mut tx).await?;
+            apply_channel_lock_schema(&mut tx).await?;
+            apply_attachment_position_schema(&mut tx).await?;
+            apply_guild_profile_schema(&mut tx).await?;
+            apply_user_created_at_schema(&mut tx).await?;
+            apply_message_content_compression_schema(&mut tx).await?;
+            apply_notification_schema(&mut tx).await?;
+            apply_push_subscription_schema(&mut tx).await?;
+            apply_channel_retention_schema(&mut tx).await?;
+            apply_message_edit_version_schema(&mut tx).await?;
+            apply_channel_voting_schema(&mut tx).await?;
+            apply_channel_category_schema(&mut tx).await?;
+            apply_guild_system_channel_schema(&mut tx).await?;
+            apply_refresh_token_successor_schema(&mut tx).await?;
+            apply_message_content_sealed_schema(&mut tx).await?;
+            apply_guild_tag_schema(&mut tx).await?;
+            apply_audit_log_retention_schema(&mut tx).await?;
 
             tx.commit().await?;
 
@@ -100,6 +144,7 @@ pub(crate) fn channel_kind_to_i16(kind: ChannelKind) -> i16 {
     match kind {
         ChannelKind::Text => 0,
         ChannelKind::Voice => 1,
+        ChannelKind::Category => 2,
     }
 }
 
@@ -107,6 +152,7 @@ pub(crate) fn channel_kind_from_i16(value: i16) -> Option<ChannelKind> {
     match value {
         0 => Some(ChannelKind::Text),
         1 => Some(ChannelKind::Voice),
+        2 => Some(ChannelKind::Category),
         _ => None,
     }
 }
@@ -124,7 +170,7 @@ pub(crate) fn permission_set_from_list(values: &[Permission]) -> PermissionSet {
 }
 
 pub(crate) fn permission_list_from_set(value: PermissionSet) -> Vec<Permission> {
-    const ORDERED_PERMISSIONS: [Permission; 12] = [
+    const ORDERED_PERMISSIONS: [Permission; 13] = [
         Permission::ManageRoles,
         Permission::ManageMemberRoles,
         Permission::ManageWorkspaceRoles,
@@ -137,6 +183,7 @@ pub(crate) fn permission_list_from_set(value: PermissionSet) -> Vec<Permission>
         Permission::PublishVideo,
         Permission::PublishScreenShare,
         Permission::SubscribeStreams,
+        Permission::MentionEveryone,
     ];
 
     ORDERED_PERMISSIONS
@@ -1,7 +1,29 @@
 pub(crate) mod v10_role_color_schema;
 pub(crate) mod v11_profile_banner_schema;
+pub(crate) mod v12_link_preview_schema;
+pub(crate) mod v13_message_author_index_schema;
+pub(crate) mod v14_message_tombstone_schema;
+pub(crate) mod v15_guild_search_timeout_schema;
+pub(crate) mod v16_report_schema;
+pub(crate) mod v17_guild_mute_schema;
+pub(crate) mod v18_channel_lock_schema;
+pub(crate) mod v19_attachment_position_schema;
 pub(crate) mod v1_hierarchical_permissions;
+pub(crate) mod v20_guild_profile_schema;
+pub(crate) mod v21_user_created_at_schema;
+pub(crate) mod v22_message_content_compression_schema;
+pub(crate) mod v23_notification_schema;
+pub(crate) mod v24_push_subscription_schema;
+pub(crate) mod v25_channel_retention_schema;
+pub(crate) mod v26_message_edit_version_schema;
+pub(crate) mod v27_channel_voting_schema;
+pub(crate) mod v28_channel_category_schema;
+pub(crate) mod v29_guild_system_channel_schema;
 pub(crate) mod v2_attachment_schema;
+pub(crate) mod v30_refresh_token_successor_schema;
+pub(crate) mod v31_message_content_sealed_schema;
+pub(crate) mod v32_guild_tag_schema;
+pub(crate) mod v33_audit_log_retention_schema;
 pub(crate) mod v3_social_graph_schema;
 pub(crate) mod v4_moderation_audit_schema;
 pub(crate) mod v5_identity_schema;
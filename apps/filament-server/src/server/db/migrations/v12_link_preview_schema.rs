@@ -0,0 +1,39 @@
+use sqlx::{Postgres, Transaction};
+
+const CREATE_LINK_PREVIEWS_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS link_previews (
+                    url TEXT PRIMARY KEY,
+                    title TEXT,
+                    description TEXT,
+                    image_url TEXT,
+                    fetched_at_unix BIGINT NOT NULL
+                )";
+const CREATE_LINK_PREVIEWS_FETCHED_AT_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_link_previews_fetched_at_unix
+                    ON link_previews(fetched_at_unix)";
+
+pub(crate) async fn apply_link_preview_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_LINK_PREVIEWS_TABLE_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(CREATE_LINK_PREVIEWS_FETCHED_AT_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CREATE_LINK_PREVIEWS_FETCHED_AT_INDEX_SQL, CREATE_LINK_PREVIEWS_TABLE_SQL};
+
+    #[test]
+    fn link_preview_schema_defines_url_keyed_cache_table() {
+        assert!(CREATE_LINK_PREVIEWS_TABLE_SQL.contains("CREATE TABLE IF NOT EXISTS link_previews"));
+        assert!(CREATE_LINK_PREVIEWS_TABLE_SQL.contains("url TEXT PRIMARY KEY"));
+        assert!(CREATE_LINK_PREVIEWS_TABLE_SQL.contains("fetched_at_unix BIGINT NOT NULL"));
+        assert!(
+            CREATE_LINK_PREVIEWS_FETCHED_AT_INDEX_SQL.contains("ON link_previews(fetched_at_unix)")
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use sqlx::{Postgres, Transaction};
+
+const CREATE_MESSAGES_AUTHOR_MESSAGE_ID_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_messages_author_message_id
+                    ON messages(author_id, message_id)";
+
+pub(crate) async fn apply_message_author_index_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_MESSAGES_AUTHOR_MESSAGE_ID_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CREATE_MESSAGES_AUTHOR_MESSAGE_ID_INDEX_SQL;
+
+    #[test]
+    fn message_author_index_covers_author_keyed_pagination() {
+        assert!(
+            CREATE_MESSAGES_AUTHOR_MESSAGE_ID_INDEX_SQL.contains("idx_messages_author_message_id")
+        );
+        assert!(
+            CREATE_MESSAGES_AUTHOR_MESSAGE_ID_INDEX_SQL.contains("messages(author_id, message_id)")
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_MESSAGES_DELETED_AT_UNIX_SQL: &str =
+    "ALTER TABLE messages ADD COLUMN IF NOT EXISTS deleted_at_unix BIGINT";
+const CREATE_MESSAGES_DELETED_AT_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_messages_deleted_at_unix
+                    ON messages(deleted_at_unix)
+                    WHERE deleted_at_unix IS NOT NULL";
+
+pub(crate) async fn apply_message_tombstone_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_MESSAGES_DELETED_AT_UNIX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(CREATE_MESSAGES_DELETED_AT_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ADD_MESSAGES_DELETED_AT_UNIX_SQL, CREATE_MESSAGES_DELETED_AT_INDEX_SQL};
+
+    #[test]
+    fn message_tombstone_schema_adds_nullable_column_and_partial_index() {
+        assert!(ADD_MESSAGES_DELETED_AT_UNIX_SQL.contains("deleted_at_unix BIGINT"));
+        assert!(!ADD_MESSAGES_DELETED_AT_UNIX_SQL.contains("NOT NULL"));
+        assert!(CREATE_MESSAGES_DELETED_AT_INDEX_SQL.contains("idx_messages_deleted_at_unix"));
+        assert!(CREATE_MESSAGES_DELETED_AT_INDEX_SQL.contains("WHERE deleted_at_unix IS NOT NULL"));
+    }
+}
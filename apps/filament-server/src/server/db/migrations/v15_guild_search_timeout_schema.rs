@@ -0,0 +1,25 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_GUILD_SEARCH_QUERY_TIMEOUT_COLUMN_SQL: &str = "ALTER TABLE guilds
+                 ADD COLUMN IF NOT EXISTS search_query_timeout_ms BIGINT";
+
+pub(crate) async fn apply_guild_search_timeout_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_GUILD_SEARCH_QUERY_TIMEOUT_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_GUILD_SEARCH_QUERY_TIMEOUT_COLUMN_SQL;
+
+    #[test]
+    fn guild_search_timeout_schema_adds_nullable_column() {
+        assert!(ADD_GUILD_SEARCH_QUERY_TIMEOUT_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS search_query_timeout_ms BIGINT"));
+        assert!(!ADD_GUILD_SEARCH_QUERY_TIMEOUT_COLUMN_SQL.contains("NOT NULL"));
+    }
+}
@@ -0,0 +1,41 @@
+use sqlx::{Postgres, Transaction};
+
+const CREATE_REPORTS_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS reports (
+                    report_id TEXT PRIMARY KEY,
+                    guild_id TEXT NOT NULL REFERENCES guilds(guild_id) ON DELETE CASCADE,
+                    reporter_user_id TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                    target_user_id TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                    channel_id TEXT NULL,
+                    message_id TEXT NULL,
+                    message_content TEXT NULL,
+                    reason TEXT NOT NULL,
+                    created_at_unix BIGINT NOT NULL
+                )";
+const CREATE_REPORTS_GUILD_INDEX_SQL: &str = "CREATE INDEX IF NOT EXISTS idx_reports_guild_report
+                    ON reports(guild_id, report_id DESC)";
+
+pub(crate) async fn apply_report_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_REPORTS_TABLE_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(CREATE_REPORTS_GUILD_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CREATE_REPORTS_GUILD_INDEX_SQL, CREATE_REPORTS_TABLE_SQL};
+
+    #[test]
+    fn report_schema_scopes_reports_to_guilds() {
+        assert!(CREATE_REPORTS_TABLE_SQL.contains("CREATE TABLE IF NOT EXISTS reports"));
+        assert!(CREATE_REPORTS_TABLE_SQL
+            .contains("guild_id TEXT NOT NULL REFERENCES guilds(guild_id) ON DELETE CASCADE"));
+        assert!(CREATE_REPORTS_TABLE_SQL.contains("message_id TEXT NULL"));
+        assert!(CREATE_REPORTS_GUILD_INDEX_SQL.contains("ON reports(guild_id, report_id DESC)"));
+    }
+}
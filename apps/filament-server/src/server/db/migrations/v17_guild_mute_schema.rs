@@ -0,0 +1,38 @@
+use sqlx::{Postgres, Transaction};
+
+const CREATE_GUILD_MUTES_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS guild_mutes (
+                    guild_id TEXT NOT NULL REFERENCES guilds(guild_id) ON DELETE CASCADE,
+                    user_id TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                    muted_by_user_id TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                    created_at_unix BIGINT NOT NULL,
+                    expires_at_unix BIGINT NOT NULL,
+                    PRIMARY KEY(guild_id, user_id)
+                )";
+const CREATE_GUILD_MUTES_EXPIRY_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_guild_mutes_expires
+                    ON guild_mutes(expires_at_unix)";
+
+pub(crate) async fn apply_guild_mute_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_GUILD_MUTES_TABLE_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(CREATE_GUILD_MUTES_EXPIRY_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CREATE_GUILD_MUTES_EXPIRY_INDEX_SQL, CREATE_GUILD_MUTES_TABLE_SQL};
+
+    #[test]
+    fn guild_mute_schema_keeps_one_mute_per_member() {
+        assert!(CREATE_GUILD_MUTES_TABLE_SQL.contains("CREATE TABLE IF NOT EXISTS guild_mutes"));
+        assert!(CREATE_GUILD_MUTES_TABLE_SQL.contains("PRIMARY KEY(guild_id, user_id)"));
+        assert!(CREATE_GUILD_MUTES_TABLE_SQL.contains("expires_at_unix BIGINT NOT NULL"));
+        assert!(CREATE_GUILD_MUTES_EXPIRY_INDEX_SQL.contains("ON guild_mutes(expires_at_unix)"));
+    }
+}
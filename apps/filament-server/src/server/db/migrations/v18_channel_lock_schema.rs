@@ -0,0 +1,24 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_CHANNEL_LOCKED_COLUMN_SQL: &str = "ALTER TABLE channels
+                 ADD COLUMN IF NOT EXISTS locked BOOLEAN NOT NULL DEFAULT FALSE";
+
+pub(crate) async fn apply_channel_lock_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_CHANNEL_LOCKED_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_CHANNEL_LOCKED_COLUMN_SQL;
+
+    #[test]
+    fn channel_lock_schema_defaults_existing_channels_to_unlocked() {
+        assert!(ADD_CHANNEL_LOCKED_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS locked BOOLEAN NOT NULL DEFAULT FALSE"));
+    }
+}
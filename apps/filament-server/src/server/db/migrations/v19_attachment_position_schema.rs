@@ -0,0 +1,25 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_ATTACHMENT_MESSAGE_POSITION_COLUMN_SQL: &str = "ALTER TABLE attachments
+                 ADD COLUMN IF NOT EXISTS message_position INTEGER";
+
+pub(crate) async fn apply_attachment_position_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_ATTACHMENT_MESSAGE_POSITION_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_ATTACHMENT_MESSAGE_POSITION_COLUMN_SQL;
+
+    #[test]
+    fn attachment_position_schema_leaves_existing_rows_unpositioned() {
+        assert!(ADD_ATTACHMENT_MESSAGE_POSITION_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS message_position INTEGER"));
+        assert!(!ADD_ATTACHMENT_MESSAGE_POSITION_COLUMN_SQL.contains("NOT NULL"));
+    }
+}
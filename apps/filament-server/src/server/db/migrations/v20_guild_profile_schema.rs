@@ -0,0 +1,26 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_GUILD_PROFILE_COLUMNS_SQL: &str = "ALTER TABLE guilds
+                 ADD COLUMN IF NOT EXISTS description TEXT,
+                 ADD COLUMN IF NOT EXISTS icon_attachment_id TEXT";
+
+pub(crate) async fn apply_guild_profile_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_GUILD_PROFILE_COLUMNS_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_GUILD_PROFILE_COLUMNS_SQL;
+
+    #[test]
+    fn guild_profile_schema_adds_nullable_columns() {
+        assert!(ADD_GUILD_PROFILE_COLUMNS_SQL.contains("ADD COLUMN IF NOT EXISTS description TEXT"));
+        assert!(ADD_GUILD_PROFILE_COLUMNS_SQL
+            .contains("ADD COLUMN IF NOT EXISTS icon_attachment_id TEXT"));
+    }
+}
@@ -0,0 +1,26 @@
+use sqlx::{Postgres, Transaction};
+
+// Accounts that predate this column are backfilled with `0`, so they always
+// satisfy account-age gates.
+const ADD_USER_CREATED_AT_COLUMN_SQL: &str = "ALTER TABLE users
+                 ADD COLUMN IF NOT EXISTS created_at_unix BIGINT NOT NULL DEFAULT 0";
+
+pub(crate) async fn apply_user_created_at_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_USER_CREATED_AT_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_USER_CREATED_AT_COLUMN_SQL;
+
+    #[test]
+    fn user_created_at_schema_backfills_existing_accounts() {
+        assert!(ADD_USER_CREATED_AT_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS created_at_unix BIGINT NOT NULL DEFAULT 0"));
+    }
+}
@@ -0,0 +1,27 @@
+use sqlx::{Postgres, Transaction};
+
+// `NULL` means `content` holds the plaintext, which covers every row written
+// before compression existed or while it is disabled.
+const ADD_MESSAGE_CONTENT_ZSTD_COLUMN_SQL: &str = "ALTER TABLE messages
+                 ADD COLUMN IF NOT EXISTS content_zstd BYTEA";
+
+pub(crate) async fn apply_message_content_compression_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_MESSAGE_CONTENT_ZSTD_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_MESSAGE_CONTENT_ZSTD_COLUMN_SQL;
+
+    #[test]
+    fn message_content_compression_schema_keeps_legacy_rows_readable() {
+        assert!(ADD_MESSAGE_CONTENT_ZSTD_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS content_zstd BYTEA"));
+        assert!(!ADD_MESSAGE_CONTENT_ZSTD_COLUMN_SQL.contains("NOT NULL"));
+    }
+}
@@ -0,0 +1,43 @@
+use sqlx::{Postgres, Transaction};
+
+const CREATE_NOTIFICATIONS_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS notifications (
+                    notification_id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                    guild_id TEXT NOT NULL REFERENCES guilds(guild_id) ON DELETE CASCADE,
+                    channel_id TEXT NOT NULL,
+                    message_id TEXT NOT NULL,
+                    author_id TEXT NOT NULL,
+                    scope TEXT NOT NULL,
+                    created_at_unix BIGINT NOT NULL,
+                    read_at_unix BIGINT NULL,
+                    UNIQUE(user_id, message_id)
+                )";
+const CREATE_NOTIFICATIONS_USER_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_notifications_user_notification
+                    ON notifications(user_id, notification_id DESC)";
+
+pub(crate) async fn apply_notification_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_NOTIFICATIONS_TABLE_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(CREATE_NOTIFICATIONS_USER_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CREATE_NOTIFICATIONS_TABLE_SQL, CREATE_NOTIFICATIONS_USER_INDEX_SQL};
+
+    #[test]
+    fn notification_schema_keeps_one_entry_per_user_message() {
+        assert!(CREATE_NOTIFICATIONS_TABLE_SQL.contains("CREATE TABLE IF NOT EXISTS notifications"));
+        assert!(CREATE_NOTIFICATIONS_TABLE_SQL.contains("UNIQUE(user_id, message_id)"));
+        assert!(CREATE_NOTIFICATIONS_TABLE_SQL.contains("read_at_unix BIGINT NULL"));
+        assert!(CREATE_NOTIFICATIONS_USER_INDEX_SQL
+            .contains("ON notifications(user_id, notification_id DESC)"));
+    }
+}
@@ -0,0 +1,39 @@
+use sqlx::{Postgres, Transaction};
+
+const CREATE_PUSH_SUBSCRIPTIONS_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS push_subscriptions (
+                    subscription_id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                    endpoint TEXT NOT NULL UNIQUE,
+                    p256dh TEXT NOT NULL,
+                    auth TEXT NOT NULL,
+                    created_at_unix BIGINT NOT NULL
+                )";
+const CREATE_PUSH_SUBSCRIPTIONS_USER_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_push_subscriptions_user
+                    ON push_subscriptions(user_id)";
+
+pub(crate) async fn apply_push_subscription_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_PUSH_SUBSCRIPTIONS_TABLE_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(CREATE_PUSH_SUBSCRIPTIONS_USER_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CREATE_PUSH_SUBSCRIPTIONS_TABLE_SQL, CREATE_PUSH_SUBSCRIPTIONS_USER_INDEX_SQL};
+
+    #[test]
+    fn push_subscription_schema_keeps_endpoints_unique() {
+        assert!(CREATE_PUSH_SUBSCRIPTIONS_TABLE_SQL
+            .contains("CREATE TABLE IF NOT EXISTS push_subscriptions"));
+        assert!(CREATE_PUSH_SUBSCRIPTIONS_TABLE_SQL.contains("endpoint TEXT NOT NULL UNIQUE"));
+        assert!(CREATE_PUSH_SUBSCRIPTIONS_TABLE_SQL.contains("ON DELETE CASCADE"));
+        assert!(CREATE_PUSH_SUBSCRIPTIONS_USER_INDEX_SQL.contains("ON push_subscriptions(user_id)"));
+    }
+}
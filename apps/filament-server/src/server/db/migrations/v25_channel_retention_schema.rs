@@ -0,0 +1,25 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_CHANNEL_RETENTION_COLUMN_SQL: &str = "ALTER TABLE channels
+                 ADD COLUMN IF NOT EXISTS retention_secs BIGINT";
+
+pub(crate) async fn apply_channel_retention_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_CHANNEL_RETENTION_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_CHANNEL_RETENTION_COLUMN_SQL;
+
+    #[test]
+    fn channel_retention_schema_keeps_existing_channels_unbounded() {
+        assert!(ADD_CHANNEL_RETENTION_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS retention_secs BIGINT"));
+        assert!(!ADD_CHANNEL_RETENTION_COLUMN_SQL.contains("NOT NULL"));
+    }
+}
@@ -0,0 +1,25 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_MESSAGE_EDITED_AT_COLUMN_SQL: &str = "ALTER TABLE messages
+                 ADD COLUMN IF NOT EXISTS edited_at_unix BIGINT";
+
+pub(crate) async fn apply_message_edit_version_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_MESSAGE_EDITED_AT_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_MESSAGE_EDITED_AT_COLUMN_SQL;
+
+    #[test]
+    fn message_edit_version_schema_leaves_existing_messages_unedited() {
+        assert!(ADD_MESSAGE_EDITED_AT_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS edited_at_unix BIGINT"));
+        assert!(!ADD_MESSAGE_EDITED_AT_COLUMN_SQL.contains("DEFAULT"));
+    }
+}
@@ -0,0 +1,24 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_CHANNEL_VOTING_COLUMN_SQL: &str = "ALTER TABLE channels
+                 ADD COLUMN IF NOT EXISTS voting BOOLEAN NOT NULL DEFAULT FALSE";
+
+pub(crate) async fn apply_channel_voting_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_CHANNEL_VOTING_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_CHANNEL_VOTING_COLUMN_SQL;
+
+    #[test]
+    fn channel_voting_schema_leaves_existing_channels_in_normal_mode() {
+        assert!(ADD_CHANNEL_VOTING_COLUMN_SQL.contains("ADD COLUMN IF NOT EXISTS voting BOOLEAN"));
+        assert!(ADD_CHANNEL_VOTING_COLUMN_SQL.contains("DEFAULT FALSE"));
+    }
+}
@@ -0,0 +1,26 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_CHANNEL_CATEGORY_COLUMN_SQL: &str = "ALTER TABLE channels
+                 ADD COLUMN IF NOT EXISTS category_id TEXT NULL
+                 REFERENCES channels(channel_id) ON DELETE SET NULL";
+
+pub(crate) async fn apply_channel_category_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_CHANNEL_CATEGORY_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_CHANNEL_CATEGORY_COLUMN_SQL;
+
+    #[test]
+    fn channel_category_schema_leaves_existing_channels_uncategorized() {
+        assert!(ADD_CHANNEL_CATEGORY_COLUMN_SQL.contains("ADD COLUMN IF NOT EXISTS category_id"));
+        assert!(ADD_CHANNEL_CATEGORY_COLUMN_SQL.contains("NULL"));
+        assert!(ADD_CHANNEL_CATEGORY_COLUMN_SQL.contains("ON DELETE SET NULL"));
+    }
+}
@@ -0,0 +1,36 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_GUILD_SYSTEM_CHANNEL_COLUMNS_SQL: &str = "ALTER TABLE guilds
+                 ADD COLUMN IF NOT EXISTS system_channel_id TEXT NULL
+                     REFERENCES channels(channel_id) ON DELETE SET NULL,
+                 ADD COLUMN IF NOT EXISTS welcome_messages BOOLEAN NOT NULL DEFAULT FALSE";
+const ADD_MESSAGE_SYSTEM_COLUMN_SQL: &str = "ALTER TABLE messages
+                 ADD COLUMN IF NOT EXISTS system BOOLEAN NOT NULL DEFAULT FALSE";
+
+pub(crate) async fn apply_guild_system_channel_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_GUILD_SYSTEM_CHANNEL_COLUMNS_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(ADD_MESSAGE_SYSTEM_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ADD_GUILD_SYSTEM_CHANNEL_COLUMNS_SQL, ADD_MESSAGE_SYSTEM_COLUMN_SQL};
+
+    #[test]
+    fn guild_system_channel_schema_starts_with_welcomes_off() {
+        assert!(ADD_GUILD_SYSTEM_CHANNEL_COLUMNS_SQL
+            .contains("ADD COLUMN IF NOT EXISTS system_channel_id TEXT NULL"));
+        assert!(ADD_GUILD_SYSTEM_CHANNEL_COLUMNS_SQL.contains("ON DELETE SET NULL"));
+        assert!(ADD_GUILD_SYSTEM_CHANNEL_COLUMNS_SQL
+            .contains("welcome_messages BOOLEAN NOT NULL DEFAULT FALSE"));
+        assert!(ADD_MESSAGE_SYSTEM_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS system BOOLEAN NOT NULL DEFAULT FALSE"));
+    }
+}
@@ -0,0 +1,24 @@
+use sqlx::{Postgres, Transaction};
+
+const ADD_USED_REFRESH_TOKEN_SUCCESSOR_COLUMN_SQL: &str = "ALTER TABLE used_refresh_tokens
+                 ADD COLUMN IF NOT EXISTS successor_hash BYTEA NULL";
+
+pub(crate) async fn apply_refresh_token_successor_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_USED_REFRESH_TOKEN_SUCCESSOR_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_USED_REFRESH_TOKEN_SUCCESSOR_COLUMN_SQL;
+
+    #[test]
+    fn refresh_token_successor_column_is_optional_for_existing_rows() {
+        assert!(ADD_USED_REFRESH_TOKEN_SUCCESSOR_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS successor_hash BYTEA NULL"));
+    }
+}
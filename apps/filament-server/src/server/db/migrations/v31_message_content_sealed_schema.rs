@@ -0,0 +1,26 @@
+use sqlx::{Postgres, Transaction};
+
+// `NULL` means the text lives in `content` / `content_zstd`, which covers every
+// row written before encryption existed or while it is disabled.
+const ADD_MESSAGE_CONTENT_SEALED_COLUMN_SQL: &str = "ALTER TABLE messages
+                 ADD COLUMN IF NOT EXISTS content_sealed BYTEA NULL";
+
+pub(crate) async fn apply_message_content_sealed_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(ADD_MESSAGE_CONTENT_SEALED_COLUMN_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_MESSAGE_CONTENT_SEALED_COLUMN_SQL;
+
+    #[test]
+    fn message_content_sealed_column_is_optional_for_existing_rows() {
+        assert!(ADD_MESSAGE_CONTENT_SEALED_COLUMN_SQL
+            .contains("ADD COLUMN IF NOT EXISTS content_sealed BYTEA NULL"));
+    }
+}
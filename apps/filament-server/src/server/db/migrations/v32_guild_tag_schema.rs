@@ -0,0 +1,34 @@
+use sqlx::{Postgres, Transaction};
+
+const CREATE_GUILD_TAGS_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS guild_tags (
+                    guild_id TEXT NOT NULL REFERENCES guilds(guild_id) ON DELETE CASCADE,
+                    tag TEXT NOT NULL,
+                    PRIMARY KEY(guild_id, tag)
+                )";
+const CREATE_GUILD_TAGS_TAG_INDEX_SQL: &str = "CREATE INDEX IF NOT EXISTS idx_guild_tags_tag
+                    ON guild_tags(tag)";
+
+pub(crate) async fn apply_guild_tag_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_GUILD_TAGS_TABLE_SQL)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(CREATE_GUILD_TAGS_TAG_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CREATE_GUILD_TAGS_TABLE_SQL, CREATE_GUILD_TAGS_TAG_INDEX_SQL};
+
+    #[test]
+    fn guild_tag_schema_keeps_each_tag_once_per_guild() {
+        assert!(CREATE_GUILD_TAGS_TABLE_SQL.contains("CREATE TABLE IF NOT EXISTS guild_tags"));
+        assert!(CREATE_GUILD_TAGS_TABLE_SQL.contains("PRIMARY KEY(guild_id, tag)"));
+        assert!(CREATE_GUILD_TAGS_TABLE_SQL.contains("ON DELETE CASCADE"));
+        assert!(CREATE_GUILD_TAGS_TAG_INDEX_SQL.contains("ON guild_tags(tag)"));
+    }
+}
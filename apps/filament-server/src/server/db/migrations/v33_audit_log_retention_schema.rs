@@ -0,0 +1,26 @@
+use sqlx::{Postgres, Transaction};
+
+// Retention purges scan by age across every guild, including entries with no
+// guild, which the per-guild indexes cannot serve.
+const CREATE_AUDIT_LOGS_CREATED_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_audit_logs_created
+                    ON audit_logs(created_at_unix)";
+
+pub(crate) async fn apply_audit_log_retention_schema(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(CREATE_AUDIT_LOGS_CREATED_INDEX_SQL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CREATE_AUDIT_LOGS_CREATED_INDEX_SQL;
+
+    #[test]
+    fn audit_log_retention_index_covers_creation_time() {
+        assert!(CREATE_AUDIT_LOGS_CREATED_INDEX_SQL.contains("ON audit_logs(created_at_unix)"));
+    }
+}
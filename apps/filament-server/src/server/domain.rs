@@ -1,36 +1,78 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use filament_core::{ChannelPermissionOverwrite, Permission, PermissionSet, Role, UserId};
+use filament_core::{
+    ChannelKind, ChannelPermissionOverwrite, Permission, PermissionSet, Role, UserId,
+};
 use sqlx::{PgPool, Row};
 use ulid::Ulid;
 
 mod attachments;
+mod audit_retention;
+mod link_previews;
+mod mentions;
+mod message_content;
+mod message_export;
+mod message_retention;
 mod moderation;
+mod notifications;
+mod outbound_http;
 mod permissions_eval;
+mod push;
 mod reactions;
 
 pub(crate) use attachments::{
-    attach_message_media, attachment_responses_from_db_rows, parse_attachment_ids,
+    attach_message_media, attachment_object_key, attachment_responses_from_db_rows,
+    ensure_attachment_extension, parse_attachment_ids, resolve_attachment_type,
     validate_attachment_filename,
 };
-pub(crate) use moderation::{enforce_guild_ip_ban_for_request, guild_has_active_ip_ban_for_client};
+pub(crate) use audit_retention::start_audit_log_purge;
+pub(crate) use link_previews::{
+    attach_message_embeds, spawn_link_preview_fetch, start_link_preview_prune,
+};
+pub(crate) use mentions::{guild_mention_scope, MentionScope};
+pub(crate) use message_content::{
+    build_message_content_cipher, decode_message_content, encode_message_content,
+    message_content_from_row, MessageContentCipher, StoredMessageContent,
+};
+pub(crate) use message_export::{
+    channel_export_page, export_page_ndjson, user_export_chunk, UserExportCursor,
+};
+pub(crate) use message_retention::{
+    delete_attachments_in_memory, hard_delete_message_db, start_channel_retention_prune,
+    start_deleted_message_purge,
+};
+pub(crate) use moderation::{
+    enforce_guild_ip_ban_for_request, enforce_guild_mute, guild_has_active_ip_ban_for_client,
+    start_guild_mute_purge,
+};
+pub(crate) use notifications::{
+    list_user_notifications, mark_user_notifications_read, record_mention_notifications,
+};
+pub(crate) use outbound_http::{pinned_public_client, OutboundTargetError};
 pub(crate) use permissions_eval::{
-    ensure_required_roles, normalize_assigned_role_ids, resolve_db_channel_permissions,
-    resolve_guild_permission_summary, resolve_in_memory_channel_permissions, role_ids_from_map,
+    ensure_required_roles, i64_to_masked_permissions, normalize_assigned_role_ids,
+    resolve_db_channel_permissions, resolve_guild_permission_summary,
+    resolve_in_memory_channel_permissions, resolve_role_channel_permissions, role_ids_from_map,
     role_records_from_db_rows, summarize_in_memory_guild_permissions,
     sync_legacy_channel_overrides, sync_legacy_role_assignments,
 };
+pub(crate) use push::{
+    delete_push_subscription, enqueue_offline_push, push_subscription_keys_valid,
+    register_push_subscription, validate_push_endpoint,
+};
 pub(crate) use reactions::{
-    attach_message_reactions, reaction_summaries_from_users, validate_reaction_emoji,
+    attach_message_reactions, attach_vote_scores, reaction_summaries_from_users,
+    validate_reaction_emoji,
 };
 
 use super::{
     auth::now_unix,
     core::{AppState, AttachmentRecord, ChannelPermissionOverrideRecord},
-    db::role_from_i16,
+    db::{channel_kind_from_i16, role_from_i16},
     errors::AuthFailure,
+    metrics::{record_audit_log_pruned, record_audit_log_written},
     permissions::{all_permissions, default_everyone_permissions},
-    types::{AttachmentPath, AttachmentResponse, ReactionResponse},
+    types::{AttachmentPath, AttachmentResponse, FriendRecordResponse, ReactionResponse},
 };
 
 pub(crate) async fn user_can_write_channel(
@@ -63,7 +105,7 @@ pub(crate) async fn check_channel_permission(
 const OVERRIDE_TARGET_ROLE: i16 = 0;
 const OVERRIDE_TARGET_MEMBER: i16 = 1;
 
-fn is_server_owner(state: &AppState, user_id: UserId) -> bool {
+pub(crate) fn is_server_owner(state: &AppState, user_id: UserId) -> bool {
     state
         .runtime
         .server_owner_user_id
@@ -197,7 +239,19 @@ async fn resolve_channel_permissions_db(
     .fetch_optional(pool)
     .await
     .map_err(|_| AuthFailure::Internal)?;
-    let membership_row = membership_row.ok_or(AuthFailure::Forbidden)?;
+    let Some(membership_row) = membership_row else {
+        let guild_exists = sqlx::query("SELECT 1 FROM guilds WHERE guild_id = $1")
+            .bind(guild_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|_| AuthFailure::Internal)?
+            .is_some();
+        return Err(if guild_exists {
+            AuthFailure::NotGuildMember
+        } else {
+            AuthFailure::NotFound
+        });
+    };
     let legacy_role = role_from_i16(
         membership_row
             .try_get::<i16, _>("role")
@@ -414,13 +468,13 @@ async fn resolve_channel_permissions_in_memory(
     }
 
     if guild.banned_members.contains(&user_id) {
-        return Err(AuthFailure::Forbidden);
+        return Err(AuthFailure::NotGuildMember);
     }
     let legacy_role = guild
         .members
         .get(&user_id)
         .copied()
-        .ok_or(AuthFailure::Forbidden)?;
+        .ok_or(AuthFailure::NotGuildMember)?;
     if let Some(channel_id) = channel_id {
         if !guild.channels.contains_key(channel_id) {
             return Err(AuthFailure::NotFound);
@@ -471,6 +525,67 @@ async fn resolve_channel_permissions_in_memory(
     Ok((resolved_role, permissions))
 }
 
+/// Resolves the caller's role and channel permissions. A missing guild or
+/// channel is `NotFound`; a guild the caller is not a member of (or is banned
+/// from) is `NotGuildMember`. Both backends draw the same line, and both
+/// variants render as `404 not_found`, so responses never reveal whether a
+/// guild exists. Handlers answer `Forbidden` only when a visible resource lacks
+/// the permission they need.
+/// Whether the channel is locked to members who can moderate it.
+pub(crate) async fn channel_is_locked(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+) -> Result<bool, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let row = sqlx::query(
+            "SELECT locked
+             FROM channels
+             WHERE guild_id = $1 AND channel_id = $2",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        return row.try_get("locked").map_err(|_| AuthFailure::Internal);
+    }
+
+    let guilds = state.membership_store.guilds().read().await;
+    guilds
+        .get(guild_id)
+        .and_then(|guild| guild.channels.get(channel_id))
+        .map(|channel| channel.locked)
+        .ok_or(AuthFailure::NotFound)
+}
+
+pub(crate) async fn channel_kind(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+) -> Result<ChannelKind, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let kind = sqlx::query_scalar::<_, i16>(
+            "SELECT kind FROM channels WHERE guild_id = $1 AND channel_id = $2",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        return channel_kind_from_i16(kind).ok_or(AuthFailure::Internal);
+    }
+
+    let guilds = state.membership_store.guilds().read().await;
+    guilds
+        .get(guild_id)
+        .and_then(|guild| guild.channels.get(channel_id))
+        .map(|channel| channel.kind)
+        .ok_or(AuthFailure::NotFound)
+}
+
 pub(crate) async fn channel_permission_snapshot(
     state: &AppState,
     user_id: UserId,
@@ -484,6 +599,32 @@ pub(crate) async fn channel_permission_snapshot(
     resolve_channel_permissions_in_memory(state, user_id, guild_id, Some(channel_id)).await
 }
 
+/// Keeps the users in `user_ids` who may read `channel_id`, using the same
+/// `CreateMessage` rule as channel history. Users who left the guild or lost
+/// the channel are dropped; other lookup failures are returned.
+pub(crate) async fn channel_readers(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+    user_ids: impl IntoIterator<Item = UserId>,
+) -> Result<HashSet<UserId>, AuthFailure> {
+    let mut readers = HashSet::new();
+    for user_id in user_ids {
+        match channel_permission_snapshot(state, user_id, guild_id, channel_id).await {
+            Ok((_, permissions)) => {
+                if permissions.contains(Permission::CreateMessage) {
+                    readers.insert(user_id);
+                }
+            }
+            Err(AuthFailure::NotGuildMember | AuthFailure::NotFound | AuthFailure::Forbidden) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(readers)
+}
+
+/// Guild-level counterpart of [`channel_permission_snapshot`] with the same
+/// `NotFound` / `NotGuildMember` contract.
 pub(crate) async fn guild_permission_snapshot(
     state: &AppState,
     user_id: UserId,
@@ -555,6 +696,14 @@ pub(crate) async fn find_attachment(
     attachments::find_attachment(state, path).await
 }
 
+pub(crate) async fn find_guild_attachment(
+    state: &AppState,
+    guild_id: &str,
+    attachment_id: &str,
+) -> Result<AttachmentRecord, AuthFailure> {
+    attachments::find_guild_attachment(state, guild_id, attachment_id).await
+}
+
 pub(crate) async fn bind_message_attachments_db(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     attachment_ids: &[String],
@@ -562,37 +711,110 @@ pub(crate) async fn bind_message_attachments_db(
     guild_id: &str,
     channel_id: &str,
     owner_id: UserId,
+    max_total_bytes: u64,
 ) -> Result<(), AuthFailure> {
     if attachment_ids.is_empty() {
         return Ok(());
     }
 
-    let update_result = sqlx::query(
+    let rows = sqlx::query(
         "UPDATE attachments
-         SET message_id = $1
+         SET message_id = $1,
+             message_position = array_position($2::text[], attachment_id) - 1
          WHERE attachment_id = ANY($2::text[])
            AND guild_id = $3
            AND channel_id = $4
            AND owner_id = $5
-           AND message_id IS NULL",
+           AND message_id IS NULL
+         RETURNING size_bytes",
     )
     .bind(message_id)
     .bind(attachment_ids)
     .bind(guild_id)
     .bind(channel_id)
     .bind(owner_id.to_string())
-    .execute(&mut **tx)
+    .fetch_all(&mut **tx)
     .await
     .map_err(|_| AuthFailure::Internal)?;
 
-    let updated =
-        usize::try_from(update_result.rows_affected()).map_err(|_| AuthFailure::Internal)?;
-    if updated != attachment_ids.len() {
-        return Err(AuthFailure::InvalidRequest);
+    if rows.len() != attachment_ids.len() {
+        return Err(classify_attachment_bind_failure_db(
+            tx,
+            attachment_ids,
+            message_id,
+            guild_id,
+            channel_id,
+            owner_id,
+        )
+        .await);
+    }
+    let mut total_bytes: u64 = 0;
+    for row in rows {
+        let size_bytes: i64 = row
+            .try_get("size_bytes")
+            .map_err(|_| AuthFailure::Internal)?;
+        let size_bytes = u64::try_from(size_bytes).map_err(|_| AuthFailure::Internal)?;
+        total_bytes = total_bytes.saturating_add(size_bytes);
+    }
+    // The caller's transaction is dropped on error, which rolls back the bind.
+    if total_bytes > max_total_bytes {
+        return Err(AuthFailure::MessageAttachmentsTooLarge);
     }
     Ok(())
 }
 
+/// Explains why the bind above matched fewer rows than requested, using the
+/// same rules and precedence as `bind_message_attachments_in_memory`. Rows
+/// the failed statement already bound to `message_id` are skipped.
+async fn classify_attachment_bind_failure_db(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    attachment_ids: &[String],
+    message_id: &str,
+    guild_id: &str,
+    channel_id: &str,
+    owner_id: UserId,
+) -> AuthFailure {
+    let Ok(rows) = sqlx::query(
+        "SELECT attachment_id, guild_id, channel_id, owner_id, message_id
+         FROM attachments
+         WHERE attachment_id = ANY($1::text[])",
+    )
+    .bind(attachment_ids)
+    .fetch_all(&mut **tx)
+    .await
+    else {
+        return AuthFailure::Internal;
+    };
+
+    let owner_id = owner_id.to_string();
+    for attachment_id in attachment_ids {
+        let Some(row) = rows.iter().find(|row| {
+            row.try_get::<String, _>("attachment_id")
+                .is_ok_and(|id| &id == attachment_id)
+        }) else {
+            return AuthFailure::AttachmentNotFound;
+        };
+        let (Ok(row_guild_id), Ok(row_channel_id), Ok(row_owner_id), Ok(row_message_id)) = (
+            row.try_get::<String, _>("guild_id"),
+            row.try_get::<String, _>("channel_id"),
+            row.try_get::<String, _>("owner_id"),
+            row.try_get::<Option<String>, _>("message_id"),
+        ) else {
+            return AuthFailure::Internal;
+        };
+        if row_guild_id != guild_id || row_owner_id != owner_id {
+            return AuthFailure::AttachmentNotFound;
+        }
+        if row_channel_id != channel_id {
+            return AuthFailure::AttachmentWrongChannel;
+        }
+        if row_message_id.is_some_and(|bound| bound != message_id) {
+            return AuthFailure::AttachmentAlreadyUsed;
+        }
+    }
+    AuthFailure::InvalidRequest
+}
+
 pub(crate) async fn fetch_attachments_for_message_db(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     guild_id: &str,
@@ -603,7 +825,7 @@ pub(crate) async fn fetch_attachments_for_message_db(
         "SELECT attachment_id, guild_id, channel_id, owner_id, filename, mime_type, size_bytes, sha256_hex
          FROM attachments
          WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3
-         ORDER BY created_at_unix ASC, attachment_id ASC",
+         ORDER BY message_position ASC NULLS LAST, created_at_unix ASC, attachment_id ASC",
     )
     .bind(guild_id)
     .bind(channel_id)
@@ -680,6 +902,112 @@ pub(crate) async fn reaction_map_for_messages_db(
         .await
 }
 
+/// Rejects accounts younger than `min_account_age_for_guild_create`. Accounts
+/// created before creation times were recorded count as old enough.
+pub(crate) async fn enforce_min_account_age(
+    state: &AppState,
+    user_id: UserId,
+) -> Result<(), AuthFailure> {
+    let min_age = state.runtime.min_account_age_for_guild_create;
+    if min_age.is_zero() {
+        return Ok(());
+    }
+
+    let created_at_unix = if let Some(pool) = &state.db_pool {
+        let row = sqlx::query("SELECT created_at_unix FROM users WHERE user_id = $1")
+            .bind(user_id.to_string())
+            .fetch_optional(pool)
+            .await
+            .map_err(|_| AuthFailure::Internal)?
+            .ok_or(AuthFailure::Unauthorized)?;
+        row.try_get::<i64, _>("created_at_unix")
+            .map_err(|_| AuthFailure::Internal)?
+    } else {
+        let username = state
+            .user_ids
+            .read()
+            .await
+            .get(&user_id.to_string())
+            .cloned()
+            .ok_or(AuthFailure::Unauthorized)?;
+        state
+            .users
+            .read()
+            .await
+            .get(&username)
+            .map(|user| user.created_at_unix)
+            .ok_or(AuthFailure::Unauthorized)?
+    };
+
+    let min_age_secs = i64::try_from(min_age.as_secs()).unwrap_or(i64::MAX);
+    if now_unix().saturating_sub(created_at_unix) < min_age_secs {
+        return Err(AuthFailure::AccountTooNew);
+    }
+    Ok(())
+}
+
+pub(crate) async fn friend_records_for_user(
+    state: &AppState,
+    user_id: UserId,
+) -> Result<Vec<FriendRecordResponse>, AuthFailure> {
+    let auth_user_id = user_id.to_string();
+
+    if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "SELECT u.user_id, u.username, f.created_at_unix
+             FROM friendships f
+             JOIN users u
+               ON u.user_id = CASE
+                   WHEN f.user_a_id = $1 THEN f.user_b_id
+                   ELSE f.user_a_id
+               END
+             WHERE f.user_a_id = $1 OR f.user_b_id = $1
+             ORDER BY f.created_at_unix DESC",
+        )
+        .bind(&auth_user_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+
+        let mut friends = Vec::with_capacity(rows.len());
+        for row in rows {
+            friends.push(FriendRecordResponse {
+                user_id: row.try_get("user_id").map_err(|_| AuthFailure::Internal)?,
+                username: row.try_get("username").map_err(|_| AuthFailure::Internal)?,
+                created_at_unix: row
+                    .try_get("created_at_unix")
+                    .map_err(|_| AuthFailure::Internal)?,
+            });
+        }
+        return Ok(friends);
+    }
+
+    let friendships = state.friendships.read().await;
+    let user_ids = state.user_ids.read().await;
+    let mut friends = Vec::new();
+    for (user_a, user_b) in &*friendships {
+        let friend_user_id = if user_a == &auth_user_id {
+            Some(user_b.clone())
+        } else if user_b == &auth_user_id {
+            Some(user_a.clone())
+        } else {
+            None
+        };
+        if let Some(friend_user_id) = friend_user_id {
+            let Some(username) = user_ids.get(&friend_user_id).cloned() else {
+                continue;
+            };
+            friends.push(FriendRecordResponse {
+                user_id: friend_user_id,
+                username,
+                created_at_unix: 0,
+            });
+        }
+    }
+    friends.sort_by(|left, right| left.user_id.cmp(&right.user_id));
+    Ok(friends)
+}
+
 pub(crate) async fn write_audit_log(
     state: &AppState,
     guild_id: Option<String>,
@@ -705,10 +1033,16 @@ pub(crate) async fn write_audit_log(
         .execute(pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
+        record_audit_log_written(action);
         return Ok(());
     }
 
-    state.audit_logs.write().await.push(serde_json::json!({
+    let mut logs = state.audit_logs.write().await;
+    let overflow = (logs.len() + 1)
+        .saturating_sub(state.runtime.audit_log_memory_max_entries)
+        .min(logs.len());
+    logs.drain(..overflow);
+    logs.push_back(serde_json::json!({
         "audit_id": audit_id,
         "guild_id": guild_id,
         "actor_user_id": actor_user_id.to_string(),
@@ -717,18 +1051,22 @@ pub(crate) async fn write_audit_log(
         "details": details_json,
         "created_at_unix": created_at_unix,
     }));
+    drop(logs);
+    record_audit_log_written(action);
+    record_audit_log_pruned("capacity", u64::try_from(overflow).unwrap_or(u64::MAX));
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{channel_permission_snapshot, guild_permission_snapshot};
+    use super::{channel_permission_snapshot, guild_permission_snapshot, user_role_in_guild};
     use crate::server::{
         auth::now_unix,
         core::{
             AppConfig, AppState, ChannelPermissionOverrideRecord, ChannelRecord, GuildRecord,
             GuildVisibility, WorkspaceRoleRecord,
         },
+        errors::AuthFailure,
         permissions::{
             all_permissions, DEFAULT_ROLE_MEMBER, DEFAULT_ROLE_MODERATOR, SYSTEM_ROLE_EVERYONE,
             SYSTEM_ROLE_WORKSPACE_OWNER,
@@ -825,6 +1163,10 @@ mod tests {
         ChannelRecord {
             name: String::from("general"),
             kind: ChannelKind::try_from(String::from("text")).expect("text kind should be valid"),
+            locked: false,
+            retention_secs: None,
+            voting: false,
+            category_id: None,
             messages: Vec::new(),
             role_overrides: HashMap::new(),
         }
@@ -848,6 +1190,11 @@ mod tests {
                 visibility: GuildVisibility::Private,
                 created_by_user_id: guild_creator,
                 default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
                 members: HashMap::new(),
                 banned_members: HashSet::new(),
                 channels: HashMap::new(),
@@ -862,6 +1209,47 @@ mod tests {
         assert_eq!(permissions.bits(), all_permissions().bits());
     }
 
+    #[tokio::test]
+    async fn missing_guilds_and_non_members_are_distinct_failures() {
+        let member = UserId::new();
+        let outsider = UserId::new();
+        let banned = UserId::new();
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let guild_id = String::from("01ARZ3NDEKTSV4RRFFQ69G5EEE");
+        state.membership_store.guilds().write().await.insert(
+            guild_id.clone(),
+            GuildRecord {
+                name: String::from("members"),
+                visibility: GuildVisibility::Public,
+                created_by_user_id: member,
+                default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
+                members: HashMap::from([(member, Role::Owner)]),
+                banned_members: HashSet::from([banned]),
+                channels: HashMap::new(),
+            },
+        );
+
+        assert!(matches!(
+            user_role_in_guild(&state, member, "01ARZ3NDEKTSV4RRFFQ69G5000").await,
+            Err(AuthFailure::NotFound)
+        ));
+        for user_id in [outsider, banned] {
+            assert!(matches!(
+                user_role_in_guild(&state, user_id, &guild_id).await,
+                Err(AuthFailure::NotGuildMember)
+            ));
+        }
+        assert_eq!(
+            user_role_in_guild(&state, member, &guild_id).await.unwrap(),
+            Role::Owner
+        );
+    }
+
     #[tokio::test]
     async fn channel_permission_snapshot_applies_full_override_priority_matrix() {
         let actor_user_id = UserId::new();
@@ -877,6 +1265,11 @@ mod tests {
                 visibility: GuildVisibility::Private,
                 created_by_user_id: guild_creator,
                 default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
                 members: HashMap::from([(actor_user_id, Role::Member)]),
                 banned_members: HashSet::new(),
                 channels: HashMap::from([(channel_id.clone(), empty_channel_record())]),
@@ -972,6 +1365,11 @@ mod tests {
                 visibility: GuildVisibility::Private,
                 created_by_user_id: guild_creator,
                 default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
                 members: HashMap::from([(owner_user_id, Role::Owner)]),
                 banned_members: HashSet::new(),
                 channels: HashMap::from([(channel_id.clone(), empty_channel_record())]),
@@ -9,6 +9,61 @@ use filament_core::UserId;
 use sqlx::PgPool;
 use sqlx::Row;
 use std::collections::{HashMap, HashSet};
+use unicode_normalization::UnicodeNormalization;
+
+const MAX_ATTACHMENT_FILENAME_BYTES: usize = 128;
+const MAX_ATTACHMENT_EXTENSION_CHARS: usize = 16;
+
+/// Types a browser may execute script from when it opens the download.
+/// These are refused whether they were declared or sniffed.
+const ACTIVE_CONTENT_MIME_TYPES: &[&str] = &[
+    "application/xhtml+xml",
+    "application/xml",
+    "image/svg+xml",
+    "text/html",
+    "text/xml",
+];
+
+/// Container or catch-all types `infer` reports when a more specific format
+/// (e.g. a `.docx` inside a zip) is not identifiable from the sniff window.
+/// These only pass when the uploader declared the same type.
+const AMBIGUOUS_SNIFFED_MIME_TYPES: &[&str] = &[
+    "application/octet-stream",
+    "application/x-ole-storage",
+    "application/zip",
+];
+
+/// Declared types accepted for payloads with no binary signature, provided
+/// the sniffed prefix is plain UTF-8 text.
+const TEXT_ATTACHMENT_MIME_TYPES: &[(&str, &str)] = &[
+    ("application/json", "json"),
+    ("text/csv", "csv"),
+    ("text/markdown", "md"),
+    ("text/plain", "txt"),
+];
+
+/// Builds the object store key for a new upload. With sharding enabled the
+/// id is nested under its last `shard_chars` characters: those come from the
+/// ULID's random component, so uploads spread evenly across directories,
+/// whereas a leading prefix is timestamp and barely changes. Existing
+/// records keep whatever key they were stored with.
+pub(crate) fn attachment_object_key(
+    prefix: &str,
+    shard_chars: usize,
+    attachment_id: &str,
+) -> String {
+    let shard_start = attachment_id.len().saturating_sub(shard_chars);
+    match attachment_id.get(shard_start..) {
+        Some(shard) if !shard.is_empty() => format!("{prefix}/{shard}/{attachment_id}"),
+        _ => format!("{prefix}/{attachment_id}"),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ResolvedAttachmentType {
+    pub(crate) mime_type: &'static str,
+    pub(crate) extension: &'static str,
+}
 
 #[derive(Debug)]
 pub(crate) struct AttachmentDbRow {
@@ -44,7 +99,9 @@ pub(crate) struct AttachmentMapDbRow {
 
 pub(crate) fn parse_attachment_ids(value: Vec<String>) -> Result<Vec<String>, AuthFailure> {
     if value.len() > MAX_ATTACHMENTS_PER_MESSAGE {
-        return Err(AuthFailure::InvalidRequest);
+        return Err(AuthFailure::TooManyAttachments {
+            limit: MAX_ATTACHMENTS_PER_MESSAGE,
+        });
     }
 
     let mut deduped = Vec::with_capacity(value.len());
@@ -60,14 +117,131 @@ pub(crate) fn parse_attachment_ids(value: Vec<String>) -> Result<Vec<String>, Au
     Ok(deduped)
 }
 
-pub(crate) fn validate_attachment_filename(value: String) -> Result<String, AuthFailure> {
-    if value.is_empty() || value.len() > 128 {
+/// Characters that render invisibly or reorder surrounding text, which lets a
+/// name like `evil\u{202e}gpj.exe` display as `evilexe.jpg`.
+fn is_hidden_filename_char(ch: char) -> bool {
+    ch.is_control()
+        || matches!(
+            ch,
+            '\u{061c}'
+                | '\u{200b}'..='\u{200f}'
+                | '\u{202a}'..='\u{202e}'
+                | '\u{2060}'..='\u{2069}'
+                | '\u{feff}'
+        )
+}
+
+/// Validates an upload filename and returns the sanitized name to store:
+/// NFC-normalized, with control, bidi and zero-width characters removed.
+pub(crate) fn validate_attachment_filename(value: &str) -> Result<String, AuthFailure> {
+    if value.is_empty() || value.len() > MAX_ATTACHMENT_FILENAME_BYTES {
         return Err(AuthFailure::InvalidRequest);
     }
     if value.contains('/') || value.contains('\\') || value.contains('\0') {
         return Err(AuthFailure::InvalidRequest);
     }
-    Ok(value)
+    let sanitized: String = value
+        .nfc()
+        .filter(|ch| !is_hidden_filename_char(*ch))
+        .collect();
+    let sanitized = sanitized.trim();
+    if sanitized.is_empty() || sanitized.chars().all(|ch| ch == '.') {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(sanitized.to_owned())
+}
+
+fn is_active_content_type(mime_type: &str) -> bool {
+    ACTIVE_CONTENT_MIME_TYPES.contains(&mime_type)
+}
+
+/// True when `prefix` is UTF-8 without NUL bytes. A multi-byte character cut
+/// off at the end of the sniff window still counts as text, unless the
+/// window holds the whole upload and the payload itself is truncated.
+fn is_plain_text_prefix(prefix: &[u8], is_complete: bool) -> bool {
+    if prefix.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(prefix) {
+        Ok(_) => true,
+        Err(error) => !is_complete && error.error_len().is_none(),
+    }
+}
+
+fn contains_svg_markup(prefix: &[u8]) -> bool {
+    prefix
+        .windows(4)
+        .any(|window| window.eq_ignore_ascii_case(b"<svg"))
+}
+
+/// Picks the stored content type for an upload from its sniffed prefix and
+/// optional declared type. Binary formats must match what `infer` detects,
+/// and ambiguous container types must be declared; text needs a declared
+/// allowlisted type; script-capable types never pass. `is_complete` is true
+/// when `sniff_buffer` holds the entire upload.
+pub(crate) fn resolve_attachment_type(
+    declared: Option<&mime::Mime>,
+    sniff_buffer: &[u8],
+    is_complete: bool,
+) -> Result<ResolvedAttachmentType, AuthFailure> {
+    let declared = declared.map(mime::Mime::essence_str);
+    if declared.is_some_and(is_active_content_type) {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    if let Some(sniffed) = infer::get(sniff_buffer) {
+        let sniffed_mime = sniffed.mime_type();
+        let ambiguous = AMBIGUOUS_SNIFFED_MIME_TYPES.contains(&sniffed_mime);
+        if is_active_content_type(sniffed_mime)
+            || declared.map_or(ambiguous, |declared| declared != sniffed_mime)
+        {
+            return Err(AuthFailure::InvalidRequest);
+        }
+        return Ok(ResolvedAttachmentType {
+            mime_type: sniffed_mime,
+            extension: sniffed.extension(),
+        });
+    }
+
+    let declared = declared.ok_or(AuthFailure::InvalidRequest)?;
+    let &(mime_type, extension) = TEXT_ATTACHMENT_MIME_TYPES
+        .iter()
+        .find(|(mime_type, _)| *mime_type == declared)
+        .ok_or(AuthFailure::InvalidRequest)?;
+    if !is_plain_text_prefix(sniff_buffer, is_complete) || contains_svg_markup(sniff_buffer) {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(ResolvedAttachmentType {
+        mime_type,
+        extension,
+    })
+}
+
+fn filename_extension(filename: &str) -> Option<&str> {
+    let (stem, extension) = filename.rsplit_once('.')?;
+    (!stem.is_empty()
+        && !extension.is_empty()
+        && extension.chars().count() <= MAX_ATTACHMENT_EXTENSION_CHARS
+        && extension.chars().all(|ch| ch.is_ascii_alphanumeric()))
+    .then_some(extension)
+}
+
+/// Keeps a sanitized filename's own extension, or appends the sniffed
+/// content type's extension when the name has none, shortening the stem so
+/// the result still fits the filename limit.
+pub(crate) fn ensure_attachment_extension(filename: String, sniffed_extension: &str) -> String {
+    if filename_extension(&filename).is_some() {
+        return filename;
+    }
+    let suffix = format!(".{sniffed_extension}");
+    let max_stem_bytes = MAX_ATTACHMENT_FILENAME_BYTES.saturating_sub(suffix.len());
+    let mut stem_end = filename.len().min(max_stem_bytes);
+    while !filename.is_char_boundary(stem_end) {
+        stem_end -= 1;
+    }
+    let mut named = filename[..stem_end].trim_end_matches('.').to_owned();
+    named.push_str(&suffix);
+    named
 }
 
 pub(crate) fn attach_message_media(
@@ -142,6 +316,7 @@ pub(crate) fn attachment_record_from_db_fields(
         sha256_hex,
         object_key,
         message_id,
+        message_position: None,
     })
 }
 
@@ -206,6 +381,8 @@ pub(crate) fn attachment_map_from_db_rows(
     Ok(attachment_map_from_db_records(records))
 }
 
+/// Groups bound attachments by message in the order their author listed
+/// them. Records bound before positions were tracked sort last, by id.
 pub(crate) fn attachment_map_from_records<'a>(
     records: impl Iterator<Item = &'a AttachmentRecord>,
     guild_id: &str,
@@ -217,7 +394,7 @@ pub(crate) fn attachment_map_from_records<'a>(
     }
 
     let wanted: HashSet<&str> = message_ids.iter().map(String::as_str).collect();
-    let mut by_message: HashMap<String, Vec<AttachmentResponse>> = HashMap::new();
+    let mut by_message: HashMap<String, Vec<&AttachmentRecord>> = HashMap::new();
     for record in records {
         let Some(message_id) = record.message_id.as_deref() else {
             continue;
@@ -234,12 +411,22 @@ pub(crate) fn attachment_map_from_records<'a>(
         by_message
             .entry(message_id.to_owned())
             .or_default()
-            .push(attachment_response_from_record(record));
-    }
-    for values in by_message.values_mut() {
-        values.sort_by(|a, b| a.attachment_id.cmp(&b.attachment_id));
+            .push(record);
     }
     by_message
+        .into_iter()
+        .map(|(message_id, mut values)| {
+            values.sort_by(|a, b| {
+                (a.message_position.unwrap_or(usize::MAX), &a.attachment_id)
+                    .cmp(&(b.message_position.unwrap_or(usize::MAX), &b.attachment_id))
+            });
+            let responses = values
+                .into_iter()
+                .map(attachment_response_from_record)
+                .collect();
+            (message_id, responses)
+        })
+        .collect()
 }
 
 pub(crate) fn attachment_map_from_db_records(
@@ -310,16 +497,41 @@ pub(crate) async fn attachment_usage_for_user(
 pub(crate) async fn find_attachment(
     state: &AppState,
     path: &AttachmentPath,
+) -> Result<AttachmentRecord, AuthFailure> {
+    find_attachment_scoped(
+        state,
+        &path.attachment_id,
+        &path.guild_id,
+        Some(&path.channel_id),
+    )
+    .await
+}
+
+/// Looks up an attachment anywhere in a guild, for references that are not
+/// tied to one channel such as the guild icon.
+pub(crate) async fn find_guild_attachment(
+    state: &AppState,
+    guild_id: &str,
+    attachment_id: &str,
+) -> Result<AttachmentRecord, AuthFailure> {
+    find_attachment_scoped(state, attachment_id, guild_id, None).await
+}
+
+async fn find_attachment_scoped(
+    state: &AppState,
+    attachment_id: &str,
+    guild_id: &str,
+    channel_id: Option<&str>,
 ) -> Result<AttachmentRecord, AuthFailure> {
     if let Some(pool) = &state.db_pool {
         let row = sqlx::query(
             "SELECT attachment_id, guild_id, channel_id, owner_id, filename, mime_type, size_bytes, sha256_hex, object_key, message_id
              FROM attachments
-             WHERE attachment_id = $1 AND guild_id = $2 AND channel_id = $3",
+             WHERE attachment_id = $1 AND guild_id = $2 AND ($3::text IS NULL OR channel_id = $3)",
         )
-        .bind(&path.attachment_id)
-        .bind(&path.guild_id)
-        .bind(&path.channel_id)
+        .bind(attachment_id)
+        .bind(guild_id)
+        .bind(channel_id)
         .fetch_optional(pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
@@ -356,8 +568,11 @@ pub(crate) async fn find_attachment(
         .attachments
         .read()
         .await
-        .get(&path.attachment_id)
-        .filter(|record| record.guild_id == path.guild_id && record.channel_id == path.channel_id)
+        .get(attachment_id)
+        .filter(|record| {
+            record.guild_id == guild_id
+                && channel_id.is_none_or(|channel_id| record.channel_id == channel_id)
+        })
         .cloned()
         .ok_or(AuthFailure::NotFound)
 }
@@ -394,7 +609,7 @@ pub(crate) async fn attachment_map_for_messages_db(
             "SELECT attachment_id, guild_id, channel_id, owner_id, filename, mime_type, size_bytes, sha256_hex, message_id
              FROM attachments
              WHERE guild_id = $1 AND channel_id = $2 AND message_id = ANY($3::text[])
-             ORDER BY created_at_unix ASC, attachment_id ASC",
+             ORDER BY message_position ASC NULLS LAST, created_at_unix ASC, attachment_id ASC",
         )
         .bind(guild_id)
         .bind(channel_id)
@@ -407,7 +622,7 @@ pub(crate) async fn attachment_map_for_messages_db(
             "SELECT attachment_id, guild_id, channel_id, owner_id, filename, mime_type, size_bytes, sha256_hex, message_id
              FROM attachments
              WHERE guild_id = $1 AND message_id = ANY($2::text[])
-             ORDER BY created_at_unix ASC, attachment_id ASC",
+             ORDER BY message_position ASC NULLS LAST, created_at_unix ASC, attachment_id ASC",
         )
         .bind(guild_id)
         .bind(message_ids)
@@ -453,13 +668,14 @@ mod tests {
     use super::{
         attachment_map_for_messages_in_memory, attachment_map_from_db_records,
         attachment_map_from_db_rows, attachment_map_from_records,
-        attachment_map_record_from_db_row, attachment_record_from_db_fields,
+        attachment_map_record_from_db_row, attachment_object_key, attachment_record_from_db_fields,
         attachment_record_from_db_row, attachment_response_from_db_fields,
         attachment_response_from_db_row, attachment_response_from_record,
         attachment_responses_from_db_rows, attachment_usage_for_owner, attachment_usage_for_user,
         attachment_usage_total_from_db, attachments_for_message_in_memory,
-        attachments_from_ids_in_memory, find_attachment, parse_attachment_ids,
-        validate_attachment_filename,
+        attachments_from_ids_in_memory, ensure_attachment_extension, find_attachment,
+        parse_attachment_ids, resolve_attachment_type, validate_attachment_filename,
+        ResolvedAttachmentType,
     };
     use crate::server::core::MAX_ATTACHMENTS_PER_MESSAGE;
     use crate::server::core::{AppConfig, AppState, AttachmentRecord};
@@ -469,6 +685,23 @@ mod tests {
     use std::collections::HashMap;
     use ulid::Ulid;
 
+    #[test]
+    fn attachment_object_key_shards_by_ulid_suffix() {
+        let id = "01ARZ3NDEKTSV4RRFFQ69G5FAV";
+        assert_eq!(
+            attachment_object_key("attachments", 2, id),
+            "attachments/AV/01ARZ3NDEKTSV4RRFFQ69G5FAV"
+        );
+        assert_eq!(
+            attachment_object_key("blobs/uploads", 4, id),
+            "blobs/uploads/5FAV/01ARZ3NDEKTSV4RRFFQ69G5FAV"
+        );
+        assert_eq!(
+            attachment_object_key("attachments", 0, id),
+            "attachments/01ARZ3NDEKTSV4RRFFQ69G5FAV"
+        );
+    }
+
     #[test]
     fn parse_attachment_ids_rejects_over_cap() {
         let ids = (0..=MAX_ATTACHMENTS_PER_MESSAGE)
@@ -476,7 +709,9 @@ mod tests {
             .collect::<Vec<_>>();
         assert!(matches!(
             parse_attachment_ids(ids),
-            Err(AuthFailure::InvalidRequest)
+            Err(AuthFailure::TooManyAttachments {
+                limit: MAX_ATTACHMENTS_PER_MESSAGE
+            })
         ));
     }
 
@@ -507,7 +742,7 @@ mod tests {
     fn validate_attachment_filename_rejects_path_control_bytes() {
         for value in ["a/b", "a\\b", "a\0b"] {
             assert!(matches!(
-                validate_attachment_filename(value.to_owned()),
+                validate_attachment_filename(value),
                 Err(AuthFailure::InvalidRequest)
             ));
         }
@@ -517,11 +752,153 @@ mod tests {
     fn validate_attachment_filename_accepts_safe_name() {
         let value = String::from("report.png");
         assert_eq!(
-            validate_attachment_filename(value.clone()).expect("name should be accepted"),
+            validate_attachment_filename(&value).expect("name should be accepted"),
             value
         );
     }
 
+    #[test]
+    fn validate_attachment_filename_strips_bidi_and_control_characters() {
+        assert_eq!(
+            validate_attachment_filename("evil\u{202e}gpj.exe").unwrap(),
+            "evilgpj.exe"
+        );
+        assert_eq!(
+            validate_attachment_filename(" re\u{200b}port\t.pdf\u{2066} ").unwrap(),
+            "report.pdf"
+        );
+        assert_eq!(
+            validate_attachment_filename("cafe\u{301}.txt").unwrap(),
+            "caf\u{e9}.txt"
+        );
+        for value in ["\u{202e}\u{202c}", " . ", ".."] {
+            assert!(matches!(
+                validate_attachment_filename(value),
+                Err(AuthFailure::InvalidRequest)
+            ));
+        }
+    }
+
+    #[test]
+    fn resolve_attachment_type_requires_declared_binary_types_to_match_sniffed() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let resolved = resolve_attachment_type(None, png, true).expect("png should resolve");
+        assert_eq!(
+            resolved,
+            ResolvedAttachmentType {
+                mime_type: "image/png",
+                extension: "png",
+            }
+        );
+        let declared_gif: mime::Mime = "image/gif".parse().unwrap();
+        assert!(matches!(
+            resolve_attachment_type(Some(&declared_gif), png, true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+    }
+
+    #[test]
+    fn resolve_attachment_type_requires_declared_type_for_ambiguous_containers() {
+        let zip = b"PK\x03\x04\x14\0\0\0\x08\0";
+        assert!(matches!(
+            resolve_attachment_type(None, zip, true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+        let declared_zip: mime::Mime = "application/zip".parse().unwrap();
+        assert_eq!(
+            resolve_attachment_type(Some(&declared_zip), zip, true)
+                .expect("declared zip should resolve")
+                .mime_type,
+            "application/zip"
+        );
+        let declared_docx: mime::Mime =
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                .parse()
+                .unwrap();
+        assert!(matches!(
+            resolve_attachment_type(Some(&declared_docx), zip, true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+    }
+
+    #[test]
+    fn resolve_attachment_type_accepts_declared_text_types() {
+        let declared: mime::Mime = "text/plain; charset=utf-8".parse().unwrap();
+        let resolved = resolve_attachment_type(Some(&declared), "notes \u{e9}".as_bytes(), true)
+            .expect("utf-8 text should resolve");
+        assert_eq!(resolved.mime_type, "text/plain");
+        assert_eq!(resolved.extension, "txt");
+
+        let truncated = &"ab\u{e9}".as_bytes()[..3];
+        assert!(resolve_attachment_type(Some(&declared), truncated, false).is_ok());
+        assert!(matches!(
+            resolve_attachment_type(Some(&declared), truncated, true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+
+        for payload in [&b"bin\0ary"[..], &b"\xff\xfeoops"[..]] {
+            assert!(matches!(
+                resolve_attachment_type(Some(&declared), payload, true),
+                Err(AuthFailure::InvalidRequest)
+            ));
+        }
+        assert!(matches!(
+            resolve_attachment_type(None, b"plain text", true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+        let undeclared_text: mime::Mime = "text/x-shellscript".parse().unwrap();
+        assert!(matches!(
+            resolve_attachment_type(Some(&undeclared_text), b"echo hi", true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+    }
+
+    #[test]
+    fn resolve_attachment_type_rejects_script_capable_content() {
+        let svg: mime::Mime = "image/svg+xml".parse().unwrap();
+        let svg_body = b"<svg xmlns=\"http://www.w3.org/2000/svg\"><script/></svg>";
+        assert!(matches!(
+            resolve_attachment_type(Some(&svg), svg_body, true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+        let text: mime::Mime = "text/plain".parse().unwrap();
+        assert!(matches!(
+            resolve_attachment_type(Some(&text), b"hello <SVG onload=alert(1)>", true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+        assert!(matches!(
+            resolve_attachment_type(None, b"<!DOCTYPE html><html><body></body></html>", true),
+            Err(AuthFailure::InvalidRequest)
+        ));
+    }
+
+    #[test]
+    fn ensure_attachment_extension_keeps_or_appends_extension() {
+        assert_eq!(
+            ensure_attachment_extension(String::from("photo.jpeg"), "png"),
+            "photo.jpeg"
+        );
+        assert_eq!(
+            ensure_attachment_extension(String::from("photo"), "png"),
+            "photo.png"
+        );
+        assert_eq!(
+            ensure_attachment_extension(String::from(".bashrc"), "png"),
+            ".bashrc.png"
+        );
+        assert_eq!(
+            ensure_attachment_extension(String::from("photo."), "png"),
+            "photo.png"
+        );
+
+        let long = "\u{e9}".repeat(64);
+        let named = ensure_attachment_extension(long, "png");
+        assert!(named.len() <= 128);
+        assert!(std::path::Path::new(&named)
+            .extension()
+            .is_some_and(|extension| extension == "png"));
+    }
+
     #[test]
     fn attachment_response_from_record_maps_expected_fields() {
         let owner_id = UserId::new();
@@ -536,6 +913,7 @@ mod tests {
             sha256_hex: String::from("abc123"),
             object_key: String::from("objects/key"),
             message_id: Some(Ulid::new().to_string()),
+            message_position: None,
         };
 
         let response = attachment_response_from_record(&record);
@@ -788,6 +1166,7 @@ mod tests {
             sha256_hex: String::from("b"),
             object_key: String::from("k2"),
             message_id: Some(keep_message.clone()),
+            message_position: None,
         };
         let record_b = AttachmentRecord {
             attachment_id: String::from("01ARZ3NDEKTSV4RRFFQ69G5FAV"),
@@ -800,6 +1179,7 @@ mod tests {
             sha256_hex: String::from("a"),
             object_key: String::from("k1"),
             message_id: Some(keep_message.clone()),
+            message_position: None,
         };
         let other_guild = AttachmentRecord {
             attachment_id: Ulid::new().to_string(),
@@ -812,6 +1192,7 @@ mod tests {
             sha256_hex: String::from("c"),
             object_key: String::from("k3"),
             message_id: Some(keep_message.clone()),
+            message_position: None,
         };
         let other_message_record = AttachmentRecord {
             attachment_id: Ulid::new().to_string(),
@@ -824,6 +1205,7 @@ mod tests {
             sha256_hex: String::from("d"),
             object_key: String::from("k4"),
             message_id: Some(other_message.clone()),
+            message_position: None,
         };
 
         let rows = [record_a, record_b, other_guild, other_message_record];
@@ -841,6 +1223,52 @@ mod tests {
         assert_eq!(kept[1].attachment_id, "02ARZ3NDEKTSV4RRFFQ69G5FAV");
     }
 
+    #[test]
+    fn attachment_map_from_records_keeps_author_order() {
+        let owner_id = UserId::new();
+        let message_id = Ulid::new().to_string();
+        let guild_id = Ulid::new().to_string();
+        let channel_id = Ulid::new().to_string();
+        let record = |attachment_id: &str, message_position: Option<usize>| AttachmentRecord {
+            attachment_id: String::from(attachment_id),
+            guild_id: guild_id.clone(),
+            channel_id: channel_id.clone(),
+            owner_id,
+            filename: String::from("a.png"),
+            mime_type: String::from("image/png"),
+            size_bytes: 1,
+            sha256_hex: String::from("a"),
+            object_key: String::from(attachment_id),
+            message_id: Some(message_id.clone()),
+            message_position,
+        };
+
+        let rows = [
+            record("01ARZ3NDEKTSV4RRFFQ69G5FAV", Some(1)),
+            record("00ARZ3NDEKTSV4RRFFQ69G5FAV", None),
+            record("02ARZ3NDEKTSV4RRFFQ69G5FAV", Some(0)),
+        ];
+        let map = attachment_map_from_records(
+            rows.iter(),
+            &guild_id,
+            None,
+            std::slice::from_ref(&message_id),
+        );
+
+        let ordered: Vec<&str> = map[&message_id]
+            .iter()
+            .map(|attachment| attachment.attachment_id.as_str())
+            .collect();
+        assert_eq!(
+            ordered,
+            [
+                "02ARZ3NDEKTSV4RRFFQ69G5FAV",
+                "01ARZ3NDEKTSV4RRFFQ69G5FAV",
+                "00ARZ3NDEKTSV4RRFFQ69G5FAV",
+            ]
+        );
+    }
+
     #[test]
     fn attachment_map_from_db_records_groups_by_message_and_skips_null_message_id() {
         let entry_a = AttachmentResponse {
@@ -897,6 +1325,7 @@ mod tests {
                 sha256_hex: String::from("hash-a"),
                 object_key: String::from("obj-a"),
                 message_id: None,
+                message_position: None,
             },
         );
         attachments.insert(
@@ -912,6 +1341,7 @@ mod tests {
                 sha256_hex: String::from("hash-b"),
                 object_key: String::from("obj-b"),
                 message_id: None,
+                message_position: None,
             },
         );
 
@@ -949,6 +1379,7 @@ mod tests {
                 sha256_hex: String::from("ha"),
                 object_key: String::from("oa"),
                 message_id: None,
+                message_position: None,
             },
             AttachmentRecord {
                 attachment_id: Ulid::new().to_string(),
@@ -961,6 +1392,7 @@ mod tests {
                 sha256_hex: String::from("hb"),
                 object_key: String::from("ob"),
                 message_id: None,
+                message_position: None,
             },
             AttachmentRecord {
                 attachment_id: Ulid::new().to_string(),
@@ -973,6 +1405,7 @@ mod tests {
                 sha256_hex: String::from("hc"),
                 object_key: String::from("oc"),
                 message_id: None,
+                message_position: None,
             },
         ];
 
@@ -1005,6 +1438,7 @@ mod tests {
                 sha256_hex: String::from("abc"),
                 object_key: String::from("obj-1"),
                 message_id: None,
+                message_position: None,
             },
         );
         state.attachments.write().await.insert(
@@ -1020,6 +1454,7 @@ mod tests {
                 sha256_hex: String::from("def"),
                 object_key: String::from("obj-2"),
                 message_id: None,
+                message_position: None,
             },
         );
 
@@ -1050,6 +1485,7 @@ mod tests {
                 sha256_hex: String::from("ghi"),
                 object_key: String::from("obj-3"),
                 message_id: None,
+                message_position: None,
             },
         );
 
@@ -1106,6 +1542,7 @@ mod tests {
                 sha256_hex: String::from("jkl"),
                 object_key: String::from("obj-4"),
                 message_id: Some(message_id.clone()),
+                message_position: None,
             },
         );
 
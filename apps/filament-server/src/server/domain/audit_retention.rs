@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use tokio::time::interval;
+
+use crate::server::{
+    auth::now_unix,
+    core::{AppState, AUDIT_LOG_PURGE_BATCH, AUDIT_LOG_PURGE_INTERVAL_SECS},
+    errors::AuthFailure,
+    metrics::record_audit_log_pruned,
+};
+
+/// Deletes audit entries written at or before the retention cutoff, at most
+/// `AUDIT_LOG_PURGE_BATCH` rows per call on Postgres. Returns how many were
+/// removed.
+pub(crate) async fn purge_expired_audit_logs(
+    state: &AppState,
+    now: i64,
+) -> Result<u64, AuthFailure> {
+    let retention = state.runtime.audit_log_retention;
+    if retention.is_zero() {
+        return Ok(0);
+    }
+    let cutoff = now.saturating_sub(i64::try_from(retention.as_secs()).unwrap_or(i64::MAX));
+
+    let purged = if let Some(pool) = &state.db_pool {
+        let batch = i64::try_from(AUDIT_LOG_PURGE_BATCH).map_err(|_| AuthFailure::Internal)?;
+        sqlx::query(
+            "DELETE FROM audit_logs
+             WHERE audit_id IN (
+                 SELECT audit_id
+                 FROM audit_logs
+                 WHERE created_at_unix <= $1
+                 ORDER BY created_at_unix ASC
+                 LIMIT $2
+             )",
+        )
+        .bind(cutoff)
+        .bind(batch)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .rows_affected()
+    } else {
+        // Entries are appended in write order, so expired ones form a prefix.
+        let mut logs = state.audit_logs.write().await;
+        let expired = logs
+            .iter()
+            .take_while(|entry| {
+                entry["created_at_unix"]
+                    .as_i64()
+                    .is_some_and(|created_at_unix| created_at_unix <= cutoff)
+            })
+            .count();
+        logs.drain(..expired);
+        u64::try_from(expired).unwrap_or(u64::MAX)
+    };
+    record_audit_log_pruned("retention", purged);
+    Ok(purged)
+}
+
+/// Background task that applies `audit_log_retention`. Exits immediately when
+/// audit entries are kept forever.
+pub(crate) async fn start_audit_log_purge(state: AppState) {
+    if state.runtime.audit_log_retention.is_zero() {
+        return;
+    }
+
+    let batch = u64::try_from(AUDIT_LOG_PURGE_BATCH).unwrap_or(u64::MAX);
+    let mut ticker = interval(Duration::from_secs(AUDIT_LOG_PURGE_INTERVAL_SECS));
+    loop {
+        ticker.tick().await;
+        let mut purged = 0_u64;
+        let outcome = loop {
+            match purge_expired_audit_logs(&state, now_unix()).await {
+                Ok(removed) => {
+                    purged += removed;
+                    if removed < batch || state.db_pool.is_none() {
+                        break Ok(());
+                    }
+                }
+                Err(error) => break Err(error),
+            }
+        };
+        if purged > 0 {
+            tracing::info!(event = "audit.purge", purged);
+        }
+        if let Err(error) = outcome {
+            tracing::warn!(event = "audit.purge", outcome = "failed", error = %error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, time::Duration};
+
+    use filament_core::UserId;
+
+    use super::purge_expired_audit_logs;
+    use crate::server::{
+        auth::now_unix,
+        core::{AppConfig, AppState},
+        domain::write_audit_log,
+    };
+
+    fn audit_actions(logs: &VecDeque<serde_json::Value>) -> Vec<String> {
+        logs.iter()
+            .map(|entry| entry["action"].as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn in_memory_audit_log_keeps_only_newest_entries() {
+        let state = AppState::new(&AppConfig {
+            audit_log_memory_max_entries: 2,
+            ..AppConfig::default()
+        })
+        .expect("state initializes");
+        let actor = UserId::new();
+        for action in ["first", "second", "third"] {
+            write_audit_log(&state, None, actor, None, action, serde_json::json!({}))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            audit_actions(&*state.audit_logs.read().await),
+            vec![String::from("second"), String::from("third")]
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_audit_entries_are_purged_only_with_retention() {
+        let keep_forever = AppState::new(&AppConfig::default()).expect("state initializes");
+        let state = AppState::new(&AppConfig {
+            audit_log_retention: Duration::from_secs(60),
+            ..AppConfig::default()
+        })
+        .expect("state initializes");
+        let actor = UserId::new();
+        for target in [&keep_forever, &state] {
+            for action in ["old", "new"] {
+                write_audit_log(target, None, actor, None, action, serde_json::json!({}))
+                    .await
+                    .unwrap();
+            }
+            let mut logs = target.audit_logs.write().await;
+            logs[0]["created_at_unix"] = serde_json::json!(now_unix() - 120);
+        }
+
+        let now = now_unix();
+        assert_eq!(
+            purge_expired_audit_logs(&keep_forever, now).await.unwrap(),
+            0
+        );
+        assert_eq!(keep_forever.audit_logs.read().await.len(), 2);
+
+        assert_eq!(purge_expired_audit_logs(&state, now).await.unwrap(), 1);
+        assert_eq!(
+            audit_actions(&*state.audit_logs.read().await),
+            vec![String::from("new")]
+        );
+        assert_eq!(purge_expired_audit_logs(&state, now).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,101 @@
+use filament_core::MarkdownToken;
+use serde::Serialize;
+
+const MENTION_EVERYONE: &str = "@everyone";
+const MENTION_HERE: &str = "@here";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MentionScope {
+    Everyone,
+    Here,
+}
+
+impl MentionScope {
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Everyone => "everyone",
+            Self::Here => "here",
+        }
+    }
+}
+
+/// Finds the broadest guild-wide mention in a message's prose. Code spans and
+/// fenced blocks are ignored so quoting `@everyone` never pings anyone.
+pub(crate) fn guild_mention_scope(tokens: &[MarkdownToken]) -> Option<MentionScope> {
+    let mut scope = None;
+    for token in tokens {
+        let MarkdownToken::Text { text } = token else {
+            continue;
+        };
+        if contains_mention(text, MENTION_EVERYONE) {
+            return Some(MentionScope::Everyone);
+        }
+        if contains_mention(text, MENTION_HERE) {
+            scope = Some(MentionScope::Here);
+        }
+    }
+    scope
+}
+
+fn contains_mention(text: &str, mention: &str) -> bool {
+    text.match_indices(mention).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + mention.len()..].chars().next();
+        !before.is_some_and(is_mention_word_char) && !after.is_some_and(is_mention_word_char)
+    })
+}
+
+fn is_mention_word_char(value: char) -> bool {
+    value.is_alphanumeric() || value == '_' || value == '@'
+}
+
+#[cfg(test)]
+mod tests {
+    use filament_core::MarkdownToken;
+
+    use super::{guild_mention_scope, MentionScope};
+
+    fn text(value: &str) -> MarkdownToken {
+        MarkdownToken::Text {
+            text: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn guild_mention_scope_prefers_everyone_over_here() {
+        assert_eq!(
+            guild_mention_scope(&[text("@here first"), text("then @everyone!")]),
+            Some(MentionScope::Everyone)
+        );
+        assert_eq!(
+            guild_mention_scope(&[text("ping @here, please")]),
+            Some(MentionScope::Here)
+        );
+        assert_eq!(guild_mention_scope(&[text("hello")]), None);
+    }
+
+    #[test]
+    fn guild_mention_scope_requires_word_boundaries() {
+        assert_eq!(
+            guild_mention_scope(&[text("mail user@everyone.example")]),
+            None
+        );
+        assert_eq!(guild_mention_scope(&[text("@everyones @heresy")]), None);
+        assert_eq!(guild_mention_scope(&[text("@@here")]), None);
+    }
+
+    #[test]
+    fn guild_mention_scope_ignores_code() {
+        let tokens = [
+            MarkdownToken::Code {
+                code: String::from("@everyone"),
+            },
+            MarkdownToken::FencedCode {
+                language: None,
+                code: String::from("@here"),
+            },
+        ];
+        assert_eq!(guild_mention_scope(&tokens), None);
+    }
+}
@@ -0,0 +1,427 @@
+use anyhow::anyhow;
+use aws_lc_rs::aead::{Aad, Nonce, RandomizedNonceKey, AES_256_GCM_SIV, NONCE_LEN};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use sqlx::{postgres::PgRow, Row};
+
+use crate::server::{auth::MAX_MESSAGE_CONTENT_BYTES, core::AppConfig, errors::AuthFailure};
+
+/// zstd level used when `message_content_compression` is enabled. Level 3 is
+/// zstd's default; `compression_benchmark_snapshot` reports its cost.
+pub(crate) const MESSAGE_CONTENT_ZSTD_LEVEL: i32 = 3;
+/// Short messages rarely shrink once the zstd frame header is paid for.
+pub(crate) const MESSAGE_CONTENT_COMPRESSION_MIN_BYTES: usize = 128;
+/// Length of the decoded `message_content_encryption_key`.
+pub(crate) const MESSAGE_CONTENT_KEY_BYTES: usize = 32;
+/// First byte of a sealed payload, saying how the rest of it is encoded.
+const SEALED_ENCODING_PLAIN: u8 = 0;
+const SEALED_ENCODING_ZSTD: u8 = 1;
+
+/// Column values for `messages.content` / `messages.content_zstd` /
+/// `messages.content_sealed`. At most one of them carries the message text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct StoredMessageContent {
+    pub(crate) content: String,
+    pub(crate) content_zstd: Option<Vec<u8>>,
+    pub(crate) content_sealed: Option<Vec<u8>>,
+}
+
+/// AES-256-GCM-SIV key for sealing message content at rest. GCM-SIV keeps a
+/// repeated random nonce from leaking more than message equality, so one
+/// long-lived key can seal every message the server stores.
+pub(crate) struct MessageContentCipher {
+    key: RandomizedNonceKey,
+}
+
+impl std::fmt::Debug for MessageContentCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageContentCipher")
+            .finish_non_exhaustive()
+    }
+}
+
+impl MessageContentCipher {
+    pub(crate) fn new(key_bytes: &[u8]) -> anyhow::Result<Self> {
+        if key_bytes.len() != MESSAGE_CONTENT_KEY_BYTES {
+            return Err(anyhow!(
+                "message content encryption key must be {MESSAGE_CONTENT_KEY_BYTES} bytes"
+            ));
+        }
+        let key = RandomizedNonceKey::new(&AES_256_GCM_SIV, key_bytes)
+            .map_err(|_| anyhow!("message content encryption key is invalid"))?;
+        Ok(Self { key })
+    }
+
+    /// Seals `payload` bound to `message_id`, so a sealed value copied onto
+    /// another row fails to open. Output is the nonce followed by the
+    /// ciphertext and tag.
+    fn seal(&self, message_id: &str, mut payload: Vec<u8>) -> Result<Vec<u8>, AuthFailure> {
+        let nonce = self
+            .key
+            .seal_in_place_append_tag(Aad::from(message_id.as_bytes()), &mut payload)
+            .map_err(|_| {
+                tracing::error!(event = "messages.content.seal_failed");
+                AuthFailure::Internal
+            })?;
+        let mut sealed = Vec::with_capacity(NONCE_LEN + payload.len());
+        sealed.extend_from_slice(nonce.as_ref());
+        sealed.extend_from_slice(&payload);
+        Ok(sealed)
+    }
+
+    fn open(&self, message_id: &str, mut sealed: Vec<u8>) -> Result<Vec<u8>, AuthFailure> {
+        let open_failed = || {
+            tracing::error!(event = "messages.content.open_failed", message_id);
+            AuthFailure::Internal
+        };
+        if sealed.len() < NONCE_LEN {
+            return Err(open_failed());
+        }
+        let mut ciphertext = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed).map_err(|_| open_failed())?;
+        let plaintext_len = self
+            .key
+            .open_in_place(nonce, Aad::from(message_id.as_bytes()), &mut ciphertext)
+            .map_err(|_| open_failed())?
+            .len();
+        ciphertext.truncate(plaintext_len);
+        Ok(ciphertext)
+    }
+}
+
+/// Parses `message_content_encryption_key` (base64url, 32 bytes). `None`
+/// leaves new content unsealed.
+pub(crate) fn build_message_content_cipher(
+    config: &AppConfig,
+) -> anyhow::Result<Option<MessageContentCipher>> {
+    let Some(encoded) = &config.message_content_encryption_key else {
+        return Ok(None);
+    };
+    let key_bytes = URL_SAFE_NO_PAD
+        .decode(encoded.trim().trim_end_matches('='))
+        .map_err(|_| anyhow!("message content encryption key must be base64url"))?;
+    MessageContentCipher::new(&key_bytes).map(Some)
+}
+
+/// Compresses `content` for storage when enabled and worthwhile. Content that
+/// does not shrink is stored as plaintext so reads stay cheap. With a cipher
+/// the result is sealed into `content_sealed` and the other columns stay
+/// empty.
+pub(crate) fn encode_message_content(
+    content: &str,
+    message_id: &str,
+    compress: bool,
+    cipher: Option<&MessageContentCipher>,
+) -> Result<StoredMessageContent, AuthFailure> {
+    let compressed = compress_message_content(content, compress);
+    let Some(cipher) = cipher else {
+        return Ok(match compressed {
+            Some(compressed) => StoredMessageContent {
+                content_zstd: Some(compressed),
+                ..StoredMessageContent::default()
+            },
+            None => StoredMessageContent {
+                content: content.to_owned(),
+                ..StoredMessageContent::default()
+            },
+        });
+    };
+    let payload = match compressed {
+        Some(compressed) => [&[SEALED_ENCODING_ZSTD][..], &compressed].concat(),
+        None => [&[SEALED_ENCODING_PLAIN][..], content.as_bytes()].concat(),
+    };
+    Ok(StoredMessageContent {
+        content_sealed: Some(cipher.seal(message_id, payload)?),
+        ..StoredMessageContent::default()
+    })
+}
+
+fn compress_message_content(content: &str, compress: bool) -> Option<Vec<u8>> {
+    if !compress || content.len() < MESSAGE_CONTENT_COMPRESSION_MIN_BYTES {
+        return None;
+    }
+    match zstd::bulk::compress(content.as_bytes(), MESSAGE_CONTENT_ZSTD_LEVEL) {
+        Ok(compressed) if compressed.len() < content.len() => Some(compressed),
+        Ok(_) => None,
+        Err(error) => {
+            tracing::warn!(event = "messages.content.compress_failed", error = %error);
+            None
+        }
+    }
+}
+
+/// Restores message text from its stored columns. Rows without
+/// `content_zstd` or `content_sealed` are legacy or uncompressed and returned
+/// as-is. Output is capped at the message content limit so a corrupt frame
+/// cannot balloon. Sealed rows need the cipher they were written with.
+pub(crate) fn decode_message_content(
+    stored: StoredMessageContent,
+    message_id: &str,
+    cipher: Option<&MessageContentCipher>,
+) -> Result<String, AuthFailure> {
+    let StoredMessageContent {
+        content,
+        content_zstd,
+        content_sealed,
+    } = stored;
+    if let Some(sealed) = content_sealed {
+        let Some(cipher) = cipher else {
+            tracing::error!(event = "messages.content.sealed_without_key", message_id);
+            return Err(AuthFailure::Internal);
+        };
+        let mut payload = cipher.open(message_id, sealed)?;
+        return match payload.first().copied() {
+            Some(SEALED_ENCODING_PLAIN) => {
+                payload.remove(0);
+                String::from_utf8(payload).map_err(|_| AuthFailure::Internal)
+            }
+            Some(SEALED_ENCODING_ZSTD) => decompress_message_content(&payload[1..]),
+            _ => Err(AuthFailure::Internal),
+        };
+    }
+    let Some(compressed) = content_zstd else {
+        return Ok(content);
+    };
+    decompress_message_content(&compressed)
+}
+
+fn decompress_message_content(compressed: &[u8]) -> Result<String, AuthFailure> {
+    let raw = zstd::bulk::decompress(compressed, MAX_MESSAGE_CONTENT_BYTES).map_err(|error| {
+        tracing::error!(event = "messages.content.decompress_failed", error = %error);
+        AuthFailure::Internal
+    })?;
+    String::from_utf8(raw).map_err(|_| AuthFailure::Internal)
+}
+
+/// Reads and decodes the `content` / `content_zstd` / `content_sealed`
+/// columns of a message row.
+pub(crate) fn message_content_from_row(
+    row: &PgRow,
+    message_id: &str,
+    cipher: Option<&MessageContentCipher>,
+) -> Result<String, AuthFailure> {
+    let stored = StoredMessageContent {
+        content: row.try_get("content").map_err(|_| AuthFailure::Internal)?,
+        content_zstd: row
+            .try_get("content_zstd")
+            .map_err(|_| AuthFailure::Internal)?,
+        content_sealed: row
+            .try_get("content_sealed")
+            .map_err(|_| AuthFailure::Internal)?,
+    };
+    decode_message_content(stored, message_id, cipher)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::{
+        build_message_content_cipher, decode_message_content, encode_message_content,
+        MessageContentCipher, StoredMessageContent, MESSAGE_CONTENT_COMPRESSION_MIN_BYTES,
+        MESSAGE_CONTENT_ZSTD_LEVEL,
+    };
+    use crate::server::{auth::MAX_MESSAGE_CONTENT_BYTES, core::AppConfig, errors::AuthFailure};
+
+    const MESSAGE_ID: &str = "01J00000000000000000000000";
+
+    fn cipher() -> MessageContentCipher {
+        MessageContentCipher::new(&[7; 32]).expect("32-byte key should build")
+    }
+
+    fn encode(content: &str, compress: bool) -> StoredMessageContent {
+        encode_message_content(content, MESSAGE_ID, compress, None).expect("content should encode")
+    }
+
+    fn decode(stored: StoredMessageContent) -> Result<String, AuthFailure> {
+        decode_message_content(stored, MESSAGE_ID, None)
+    }
+
+    fn zstd_only(content_zstd: Vec<u8>) -> StoredMessageContent {
+        StoredMessageContent {
+            content_zstd: Some(content_zstd),
+            ..StoredMessageContent::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_compressible_content() {
+        let content = "the quick brown fox jumps over the lazy dog ".repeat(20);
+        let stored = encode(&content, true);
+        assert!(stored.content.is_empty());
+        let compressed = stored
+            .content_zstd
+            .clone()
+            .expect("repetitive content should compress");
+        assert!(compressed.len() < content.len());
+        assert_eq!(decode(stored).expect("content should decode"), content);
+    }
+
+    #[test]
+    fn leaves_short_or_disabled_content_as_plaintext() {
+        let short = "x".repeat(MESSAGE_CONTENT_COMPRESSION_MIN_BYTES - 1);
+        assert_eq!(encode(&short, true).content, short);
+        assert_eq!(encode(&short, true).content_zstd, None);
+
+        let long = "y".repeat(1_000);
+        let stored = encode(&long, false);
+        assert_eq!(stored.content, long);
+        assert_eq!(stored.content_zstd, None);
+    }
+
+    #[test]
+    fn legacy_rows_decode_unchanged() {
+        assert_eq!(
+            decode(StoredMessageContent {
+                content: String::from("legacy"),
+                ..StoredMessageContent::default()
+            })
+            .expect("legacy row decodes"),
+            "legacy"
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_or_oversized_frames() {
+        assert!(matches!(
+            decode(zstd_only(vec![0, 1, 2, 3])),
+            Err(AuthFailure::Internal)
+        ));
+        let oversized = zstd::bulk::compress("z".repeat(10_000).as_bytes(), 3)
+            .expect("compression should succeed");
+        assert!(matches!(
+            decode(zstd_only(oversized)),
+            Err(AuthFailure::Internal)
+        ));
+    }
+
+    #[test]
+    fn sealed_content_round_trips_and_hides_plaintext() {
+        let cipher = cipher();
+        let short = "meet at the usual place";
+        let long = "the quick brown fox jumps over the lazy dog ".repeat(20);
+        for (content, compress) in [(short, true), (long.as_str(), true), (long.as_str(), false)] {
+            let stored = encode_message_content(content, MESSAGE_ID, compress, Some(&cipher))
+                .expect("content should seal");
+            assert!(stored.content.is_empty());
+            assert_eq!(stored.content_zstd, None);
+            let sealed = stored.content_sealed.clone().expect("content is sealed");
+            assert!(!sealed
+                .windows(short.len())
+                .any(|window| window == short.as_bytes()));
+            assert_eq!(
+                decode_message_content(stored, MESSAGE_ID, Some(&cipher))
+                    .expect("content should open"),
+                content
+            );
+        }
+        let compressed = encode_message_content(&long, MESSAGE_ID, true, Some(&cipher))
+            .expect("content should seal");
+        let uncompressed = encode_message_content(&long, MESSAGE_ID, false, Some(&cipher))
+            .expect("content should seal");
+        assert!(
+            compressed.content_sealed.unwrap().len() < uncompressed.content_sealed.unwrap().len()
+        );
+    }
+
+    #[test]
+    fn sealed_content_rejects_wrong_key_row_or_missing_cipher() {
+        let cipher = cipher();
+        let seal = || {
+            encode_message_content("secret", MESSAGE_ID, false, Some(&cipher))
+                .expect("content should seal")
+        };
+        let other_key = MessageContentCipher::new(&[8; 32]).expect("32-byte key should build");
+        assert!(matches!(
+            decode_message_content(seal(), MESSAGE_ID, Some(&other_key)),
+            Err(AuthFailure::Internal)
+        ));
+        assert!(matches!(
+            decode_message_content(seal(), "01J00000000000000000000001", Some(&cipher)),
+            Err(AuthFailure::Internal)
+        ));
+        assert!(matches!(
+            decode_message_content(seal(), MESSAGE_ID, None),
+            Err(AuthFailure::Internal)
+        ));
+        let mut tampered = seal();
+        if let Some(byte) = tampered
+            .content_sealed
+            .as_mut()
+            .and_then(|sealed| sealed.last_mut())
+        {
+            *byte ^= 1;
+        }
+        assert!(matches!(
+            decode_message_content(tampered, MESSAGE_ID, Some(&cipher)),
+            Err(AuthFailure::Internal)
+        ));
+        assert_eq!(
+            decode_message_content(encode("plain", false), MESSAGE_ID, Some(&cipher))
+                .expect("unsealed rows stay readable with a key"),
+            "plain"
+        );
+    }
+
+    #[test]
+    fn encryption_key_config_must_be_32_bytes_of_base64url() {
+        assert!(build_message_content_cipher(&AppConfig::default())
+            .expect("unset key is valid")
+            .is_none());
+        let config = |key: &str| AppConfig {
+            message_content_encryption_key: Some(key.to_owned()),
+            ..AppConfig::default()
+        };
+        assert!(build_message_content_cipher(&config(
+            "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc"
+        ))
+        .expect("32-byte key is valid")
+        .is_some());
+        assert!(build_message_content_cipher(&config("c2hvcnQ")).is_err());
+        assert!(build_message_content_cipher(&config("not base64!")).is_err());
+    }
+
+    #[test]
+    #[ignore = "benchmark snapshot"]
+    fn compression_benchmark_snapshot() {
+        let iterations = 2_000u32;
+        let sentence =
+            "Deploy went out at 14:02, rollback plan is in the runbook; ping me if p95 regresses. ";
+        for size in [
+            MESSAGE_CONTENT_COMPRESSION_MIN_BYTES,
+            512,
+            MAX_MESSAGE_CONTENT_BYTES,
+        ] {
+            let content: String = sentence.chars().cycle().take(size).collect();
+
+            for sealed in [false, true] {
+                let cipher = sealed.then(cipher);
+                let cipher = cipher.as_ref();
+                let started = Instant::now();
+                let mut stored = encode_message_content(&content, MESSAGE_ID, true, cipher)
+                    .expect("content should encode");
+                for _ in 1..iterations {
+                    stored = encode_message_content(&content, MESSAGE_ID, true, cipher)
+                        .expect("content should encode");
+                }
+                let encode_ns = started.elapsed().as_nanos() / u128::from(iterations);
+
+                let started = Instant::now();
+                for _ in 0..iterations {
+                    let decoded = decode_message_content(stored.clone(), MESSAGE_ID, cipher)
+                        .expect("content should decode");
+                    assert_eq!(decoded.len(), size);
+                }
+                let decode_ns = started.elapsed().as_nanos() / u128::from(iterations);
+
+                let stored_bytes = stored
+                    .content_sealed
+                    .as_ref()
+                    .or(stored.content_zstd.as_ref())
+                    .map_or(stored.content.len(), Vec::len);
+                println!(
+                    "message_content_snapshot level={MESSAGE_CONTENT_ZSTD_LEVEL} sealed={sealed} bytes={size} stored_bytes={stored_bytes} ns_per_encode={encode_ns} ns_per_decode={decode_ns}",
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use filament_core::UserId;
+use sqlx::{PgPool, Row};
+
+use crate::server::{
+    core::AppState,
+    errors::AuthFailure,
+    types::{AttachmentResponse, ExportedMessage, UserExportRecord},
+};
+
+use super::{
+    attachment_map_for_messages_db, attachment_map_for_messages_in_memory,
+    attachments::attachment_response_from_record, friend_records_for_user,
+    message_content_from_row, reaction_map_for_messages_db, reaction_summaries_from_users,
+    rows_to_attachment_responses,
+};
+
+pub(crate) const MESSAGE_EXPORT_PAGE_SIZE: usize = 200;
+
+/// Position of a streaming personal data export. Sections are emitted in
+/// order; paged sections remember the last id written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum UserExportCursor {
+    Friends,
+    Attachments(Option<String>),
+    Messages(Option<String>),
+    Done,
+}
+
+/// Loads the next page of a channel's messages in chronological order, after
+/// `after_message_id` when given. An empty page marks the end of the export.
+pub(crate) async fn channel_export_page(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+    after_message_id: Option<&str>,
+) -> Result<Vec<ExportedMessage>, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "SELECT message_id, guild_id, channel_id, author_id, content, content_zstd,
+                    content_sealed, created_at_unix
+             FROM messages
+             WHERE guild_id = $1 AND channel_id = $2 AND ($3::text IS NULL OR message_id > $3)
+               AND deleted_at_unix IS NULL
+             ORDER BY message_id ASC
+             LIMIT $4",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(after_message_id)
+        .bind(export_page_limit()?)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let mut messages = exported_messages_from_db_rows(state, rows)?;
+        attach_export_media_db(pool, &mut messages).await?;
+        return Ok(messages);
+    }
+
+    let mut messages = {
+        let guilds = state.membership_store.guilds().read().await;
+        let guild = guilds.get(guild_id).ok_or(AuthFailure::NotFound)?;
+        let channel = guild
+            .channels
+            .get(channel_id)
+            .ok_or(AuthFailure::NotFound)?;
+        let mut page: Vec<_> = channel
+            .messages
+            .iter()
+            .filter(|message| message.deleted_at_unix.is_none())
+            .filter(|message| after_message_id.is_none_or(|after| message.id.as_str() > after))
+            .collect();
+        page.sort_by(|a, b| a.id.cmp(&b.id));
+        page.truncate(MESSAGE_EXPORT_PAGE_SIZE);
+        page.into_iter()
+            .map(|message| ExportedMessage {
+                message_id: message.id.clone(),
+                guild_id: guild_id.to_owned(),
+                channel_id: channel_id.to_owned(),
+                author_id: message.author_id.to_string(),
+                content: message.content.clone(),
+                created_at_unix: message.created_at_unix,
+                attachments: Vec::new(),
+                reactions: reaction_summaries_from_users(&message.reactions, None),
+            })
+            .collect::<Vec<_>>()
+    };
+    attach_export_media_in_memory(state, &mut messages).await;
+    Ok(messages)
+}
+
+/// Produces the next NDJSON chunk of a user's personal data export and the
+/// cursor to resume from. Returns `None` once every section has been written.
+pub(crate) async fn user_export_chunk(
+    state: &AppState,
+    user_id: UserId,
+    cursor: UserExportCursor,
+) -> Result<Option<(Vec<u8>, UserExportCursor)>, AuthFailure> {
+    let mut cursor = cursor;
+    loop {
+        let (records, next): (Vec<UserExportRecord>, _) = match cursor {
+            UserExportCursor::Friends => {
+                let records = friend_records_for_user(state, user_id)
+                    .await?
+                    .into_iter()
+                    .map(UserExportRecord::Friend)
+                    .collect();
+                (records, UserExportCursor::Attachments(None))
+            }
+            UserExportCursor::Attachments(after) => {
+                let page = user_attachment_export_page(state, user_id, after.as_deref()).await?;
+                let next = match page.last() {
+                    Some(last) if page.len() == MESSAGE_EXPORT_PAGE_SIZE => {
+                        UserExportCursor::Attachments(Some(last.attachment_id.clone()))
+                    }
+                    _ => UserExportCursor::Messages(None),
+                };
+                (
+                    page.into_iter().map(UserExportRecord::Attachment).collect(),
+                    next,
+                )
+            }
+            UserExportCursor::Messages(after) => {
+                let page = user_message_export_page(state, user_id, after.as_deref()).await?;
+                let next = match page.last() {
+                    Some(last) if page.len() == MESSAGE_EXPORT_PAGE_SIZE => {
+                        UserExportCursor::Messages(Some(last.message_id.clone()))
+                    }
+                    _ => UserExportCursor::Done,
+                };
+                (
+                    page.into_iter().map(UserExportRecord::Message).collect(),
+                    next,
+                )
+            }
+            UserExportCursor::Done => return Ok(None),
+        };
+        if !records.is_empty() {
+            return Ok(Some((export_page_ndjson(&records)?, next)));
+        }
+        cursor = next;
+    }
+}
+
+async fn user_attachment_export_page(
+    state: &AppState,
+    user_id: UserId,
+    after_attachment_id: Option<&str>,
+) -> Result<Vec<AttachmentResponse>, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "SELECT attachment_id, guild_id, channel_id, owner_id, filename, mime_type, size_bytes, sha256_hex
+             FROM attachments
+             WHERE owner_id = $1 AND ($2::text IS NULL OR attachment_id > $2)
+             ORDER BY attachment_id ASC
+             LIMIT $3",
+        )
+        .bind(user_id.to_string())
+        .bind(after_attachment_id)
+        .bind(export_page_limit()?)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        return rows_to_attachment_responses(rows);
+    }
+
+    let attachments = state.attachments.read().await;
+    let mut page: Vec<_> = attachments
+        .values()
+        .filter(|record| record.owner_id == user_id)
+        .filter(|record| {
+            after_attachment_id.is_none_or(|after| record.attachment_id.as_str() > after)
+        })
+        .collect();
+    page.sort_by(|a, b| a.attachment_id.cmp(&b.attachment_id));
+    page.truncate(MESSAGE_EXPORT_PAGE_SIZE);
+    Ok(page
+        .into_iter()
+        .map(attachment_response_from_record)
+        .collect())
+}
+
+async fn user_message_export_page(
+    state: &AppState,
+    user_id: UserId,
+    after_message_id: Option<&str>,
+) -> Result<Vec<ExportedMessage>, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "SELECT message_id, guild_id, channel_id, author_id, content, content_zstd,
+                    content_sealed, created_at_unix
+             FROM messages
+             WHERE author_id = $1 AND ($2::text IS NULL OR message_id > $2)
+               AND deleted_at_unix IS NULL
+             ORDER BY message_id ASC
+             LIMIT $3",
+        )
+        .bind(user_id.to_string())
+        .bind(after_message_id)
+        .bind(export_page_limit()?)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let mut messages = exported_messages_from_db_rows(state, rows)?;
+        attach_export_media_db(pool, &mut messages).await?;
+        return Ok(messages);
+    }
+
+    let mut messages = {
+        let guilds = state.membership_store.guilds().read().await;
+        let mut page = Vec::new();
+        for (guild_id, guild) in guilds.iter() {
+            for (channel_id, channel) in &guild.channels {
+                page.extend(
+                    channel
+                        .messages
+                        .iter()
+                        .filter(|message| {
+                            message.author_id == user_id && message.deleted_at_unix.is_none()
+                        })
+                        .filter(|message| {
+                            after_message_id.is_none_or(|after| message.id.as_str() > after)
+                        })
+                        .map(|message| (guild_id, channel_id, message)),
+                );
+            }
+        }
+        page.sort_by(|a, b| a.2.id.cmp(&b.2.id));
+        page.truncate(MESSAGE_EXPORT_PAGE_SIZE);
+        page.into_iter()
+            .map(|(guild_id, channel_id, message)| ExportedMessage {
+                message_id: message.id.clone(),
+                guild_id: guild_id.clone(),
+                channel_id: channel_id.clone(),
+                author_id: message.author_id.to_string(),
+                content: message.content.clone(),
+                created_at_unix: message.created_at_unix,
+                attachments: Vec::new(),
+                reactions: reaction_summaries_from_users(&message.reactions, None),
+            })
+            .collect::<Vec<_>>()
+    };
+    attach_export_media_in_memory(state, &mut messages).await;
+    Ok(messages)
+}
+
+fn export_page_limit() -> Result<i64, AuthFailure> {
+    i64::try_from(MESSAGE_EXPORT_PAGE_SIZE).map_err(|_| AuthFailure::Internal)
+}
+
+fn exported_messages_from_db_rows(
+    state: &AppState,
+    rows: Vec<sqlx::postgres::PgRow>,
+) -> Result<Vec<ExportedMessage>, AuthFailure> {
+    let mut messages = Vec::with_capacity(rows.len());
+    for row in rows {
+        let message_id: String = row
+            .try_get("message_id")
+            .map_err(|_| AuthFailure::Internal)?;
+        let content =
+            message_content_from_row(&row, &message_id, state.message_content_cipher.as_deref())?;
+        messages.push(ExportedMessage {
+            message_id,
+            guild_id: row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?,
+            channel_id: row
+                .try_get("channel_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            author_id: row
+                .try_get("author_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            content,
+            created_at_unix: row
+                .try_get("created_at_unix")
+                .map_err(|_| AuthFailure::Internal)?,
+            attachments: Vec::new(),
+            reactions: Vec::new(),
+        });
+    }
+    Ok(messages)
+}
+
+fn export_message_ids_by_guild(messages: &[ExportedMessage]) -> HashMap<String, Vec<String>> {
+    let mut by_guild: HashMap<String, Vec<String>> = HashMap::new();
+    for message in messages {
+        by_guild
+            .entry(message.guild_id.clone())
+            .or_default()
+            .push(message.message_id.clone());
+    }
+    by_guild
+}
+
+async fn attach_export_media_db(
+    pool: &PgPool,
+    messages: &mut [ExportedMessage],
+) -> Result<(), AuthFailure> {
+    for (guild_id, message_ids) in export_message_ids_by_guild(messages) {
+        let mut attachment_map =
+            attachment_map_for_messages_db(pool, &guild_id, None, &message_ids).await?;
+        let mut reaction_map =
+            reaction_map_for_messages_db(pool, &guild_id, None, &message_ids, None).await?;
+        for message in messages
+            .iter_mut()
+            .filter(|message| message.guild_id == guild_id)
+        {
+            if let Some(attachments) = attachment_map.remove(&message.message_id) {
+                message.attachments = attachments;
+            }
+            if let Some(reactions) = reaction_map.remove(&message.message_id) {
+                message.reactions = reactions;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn attach_export_media_in_memory(state: &AppState, messages: &mut [ExportedMessage]) {
+    for (guild_id, message_ids) in export_message_ids_by_guild(messages) {
+        let mut attachment_map =
+            attachment_map_for_messages_in_memory(state, &guild_id, None, &message_ids).await;
+        for message in messages
+            .iter_mut()
+            .filter(|message| message.guild_id == guild_id)
+        {
+            if let Some(attachments) = attachment_map.remove(&message.message_id) {
+                message.attachments = attachments;
+            }
+        }
+    }
+}
+
+/// Serializes one page as newline-delimited JSON.
+pub(crate) fn export_page_ndjson<T: serde::Serialize>(page: &[T]) -> Result<Vec<u8>, AuthFailure> {
+    let mut buffer = Vec::new();
+    for record in page {
+        serde_json::to_writer(&mut buffer, record).map_err(|_| AuthFailure::Internal)?;
+        buffer.push(b'\n');
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::export_page_ndjson;
+    use crate::server::types::{ExportedMessage, FriendRecordResponse, UserExportRecord};
+
+    fn exported_message(message_id: &str, content: &str) -> ExportedMessage {
+        ExportedMessage {
+            message_id: String::from(message_id),
+            guild_id: String::from("g1"),
+            channel_id: String::from("c1"),
+            author_id: String::from("u1"),
+            content: String::from(content),
+            created_at_unix: 1,
+            attachments: Vec::new(),
+            reactions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn export_page_ndjson_writes_one_object_per_line() {
+        let page = vec![
+            exported_message("m1", "first\nline"),
+            exported_message("m2", "second"),
+        ];
+
+        let bytes = export_page_ndjson(&page).expect("page should serialize");
+        let text = String::from_utf8(bytes).expect("ndjson should be utf-8");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: serde_json::Value = serde_json::from_str(lines[0]).expect("line should parse");
+        assert_eq!(first["content"], "first\nline");
+        assert_eq!(first["message_id"], "m1");
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn user_export_records_are_tagged_by_kind() {
+        let records = vec![
+            UserExportRecord::Friend(FriendRecordResponse {
+                user_id: String::from("u2"),
+                username: String::from("friend"),
+                created_at_unix: 0,
+            }),
+            UserExportRecord::Message(exported_message("m1", "hello")),
+        ];
+
+        let bytes = export_page_ndjson(&records).expect("records should serialize");
+        let lines: Vec<serde_json::Value> = String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["kind"], "friend");
+        assert_eq!(lines[0]["username"], "friend");
+        assert_eq!(lines[1]["kind"], "message");
+        assert_eq!(lines[1]["content"], "hello");
+    }
+}
@@ -0,0 +1,480 @@
+use std::time::Duration;
+
+use object_store::{path::Path as ObjectPath, ObjectStoreExt};
+use sqlx::{PgPool, Row};
+use tokio::time::interval;
+
+use crate::server::{
+    auth::now_unix,
+    core::{
+        AppState, SearchOperation, CHANNEL_RETENTION_PRUNE_BATCH,
+        CHANNEL_RETENTION_PRUNE_INTERVAL_SECS, DELETED_MESSAGE_PURGE_BATCH,
+        DELETED_MESSAGE_PURGE_INTERVAL_SECS,
+    },
+    errors::AuthFailure,
+    metrics::record_channel_retention_pruned,
+    realtime::enqueue_search_operation,
+};
+
+/// Removes a message row together with its attachment rows and stored
+/// objects. Reactions go with the row through `ON DELETE CASCADE`. Returns how
+/// many attachments were removed.
+pub(crate) async fn hard_delete_message_db(
+    state: &AppState,
+    pool: &PgPool,
+    guild_id: &str,
+    channel_id: &str,
+    message_id: &str,
+) -> Result<usize, AuthFailure> {
+    let linked_attachment_rows = sqlx::query(
+        "SELECT attachment_id, object_key
+         FROM attachments
+         WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3",
+    )
+    .bind(guild_id)
+    .bind(channel_id)
+    .bind(message_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+
+    sqlx::query(
+        "DELETE FROM messages
+         WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3",
+    )
+    .bind(guild_id)
+    .bind(channel_id)
+    .bind(message_id)
+    .execute(pool)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+    if !linked_attachment_rows.is_empty() {
+        sqlx::query(
+            "DELETE FROM attachments
+             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(message_id)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+    }
+    let removed = linked_attachment_rows.len();
+    for row in linked_attachment_rows {
+        let object_key: String = row
+            .try_get("object_key")
+            .map_err(|_| AuthFailure::Internal)?;
+        let object_path = ObjectPath::from(object_key);
+        let _ = state.attachment_store.delete(&object_path).await;
+    }
+    Ok(removed)
+}
+
+/// Drops in-memory attachment records and their stored objects.
+pub(crate) async fn delete_attachments_in_memory(state: &AppState, attachment_ids: Vec<String>) {
+    if attachment_ids.is_empty() {
+        return;
+    }
+    let mut attachments = state.attachments.write().await;
+    let mut object_keys = Vec::new();
+    for attachment_id in attachment_ids {
+        if let Some(record) = attachments.remove(&attachment_id) {
+            object_keys.push(record.object_key);
+        }
+    }
+    drop(attachments);
+    for object_key in object_keys {
+        let object_path = ObjectPath::from(object_key);
+        let _ = state.attachment_store.delete(&object_path).await;
+    }
+}
+
+/// Hard-deletes tombstoned messages whose retention window ended at or before
+/// `now`. Returns how many messages were purged.
+pub(crate) async fn purge_expired_deleted_messages(
+    state: &AppState,
+    now: i64,
+) -> Result<usize, AuthFailure> {
+    let retention = state.runtime.deleted_message_retention;
+    if retention.is_zero() {
+        return Ok(0);
+    }
+    let cutoff = now.saturating_sub(i64::try_from(retention.as_secs()).unwrap_or(i64::MAX));
+
+    if let Some(pool) = &state.db_pool {
+        let batch =
+            i64::try_from(DELETED_MESSAGE_PURGE_BATCH).map_err(|_| AuthFailure::Internal)?;
+        let rows = sqlx::query(
+            "SELECT guild_id, channel_id, message_id
+             FROM messages
+             WHERE deleted_at_unix IS NOT NULL AND deleted_at_unix <= $1
+             ORDER BY deleted_at_unix ASC
+             LIMIT $2",
+        )
+        .bind(cutoff)
+        .bind(batch)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let purged = rows.len();
+        for row in rows {
+            let guild_id: String = row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?;
+            let channel_id: String = row
+                .try_get("channel_id")
+                .map_err(|_| AuthFailure::Internal)?;
+            let message_id: String = row
+                .try_get("message_id")
+                .map_err(|_| AuthFailure::Internal)?;
+            hard_delete_message_db(state, pool, &guild_id, &channel_id, &message_id).await?;
+        }
+        return Ok(purged);
+    }
+
+    let mut purged = 0;
+    let mut attachment_ids = Vec::new();
+    {
+        let mut guilds = state.membership_store.guilds().write().await;
+        for guild in guilds.values_mut() {
+            for channel in guild.channels.values_mut() {
+                channel.messages.retain(|message| {
+                    let expired = message
+                        .deleted_at_unix
+                        .is_some_and(|deleted_at| deleted_at <= cutoff);
+                    if expired {
+                        purged += 1;
+                        attachment_ids.extend(message.attachment_ids.iter().cloned());
+                    }
+                    !expired
+                });
+            }
+        }
+    }
+    delete_attachments_in_memory(state, attachment_ids).await;
+    Ok(purged)
+}
+
+/// Background task that purges expired tombstones. Exits immediately when
+/// deletes are configured to be immediate.
+pub(crate) async fn start_deleted_message_purge(state: AppState) {
+    if state.runtime.deleted_message_retention.is_zero() {
+        return;
+    }
+
+    let mut ticker = interval(Duration::from_secs(DELETED_MESSAGE_PURGE_INTERVAL_SECS));
+    loop {
+        ticker.tick().await;
+        match purge_expired_deleted_messages(&state, now_unix()).await {
+            Ok(0) => {}
+            Ok(purged) => {
+                tracing::info!(event = "messages.tombstone.purge", purged);
+            }
+            Err(error) => {
+                tracing::warn!(
+                    event = "messages.tombstone.purge",
+                    outcome = "failed",
+                    error = %error
+                );
+            }
+        }
+    }
+}
+
+/// Hard-deletes up to `CHANNEL_RETENTION_PRUNE_BATCH` messages that are older
+/// than their channel's `retention_secs`, along with their attachments and
+/// search documents. Returns how many messages were pruned.
+pub(crate) async fn prune_channel_retention(
+    state: &AppState,
+    now: i64,
+) -> Result<usize, AuthFailure> {
+    let mut pruned_message_ids = Vec::new();
+    let pruned_attachments;
+
+    if let Some(pool) = &state.db_pool {
+        let batch =
+            i64::try_from(CHANNEL_RETENTION_PRUNE_BATCH).map_err(|_| AuthFailure::Internal)?;
+        let rows = sqlx::query(
+            "SELECT m.guild_id, m.channel_id, m.message_id
+             FROM messages m
+             JOIN channels c ON c.guild_id = m.guild_id AND c.channel_id = m.channel_id
+             WHERE c.retention_secs IS NOT NULL
+               AND m.created_at_unix <= $1 - c.retention_secs
+             ORDER BY m.created_at_unix ASC
+             LIMIT $2",
+        )
+        .bind(now)
+        .bind(batch)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let mut removed_attachments = 0;
+        for row in rows {
+            let guild_id: String = row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?;
+            let channel_id: String = row
+                .try_get("channel_id")
+                .map_err(|_| AuthFailure::Internal)?;
+            let message_id: String = row
+                .try_get("message_id")
+                .map_err(|_| AuthFailure::Internal)?;
+            removed_attachments +=
+                hard_delete_message_db(state, pool, &guild_id, &channel_id, &message_id).await?;
+            pruned_message_ids.push(message_id);
+        }
+        pruned_attachments = removed_attachments;
+    } else {
+        let mut attachment_ids = Vec::new();
+        {
+            let mut remaining = CHANNEL_RETENTION_PRUNE_BATCH;
+            let mut guilds = state.membership_store.guilds().write().await;
+            for guild in guilds.values_mut() {
+                for channel in guild.channels.values_mut() {
+                    let Some(retention_secs) = channel.retention_secs else {
+                        continue;
+                    };
+                    let cutoff =
+                        now.saturating_sub(i64::try_from(retention_secs).unwrap_or(i64::MAX));
+                    channel.messages.retain(|message| {
+                        let expired = remaining > 0 && message.created_at_unix <= cutoff;
+                        if expired {
+                            remaining -= 1;
+                            pruned_message_ids.push(message.id.clone());
+                            attachment_ids.extend(message.attachment_ids.iter().cloned());
+                        }
+                        !expired
+                    });
+                }
+            }
+        }
+        pruned_attachments = attachment_ids.len();
+        delete_attachments_in_memory(state, attachment_ids).await;
+    }
+
+    let pruned = pruned_message_ids.len();
+    if pruned == 0 {
+        return Ok(0);
+    }
+    record_channel_retention_pruned("messages", u64::try_from(pruned).unwrap_or(u64::MAX));
+    record_channel_retention_pruned(
+        "attachments",
+        u64::try_from(pruned_attachments).unwrap_or(u64::MAX),
+    );
+    enqueue_search_operation(
+        state,
+        SearchOperation::Reconcile {
+            upserts: Vec::new(),
+            delete_message_ids: pruned_message_ids,
+        },
+        true,
+    )
+    .await?;
+    Ok(pruned)
+}
+
+/// Background task that enforces per-channel retention. A full batch is
+/// followed immediately by another so a backlog drains between ticks.
+pub(crate) async fn start_channel_retention_prune(state: AppState) {
+    let mut ticker = interval(Duration::from_secs(CHANNEL_RETENTION_PRUNE_INTERVAL_SECS));
+    loop {
+        ticker.tick().await;
+        loop {
+            match prune_channel_retention(&state, now_unix()).await {
+                Ok(0) => break,
+                Ok(pruned) => {
+                    tracing::info!(event = "messages.retention.prune", pruned);
+                    if pruned < CHANNEL_RETENTION_PRUNE_BATCH {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        event = "messages.retention.prune",
+                        outcome = "failed",
+                        error = %error
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        time::Duration,
+    };
+
+    use filament_core::{ChannelKind, Role, UserId};
+
+    use super::{prune_channel_retention, purge_expired_deleted_messages};
+    use crate::server::core::{
+        AppConfig, AppState, ChannelRecord, GuildRecord, GuildVisibility, MessageRecord,
+        CHANNEL_RETENTION_PRUNE_BATCH,
+    };
+
+    fn message(id: &str, author_id: UserId, deleted_at_unix: Option<i64>) -> MessageRecord {
+        message_at(id, author_id, 1, deleted_at_unix)
+    }
+
+    fn message_at(
+        id: &str,
+        author_id: UserId,
+        created_at_unix: i64,
+        deleted_at_unix: Option<i64>,
+    ) -> MessageRecord {
+        MessageRecord {
+            id: String::from(id),
+            author_id,
+            content: String::from("hello"),
+            markdown_tokens: Vec::new(),
+            attachment_ids: Vec::new(),
+            created_at_unix,
+            reactions: HashMap::new(),
+            deleted_at_unix,
+            edited_at_unix: None,
+            system: false,
+        }
+    }
+
+    async fn state_with_messages(retention: Duration, messages: Vec<MessageRecord>) -> AppState {
+        let state = AppState::new(&AppConfig {
+            deleted_message_retention: retention,
+            ..AppConfig::default()
+        })
+        .expect("state initializes");
+        let owner = messages
+            .first()
+            .map_or_else(UserId::new, |message| message.author_id);
+        state.membership_store.guilds().write().await.insert(
+            String::from("g1"),
+            GuildRecord {
+                name: String::from("Guild"),
+                visibility: GuildVisibility::Private,
+                created_by_user_id: owner,
+                default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
+                members: HashMap::from([(owner, Role::Owner)]),
+                banned_members: HashSet::new(),
+                channels: HashMap::from([(
+                    String::from("c1"),
+                    ChannelRecord {
+                        name: String::from("general"),
+                        kind: ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
+                        messages,
+                        role_overrides: HashMap::new(),
+                    },
+                )]),
+            },
+        );
+        state
+    }
+
+    async fn remaining_ids(state: &AppState) -> Vec<String> {
+        let guilds = state.membership_store.guilds().read().await;
+        guilds["g1"].channels["c1"]
+            .messages
+            .iter()
+            .map(|message| message.id.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn purge_removes_only_tombstones_past_the_retention_window() {
+        let author = UserId::new();
+        let state = state_with_messages(
+            Duration::from_secs(100),
+            vec![
+                message("m1", author, None),
+                message("m2", author, Some(1_000)),
+                message("m3", author, Some(1_050)),
+            ],
+        )
+        .await;
+
+        let purged = purge_expired_deleted_messages(&state, 1_100)
+            .await
+            .expect("purge should succeed");
+        assert_eq!(purged, 1);
+        assert_eq!(remaining_ids(&state).await, ["m1", "m3"]);
+    }
+
+    #[tokio::test]
+    async fn purge_is_a_no_op_when_retention_is_disabled() {
+        let author = UserId::new();
+        let state = state_with_messages(Duration::ZERO, vec![message("m1", author, Some(1))]).await;
+
+        let purged = purge_expired_deleted_messages(&state, i64::MAX)
+            .await
+            .expect("purge should succeed");
+        assert_eq!(purged, 0);
+        assert_eq!(remaining_ids(&state).await, ["m1"]);
+    }
+
+    async fn set_channel_retention(state: &AppState, retention_secs: Option<u64>) {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let channel = guilds
+            .get_mut("g1")
+            .and_then(|guild| guild.channels.get_mut("c1"))
+            .expect("channel exists");
+        channel.retention_secs = retention_secs;
+    }
+
+    #[tokio::test]
+    async fn prune_removes_messages_past_the_channel_retention_window() {
+        let author = UserId::new();
+        let state = state_with_messages(
+            Duration::ZERO,
+            vec![
+                message_at("m1", author, 900, None),
+                message_at("m2", author, 1_000, Some(1_050)),
+                message_at("m3", author, 1_001, None),
+            ],
+        )
+        .await;
+        set_channel_retention(&state, Some(100)).await;
+
+        let pruned = prune_channel_retention(&state, 1_100)
+            .await
+            .expect("prune should succeed");
+        assert_eq!(pruned, 2);
+        assert_eq!(remaining_ids(&state).await, ["m3"]);
+    }
+
+    #[tokio::test]
+    async fn prune_leaves_channels_without_retention_untouched() {
+        let author = UserId::new();
+        let state =
+            state_with_messages(Duration::ZERO, vec![message_at("m1", author, 1, None)]).await;
+
+        let pruned = prune_channel_retention(&state, i64::MAX)
+            .await
+            .expect("prune should succeed");
+        assert_eq!(pruned, 0);
+        assert_eq!(remaining_ids(&state).await, ["m1"]);
+    }
+
+    #[tokio::test]
+    async fn prune_stops_at_the_batch_limit() {
+        let author = UserId::new();
+        let messages = (0..=CHANNEL_RETENTION_PRUNE_BATCH)
+            .map(|index| message_at(&format!("m{index:04}"), author, 1, None))
+            .collect();
+        let state = state_with_messages(Duration::ZERO, messages).await;
+        set_channel_retention(&state, Some(60)).await;
+
+        let pruned = prune_channel_retention(&state, 1_000)
+            .await
+            .expect("prune should succeed");
+        assert_eq!(pruned, CHANNEL_RETENTION_PRUNE_BATCH);
+        assert_eq!(remaining_ids(&state).await.len(), 1);
+    }
+}
@@ -1,11 +1,14 @@
+use std::time::Duration;
+
 use crate::server::{
     auth::{now_unix, ClientIp},
-    core::AppState,
+    core::{AppState, GUILD_MUTE_PURGE_INTERVAL_SECS},
     directory_contract::IpNetwork,
     errors::AuthFailure,
 };
 use filament_core::UserId;
 use sqlx::Row;
+use tokio::time::interval;
 
 use super::write_audit_log;
 
@@ -83,18 +86,160 @@ pub(crate) async fn enforce_guild_ip_ban_for_request(
     Err(AuthFailure::Forbidden)
 }
 
+/// Returns when the member's mute lifts, or `None` when they are not muted.
+/// Expired mutes that the purge task has not reached yet are ignored.
+pub(crate) async fn active_guild_mute_expiry(
+    state: &AppState,
+    guild_id: &str,
+    user_id: UserId,
+) -> Result<Option<i64>, AuthFailure> {
+    let now = now_unix();
+    if let Some(pool) = &state.db_pool {
+        let row = sqlx::query(
+            "SELECT expires_at_unix
+             FROM guild_mutes
+             WHERE guild_id = $1 AND user_id = $2 AND expires_at_unix > $3",
+        )
+        .bind(guild_id)
+        .bind(user_id.to_string())
+        .bind(now)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        return row
+            .map(|row| row.try_get("expires_at_unix"))
+            .transpose()
+            .map_err(|_| AuthFailure::Internal);
+    }
+
+    let mutes = state.guild_mutes.read().await;
+    Ok(mutes
+        .get(guild_id)
+        .and_then(|guild_mutes| guild_mutes.get(&user_id))
+        .map(|mute| mute.expires_at_unix)
+        .filter(|expires| *expires > now))
+}
+
+/// Rejects posting and reacting while the member is muted in the guild.
+pub(crate) async fn enforce_guild_mute(
+    state: &AppState,
+    guild_id: &str,
+    user_id: UserId,
+) -> Result<(), AuthFailure> {
+    if active_guild_mute_expiry(state, guild_id, user_id)
+        .await?
+        .is_some()
+    {
+        return Err(AuthFailure::Forbidden);
+    }
+    Ok(())
+}
+
+pub(crate) async fn purge_expired_guild_mutes(
+    state: &AppState,
+    now: i64,
+) -> Result<u64, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let result = sqlx::query("DELETE FROM guild_mutes WHERE expires_at_unix <= $1")
+            .bind(now)
+            .execute(pool)
+            .await
+            .map_err(|_| AuthFailure::Internal)?;
+        return Ok(result.rows_affected());
+    }
+
+    let mut purged = 0_u64;
+    let mut mutes = state.guild_mutes.write().await;
+    mutes.retain(|_, guild_mutes| {
+        guild_mutes.retain(|_, mute| {
+            let expired = mute.expires_at_unix <= now;
+            if expired {
+                purged += 1;
+            }
+            !expired
+        });
+        !guild_mutes.is_empty()
+    });
+    Ok(purged)
+}
+
+/// Background task that drops expired mutes so the table does not grow
+/// without bound.
+pub(crate) async fn start_guild_mute_purge(state: AppState) {
+    let mut ticker = interval(Duration::from_secs(GUILD_MUTE_PURGE_INTERVAL_SECS));
+    loop {
+        ticker.tick().await;
+        match purge_expired_guild_mutes(&state, now_unix()).await {
+            Ok(0) => {}
+            Ok(purged) => {
+                tracing::info!(event = "moderation.mute.purge", purged);
+            }
+            Err(error) => {
+                tracing::warn!(
+                    event = "moderation.mute.purge",
+                    outcome = "failed",
+                    error = %error
+                );
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::guild_has_active_ip_ban_for_client;
+    use super::{
+        active_guild_mute_expiry, guild_has_active_ip_ban_for_client, purge_expired_guild_mutes,
+    };
     use crate::server::{
         auth::resolve_client_ip,
-        core::{AppConfig, AppState, GuildIpBanRecord},
+        core::{AppConfig, AppState, GuildIpBanRecord, GuildMuteRecord},
         directory_contract::IpNetwork,
     };
     use axum::http::HeaderMap;
     use filament_core::UserId;
+    use std::collections::HashMap;
     use ulid::Ulid;
 
+    #[tokio::test]
+    async fn expired_guild_mutes_are_ignored_and_purged() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let moderator = UserId::new();
+        let muted = UserId::new();
+        let lapsed = UserId::new();
+        let now = crate::server::auth::now_unix();
+        let mute = |expires_at_unix| GuildMuteRecord {
+            muted_by_user_id: moderator,
+            created_at_unix: now - 120,
+            expires_at_unix,
+        };
+        state.guild_mutes.write().await.insert(
+            String::from("g1"),
+            HashMap::from([(muted, mute(now + 600)), (lapsed, mute(now - 1))]),
+        );
+
+        assert_eq!(
+            active_guild_mute_expiry(&state, "g1", muted).await.unwrap(),
+            Some(now + 600)
+        );
+        assert_eq!(
+            active_guild_mute_expiry(&state, "g1", lapsed)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            active_guild_mute_expiry(&state, "g2", muted).await.unwrap(),
+            None
+        );
+
+        assert_eq!(purge_expired_guild_mutes(&state, now).await.unwrap(), 1);
+        assert_eq!(
+            purge_expired_guild_mutes(&state, now + 600).await.unwrap(),
+            1
+        );
+        assert!(state.guild_mutes.read().await.is_empty());
+    }
+
     #[tokio::test]
     async fn guild_ip_ban_matching_handles_ipv4_and_ipv6_host_observations() {
         let state = AppState::new(&AppConfig::default()).expect("state initializes");
@@ -0,0 +1,471 @@
+use std::collections::{HashSet, VecDeque};
+
+use filament_core::UserId;
+use sqlx::{PgPool, Row};
+use ulid::Ulid;
+
+use crate::server::{
+    auth::now_unix,
+    core::{AppState, NotificationRecord, MAX_MENTION_RECIPIENTS, MAX_NOTIFICATIONS_PER_USER},
+    errors::AuthFailure,
+};
+
+use super::{channel_readers, enqueue_offline_push, MentionScope};
+
+/// Users a mention reaches: up to [`MAX_MENTION_RECIPIENTS`] members for
+/// `@everyone`, and for `@here` only members with a gateway connection
+/// watching the guild. Either way only members who can read the channel are
+/// kept, the same audience as the `message_mention` event. The author is
+/// never included.
+async fn mention_recipients(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+    author_id: UserId,
+    scope: MentionScope,
+) -> Result<Vec<UserId>, AuthFailure> {
+    let candidates: HashSet<UserId> = match scope {
+        MentionScope::Everyone => {
+            if let Some(pool) = &state.db_pool {
+                let rows = sqlx::query(
+                    "SELECT user_id FROM guild_members
+                     WHERE guild_id = $1 AND user_id <> $2
+                     ORDER BY user_id
+                     LIMIT $3",
+                )
+                .bind(guild_id)
+                .bind(author_id.to_string())
+                .bind(i64::try_from(MAX_MENTION_RECIPIENTS).map_err(|_| AuthFailure::Internal)?)
+                .fetch_all(pool)
+                .await
+                .map_err(|_| AuthFailure::Internal)?;
+                let mut user_ids = HashSet::with_capacity(rows.len());
+                for row in rows {
+                    let user_id: String =
+                        row.try_get("user_id").map_err(|_| AuthFailure::Internal)?;
+                    user_ids.insert(UserId::try_from(user_id).map_err(|_| AuthFailure::Internal)?);
+                }
+                user_ids
+            } else {
+                state
+                    .membership_store
+                    .guilds()
+                    .read()
+                    .await
+                    .get(guild_id)
+                    .map(|guild| {
+                        guild
+                            .members
+                            .keys()
+                            .copied()
+                            .filter(|user_id| *user_id != author_id)
+                            .take(MAX_MENTION_RECIPIENTS)
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+        }
+        MentionScope::Here => {
+            let guild_connections = state.realtime_registry.guild_connections().read().await;
+            let presence = state.realtime_registry.connection_presence().read().await;
+            guild_connections
+                .get(guild_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|connection_id| presence.get(connection_id))
+                .map(|entry| entry.user_id)
+                .filter(|user_id| *user_id != author_id)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .take(MAX_MENTION_RECIPIENTS)
+                .collect()
+        }
+    };
+    let recipients = channel_readers(state, guild_id, channel_id, candidates).await?;
+    Ok(recipients.into_iter().collect())
+}
+
+async fn insert_notifications_db(
+    pool: &PgPool,
+    recipients: &[UserId],
+    template: &NotificationRecord,
+) -> Result<(), AuthFailure> {
+    let notification_ids: Vec<String> =
+        recipients.iter().map(|_| Ulid::new().to_string()).collect();
+    let user_ids: Vec<String> = recipients.iter().map(ToString::to_string).collect();
+    let mut tx = pool.begin().await.map_err(|_| AuthFailure::Internal)?;
+    sqlx::query(
+        "INSERT INTO notifications (notification_id, user_id, guild_id, channel_id, message_id, author_id, scope, created_at_unix)
+         SELECT recipient.notification_id, recipient.user_id, $3, $4, $5, $6, $7, $8
+         FROM UNNEST($1::text[], $2::text[]) AS recipient(notification_id, user_id)
+         ON CONFLICT (user_id, message_id) DO NOTHING",
+    )
+    .bind(&notification_ids)
+    .bind(&user_ids)
+    .bind(&template.guild_id)
+    .bind(&template.channel_id)
+    .bind(&template.message_id)
+    .bind(template.author_id.to_string())
+    .bind(&template.scope)
+    .bind(template.created_at_unix)
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+    sqlx::query(
+        "DELETE FROM notifications
+         WHERE notification_id IN (
+             SELECT notification_id FROM (
+                 SELECT notification_id,
+                        ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY notification_id DESC) AS position
+                 FROM notifications
+                 WHERE user_id = ANY($1)
+             ) ranked
+             WHERE ranked.position > $2
+         )",
+    )
+    .bind(&user_ids)
+    .bind(i64::try_from(MAX_NOTIFICATIONS_PER_USER).map_err(|_| AuthFailure::Internal)?)
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+    tx.commit().await.map_err(|_| AuthFailure::Internal)
+}
+
+fn push_notification(inbox: &mut VecDeque<NotificationRecord>, record: NotificationRecord) {
+    if inbox
+        .iter()
+        .any(|existing| existing.message_id == record.message_id)
+    {
+        return;
+    }
+    inbox.push_back(record);
+    while inbox.len() > MAX_NOTIFICATIONS_PER_USER {
+        inbox.pop_front();
+    }
+}
+
+/// Writes an inbox entry for everyone a mention reaches and queues a push
+/// for those who are offline. Returns how many users were targeted.
+pub(crate) async fn record_mention_notifications(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+    message_id: &str,
+    author_id: UserId,
+    scope: MentionScope,
+) -> Result<usize, AuthFailure> {
+    let recipients = mention_recipients(state, guild_id, channel_id, author_id, scope).await?;
+    if recipients.is_empty() {
+        return Ok(0);
+    }
+    let template = NotificationRecord {
+        notification_id: String::new(),
+        guild_id: guild_id.to_owned(),
+        channel_id: channel_id.to_owned(),
+        message_id: message_id.to_owned(),
+        author_id,
+        scope: scope.as_str().to_owned(),
+        created_at_unix: now_unix(),
+        read_at_unix: None,
+    };
+
+    if let Some(pool) = &state.db_pool {
+        insert_notifications_db(pool, &recipients, &template).await?;
+    } else {
+        let mut notifications = state.notifications.write().await;
+        for user_id in &recipients {
+            push_notification(
+                notifications.entry(*user_id).or_default(),
+                NotificationRecord {
+                    notification_id: Ulid::new().to_string(),
+                    ..template.clone()
+                },
+            );
+        }
+    }
+
+    let payload = serde_json::json!({
+        "kind": "mention",
+        "guild_id": template.guild_id,
+        "channel_id": template.channel_id,
+        "message_id": template.message_id,
+        "scope": template.scope,
+    });
+    if let Ok(payload) = serde_json::to_vec(&payload) {
+        enqueue_offline_push(state, &recipients, payload).await;
+    }
+    Ok(recipients.len())
+}
+
+fn notification_from_row(row: &sqlx::postgres::PgRow) -> Result<NotificationRecord, AuthFailure> {
+    let author_id: String = row
+        .try_get("author_id")
+        .map_err(|_| AuthFailure::Internal)?;
+    Ok(NotificationRecord {
+        notification_id: row
+            .try_get("notification_id")
+            .map_err(|_| AuthFailure::Internal)?,
+        guild_id: row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?,
+        channel_id: row
+            .try_get("channel_id")
+            .map_err(|_| AuthFailure::Internal)?,
+        message_id: row
+            .try_get("message_id")
+            .map_err(|_| AuthFailure::Internal)?,
+        author_id: UserId::try_from(author_id).map_err(|_| AuthFailure::Internal)?,
+        scope: row.try_get("scope").map_err(|_| AuthFailure::Internal)?,
+        created_at_unix: row
+            .try_get("created_at_unix")
+            .map_err(|_| AuthFailure::Internal)?,
+        read_at_unix: row
+            .try_get("read_at_unix")
+            .map_err(|_| AuthFailure::Internal)?,
+    })
+}
+
+/// Lists a user's notifications newest first, plus their total unread count.
+pub(crate) async fn list_user_notifications(
+    state: &AppState,
+    user_id: UserId,
+    before: Option<&str>,
+    unread_only: bool,
+    limit: usize,
+) -> Result<(Vec<NotificationRecord>, u64), AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "SELECT notification_id, guild_id, channel_id, message_id, author_id, scope, created_at_unix, read_at_unix
+             FROM notifications
+             WHERE user_id = $1
+               AND ($2::text IS NULL OR notification_id < $2)
+               AND (NOT $3 OR read_at_unix IS NULL)
+             ORDER BY notification_id DESC
+             LIMIT $4",
+        )
+        .bind(user_id.to_string())
+        .bind(before)
+        .bind(unread_only)
+        .bind(i64::try_from(limit).map_err(|_| AuthFailure::Internal)?)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let records = rows
+            .iter()
+            .map(notification_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        let unread: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at_unix IS NULL",
+        )
+        .bind(user_id.to_string())
+        .fetch_one(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        return Ok((
+            records,
+            u64::try_from(unread).map_err(|_| AuthFailure::Internal)?,
+        ));
+    }
+
+    let notifications = state.notifications.read().await;
+    let Some(inbox) = notifications.get(&user_id) else {
+        return Ok((Vec::new(), 0));
+    };
+    let records = inbox
+        .iter()
+        .rev()
+        .filter(|record| {
+            before.is_none_or(|before| record.notification_id.as_str() < before)
+                && (!unread_only || record.read_at_unix.is_none())
+        })
+        .take(limit)
+        .cloned()
+        .collect();
+    let unread = inbox
+        .iter()
+        .filter(|record| record.read_at_unix.is_none())
+        .count();
+    Ok((
+        records,
+        u64::try_from(unread).map_err(|_| AuthFailure::Internal)?,
+    ))
+}
+
+/// Marks the caller's own notifications read; ids belonging to other users
+/// or already read are ignored.
+pub(crate) async fn mark_user_notifications_read(
+    state: &AppState,
+    user_id: UserId,
+    notification_ids: &[String],
+) -> Result<(), AuthFailure> {
+    let now = now_unix();
+    if let Some(pool) = &state.db_pool {
+        sqlx::query(
+            "UPDATE notifications
+             SET read_at_unix = $3
+             WHERE user_id = $1 AND notification_id = ANY($2) AND read_at_unix IS NULL",
+        )
+        .bind(user_id.to_string())
+        .bind(notification_ids)
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        return Ok(());
+    }
+
+    let mut notifications = state.notifications.write().await;
+    if let Some(inbox) = notifications.get_mut(&user_id) {
+        for record in inbox.iter_mut().filter(|record| {
+            record.read_at_unix.is_none() && notification_ids.contains(&record.notification_id)
+        }) {
+            record.read_at_unix = Some(now);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    use filament_core::{
+        ChannelKind, ChannelPermissionOverwrite, Permission, PermissionSet, Role, UserId,
+    };
+
+    use super::{push_notification, record_mention_notifications};
+    use crate::server::{
+        core::{
+            AppConfig, AppState, ChannelRecord, GuildRecord, GuildVisibility, NotificationRecord,
+            MAX_MENTION_RECIPIENTS, MAX_NOTIFICATIONS_PER_USER,
+        },
+        domain::MentionScope,
+    };
+
+    fn record(notification_id: usize, message_id: &str) -> NotificationRecord {
+        NotificationRecord {
+            notification_id: format!("{notification_id:026}"),
+            guild_id: String::from("g"),
+            channel_id: String::from("c"),
+            message_id: message_id.to_owned(),
+            author_id: UserId::new(),
+            scope: String::from("everyone"),
+            created_at_unix: 0,
+            read_at_unix: None,
+        }
+    }
+
+    #[test]
+    fn push_notification_skips_duplicate_messages() {
+        let mut inbox = VecDeque::new();
+        push_notification(&mut inbox, record(1, "m1"));
+        push_notification(&mut inbox, record(2, "m1"));
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].notification_id, format!("{:026}", 1));
+    }
+
+    #[test]
+    fn push_notification_prunes_oldest_past_cap() {
+        let mut inbox = VecDeque::new();
+        for index in 0..=MAX_NOTIFICATIONS_PER_USER {
+            push_notification(&mut inbox, record(index, &format!("m{index}")));
+        }
+        assert_eq!(inbox.len(), MAX_NOTIFICATIONS_PER_USER);
+        assert_eq!(inbox[0].message_id, "m1");
+    }
+
+    async fn state_with_guild(owner_id: UserId, member_ids: &[UserId]) -> AppState {
+        let state = AppState::new(&AppConfig::default()).unwrap();
+        let mut guild = GuildRecord {
+            name: String::from("Mentions"),
+            visibility: GuildVisibility::Private,
+            created_by_user_id: owner_id,
+            default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
+            members: HashMap::new(),
+            banned_members: HashSet::new(),
+            channels: HashMap::new(),
+        };
+        guild.members.insert(owner_id, Role::Owner);
+        for member_id in member_ids {
+            guild.members.insert(*member_id, Role::Member);
+        }
+        guild.channels.insert(
+            String::from("c"),
+            ChannelRecord {
+                name: String::from("general"),
+                kind: ChannelKind::Text,
+                locked: false,
+                retention_secs: None,
+                voting: false,
+                category_id: None,
+                messages: Vec::new(),
+                role_overrides: HashMap::new(),
+            },
+        );
+        state
+            .membership_store
+            .guilds()
+            .write()
+            .await
+            .insert(String::from("g"), guild);
+        state
+    }
+
+    #[tokio::test]
+    async fn everyone_mentions_skip_members_who_cannot_read_the_channel() {
+        let owner_id = UserId::new();
+        let (reader_id, hidden_id) = (UserId::new(), UserId::new());
+        let state = state_with_guild(owner_id, &[reader_id, hidden_id]).await;
+        let mut deny = PermissionSet::empty();
+        deny.insert(Permission::CreateMessage);
+        state
+            .membership_store
+            .guild_channel_permission_overrides()
+            .write()
+            .await
+            .entry(String::from("g"))
+            .or_default()
+            .entry(String::from("c"))
+            .or_default()
+            .member_overrides
+            .insert(
+                hidden_id,
+                ChannelPermissionOverwrite {
+                    allow: PermissionSet::empty(),
+                    deny,
+                },
+            );
+
+        let notified =
+            record_mention_notifications(&state, "g", "c", "m1", owner_id, MentionScope::Everyone)
+                .await
+                .unwrap();
+        assert_eq!(notified, 1);
+        let inboxes = state.notifications.read().await;
+        assert_eq!(inboxes.get(&reader_id).map(VecDeque::len), Some(1));
+        assert!(!inboxes.contains_key(&hidden_id));
+        assert!(!inboxes.contains_key(&owner_id));
+    }
+
+    #[tokio::test]
+    async fn everyone_mentions_stop_at_the_recipient_cap() {
+        let owner_id = UserId::new();
+        let member_ids: Vec<UserId> = (0..MAX_MENTION_RECIPIENTS + 3)
+            .map(|_| UserId::new())
+            .collect();
+        let state = state_with_guild(owner_id, &member_ids).await;
+
+        let notified =
+            record_mention_notifications(&state, "g", "c", "m1", owner_id, MentionScope::Everyone)
+                .await
+                .unwrap();
+        assert_eq!(notified, MAX_MENTION_RECIPIENTS);
+        assert_eq!(
+            state.notifications.read().await.len(),
+            MAX_MENTION_RECIPIENTS
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Guards for server-initiated outbound HTTP to user-supplied destinations.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use reqwest::{redirect::Policy, ClientBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutboundTargetError {
+    /// DNS lookup failed or timed out; worth retrying later.
+    Unresolved,
+    /// No addresses, or at least one is not publicly routable.
+    Blocked,
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b == 18 || b == 19))
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(mapped) = ip.to_ipv4_mapped() {
+        return is_public_ipv4(mapped);
+    }
+    let segments = ip.segments();
+    // NAT64 (64:ff9b::/96) and 6to4 (2002::/16) embed an IPv4 destination.
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [_, _, _, _, _, _, hi, lo] = segments;
+        return is_public_ipv4(Ipv4Addr::from((u32::from(hi) << 16) | u32::from(lo)));
+    }
+    if segments[0] == 0x2002 {
+        return is_public_ipv4(Ipv4Addr::from(
+            (u32::from(segments[1]) << 16) | u32::from(segments[2]),
+        ));
+    }
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+}
+
+pub(crate) fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+/// Resolve `host:port` and return a client builder pinned to the resolved
+/// addresses, so a second DNS answer cannot retarget the connection.
+///
+/// Every resolved address must be public. Redirects and environment proxies
+/// are disabled, and `timeout` bounds both the lookup and each request.
+pub(crate) async fn pinned_public_client(
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<ClientBuilder, OutboundTargetError> {
+    let lookup_host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> =
+        match tokio::time::timeout(timeout, tokio::net::lookup_host((lookup_host, port))).await {
+            Ok(Ok(addrs)) => addrs.collect(),
+            _ => return Err(OutboundTargetError::Unresolved),
+        };
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err(OutboundTargetError::Blocked);
+    }
+    Ok(reqwest::Client::builder()
+        .redirect(Policy::none())
+        .no_proxy()
+        .timeout(timeout)
+        .resolve_to_addrs(host, &addrs))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+        time::Duration,
+    };
+
+    use tokio::net::TcpListener;
+
+    use super::{is_public_ip, pinned_public_client, OutboundTargetError};
+
+    #[test]
+    fn public_ip_check_rejects_internal_ranges() {
+        for blocked in [
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3)),
+            IpAddr::V4(Ipv4Addr::new(172, 16, 0, 1)),
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+            IpAddr::V4(Ipv4Addr::new(169, 254, 169, 254)),
+            IpAddr::V4(Ipv4Addr::new(100, 64, 0, 1)),
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(Ipv6Addr::LOCALHOST),
+            IpAddr::V6("fd00::1".parse().expect("valid ipv6")),
+            IpAddr::V6("fe80::1".parse().expect("valid ipv6")),
+            IpAddr::V6("::ffff:127.0.0.1".parse().expect("valid ipv6")),
+            IpAddr::V6("64:ff9b::a00:1".parse().expect("valid ipv6")),
+            IpAddr::V6("2002:c0a8:101::1".parse().expect("valid ipv6")),
+        ] {
+            assert!(!is_public_ip(blocked), "{blocked} should be blocked");
+        }
+        assert!(is_public_ip(IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34))));
+        assert!(is_public_ip(IpAddr::V6(
+            "2606:4700::1111".parse().expect("valid ipv6")
+        )));
+    }
+
+    #[tokio::test]
+    async fn pinned_client_refuses_internal_destinations() {
+        for host in ["127.0.0.1", "[::1]", "localhost", "10.0.0.1"] {
+            let result = pinned_public_client(host, 443, Duration::from_secs(2)).await;
+            assert_eq!(
+                result.err(),
+                Some(OutboundTargetError::Blocked),
+                "{host} should be blocked"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn pinned_client_ignores_proxy_environment() {
+        let proxy = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("proxy should bind");
+        let proxy_url = format!("http://{}", proxy.local_addr().expect("proxy address"));
+        std::env::set_var("HTTPS_PROXY", &proxy_url);
+        let client = pinned_public_client("93.184.216.34", 443, Duration::from_millis(300))
+            .await
+            .expect("public address should be allowed")
+            .build();
+        std::env::remove_var("HTTPS_PROXY");
+        let client = client.expect("client should build");
+
+        let _ = client.get("https://93.184.216.34/").send().await;
+        let proxied = tokio::time::timeout(Duration::from_millis(100), proxy.accept()).await;
+        assert!(
+            proxied.is_err(),
+            "pinned client must not route through an environment proxy"
+        );
+    }
+}
@@ -492,15 +492,18 @@ pub(crate) fn resolve_db_channel_permissions(
         override_target_member,
     )?;
 
+    let mut unknown_override_bits = override_summary.unknown_override_bits;
     let role_overwrite = if override_summary.used_new_overrides {
         override_summary.role_overwrite
     } else {
-        merge_legacy_channel_role_overrides(
+        let (merged, unknown_legacy_bits) = merge_legacy_channel_role_overrides(
             override_summary.role_overwrite,
             legacy_override_rows.unwrap_or_default(),
             assigned_role_ids,
             role_ids,
-        )?
+        )?;
+        unknown_override_bits |= unknown_legacy_bits;
+        merged
     };
 
     let permissions = finalize_channel_permissions(
@@ -510,7 +513,7 @@ pub(crate) fn resolve_db_channel_permissions(
         role_overwrite,
         override_summary.member_overwrite,
     );
-    Ok((permissions, override_summary.unknown_override_bits))
+    Ok((permissions, unknown_override_bits))
 }
 
 pub(crate) fn merge_legacy_channel_role_overrides(
@@ -518,13 +521,22 @@ pub(crate) fn merge_legacy_channel_role_overrides(
     rows: Vec<LegacyChannelRoleOverrideDbRow>,
     assigned_role_ids: &HashSet<String>,
     role_ids: &RoleIdSet,
-) -> Result<ChannelPermissionOverwrite, AuthFailure> {
+) -> Result<(ChannelPermissionOverwrite, u64), AuthFailure> {
     let mut role_overwrite = base_role_overwrite;
+    let mut unknown_bits = 0_u64;
 
     for row in rows {
-        let role = crate::server::db::role_from_i16(row.role).unwrap_or(Role::Member);
-        let (allow, _) = i64_to_masked_permissions(row.allow_mask)?;
-        let (deny, _) = i64_to_masked_permissions(row.deny_mask)?;
+        let (allow, unknown_allow) = i64_to_masked_permissions(row.allow_mask)?;
+        let (deny, unknown_deny) = i64_to_masked_permissions(row.deny_mask)?;
+        unknown_bits |= unknown_allow | unknown_deny;
+        // A row with an unrecognized role must not fall back to any real role.
+        let Some(role) = crate::server::db::role_from_i16(row.role) else {
+            tracing::warn!(
+                event = "permissions.legacy_override.unknown_role",
+                role = row.role
+            );
+            continue;
+        };
         let overwrite = ChannelPermissionOverwrite { allow, deny };
         match role {
             Role::Member => {
@@ -545,7 +557,7 @@ pub(crate) fn merge_legacy_channel_role_overrides(
         }
     }
 
-    Ok(role_overwrite)
+    Ok((role_overwrite, unknown_bits))
 }
 
 pub(crate) fn merge_assigned_role_overrides(
@@ -695,6 +707,35 @@ pub(crate) fn finalize_channel_permissions(
     )
 }
 
+/// Channel permissions of a member who holds only `role_id` (on top of
+/// `@everyone`) and has no member override, for access audits that reason
+/// about roles rather than individual users.
+pub(crate) fn resolve_role_channel_permissions(
+    roles: &HashMap<String, WorkspaceRoleRecord>,
+    role_ids: &RoleIdSet,
+    role_id: &str,
+    role_overrides: &HashMap<String, ChannelPermissionOverwrite>,
+) -> PermissionSet {
+    let assigned_role_ids = HashSet::from([role_id.to_owned()]);
+    let summary = resolve_guild_permission_summary(roles, &assigned_role_ids, role_ids);
+    let everyone_overwrite = role_overrides
+        .get(&role_ids.everyone)
+        .copied()
+        .unwrap_or_default();
+    let role_overwrite = if role_id == role_ids.everyone {
+        ChannelPermissionOverwrite::default()
+    } else {
+        merge_assigned_role_overrides(role_overrides, &assigned_role_ids)
+    };
+    finalize_channel_permissions(
+        summary.guild_permissions,
+        summary.is_workspace_owner,
+        everyone_overwrite,
+        role_overwrite,
+        ChannelPermissionOverwrite::default(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
@@ -703,8 +744,8 @@ mod tests {
         i64_to_masked_permissions, merge_assigned_role_overrides, merge_channel_overwrite,
         merge_legacy_channel_role_overrides, normalize_assigned_role_ids,
         resolve_db_channel_permissions, resolve_guild_permission_summary,
-        resolve_in_memory_channel_permissions, role_ids_from_map, role_records_from_db_rows,
-        summarize_channel_overrides, summarize_guild_permissions,
+        resolve_in_memory_channel_permissions, resolve_role_channel_permissions, role_ids_from_map,
+        role_records_from_db_rows, summarize_channel_overrides, summarize_guild_permissions,
         summarize_in_memory_channel_overrides, summarize_in_memory_guild_permissions,
         sync_legacy_channel_overrides, sync_legacy_role_assignments,
     };
@@ -764,6 +805,42 @@ mod tests {
         assert!(!resolved.contains(Permission::CreateMessage));
     }
 
+    #[test]
+    fn role_channel_permissions_layer_everyone_and_role_overrides() {
+        let mut roles = HashMap::new();
+        let role_ids = ensure_required_roles(&mut roles);
+        let deny_create = ChannelPermissionOverwrite {
+            allow: PermissionSet::empty(),
+            deny: permission_set(&[Permission::CreateMessage]),
+        };
+        let allow_create = ChannelPermissionOverwrite {
+            allow: permission_set(&[Permission::CreateMessage]),
+            deny: PermissionSet::empty(),
+        };
+        let overrides = HashMap::from([
+            (role_ids.everyone.clone(), deny_create),
+            (role_ids.moderator.clone(), allow_create),
+        ]);
+
+        let everyone =
+            resolve_role_channel_permissions(&roles, &role_ids, &role_ids.everyone, &overrides);
+        let member =
+            resolve_role_channel_permissions(&roles, &role_ids, &role_ids.member, &overrides);
+        let moderator =
+            resolve_role_channel_permissions(&roles, &role_ids, &role_ids.moderator, &overrides);
+        let owner = resolve_role_channel_permissions(
+            &roles,
+            &role_ids,
+            &role_ids.workspace_owner,
+            &overrides,
+        );
+        assert!(!everyone.contains(Permission::CreateMessage));
+        assert!(!member.contains(Permission::CreateMessage));
+        assert!(moderator.contains(Permission::CreateMessage));
+        assert!(moderator.contains(Permission::DeleteMessage));
+        assert!(owner.contains(Permission::CreateMessage));
+    }
+
     #[test]
     fn apply_legacy_role_assignment_inserts_expected_role_id() {
         let mut assigned = HashSet::new();
@@ -1392,7 +1469,7 @@ mod tests {
         let owner_allow = i64::try_from(permission_set(&[Permission::ManageRoles]).bits())
             .expect("test mask should fit i64");
 
-        let merged = merge_legacy_channel_role_overrides(
+        let (merged, unknown_bits) = merge_legacy_channel_role_overrides(
             ChannelPermissionOverwrite::default(),
             vec![
                 super::LegacyChannelRoleOverrideDbRow {
@@ -1419,6 +1496,47 @@ mod tests {
         assert!(merged.allow.contains(Permission::CreateMessage));
         assert!(merged.allow.contains(Permission::DeleteMessage));
         assert!(!merged.allow.contains(Permission::ManageRoles));
+        assert_eq!(unknown_bits, 0);
+    }
+
+    #[test]
+    fn merge_legacy_channel_role_overrides_masks_unknown_bits_and_skips_unknown_roles() {
+        let role_ids = super::RoleIdSet {
+            everyone: String::from("everyone"),
+            workspace_owner: String::from("owner"),
+            member: String::from("member"),
+            moderator: String::from("moderator"),
+        };
+        let assigned = HashSet::from([String::from("member")]);
+        let create_message = i64::try_from(permission_set(&[Permission::CreateMessage]).bits())
+            .expect("test mask should fit i64");
+
+        let (merged, unknown_bits) = merge_legacy_channel_role_overrides(
+            ChannelPermissionOverwrite::default(),
+            vec![
+                super::LegacyChannelRoleOverrideDbRow {
+                    role: 0,
+                    allow_mask: create_message | (1 << 40),
+                    deny_mask: 1 << 41,
+                },
+                super::LegacyChannelRoleOverrideDbRow {
+                    role: 7,
+                    allow_mask: i64::try_from(permission_set(&[Permission::DeleteMessage]).bits())
+                        .expect("test mask should fit i64"),
+                    deny_mask: 0,
+                },
+            ],
+            &assigned,
+            &role_ids,
+        )
+        .expect("legacy role overrides should merge");
+
+        assert_eq!(
+            merged.allow.bits(),
+            permission_set(&[Permission::CreateMessage]).bits()
+        );
+        assert_eq!(merged.deny.bits(), 0);
+        assert_eq!(unknown_bits, (1 << 40) | (1 << 41));
     }
 
     #[test]
@@ -4,6 +4,7 @@ use sqlx::{PgPool, Row};
 use crate::server::{
     core::{
         MAX_REACTIONS_PER_MESSAGE, MAX_REACTION_EMOJI_CHARS, MAX_REACTOR_USER_IDS_PER_REACTION,
+        VOTE_DOWN_EMOJI, VOTE_UP_EMOJI,
     },
     errors::AuthFailure,
     types::{MessageResponse, ReactionResponse},
@@ -36,6 +37,26 @@ pub(crate) fn attach_message_reactions(
     }
 }
 
+/// Sets each message's `score` to its up votes minus its down votes. Other
+/// reactions do not count.
+pub(crate) fn attach_vote_scores(messages: &mut [MessageResponse]) {
+    for message in messages {
+        let score = message
+            .reactions
+            .iter()
+            .map(|reaction| {
+                let count = i64::try_from(reaction.count).unwrap_or(i64::MAX);
+                match reaction.emoji.as_str() {
+                    VOTE_UP_EMOJI => count,
+                    VOTE_DOWN_EMOJI => -count,
+                    _ => 0,
+                }
+            })
+            .fold(0_i64, i64::saturating_add);
+        message.score = Some(score);
+    }
+}
+
 pub(crate) fn reaction_summaries_from_users(
     reactions: &HashMap<String, HashSet<UserId>>,
     viewer_user_id: Option<UserId>,
@@ -1,16 +1,39 @@
-use axum::{http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    http::{header::RETRY_AFTER, StatusCode},
+    response::IntoResponse,
+    Json,
+};
 
 use super::{
     directory_contract::{
         AUDIT_ACCESS_DENIED_ERROR, DIRECTORY_JOIN_IP_BANNED_ERROR, DIRECTORY_JOIN_USER_BANNED_ERROR,
     },
     metrics::{record_auth_failure, record_rate_limit_hit},
-    types::AuthError,
+    types::{AuthError, LimitError},
 };
 
 #[derive(Debug)]
 pub(crate) enum AuthFailure {
     InvalidRequest,
+    /// Message content was empty or had no visible characters, and there was
+    /// nothing else to send.
+    ContentEmpty,
+    /// Message content is longer than `limit` bytes.
+    ContentTooLong {
+        limit: usize,
+    },
+    /// More than `limit` attachments were bound to one message.
+    TooManyAttachments {
+        limit: usize,
+    },
+    /// The password is shorter than `limit` bytes.
+    PasswordTooShort {
+        limit: usize,
+    },
+    /// The password is longer than `limit` bytes.
+    PasswordTooLong {
+        limit: usize,
+    },
     CaptchaFailed,
     Unauthorized,
     Forbidden,
@@ -18,10 +41,50 @@ pub(crate) enum AuthFailure {
     DirectoryJoinUserBanned,
     DirectoryJoinIpBanned,
     GuildCreationLimitReached,
+    /// The account is younger than the operator-configured minimum age for
+    /// creating guilds or sending friend requests.
+    AccountTooNew,
+    /// Accepting the friend request would put either party over
+    /// `max_friends_per_user`.
+    FriendLimitReached,
+    /// The recipient already has `max_incoming_friend_requests_per_user`
+    /// pending incoming friend requests.
+    IncomingFriendRequestLimitReached,
+    /// The user already has `MAX_PUSH_SUBSCRIPTIONS_PER_USER` push
+    /// subscriptions registered.
+    PushSubscriptionLimitReached,
+    /// The in-memory backend is at one of its `in_memory_max_*` ceilings.
+    InMemoryCapacityReached,
     NotFound,
+    /// The guild exists but the caller is not a member or is banned. Rendered
+    /// exactly like `NotFound` so responses do not reveal that the guild exists.
+    NotGuildMember,
     RateLimited,
+    /// Rate limited with a known delay, in whole seconds, until the caller's
+    /// oldest tracked hit ages out of the limiter window.
+    RateLimitedRetryAfter(u64),
+    /// The user already holds `gateway_max_connections_per_user` gateway
+    /// connections.
+    TooManyGatewayConnections,
     PayloadTooLarge,
+    /// The attachments bound to a new message add up to more than
+    /// `max_message_attachment_total_bytes`.
+    MessageAttachmentsTooLarge,
+    /// An attachment listed on a new message does not exist, or belongs to
+    /// another user or guild.
+    AttachmentNotFound,
+    /// An attachment listed on a new message was uploaded to another channel
+    /// of the same guild.
+    AttachmentWrongChannel,
+    /// An attachment listed on a new message is already bound to a message.
+    AttachmentAlreadyUsed,
     QuotaExceeded,
+    /// A request with the same idempotency key is still being processed.
+    IdempotencyConflict,
+    /// The message was edited since the version the client expected.
+    EditConflict,
+    /// Stored attachment bytes no longer hash to the digest recorded at upload.
+    AttachmentIntegrityMismatch,
     Internal,
 }
 
@@ -36,19 +99,42 @@ impl IntoResponse for AuthFailure {
     fn into_response(self) -> axum::response::Response {
         match self {
             Self::Unauthorized => record_auth_failure("unauthorized"),
+            Self::NotGuildMember => record_auth_failure("not_guild_member"),
             Self::Forbidden
             | Self::AuditAccessDenied
             | Self::DirectoryJoinUserBanned
             | Self::DirectoryJoinIpBanned => {
                 record_auth_failure("forbidden");
             }
-            Self::RateLimited => record_rate_limit_hit("http", "auth_failure"),
+            Self::RateLimited | Self::RateLimitedRetryAfter(_) => {
+                record_rate_limit_hit("http", "auth_failure");
+            }
+            Self::TooManyGatewayConnections => {
+                record_rate_limit_hit("gateway", "too_many_connections");
+            }
             Self::InvalidRequest
+            | Self::ContentEmpty
+            | Self::ContentTooLong { .. }
+            | Self::TooManyAttachments { .. }
+            | Self::PasswordTooShort { .. }
+            | Self::PasswordTooLong { .. }
             | Self::CaptchaFailed
             | Self::GuildCreationLimitReached
+            | Self::AccountTooNew
+            | Self::FriendLimitReached
+            | Self::IncomingFriendRequestLimitReached
+            | Self::PushSubscriptionLimitReached
+            | Self::InMemoryCapacityReached
             | Self::NotFound
             | Self::PayloadTooLarge
+            | Self::MessageAttachmentsTooLarge
+            | Self::AttachmentNotFound
+            | Self::AttachmentWrongChannel
+            | Self::AttachmentAlreadyUsed
             | Self::QuotaExceeded
+            | Self::IdempotencyConflict
+            | Self::EditConflict
+            | Self::AttachmentIntegrityMismatch
             | Self::Internal => {}
         }
 
@@ -60,6 +146,45 @@ impl IntoResponse for AuthFailure {
                 }),
             )
                 .into_response(),
+            Self::ContentEmpty => (
+                StatusCode::BAD_REQUEST,
+                Json(AuthError {
+                    error: "content_empty",
+                }),
+            )
+                .into_response(),
+            Self::ContentTooLong { limit } => (
+                StatusCode::BAD_REQUEST,
+                Json(LimitError {
+                    error: "content_too_long",
+                    limit,
+                }),
+            )
+                .into_response(),
+            Self::TooManyAttachments { limit } => (
+                StatusCode::BAD_REQUEST,
+                Json(LimitError {
+                    error: "too_many_attachments",
+                    limit,
+                }),
+            )
+                .into_response(),
+            Self::PasswordTooShort { limit } => (
+                StatusCode::BAD_REQUEST,
+                Json(LimitError {
+                    error: "password_too_short",
+                    limit,
+                }),
+            )
+                .into_response(),
+            Self::PasswordTooLong { limit } => (
+                StatusCode::BAD_REQUEST,
+                Json(LimitError {
+                    error: "password_too_long",
+                    limit,
+                }),
+            )
+                .into_response(),
             Self::CaptchaFailed => (
                 StatusCode::FORBIDDEN,
                 Json(AuthError {
@@ -107,7 +232,42 @@ impl IntoResponse for AuthFailure {
                 }),
             )
                 .into_response(),
-            Self::NotFound => (
+            Self::AccountTooNew => (
+                StatusCode::FORBIDDEN,
+                Json(AuthError {
+                    error: "account_too_new",
+                }),
+            )
+                .into_response(),
+            Self::FriendLimitReached => (
+                StatusCode::FORBIDDEN,
+                Json(AuthError {
+                    error: "friend_limit_reached",
+                }),
+            )
+                .into_response(),
+            Self::IncomingFriendRequestLimitReached => (
+                StatusCode::FORBIDDEN,
+                Json(AuthError {
+                    error: "incoming_friend_request_limit_reached",
+                }),
+            )
+                .into_response(),
+            Self::PushSubscriptionLimitReached => (
+                StatusCode::FORBIDDEN,
+                Json(AuthError {
+                    error: "push_subscription_limit_reached",
+                }),
+            )
+                .into_response(),
+            Self::InMemoryCapacityReached => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(AuthError {
+                    error: "capacity_reached",
+                }),
+            )
+                .into_response(),
+            Self::NotFound | Self::NotGuildMember => (
                 StatusCode::NOT_FOUND,
                 Json(AuthError { error: "not_found" }),
             )
@@ -119,6 +279,21 @@ impl IntoResponse for AuthFailure {
                 }),
             )
                 .into_response(),
+            Self::RateLimitedRetryAfter(retry_after_secs) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, retry_after_secs.max(1).to_string())],
+                Json(AuthError {
+                    error: "rate_limited",
+                }),
+            )
+                .into_response(),
+            Self::TooManyGatewayConnections => (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(AuthError {
+                    error: "too_many_connections",
+                }),
+            )
+                .into_response(),
             Self::PayloadTooLarge => (
                 StatusCode::PAYLOAD_TOO_LARGE,
                 Json(AuthError {
@@ -126,6 +301,34 @@ impl IntoResponse for AuthFailure {
                 }),
             )
                 .into_response(),
+            Self::MessageAttachmentsTooLarge => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(AuthError {
+                    error: "message_attachments_too_large",
+                }),
+            )
+                .into_response(),
+            Self::AttachmentNotFound => (
+                StatusCode::BAD_REQUEST,
+                Json(AuthError {
+                    error: "attachment_not_found",
+                }),
+            )
+                .into_response(),
+            Self::AttachmentWrongChannel => (
+                StatusCode::BAD_REQUEST,
+                Json(AuthError {
+                    error: "attachment_wrong_channel",
+                }),
+            )
+                .into_response(),
+            Self::AttachmentAlreadyUsed => (
+                StatusCode::BAD_REQUEST,
+                Json(AuthError {
+                    error: "attachment_already_used",
+                }),
+            )
+                .into_response(),
             Self::QuotaExceeded => (
                 StatusCode::CONFLICT,
                 Json(AuthError {
@@ -133,6 +336,23 @@ impl IntoResponse for AuthFailure {
                 }),
             )
                 .into_response(),
+            Self::IdempotencyConflict => (
+                StatusCode::CONFLICT,
+                Json(AuthError {
+                    error: "idempotency_key_in_use",
+                }),
+            )
+                .into_response(),
+            Self::EditConflict => {
+                (StatusCode::CONFLICT, Json(AuthError { error: "conflict" })).into_response()
+            }
+            Self::AttachmentIntegrityMismatch => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(AuthError {
+                    error: "attachment_integrity_mismatch",
+                }),
+            )
+                .into_response(),
             Self::Internal => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(AuthError {
@@ -10,11 +10,15 @@ mod workspace;
 pub(crate) const EMITTED_EVENT_TYPES: &[&str] = &[
     connection::READY_EVENT,
     connection::SUBSCRIBED_EVENT,
+    connection::HISTORY_EVENT,
+    connection::MESSAGE_CREATE_ACK_EVENT,
     message_channel::MESSAGE_CREATE_EVENT,
     message_channel::MESSAGE_UPDATE_EVENT,
     message_channel::MESSAGE_DELETE_EVENT,
     message_channel::MESSAGE_REACTION_EVENT,
+    message_channel::MESSAGE_MENTION_EVENT,
     message_channel::CHANNEL_CREATE_EVENT,
+    message_channel::CHANNEL_UPDATE_EVENT,
     presence_voice::PRESENCE_SYNC_EVENT,
     presence_voice::PRESENCE_UPDATE_EVENT,
     presence_voice::VOICE_PARTICIPANT_SYNC_EVENT,
@@ -46,7 +50,10 @@ pub(crate) const EMITTED_EVENT_TYPES: &[&str] = &[
     friend::FRIEND_REMOVE_EVENT,
 ];
 
-pub(crate) use connection::{try_ready, try_subscribed, READY_EVENT, SUBSCRIBED_EVENT};
+pub(crate) use connection::{
+    try_history, try_message_create_ack, try_ready, try_subscribed, HISTORY_EVENT,
+    MESSAGE_CREATE_ACK_EVENT, READY_EVENT, SUBSCRIBED_EVENT,
+};
 pub(crate) use envelope::GatewayEvent;
 #[cfg(test)]
 pub(crate) use friend::friend_request_delete;
@@ -62,9 +69,10 @@ pub(crate) use message_channel::channel_create;
 #[cfg(test)]
 pub(crate) use message_channel::message_reaction;
 pub(crate) use message_channel::{
-    try_channel_create, try_message_create, try_message_delete, try_message_reaction,
-    try_message_update, MessageReactionOperation, CHANNEL_CREATE_EVENT, MESSAGE_CREATE_EVENT,
-    MESSAGE_DELETE_EVENT, MESSAGE_REACTION_EVENT, MESSAGE_UPDATE_EVENT,
+    try_channel_create, try_channel_update, try_message_create, try_message_delete,
+    try_message_mention, try_message_reaction, try_message_update, MessageReactionOperation,
+    CHANNEL_CREATE_EVENT, CHANNEL_UPDATE_EVENT, MESSAGE_CREATE_EVENT, MESSAGE_DELETE_EVENT,
+    MESSAGE_MENTION_EVENT, MESSAGE_REACTION_EVENT, MESSAGE_UPDATE_EVENT,
 };
 pub(crate) use presence_voice::{
     try_presence_sync, try_presence_update, try_voice_participant_join,
@@ -148,12 +156,22 @@ mod tests {
             }],
             attachments: Vec::new(),
             reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
             created_at_unix: 10,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
         };
         let channel = ChannelResponse {
             channel_id: String::from("01ARZ3NDEKTSV4RRFFQ69G5FAZ"),
             name: String::from("general"),
             kind: ChannelKind::Text,
+            locked: false,
+            retention_secs: None,
+            voting: false,
+            category_id: None,
         };
 
         let ready_event = try_ready(user_id).expect("ready event should serialize");
@@ -169,8 +187,21 @@ mod tests {
             parse_event(&try_message_create(&message).expect("message_create should serialize"));
         assert_eq!(
             message_create_payload["message_id"],
+            Value::from(message.message_id.clone())
+        );
+        let message_create_ack_payload = parse_event(
+            &try_message_create_ack(Some("nonce-1"), &message)
+                .expect("message_create_ack should serialize"),
+        );
+        assert_eq!(message_create_ack_payload["nonce"], Value::from("nonce-1"));
+        assert_eq!(
+            message_create_ack_payload["message_id"],
             Value::from(message.message_id)
         );
+        assert_eq!(
+            message_create_ack_payload["created_at_unix"],
+            Value::from(message.created_at_unix)
+        );
 
         let message_reaction_payload = parse_event(&message_reaction(
             "g",
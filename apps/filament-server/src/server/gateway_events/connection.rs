@@ -1,12 +1,17 @@
 use filament_core::UserId;
 use serde::Serialize;
 
-use crate::server::auth::outbound_event;
+use crate::server::{
+    auth::outbound_event,
+    types::{MessageHistoryResponse, MessageResponse},
+};
 
 use super::GatewayEvent;
 
 pub(crate) const READY_EVENT: &str = "ready";
 pub(crate) const SUBSCRIBED_EVENT: &str = "subscribed";
+pub(crate) const HISTORY_EVENT: &str = "history";
+pub(crate) const MESSAGE_CREATE_ACK_EVENT: &str = "message_create_ack";
 
 #[derive(Serialize)]
 struct ReadyPayload {
@@ -19,6 +24,21 @@ struct SubscribedPayload<'a> {
     channel_id: &'a str,
 }
 
+#[derive(Serialize)]
+struct HistoryPayload<'a> {
+    guild_id: &'a str,
+    channel_id: &'a str,
+    #[serde(flatten)]
+    history: &'a MessageHistoryResponse,
+}
+
+#[derive(Serialize)]
+struct MessageCreateAckPayload<'a> {
+    nonce: Option<&'a str>,
+    #[serde(flatten)]
+    message: &'a MessageResponse,
+}
+
 pub(crate) fn try_ready(user_id: UserId) -> anyhow::Result<GatewayEvent> {
     build_connection_event(
         READY_EVENT,
@@ -38,6 +58,33 @@ pub(crate) fn try_subscribed(guild_id: &str, channel_id: &str) -> anyhow::Result
     )
 }
 
+pub(crate) fn try_history(
+    guild_id: &str,
+    channel_id: &str,
+    history: &MessageHistoryResponse,
+) -> anyhow::Result<GatewayEvent> {
+    build_connection_event(
+        HISTORY_EVENT,
+        HistoryPayload {
+            guild_id,
+            channel_id,
+            history,
+        },
+    )
+}
+
+/// Confirms a gateway `message_create` to the sending connection, echoing the
+/// client's `nonce` so it can swap its placeholder for the stored message.
+pub(crate) fn try_message_create_ack(
+    nonce: Option<&str>,
+    message: &MessageResponse,
+) -> anyhow::Result<GatewayEvent> {
+    build_connection_event(
+        MESSAGE_CREATE_ACK_EVENT,
+        MessageCreateAckPayload { nonce, message },
+    )
+}
+
 fn build_connection_event<T: Serialize>(
     event_type: &'static str,
     payload: T,
@@ -80,6 +127,24 @@ mod tests {
         assert_eq!(payload["channel_id"], Value::from("channel-1"));
     }
 
+    #[test]
+    fn history_event_contains_scope_messages_and_cursor() {
+        let history = MessageHistoryResponse {
+            messages: Vec::new(),
+            next_before: Some(String::from("01JYQ4V3VW1TC0MCC4GY7Q4RPR")),
+        };
+        let event =
+            try_history("guild-1", "channel-1", &history).expect("history event should serialize");
+        let payload = parse_payload(&event);
+        assert_eq!(payload["guild_id"], Value::from("guild-1"));
+        assert_eq!(payload["channel_id"], Value::from("channel-1"));
+        assert_eq!(payload["messages"], Value::Array(Vec::new()));
+        assert_eq!(
+            payload["next_before"],
+            Value::from("01JYQ4V3VW1TC0MCC4GY7Q4RPR")
+        );
+    }
+
     #[test]
     fn try_builder_rejects_invalid_event_type() {
         #[derive(Serialize)]
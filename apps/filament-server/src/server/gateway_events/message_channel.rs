@@ -1,13 +1,18 @@
 use serde::Serialize;
 
 use super::{envelope::try_build_event, GatewayEvent};
-use crate::server::types::{ChannelResponse, MessageResponse};
+use crate::server::{
+    domain::MentionScope,
+    types::{ChannelResponse, MessageResponse},
+};
 
 pub(crate) const MESSAGE_CREATE_EVENT: &str = "message_create";
 pub(crate) const MESSAGE_UPDATE_EVENT: &str = "message_update";
 pub(crate) const MESSAGE_DELETE_EVENT: &str = "message_delete";
 pub(crate) const MESSAGE_REACTION_EVENT: &str = "message_reaction";
+pub(crate) const MESSAGE_MENTION_EVENT: &str = "message_mention";
 pub(crate) const CHANNEL_CREATE_EVENT: &str = "channel_create";
+pub(crate) const CHANNEL_UPDATE_EVENT: &str = "channel_update";
 
 #[derive(Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -50,6 +55,14 @@ struct MessageDeletePayload<'a> {
     deleted_at_unix: i64,
 }
 
+#[derive(Serialize)]
+struct MessageMentionPayload<'a> {
+    guild_id: &'a str,
+    channel_id: &'a str,
+    message_id: &'a str,
+    scope: MentionScope,
+}
+
 #[derive(Serialize)]
 struct ChannelCreatePayload<'a> {
     guild_id: &'a str,
@@ -61,6 +74,22 @@ struct ChannelCreateChannelPayload<'a> {
     channel_id: &'a str,
     name: &'a str,
     kind: filament_core::ChannelKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category_id: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ChannelUpdatePayload<'a> {
+    guild_id: &'a str,
+    channel_id: &'a str,
+    updated_fields: ChannelUpdateFieldsPayload,
+    updated_at_unix: i64,
+    actor_user_id: String,
+}
+
+#[derive(Serialize)]
+struct ChannelUpdateFieldsPayload {
+    locked: bool,
 }
 
 pub(crate) fn try_message_create(message: &MessageResponse) -> anyhow::Result<GatewayEvent> {
@@ -130,6 +159,23 @@ pub(crate) fn try_message_reaction(
     )
 }
 
+pub(crate) fn try_message_mention(
+    guild_id: &str,
+    channel_id: &str,
+    message_id: &str,
+    scope: MentionScope,
+) -> anyhow::Result<GatewayEvent> {
+    try_build_event(
+        MESSAGE_MENTION_EVENT,
+        MessageMentionPayload {
+            guild_id,
+            channel_id,
+            message_id,
+            scope,
+        },
+    )
+}
+
 #[cfg(test)]
 pub(crate) fn message_reaction(
     guild_id: &str,
@@ -166,11 +212,31 @@ pub(crate) fn try_channel_create(
                 channel_id: channel.channel_id.as_str(),
                 name: channel.name.as_str(),
                 kind: channel.kind,
+                category_id: channel.category_id.as_deref(),
             },
         },
     )
 }
 
+pub(crate) fn try_channel_update(
+    guild_id: &str,
+    channel_id: &str,
+    locked: bool,
+    updated_at_unix: i64,
+    actor_user_id: filament_core::UserId,
+) -> anyhow::Result<GatewayEvent> {
+    try_build_event(
+        CHANNEL_UPDATE_EVENT,
+        ChannelUpdatePayload {
+            guild_id,
+            channel_id,
+            updated_fields: ChannelUpdateFieldsPayload { locked },
+            updated_at_unix,
+            actor_user_id: actor_user_id.to_string(),
+        },
+    )
+}
+
 #[cfg(test)]
 pub(crate) fn channel_create(guild_id: &str, channel: &ChannelResponse) -> GatewayEvent {
     try_channel_create(guild_id, channel).unwrap_or_else(|error| {
@@ -221,7 +287,13 @@ mod tests {
             }],
             attachments: Vec::new(),
             reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
             created_at_unix: 1,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
         };
 
         let payload =
@@ -281,6 +353,19 @@ mod tests {
         assert!(payload["actor_user_id"].is_string());
     }
 
+    #[test]
+    fn message_mention_event_emits_scope_without_content() {
+        let payload = parse_payload(
+            &try_message_mention("guild-1", "channel-1", "msg-1", MentionScope::Here)
+                .expect("message_mention should serialize"),
+        );
+        assert_eq!(payload["guild_id"], Value::from("guild-1"));
+        assert_eq!(payload["channel_id"], Value::from("channel-1"));
+        assert_eq!(payload["message_id"], Value::from("msg-1"));
+        assert_eq!(payload["scope"], Value::from("here"));
+        assert!(payload.get("content").is_none());
+    }
+
     #[test]
     fn try_message_reaction_rejects_invalid_event_type() {
         let Err(error) = try_build_message_reaction_event(
@@ -309,6 +394,10 @@ mod tests {
             channel_id: String::from("channel-1"),
             name: String::from("general"),
             kind: ChannelKind::Text,
+            locked: false,
+            retention_secs: None,
+            voting: false,
+            category_id: None,
         };
 
         let payload = parse_payload(
@@ -316,6 +405,20 @@ mod tests {
         );
         assert_eq!(payload["guild_id"], Value::from("guild-1"));
         assert_eq!(payload["channel"]["name"], Value::from("general"));
+        assert!(payload["channel"].get("category_id").is_none());
+    }
+
+    #[test]
+    fn channel_update_event_emits_updated_fields() {
+        let payload = parse_payload(
+            &try_channel_update("guild-1", "channel-1", true, 42, UserId::new())
+                .expect("channel_update should serialize"),
+        );
+        assert_eq!(payload["guild_id"], Value::from("guild-1"));
+        assert_eq!(payload["channel_id"], Value::from("channel-1"));
+        assert_eq!(payload["updated_fields"]["locked"], Value::from(true));
+        assert_eq!(payload["updated_at_unix"], Value::from(42));
+        assert!(payload["actor_user_id"].is_string());
     }
 
     #[test]
@@ -324,6 +427,10 @@ mod tests {
             channel_id: String::from("channel-1"),
             name: String::from("general"),
             kind: ChannelKind::Text,
+            locked: false,
+            retention_secs: None,
+            voting: false,
+            category_id: None,
         };
         let Err(error) = try_build_channel_create_event(
             "channel create",
@@ -333,6 +440,7 @@ mod tests {
                     channel_id: channel.channel_id.as_str(),
                     name: channel.name.as_str(),
                     kind: channel.kind,
+                    category_id: None,
                 },
             },
         ) else {
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use aws_lc_rs::constant_time::verify_slices_are_equal;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::server::{
+    auth::now_unix,
+    core::{
+        AppState, ConnectionControl, DEFAULT_ADMIN_MESSAGE_RATES_LIMIT,
+        MAX_ADMIN_MESSAGE_RATES_LIMIT, MESSAGE_RATE_WINDOW_SECS,
+    },
+    errors::AuthFailure,
+    types::{
+        AdminConnectionPath, AdminConnectionResponse, AdminConnectionsResponse,
+        AdminGuildMessageRateResponse, AdminMessageRateResponse, AdminMessageRatesQuery,
+        AdminMessageRatesResponse,
+    },
+};
+
+pub(crate) const ADMIN_SECRET_HEADER: &str = "x-filament-admin-secret";
+
+/// Compares SHA-256 digests so the comparison time does not depend on how
+/// much of the presented secret matches.
+fn admin_secret_matches(expected: &str, presented: &str) -> bool {
+    let expected = Sha256::digest(expected.as_bytes());
+    let presented = Sha256::digest(presented.as_bytes());
+    verify_slices_are_equal(&expected, &presented).is_ok()
+}
+
+/// The admin endpoints do not exist unless a secret is configured, so an
+/// unconfigured server answers `404` rather than revealing them.
+pub(crate) fn authorize_admin(state: &AppState, headers: &HeaderMap) -> Result<(), AuthFailure> {
+    let Some(expected) = state.runtime.admin_api_secret.as_deref() else {
+        return Err(AuthFailure::NotFound);
+    };
+    let presented = headers
+        .get(ADMIN_SECRET_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(AuthFailure::Unauthorized)?;
+    if !admin_secret_matches(expected, presented) {
+        tracing::warn!(event = "admin.auth.rejected");
+        return Err(AuthFailure::Unauthorized);
+    }
+    Ok(())
+}
+
+pub(crate) async fn list_gateway_connections(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<AdminConnectionsResponse>, AuthFailure> {
+    authorize_admin(&state, &headers)?;
+
+    let mut connections: Vec<AdminConnectionResponse> = state
+        .realtime_registry
+        .connection_presence()
+        .read()
+        .await
+        .iter()
+        .map(|(connection_id, presence)| {
+            let mut guild_ids: Vec<String> = presence.guild_ids.iter().cloned().collect();
+            guild_ids.sort_unstable();
+            AdminConnectionResponse {
+                connection_id: connection_id.to_string(),
+                user_id: presence.user_id.to_string(),
+                guild_ids,
+            }
+        })
+        .collect();
+    connections.sort_unstable_by(|left, right| {
+        (&left.user_id, &left.connection_id).cmp(&(&right.user_id, &right.connection_id))
+    });
+    tracing::info!(
+        event = "admin.connections.list",
+        connections = connections.len()
+    );
+    Ok(Json(AdminConnectionsResponse { connections }))
+}
+
+/// Signals the connection's control channel; the gateway task sends the close
+/// frame and cleans up exactly as it does for a slow consumer.
+pub(crate) async fn close_gateway_connection(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<AdminConnectionPath>,
+) -> Result<StatusCode, AuthFailure> {
+    authorize_admin(&state, &headers)?;
+    let connection_id =
+        Uuid::parse_str(&path.connection_id).map_err(|_| AuthFailure::InvalidRequest)?;
+
+    let signalled = state
+        .realtime_registry
+        .connection_controls()
+        .read()
+        .await
+        .get(&connection_id)
+        .is_some_and(|control| control.send(ConnectionControl::AdminClose).is_ok());
+    if !signalled {
+        return Err(AuthFailure::NotFound);
+    }
+    tracing::warn!(
+        event = "admin.connections.close",
+        connection_id = %connection_id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists the authors and guilds with the most messages created in the last
+/// `MESSAGE_RATE_WINDOW_SECS`, busiest first.
+pub(crate) async fn list_message_rates(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AdminMessageRatesQuery>,
+) -> Result<Json<AdminMessageRatesResponse>, AuthFailure> {
+    authorize_admin(&state, &headers)?;
+    let limit = query.limit.unwrap_or(DEFAULT_ADMIN_MESSAGE_RATES_LIMIT);
+    if limit == 0 || limit > MAX_ADMIN_MESSAGE_RATES_LIMIT {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let now = now_unix();
+    let users = busiest_in_window(&*state.message_create_hits.read().await, now, limit)
+        .into_iter()
+        .map(|(user_id, messages)| AdminMessageRateResponse { user_id, messages })
+        .collect();
+    let guilds = busiest_in_window(&*state.message_create_guild_hits.read().await, now, limit)
+        .into_iter()
+        .map(|(guild_id, messages)| AdminGuildMessageRateResponse { guild_id, messages })
+        .collect();
+    Ok(Json(AdminMessageRatesResponse {
+        window_secs: MESSAGE_RATE_WINDOW_SECS,
+        users,
+        guilds,
+    }))
+}
+
+fn busiest_in_window(
+    hits: &HashMap<String, Vec<i64>>,
+    now: i64,
+    limit: usize,
+) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = hits
+        .iter()
+        .map(|(key, hits)| {
+            let messages = hits
+                .iter()
+                .filter(|timestamp| now.saturating_sub(**timestamp) < MESSAGE_RATE_WINDOW_SECS)
+                .count();
+            (key.clone(), messages)
+        })
+        .filter(|(_, messages)| *messages > 0)
+        .collect();
+    counts.sort_unstable_by(|(left_key, left), (right_key, right)| {
+        right.cmp(left).then_with(|| left_key.cmp(right_key))
+    });
+    counts.truncate(limit);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::admin_secret_matches;
+
+    #[test]
+    fn admin_secret_matches_only_exact_secret() {
+        let secret = "0123456789abcdef0123456789abcdef";
+        assert!(admin_secret_matches(secret, secret));
+        assert!(!admin_secret_matches(
+            secret,
+            "0123456789abcdef0123456789abcdeF"
+        ));
+        assert!(!admin_secret_matches(secret, ""));
+    }
+}
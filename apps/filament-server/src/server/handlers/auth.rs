@@ -3,30 +3,63 @@ use std::error::Error as StdError;
 use std::net::SocketAddr;
 
 use axum::{
+    body::Body,
     extract::{connect_info::ConnectInfo, Extension, State},
-    http::{HeaderMap, StatusCode},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+    },
+    response::Response,
     Json,
 };
+use futures_util::{stream, StreamExt};
 use ulid::Ulid;
 
 use filament_core::{tokenize_markdown, UserId, Username};
 
 use crate::server::{
     auth::{
-        authenticate, enforce_auth_route_rate_limit, extract_client_ip, find_username_by_user_id,
-        hash_password, hash_refresh_token, issue_tokens, now_unix, validate_password, ClientIp,
+        access_token_expires_at_unix, authenticate, authenticate_with_token, bearer_token,
+        enforce_auth_route_rate_limit, enforce_user_export_rate_limit, extract_client_ip,
+        find_username_by_user_id, hash_password, hash_refresh_token, issue_tokens, now_unix,
+        validate_password, verify_access_token, ClientIp,
     },
     auth_repository::{
         refresh_session_ttl_unix, AuthPersistence, AuthRepository, RefreshCheckError,
     },
-    core::{AppState, ACCESS_TOKEN_TTL_SECS, MAX_USER_LOOKUP_IDS},
+    core::{AppState, CaptchaConfig, ACCESS_TOKEN_TTL_SECS, MAX_USER_LOOKUP_IDS},
+    domain::{
+        export_page_ndjson, pinned_public_client, user_export_chunk, OutboundTargetError,
+        UserExportCursor,
+    },
     errors::AuthFailure,
     types::{
         AuthResponse, CaptchaToken, HcaptchaVerifyResponse, LoginRequest, MeResponse,
-        RefreshRequest, RegisterRequest, RegisterResponse, UserLookupRequest, UserLookupResponse,
+        RefreshRequest, RegisterRequest, RegisterResponse, TokenStatusResponse, UserExportRecord,
+        UserLookupByUsernameRequest, UserLookupRequest, UserLookupResponse,
     },
 };
 
+/// Client for one verify call. `https` URLs get the SSRF-guarded pinned
+/// client; only the localhost `http` URL accepted for tests skips it.
+async fn captcha_verify_client(
+    state: &AppState,
+    config: &CaptchaConfig,
+) -> Result<reqwest::Client, OutboundTargetError> {
+    if config.verify_url.starts_with("http://") {
+        return Ok((*state.http_client).clone());
+    }
+    let url = reqwest::Url::parse(&config.verify_url).map_err(|_| OutboundTargetError::Blocked)?;
+    let host = url.host_str().ok_or(OutboundTargetError::Blocked)?;
+    let port = url
+        .port_or_known_default()
+        .ok_or(OutboundTargetError::Blocked)?;
+    pinned_public_client(host, port, config.verify_timeout)
+        .await?
+        .build()
+        .map_err(|_| OutboundTargetError::Blocked)
+}
+
 pub(crate) async fn verify_captcha_token(
     state: &AppState,
     client_ip: ClientIp,
@@ -65,8 +98,19 @@ pub(crate) async fn verify_captcha_token(
         form_data.push(("remoteip", remote_ip.to_string()));
     }
 
-    let response = state
-        .http_client
+    let client = captcha_verify_client(state, &config)
+        .await
+        .map_err(|error| {
+            tracing::warn!(
+                event = "auth.captcha.verify",
+                outcome = "destination_rejected",
+                error = ?error,
+                verify_url = %config.verify_url,
+                client_ip_source = client_ip.source().as_str()
+            );
+            AuthFailure::CaptchaFailed
+        })?;
+    let response = client
         .post(&config.verify_url)
         .timeout(config.verify_timeout)
         .form(&form_data)
@@ -187,7 +231,7 @@ pub(crate) async fn register(
     verify_captcha_token(&state, client_ip, payload.captcha_token).await?;
 
     let username = Username::try_from(payload.username).map_err(|_| AuthFailure::InvalidRequest)?;
-    validate_password(&payload.password).map_err(|_| AuthFailure::InvalidRequest)?;
+    validate_password(&payload.password)?;
     let password_hash = hash_password(&payload.password).map_err(|_| AuthFailure::Internal)?;
     let repository = AuthRepository::from_state(&state);
 
@@ -297,6 +341,9 @@ pub(crate) async fn refresh(
     let session_id = refresh_check.session_id;
     let user_id = refresh_check.user_id;
     let token_hash = refresh_check.presented_hash;
+    if refresh_check.grace_retry {
+        tracing::info!(event = "auth.refresh", outcome = "grace_retry", session_id = %session_id);
+    }
     let now = now_unix();
     let username = find_username_by_user_id(&state, user_id)
         .await
@@ -311,6 +358,7 @@ pub(crate) async fn refresh(
             refresh_hash,
             now,
             refresh_session_ttl_unix(now),
+            refresh_check.grace_retry,
         )
         .await?;
 
@@ -372,9 +420,90 @@ pub(crate) async fn me(
         about_markdown_tokens: tokenize_markdown(&profile.1),
         avatar_version: profile.2,
         banner_version: profile.3,
+        created_at_unix: profile.4,
+    }))
+}
+
+pub(crate) async fn token_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<TokenStatusResponse>, AuthFailure> {
+    let access_token = bearer_token(&headers).ok_or(AuthFailure::Unauthorized)?;
+    let auth = authenticate_with_token(&state, access_token).await?;
+    let claims =
+        verify_access_token(&state, access_token).map_err(|_| AuthFailure::Unauthorized)?;
+    let expires_at_unix = access_token_expires_at_unix(&claims).ok_or(AuthFailure::Internal)?;
+
+    Ok(Json(TokenStatusResponse {
+        user_id: auth.user_id.to_string(),
+        expires_at_unix,
+        expires_in_secs: expires_at_unix.saturating_sub(now_unix()).max(0),
     }))
 }
 
+pub(crate) async fn export_me(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+) -> Result<Response, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_user_export_rate_limit(&state, client_ip, auth.user_id).await?;
+
+    let account = export_page_ndjson(&[UserExportRecord::Account {
+        user_id: auth.user_id.to_string(),
+        username: auth.username.clone(),
+        exported_at_unix: now_unix(),
+    }])?;
+    tracing::info!(event = "auth.me.export", user_id = %auth.user_id);
+
+    let user_id = auth.user_id;
+    let sections = stream::try_unfold(
+        (state, Some(UserExportCursor::Friends)),
+        move |(state, cursor)| async move {
+            let Some(cursor) = cursor else {
+                return Ok(None);
+            };
+            match user_export_chunk(&state, user_id, cursor).await {
+                Ok(Some((chunk, next))) => Ok(Some((chunk, (state, Some(next))))),
+                Ok(None) => Ok(None),
+                Err(error) => {
+                    tracing::warn!(
+                        event = "auth.me.export.page_failed",
+                        user_id = %user_id,
+                        error = %error
+                    );
+                    Err(std::io::Error::other(error.to_string()))
+                }
+            }
+        },
+    );
+    let body = stream::iter([Ok::<_, std::io::Error>(account)]).chain(sections);
+
+    let mut response = Response::new(Body::from_stream(body));
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/x-ndjson"),
+    );
+    response.headers_mut().insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"filament-export.ndjson\""),
+    );
+    response.headers_mut().insert(
+        HeaderName::from_static("x-content-type-options"),
+        HeaderValue::from_static("nosniff"),
+    );
+    response.headers_mut().insert(
+        HeaderName::from_static("cache-control"),
+        HeaderValue::from_static("private, no-store"),
+    );
+    Ok(response)
+}
+
 pub(crate) async fn lookup_users(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -401,3 +530,30 @@ pub(crate) async fn lookup_users(
     let users = repository.lookup_users(&deduped).await?;
     Ok(Json(UserLookupResponse { users }))
 }
+
+pub(crate) async fn lookup_users_by_username(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<UserLookupByUsernameRequest>,
+) -> Result<Json<UserLookupResponse>, AuthFailure> {
+    let _auth = authenticate(&state, &headers).await?;
+    if payload.usernames.is_empty() || payload.usernames.len() > MAX_USER_LOOKUP_IDS {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let mut deduped = Vec::with_capacity(payload.usernames.len());
+    let mut seen = HashSet::with_capacity(payload.usernames.len());
+    for raw_username in payload.usernames {
+        let username = Username::try_from(raw_username).map_err(|_| AuthFailure::InvalidRequest)?;
+        if seen.insert(username.as_str().to_owned()) {
+            deduped.push(username);
+        }
+    }
+    if deduped.is_empty() {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let repository = AuthRepository::from_state(&state);
+    let users = repository.lookup_users_by_username(&deduped).await?;
+    Ok(Json(UserLookupResponse { users }))
+}
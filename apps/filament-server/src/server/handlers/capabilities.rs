@@ -0,0 +1,34 @@
+use axum::{extract::State, Json};
+use filament_core::ChannelKind;
+
+use crate::server::{
+    auth::MAX_MESSAGE_CONTENT_BYTES,
+    core::{AppState, MAX_ATTACHMENTS_PER_MESSAGE},
+    types::{CapabilitiesResponse, CaptchaCapability},
+};
+
+/// Advertises the limits and optional features this server runs with so
+/// clients do not have to hardcode them. Only public values are exposed: the
+/// hCaptcha site key is meant for clients, the secret is not.
+pub(crate) async fn get_capabilities(State(state): State<AppState>) -> Json<CapabilitiesResponse> {
+    let runtime = &state.runtime;
+    Json(CapabilitiesResponse {
+        max_message_content_bytes: MAX_MESSAGE_CONTENT_BYTES,
+        max_attachments_per_message: MAX_ATTACHMENTS_PER_MESSAGE,
+        max_attachment_bytes: runtime.max_attachment_bytes,
+        max_message_attachment_total_bytes: runtime.max_message_attachment_total_bytes,
+        user_attachment_quota_bytes: runtime.user_attachment_quota_bytes,
+        max_profile_avatar_bytes: runtime.max_profile_avatar_bytes,
+        max_profile_banner_bytes: runtime.max_profile_banner_bytes,
+        history_default_limit: runtime.history_default_limit,
+        history_max_limit: runtime.history_max_limit,
+        search_query_max_chars: runtime.search_query_max_chars,
+        channel_kinds: vec![ChannelKind::Text, ChannelKind::Voice, ChannelKind::Category],
+        captcha: runtime.captcha.as_ref().map(|captcha| CaptchaCapability {
+            provider: "hcaptcha",
+            site_key: captcha.site_key.clone(),
+        }),
+        voice_enabled: state.livekit.is_some(),
+        search_enabled: state.search.is_some(),
+    })
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use axum::{
     extract::{Path, State},
     http::{HeaderMap, StatusCode},
@@ -10,14 +12,15 @@ use ulid::Ulid;
 use crate::server::{
     auth::{authenticate, now_unix},
     core::{AppState, FriendshipRequestRecord},
+    domain::{enforce_min_account_age, friend_records_for_user},
     errors::AuthFailure,
     gateway_events,
     metrics::record_gateway_event_dropped,
     realtime::broadcast_user_event,
     types::{
-        CreateFriendRequest, FriendListResponse, FriendPath, FriendRecordResponse,
-        FriendRequestPath, FriendshipRequestCreateResponse, FriendshipRequestListResponse,
-        FriendshipRequestResponse, ModerationResponse,
+        CreateFriendRequest, FriendListResponse, FriendPath, FriendRequestPath,
+        FriendshipRequestCreateResponse, FriendshipRequestListResponse, FriendshipRequestResponse,
+        ModerationResponse, RelationshipResponse, RelationshipStatus, UserPath,
     },
 };
 
@@ -43,6 +46,7 @@ pub(crate) async fn create_friend_request(
     if recipient_user_id == auth.user_id {
         return Err(AuthFailure::InvalidRequest);
     }
+    enforce_min_account_age(&state, auth.user_id).await?;
 
     let request_id = Ulid::new().to_string();
     let created_at_unix = now_unix();
@@ -91,6 +95,19 @@ pub(crate) async fn create_friend_request(
             return Err(AuthFailure::InvalidRequest);
         }
 
+        let pending_incoming: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM friendship_requests WHERE recipient_user_id = $1",
+        )
+        .bind(&recipient_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let limit =
+            i64::try_from(state.runtime.max_incoming_friend_requests_per_user).unwrap_or(i64::MAX);
+        if pending_incoming >= limit {
+            return Err(AuthFailure::IncomingFriendRequestLimitReached);
+        }
+
         sqlx::query(
             "INSERT INTO friendship_requests (request_id, sender_user_id, recipient_user_id, created_at_unix)
              VALUES ($1, $2, $3, $4)",
@@ -116,7 +133,7 @@ pub(crate) async fn create_friend_request(
         }
         drop(friendships);
 
-        let requests = state.friendship_requests.read().await;
+        let mut requests = state.friendship_requests.write().await;
         let exists = requests.values().any(|request| {
             (request.sender_user_id == auth.user_id
                 && request.recipient_user_id == recipient_user_id)
@@ -126,9 +143,23 @@ pub(crate) async fn create_friend_request(
         if exists {
             return Err(AuthFailure::InvalidRequest);
         }
-        drop(requests);
-
-        state.friendship_requests.write().await.insert(
+        let pending_incoming = requests
+            .values()
+            .filter(|request| request.recipient_user_id == recipient_user_id)
+            .count();
+        if pending_incoming >= state.runtime.max_incoming_friend_requests_per_user {
+            return Err(AuthFailure::IncomingFriendRequestLimitReached);
+        }
+        let pending_outgoing = requests
+            .values()
+            .filter(|request| request.sender_user_id == auth.user_id)
+            .count();
+        if pending_outgoing >= state.runtime.in_memory_max_friend_requests
+            || requests.len() >= state.runtime.in_memory_max_total_friend_requests
+        {
+            return Err(AuthFailure::InMemoryCapacityReached);
+        }
+        requests.insert(
             request_id.clone(),
             FriendshipRequestRecord {
                 sender_user_id: auth.user_id,
@@ -265,6 +296,8 @@ pub(crate) async fn list_friend_requests(
     let mut incoming = Vec::new();
     let mut outgoing = Vec::new();
 
+    // Requests are not indexed by user, so this walks the whole map; create
+    // keeps it under `in_memory_max_total_friend_requests`.
     for (request_id, request) in &*requests {
         if request.recipient_user_id == auth.user_id || request.sender_user_id == auth.user_id {
             let sender_id = request.sender_user_id.to_string();
@@ -298,6 +331,55 @@ pub(crate) async fn list_friend_requests(
     Ok(Json(FriendshipRequestListResponse { incoming, outgoing }))
 }
 
+/// Counts each party's friendships, ignoring the pair being created, and
+/// rejects the acceptance when either is already at `max_friends_per_user`.
+async fn ensure_friend_capacity_db(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    limit: usize,
+    pair_a: &str,
+    pair_b: &str,
+) -> Result<(), AuthFailure> {
+    let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+    for user_id in [pair_a, pair_b] {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*)
+             FROM friendships
+             WHERE (user_a_id = $1 OR user_b_id = $1)
+               AND NOT (user_a_id = $2 AND user_b_id = $3)",
+        )
+        .bind(user_id)
+        .bind(pair_a)
+        .bind(pair_b)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        if count >= limit {
+            return Err(AuthFailure::FriendLimitReached);
+        }
+    }
+    Ok(())
+}
+
+fn ensure_friend_capacity_in_memory(
+    friendships: &HashSet<(String, String)>,
+    limit: usize,
+    pair_a: &str,
+    pair_b: &str,
+) -> Result<(), AuthFailure> {
+    for user_id in [pair_a, pair_b] {
+        let count = friendships
+            .iter()
+            .filter(|(left, right)| {
+                (left == user_id || right == user_id) && !(left == pair_a && right == pair_b)
+            })
+            .count();
+        if count >= limit {
+            return Err(AuthFailure::FriendLimitReached);
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_lines)]
 pub(crate) async fn accept_friend_request(
     State(state): State<AppState>,
@@ -339,6 +421,13 @@ pub(crate) async fn accept_friend_request(
         let (pair_a, pair_b) = canonical_friend_pair(sender_user_id, auth.user_id);
         let friendship_created_at_unix = now_unix();
         let mut tx = pool.begin().await.map_err(|_| AuthFailure::Internal)?;
+        ensure_friend_capacity_db(
+            &mut tx,
+            state.runtime.max_friends_per_user,
+            &pair_a,
+            &pair_b,
+        )
+        .await?;
         sqlx::query(
             "INSERT INTO friendships (user_a_id, user_b_id, created_at_unix)
              VALUES ($1, $2, $3)
@@ -426,6 +515,15 @@ pub(crate) async fn accept_friend_request(
         return Err(AuthFailure::NotFound);
     }
     let (pair_a, pair_b) = canonical_friend_pair(request.sender_user_id, request.recipient_user_id);
+    let mut friendships = state.friendships.write().await;
+    ensure_friend_capacity_in_memory(
+        &friendships,
+        state.runtime.max_friends_per_user,
+        &pair_a,
+        &pair_b,
+    )?;
+    friendships.insert((pair_a, pair_b));
+    drop(friendships);
     requests.remove(&path.request_id);
     drop(requests);
     let sender_user_id = request.sender_user_id;
@@ -441,7 +539,6 @@ pub(crate) async fn accept_friend_request(
         .ok_or(AuthFailure::Internal)?;
     drop(user_ids);
     let friendship_created_at_unix = now_unix();
-    state.friendships.write().await.insert((pair_a, pair_b));
     let updated_at_unix = now_unix();
     let recipient_event = match gateway_events::try_friend_request_update(
         &path.request_id,
@@ -609,61 +706,7 @@ pub(crate) async fn list_friends(
     headers: HeaderMap,
 ) -> Result<Json<FriendListResponse>, AuthFailure> {
     let auth = authenticate(&state, &headers).await?;
-    let auth_user_id = auth.user_id.to_string();
-
-    if let Some(pool) = &state.db_pool {
-        let rows = sqlx::query(
-            "SELECT u.user_id, u.username, f.created_at_unix
-             FROM friendships f
-             JOIN users u
-               ON u.user_id = CASE
-                   WHEN f.user_a_id = $1 THEN f.user_b_id
-                   ELSE f.user_a_id
-               END
-             WHERE f.user_a_id = $1 OR f.user_b_id = $1
-             ORDER BY f.created_at_unix DESC",
-        )
-        .bind(&auth_user_id)
-        .fetch_all(pool)
-        .await
-        .map_err(|_| AuthFailure::Internal)?;
-
-        let mut friends = Vec::with_capacity(rows.len());
-        for row in rows {
-            friends.push(FriendRecordResponse {
-                user_id: row.try_get("user_id").map_err(|_| AuthFailure::Internal)?,
-                username: row.try_get("username").map_err(|_| AuthFailure::Internal)?,
-                created_at_unix: row
-                    .try_get("created_at_unix")
-                    .map_err(|_| AuthFailure::Internal)?,
-            });
-        }
-        return Ok(Json(FriendListResponse { friends }));
-    }
-
-    let friendships = state.friendships.read().await;
-    let user_ids = state.user_ids.read().await;
-    let mut friends = Vec::new();
-    for (user_a, user_b) in &*friendships {
-        let friend_user_id = if user_a == &auth_user_id {
-            Some(user_b.clone())
-        } else if user_b == &auth_user_id {
-            Some(user_a.clone())
-        } else {
-            None
-        };
-        if let Some(friend_user_id) = friend_user_id {
-            let Some(username) = user_ids.get(&friend_user_id).cloned() else {
-                continue;
-            };
-            friends.push(FriendRecordResponse {
-                user_id: friend_user_id,
-                username,
-                created_at_unix: 0,
-            });
-        }
-    }
-    friends.sort_by(|left, right| left.user_id.cmp(&right.user_id));
+    let friends = friend_records_for_user(&state, auth.user_id).await?;
     Ok(Json(FriendListResponse { friends }))
 }
 
@@ -746,3 +789,118 @@ pub(crate) async fn remove_friend(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+fn relationship_for_request(
+    caller: UserId,
+    sender_user_id: UserId,
+    request_id: String,
+) -> (RelationshipStatus, Option<String>) {
+    let status = if sender_user_id == caller {
+        RelationshipStatus::OutgoingRequest
+    } else {
+        RelationshipStatus::IncomingRequest
+    };
+    (status, Some(request_id))
+}
+
+async fn relationship_db(
+    pool: &sqlx::PgPool,
+    caller: UserId,
+    other: UserId,
+) -> Result<(RelationshipStatus, Option<String>), AuthFailure> {
+    sqlx::query("SELECT 1 FROM users WHERE user_id = $1")
+        .bind(other.to_string())
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+
+    let (pair_a, pair_b) = canonical_friend_pair(caller, other);
+    let friendship =
+        sqlx::query("SELECT 1 FROM friendships WHERE user_a_id = $1 AND user_b_id = $2")
+            .bind(&pair_a)
+            .bind(&pair_b)
+            .fetch_optional(pool)
+            .await
+            .map_err(|_| AuthFailure::Internal)?;
+    if friendship.is_some() {
+        return Ok((RelationshipStatus::Friends, None));
+    }
+
+    let request = sqlx::query(
+        "SELECT request_id, sender_user_id
+         FROM friendship_requests
+         WHERE (sender_user_id = $1 AND recipient_user_id = $2)
+            OR (sender_user_id = $2 AND recipient_user_id = $1)
+         LIMIT 1",
+    )
+    .bind(caller.to_string())
+    .bind(other.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+    let Some(request) = request else {
+        return Ok((RelationshipStatus::None, None));
+    };
+    let request_id: String = request
+        .try_get("request_id")
+        .map_err(|_| AuthFailure::Internal)?;
+    let sender_user_id: String = request
+        .try_get("sender_user_id")
+        .map_err(|_| AuthFailure::Internal)?;
+    let sender_user_id = UserId::try_from(sender_user_id).map_err(|_| AuthFailure::Internal)?;
+    Ok(relationship_for_request(caller, sender_user_id, request_id))
+}
+
+async fn relationship_in_memory(
+    state: &AppState,
+    caller: UserId,
+    other: UserId,
+) -> Result<(RelationshipStatus, Option<String>), AuthFailure> {
+    if !state.user_ids.read().await.contains_key(&other.to_string()) {
+        return Err(AuthFailure::NotFound);
+    }
+    if state
+        .friendships
+        .read()
+        .await
+        .contains(&canonical_friend_pair(caller, other))
+    {
+        return Ok((RelationshipStatus::Friends, None));
+    }
+    let requests = state.friendship_requests.read().await;
+    Ok(requests
+        .iter()
+        .find(|(_, request)| {
+            (request.sender_user_id == caller && request.recipient_user_id == other)
+                || (request.sender_user_id == other && request.recipient_user_id == caller)
+        })
+        .map_or((RelationshipStatus::None, None), |(request_id, request)| {
+            relationship_for_request(caller, request.sender_user_id, request_id.clone())
+        }))
+}
+
+/// Reports the caller's relationship with one user so profile views do not
+/// need to cross-reference the full friend and request lists.
+pub(crate) async fn get_relationship(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<UserPath>,
+) -> Result<Json<RelationshipResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let other = UserId::try_from(path.user_id).map_err(|_| AuthFailure::InvalidRequest)?;
+    if other == auth.user_id {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let (relationship, request_id) = if let Some(pool) = &state.db_pool {
+        relationship_db(pool, auth.user_id, other).await?
+    } else {
+        relationship_in_memory(&state, auth.user_id, other).await?
+    };
+    Ok(Json(RelationshipResponse {
+        user_id: other.to_string(),
+        relationship,
+        request_id,
+    }))
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     net::SocketAddr,
     time::Instant,
 };
@@ -10,9 +10,11 @@ use axum::{
     Json,
 };
 use filament_core::{
-    can_assign_role_legacy, can_moderate_member_legacy, has_permission_legacy, ChannelKind,
+    apply_channel_overwrite_legacy, base_permissions_legacy, can_assign_role_legacy,
+    can_moderate_member_legacy, can_override_role_legacy, has_permission_legacy, ChannelKind,
     ChannelName, ChannelPermissionOverwrite, GuildName, Permission, Role, UserId,
 };
+use serde::{de::IntoDeserializer, Deserialize};
 use sqlx::Row;
 use ulid::Ulid;
 
@@ -20,11 +22,15 @@ use crate::server::{
     auth::{
         authenticate, enforce_directory_join_rate_limit, extract_client_ip, now_unix, ClientIp,
     },
-    core::{AppState, ChannelRecord, GuildRecord, GuildVisibility},
+    core::{
+        AppState, ChannelPermissionOverrideRecord, ChannelRecord, GuildRecord, GuildVisibility,
+        IndexedGuild, WorkspaceRoleRecord, MAX_CHANNEL_RETENTION_SECS, MAX_GUILD_DESCRIPTION_CHARS,
+        MAX_GUILD_TAGS, MAX_GUILD_TAG_CHARS, MIN_CHANNEL_RETENTION_SECS,
+    },
     db::{
-        channel_kind_from_i16, channel_kind_to_i16, permission_set_from_list,
-        permission_set_to_i64, role_to_i16, seed_hierarchical_permissions_for_new_guild,
-        visibility_from_i16, visibility_to_i16,
+        channel_kind_from_i16, channel_kind_to_i16, permission_list_from_set,
+        permission_set_from_list, permission_set_to_i64, role_from_i16, role_to_i16,
+        seed_hierarchical_permissions_for_new_guild, visibility_from_i16, visibility_to_i16,
     },
     directory_contract::{
         validate_workspace_role_name, AuditListQuery, AuditListQueryDto, DirectoryContractError,
@@ -33,43 +39,65 @@ use crate::server::{
         IpNetwork, WorkspaceRoleId,
     },
     domain::{
-        enforce_guild_ip_ban_for_request, guild_has_active_ip_ban_for_client,
-        guild_permission_snapshot, member_role_in_guild, user_role_in_guild, write_audit_log,
+        channel_permission_snapshot, enforce_guild_ip_ban_for_request, enforce_min_account_age,
+        find_guild_attachment, guild_has_active_ip_ban_for_client, guild_permission_snapshot,
+        i64_to_masked_permissions, member_role_in_guild, resolve_role_channel_permissions,
+        role_ids_from_map, user_role_in_guild, write_audit_log,
     },
     errors::AuthFailure,
     gateway_events,
     metrics::record_gateway_event_dropped,
     permissions::{
-        DEFAULT_ROLE_MEMBER, DEFAULT_ROLE_MODERATOR, MAX_GUILD_ROLES, MAX_MEMBER_ROLE_ASSIGNMENTS,
-        MAX_ROLE_NAME_CHARS, SYSTEM_ROLE_EVERYONE, SYSTEM_ROLE_WORKSPACE_OWNER,
+        DEFAULT_ROLE_MEMBER, DEFAULT_ROLE_MODERATOR, MAX_CHANNEL_ACCESS_MEMBER_ENTRIES,
+        MAX_GUILD_ROLES, MAX_MEMBER_ROLE_ASSIGNMENTS, MAX_ROLE_NAME_CHARS, SYSTEM_ROLE_EVERYONE,
+        SYSTEM_ROLE_WORKSPACE_OWNER,
+    },
+    realtime::{
+        broadcast_guild_event, post_welcome_message, run_guild_directory_search,
+        sync_guild_directory_entry,
     },
-    realtime::broadcast_guild_event,
     types::{
-        ChannelListResponse, ChannelPermissionOverridePath, ChannelResponse, ChannelRolePath,
+        ChannelAccessResponse, ChannelListResponse, ChannelMemberAccessResponse,
+        ChannelOverridePreviewQuery, ChannelPath, ChannelPermissionOverridePath,
+        ChannelPermissionsResponse, ChannelResponse, ChannelRoleAccessResponse,
+        ChannelRoleOverrideListResponse, ChannelRoleOverrideResetResponse,
+        ChannelRoleOverrideResponse, ChannelRolePath, CreateChannelBatchRequest,
         CreateChannelRequest, CreateGuildRequest, CreateGuildRoleRequest,
         DirectoryJoinOutcomeResponse, DirectoryJoinResponse, GuildAuditEventResponse,
-        GuildAuditListResponse, GuildIpBanApplyResponse, GuildIpBanListResponse, GuildIpBanPath,
-        GuildIpBanRecordResponse, GuildListResponse, GuildMemberListResponse,
-        GuildMemberRecordResponse, GuildPath, GuildResponse, GuildRoleListResponse,
-        GuildRoleMemberPath, GuildRolePath, GuildRoleResponse, MemberPath, ModerationResponse,
-        PublicGuildListItem, PublicGuildListQuery, PublicGuildListResponse,
-        ReorderGuildRolesRequest, UpdateChannelPermissionOverrideRequest,
-        UpdateChannelRoleOverrideRequest, UpdateGuildDefaultJoinRoleRequest, UpdateGuildRequest,
-        UpdateGuildRoleRequest, UpdateMemberRoleRequest,
+        GuildAuditListResponse, GuildDetailResponse, GuildIpBanApplyResponse,
+        GuildIpBanListResponse, GuildIpBanPath, GuildIpBanRecordResponse, GuildListResponse,
+        GuildMemberListResponse, GuildMemberRecordResponse, GuildPath, GuildResponse,
+        GuildRoleListResponse, GuildRoleMemberPath, GuildRolePath, GuildRoleResponse,
+        GuildSystemChannelResponse, GuildTagsResponse, MemberPath, ModerationResponse,
+        PermissionOverrideTargetKind, PublicGuildListItem, PublicGuildListQuery,
+        PublicGuildListResponse, ReorderGuildRolesRequest, UpdateChannelCategoryRequest,
+        UpdateChannelPermissionOverrideRequest, UpdateChannelRequest,
+        UpdateChannelRetentionRequest, UpdateChannelRoleOverrideRequest,
+        UpdateChannelVotingRequest, UpdateGuildDefaultJoinRoleRequest, UpdateGuildRequest,
+        UpdateGuildRoleRequest, UpdateGuildSystemChannelRequest, UpdateGuildTagsRequest,
+        UpdateMemberRoleRequest,
     },
 };
 
-pub(crate) async fn create_guild(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Json(payload): Json<CreateGuildRequest>,
-) -> Result<Json<GuildResponse>, AuthFailure> {
-    let auth = authenticate(&state, &headers).await?;
+#[allow(clippy::too_many_lines)]
+async fn insert_guild(
+    state: &AppState,
+    user_id: UserId,
+    payload: CreateGuildRequest,
+) -> Result<GuildResponse, AuthFailure> {
     let name = GuildName::try_from(payload.name).map_err(|_| AuthFailure::InvalidRequest)?;
     let visibility = payload.visibility.unwrap_or(GuildVisibility::Private);
 
     let guild_id = Ulid::new().to_string();
-    let creator_user_id = auth.user_id.to_string();
+    let response = GuildResponse {
+        guild_id: guild_id.clone(),
+        name: name.as_str().to_owned(),
+        visibility,
+        description: None,
+        icon_attachment_id: None,
+        channels: default_guild_channels(state, payload.default_channels),
+    };
+    let creator_user_id = user_id.to_string();
     let limit = state.runtime.max_created_guilds_per_user;
     if let Some(pool) = &state.db_pool {
         let mut tx = pool.begin().await.map_err(|_| AuthFailure::Internal)?;
@@ -89,11 +117,21 @@ pub(crate) async fn create_guild(
             tracing::warn!(
                 event = "guild.create",
                 outcome = "limit_reached",
-                user_id = %auth.user_id,
+                user_id = %user_id,
                 max_created_guilds_per_user = limit,
             );
             return Err(AuthFailure::GuildCreationLimitReached);
         }
+        if !state.runtime.guild_create_cooldown.is_zero() {
+            let last_created_at_unix = sqlx::query_scalar::<_, Option<i64>>(
+                "SELECT MAX(created_at_unix) FROM guilds WHERE created_by_user_id = $1",
+            )
+            .bind(&creator_user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|_| AuthFailure::Internal)?;
+            enforce_guild_create_cooldown(state, user_id, last_created_at_unix)?;
+        }
         sqlx::query(
             "INSERT INTO guilds
                 (guild_id, name, visibility, created_by_user_id, default_join_role_id, created_at_unix)
@@ -117,51 +155,185 @@ pub(crate) async fn create_guild(
         seed_hierarchical_permissions_for_new_guild(&mut tx, &guild_id, &creator_user_id)
             .await
             .map_err(|_| AuthFailure::Internal)?;
+        insert_channel_rows(&mut tx, &guild_id, &response.channels)
+            .await
+            .map_err(|_| AuthFailure::Internal)?;
         tx.commit().await.map_err(|_| AuthFailure::Internal)?;
 
-        return Ok(Json(GuildResponse {
-            guild_id,
-            name: name.as_str().to_owned(),
-            visibility,
-        }));
+        return Ok(response);
     }
 
     let mut members = HashMap::new();
-    members.insert(auth.user_id, Role::Owner);
+    members.insert(user_id, Role::Owner);
 
     let mut guilds = state.membership_store.guilds().write().await;
+    if guilds.len() >= state.runtime.in_memory_max_guilds {
+        tracing::warn!(
+            event = "guild.create",
+            outcome = "in_memory_capacity_reached",
+            in_memory_max_guilds = state.runtime.in_memory_max_guilds,
+        );
+        return Err(AuthFailure::InMemoryCapacityReached);
+    }
     let current_count = guilds
         .values()
-        .filter(|record| record.created_by_user_id == auth.user_id)
+        .filter(|record| record.created_by_user_id == user_id)
         .count();
     if current_count >= limit {
         tracing::warn!(
             event = "guild.create",
             outcome = "limit_reached",
-            user_id = %auth.user_id,
+            user_id = %user_id,
             max_created_guilds_per_user = limit,
         );
         return Err(AuthFailure::GuildCreationLimitReached);
     }
+    if !state.runtime.guild_create_cooldown.is_zero() {
+        // In-memory guilds keep no creation time, but their ULID ids do.
+        let last_created_at_unix = guilds
+            .iter()
+            .filter(|(_, record)| record.created_by_user_id == user_id)
+            .filter_map(|(guild_id, _)| Ulid::from_string(guild_id).ok())
+            .map(|guild_id| i64::try_from(guild_id.timestamp_ms() / 1000).unwrap_or(i64::MAX))
+            .max();
+        enforce_guild_create_cooldown(state, user_id, last_created_at_unix)?;
+    }
 
     guilds.insert(
         guild_id.clone(),
         GuildRecord {
             name: name.as_str().to_owned(),
             visibility,
-            created_by_user_id: auth.user_id,
+            created_by_user_id: user_id,
             default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
             members,
             banned_members: HashSet::new(),
-            channels: HashMap::new(),
+            channels: channel_records_from_responses(&response.channels),
         },
     );
 
-    Ok(Json(GuildResponse {
-        guild_id,
-        name: name.as_str().to_owned(),
-        visibility,
-    }))
+    Ok(response)
+}
+
+/// Rejects a create that comes sooner than `guild_create_cooldown` after the
+/// user's most recent guild, telling the client how long to wait.
+fn enforce_guild_create_cooldown(
+    state: &AppState,
+    user_id: UserId,
+    last_created_at_unix: Option<i64>,
+) -> Result<(), AuthFailure> {
+    let Some(last_created_at_unix) = last_created_at_unix else {
+        return Ok(());
+    };
+    let cooldown_secs =
+        i64::try_from(state.runtime.guild_create_cooldown.as_secs()).unwrap_or(i64::MAX);
+    let elapsed = now_unix().saturating_sub(last_created_at_unix);
+    if elapsed >= cooldown_secs {
+        return Ok(());
+    }
+    tracing::warn!(
+        event = "guild.create",
+        outcome = "cooldown",
+        user_id = %user_id,
+        guild_create_cooldown_secs = cooldown_secs,
+    );
+    Err(AuthFailure::RateLimitedRetryAfter(
+        u64::try_from(cooldown_secs.saturating_sub(elapsed)).unwrap_or(1),
+    ))
+}
+
+/// The channels a new guild starts with: `general`, plus `voice` when the
+/// server enables it. `requested` overrides whether any are created.
+fn default_guild_channels(state: &AppState, requested: Option<bool>) -> Vec<ChannelResponse> {
+    if !requested.unwrap_or(state.runtime.create_default_guild_channels) {
+        return Vec::new();
+    }
+    let mut channels = vec![("general", ChannelKind::Text)];
+    if state.runtime.create_default_guild_voice_channel {
+        channels.push(("voice", ChannelKind::Voice));
+    }
+    channels
+        .into_iter()
+        .map(|(name, kind)| ChannelResponse {
+            channel_id: Ulid::new().to_string(),
+            name: String::from(name),
+            kind,
+            locked: false,
+            retention_secs: None,
+            voting: false,
+            category_id: None,
+        })
+        .collect()
+}
+
+async fn insert_channel_rows(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    guild_id: &str,
+    channels: &[ChannelResponse],
+) -> Result<(), sqlx::Error> {
+    let created_at_unix = now_unix();
+    for channel in channels {
+        sqlx::query(
+            "INSERT INTO channels (channel_id, guild_id, name, kind, created_at_unix, category_id)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(&channel.channel_id)
+        .bind(guild_id)
+        .bind(&channel.name)
+        .bind(channel_kind_to_i16(channel.kind))
+        .bind(created_at_unix)
+        .bind(&channel.category_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+fn channel_records_from_responses(channels: &[ChannelResponse]) -> HashMap<String, ChannelRecord> {
+    channels
+        .iter()
+        .map(|channel| {
+            (
+                channel.channel_id.clone(),
+                ChannelRecord {
+                    name: channel.name.clone(),
+                    kind: channel.kind,
+                    locked: false,
+                    retention_secs: None,
+                    voting: false,
+                    category_id: channel.category_id.clone(),
+                    messages: Vec::new(),
+                    role_overrides: HashMap::new(),
+                },
+            )
+        })
+        .collect()
+}
+
+pub(crate) async fn create_guild(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateGuildRequest>,
+) -> Result<Json<GuildResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    enforce_min_account_age(&state, auth.user_id).await?;
+    let response = insert_guild(&state, auth.user_id, payload).await?;
+    sync_guild_directory_from_response(&state, &response).await;
+    Ok(Json(response))
+}
+
+async fn sync_guild_directory_from_response(state: &AppState, guild: &GuildResponse) {
+    let entry = (guild.visibility == GuildVisibility::Public).then(|| IndexedGuild {
+        guild_id: guild.guild_id.clone(),
+        name: guild.name.clone(),
+        description: guild.description.clone(),
+    });
+    sync_guild_directory_entry(state, &guild.guild_id, entry).await;
 }
 
 pub(crate) const MAX_GUILD_LIST_LIMIT: usize = 200;
@@ -174,7 +346,7 @@ pub(crate) async fn list_guilds(
 
     if let Some(pool) = &state.db_pool {
         let rows = sqlx::query(
-            "SELECT g.guild_id, g.name, g.visibility
+            "SELECT g.guild_id, g.name, g.visibility, g.description, g.icon_attachment_id
              FROM guild_members gm
              JOIN guilds g ON g.guild_id = gm.guild_id
              LEFT JOIN guild_bans gb ON gb.guild_id = gm.guild_id AND gb.user_id = gm.user_id
@@ -199,6 +371,13 @@ pub(crate) async fn list_guilds(
                 guild_id: row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?,
                 name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
                 visibility,
+                description: row
+                    .try_get("description")
+                    .map_err(|_| AuthFailure::Internal)?,
+                icon_attachment_id: row
+                    .try_get("icon_attachment_id")
+                    .map_err(|_| AuthFailure::Internal)?,
+                channels: Vec::new(),
             });
         }
         return Ok(Json(GuildListResponse { guilds }));
@@ -218,6 +397,9 @@ pub(crate) async fn list_guilds(
                 guild_id: guild_id.clone(),
                 name: guild.name.clone(),
                 visibility: guild.visibility,
+                description: guild.description.clone(),
+                icon_attachment_id: guild.icon_attachment_id.clone(),
+                channels: Vec::new(),
             })
         })
         .collect::<Vec<_>>();
@@ -226,6 +408,113 @@ pub(crate) async fn list_guilds(
     Ok(Json(GuildListResponse { guilds: response }))
 }
 
+/// Returns one guild's profile, member count, and the caller's role. Only
+/// members can read it; anyone else gets `404` as if the guild did not exist.
+pub(crate) async fn get_guild(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<GuildPath>,
+) -> Result<Json<GuildDetailResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (role, _) = guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+
+    let mut response = if let Some(pool) = &state.db_pool {
+        let row = sqlx::query(
+            "SELECT g.name, g.visibility, g.description, g.icon_attachment_id,
+                    (SELECT COUNT(*) FROM guild_members gm WHERE gm.guild_id = g.guild_id)
+                        AS member_count
+             FROM guilds g
+             WHERE g.guild_id = $1",
+        )
+        .bind(&path.guild_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        let visibility_raw: i16 = row
+            .try_get("visibility")
+            .map_err(|_| AuthFailure::Internal)?;
+        let member_count: i64 = row
+            .try_get("member_count")
+            .map_err(|_| AuthFailure::Internal)?;
+        GuildDetailResponse {
+            guild_id: path.guild_id.clone(),
+            name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
+            visibility: visibility_from_i16(visibility_raw).ok_or(AuthFailure::Internal)?,
+            description: row
+                .try_get("description")
+                .map_err(|_| AuthFailure::Internal)?,
+            icon_attachment_id: row
+                .try_get("icon_attachment_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            tags: Vec::new(),
+            member_count: u64::try_from(member_count).map_err(|_| AuthFailure::Internal)?,
+            role,
+        }
+    } else {
+        let guilds = state.membership_store.guilds().read().await;
+        let guild = guilds.get(&path.guild_id).ok_or(AuthFailure::NotFound)?;
+        GuildDetailResponse {
+            guild_id: path.guild_id.clone(),
+            name: guild.name.clone(),
+            visibility: guild.visibility,
+            description: guild.description.clone(),
+            icon_attachment_id: guild.icon_attachment_id.clone(),
+            tags: Vec::new(),
+            member_count: u64::try_from(guild.members.len()).unwrap_or(u64::MAX),
+            role,
+        }
+    };
+    response.tags = load_guild_tags(&state, std::slice::from_ref(&path.guild_id))
+        .await?
+        .remove(&path.guild_id)
+        .unwrap_or_default();
+    Ok(Json(response))
+}
+
+fn parse_guild_description(raw: &str) -> Result<Option<String>, AuthFailure> {
+    let description = raw.trim();
+    if description.is_empty() {
+        return Ok(None);
+    }
+    if description.chars().count() > MAX_GUILD_DESCRIPTION_CHARS
+        || description.chars().any(|ch| ch.is_control() && ch != '\n')
+    {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(Some(description.to_owned()))
+}
+
+/// Resolves a requested guild icon. The icon must be an image the actor
+/// uploaded to this guild; an empty id clears the icon.
+async fn resolve_guild_icon(
+    state: &AppState,
+    guild_id: &str,
+    actor_user_id: UserId,
+    raw: &str,
+) -> Result<Option<String>, AuthFailure> {
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    let attachment_id = Ulid::from_string(raw)
+        .map_err(|_| AuthFailure::InvalidRequest)?
+        .to_string();
+    let attachment = find_guild_attachment(state, guild_id, &attachment_id)
+        .await
+        .map_err(|error| match error {
+            AuthFailure::NotFound => AuthFailure::InvalidRequest,
+            other => other,
+        })?;
+    if attachment.owner_id != actor_user_id || !attachment.mime_type.starts_with("image/") {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(Some(attachment.attachment_id))
+}
+
+/// Updates a guild's name, visibility, description, or icon. Renames need
+/// `manage_roles`; visibility, description, and icon changes are reserved
+/// for the owner. Making a guild private takes effect for the next directory
+/// join, which re-checks visibility.
 pub(crate) async fn update_guild(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -233,7 +522,8 @@ pub(crate) async fn update_guild(
     Json(payload): Json<UpdateGuildRequest>,
 ) -> Result<Json<GuildResponse>, AuthFailure> {
     let auth = authenticate(&state, &headers).await?;
-    let (_, permissions) = guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    let (actor_role, permissions) =
+        guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
     if !permissions.contains(Permission::ManageRoles) {
         return Err(AuthFailure::Forbidden);
     }
@@ -244,20 +534,39 @@ pub(crate) async fn update_guild(
         .transpose()
         .map_err(|_| AuthFailure::InvalidRequest)?;
     let visibility = payload.visibility;
-    if name.is_none() && visibility.is_none() {
+    let description = payload
+        .description
+        .as_deref()
+        .map(parse_guild_description)
+        .transpose()?;
+    if name.is_none()
+        && visibility.is_none()
+        && description.is_none()
+        && payload.icon_attachment_id.is_none()
+    {
         return Err(AuthFailure::InvalidRequest);
     }
+    if (description.is_some() || payload.icon_attachment_id.is_some()) && actor_role != Role::Owner
+    {
+        return Err(AuthFailure::Forbidden);
+    }
+    let icon_attachment_id = match payload.icon_attachment_id.as_deref() {
+        Some(raw) => Some(resolve_guild_icon(&state, &path.guild_id, auth.user_id, raw).await?),
+        None => None,
+    };
 
     let mut changed_name: Option<String> = None;
     let mut changed_visibility: Option<GuildVisibility> = None;
     let updated_at_unix = now_unix();
     let response = if let Some(pool) = &state.db_pool {
-        let current = sqlx::query("SELECT name, visibility FROM guilds WHERE guild_id = $1")
-            .bind(&path.guild_id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|_| AuthFailure::Internal)?
-            .ok_or(AuthFailure::NotFound)?;
+        let current = sqlx::query(
+            "SELECT name, visibility, description, icon_attachment_id FROM guilds WHERE guild_id = $1",
+        )
+        .bind(&path.guild_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
 
         let current_name: String = current.try_get("name").map_err(|_| AuthFailure::Internal)?;
         let current_visibility_raw: i16 = current
@@ -265,24 +574,37 @@ pub(crate) async fn update_guild(
             .map_err(|_| AuthFailure::Internal)?;
         let current_visibility =
             visibility_from_i16(current_visibility_raw).ok_or(AuthFailure::Internal)?;
+        let current_description: Option<String> = current
+            .try_get("description")
+            .map_err(|_| AuthFailure::Internal)?;
+        let current_icon_attachment_id: Option<String> = current
+            .try_get("icon_attachment_id")
+            .map_err(|_| AuthFailure::Internal)?;
 
         let next_name = name.clone().unwrap_or(current_name.clone());
         let next_visibility = visibility.unwrap_or(current_visibility);
+        let next_description = description.unwrap_or(current_description);
+        let next_icon_attachment_id = icon_attachment_id.unwrap_or(current_icon_attachment_id);
         if next_name != current_name {
             changed_name = Some(next_name.clone());
         }
         if next_visibility != current_visibility {
+            if actor_role != Role::Owner {
+                return Err(AuthFailure::Forbidden);
+            }
             changed_visibility = Some(next_visibility);
         }
 
         let update = sqlx::query(
             "UPDATE guilds
-             SET name = $2, visibility = $3
+             SET name = $2, visibility = $3, description = $4, icon_attachment_id = $5
              WHERE guild_id = $1",
         )
         .bind(&path.guild_id)
         .bind(&next_name)
         .bind(visibility_to_i16(next_visibility))
+        .bind(next_description.as_deref())
+        .bind(next_icon_attachment_id.as_deref())
         .execute(pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
@@ -294,12 +616,18 @@ pub(crate) async fn update_guild(
             guild_id: path.guild_id.clone(),
             name: next_name,
             visibility: next_visibility,
+            description: next_description,
+            icon_attachment_id: next_icon_attachment_id,
+            channels: Vec::new(),
         }
     } else {
         let mut guilds = state.membership_store.guilds().write().await;
         let guild = guilds
             .get_mut(&path.guild_id)
             .ok_or(AuthFailure::NotFound)?;
+        if visibility.is_some_and(|next| next != guild.visibility) && actor_role != Role::Owner {
+            return Err(AuthFailure::Forbidden);
+        }
 
         if let Some(next_name) = name {
             if next_name != guild.name {
@@ -313,14 +641,46 @@ pub(crate) async fn update_guild(
                 guild.visibility = next_visibility;
             }
         }
+        if let Some(next_description) = description {
+            guild.description = next_description;
+        }
+        if let Some(next_icon_attachment_id) = icon_attachment_id {
+            guild.icon_attachment_id = next_icon_attachment_id;
+        }
 
         GuildResponse {
             guild_id: path.guild_id.clone(),
             name: guild.name.clone(),
             visibility: guild.visibility,
+            description: guild.description.clone(),
+            icon_attachment_id: guild.icon_attachment_id.clone(),
+            channels: Vec::new(),
         }
     };
 
+    if changed_name.is_some() || changed_visibility.is_some() || payload.description.is_some() {
+        sync_guild_directory_from_response(&state, &response).await;
+    }
+    if changed_name.is_some()
+        || changed_visibility.is_some()
+        || payload.description.is_some()
+        || payload.icon_attachment_id.is_some()
+    {
+        write_audit_log(
+            &state,
+            Some(path.guild_id.clone()),
+            auth.user_id,
+            None,
+            "guild.update",
+            serde_json::json!({
+                "name": changed_name,
+                "visibility": changed_visibility,
+                "description_updated": payload.description.is_some(),
+                "icon_updated": payload.icon_attachment_id.is_some(),
+            }),
+        )
+        .await?;
+    }
     if changed_name.is_some() || changed_visibility.is_some() {
         let event = match gateway_events::try_workspace_update(
             &path.guild_id,
@@ -560,32 +920,7 @@ async fn enforce_audit_access(
     guild_id: &str,
     user_id: UserId,
 ) -> Result<(), AuthFailure> {
-    if let Some(pool) = &state.db_pool {
-        let guild_exists = sqlx::query("SELECT 1 FROM guilds WHERE guild_id = $1")
-            .bind(guild_id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|_| AuthFailure::Internal)?
-            .is_some();
-        if !guild_exists {
-            return Err(AuthFailure::NotFound);
-        }
-    } else if !state
-        .membership_store
-        .guilds()
-        .read()
-        .await
-        .contains_key(guild_id)
-    {
-        return Err(AuthFailure::NotFound);
-    }
-
-    let (_, permissions) = match guild_permission_snapshot(state, user_id, guild_id).await {
-        Ok(value) => value,
-        Err(AuthFailure::Forbidden) => return Err(AuthFailure::AuditAccessDenied),
-        Err(AuthFailure::NotFound) => return Err(AuthFailure::NotFound),
-        Err(error) => return Err(error),
-    };
+    let (_, permissions) = guild_permission_snapshot(state, user_id, guild_id).await?;
     if !permissions.contains(Permission::ViewAuditLog) {
         return Err(AuthFailure::AuditAccessDenied);
     }
@@ -597,32 +932,7 @@ async fn enforce_guild_ip_ban_moderation_access(
     guild_id: &str,
     user_id: UserId,
 ) -> Result<(), AuthFailure> {
-    if let Some(pool) = &state.db_pool {
-        let guild_exists = sqlx::query("SELECT 1 FROM guilds WHERE guild_id = $1")
-            .bind(guild_id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|_| AuthFailure::Internal)?
-            .is_some();
-        if !guild_exists {
-            return Err(AuthFailure::NotFound);
-        }
-    } else if !state
-        .membership_store
-        .guilds()
-        .read()
-        .await
-        .contains_key(guild_id)
-    {
-        return Err(AuthFailure::NotFound);
-    }
-
-    let (_, permissions) = match guild_permission_snapshot(state, user_id, guild_id).await {
-        Ok(value) => value,
-        Err(AuthFailure::Forbidden) => return Err(AuthFailure::Forbidden),
-        Err(AuthFailure::NotFound) => return Err(AuthFailure::NotFound),
-        Err(error) => return Err(error),
-    };
+    let (_, permissions) = guild_permission_snapshot(state, user_id, guild_id).await?;
     if permissions.contains(Permission::ManageIpBans) {
         Ok(())
     } else {
@@ -800,7 +1110,20 @@ async fn load_actor_role_context(
     if !permissions.contains(required_permission) && !is_server_owner {
         return Err(AuthFailure::Forbidden);
     }
+    resolve_role_context(state, guild_id, user_id).await
+}
 
+/// Resolves a member's place in the role hierarchy without checking any
+/// permission, for comparing an actor against a target member.
+async fn resolve_role_context(
+    state: &AppState,
+    guild_id: &str,
+    user_id: UserId,
+) -> Result<RoleManageContext, AuthFailure> {
+    let is_server_owner = state
+        .runtime
+        .server_owner_user_id
+        .is_some_and(|owner| owner == user_id);
     let roles = if let Some(pool) = &state.db_pool {
         guild_roles_db(pool, guild_id).await?
     } else {
@@ -1473,39 +1796,285 @@ pub(crate) async fn update_guild_default_join_role(
     Ok(Json(ModerationResponse { accepted: true }))
 }
 
-pub(crate) async fn create_guild_role(
+/// Returns where the guild posts system messages and whether joins are
+/// welcomed there.
+pub(crate) async fn get_guild_system_channel(
     State(state): State<AppState>,
     headers: HeaderMap,
     Path(path): Path<GuildPath>,
-    Json(payload): Json<CreateGuildRoleRequest>,
-) -> Result<Json<GuildRoleResponse>, AuthFailure> {
+) -> Result<Json<GuildSystemChannelResponse>, AuthFailure> {
     let auth = authenticate(&state, &headers).await?;
-    let context = load_actor_role_context(
-        &state,
-        &path.guild_id,
-        auth.user_id,
-        Permission::ManageWorkspaceRoles,
-    )
-    .await?;
-    let name = validate_role_name_input(&payload.name)?;
-    let permissions = permission_set_from_list(&payload.permissions);
-    let color_hex = payload
-        .color_hex
-        .as_deref()
-        .map(validate_role_color_hex_input)
-        .transpose()?;
+    guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
 
-    let position = payload
-        .position
-        .unwrap_or_else(|| context.highest_position.saturating_sub(1).max(1));
-    if !context.is_server_owner
-        && !context.is_workspace_owner
-        && position >= context.highest_position
-    {
-        return Err(AuthFailure::Forbidden);
-    }
+    let response = if let Some(pool) = &state.db_pool {
+        let row = sqlx::query(
+            "SELECT system_channel_id, welcome_messages FROM guilds WHERE guild_id = $1",
+        )
+        .bind(&path.guild_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        GuildSystemChannelResponse {
+            system_channel_id: row
+                .try_get("system_channel_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            welcome_messages: row
+                .try_get("welcome_messages")
+                .map_err(|_| AuthFailure::Internal)?,
+        }
+    } else {
+        let guilds = state.membership_store.guilds().read().await;
+        let guild = guilds.get(&path.guild_id).ok_or(AuthFailure::NotFound)?;
+        GuildSystemChannelResponse {
+            system_channel_id: guild.system_channel_id.clone(),
+            welcome_messages: guild.welcome_messages,
+        }
+    };
+    Ok(Json(response))
+}
 
-    let role_id = WorkspaceRoleId::new().to_string();
+/// Sets the guild's system channel and welcome toggle. The channel must be a
+/// text channel in the guild, and welcomes need a channel to post to.
+pub(crate) async fn update_guild_system_channel(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<GuildPath>,
+    Json(payload): Json<UpdateGuildSystemChannelRequest>,
+) -> Result<Json<GuildSystemChannelResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (_, permissions) = guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !permissions.contains(Permission::ManageRoles) {
+        return Err(AuthFailure::Forbidden);
+    }
+    if payload.welcome_messages && payload.system_channel_id.is_none() {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    if let Some(pool) = &state.db_pool {
+        if let Some(channel_id) = &payload.system_channel_id {
+            let kind = sqlx::query_scalar::<_, i16>(
+                "SELECT kind FROM channels WHERE guild_id = $1 AND channel_id = $2",
+            )
+            .bind(&path.guild_id)
+            .bind(channel_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|_| AuthFailure::Internal)?
+            .and_then(channel_kind_from_i16);
+            if kind != Some(ChannelKind::Text) {
+                return Err(AuthFailure::InvalidRequest);
+            }
+        }
+        let updated = sqlx::query(
+            "UPDATE guilds SET system_channel_id = $2, welcome_messages = $3 WHERE guild_id = $1",
+        )
+        .bind(&path.guild_id)
+        .bind(&payload.system_channel_id)
+        .bind(payload.welcome_messages)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        if updated.rows_affected() == 0 {
+            return Err(AuthFailure::NotFound);
+        }
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let guild = guilds
+            .get_mut(&path.guild_id)
+            .ok_or(AuthFailure::NotFound)?;
+        if let Some(channel_id) = &payload.system_channel_id {
+            let kind = guild.channels.get(channel_id).map(|channel| channel.kind);
+            if kind != Some(ChannelKind::Text) {
+                return Err(AuthFailure::InvalidRequest);
+            }
+        }
+        guild
+            .system_channel_id
+            .clone_from(&payload.system_channel_id);
+        guild.welcome_messages = payload.welcome_messages;
+    }
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        None,
+        "guild.system_channel.update",
+        serde_json::json!({
+            "system_channel_id": payload.system_channel_id,
+            "welcome_messages": payload.welcome_messages,
+        }),
+    )
+    .await?;
+
+    Ok(Json(GuildSystemChannelResponse {
+        system_channel_id: payload.system_channel_id,
+        welcome_messages: payload.welcome_messages,
+    }))
+}
+
+/// Tags are lowercase ASCII letters, digits, and inner dashes so they stay
+/// stable as filter values.
+fn parse_guild_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().to_ascii_lowercase();
+    let valid = !tag.is_empty()
+        && tag.len() <= MAX_GUILD_TAG_CHARS
+        && !tag.starts_with('-')
+        && !tag.ends_with('-')
+        && tag
+            .bytes()
+            .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-');
+    valid.then_some(tag)
+}
+
+fn parse_guild_tags(raw: &[String]) -> Result<Vec<String>, AuthFailure> {
+    let tags = raw
+        .iter()
+        .map(|value| parse_guild_tag(value).ok_or(AuthFailure::InvalidRequest))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    if tags.len() > MAX_GUILD_TAGS {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(tags.into_iter().collect())
+}
+
+/// Loads the sorted tags for each listed guild that has any.
+async fn load_guild_tags(
+    state: &AppState,
+    guild_ids: &[String],
+) -> Result<HashMap<String, Vec<String>>, AuthFailure> {
+    let Some(pool) = &state.db_pool else {
+        let tags = state.guild_tags.read().await;
+        return Ok(guild_ids
+            .iter()
+            .filter_map(|guild_id| {
+                tags.get(guild_id)
+                    .map(|guild_tags| (guild_id.clone(), guild_tags.clone()))
+            })
+            .collect());
+    };
+    let rows = sqlx::query(
+        "SELECT guild_id, tag
+         FROM guild_tags
+         WHERE guild_id = ANY($1)
+         ORDER BY guild_id, tag",
+    )
+    .bind(guild_ids)
+    .fetch_all(pool)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        let guild_id: String = row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?;
+        let tag: String = row.try_get("tag").map_err(|_| AuthFailure::Internal)?;
+        tags.entry(guild_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+pub(crate) async fn get_guild_tags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<GuildPath>,
+) -> Result<Json<GuildTagsResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+
+    let tags = load_guild_tags(&state, std::slice::from_ref(&path.guild_id))
+        .await?
+        .remove(&path.guild_id)
+        .unwrap_or_default();
+    Ok(Json(GuildTagsResponse { tags }))
+}
+
+/// Replaces the guild's discovery tags. Owner only, like the description and
+/// icon that also shape the guild's directory listing.
+pub(crate) async fn update_guild_tags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<GuildPath>,
+    Json(payload): Json<UpdateGuildTagsRequest>,
+) -> Result<Json<GuildTagsResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (actor_role, _) = guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if actor_role != Role::Owner {
+        return Err(AuthFailure::Forbidden);
+    }
+    let tags = parse_guild_tags(&payload.tags)?;
+
+    if let Some(pool) = &state.db_pool {
+        let mut tx = pool.begin().await.map_err(|_| AuthFailure::Internal)?;
+        sqlx::query("DELETE FROM guild_tags WHERE guild_id = $1")
+            .bind(&path.guild_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| AuthFailure::Internal)?;
+        sqlx::query(
+            "INSERT INTO guild_tags (guild_id, tag)
+             SELECT $1, tag FROM UNNEST($2::text[]) AS tag",
+        )
+        .bind(&path.guild_id)
+        .bind(&tags)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        tx.commit().await.map_err(|_| AuthFailure::Internal)?;
+    } else {
+        let mut guild_tags = state.guild_tags.write().await;
+        if tags.is_empty() {
+            guild_tags.remove(&path.guild_id);
+        } else {
+            guild_tags.insert(path.guild_id.clone(), tags.clone());
+        }
+    }
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        None,
+        "guild.tags.update",
+        serde_json::json!({ "tags": tags }),
+    )
+    .await?;
+
+    Ok(Json(GuildTagsResponse { tags }))
+}
+
+pub(crate) async fn create_guild_role(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<GuildPath>,
+    Json(payload): Json<CreateGuildRoleRequest>,
+) -> Result<Json<GuildRoleResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let context = load_actor_role_context(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        Permission::ManageWorkspaceRoles,
+    )
+    .await?;
+    let name = validate_role_name_input(&payload.name)?;
+    let permissions = permission_set_from_list(&payload.permissions);
+    let color_hex = payload
+        .color_hex
+        .as_deref()
+        .map(validate_role_color_hex_input)
+        .transpose()?;
+
+    let position = payload
+        .position
+        .unwrap_or_else(|| context.highest_position.saturating_sub(1).max(1));
+    if !context.is_server_owner
+        && !context.is_workspace_owner
+        && position >= context.highest_position
+    {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let role_id = WorkspaceRoleId::new().to_string();
     if let Some(pool) = &state.db_pool {
         let existing_count =
             sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM guild_roles WHERE guild_id = $1")
@@ -2825,6 +3394,98 @@ pub(crate) async fn remove_guild_ip_ban(
     Ok(Json(ModerationResponse { accepted: true }))
 }
 
+fn public_guild_item_from_row(
+    row: &sqlx::postgres::PgRow,
+) -> Result<Option<PublicGuildListItem>, AuthFailure> {
+    let visibility_raw: i16 = row
+        .try_get("visibility")
+        .map_err(|_| AuthFailure::Internal)?;
+    let visibility = visibility_from_i16(visibility_raw).ok_or(AuthFailure::Internal)?;
+    if visibility != GuildVisibility::Public {
+        return Ok(None);
+    }
+    Ok(Some(PublicGuildListItem {
+        guild_id: row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?,
+        name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
+        visibility,
+        description: row
+            .try_get("description")
+            .map_err(|_| AuthFailure::Internal)?,
+        icon_attachment_id: row
+            .try_get("icon_attachment_id")
+            .map_err(|_| AuthFailure::Internal)?,
+        tags: Vec::new(),
+    }))
+}
+
+/// Loads directory search hits in rank order, dropping any guild that is no
+/// longer public.
+async fn public_guilds_by_id(
+    state: &AppState,
+    guild_ids: &[String],
+) -> Result<Vec<PublicGuildListItem>, AuthFailure> {
+    let mut found: HashMap<String, PublicGuildListItem> = HashMap::new();
+    if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "SELECT guild_id, name, visibility, description, icon_attachment_id
+             FROM guilds
+             WHERE guild_id = ANY($1)",
+        )
+        .bind(guild_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        for row in rows {
+            if let Some(item) = public_guild_item_from_row(&row)? {
+                found.insert(item.guild_id.clone(), item);
+            }
+        }
+    } else {
+        let guilds = state.membership_store.guilds().read().await;
+        for guild_id in guild_ids {
+            let Some(guild) = guilds
+                .get(guild_id)
+                .filter(|guild| guild.visibility == GuildVisibility::Public)
+            else {
+                continue;
+            };
+            found.insert(
+                guild_id.clone(),
+                PublicGuildListItem {
+                    guild_id: guild_id.clone(),
+                    name: guild.name.clone(),
+                    visibility: guild.visibility,
+                    description: guild.description.clone(),
+                    icon_attachment_id: guild.icon_attachment_id.clone(),
+                    tags: Vec::new(),
+                },
+            );
+        }
+    }
+    let mut guilds = guild_ids
+        .iter()
+        .filter_map(|guild_id| found.remove(guild_id))
+        .collect::<Vec<_>>();
+    attach_guild_tags(state, &mut guilds).await?;
+    Ok(guilds)
+}
+
+async fn attach_guild_tags(
+    state: &AppState,
+    guilds: &mut [PublicGuildListItem],
+) -> Result<(), AuthFailure> {
+    let guild_ids = guilds
+        .iter()
+        .map(|guild| guild.guild_id.clone())
+        .collect::<Vec<_>>();
+    let mut tags = load_guild_tags(state, &guild_ids).await?;
+    for guild in guilds {
+        guild.tags = tags.remove(&guild.guild_id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_lines)]
 pub(crate) async fn list_public_guilds(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -2844,46 +3505,95 @@ pub(crate) async fn list_public_guilds(
         return Err(AuthFailure::InvalidRequest);
     }
     let has_query = needle.as_ref().is_some_and(|value| !value.is_empty());
+    let tag = query
+        .tag
+        .as_deref()
+        .map(|raw| parse_guild_tag(raw).ok_or(AuthFailure::InvalidRequest))
+        .transpose()?;
+
+    let cursor = parse_public_guild_page_cursor(query.before.as_deref(), query.after.as_deref())?;
+    let ascending = cursor
+        .as_ref()
+        .is_some_and(|cursor| cursor.direction == PublicGuildPageDirection::After);
+
+    // The directory index carries neither tags nor creation order, so tag
+    // filters and paging take the scan below even when a name query is present.
+    if let Some(term) = needle
+        .as_deref()
+        .filter(|_| has_query && tag.is_none() && cursor.is_none())
+    {
+        if let Ok(guild_ids) = run_guild_directory_search(&state, term, limit).await {
+            let guilds = public_guilds_by_id(&state, &guild_ids).await?;
+            return Ok(Json(PublicGuildListResponse {
+                guilds,
+                next_before: None,
+                next_after: None,
+            }));
+        }
+        tracing::warn!(event = "guilds.public.search_fallback");
+    }
 
     if let Some(pool) = &state.db_pool {
-        let limit_i64 = i64::try_from(limit).map_err(|_| AuthFailure::InvalidRequest)?;
+        let limit_plus_one = i64::try_from(limit + 1).map_err(|_| AuthFailure::InvalidRequest)?;
         let sql_like = needle
             .as_ref()
             .filter(|_| has_query)
             .map(|value| format!("%{value}%"));
+        let position = cursor.as_ref().map(|cursor| &cursor.position);
+        // `after` pages walk forward from the cursor, so they are read oldest
+        // first and flipped back to newest first in `page_public_guilds`.
         let rows = sqlx::query(
-            "SELECT guild_id, name, visibility
+            "SELECT guild_id, name, visibility, description, icon_attachment_id, created_at_unix
              FROM guilds
              WHERE visibility = $1
                AND ($2::text IS NULL OR LOWER(name) LIKE $2)
-             ORDER BY created_at_unix DESC
+               AND ($4::text IS NULL OR EXISTS (
+                   SELECT 1 FROM guild_tags gt
+                   WHERE gt.guild_id = guilds.guild_id AND gt.tag = $4
+               ))
+               AND (
+                   $5::bigint IS NULL
+                   OR ($7 AND (created_at_unix, guild_id) > ($5, $6::text))
+                   OR (NOT $7 AND (created_at_unix, guild_id) < ($5, $6::text))
+               )
+             ORDER BY CASE WHEN $7 THEN created_at_unix END ASC,
+                      CASE WHEN $7 THEN guild_id END ASC,
+                      created_at_unix DESC,
+                      guild_id DESC
              LIMIT $3",
         )
         .bind(visibility_to_i16(GuildVisibility::Public))
         .bind(sql_like)
-        .bind(limit_i64)
+        .bind(limit_plus_one)
+        .bind(tag.as_deref())
+        .bind(position.map(|position| position.created_at_unix))
+        .bind(position.map(|position| position.guild_id.as_str()))
+        .bind(ascending)
         .fetch_all(pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
 
-        let mut guilds = Vec::with_capacity(rows.len());
+        let mut entries = Vec::with_capacity(rows.len());
         for row in rows {
-            let visibility_raw: i16 = row
-                .try_get("visibility")
+            let created_at_unix: i64 = row
+                .try_get("created_at_unix")
                 .map_err(|_| AuthFailure::Internal)?;
-            let visibility = visibility_from_i16(visibility_raw).ok_or(AuthFailure::Internal)?;
-            if visibility != GuildVisibility::Public {
-                continue;
+            if let Some(item) = public_guild_item_from_row(&row)? {
+                entries.push((
+                    PublicGuildCursorPosition {
+                        created_at_unix,
+                        guild_id: item.guild_id.clone(),
+                    },
+                    item,
+                ));
             }
-            guilds.push(PublicGuildListItem {
-                guild_id: row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?,
-                name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
-                visibility,
-            });
         }
-        return Ok(Json(PublicGuildListResponse { guilds }));
+        let mut response = page_public_guilds(entries, limit, cursor.as_ref());
+        attach_guild_tags(&state, &mut response.guilds).await?;
+        return Ok(Json(response));
     }
 
+    let guild_tags = state.guild_tags.read().await;
     let guilds = state.membership_store.guilds().read().await;
     let query_term = needle
         .as_ref()
@@ -2895,21 +3605,138 @@ pub(crate) async fn list_public_guilds(
             if guild.visibility != GuildVisibility::Public {
                 return None;
             }
+            // In-memory guilds keep no creation time, but their ULID ids do.
+            let position = PublicGuildCursorPosition {
+                created_at_unix: Ulid::from_string(guild_id)
+                    .map(|ulid| i64::try_from(ulid.timestamp_ms() / 1000).unwrap_or(i64::MAX))
+                    .unwrap_or_default(),
+                guild_id: guild_id.clone(),
+            };
+            if let Some(cursor) = &cursor {
+                let in_range = match cursor.direction {
+                    PublicGuildPageDirection::Before => position < cursor.position,
+                    PublicGuildPageDirection::After => position > cursor.position,
+                };
+                if !in_range {
+                    return None;
+                }
+            }
             if let Some(term) = query_term {
                 if !guild.name.to_ascii_lowercase().contains(term) {
                     return None;
                 }
             }
-            Some(PublicGuildListItem {
-                guild_id: guild_id.clone(),
-                name: guild.name.clone(),
-                visibility: guild.visibility,
-            })
+            let tags = guild_tags.get(guild_id).cloned().unwrap_or_default();
+            if tag.as_ref().is_some_and(|wanted| !tags.contains(wanted)) {
+                return None;
+            }
+            Some((
+                position,
+                PublicGuildListItem {
+                    guild_id: guild_id.clone(),
+                    name: guild.name.clone(),
+                    visibility: guild.visibility,
+                    description: guild.description.clone(),
+                    icon_attachment_id: guild.icon_attachment_id.clone(),
+                    tags,
+                },
+            ))
         })
         .collect::<Vec<_>>();
-    results.sort_by(|left, right| right.guild_id.cmp(&left.guild_id));
-    results.truncate(limit);
-    Ok(Json(PublicGuildListResponse { guilds: results }))
+    if ascending {
+        results.sort_by(|left, right| left.0.cmp(&right.0));
+    } else {
+        results.sort_by(|left, right| right.0.cmp(&left.0));
+    }
+    results.truncate(limit + 1);
+    Ok(Json(page_public_guilds(results, limit, cursor.as_ref())))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PublicGuildPageDirection {
+    Before,
+    After,
+}
+
+/// A guild's place in the directory's creation order. Serialized as
+/// `{created_at_unix}_{guild_id}`, the same shape as audit cursors.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct PublicGuildCursorPosition {
+    created_at_unix: i64,
+    guild_id: String,
+}
+
+struct PublicGuildPageCursor {
+    direction: PublicGuildPageDirection,
+    position: PublicGuildCursorPosition,
+}
+
+fn parse_public_guild_cursor(raw: &str) -> Result<PublicGuildCursorPosition, AuthFailure> {
+    let (created_at_raw, guild_id) = raw.split_once('_').ok_or(AuthFailure::InvalidRequest)?;
+    let created_at_unix = created_at_raw
+        .parse::<i64>()
+        .map_err(|_| AuthFailure::InvalidRequest)?;
+    if Ulid::from_string(guild_id).is_err() {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(PublicGuildCursorPosition {
+        created_at_unix,
+        guild_id: guild_id.to_owned(),
+    })
+}
+
+fn parse_public_guild_page_cursor(
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Result<Option<PublicGuildPageCursor>, AuthFailure> {
+    let (direction, raw) = match (before, after) {
+        (Some(_), Some(_)) => return Err(AuthFailure::InvalidRequest),
+        (Some(raw), None) => (PublicGuildPageDirection::Before, raw),
+        (None, Some(raw)) => (PublicGuildPageDirection::After, raw),
+        (None, None) => return Ok(None),
+    };
+    Ok(Some(PublicGuildPageCursor {
+        direction,
+        position: parse_public_guild_cursor(raw)?,
+    }))
+}
+
+fn build_public_guild_cursor(position: &PublicGuildCursorPosition) -> String {
+    format!("{}_{}", position.created_at_unix, position.guild_id)
+}
+
+/// Turns up to `limit + 1` entries, read moving away from the cursor, into a
+/// newest-first page. The extra entry only signals that another page exists
+/// in that direction; the opposite direction has more whenever a cursor was
+/// given, since the cursor's own guild lies that way.
+fn page_public_guilds(
+    mut entries: Vec<(PublicGuildCursorPosition, PublicGuildListItem)>,
+    limit: usize,
+    cursor: Option<&PublicGuildPageCursor>,
+) -> PublicGuildListResponse {
+    let has_more = entries.len() > limit;
+    entries.truncate(limit);
+    let direction = cursor.map_or(PublicGuildPageDirection::Before, |cursor| cursor.direction);
+    if direction == PublicGuildPageDirection::After {
+        entries.reverse();
+    }
+    let (more_before, more_after) = match direction {
+        PublicGuildPageDirection::Before => (has_more, cursor.is_some()),
+        PublicGuildPageDirection::After => (true, has_more),
+    };
+    let next_before = entries
+        .last()
+        .filter(|_| more_before)
+        .map(|(position, _)| build_public_guild_cursor(position));
+    let next_after = entries
+        .first()
+        .filter(|_| more_after)
+        .map(|(position, _)| build_public_guild_cursor(position));
+    PublicGuildListResponse {
+        guilds: entries.into_iter().map(|(_, item)| item).collect(),
+        next_before,
+        next_after,
+    }
 }
 
 fn join_failure_from_outcome(outcome: DirectoryJoinOutcome) -> Option<AuthFailure> {
@@ -3220,6 +4047,7 @@ pub(crate) async fn join_public_guild(
                 );
             }
         }
+        post_welcome_message(&state, &path.guild_id, auth.user_id).await;
         broadcast_ms = broadcast_start.elapsed().as_millis();
     }
     if timing_enabled {
@@ -3240,6 +4068,7 @@ pub(crate) async fn join_public_guild(
 }
 
 pub(crate) const MAX_CHANNEL_LIST_LIMIT: usize = 500;
+pub(crate) const MAX_CHANNEL_BATCH: usize = 50;
 
 pub(crate) async fn list_guild_channels(
     State(state): State<AppState>,
@@ -3265,10 +4094,10 @@ pub(crate) async fn list_guild_channels(
 
     let channel_candidates = if let Some(pool) = &state.db_pool {
         let rows = sqlx::query(
-            "SELECT channel_id, name, kind
+            "SELECT channel_id, name, kind, locked, retention_secs, voting, category_id
              FROM channels
              WHERE guild_id = $1
-             ORDER BY created_at_unix ASC
+             ORDER BY created_at_unix ASC, channel_id ASC
              LIMIT $2",
         )
         .bind(&path.guild_id)
@@ -3286,6 +4115,12 @@ pub(crate) async fn list_guild_channels(
                     .map_err(|_| AuthFailure::Internal)?,
                 name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
                 kind,
+                locked: row.try_get("locked").map_err(|_| AuthFailure::Internal)?,
+                retention_secs: retention_secs_from_row(&row)?,
+                voting: row.try_get("voting").map_err(|_| AuthFailure::Internal)?,
+                category_id: row
+                    .try_get("category_id")
+                    .map_err(|_| AuthFailure::Internal)?,
             });
         }
         entries
@@ -3299,6 +4134,10 @@ pub(crate) async fn list_guild_channels(
                 channel_id: channel_id.clone(),
                 name: channel.name.clone(),
                 kind: channel.kind,
+                locked: channel.locked,
+                retention_secs: channel.retention_secs,
+                voting: channel.voting,
+                category_id: channel.category_id.clone(),
             })
             .collect::<Vec<_>>();
         entries.sort_by(|left, right| left.channel_id.cmp(&right.channel_id));
@@ -3351,17 +4190,21 @@ pub(crate) async fn create_channel(
     if !actor_permissions.contains(Permission::ManageChannelOverrides) {
         return Err(AuthFailure::Forbidden);
     }
+    let category_id =
+        resolve_channel_category(&state, &path.guild_id, kind, payload.category_id).await?;
 
     let channel_id = Ulid::new().to_string();
     if let Some(pool) = &state.db_pool {
         sqlx::query(
-            "INSERT INTO channels (channel_id, guild_id, name, kind, created_at_unix) VALUES ($1, $2, $3, $4, $5)",
+            "INSERT INTO channels (channel_id, guild_id, name, kind, created_at_unix, category_id)
+             VALUES ($1, $2, $3, $4, $5, $6)",
         )
         .bind(&channel_id)
         .bind(&path.guild_id)
         .bind(name.as_str())
         .bind(channel_kind_to_i16(kind))
         .bind(now_unix())
+        .bind(&category_id)
         .execute(pool)
         .await
         .map_err(|e| {
@@ -3382,6 +4225,10 @@ pub(crate) async fn create_channel(
             ChannelRecord {
                 name: name.as_str().to_owned(),
                 kind,
+                locked: false,
+                retention_secs: None,
+                voting: false,
+                category_id: category_id.clone(),
                 messages: Vec::new(),
                 role_overrides: HashMap::new(),
             },
@@ -3392,30 +4239,519 @@ pub(crate) async fn create_channel(
         channel_id,
         name: name.as_str().to_owned(),
         kind,
+        locked: false,
+        retention_secs: None,
+        voting: false,
+        category_id,
     };
-    match gateway_events::try_channel_create(&path.guild_id, &response) {
-        Ok(event) => {
-            broadcast_guild_event(&state, &path.guild_id, &event).await;
-        }
-        Err(error) => {
-            tracing::warn!(
-                event = "gateway.channel_create.serialize_failed",
-                event_type = gateway_events::CHANNEL_CREATE_EVENT,
+    broadcast_channel_create(&state, &path.guild_id, &response).await;
+
+    Ok(Json(response))
+}
+
+/// Creates up to `MAX_CHANNEL_BATCH` channels in one transaction, with the
+/// same validation and permission as single channel creation. Ids are
+/// monotonic, so the channels list in request order.
+pub(crate) async fn create_channel_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<GuildPath>,
+    Json(payload): Json<CreateChannelBatchRequest>,
+) -> Result<Json<ChannelListResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "guild.channels.create",
+    )
+    .await?;
+    if payload.channels.is_empty() || payload.channels.len() > MAX_CHANNEL_BATCH {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let mut generator = ulid::Generator::new();
+    let mut channels = Vec::with_capacity(payload.channels.len());
+    for entry in payload.channels {
+        let name = ChannelName::try_from(entry.name).map_err(|_| AuthFailure::InvalidRequest)?;
+        channels.push(ChannelResponse {
+            channel_id: generator
+                .generate()
+                .map_err(|_| AuthFailure::Internal)?
+                .to_string(),
+            name: name.as_str().to_owned(),
+            kind: entry.kind.unwrap_or(ChannelKind::Text),
+            locked: false,
+            retention_secs: None,
+            voting: false,
+            category_id: entry.category_id,
+        });
+    }
+    let (_, actor_permissions) =
+        guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !actor_permissions.contains(Permission::ManageChannelOverrides) {
+        return Err(AuthFailure::Forbidden);
+    }
+    for channel in &mut channels {
+        channel.category_id = resolve_channel_category(
+            &state,
+            &path.guild_id,
+            channel.kind,
+            channel.category_id.take(),
+        )
+        .await?;
+    }
+
+    if let Some(pool) = &state.db_pool {
+        let mut tx = pool.begin().await.map_err(|_| AuthFailure::Internal)?;
+        insert_channel_rows(&mut tx, &path.guild_id, &channels)
+            .await
+            .map_err(|e| {
+                if matches!(e, sqlx::Error::Database(_)) {
+                    AuthFailure::NotFound
+                } else {
+                    AuthFailure::Internal
+                }
+            })?;
+        tx.commit().await.map_err(|_| AuthFailure::Internal)?;
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let guild = guilds
+            .get_mut(&path.guild_id)
+            .ok_or(AuthFailure::NotFound)?;
+        guild
+            .channels
+            .extend(channel_records_from_responses(&channels));
+    }
+
+    for channel in &channels {
+        broadcast_channel_create(&state, &path.guild_id, channel).await;
+    }
+
+    Ok(Json(ChannelListResponse { channels }))
+}
+
+async fn broadcast_channel_create(state: &AppState, guild_id: &str, channel: &ChannelResponse) {
+    match gateway_events::try_channel_create(guild_id, channel) {
+        Ok(event) => {
+            broadcast_guild_event(state, guild_id, &event).await;
+        }
+        Err(error) => {
+            tracing::warn!(
+                event = "gateway.channel_create.serialize_failed",
+                event_type = gateway_events::CHANNEL_CREATE_EVENT,
+                guild_id = %guild_id,
+                channel_id = %channel.channel_id,
+                error = %error,
+            );
+            record_gateway_event_dropped(
+                "guild",
+                gateway_events::CHANNEL_CREATE_EVENT,
+                "serialize_error",
+            );
+        }
+    }
+}
+
+/// Checks that `category_id` names a category-kind channel in the guild.
+/// Categories themselves cannot be nested under another category.
+async fn resolve_channel_category(
+    state: &AppState,
+    guild_id: &str,
+    kind: ChannelKind,
+    category_id: Option<String>,
+) -> Result<Option<String>, AuthFailure> {
+    let Some(category_id) = category_id else {
+        return Ok(None);
+    };
+    if kind == ChannelKind::Category {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let category_kind = if let Some(pool) = &state.db_pool {
+        sqlx::query_scalar::<_, i16>(
+            "SELECT kind FROM channels WHERE guild_id = $1 AND channel_id = $2",
+        )
+        .bind(guild_id)
+        .bind(&category_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .and_then(channel_kind_from_i16)
+    } else {
+        let guilds = state.membership_store.guilds().read().await;
+        guilds
+            .get(guild_id)
+            .and_then(|guild| guild.channels.get(&category_id))
+            .map(|channel| channel.kind)
+    };
+    if category_kind != Some(ChannelKind::Category) {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(Some(category_id))
+}
+
+/// Locks or unlocks a channel. Requires `manage_channel_overrides`.
+pub(crate) async fn update_channel(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<ChannelPath>,
+    Json(payload): Json<UpdateChannelRequest>,
+) -> Result<Json<ChannelResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (_, actor_permissions) =
+        guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !actor_permissions.contains(Permission::ManageChannelOverrides) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let response = if let Some(pool) = &state.db_pool {
+        let row = sqlx::query(
+            "UPDATE channels SET locked = $3
+             WHERE guild_id = $1 AND channel_id = $2
+             RETURNING name, kind, retention_secs, voting, category_id",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(payload.locked)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        let kind_raw: i16 = row.try_get("kind").map_err(|_| AuthFailure::Internal)?;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
+            kind: channel_kind_from_i16(kind_raw).ok_or(AuthFailure::Internal)?,
+            locked: payload.locked,
+            retention_secs: retention_secs_from_row(&row)?,
+            voting: row.try_get("voting").map_err(|_| AuthFailure::Internal)?,
+            category_id: row
+                .try_get("category_id")
+                .map_err(|_| AuthFailure::Internal)?,
+        }
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let channel = guilds
+            .get_mut(&path.guild_id)
+            .and_then(|guild| guild.channels.get_mut(&path.channel_id))
+            .ok_or(AuthFailure::NotFound)?;
+        channel.locked = payload.locked;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: channel.name.clone(),
+            kind: channel.kind,
+            locked: channel.locked,
+            retention_secs: channel.retention_secs,
+            voting: channel.voting,
+            category_id: channel.category_id.clone(),
+        }
+    };
+
+    let updated_at_unix = now_unix();
+    match gateway_events::try_channel_update(
+        &path.guild_id,
+        &response.channel_id,
+        response.locked,
+        updated_at_unix,
+        auth.user_id,
+    ) {
+        Ok(event) => broadcast_guild_event(&state, &path.guild_id, &event).await,
+        Err(error) => {
+            tracing::warn!(
+                event = "gateway.channel_update.serialize_failed",
+                event_type = gateway_events::CHANNEL_UPDATE_EVENT,
                 guild_id = %path.guild_id,
                 channel_id = %response.channel_id,
                 error = %error,
             );
             record_gateway_event_dropped(
                 "guild",
-                gateway_events::CHANNEL_CREATE_EVENT,
+                gateway_events::CHANNEL_UPDATE_EVENT,
                 "serialize_error",
             );
         }
     }
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        None,
+        "channel.update",
+        serde_json::json!({
+            "channel_id": response.channel_id,
+            "locked": response.locked,
+        }),
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// Sets or clears a channel's message retention window. Requires
+/// `manage_channel_overrides`; windows must fall within
+/// `MIN_CHANNEL_RETENTION_SECS..=MAX_CHANNEL_RETENTION_SECS`.
+pub(crate) async fn update_channel_retention(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<ChannelPath>,
+    Json(payload): Json<UpdateChannelRetentionRequest>,
+) -> Result<Json<ChannelResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (_, actor_permissions) =
+        guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !actor_permissions.contains(Permission::ManageChannelOverrides) {
+        return Err(AuthFailure::Forbidden);
+    }
+    if payload.retention_secs.is_some_and(|secs| {
+        !(MIN_CHANNEL_RETENTION_SECS..=MAX_CHANNEL_RETENTION_SECS).contains(&secs)
+    }) {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let response = if let Some(pool) = &state.db_pool {
+        let stored = payload
+            .retention_secs
+            .map(i64::try_from)
+            .transpose()
+            .map_err(|_| AuthFailure::InvalidRequest)?;
+        let row = sqlx::query(
+            "UPDATE channels SET retention_secs = $3
+             WHERE guild_id = $1 AND channel_id = $2
+             RETURNING name, kind, locked, voting, category_id",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(stored)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        let kind_raw: i16 = row.try_get("kind").map_err(|_| AuthFailure::Internal)?;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
+            kind: channel_kind_from_i16(kind_raw).ok_or(AuthFailure::Internal)?,
+            locked: row.try_get("locked").map_err(|_| AuthFailure::Internal)?,
+            retention_secs: payload.retention_secs,
+            voting: row.try_get("voting").map_err(|_| AuthFailure::Internal)?,
+            category_id: row
+                .try_get("category_id")
+                .map_err(|_| AuthFailure::Internal)?,
+        }
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let channel = guilds
+            .get_mut(&path.guild_id)
+            .and_then(|guild| guild.channels.get_mut(&path.channel_id))
+            .ok_or(AuthFailure::NotFound)?;
+        channel.retention_secs = payload.retention_secs;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: channel.name.clone(),
+            kind: channel.kind,
+            locked: channel.locked,
+            retention_secs: channel.retention_secs,
+            voting: channel.voting,
+            category_id: channel.category_id.clone(),
+        }
+    };
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        None,
+        "channel.retention.update",
+        serde_json::json!({
+            "channel_id": response.channel_id,
+            "retention_secs": response.retention_secs,
+        }),
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// Turns a channel's voting mode on or off. Requires `manage_channel_overrides`.
+pub(crate) async fn update_channel_voting(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<ChannelPath>,
+    Json(payload): Json<UpdateChannelVotingRequest>,
+) -> Result<Json<ChannelResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (_, actor_permissions) =
+        guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !actor_permissions.contains(Permission::ManageChannelOverrides) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let response = if let Some(pool) = &state.db_pool {
+        let row = sqlx::query(
+            "UPDATE channels SET voting = $3
+             WHERE guild_id = $1 AND channel_id = $2
+             RETURNING name, kind, locked, retention_secs, category_id",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(payload.voting)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        let kind_raw: i16 = row.try_get("kind").map_err(|_| AuthFailure::Internal)?;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
+            kind: channel_kind_from_i16(kind_raw).ok_or(AuthFailure::Internal)?,
+            locked: row.try_get("locked").map_err(|_| AuthFailure::Internal)?,
+            retention_secs: retention_secs_from_row(&row)?,
+            voting: payload.voting,
+            category_id: row
+                .try_get("category_id")
+                .map_err(|_| AuthFailure::Internal)?,
+        }
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let channel = guilds
+            .get_mut(&path.guild_id)
+            .and_then(|guild| guild.channels.get_mut(&path.channel_id))
+            .ok_or(AuthFailure::NotFound)?;
+        channel.voting = payload.voting;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: channel.name.clone(),
+            kind: channel.kind,
+            locked: channel.locked,
+            retention_secs: channel.retention_secs,
+            voting: channel.voting,
+            category_id: channel.category_id.clone(),
+        }
+    };
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        None,
+        "channel.voting.update",
+        serde_json::json!({
+            "channel_id": response.channel_id,
+            "voting": response.voting,
+        }),
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// Moves a channel into a category, or out of one with `null`. Requires
+/// `manage_channel_overrides`.
+pub(crate) async fn update_channel_category(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<ChannelPath>,
+    Json(payload): Json<UpdateChannelCategoryRequest>,
+) -> Result<Json<ChannelResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (_, actor_permissions) =
+        guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !actor_permissions.contains(Permission::ManageChannelOverrides) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let response = if let Some(pool) = &state.db_pool {
+        let kind_raw = sqlx::query_scalar::<_, i16>(
+            "SELECT kind FROM channels WHERE guild_id = $1 AND channel_id = $2",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        let kind = channel_kind_from_i16(kind_raw).ok_or(AuthFailure::Internal)?;
+        let category_id =
+            resolve_channel_category(&state, &path.guild_id, kind, payload.category_id).await?;
+        let row = sqlx::query(
+            "UPDATE channels SET category_id = $3
+             WHERE guild_id = $1 AND channel_id = $2
+             RETURNING name, locked, retention_secs, voting",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(&category_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
+            kind,
+            locked: row.try_get("locked").map_err(|_| AuthFailure::Internal)?,
+            retention_secs: retention_secs_from_row(&row)?,
+            voting: row.try_get("voting").map_err(|_| AuthFailure::Internal)?,
+            category_id,
+        }
+    } else {
+        let kind = {
+            let guilds = state.membership_store.guilds().read().await;
+            guilds
+                .get(&path.guild_id)
+                .and_then(|guild| guild.channels.get(&path.channel_id))
+                .map(|channel| channel.kind)
+                .ok_or(AuthFailure::NotFound)?
+        };
+        let category_id =
+            resolve_channel_category(&state, &path.guild_id, kind, payload.category_id).await?;
+        let mut guilds = state.membership_store.guilds().write().await;
+        let channel = guilds
+            .get_mut(&path.guild_id)
+            .and_then(|guild| guild.channels.get_mut(&path.channel_id))
+            .ok_or(AuthFailure::NotFound)?;
+        channel.category_id = category_id;
+        ChannelResponse {
+            channel_id: path.channel_id,
+            name: channel.name.clone(),
+            kind: channel.kind,
+            locked: channel.locked,
+            retention_secs: channel.retention_secs,
+            voting: channel.voting,
+            category_id: channel.category_id.clone(),
+        }
+    };
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        None,
+        "channel.category.update",
+        serde_json::json!({
+            "channel_id": response.channel_id,
+            "category_id": response.category_id,
+        }),
+    )
+    .await?;
 
     Ok(Json(response))
 }
 
+fn retention_secs_from_row(row: &sqlx::postgres::PgRow) -> Result<Option<u64>, AuthFailure> {
+    row.try_get::<Option<i64>, _>("retention_secs")
+        .map_err(|_| AuthFailure::Internal)?
+        .map(u64::try_from)
+        .transpose()
+        .map_err(|_| AuthFailure::Internal)
+}
+
 pub(crate) async fn add_member(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -3509,6 +4845,7 @@ pub(crate) async fn add_member(
                 );
             }
         }
+        post_welcome_message(&state, &path.guild_id, target_user_id).await;
     }
 
     Ok(Json(ModerationResponse { accepted: true }))
@@ -3725,28 +5062,362 @@ pub(crate) async fn update_member_role(
 
     crate::server::realtime::livekit_sync::schedule_livekit_permission_reevaluation_for_guild(
         &state,
-        &path.guild_id,
+        &path.guild_id,
+    );
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        Some(target_user_id),
+        "member.role.update",
+        serde_json::json!({"role": payload.role}),
+    )
+    .await?;
+    Ok(Json(ModerationResponse { accepted: true }))
+}
+
+pub(crate) async fn set_channel_role_override(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<ChannelRolePath>,
+    Json(payload): Json<UpdateChannelRoleOverrideRequest>,
+) -> Result<Json<ModerationResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "guild.channel_overrides.update",
+    )
+    .await?;
+    let actor_role = user_role_in_guild(&state, auth.user_id, &path.guild_id).await?;
+    if !can_override_role_legacy(actor_role, path.role) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let allow = permission_set_from_list(&payload.allow);
+    let deny = permission_set_from_list(&payload.deny);
+    if allow.bits() & deny.bits() != 0 {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    if let Some(pool) = &state.db_pool {
+        let result = sqlx::query(
+            "INSERT INTO channel_role_overrides (guild_id, channel_id, role, allow_mask, deny_mask)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (guild_id, channel_id, role)
+             DO UPDATE SET allow_mask = EXCLUDED.allow_mask, deny_mask = EXCLUDED.deny_mask",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(role_to_i16(path.role))
+        .bind(permission_set_to_i64(allow)?)
+        .bind(permission_set_to_i64(deny)?)
+        .execute(pool)
+        .await
+        .map_err(|e| {
+            if matches!(e, sqlx::Error::Database(_)) {
+                AuthFailure::NotFound
+            } else {
+                AuthFailure::Internal
+            }
+        })?;
+        if result.rows_affected() == 0 {
+            return Err(AuthFailure::NotFound);
+        }
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let guild = guilds
+            .get_mut(&path.guild_id)
+            .ok_or(AuthFailure::NotFound)?;
+        let channel = guild
+            .channels
+            .get_mut(&path.channel_id)
+            .ok_or(AuthFailure::NotFound)?;
+        channel
+            .role_overrides
+            .insert(path.role, ChannelPermissionOverwrite { allow, deny });
+    }
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id.clone()),
+        auth.user_id,
+        None,
+        "channel.override.update",
+        serde_json::json!({
+            "channel_id": path.channel_id,
+            "role": path.role,
+            "allow_bits": allow.bits(),
+            "deny_bits": deny.bits(),
+        }),
+    )
+    .await?;
+
+    emit_channel_role_override_events(&state, &path, &payload, auth.user_id).await;
+
+    crate::server::realtime::livekit_sync::schedule_livekit_permission_reevaluation_for_guild(
+        &state,
+        &path.guild_id,
+    );
+
+    Ok(Json(ModerationResponse { accepted: true }))
+}
+
+/// Removes a role override so the role falls back to its base permissions,
+/// which is not the same as keeping an override with empty masks around.
+pub(crate) async fn delete_channel_role_override(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<ChannelRolePath>,
+) -> Result<Json<ModerationResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "guild.channel_overrides.delete",
+    )
+    .await?;
+    let actor_role = user_role_in_guild(&state, auth.user_id, &path.guild_id).await?;
+    if !can_override_role_legacy(actor_role, path.role) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    if let Some(pool) = &state.db_pool {
+        let result = sqlx::query(
+            "DELETE FROM channel_role_overrides
+             WHERE guild_id = $1 AND channel_id = $2 AND role = $3",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(role_to_i16(path.role))
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        if result.rows_affected() == 0 {
+            return Err(AuthFailure::NotFound);
+        }
+    } else {
+        let removed = {
+            let mut guilds = state.membership_store.guilds().write().await;
+            let channel = guilds
+                .get_mut(&path.guild_id)
+                .and_then(|guild| guild.channels.get_mut(&path.channel_id))
+                .ok_or(AuthFailure::NotFound)?;
+            channel
+                .role_overrides
+                .remove(&path.role)
+                .ok_or(AuthFailure::NotFound)?
+        };
+        remove_mirrored_channel_role_overrides(
+            &state,
+            &path.guild_id,
+            &[(path.channel_id.clone(), path.role, removed)],
+        )
+        .await;
+    }
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id.clone()),
+        auth.user_id,
+        None,
+        "channel.override.delete",
+        serde_json::json!({
+            "channel_id": path.channel_id,
+            "role": path.role,
+        }),
+    )
+    .await?;
+
+    let cleared = UpdateChannelRoleOverrideRequest {
+        allow: Vec::new(),
+        deny: Vec::new(),
+    };
+    emit_channel_role_override_events(&state, &path, &cleared, auth.user_id).await;
+
+    crate::server::realtime::livekit_sync::schedule_livekit_permission_reevaluation_for_guild(
+        &state,
+        &path.guild_id,
+    );
+
+    Ok(Json(ModerationResponse { accepted: true }))
+}
+
+/// Clears every legacy role override in the guild in one pass so all channels
+/// fall back to their roles' base permissions.
+pub(crate) async fn reset_guild_channel_role_overrides(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<GuildPath>,
+) -> Result<Json<ChannelRoleOverrideResetResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
     );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "guild.channel_overrides.reset",
+    )
+    .await?;
+    let actor_role = user_role_in_guild(&state, auth.user_id, &path.guild_id).await?;
+    if actor_role != Role::Owner {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let cleared: Vec<(String, Role)> = if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "DELETE FROM channel_role_overrides
+             WHERE guild_id = $1
+             RETURNING channel_id, role",
+        )
+        .bind(&path.guild_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let mut cleared = Vec::with_capacity(rows.len());
+        for row in rows {
+            let channel_id: String = row
+                .try_get("channel_id")
+                .map_err(|_| AuthFailure::Internal)?;
+            let role: i16 = row.try_get("role").map_err(|_| AuthFailure::Internal)?;
+            cleared.push((
+                channel_id,
+                role_from_i16(role).ok_or(AuthFailure::Internal)?,
+            ));
+        }
+        cleared
+    } else {
+        let removed: Vec<(String, Role, ChannelPermissionOverwrite)> = {
+            let mut guilds = state.membership_store.guilds().write().await;
+            let guild = guilds
+                .get_mut(&path.guild_id)
+                .ok_or(AuthFailure::NotFound)?;
+            guild
+                .channels
+                .iter_mut()
+                .flat_map(|(channel_id, channel)| {
+                    channel
+                        .role_overrides
+                        .drain()
+                        .map(|(role, overwrite)| (channel_id.clone(), role, overwrite))
+                })
+                .collect()
+        };
+        remove_mirrored_channel_role_overrides(&state, &path.guild_id, &removed).await;
+        removed
+            .into_iter()
+            .map(|(channel_id, role, _)| (channel_id, role))
+            .collect()
+    };
 
     write_audit_log(
         &state,
-        Some(path.guild_id),
+        Some(path.guild_id.clone()),
         auth.user_id,
-        Some(target_user_id),
-        "member.role.update",
-        serde_json::json!({"role": payload.role}),
+        None,
+        "channel.override.reset",
+        serde_json::json!({ "cleared": cleared.len() }),
     )
     .await?;
-    Ok(Json(ModerationResponse { accepted: true }))
+
+    let reset = UpdateChannelRoleOverrideRequest {
+        allow: Vec::new(),
+        deny: Vec::new(),
+    };
+    for (channel_id, role) in &cleared {
+        let channel_path = ChannelRolePath {
+            guild_id: path.guild_id.clone(),
+            channel_id: channel_id.clone(),
+            role: *role,
+        };
+        emit_channel_role_override_events(&state, &channel_path, &reset, auth.user_id).await;
+    }
+    if !cleared.is_empty() {
+        crate::server::realtime::livekit_sync::schedule_livekit_permission_reevaluation_for_guild(
+            &state,
+            &path.guild_id,
+        );
+    }
+
+    Ok(Json(ChannelRoleOverrideResetResponse {
+        cleared: cleared.len(),
+    }))
 }
 
-pub(crate) async fn set_channel_role_override(
+/// Legacy overrides are mirrored onto the matching workspace role the first
+/// time a guild's permissions are resolved; drops those copies alongside the
+/// removed legacy entries.
+async fn remove_mirrored_channel_role_overrides(
+    state: &AppState,
+    guild_id: &str,
+    removed: &[(String, Role, ChannelPermissionOverwrite)],
+) {
+    if removed.is_empty() {
+        return;
+    }
+    let Some(role_ids) = state
+        .membership_store
+        .guild_roles()
+        .read()
+        .await
+        .get(guild_id)
+        .and_then(role_ids_from_map)
+    else {
+        return;
+    };
+    let mut overrides_map = state
+        .membership_store
+        .guild_channel_permission_overrides()
+        .write()
+        .await;
+    let Some(channels) = overrides_map.get_mut(guild_id) else {
+        return;
+    };
+    for (channel_id, role, overwrite) in removed {
+        let role_id = match role {
+            Role::Owner => &role_ids.workspace_owner,
+            Role::Moderator => &role_ids.moderator,
+            Role::Member => &role_ids.member,
+        };
+        if let Some(channel_override) = channels.get_mut(channel_id) {
+            if channel_override.role_overrides.get(role_id) == Some(overwrite) {
+                channel_override.role_overrides.remove(role_id);
+            }
+        }
+    }
+}
+
+/// Lists the role overrides stored for a channel by `set_channel_role_override`,
+/// highest role first, so admin UIs can show the current configuration.
+pub(crate) async fn list_channel_role_overrides(
     State(state): State<AppState>,
     headers: HeaderMap,
     connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
-    Path(path): Path<ChannelRolePath>,
-    Json(payload): Json<UpdateChannelRoleOverrideRequest>,
-) -> Result<Json<ModerationResponse>, AuthFailure> {
+    Path(path): Path<ChannelPath>,
+) -> Result<Json<ChannelRoleOverrideListResponse>, AuthFailure> {
     let client_ip = extract_client_ip(
         &state,
         &headers,
@@ -3758,81 +5429,282 @@ pub(crate) async fn set_channel_role_override(
         &path.guild_id,
         auth.user_id,
         client_ip,
-        "guild.channel_overrides.update",
+        "guild.channel_overrides.read",
     )
     .await?;
-    let actor_role = user_role_in_guild(&state, auth.user_id, &path.guild_id).await?;
+    let (actor_role, _) =
+        channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
     if !has_permission_legacy(actor_role, Permission::ManageChannelOverrides) {
         return Err(AuthFailure::Forbidden);
     }
 
-    let allow = permission_set_from_list(&payload.allow);
-    let deny = permission_set_from_list(&payload.deny);
-    if allow.bits() & deny.bits() != 0 {
-        return Err(AuthFailure::InvalidRequest);
-    }
-
-    if let Some(pool) = &state.db_pool {
-        let result = sqlx::query(
-            "INSERT INTO channel_role_overrides (guild_id, channel_id, role, allow_mask, deny_mask)
-             VALUES ($1, $2, $3, $4, $5)
-             ON CONFLICT (guild_id, channel_id, role)
-             DO UPDATE SET allow_mask = EXCLUDED.allow_mask, deny_mask = EXCLUDED.deny_mask",
+    let mut overrides: Vec<(Role, ChannelPermissionOverwrite)> = if let Some(pool) = &state.db_pool
+    {
+        let rows = sqlx::query(
+            "SELECT role, allow_mask, deny_mask
+                 FROM channel_role_overrides
+                 WHERE guild_id = $1 AND channel_id = $2",
         )
         .bind(&path.guild_id)
         .bind(&path.channel_id)
-        .bind(role_to_i16(path.role))
-        .bind(permission_set_to_i64(allow)?)
-        .bind(permission_set_to_i64(deny)?)
-        .execute(pool)
+        .fetch_all(pool)
         .await
-        .map_err(|e| {
-            if matches!(e, sqlx::Error::Database(_)) {
-                AuthFailure::NotFound
-            } else {
-                AuthFailure::Internal
-            }
-        })?;
-        if result.rows_affected() == 0 {
-            return Err(AuthFailure::NotFound);
+        .map_err(|_| AuthFailure::Internal)?;
+        let mut overrides = Vec::with_capacity(rows.len());
+        for row in rows {
+            let role_value: i16 = row.try_get("role").map_err(|_| AuthFailure::Internal)?;
+            let Some(role) = role_from_i16(role_value) else {
+                continue;
+            };
+            let allow_mask: i64 = row
+                .try_get("allow_mask")
+                .map_err(|_| AuthFailure::Internal)?;
+            let deny_mask: i64 = row
+                .try_get("deny_mask")
+                .map_err(|_| AuthFailure::Internal)?;
+            let (allow, _) = i64_to_masked_permissions(allow_mask)?;
+            let (deny, _) = i64_to_masked_permissions(deny_mask)?;
+            overrides.push((role, ChannelPermissionOverwrite { allow, deny }));
         }
+        overrides
     } else {
-        let mut guilds = state.membership_store.guilds().write().await;
-        let guild = guilds
-            .get_mut(&path.guild_id)
-            .ok_or(AuthFailure::NotFound)?;
-        let channel = guild
-            .channels
-            .get_mut(&path.channel_id)
+        let guilds = state.membership_store.guilds().read().await;
+        let channel = guilds
+            .get(&path.guild_id)
+            .and_then(|guild| guild.channels.get(&path.channel_id))
             .ok_or(AuthFailure::NotFound)?;
         channel
             .role_overrides
-            .insert(path.role, ChannelPermissionOverwrite { allow, deny });
-    }
+            .iter()
+            .map(|(role, overwrite)| (*role, *overwrite))
+            .collect()
+    };
+    overrides.sort_by_key(|(role, _)| std::cmp::Reverse(role_to_i16(*role)));
 
-    write_audit_log(
+    Ok(Json(ChannelRoleOverrideListResponse {
+        overrides: overrides
+            .into_iter()
+            .map(|(role, overwrite)| ChannelRoleOverrideResponse {
+                role,
+                allow: permission_list_from_set(overwrite.allow),
+                deny: permission_list_from_set(overwrite.deny),
+            })
+            .collect(),
+    }))
+}
+
+/// Resolves what a role override would grant without persisting it, so admins
+/// can check a change before applying it with `set_channel_role_override`.
+pub(crate) async fn preview_channel_role_override(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<ChannelPath>,
+    Query(query): Query<ChannelOverridePreviewQuery>,
+) -> Result<Json<ChannelPermissionsResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
         &state,
-        Some(path.guild_id.clone()),
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
         auth.user_id,
-        None,
-        "channel.override.update",
-        serde_json::json!({
-            "channel_id": path.channel_id,
-            "role": path.role,
-            "allow_bits": allow.bits(),
-            "deny_bits": deny.bits(),
-        }),
+        client_ip,
+        "guild.channel_overrides.preview",
     )
     .await?;
+    let (actor_role, _) =
+        channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
+    if !has_permission_legacy(actor_role, Permission::ManageChannelOverrides) {
+        return Err(AuthFailure::Forbidden);
+    }
 
-    emit_channel_role_override_events(&state, &path, &payload, auth.user_id).await;
+    let allow = permission_set_from_list(&parse_permission_list(&query.allow)?);
+    let deny = permission_set_from_list(&parse_permission_list(&query.deny)?);
+    if allow.bits() & deny.bits() != 0 {
+        return Err(AuthFailure::InvalidRequest);
+    }
 
-    crate::server::realtime::livekit_sync::schedule_livekit_permission_reevaluation_for_guild(
+    let resolved = apply_channel_overwrite_legacy(
+        base_permissions_legacy(query.role),
+        Some(ChannelPermissionOverwrite { allow, deny }),
+    );
+    Ok(Json(ChannelPermissionsResponse {
+        role: query.role,
+        permissions: permission_list_from_set(resolved),
+    }))
+}
+
+/// Reports who can use a channel once its override stack is applied: one
+/// entry per guild role (as held alone, on top of `@everyone`) and one per
+/// member with a member override. `can_access` means the resolved set holds
+/// `create_message`, which both reading history and posting require.
+#[allow(clippy::too_many_lines)]
+pub(crate) async fn get_channel_access(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<ChannelPath>,
+) -> Result<Json<ChannelAccessResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
         &state,
-        &path.guild_id,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
     );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "guild.channel_access.read",
+    )
+    .await?;
+    let (actor_role, _) =
+        channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
+    if !has_permission_legacy(actor_role, Permission::ManageChannelOverrides) {
+        return Err(AuthFailure::Forbidden);
+    }
 
-    Ok(Json(ModerationResponse { accepted: true }))
+    let role_models = if let Some(pool) = &state.db_pool {
+        guild_roles_db(pool, &path.guild_id).await?
+    } else {
+        guild_roles_in_memory(&state, &path.guild_id).await?
+    };
+    let roles: HashMap<String, WorkspaceRoleRecord> = role_models
+        .iter()
+        .map(|model| {
+            (
+                model.role_id.clone(),
+                WorkspaceRoleRecord {
+                    role_id: model.role_id.clone(),
+                    name: model.name.clone(),
+                    position: model.position,
+                    is_system: model.is_system,
+                    system_key: model.system_key.clone(),
+                    permissions_allow: model.permissions,
+                    color_hex: model.color_hex.clone(),
+                    created_at_unix: model.created_at_unix,
+                },
+            )
+        })
+        .collect();
+    let role_ids = role_ids_from_map(&roles).ok_or(AuthFailure::Internal)?;
+    let overrides = channel_permission_overrides(&state, &path.guild_id, &path.channel_id).await?;
+
+    let role_entries = role_models
+        .into_iter()
+        .map(|model| {
+            let permissions = resolve_role_channel_permissions(
+                &roles,
+                &role_ids,
+                &model.role_id,
+                &overrides.role_overrides,
+            );
+            ChannelRoleAccessResponse {
+                role_id: model.role_id,
+                name: model.name,
+                position: model.position,
+                can_access: permissions.contains(Permission::CreateMessage),
+                permissions: permission_list_from_set(permissions),
+            }
+        })
+        .collect();
+
+    let mut member_ids: Vec<UserId> = overrides.member_overrides.into_keys().collect();
+    member_ids.sort_by_key(ToString::to_string);
+    member_ids.truncate(MAX_CHANNEL_ACCESS_MEMBER_ENTRIES);
+    let mut member_entries = Vec::with_capacity(member_ids.len());
+    for user_id in member_ids {
+        let permissions =
+            match channel_permission_snapshot(&state, user_id, &path.guild_id, &path.channel_id)
+                .await
+            {
+                Ok((_, permissions)) => permissions,
+                Err(AuthFailure::NotGuildMember) => continue,
+                Err(error) => return Err(error),
+            };
+        member_entries.push(ChannelMemberAccessResponse {
+            user_id: user_id.to_string(),
+            can_access: permissions.contains(Permission::CreateMessage),
+            permissions: permission_list_from_set(permissions),
+        });
+    }
+
+    Ok(Json(ChannelAccessResponse {
+        roles: role_entries,
+        members: member_entries,
+    }))
+}
+
+async fn channel_permission_overrides(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+) -> Result<ChannelPermissionOverrideRecord, AuthFailure> {
+    let Some(pool) = &state.db_pool else {
+        return Ok(state
+            .membership_store
+            .guild_channel_permission_overrides()
+            .read()
+            .await
+            .get(guild_id)
+            .and_then(|channels| channels.get(channel_id))
+            .cloned()
+            .unwrap_or_default());
+    };
+
+    let rows = sqlx::query(
+        "SELECT target_kind, target_id, allow_mask, deny_mask
+         FROM channel_permission_overrides
+         WHERE guild_id = $1 AND channel_id = $2",
+    )
+    .bind(guild_id)
+    .bind(channel_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+    let mut record = ChannelPermissionOverrideRecord::default();
+    for row in rows {
+        let target_kind: i16 = row
+            .try_get("target_kind")
+            .map_err(|_| AuthFailure::Internal)?;
+        let target_id: String = row
+            .try_get("target_id")
+            .map_err(|_| AuthFailure::Internal)?;
+        let (allow, _) = i64_to_masked_permissions(
+            row.try_get("allow_mask")
+                .map_err(|_| AuthFailure::Internal)?,
+        )?;
+        let (deny, _) = i64_to_masked_permissions(
+            row.try_get("deny_mask")
+                .map_err(|_| AuthFailure::Internal)?,
+        )?;
+        let overwrite = ChannelPermissionOverwrite { allow, deny };
+        if target_kind == PermissionOverrideTargetKind::Role.to_i16() {
+            record.role_overrides.insert(target_id, overwrite);
+        } else if target_kind == PermissionOverrideTargetKind::Member.to_i16() {
+            if let Ok(user_id) = UserId::try_from(target_id) {
+                record.member_overrides.insert(user_id, overwrite);
+            }
+        }
+    }
+    Ok(record)
+}
+
+fn parse_permission_list(raw: &str) -> Result<Vec<Permission>, AuthFailure> {
+    if raw.is_empty() {
+        return Ok(Vec::new());
+    }
+    raw.split(',')
+        .map(|name| {
+            Permission::deserialize(name.trim().into_deserializer())
+                .map_err(|_: serde::de::value::Error| AuthFailure::InvalidRequest)
+        })
+        .collect()
 }
 
 async fn emit_channel_role_override_events(
@@ -3883,6 +5755,44 @@ fn parse_channel_permission_override_masks(
     Ok((allow, deny))
 }
 
+/// Overrides may only target roles and members below the actor in the role
+/// hierarchy; otherwise a moderator could strip permissions from the owner.
+async fn ensure_can_override_target(
+    state: &AppState,
+    path: &ChannelPermissionOverridePath,
+    actor_user_id: UserId,
+) -> Result<(), AuthFailure> {
+    let actor = resolve_role_context(state, &path.guild_id, actor_user_id).await?;
+    if actor.is_workspace_owner {
+        return Ok(());
+    }
+
+    if path.target_kind == crate::server::types::PermissionOverrideTargetKind::Role {
+        let roles = if let Some(pool) = &state.db_pool {
+            guild_roles_db(pool, &path.guild_id).await?
+        } else {
+            guild_roles_in_memory(state, &path.guild_id).await?
+        };
+        let role = roles
+            .iter()
+            .find(|role| role.role_id == path.target_id)
+            .ok_or(AuthFailure::NotFound)?;
+        if role.system_key.as_deref() == Some(SYSTEM_ROLE_EVERYONE) || can_manage_role(role, actor)
+        {
+            return Ok(());
+        }
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let target_user_id =
+        UserId::try_from(path.target_id.clone()).map_err(|_| AuthFailure::InvalidRequest)?;
+    let target = resolve_role_context(state, &path.guild_id, target_user_id).await?;
+    if target.is_workspace_owner || target.highest_position >= actor.highest_position {
+        return Err(AuthFailure::Forbidden);
+    }
+    Ok(())
+}
+
 async fn apply_channel_permission_override(
     state: &AppState,
     path: &ChannelPermissionOverridePath,
@@ -3975,6 +5885,7 @@ pub(crate) async fn set_channel_permission_override(
         Permission::ManageChannelOverrides,
     )
     .await?;
+    ensure_can_override_target(&state, &path, auth.user_id).await?;
 
     let (allow, deny) = parse_channel_permission_override_masks(&payload)?;
     apply_channel_permission_override(&state, &path, allow, deny).await?;
@@ -4524,6 +6435,11 @@ mod tests {
                 visibility: GuildVisibility::Private,
                 created_by_user_id: user_id,
                 default_join_role_id: Some(role_id.clone()),
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
                 members: HashMap::from([(user_id, Role::Member)]),
                 banned_members: HashSet::new(),
                 channels: HashMap::new(),
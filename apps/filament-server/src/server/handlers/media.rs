@@ -24,10 +24,11 @@ use crate::server::{
         enforce_media_token_rate_limit, extract_client_ip, now_unix,
         release_media_subscribe_lease_for_channel,
     },
-    core::{AppState, AttachmentRecord, MAX_MIME_SNIFF_BYTES},
+    core::{AppState, AttachmentRecord},
     domain::{
-        attachment_usage_for_user, channel_permission_snapshot, enforce_guild_ip_ban_for_request,
-        find_attachment, user_can_write_channel, user_role_in_guild, validate_attachment_filename,
+        attachment_object_key, attachment_usage_for_user, channel_permission_snapshot,
+        check_channel_permission, enforce_guild_ip_ban_for_request, ensure_attachment_extension,
+        find_attachment, resolve_attachment_type, user_role_in_guild, validate_attachment_filename,
         write_audit_log,
     },
     errors::AuthFailure,
@@ -64,9 +65,14 @@ pub(crate) async fn upload_attachment(
         "attachments.upload",
     )
     .await?;
-    if !user_can_write_channel(&state, auth.user_id, &path.guild_id, &path.channel_id).await {
-        return Err(AuthFailure::Forbidden);
-    }
+    check_channel_permission(
+        &state,
+        auth.user_id,
+        &path.guild_id,
+        &path.channel_id,
+        Permission::CreateMessage,
+    )
+    .await?;
 
     let declared_content_type = if let Some(content_type) = headers
         .get(CONTENT_TYPE)
@@ -81,8 +87,7 @@ pub(crate) async fn upload_attachment(
         None
     };
 
-    let filename =
-        validate_attachment_filename(query.filename.unwrap_or_else(|| String::from("upload.bin")))?;
+    let filename = validate_attachment_filename(query.filename.as_deref().unwrap_or("upload.bin"))?;
     let usage = attachment_usage_for_user(&state, auth.user_id).await?;
     let remaining_quota = state
         .runtime
@@ -93,7 +98,11 @@ pub(crate) async fn upload_attachment(
     }
 
     let attachment_id = Ulid::new().to_string();
-    let object_key = format!("attachments/{attachment_id}");
+    let object_key = attachment_object_key(
+        &state.runtime.attachment_key_prefix,
+        state.runtime.attachment_key_shard_chars,
+        &attachment_id,
+    );
     let object_path = ObjectPath::from(object_key.clone());
     let mut upload = state
         .attachment_store
@@ -124,8 +133,8 @@ pub(crate) async fn upload_attachment(
             return Err(AuthFailure::QuotaExceeded);
         }
 
-        if sniff_buffer.len() < MAX_MIME_SNIFF_BYTES {
-            let remaining = MAX_MIME_SNIFF_BYTES - sniff_buffer.len();
+        if sniff_buffer.len() < state.runtime.mime_sniff_bytes {
+            let remaining = state.runtime.mime_sniff_bytes - sniff_buffer.len();
             let copy_len = remaining.min(chunk.len());
             sniff_buffer.extend_from_slice(&chunk[..copy_len]);
         }
@@ -140,17 +149,20 @@ pub(crate) async fn upload_attachment(
         let _ = upload.abort().await;
         return Err(AuthFailure::InvalidRequest);
     }
-    let Some(sniffed) = infer::get(&sniff_buffer) else {
-        let _ = upload.abort().await;
-        return Err(AuthFailure::InvalidRequest);
-    };
-    let sniffed_mime = sniffed.mime_type();
-    if let Some(declared) = declared_content_type.as_ref() {
-        if declared.essence_str() != sniffed_mime {
+    let sniffed_whole_upload = u64::try_from(sniff_buffer.len()).ok() == Some(total_size);
+    let resolved = match resolve_attachment_type(
+        declared_content_type.as_ref(),
+        &sniff_buffer,
+        sniffed_whole_upload,
+    ) {
+        Ok(resolved) => resolved,
+        Err(error) => {
             let _ = upload.abort().await;
-            return Err(AuthFailure::InvalidRequest);
+            return Err(error);
         }
-    }
+    };
+    let mime_type = resolved.mime_type;
+    let filename = ensure_attachment_extension(filename, resolved.extension);
     upload.complete().await.map_err(|_| AuthFailure::Internal)?;
 
     let sha256_hex = {
@@ -172,7 +184,7 @@ pub(crate) async fn upload_attachment(
         .bind(&path.channel_id)
         .bind(auth.user_id.to_string())
         .bind(&filename)
-        .bind(sniffed_mime)
+        .bind(mime_type)
         .bind(i64::try_from(total_size).map_err(|_| AuthFailure::InvalidRequest)?)
         .bind(&sha256_hex)
         .bind(&object_key)
@@ -200,11 +212,12 @@ pub(crate) async fn upload_attachment(
                 channel_id: path.channel_id.clone(),
                 owner_id: auth.user_id,
                 filename: filename.clone(),
-                mime_type: String::from(sniffed_mime),
+                mime_type: String::from(mime_type),
                 size_bytes: total_size,
                 sha256_hex: sha256_hex.clone(),
                 object_key: object_key.clone(),
                 message_id: None,
+                message_position: None,
             },
         );
     }
@@ -215,7 +228,7 @@ pub(crate) async fn upload_attachment(
         channel_id: path.channel_id,
         owner_id: auth.user_id.to_string(),
         filename,
-        mime_type: String::from(sniffed_mime),
+        mime_type: String::from(mime_type),
         size_bytes: total_size,
         sha256_hex,
     }))
@@ -241,9 +254,14 @@ pub(crate) async fn download_attachment(
         "attachments.download",
     )
     .await?;
-    if !user_can_write_channel(&state, auth.user_id, &path.guild_id, &path.channel_id).await {
-        return Err(AuthFailure::Forbidden);
-    }
+    check_channel_permission(
+        &state,
+        auth.user_id,
+        &path.guild_id,
+        &path.channel_id,
+        Permission::CreateMessage,
+    )
+    .await?;
 
     let record = find_attachment(&state, &path).await?;
     let object_path = ObjectPath::from(record.object_key.clone());
@@ -256,6 +274,23 @@ pub(crate) async fn download_attachment(
         .bytes()
         .await
         .map_err(|_| AuthFailure::Internal)?;
+    if state.runtime.attachment_verify_integrity {
+        let digest = Sha256::digest(&payload);
+        let mut actual_sha256_hex = String::with_capacity(digest.len() * 2);
+        for byte in digest {
+            let _ = std::fmt::Write::write_fmt(&mut actual_sha256_hex, format_args!("{byte:02x}"));
+        }
+        if !actual_sha256_hex.eq_ignore_ascii_case(&record.sha256_hex) {
+            tracing::error!(
+                event = "attachments.integrity_mismatch",
+                attachment_id = %record.attachment_id,
+                object_key = %record.object_key,
+                expected_sha256 = %record.sha256_hex,
+                actual_sha256 = %actual_sha256_hex
+            );
+            return Err(AuthFailure::AttachmentIntegrityMismatch);
+        }
+    }
 
     let mut response = Response::new(payload.into());
     let content_type =
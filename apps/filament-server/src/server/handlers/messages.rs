@@ -1,38 +1,60 @@
 use axum::{
+    body::Body,
     extract::{connect_info::ConnectInfo, Extension, Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+    },
+    response::Response,
     Json,
 };
 use filament_core::{tokenize_markdown, Permission, UserId};
-use object_store::{path::Path as ObjectPath, ObjectStoreExt};
+use futures_util::stream;
 use sqlx::Row;
-use std::net::SocketAddr;
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::SocketAddr,
+};
+use ulid::Ulid;
 
 use crate::server::{
-    auth::{authenticate, channel_key, extract_client_ip, now_unix, validate_message_content},
-    core::{AppState, SearchOperation, MAX_HISTORY_LIMIT, MAX_REACTOR_USER_IDS_PER_REACTION},
+    auth::{
+        apply_message_content_policy, authenticate, channel_key, enforce_channel_export_rate_limit,
+        extract_client_ip, now_unix, validate_message_content,
+    },
+    core::{
+        AppState, SearchOperation, DEFAULT_RECENT_MESSAGES_PER_CHANNEL,
+        MAX_REACTOR_USER_IDS_PER_REACTION, MAX_RECENT_MESSAGES_CHANNELS,
+        MAX_RECENT_MESSAGES_PER_CHANNEL,
+    },
     db::permission_list_from_set,
     domain::{
         attach_message_media, attach_message_reactions, attachment_map_for_messages_db,
-        attachment_map_for_messages_in_memory, attachments_for_message_in_memory,
-        channel_permission_snapshot, enforce_guild_ip_ban_for_request,
-        reaction_map_for_messages_db, reaction_summaries_from_users, user_can_write_channel,
-        validate_reaction_emoji, write_audit_log,
+        attachments_for_message_in_memory, channel_export_page, channel_is_locked, channel_kind,
+        channel_permission_snapshot, check_channel_permission, delete_attachments_in_memory,
+        encode_message_content, enforce_guild_ip_ban_for_request, enforce_guild_mute,
+        export_page_ndjson, guild_permission_snapshot, hard_delete_message_db,
+        message_content_from_row, reaction_map_for_messages_db, reaction_summaries_from_users,
+        spawn_link_preview_fetch, validate_reaction_emoji, write_audit_log,
     },
     errors::AuthFailure,
     gateway_events,
     metrics::record_gateway_event_dropped,
     realtime::{
-        broadcast_channel_event, create_message_internal, enqueue_search_operation,
-        indexed_message_from_response,
+        broadcast_channel_event, claim_message_idempotency, complete_message_idempotency,
+        create_message_internal, enqueue_search_operation, indexed_message_from_response,
+        load_message_history, parse_idempotency_key, release_message_idempotency, IdempotencyClaim,
     },
     types::{
         ChannelPath, ChannelPermissionsResponse, CreateMessageRequest, EditMessageRequest,
-        HistoryQuery, MessageHistoryResponse, MessagePath, MessageResponse, ReactionPath,
-        ReactionResponse,
+        GuildPath, HistoryQuery, HistorySort, MessageHistoryResponse, MessagePath, MessageResponse,
+        MoveMessageRequest, ReactionPath, ReactionResponse, RecentMessagesRequest,
+        RecentMessagesResponse,
     },
 };
 
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
 async fn broadcast_message_reaction_event(
     state: &AppState,
     path: &ReactionPath,
@@ -79,14 +101,18 @@ fn bounded_reactor_user_ids(users: &std::collections::HashSet<UserId>) -> Vec<St
     ids
 }
 
-async fn broadcast_message_update_event(state: &AppState, response: &MessageResponse) {
+async fn broadcast_message_update_event(
+    state: &AppState,
+    response: &MessageResponse,
+    updated_at_unix: i64,
+) {
     let Ok(event) = gateway_events::try_message_update(
         &response.guild_id,
         &response.channel_id,
         &response.message_id,
         &response.content,
         &response.markdown_tokens,
-        now_unix(),
+        updated_at_unix,
     ) else {
         record_gateway_event_dropped(
             "channel",
@@ -137,6 +163,29 @@ async fn broadcast_message_delete_event(state: &AppState, path: &MessagePath) {
     .await;
 }
 
+async fn broadcast_message_create_event(state: &AppState, response: &MessageResponse) {
+    let Ok(event) = gateway_events::try_message_create(response) else {
+        record_gateway_event_dropped(
+            "channel",
+            gateway_events::MESSAGE_CREATE_EVENT,
+            "serialize_error",
+        );
+        tracing::warn!(
+            guild_id = response.guild_id,
+            channel_id = response.channel_id,
+            message_id = response.message_id,
+            "dropped message_create outbound event because serialization failed"
+        );
+        return;
+    };
+    broadcast_channel_event(
+        state,
+        &channel_key(&response.guild_id, &response.channel_id),
+        &event,
+    )
+    .await;
+}
+
 pub(crate) async fn create_message(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -158,7 +207,40 @@ pub(crate) async fn create_message(
         "messages.create",
     )
     .await?;
-    let response = create_message_internal(
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| AuthFailure::InvalidRequest)
+                .and_then(parse_idempotency_key)
+        })
+        .transpose()?;
+    let Some(idempotency_key) = idempotency_key else {
+        let response = create_message_internal(
+            &state,
+            &auth,
+            &path.guild_id,
+            &path.channel_id,
+            payload.content,
+            payload.attachment_ids.unwrap_or_default(),
+        )
+        .await?;
+        return Ok(Json(response));
+    };
+
+    let claim = claim_message_idempotency(
+        &state,
+        auth.user_id,
+        &idempotency_key,
+        &path.guild_id,
+        &path.channel_id,
+    )
+    .await?;
+    if let IdempotencyClaim::Replay(response) = claim {
+        return Ok(Json(*response));
+    }
+    let created = create_message_internal(
         &state,
         &auth,
         &path.guild_id,
@@ -166,8 +248,14 @@ pub(crate) async fn create_message(
         payload.content,
         payload.attachment_ids.unwrap_or_default(),
     )
-    .await?;
-    Ok(Json(response))
+    .await;
+    match &created {
+        Ok(response) => {
+            complete_message_idempotency(&state, auth.user_id, &idempotency_key, response).await;
+        }
+        Err(_) => release_message_idempotency(&state, auth.user_id, &idempotency_key).await,
+    }
+    Ok(Json(created?))
 }
 
 pub(crate) async fn get_channel_permissions(
@@ -202,14 +290,12 @@ pub(crate) async fn get_channel_permissions(
     }))
 }
 
-#[allow(clippy::too_many_lines)]
-pub(crate) async fn get_messages(
+pub(crate) async fn export_channel_messages(
     State(state): State<AppState>,
     headers: HeaderMap,
     connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
     Path(path): Path<ChannelPath>,
-    Query(query): Query<HistoryQuery>,
-) -> Result<Json<MessageHistoryResponse>, AuthFailure> {
+) -> Result<Response, AuthFailure> {
     let client_ip = extract_client_ip(
         &state,
         &headers,
@@ -221,142 +307,172 @@ pub(crate) async fn get_messages(
         &path.guild_id,
         auth.user_id,
         client_ip,
-        "messages.list",
+        "messages.export",
     )
     .await?;
-    let limit = query.limit.unwrap_or(20);
-    if limit == 0 || limit > MAX_HISTORY_LIMIT {
-        return Err(AuthFailure::InvalidRequest);
-    }
     let (_, permissions) =
         channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
-    if !permissions.contains(Permission::CreateMessage) {
+    if !permissions.contains(Permission::CreateMessage)
+        || !permissions.contains(Permission::DeleteMessage)
+    {
         return Err(AuthFailure::Forbidden);
     }
+    enforce_channel_export_rate_limit(&state, client_ip, auth.user_id, &path).await?;
 
-    if let Some(pool) = &state.db_pool {
-        let limit_i64 = i64::try_from(limit).map_err(|_| AuthFailure::InvalidRequest)?;
-        let rows = sqlx::query(
-            "SELECT message_id, author_id, content, created_at_unix
-             FROM messages
-             WHERE guild_id = $1 AND channel_id = $2 AND ($3::text IS NULL OR message_id < $3)
-             ORDER BY message_id DESC
-             LIMIT $4",
-        )
-        .bind(&path.guild_id)
-        .bind(&path.channel_id)
-        .bind(query.before.clone())
-        .bind(limit_i64)
-        .fetch_all(pool)
-        .await
-        .map_err(|_| AuthFailure::Internal)?;
-
-        let mut messages = Vec::with_capacity(rows.len());
-        for row in rows {
-            let message_id: String = row
-                .try_get("message_id")
-                .map_err(|_| AuthFailure::Internal)?;
-            let author_id: String = row
-                .try_get("author_id")
-                .map_err(|_| AuthFailure::Internal)?;
-            let content: String = row.try_get("content").map_err(|_| AuthFailure::Internal)?;
-            let created_at_unix: i64 = row
-                .try_get("created_at_unix")
-                .map_err(|_| AuthFailure::Internal)?;
-            messages.push(MessageResponse {
-                message_id,
-                guild_id: path.guild_id.clone(),
-                channel_id: path.channel_id.clone(),
-                author_id,
-                content: content.clone(),
-                markdown_tokens: tokenize_markdown(&content),
-                attachments: Vec::new(),
-                reactions: Vec::new(),
-                created_at_unix,
-            });
-        }
-        let message_ids: Vec<String> = messages
-            .iter()
-            .map(|message| message.message_id.clone())
-            .collect();
-        let attachment_map = attachment_map_for_messages_db(
-            pool,
-            &path.guild_id,
-            Some(&path.channel_id),
-            &message_ids,
-        )
-        .await?;
-        let reaction_map = reaction_map_for_messages_db(
-            pool,
-            &path.guild_id,
-            Some(&path.channel_id),
-            &message_ids,
-            Some(auth.user_id),
-        )
-        .await?;
-        attach_message_media(&mut messages, &attachment_map);
-        attach_message_reactions(&mut messages, &reaction_map);
-        let next_before = messages.last().map(|message| message.message_id.clone());
-        return Ok(Json(MessageHistoryResponse {
-            messages,
-            next_before,
-        }));
-    }
+    let first_page = channel_export_page(&state, &path.guild_id, &path.channel_id, None).await?;
+    tracing::info!(
+        event = "messages.export",
+        user_id = %auth.user_id,
+        guild_id = %path.guild_id,
+        channel_id = %path.channel_id
+    );
 
-    let guilds = state.membership_store.guilds().read().await;
-    let guild = guilds.get(&path.guild_id).ok_or(AuthFailure::NotFound)?;
-    let channel = guild
-        .channels
-        .get(&path.channel_id)
-        .ok_or(AuthFailure::NotFound)?;
+    let filename = HeaderValue::from_str(&format!(
+        "attachment; filename=\"channel-{}.ndjson\"",
+        path.channel_id
+    ))
+    .map_err(|_| AuthFailure::Internal)?;
+    let pages = stream::try_unfold(
+        (state, path, Some(first_page)),
+        |(state, path, page)| async move {
+            let Some(page) = page else {
+                return Ok(None);
+            };
+            if page.is_empty() {
+                return Ok(None);
+            }
+            let chunk = export_page_ndjson(&page)
+                .map_err(|error| std::io::Error::other(error.to_string()))?;
+            let next = match page.last() {
+                Some(last) => channel_export_page(
+                    &state,
+                    &path.guild_id,
+                    &path.channel_id,
+                    Some(&last.message_id),
+                )
+                .await
+                .map_err(|error| {
+                    tracing::warn!(
+                        event = "messages.export.page_failed",
+                        guild_id = %path.guild_id,
+                        channel_id = %path.channel_id,
+                        error = %error
+                    );
+                    std::io::Error::other(error.to_string())
+                })?,
+                None => Vec::new(),
+            };
+            Ok::<_, std::io::Error>(Some((chunk, (state, path, Some(next)))))
+        },
+    );
 
-    let mut messages = Vec::with_capacity(limit);
-    let mut collecting = query.before.is_none();
+    let mut response = Response::new(Body::from_stream(pages));
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/x-ndjson"),
+    );
+    response.headers_mut().insert(CONTENT_DISPOSITION, filename);
+    response.headers_mut().insert(
+        HeaderName::from_static("x-content-type-options"),
+        HeaderValue::from_static("nosniff"),
+    );
+    response.headers_mut().insert(
+        HeaderName::from_static("cache-control"),
+        HeaderValue::from_static("private, no-store"),
+    );
+    Ok(response)
+}
 
-    for message in channel.messages.iter().rev() {
-        if !collecting {
-            if query.before.as_deref() == Some(message.id.as_str()) {
-                collecting = true;
-            }
-            continue;
-        }
+pub(crate) async fn get_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<ChannelPath>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<MessageHistoryResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "messages.list",
+    )
+    .await?;
+    let history = load_message_history(
+        &state,
+        auth.user_id,
+        &path.guild_id,
+        &path.channel_id,
+        &query,
+    )
+    .await?;
+    Ok(Json(history))
+}
 
-        if messages.len() >= limit {
-            break;
+/// Returns the newest messages of several channels in one round-trip. Each
+/// channel gets the same checks as `GET .../messages`; forbidden or unknown
+/// channels are skipped rather than failing the whole batch.
+pub(crate) async fn get_recent_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<GuildPath>,
+    Json(payload): Json<RecentMessagesRequest>,
+) -> Result<Json<RecentMessagesResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    if payload.channel_ids.is_empty() || payload.channel_ids.len() > MAX_RECENT_MESSAGES_CHANNELS {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let limit = payload.limit.unwrap_or(DEFAULT_RECENT_MESSAGES_PER_CHANNEL);
+    if limit == 0 || limit > MAX_RECENT_MESSAGES_PER_CHANNEL {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let mut channel_ids = HashSet::with_capacity(payload.channel_ids.len());
+    for channel_id in &payload.channel_ids {
+        if Ulid::from_string(channel_id).is_err() {
+            return Err(AuthFailure::InvalidRequest);
         }
-
-        messages.push(MessageResponse {
-            message_id: message.id.clone(),
-            guild_id: path.guild_id.clone(),
-            channel_id: path.channel_id.clone(),
-            author_id: message.author_id.to_string(),
-            content: message.content.clone(),
-            markdown_tokens: message.markdown_tokens.clone(),
-            attachments: Vec::new(),
-            reactions: reaction_summaries_from_users(&message.reactions, Some(auth.user_id)),
-            created_at_unix: message.created_at_unix,
-        });
+        channel_ids.insert(channel_id.as_str());
     }
 
-    let message_ids: Vec<String> = messages
-        .iter()
-        .map(|message| message.message_id.clone())
-        .collect();
-    let attachment_map = attachment_map_for_messages_in_memory(
+    enforce_guild_ip_ban_for_request(
         &state,
         &path.guild_id,
-        Some(&path.channel_id),
-        &message_ids,
+        auth.user_id,
+        client_ip,
+        "messages.recent",
     )
-    .await;
-    attach_message_media(&mut messages, &attachment_map);
-
-    let next_before = messages.last().map(|message| message.message_id.clone());
+    .await?;
+    guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
 
-    Ok(Json(MessageHistoryResponse {
-        messages,
-        next_before,
-    }))
+    let query = HistoryQuery {
+        limit: Some(limit.min(state.runtime.history_max_limit)),
+        before: None,
+        include_deleted: false,
+        sort: HistorySort::Recent,
+    };
+    let mut channels = BTreeMap::new();
+    for channel_id in channel_ids {
+        match load_message_history(&state, auth.user_id, &path.guild_id, channel_id, &query).await {
+            Ok(history) => {
+                channels.insert(channel_id.to_owned(), history.messages);
+            }
+            Err(AuthFailure::Forbidden | AuthFailure::NotFound) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(Json(RecentMessagesResponse { channels }))
 }
 
 #[allow(clippy::too_many_lines)]
@@ -382,15 +498,18 @@ pub(crate) async fn edit_message(
     )
     .await?;
     validate_message_content(&payload.content)?;
-    let markdown_tokens = tokenize_markdown(&payload.content);
+    let expected_edited_at_unix = payload.expected_edited_at_unix;
+    let content = apply_message_content_policy(&state.runtime, payload.content)?;
+    let markdown_tokens = tokenize_markdown(&content);
     let (_, permissions) =
         channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
 
     if let Some(pool) = &state.db_pool {
         let row = sqlx::query(
-            "SELECT m.author_id
+            "SELECT m.author_id, m.system
              FROM messages m
-             WHERE m.guild_id = $1 AND m.channel_id = $2 AND m.message_id = $3",
+             WHERE m.guild_id = $1 AND m.channel_id = $2 AND m.message_id = $3
+               AND m.deleted_at_unix IS NULL",
         )
         .bind(&path.guild_id)
         .bind(&path.channel_id)
@@ -402,22 +521,52 @@ pub(crate) async fn edit_message(
         let author_id: String = row
             .try_get("author_id")
             .map_err(|_| AuthFailure::Internal)?;
-        if author_id != auth.user_id.to_string() && !permissions.contains(Permission::DeleteMessage)
+        let system: bool = row.try_get("system").map_err(|_| AuthFailure::Internal)?;
+        if system
+            || (author_id != auth.user_id.to_string()
+                && !permissions.contains(Permission::DeleteMessage))
         {
             return Err(AuthFailure::Forbidden);
         }
 
-        sqlx::query(
-            "UPDATE messages SET content = $4
-             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3",
+        let stored = encode_message_content(
+            &content,
+            &path.message_id,
+            state.runtime.message_content_compression,
+            state.message_content_cipher.as_deref(),
+        )?;
+        let updated = sqlx::query(
+            "UPDATE messages
+             SET content = $4, content_zstd = $5, content_sealed = $6,
+                 edited_at_unix = GREATEST($7, COALESCE(edited_at_unix + 1, $7))
+             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3
+               AND deleted_at_unix IS NULL
+               AND ($8::BIGINT IS NULL OR COALESCE(edited_at_unix, 0) = $8)
+             RETURNING created_at_unix, edited_at_unix",
         )
         .bind(&path.guild_id)
         .bind(&path.channel_id)
         .bind(&path.message_id)
-        .bind(&payload.content)
-        .execute(pool)
+        .bind(&stored.content)
+        .bind(&stored.content_zstd)
+        .bind(&stored.content_sealed)
+        .bind(now_unix())
+        .bind(expected_edited_at_unix)
+        .fetch_optional(pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
+        // The row existed a moment ago, so a miss means a concurrent edit or delete.
+        let updated = updated.ok_or(if expected_edited_at_unix.is_some() {
+            AuthFailure::EditConflict
+        } else {
+            AuthFailure::NotFound
+        })?;
+        let created_at_unix: i64 = updated
+            .try_get("created_at_unix")
+            .map_err(|_| AuthFailure::Internal)?;
+        let edited_at_unix: i64 = updated
+            .try_get("edited_at_unix")
+            .map_err(|_| AuthFailure::Internal)?;
 
         let attachment_map = attachment_map_for_messages_db(
             pool,
@@ -439,7 +588,7 @@ pub(crate) async fn edit_message(
             guild_id: path.guild_id.clone(),
             channel_id: path.channel_id.clone(),
             author_id: author_id.clone(),
-            content: payload.content,
+            content,
             markdown_tokens,
             attachments: attachment_map
                 .get(&path.message_id)
@@ -449,7 +598,13 @@ pub(crate) async fn edit_message(
                 .get(&path.message_id)
                 .cloned()
                 .unwrap_or_default(),
-            created_at_unix: now_unix(),
+            embeds: Vec::new(),
+            author_username: None,
+            created_at_unix,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: Some(edited_at_unix),
         };
         if author_id != auth.user_id.to_string() {
             write_audit_log(
@@ -465,10 +620,11 @@ pub(crate) async fn edit_message(
         enqueue_search_operation(
             &state,
             SearchOperation::Upsert(indexed_message_from_response(&response)),
-            true,
+            state.runtime.search_sync_index_writes,
         )
         .await?;
-        broadcast_message_update_event(&state, &response).await;
+        broadcast_message_update_event(&state, &response, edited_at_unix).await;
+        spawn_link_preview_fetch(&state, &response.markdown_tokens);
         return Ok(Json(response));
     }
 
@@ -483,13 +639,25 @@ pub(crate) async fn edit_message(
     let message = channel
         .messages
         .iter_mut()
-        .find(|message| message.id == path.message_id)
+        .find(|message| message.id == path.message_id && message.deleted_at_unix.is_none())
         .ok_or(AuthFailure::NotFound)?;
-    if message.author_id != auth.user_id && !permissions.contains(Permission::DeleteMessage) {
+    if message.system
+        || (message.author_id != auth.user_id && !permissions.contains(Permission::DeleteMessage))
+    {
         return Err(AuthFailure::Forbidden);
     }
-    message.content.clone_from(&payload.content);
+    if expected_edited_at_unix
+        .is_some_and(|expected| expected != message.edited_at_unix.unwrap_or(0))
+    {
+        return Err(AuthFailure::EditConflict);
+    }
+    let now = now_unix();
+    let edited_at_unix = message
+        .edited_at_unix
+        .map_or(now, |previous| now.max(previous.saturating_add(1)));
+    message.content.clone_from(&content);
     message.markdown_tokens.clone_from(&markdown_tokens);
+    message.edited_at_unix = Some(edited_at_unix);
 
     let response = MessageResponse {
         message_id: message.id.clone(),
@@ -500,15 +668,22 @@ pub(crate) async fn edit_message(
         markdown_tokens,
         attachments: attachments_for_message_in_memory(&state, &message.attachment_ids).await?,
         reactions: reaction_summaries_from_users(&message.reactions, Some(auth.user_id)),
+        embeds: Vec::new(),
+        author_username: None,
         created_at_unix: message.created_at_unix,
+        deleted: false,
+        system: false,
+        score: None,
+        edited_at_unix: Some(edited_at_unix),
     };
     enqueue_search_operation(
         &state,
         SearchOperation::Upsert(indexed_message_from_response(&response)),
-        true,
+        state.runtime.search_sync_index_writes,
     )
     .await?;
-    broadcast_message_update_event(&state, &response).await;
+    broadcast_message_update_event(&state, &response, edited_at_unix).await;
+    spawn_link_preview_fetch(&state, &response.markdown_tokens);
     Ok(Json(response))
 }
 
@@ -535,12 +710,14 @@ pub(crate) async fn delete_message(
     .await?;
     let (_, permissions) =
         channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
+    let tombstone = !state.runtime.deleted_message_retention.is_zero();
 
     if let Some(pool) = &state.db_pool {
         let row = sqlx::query(
             "SELECT m.author_id
              FROM messages m
-             WHERE m.guild_id = $1 AND m.channel_id = $2 AND m.message_id = $3",
+             WHERE m.guild_id = $1 AND m.channel_id = $2 AND m.message_id = $3
+               AND m.deleted_at_unix IS NULL",
         )
         .bind(&path.guild_id)
         .bind(&path.channel_id)
@@ -557,46 +734,28 @@ pub(crate) async fn delete_message(
             return Err(AuthFailure::Forbidden);
         }
 
-        let linked_attachment_rows = sqlx::query(
-            "SELECT attachment_id, object_key
-             FROM attachments
-             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3",
-        )
-        .bind(&path.guild_id)
-        .bind(&path.channel_id)
-        .bind(&path.message_id)
-        .fetch_all(pool)
-        .await
-        .map_err(|_| AuthFailure::Internal)?;
-
-        sqlx::query(
-            "DELETE FROM messages
-             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3",
-        )
-        .bind(&path.guild_id)
-        .bind(&path.channel_id)
-        .bind(&path.message_id)
-        .execute(pool)
-        .await
-        .map_err(|_| AuthFailure::Internal)?;
-        if !linked_attachment_rows.is_empty() {
+        if tombstone {
             sqlx::query(
-                "DELETE FROM attachments
-                 WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3",
+                "UPDATE messages SET deleted_at_unix = $4
+                 WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3
+                   AND deleted_at_unix IS NULL",
             )
             .bind(&path.guild_id)
             .bind(&path.channel_id)
             .bind(&path.message_id)
+            .bind(now_unix())
             .execute(pool)
             .await
             .map_err(|_| AuthFailure::Internal)?;
-        }
-        for row in linked_attachment_rows {
-            let object_key: String = row
-                .try_get("object_key")
-                .map_err(|_| AuthFailure::Internal)?;
-            let object_path = ObjectPath::from(object_key);
-            let _ = state.attachment_store.delete(&object_path).await;
+        } else {
+            hard_delete_message_db(
+                &state,
+                pool,
+                &path.guild_id,
+                &path.channel_id,
+                &path.message_id,
+            )
+            .await?;
         }
 
         if author_id != auth.user_id.to_string() {
@@ -615,7 +774,7 @@ pub(crate) async fn delete_message(
             SearchOperation::Delete {
                 message_id: path.message_id.clone(),
             },
-            true,
+            state.runtime.search_sync_index_writes,
         )
         .await?;
         broadcast_message_delete_event(&state, &path).await;
@@ -633,7 +792,7 @@ pub(crate) async fn delete_message(
     let Some(index) = channel
         .messages
         .iter()
-        .position(|message| message.id == path.message_id)
+        .position(|message| message.id == path.message_id && message.deleted_at_unix.is_none())
     else {
         return Err(AuthFailure::NotFound);
     };
@@ -641,33 +800,391 @@ pub(crate) async fn delete_message(
     if author_id != auth.user_id && !permissions.contains(Permission::DeleteMessage) {
         return Err(AuthFailure::Forbidden);
     }
-    let removed = channel.messages.remove(index);
-    if !removed.attachment_ids.is_empty() {
-        let mut attachments = state.attachments.write().await;
-        let mut object_keys = Vec::new();
-        for attachment_id in removed.attachment_ids {
-            if let Some(record) = attachments.remove(&attachment_id) {
-                object_keys.push(record.object_key);
-            }
-        }
-        drop(attachments);
-        for object_key in object_keys {
-            let object_path = ObjectPath::from(object_key);
-            let _ = state.attachment_store.delete(&object_path).await;
-        }
+    if tombstone {
+        channel.messages[index].deleted_at_unix = Some(now_unix());
+        drop(guilds);
+    } else {
+        let removed = channel.messages.remove(index);
+        drop(guilds);
+        delete_attachments_in_memory(&state, removed.attachment_ids).await;
     }
     enqueue_search_operation(
         &state,
         SearchOperation::Delete {
             message_id: path.message_id.clone(),
         },
-        true,
+        state.runtime.search_sync_index_writes,
     )
     .await?;
     broadcast_message_delete_event(&state, &path).await;
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[allow(clippy::too_many_lines)]
+pub(crate) async fn restore_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<MessagePath>,
+) -> Result<Json<MessageResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "messages.restore",
+    )
+    .await?;
+    let (_, permissions) =
+        channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
+    if !permissions.contains(Permission::DeleteMessage) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    let response = if let Some(pool) = &state.db_pool {
+        let row = sqlx::query(
+            "UPDATE messages SET deleted_at_unix = NULL
+             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3
+               AND deleted_at_unix IS NOT NULL
+             RETURNING author_id, content, content_zstd, content_sealed, created_at_unix,
+                       edited_at_unix, system",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(&path.message_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        let content = message_content_from_row(
+            &row,
+            &path.message_id,
+            state.message_content_cipher.as_deref(),
+        )?;
+        let mut messages = vec![MessageResponse {
+            message_id: path.message_id.clone(),
+            guild_id: path.guild_id.clone(),
+            channel_id: path.channel_id.clone(),
+            author_id: row
+                .try_get("author_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            markdown_tokens: tokenize_markdown(&content),
+            content,
+            attachments: Vec::new(),
+            reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
+            created_at_unix: row
+                .try_get("created_at_unix")
+                .map_err(|_| AuthFailure::Internal)?,
+            deleted: false,
+            system: row.try_get("system").map_err(|_| AuthFailure::Internal)?,
+            score: None,
+            edited_at_unix: row
+                .try_get("edited_at_unix")
+                .map_err(|_| AuthFailure::Internal)?,
+        }];
+        let message_ids = [path.message_id.clone()];
+        let attachment_map = attachment_map_for_messages_db(
+            pool,
+            &path.guild_id,
+            Some(&path.channel_id),
+            &message_ids,
+        )
+        .await?;
+        let reaction_map = reaction_map_for_messages_db(
+            pool,
+            &path.guild_id,
+            Some(&path.channel_id),
+            &message_ids,
+            Some(auth.user_id),
+        )
+        .await?;
+        attach_message_media(&mut messages, &attachment_map);
+        attach_message_reactions(&mut messages, &reaction_map);
+        messages.pop().ok_or(AuthFailure::Internal)?
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let message = guilds
+            .get_mut(&path.guild_id)
+            .ok_or(AuthFailure::NotFound)?
+            .channels
+            .get_mut(&path.channel_id)
+            .ok_or(AuthFailure::NotFound)?
+            .messages
+            .iter_mut()
+            .find(|message| message.id == path.message_id && message.deleted_at_unix.is_some())
+            .ok_or(AuthFailure::NotFound)?;
+        message.deleted_at_unix = None;
+        let record = message.clone();
+        drop(guilds);
+        MessageResponse {
+            message_id: record.id,
+            guild_id: path.guild_id.clone(),
+            channel_id: path.channel_id.clone(),
+            author_id: record.author_id.to_string(),
+            content: record.content,
+            markdown_tokens: record.markdown_tokens,
+            attachments: attachments_for_message_in_memory(&state, &record.attachment_ids).await?,
+            reactions: reaction_summaries_from_users(&record.reactions, Some(auth.user_id)),
+            embeds: Vec::new(),
+            author_username: None,
+            created_at_unix: record.created_at_unix,
+            deleted: false,
+            system: record.system,
+            score: None,
+            edited_at_unix: record.edited_at_unix,
+        }
+    };
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id.clone()),
+        auth.user_id,
+        Some(UserId::try_from(response.author_id.clone()).map_err(|_| AuthFailure::Internal)?),
+        "message.restore",
+        serde_json::json!({"message_id": path.message_id, "channel_id": path.channel_id}),
+    )
+    .await?;
+    enqueue_search_operation(
+        &state,
+        SearchOperation::Upsert(indexed_message_from_response(&response)),
+        state.runtime.search_sync_index_writes,
+    )
+    .await?;
+    broadcast_message_update_event(&state, &response, now_unix()).await;
+    Ok(Json(response))
+}
+
+/// Moves a message to another channel in the same guild. Clients in the old
+/// channel see a delete and clients in the new channel see a create.
+#[allow(clippy::too_many_lines)]
+pub(crate) async fn move_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<MessagePath>,
+    Json(payload): Json<MoveMessageRequest>,
+) -> Result<Json<MessageResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "messages.move",
+    )
+    .await?;
+    let target_channel_id = payload.channel_id;
+    if target_channel_id == path.channel_id {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let (_, source_permissions) =
+        channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &path.channel_id).await?;
+    if !source_permissions.contains(Permission::DeleteMessage) {
+        return Err(AuthFailure::Forbidden);
+    }
+    let (_, target_permissions) =
+        channel_permission_snapshot(&state, auth.user_id, &path.guild_id, &target_channel_id)
+            .await?;
+    if !target_permissions.contains(Permission::CreateMessage) {
+        return Err(AuthFailure::Forbidden);
+    }
+    if !target_permissions.contains(Permission::DeleteMessage)
+        && !target_permissions.contains(Permission::ManageChannelOverrides)
+        && channel_is_locked(&state, &path.guild_id, &target_channel_id).await?
+    {
+        return Err(AuthFailure::Forbidden);
+    }
+    if !channel_kind(&state, &path.guild_id, &target_channel_id)
+        .await?
+        .holds_messages()
+    {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let response = if let Some(pool) = &state.db_pool {
+        let mut tx = pool.begin().await.map_err(|_| AuthFailure::Internal)?;
+        let row = sqlx::query(
+            "UPDATE messages SET channel_id = $4
+             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3
+               AND deleted_at_unix IS NULL
+             RETURNING author_id, content, content_zstd, content_sealed, created_at_unix,
+                       edited_at_unix, system",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(&path.message_id)
+        .bind(&target_channel_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
+        sqlx::query(
+            "UPDATE attachments SET channel_id = $3
+             WHERE guild_id = $1 AND message_id = $2",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.message_id)
+        .bind(&target_channel_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        sqlx::query(
+            "UPDATE message_reactions SET channel_id = $3
+             WHERE guild_id = $1 AND message_id = $2",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.message_id)
+        .bind(&target_channel_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        tx.commit().await.map_err(|_| AuthFailure::Internal)?;
+
+        let content = message_content_from_row(
+            &row,
+            &path.message_id,
+            state.message_content_cipher.as_deref(),
+        )?;
+        let mut messages = vec![MessageResponse {
+            message_id: path.message_id.clone(),
+            guild_id: path.guild_id.clone(),
+            channel_id: target_channel_id.clone(),
+            author_id: row
+                .try_get("author_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            markdown_tokens: tokenize_markdown(&content),
+            content,
+            attachments: Vec::new(),
+            reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
+            created_at_unix: row
+                .try_get("created_at_unix")
+                .map_err(|_| AuthFailure::Internal)?,
+            deleted: false,
+            system: row.try_get("system").map_err(|_| AuthFailure::Internal)?,
+            score: None,
+            edited_at_unix: row
+                .try_get("edited_at_unix")
+                .map_err(|_| AuthFailure::Internal)?,
+        }];
+        let message_ids = [path.message_id.clone()];
+        let attachment_map = attachment_map_for_messages_db(
+            pool,
+            &path.guild_id,
+            Some(&target_channel_id),
+            &message_ids,
+        )
+        .await?;
+        let reaction_map = reaction_map_for_messages_db(
+            pool,
+            &path.guild_id,
+            Some(&target_channel_id),
+            &message_ids,
+            Some(auth.user_id),
+        )
+        .await?;
+        attach_message_media(&mut messages, &attachment_map);
+        attach_message_reactions(&mut messages, &reaction_map);
+        messages.pop().ok_or(AuthFailure::Internal)?
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let guild = guilds
+            .get_mut(&path.guild_id)
+            .ok_or(AuthFailure::NotFound)?;
+        let target_len = guild
+            .channels
+            .get(&target_channel_id)
+            .ok_or(AuthFailure::NotFound)?
+            .messages
+            .len();
+        if target_len >= state.runtime.in_memory_max_channel_messages {
+            return Err(AuthFailure::InMemoryCapacityReached);
+        }
+        let source = guild
+            .channels
+            .get_mut(&path.channel_id)
+            .ok_or(AuthFailure::NotFound)?;
+        let index = source
+            .messages
+            .iter()
+            .position(|message| message.id == path.message_id && message.deleted_at_unix.is_none())
+            .ok_or(AuthFailure::NotFound)?;
+        let record = source.messages.remove(index);
+        let target = guild
+            .channels
+            .get_mut(&target_channel_id)
+            .ok_or(AuthFailure::Internal)?;
+        // Message ids are ULIDs, so id order keeps the history chronological.
+        let position = target
+            .messages
+            .partition_point(|message| message.id < record.id);
+        target.messages.insert(position, record.clone());
+        drop(guilds);
+
+        let mut attachments = state.attachments.write().await;
+        for attachment_id in &record.attachment_ids {
+            if let Some(attachment) = attachments.get_mut(attachment_id) {
+                attachment.channel_id.clone_from(&target_channel_id);
+            }
+        }
+        drop(attachments);
+        MessageResponse {
+            message_id: record.id,
+            guild_id: path.guild_id.clone(),
+            channel_id: target_channel_id.clone(),
+            author_id: record.author_id.to_string(),
+            content: record.content,
+            markdown_tokens: record.markdown_tokens,
+            attachments: attachments_for_message_in_memory(&state, &record.attachment_ids).await?,
+            reactions: reaction_summaries_from_users(&record.reactions, Some(auth.user_id)),
+            embeds: Vec::new(),
+            author_username: None,
+            created_at_unix: record.created_at_unix,
+            deleted: false,
+            system: record.system,
+            score: None,
+            edited_at_unix: record.edited_at_unix,
+        }
+    };
+
+    write_audit_log(
+        &state,
+        Some(path.guild_id.clone()),
+        auth.user_id,
+        Some(UserId::try_from(response.author_id.clone()).map_err(|_| AuthFailure::Internal)?),
+        "message.move",
+        serde_json::json!({
+            "message_id": path.message_id,
+            "from_channel_id": path.channel_id,
+            "to_channel_id": target_channel_id,
+        }),
+    )
+    .await?;
+    enqueue_search_operation(
+        &state,
+        SearchOperation::Upsert(indexed_message_from_response(&response)),
+        state.runtime.search_sync_index_writes,
+    )
+    .await?;
+    broadcast_message_delete_event(&state, &path).await;
+    broadcast_message_create_event(&state, &response).await;
+    Ok(Json(response))
+}
+
 pub(crate) async fn add_reaction(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -689,11 +1206,29 @@ pub(crate) async fn add_reaction(
     )
     .await?;
     validate_reaction_emoji(&path.emoji)?;
-    if !user_can_write_channel(&state, auth.user_id, &path.guild_id, &path.channel_id).await {
-        return Err(AuthFailure::Forbidden);
-    }
+    check_channel_permission(
+        &state,
+        auth.user_id,
+        &path.guild_id,
+        &path.channel_id,
+        Permission::CreateMessage,
+    )
+    .await?;
+    enforce_guild_mute(&state, &path.guild_id, auth.user_id).await?;
 
     if let Some(pool) = &state.db_pool {
+        sqlx::query(
+            "SELECT 1 FROM messages
+             WHERE guild_id = $1 AND channel_id = $2 AND message_id = $3
+               AND deleted_at_unix IS NULL",
+        )
+        .bind(&path.guild_id)
+        .bind(&path.channel_id)
+        .bind(&path.message_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?;
         sqlx::query(
             "INSERT INTO message_reactions (guild_id, channel_id, message_id, emoji, user_id, created_at_unix)
              VALUES ($1, $2, $3, $4, $5, $6)
@@ -757,7 +1292,7 @@ pub(crate) async fn add_reaction(
     let message = channel
         .messages
         .iter_mut()
-        .find(|message| message.id == path.message_id)
+        .find(|message| message.id == path.message_id && message.deleted_at_unix.is_none())
         .ok_or(AuthFailure::NotFound)?;
     let users = message.reactions.entry(path.emoji.clone()).or_default();
     users.insert(auth.user_id);
@@ -800,9 +1335,14 @@ pub(crate) async fn remove_reaction(
     )
     .await?;
     validate_reaction_emoji(&path.emoji)?;
-    if !user_can_write_channel(&state, auth.user_id, &path.guild_id, &path.channel_id).await {
-        return Err(AuthFailure::Forbidden);
-    }
+    check_channel_permission(
+        &state,
+        auth.user_id,
+        &path.guild_id,
+        &path.channel_id,
+        Permission::CreateMessage,
+    )
+    .await?;
 
     if let Some(pool) = &state.db_pool {
         sqlx::query(
@@ -860,7 +1400,7 @@ pub(crate) async fn remove_reaction(
     let message = channel
         .messages
         .iter_mut()
-        .find(|message| message.id == path.message_id)
+        .find(|message| message.id == path.message_id && message.deleted_at_unix.is_none())
         .ok_or(AuthFailure::NotFound)?;
     let count = if let Some(users) = message.reactions.get_mut(&path.emoji) {
         users.remove(&auth.user_id);
@@ -1,7 +1,13 @@
+pub(crate) mod admin;
 pub(crate) mod auth;
+pub(crate) mod capabilities;
 pub(crate) mod friends;
 pub(crate) mod guilds;
 pub(crate) mod media;
 pub(crate) mod messages;
+pub(crate) mod mutes;
+pub(crate) mod notifications;
 pub(crate) mod profile;
+pub(crate) mod push;
+pub(crate) mod reports;
 pub(crate) mod search;
@@ -0,0 +1,180 @@
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+use filament_core::{can_moderate_member_legacy, has_permission_legacy, Permission, Role, UserId};
+
+use crate::server::{
+    auth::{authenticate, now_unix},
+    core::{AppState, GuildMuteRecord, MAX_GUILD_MUTE_DURATION_SECS, MIN_GUILD_MUTE_DURATION_SECS},
+    domain::{member_role_in_guild, user_role_in_guild, write_audit_log},
+    errors::AuthFailure,
+    types::{MemberMuteResponse, MemberPath, ModerationResponse, MuteMemberRequest},
+};
+
+/// Mutes share the kick/ban gate: `ban_member` plus role hierarchy.
+async fn authorize_mute_moderation(
+    state: &AppState,
+    actor_user_id: UserId,
+    guild_id: &str,
+    raw_target_user_id: String,
+) -> Result<(Role, UserId), AuthFailure> {
+    let actor_role = user_role_in_guild(state, actor_user_id, guild_id).await?;
+    if !has_permission_legacy(actor_role, Permission::BanMember) {
+        return Err(AuthFailure::Forbidden);
+    }
+    let target_user_id =
+        UserId::try_from(raw_target_user_id).map_err(|_| AuthFailure::InvalidRequest)?;
+    Ok((actor_role, target_user_id))
+}
+
+async fn persist_member_mute(
+    state: &AppState,
+    guild_id: &str,
+    target_user_id: UserId,
+    mute: GuildMuteRecord,
+) -> Result<(), AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        sqlx::query(
+            "INSERT INTO guild_mutes (guild_id, user_id, muted_by_user_id, created_at_unix, expires_at_unix)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (guild_id, user_id) DO UPDATE SET muted_by_user_id = EXCLUDED.muted_by_user_id, created_at_unix = EXCLUDED.created_at_unix, expires_at_unix = EXCLUDED.expires_at_unix",
+        )
+        .bind(guild_id)
+        .bind(target_user_id.to_string())
+        .bind(mute.muted_by_user_id.to_string())
+        .bind(mute.created_at_unix)
+        .bind(mute.expires_at_unix)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        return Ok(());
+    }
+
+    state
+        .guild_mutes
+        .write()
+        .await
+        .entry(guild_id.to_owned())
+        .or_default()
+        .insert(target_user_id, mute);
+    Ok(())
+}
+
+/// Removes an active mute. Returns whether one was lifted; expired rows are
+/// left for the purge task.
+async fn remove_member_mute(
+    state: &AppState,
+    guild_id: &str,
+    target_user_id: UserId,
+) -> Result<bool, AuthFailure> {
+    let now = now_unix();
+    if let Some(pool) = &state.db_pool {
+        let result = sqlx::query(
+            "DELETE FROM guild_mutes
+             WHERE guild_id = $1 AND user_id = $2 AND expires_at_unix > $3",
+        )
+        .bind(guild_id)
+        .bind(target_user_id.to_string())
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        return Ok(result.rows_affected() > 0);
+    }
+
+    let mut mutes = state.guild_mutes.write().await;
+    let Some(guild_mutes) = mutes.get_mut(guild_id) else {
+        return Ok(false);
+    };
+    let lifted = guild_mutes
+        .remove(&target_user_id)
+        .is_some_and(|mute| mute.expires_at_unix > now);
+    if guild_mutes.is_empty() {
+        mutes.remove(guild_id);
+    }
+    Ok(lifted)
+}
+
+/// Mutes a member for `duration_secs`, replacing any mute already in place.
+pub(crate) async fn mute_member(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<MemberPath>,
+    Json(payload): Json<MuteMemberRequest>,
+) -> Result<Json<MemberMuteResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (actor_role, target_user_id) =
+        authorize_mute_moderation(&state, auth.user_id, &path.guild_id, path.user_id).await?;
+    let target_role = member_role_in_guild(&state, target_user_id, &path.guild_id).await?;
+    if !can_moderate_member_legacy(actor_role, target_role) {
+        return Err(AuthFailure::Forbidden);
+    }
+    if !(MIN_GUILD_MUTE_DURATION_SECS..=MAX_GUILD_MUTE_DURATION_SECS)
+        .contains(&payload.duration_secs)
+    {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let created_at_unix = now_unix();
+    let expires_at_unix = created_at_unix
+        + i64::try_from(payload.duration_secs).map_err(|_| AuthFailure::InvalidRequest)?;
+    persist_member_mute(
+        &state,
+        &path.guild_id,
+        target_user_id,
+        GuildMuteRecord {
+            muted_by_user_id: auth.user_id,
+            created_at_unix,
+            expires_at_unix,
+        },
+    )
+    .await?;
+    write_audit_log(
+        &state,
+        Some(path.guild_id.clone()),
+        auth.user_id,
+        Some(target_user_id),
+        "moderation.mute",
+        serde_json::json!({
+            "duration_secs": payload.duration_secs,
+            "expires_at_unix": expires_at_unix,
+        }),
+    )
+    .await?;
+    Ok(Json(MemberMuteResponse {
+        guild_id: path.guild_id,
+        user_id: target_user_id.to_string(),
+        expires_at_unix,
+    }))
+}
+
+/// Lifts a member's mute early. Answers `404` when no mute is active.
+pub(crate) async fn unmute_member(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<MemberPath>,
+) -> Result<Json<ModerationResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (actor_role, target_user_id) =
+        authorize_mute_moderation(&state, auth.user_id, &path.guild_id, path.user_id).await?;
+    if let Ok(target_role) = member_role_in_guild(&state, target_user_id, &path.guild_id).await {
+        if !can_moderate_member_legacy(actor_role, target_role) {
+            return Err(AuthFailure::Forbidden);
+        }
+    }
+    if !remove_member_mute(&state, &path.guild_id, target_user_id).await? {
+        return Err(AuthFailure::NotFound);
+    }
+    write_audit_log(
+        &state,
+        Some(path.guild_id),
+        auth.user_id,
+        Some(target_user_id),
+        "moderation.unmute",
+        serde_json::json!({}),
+    )
+    .await?;
+    Ok(Json(ModerationResponse { accepted: true }))
+}
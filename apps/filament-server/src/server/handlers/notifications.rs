@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use ulid::Ulid;
+
+use crate::server::{
+    auth::authenticate,
+    core::{
+        AppState, NotificationRecord, DEFAULT_NOTIFICATION_LIST_LIMIT, MAX_NOTIFICATION_LIST_LIMIT,
+        MAX_NOTIFICATION_MARK_READ_IDS,
+    },
+    domain::{list_user_notifications, mark_user_notifications_read},
+    errors::AuthFailure,
+    types::{
+        MarkNotificationsReadRequest, NotificationListQuery, NotificationListResponse,
+        NotificationResponse,
+    },
+};
+
+fn parse_notification_id(raw: &str) -> Result<String, AuthFailure> {
+    Ulid::from_string(raw)
+        .map(|ulid| ulid.to_string())
+        .map_err(|_| AuthFailure::InvalidRequest)
+}
+
+fn notification_response_from_record(record: NotificationRecord) -> NotificationResponse {
+    NotificationResponse {
+        notification_id: record.notification_id,
+        kind: "mention",
+        guild_id: record.guild_id,
+        channel_id: record.channel_id,
+        message_id: record.message_id,
+        author_id: record.author_id.to_string(),
+        scope: record.scope,
+        created_at_unix: record.created_at_unix,
+        read: record.read_at_unix.is_some(),
+    }
+}
+
+/// Lists the caller's notification inbox newest first. Entries are written
+/// whether or not the user was connected, so clients catch up here after
+/// reconnecting instead of relying on gateway events alone.
+pub(crate) async fn list_notifications(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<NotificationListQuery>,
+) -> Result<Json<NotificationListResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let limit = query.limit.unwrap_or(DEFAULT_NOTIFICATION_LIST_LIMIT);
+    if limit == 0 || limit > MAX_NOTIFICATION_LIST_LIMIT {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let before = query
+        .before
+        .as_deref()
+        .map(parse_notification_id)
+        .transpose()?;
+
+    let (mut records, unread_count) = list_user_notifications(
+        &state,
+        auth.user_id,
+        before.as_deref(),
+        query.unread_only,
+        limit + 1,
+    )
+    .await?;
+    let next_before = if records.len() > limit {
+        records.truncate(limit);
+        records.last().map(|record| record.notification_id.clone())
+    } else {
+        None
+    };
+    Ok(Json(NotificationListResponse {
+        notifications: records
+            .into_iter()
+            .map(notification_response_from_record)
+            .collect(),
+        unread_count,
+        next_before,
+    }))
+}
+
+pub(crate) async fn mark_notifications_read(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<MarkNotificationsReadRequest>,
+) -> Result<StatusCode, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    if payload.notification_ids.is_empty()
+        || payload.notification_ids.len() > MAX_NOTIFICATION_MARK_READ_IDS
+    {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let notification_ids = payload
+        .notification_ids
+        .iter()
+        .map(|raw| parse_notification_id(raw))
+        .collect::<Result<Vec<_>, _>>()?;
+    mark_user_notifications_read(&state, auth.user_id, &notification_ids).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
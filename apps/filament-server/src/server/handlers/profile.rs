@@ -16,9 +16,9 @@ use filament_core::{tokenize_markdown, ProfileAbout, UserId, Username};
 use crate::server::{
     auth::{authenticate, enforce_auth_route_rate_limit, extract_client_ip, now_unix},
     core::{
-        AppState, ProfileAvatarRecord, ProfileBannerRecord, MAX_MIME_SNIFF_BYTES,
-        MAX_PROFILE_AVATAR_MIME_CHARS, MAX_PROFILE_AVATAR_OBJECT_KEY_CHARS,
-        MAX_PROFILE_BANNER_MIME_CHARS, MAX_PROFILE_BANNER_OBJECT_KEY_CHARS,
+        AppState, ProfileAvatarRecord, ProfileBannerRecord, MAX_PROFILE_AVATAR_MIME_CHARS,
+        MAX_PROFILE_AVATAR_OBJECT_KEY_CHARS, MAX_PROFILE_BANNER_MIME_CHARS,
+        MAX_PROFILE_BANNER_OBJECT_KEY_CHARS,
     },
     errors::AuthFailure,
     gateway_events,
@@ -255,8 +255,8 @@ pub(crate) async fn upload_my_avatar(
             let _ = upload.abort().await;
             return Err(AuthFailure::PayloadTooLarge);
         }
-        if sniff_buffer.len() < MAX_MIME_SNIFF_BYTES {
-            let remaining = MAX_MIME_SNIFF_BYTES - sniff_buffer.len();
+        if sniff_buffer.len() < state.runtime.mime_sniff_bytes {
+            let remaining = state.runtime.mime_sniff_bytes - sniff_buffer.len();
             let copy_len = remaining.min(chunk.len());
             sniff_buffer.extend_from_slice(&chunk[..copy_len]);
         }
@@ -477,8 +477,8 @@ pub(crate) async fn upload_my_banner(
             let _ = upload.abort().await;
             return Err(AuthFailure::PayloadTooLarge);
         }
-        if sniff_buffer.len() < MAX_MIME_SNIFF_BYTES {
-            let remaining = MAX_MIME_SNIFF_BYTES - sniff_buffer.len();
+        if sniff_buffer.len() < state.runtime.mime_sniff_bytes {
+            let remaining = state.runtime.mime_sniff_bytes - sniff_buffer.len();
             let copy_len = remaining.min(chunk.len());
             sniff_buffer.extend_from_slice(&chunk[..copy_len]);
         }
@@ -0,0 +1,66 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use ulid::Ulid;
+
+use crate::server::{
+    auth::authenticate,
+    core::AppState,
+    domain::{
+        delete_push_subscription, push_subscription_keys_valid, register_push_subscription,
+        validate_push_endpoint,
+    },
+    errors::AuthFailure,
+    types::{PushSubscriptionPath, PushSubscriptionRequest, PushSubscriptionResponse},
+};
+
+/// Registers a browser push subscription for the caller. Pushes are only
+/// queued for notifications created while the user has no gateway
+/// connection, and only once a push sender is configured.
+pub(crate) async fn create_push_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<PushSubscriptionRequest>,
+) -> Result<Json<PushSubscriptionResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let endpoint = validate_push_endpoint(&payload.endpoint).ok_or(AuthFailure::InvalidRequest)?;
+    if !push_subscription_keys_valid(&payload.keys.p256dh, &payload.keys.auth) {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let record = register_push_subscription(
+        &state,
+        auth.user_id,
+        endpoint,
+        payload.keys.p256dh.trim().to_owned(),
+        payload.keys.auth.trim().to_owned(),
+    )
+    .await?;
+    tracing::info!(
+        event = "push.subscription.register",
+        user_id = %auth.user_id,
+        subscription_id = %record.subscription_id
+    );
+    Ok(Json(PushSubscriptionResponse {
+        subscription_id: record.subscription_id,
+        endpoint: record.endpoint,
+        created_at_unix: record.created_at_unix,
+    }))
+}
+
+pub(crate) async fn remove_push_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<PushSubscriptionPath>,
+) -> Result<StatusCode, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let subscription_id = Ulid::from_string(&path.subscription_id)
+        .map_err(|_| AuthFailure::InvalidRequest)?
+        .to_string();
+    if !delete_push_subscription(&state, auth.user_id, &subscription_id).await? {
+        return Err(AuthFailure::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
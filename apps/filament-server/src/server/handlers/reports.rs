@@ -0,0 +1,310 @@
+use axum::{
+    extract::{connect_info::ConnectInfo, Extension, Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use filament_core::{Permission, UserId};
+use sqlx::{PgPool, Row};
+use std::net::SocketAddr;
+use ulid::Ulid;
+
+use crate::server::{
+    auth::{authenticate, enforce_report_rate_limit, extract_client_ip, now_unix},
+    core::{
+        AppState, ReportRecord, DEFAULT_REPORT_LIST_LIMIT, MAX_REPORT_LIST_LIMIT,
+        MAX_REPORT_REASON_CHARS,
+    },
+    domain::{
+        check_channel_permission, enforce_guild_ip_ban_for_request, guild_permission_snapshot,
+        member_role_in_guild,
+    },
+    errors::AuthFailure,
+    realtime::hydrate_messages_by_id,
+    types::{
+        GuildPath, MessagePath, ReportListQuery, ReportListResponse, ReportMessageRequest,
+        ReportResponse, ReportUserRequest, UserPath,
+    },
+};
+
+fn parse_report_reason(raw: &str) -> Result<String, AuthFailure> {
+    let reason = raw.trim();
+    if reason.is_empty() || reason.chars().count() > MAX_REPORT_REASON_CHARS {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(reason.to_owned())
+}
+
+fn report_response_from_record(record: ReportRecord) -> ReportResponse {
+    ReportResponse {
+        report_id: record.report_id,
+        guild_id: record.guild_id,
+        reporter_user_id: record.reporter_user_id.to_string(),
+        target_user_id: record.target_user_id.to_string(),
+        channel_id: record.channel_id,
+        message_id: record.message_id,
+        message_content: record.message_content,
+        reason: record.reason,
+        created_at_unix: record.created_at_unix,
+    }
+}
+
+async fn store_report(state: &AppState, record: ReportRecord) -> Result<ReportRecord, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        sqlx::query(
+            "INSERT INTO reports (report_id, guild_id, reporter_user_id, target_user_id, channel_id, message_id, message_content, reason, created_at_unix)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(&record.report_id)
+        .bind(&record.guild_id)
+        .bind(record.reporter_user_id.to_string())
+        .bind(record.target_user_id.to_string())
+        .bind(record.channel_id.as_deref())
+        .bind(record.message_id.as_deref())
+        .bind(record.message_content.as_deref())
+        .bind(&record.reason)
+        .bind(record.created_at_unix)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+    } else {
+        state.reports.write().await.push(record.clone());
+    }
+    tracing::info!(
+        event = "reports.create",
+        report_id = %record.report_id,
+        guild_id = %record.guild_id,
+        reporter_user_id = %record.reporter_user_id,
+        target_user_id = %record.target_user_id,
+        message_id = record.message_id.as_deref().unwrap_or("")
+    );
+    Ok(record)
+}
+
+/// Reports a message to the guild's moderators. The reporter must be able to
+/// read the channel, and the message content is kept as it read at report time.
+pub(crate) async fn report_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<MessagePath>,
+    Json(payload): Json<ReportMessageRequest>,
+) -> Result<Json<ReportResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "reports.message",
+    )
+    .await?;
+    check_channel_permission(
+        &state,
+        auth.user_id,
+        &path.guild_id,
+        &path.channel_id,
+        Permission::CreateMessage,
+    )
+    .await?;
+    let reason = parse_report_reason(&payload.reason)?;
+
+    let message = hydrate_messages_by_id(
+        &state,
+        &path.guild_id,
+        Some(&path.channel_id),
+        std::slice::from_ref(&path.message_id),
+    )
+    .await?
+    .into_iter()
+    .next()
+    .ok_or(AuthFailure::NotFound)?;
+    let target_user_id = UserId::try_from(message.author_id).map_err(|_| AuthFailure::Internal)?;
+    if target_user_id == auth.user_id {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    enforce_report_rate_limit(&state, client_ip, auth.user_id).await?;
+
+    let record = store_report(
+        &state,
+        ReportRecord {
+            report_id: Ulid::new().to_string(),
+            guild_id: path.guild_id,
+            reporter_user_id: auth.user_id,
+            target_user_id,
+            channel_id: Some(path.channel_id),
+            message_id: Some(message.message_id),
+            message_content: Some(message.content),
+            reason,
+            created_at_unix: now_unix(),
+        },
+    )
+    .await?;
+    Ok(Json(report_response_from_record(record)))
+}
+
+/// Reports a fellow guild member to that guild's moderators.
+pub(crate) async fn report_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<UserPath>,
+    Json(payload): Json<ReportUserRequest>,
+) -> Result<Json<ReportResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    let target_user_id = UserId::try_from(path.user_id).map_err(|_| AuthFailure::InvalidRequest)?;
+    if target_user_id == auth.user_id {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &payload.guild_id,
+        auth.user_id,
+        client_ip,
+        "reports.user",
+    )
+    .await?;
+    guild_permission_snapshot(&state, auth.user_id, &payload.guild_id).await?;
+    let reason = parse_report_reason(&payload.reason)?;
+    member_role_in_guild(&state, target_user_id, &payload.guild_id).await?;
+    enforce_report_rate_limit(&state, client_ip, auth.user_id).await?;
+
+    let record = store_report(
+        &state,
+        ReportRecord {
+            report_id: Ulid::new().to_string(),
+            guild_id: payload.guild_id,
+            reporter_user_id: auth.user_id,
+            target_user_id,
+            channel_id: None,
+            message_id: None,
+            message_content: None,
+            reason,
+            created_at_unix: now_unix(),
+        },
+    )
+    .await?;
+    Ok(Json(report_response_from_record(record)))
+}
+
+async fn list_guild_reports_db(
+    pool: &PgPool,
+    guild_id: &str,
+    before: Option<&str>,
+    limit: usize,
+) -> Result<Vec<ReportRecord>, AuthFailure> {
+    let rows = sqlx::query(
+        "SELECT report_id, reporter_user_id, target_user_id, channel_id, message_id, message_content, reason, created_at_unix
+         FROM reports
+         WHERE guild_id = $1 AND ($2::text IS NULL OR report_id < $2)
+         ORDER BY report_id DESC
+         LIMIT $3",
+    )
+    .bind(guild_id)
+    .bind(before)
+    .bind(i64::try_from(limit).map_err(|_| AuthFailure::Internal)?)
+    .fetch_all(pool)
+    .await
+    .map_err(|_| AuthFailure::Internal)?;
+
+    let mut records = Vec::with_capacity(rows.len());
+    for row in rows {
+        let reporter_user_id: String = row
+            .try_get("reporter_user_id")
+            .map_err(|_| AuthFailure::Internal)?;
+        let target_user_id: String = row
+            .try_get("target_user_id")
+            .map_err(|_| AuthFailure::Internal)?;
+        records.push(ReportRecord {
+            report_id: row
+                .try_get("report_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            guild_id: guild_id.to_owned(),
+            reporter_user_id: UserId::try_from(reporter_user_id)
+                .map_err(|_| AuthFailure::Internal)?,
+            target_user_id: UserId::try_from(target_user_id).map_err(|_| AuthFailure::Internal)?,
+            channel_id: row
+                .try_get("channel_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            message_id: row
+                .try_get("message_id")
+                .map_err(|_| AuthFailure::Internal)?,
+            message_content: row
+                .try_get("message_content")
+                .map_err(|_| AuthFailure::Internal)?,
+            reason: row.try_get("reason").map_err(|_| AuthFailure::Internal)?,
+            created_at_unix: row
+                .try_get("created_at_unix")
+                .map_err(|_| AuthFailure::Internal)?,
+        });
+    }
+    Ok(records)
+}
+
+/// Lists a guild's reports newest first. Requires `view_audit_log`.
+pub(crate) async fn list_guild_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<GuildPath>,
+    Query(query): Query<ReportListQuery>,
+) -> Result<Json<ReportListResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    let (_, permissions) = guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !permissions.contains(Permission::ViewAuditLog) {
+        return Err(AuthFailure::Forbidden);
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_REPORT_LIST_LIMIT);
+    if limit == 0 || limit > MAX_REPORT_LIST_LIMIT {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let before = query
+        .before
+        .map(|value| {
+            Ulid::from_string(&value)
+                .map(|ulid| ulid.to_string())
+                .map_err(|_| AuthFailure::InvalidRequest)
+        })
+        .transpose()?;
+
+    let mut records = if let Some(pool) = &state.db_pool {
+        list_guild_reports_db(pool, &path.guild_id, before.as_deref(), limit + 1).await?
+    } else {
+        let reports = state.reports.read().await;
+        let mut records: Vec<ReportRecord> = reports
+            .iter()
+            .filter(|report| {
+                report.guild_id == path.guild_id
+                    && before
+                        .as_deref()
+                        .is_none_or(|before| report.report_id.as_str() < before)
+            })
+            .cloned()
+            .collect();
+        records.sort_by(|left, right| right.report_id.cmp(&left.report_id));
+        records.truncate(limit + 1);
+        records
+    };
+
+    let next_before = if records.len() > limit {
+        records.truncate(limit);
+        records.last().map(|record| record.report_id.clone())
+    } else {
+        None
+    };
+    Ok(Json(ReportListResponse {
+        reports: records
+            .into_iter()
+            .map(report_response_from_record)
+            .collect(),
+        next_before,
+    }))
+}
@@ -4,19 +4,25 @@ use axum::{
     Json,
 };
 use filament_core::Permission;
-use std::net::SocketAddr;
+use std::{collections::HashSet, net::SocketAddr};
 
 use crate::server::{
-    auth::{authenticate, extract_client_ip},
-    core::{AppState, SearchOperation, DEFAULT_SEARCH_RESULT_LIMIT, MAX_SEARCH_RECONCILE_DOCS},
-    domain::{enforce_guild_ip_ban_for_request, guild_permission_snapshot},
+    auth::{authenticate, enforce_search_rate_limit, extract_client_ip},
+    core::{AppState, SearchOperation, DEFAULT_SEARCH_RESULT_LIMIT},
+    domain::{enforce_guild_ip_ban_for_request, guild_permission_snapshot, is_server_owner},
     errors::AuthFailure,
+    handlers::admin::authorize_admin,
     realtime::{
-        collect_all_indexed_messages, enqueue_search_operation, ensure_search_bootstrapped,
-        hydrate_messages_by_id, plan_search_reconciliation, run_search_query,
+        attach_author_usernames, collect_all_indexed_messages, enqueue_search_operation,
+        ensure_search_bootstrapped, hydrate_messages_by_id, mark_search_guilds_bootstrapped,
+        reconcile_guild_search_index, reindex_guild_message, run_search_query,
+        search_facet_channel_ids, search_query_timeout_for_guild, search_service,
         validate_search_query,
     },
-    types::{GuildPath, SearchQuery, SearchReconcileResponse, SearchResponse},
+    types::{
+        GuildPath, MessagePath, SearchQuery, SearchReconcileResponse, SearchReindexResponse,
+        SearchResponse, SearchTimeoutResponse, UpdateSearchTimeoutRequest,
+    },
 };
 
 #[allow(clippy::too_many_lines)]
@@ -33,6 +39,7 @@ pub(crate) async fn search_messages(
         connect_info.as_ref().map(|value| value.0 .0.ip()),
     );
     let auth = authenticate(&state, &headers).await?;
+    search_service(&state)?;
     enforce_guild_ip_ban_for_request(
         &state,
         &path.guild_id,
@@ -46,24 +53,43 @@ pub(crate) async fn search_messages(
         return Err(AuthFailure::Forbidden);
     }
 
+    enforce_search_rate_limit(&state, client_ip, auth.user_id, &path.guild_id).await?;
     validate_search_query(&state, &query)?;
-    ensure_search_bootstrapped(&state).await?;
+    ensure_search_bootstrapped(&state, &path.guild_id).await?;
     let limit = query.limit.unwrap_or(DEFAULT_SEARCH_RESULT_LIMIT);
     let channel_id = query.channel_id.clone();
-    let message_ids = run_search_query(
+    let facet_channel_ids = if query.facets {
+        Some(search_facet_channel_ids(&state, &path.guild_id, channel_id.as_deref()).await?)
+    } else {
+        None
+    };
+    let (message_ids, facets) = run_search_query(
         &state,
         &path.guild_id,
         channel_id.as_deref(),
         &query.q,
         limit,
+        query.sort,
+        facet_channel_ids,
     )
     .await?;
-    let messages =
+    if !query.hydrate.unwrap_or(true) {
+        return Ok(Json(SearchResponse {
+            message_ids,
+            messages: None,
+            facets,
+        }));
+    }
+    let mut messages =
         hydrate_messages_by_id(&state, &path.guild_id, channel_id.as_deref(), &message_ids).await?;
+    if query.include_author_usernames {
+        attach_author_usernames(&state, &mut messages).await?;
+    }
 
     Ok(Json(SearchResponse {
         message_ids,
-        messages,
+        messages: Some(messages),
+        facets,
     }))
 }
 
@@ -79,6 +105,7 @@ pub(crate) async fn rebuild_search_index(
         connect_info.as_ref().map(|value| value.0 .0.ip()),
     );
     let auth = authenticate(&state, &headers).await?;
+    search_service(&state)?;
     enforce_guild_ip_ban_for_request(
         &state,
         &path.guild_id,
@@ -93,8 +120,26 @@ pub(crate) async fn rebuild_search_index(
     }
 
     let docs = collect_all_indexed_messages(&state).await?;
+    let guild_ids: HashSet<String> = docs.iter().map(|doc| doc.guild_id.clone()).collect();
+    enqueue_search_operation(&state, SearchOperation::Rebuild { docs }, true).await?;
+    mark_search_guilds_bootstrapped(&state, guild_ids).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Rebuilds the index for every guild at once. Gated by the admin API secret
+/// like the other `/admin/*` routes since it walks every stored message.
+pub(crate) async fn rebuild_all_search_indexes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AuthFailure> {
+    authorize_admin(&state, &headers)?;
+    search_service(&state)?;
+
+    let docs = collect_all_indexed_messages(&state).await?;
+    tracing::info!(event = "search.rebuild.all", docs = docs.len());
+    let guild_ids: HashSet<String> = docs.iter().map(|doc| doc.guild_id.clone()).collect();
     enqueue_search_operation(&state, SearchOperation::Rebuild { docs }, true).await?;
-    state.search_bootstrapped.set(()).ok();
+    mark_search_guilds_bootstrapped(&state, guild_ids).await;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -110,6 +155,7 @@ pub(crate) async fn reconcile_search_index(
         connect_info.as_ref().map(|value| value.0 .0.ip()),
     );
     let auth = authenticate(&state, &headers).await?;
+    search_service(&state)?;
     enforce_guild_ip_ban_for_request(
         &state,
         &path.guild_id,
@@ -123,22 +169,106 @@ pub(crate) async fn reconcile_search_index(
         return Err(AuthFailure::Forbidden);
     }
 
-    ensure_search_bootstrapped(&state).await?;
-    let (upserts, delete_message_ids) =
-        plan_search_reconciliation(&state, &path.guild_id, MAX_SEARCH_RECONCILE_DOCS).await?;
-    let upserted = upserts.len();
-    let deleted = delete_message_ids.len();
-    if upserted > 0 || deleted > 0 {
-        enqueue_search_operation(
-            &state,
-            SearchOperation::Reconcile {
-                upserts,
-                delete_message_ids,
-            },
-            true,
-        )
-        .await?;
-    }
+    // A full reconcile is exactly the per-guild bootstrap, so run it once.
+    let (upserted, deleted) = reconcile_guild_search_index(
+        &state,
+        &path.guild_id,
+        state.runtime.search_reconcile_batch_docs,
+    )
+    .await?;
+    mark_search_guilds_bootstrapped(&state, [path.guild_id.clone()]).await;
 
     Ok(Json(SearchReconcileResponse { upserted, deleted }))
 }
+
+pub(crate) async fn reindex_search_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Path(path): Path<MessagePath>,
+) -> Result<Json<SearchReindexResponse>, AuthFailure> {
+    let client_ip = extract_client_ip(
+        &state,
+        &headers,
+        connect_info.as_ref().map(|value| value.0 .0.ip()),
+    );
+    let auth = authenticate(&state, &headers).await?;
+    search_service(&state)?;
+    enforce_guild_ip_ban_for_request(
+        &state,
+        &path.guild_id,
+        auth.user_id,
+        client_ip,
+        "search.reindex_message",
+    )
+    .await?;
+    let (_, permissions) = guild_permission_snapshot(&state, auth.user_id, &path.guild_id).await?;
+    if !permissions.contains(Permission::ManageWorkspaceRoles) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    ensure_search_bootstrapped(&state, &path.guild_id).await?;
+    let indexed =
+        reindex_guild_message(&state, &path.guild_id, &path.channel_id, &path.message_id).await?;
+    Ok(Json(SearchReindexResponse { indexed }))
+}
+
+/// Sets or clears a guild's search query timeout override. Reserved for the
+/// configured server owner; overrides must fall within
+/// `1..=search_query_timeout_max`.
+pub(crate) async fn update_guild_search_timeout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<GuildPath>,
+    Json(payload): Json<UpdateSearchTimeoutRequest>,
+) -> Result<Json<SearchTimeoutResponse>, AuthFailure> {
+    let auth = authenticate(&state, &headers).await?;
+    search_service(&state)?;
+    if !is_server_owner(&state, auth.user_id) {
+        return Err(AuthFailure::Forbidden);
+    }
+    let max_millis =
+        u64::try_from(state.runtime.search_query_timeout_max.as_millis()).unwrap_or(u64::MAX);
+    if payload
+        .timeout_ms
+        .is_some_and(|millis| millis == 0 || millis > max_millis)
+    {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    if let Some(pool) = &state.db_pool {
+        let stored = payload
+            .timeout_ms
+            .map(i64::try_from)
+            .transpose()
+            .map_err(|_| AuthFailure::InvalidRequest)?;
+        let updated =
+            sqlx::query("UPDATE guilds SET search_query_timeout_ms = $2 WHERE guild_id = $1")
+                .bind(&path.guild_id)
+                .bind(stored)
+                .execute(pool)
+                .await
+                .map_err(|_| AuthFailure::Internal)?;
+        if updated.rows_affected() == 0 {
+            return Err(AuthFailure::NotFound);
+        }
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let guild = guilds
+            .get_mut(&path.guild_id)
+            .ok_or(AuthFailure::NotFound)?;
+        guild.search_query_timeout_ms = payload.timeout_ms;
+    }
+
+    let effective = search_query_timeout_for_guild(&state, &path.guild_id).await?;
+    tracing::info!(
+        event = "search.timeout.update",
+        user_id = %auth.user_id,
+        guild_id = %path.guild_id,
+        timeout_ms = ?payload.timeout_ms
+    );
+    Ok(Json(SearchTimeoutResponse {
+        timeout_ms: payload.timeout_ms,
+        effective_timeout_ms: u64::try_from(effective.as_millis()).unwrap_or(u64::MAX),
+    }))
+}
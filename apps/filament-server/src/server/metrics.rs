@@ -1,4 +1,4 @@
-use std::{collections::HashMap, fmt::Write as _};
+use std::{collections::HashMap, fmt::Write as _, sync::atomic::Ordering};
 
 use super::core::{MetricsState, METRICS_STATE};
 
@@ -43,6 +43,34 @@ pub(crate) fn render_metrics() -> String {
         .voice_sync_repairs
         .lock()
         .map_or_else(|_| HashMap::new(), |guard| guard.clone());
+    let push_deliveries = metrics_state()
+        .push_deliveries
+        .lock()
+        .map_or_else(|_| HashMap::new(), |guard| guard.clone());
+    let channel_retention_pruned = metrics_state()
+        .channel_retention_pruned
+        .lock()
+        .map_or_else(|_| HashMap::new(), |guard| guard.clone());
+    let search_worker_restarts = metrics_state()
+        .search_worker_restarts
+        .lock()
+        .map_or_else(|_| HashMap::new(), |guard| guard.clone());
+    let search_index_ops_dropped = metrics_state()
+        .search_index_ops_dropped
+        .lock()
+        .map_or_else(|_| HashMap::new(), |guard| guard.clone());
+    let messages_created = metrics_state().messages_created.load(Ordering::Relaxed);
+    let audit_log_entries_written = metrics_state()
+        .audit_log_entries_written
+        .lock()
+        .map_or_else(|_| HashMap::new(), |guard| guard.clone());
+    let audit_log_entries_pruned = metrics_state()
+        .audit_log_entries_pruned
+        .lock()
+        .map_or_else(|_| HashMap::new(), |guard| guard.clone());
+    let gateway_outbound_buffered_bytes = metrics_state()
+        .gateway_outbound_buffered_bytes
+        .load(Ordering::Relaxed);
 
     let mut output = String::new();
     output
@@ -161,6 +189,97 @@ pub(crate) fn render_metrics() -> String {
         );
     }
 
+    output.push_str(
+        "# HELP filament_push_deliveries_total Count of push delivery attempts by outcome\n",
+    );
+    output.push_str("# TYPE filament_push_deliveries_total counter\n");
+    let mut push_entries: Vec<_> = push_deliveries.into_iter().collect();
+    push_entries.sort_by_key(|(outcome, _)| *outcome);
+    for (outcome, value) in push_entries {
+        let _ = writeln!(
+            output,
+            "filament_push_deliveries_total{{outcome=\"{outcome}\"}} {value}"
+        );
+    }
+
+    output.push_str(
+        "# HELP filament_channel_retention_pruned_total Count of records removed by channel retention pruning by kind\n",
+    );
+    output.push_str("# TYPE filament_channel_retention_pruned_total counter\n");
+    let mut pruned_entries: Vec<_> = channel_retention_pruned.into_iter().collect();
+    pruned_entries.sort_by_key(|(kind, _)| *kind);
+    for (kind, value) in pruned_entries {
+        let _ = writeln!(
+            output,
+            "filament_channel_retention_pruned_total{{kind=\"{kind}\"}} {value}"
+        );
+    }
+
+    output.push_str(
+        "# HELP filament_search_worker_restarts_total Count of search index worker restarts by reason\n",
+    );
+    output.push_str("# TYPE filament_search_worker_restarts_total counter\n");
+    let mut restart_entries: Vec<_> = search_worker_restarts.into_iter().collect();
+    restart_entries.sort_by_key(|(reason, _)| *reason);
+    for (reason, value) in restart_entries {
+        let _ = writeln!(
+            output,
+            "filament_search_worker_restarts_total{{reason=\"{reason}\"}} {value}"
+        );
+    }
+
+    output.push_str(
+        "# HELP filament_search_index_ops_dropped_total Count of queued search index updates dropped by reason\n",
+    );
+    output.push_str("# TYPE filament_search_index_ops_dropped_total counter\n");
+    let mut dropped_entries: Vec<_> = search_index_ops_dropped.into_iter().collect();
+    dropped_entries.sort_by_key(|(reason, _)| *reason);
+    for (reason, value) in dropped_entries {
+        let _ = writeln!(
+            output,
+            "filament_search_index_ops_dropped_total{{reason=\"{reason}\"}} {value}"
+        );
+    }
+
+    output.push_str("# HELP filament_messages_created_total Count of messages created\n");
+    output.push_str("# TYPE filament_messages_created_total counter\n");
+    let _ = writeln!(output, "filament_messages_created_total {messages_created}");
+
+    output.push_str(
+        "# HELP filament_audit_log_entries_written_total Count of audit log entries written by action\n",
+    );
+    output.push_str("# TYPE filament_audit_log_entries_written_total counter\n");
+    let mut audit_written_entries: Vec<_> = audit_log_entries_written.into_iter().collect();
+    audit_written_entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+    for (action, value) in audit_written_entries {
+        let _ = writeln!(
+            output,
+            "filament_audit_log_entries_written_total{{action=\"{action}\"}} {value}"
+        );
+    }
+
+    output.push_str(
+        "# HELP filament_audit_log_entries_pruned_total Count of audit log entries removed by reason\n",
+    );
+    output.push_str("# TYPE filament_audit_log_entries_pruned_total counter\n");
+    let mut audit_pruned_entries: Vec<_> = audit_log_entries_pruned.into_iter().collect();
+    audit_pruned_entries.sort_by_key(|(reason, _)| *reason);
+    for (reason, value) in audit_pruned_entries {
+        let _ = writeln!(
+            output,
+            "filament_audit_log_entries_pruned_total{{reason=\"{reason}\"}} {value}"
+        );
+    }
+
+    output.push_str(
+        "# HELP filament_gateway_outbound_buffered_bytes Payload bytes waiting in gateway outbound queues\n",
+    );
+    output.push_str("# TYPE filament_gateway_outbound_buffered_bytes gauge\n");
+    let _ = writeln!(
+        output,
+        "filament_gateway_outbound_buffered_bytes {gateway_outbound_buffered_bytes}"
+    );
+
     output
 }
 
@@ -240,6 +359,77 @@ pub(crate) fn record_voice_sync_repair(reason: &'static str) {
     }
 }
 
+pub(crate) fn record_push_delivery(outcome: &'static str) {
+    if let Ok(mut counters) = metrics_state().push_deliveries.lock() {
+        let entry = counters.entry(outcome).or_insert(0);
+        *entry += 1;
+    }
+}
+
+pub(crate) fn record_channel_retention_pruned(kind: &'static str, count: u64) {
+    if count == 0 {
+        return;
+    }
+    if let Ok(mut counters) = metrics_state().channel_retention_pruned.lock() {
+        let entry = counters.entry(kind).or_insert(0);
+        *entry += count;
+    }
+}
+
+pub(crate) fn record_search_worker_restart(reason: &'static str) {
+    if let Ok(mut counters) = metrics_state().search_worker_restarts.lock() {
+        let entry = counters.entry(reason).or_insert(0);
+        *entry += 1;
+    }
+}
+
+pub(crate) fn record_search_index_op_dropped(reason: &'static str) {
+    if let Ok(mut counters) = metrics_state().search_index_ops_dropped.lock() {
+        let entry = counters.entry(reason).or_insert(0);
+        *entry += 1;
+    }
+}
+
+/// Unlabelled on purpose: `/metrics` is unauthenticated, so per-guild and
+/// per-user rates stay behind the admin API.
+pub(crate) fn record_message_created() {
+    metrics_state()
+        .messages_created
+        .fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_audit_log_written(action: &str) {
+    if let Ok(mut counters) = metrics_state().audit_log_entries_written.lock() {
+        if let Some(entry) = counters.get_mut(action) {
+            *entry += 1;
+        } else {
+            counters.insert(action.to_owned(), 1);
+        }
+    }
+}
+
+pub(crate) fn record_audit_log_pruned(reason: &'static str, count: u64) {
+    if count == 0 {
+        return;
+    }
+    if let Ok(mut counters) = metrics_state().audit_log_entries_pruned.lock() {
+        let entry = counters.entry(reason).or_insert(0);
+        *entry += count;
+    }
+}
+
+pub(crate) fn record_gateway_outbound_bytes_buffered(bytes: usize) {
+    metrics_state()
+        .gateway_outbound_buffered_bytes
+        .fetch_add(bytes, Ordering::Relaxed);
+}
+
+pub(crate) fn record_gateway_outbound_bytes_released(bytes: usize) {
+    metrics_state()
+        .gateway_outbound_buffered_bytes
+        .fetch_sub(bytes, Ordering::Relaxed);
+}
+
 #[cfg(test)]
 mod tests {
     use uuid::Uuid;
@@ -15,6 +15,6 @@ pub(crate) mod router;
 mod tests;
 pub(crate) mod types;
 
-pub use core::{AppConfig, MAX_LIVEKIT_TOKEN_TTL_SECS};
+pub use core::{AppConfig, MessageContentPolicy, MAX_LIVEKIT_TOKEN_TTL_SECS};
 pub use errors::init_tracing;
 pub use router::{build_router, build_router_with_db_bootstrap};
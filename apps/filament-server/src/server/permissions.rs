@@ -10,8 +10,9 @@ pub(crate) const DEFAULT_ROLE_MEMBER: &str = "member";
 pub(crate) const MAX_GUILD_ROLES: usize = 64;
 pub(crate) const MAX_MEMBER_ROLE_ASSIGNMENTS: usize = 16;
 pub(crate) const MAX_ROLE_NAME_CHARS: usize = 32;
+pub(crate) const MAX_CHANNEL_ACCESS_MEMBER_ENTRIES: usize = 100;
 
-const KNOWN_PERMISSIONS: [Permission; 12] = [
+const KNOWN_PERMISSIONS: [Permission; 13] = [
     Permission::ManageRoles,
     Permission::ManageMemberRoles,
     Permission::ManageWorkspaceRoles,
@@ -24,6 +25,7 @@ const KNOWN_PERMISSIONS: [Permission; 12] = [
     Permission::PublishVideo,
     Permission::PublishScreenShare,
     Permission::SubscribeStreams,
+    Permission::MentionEveryone,
 ];
 
 pub(crate) fn known_permission_mask() -> u64 {
@@ -62,6 +64,7 @@ pub(crate) fn default_moderator_permissions() -> PermissionSet {
     permissions.insert(Permission::PublishVideo);
     permissions.insert(Permission::PublishScreenShare);
     permissions.insert(Permission::SubscribeStreams);
+    permissions.insert(Permission::MentionEveryone);
     permissions
 }
 
@@ -100,6 +103,7 @@ fn permission_mask(permission: Permission) -> u64 {
         Permission::PublishVideo => 1 << 9,
         Permission::PublishScreenShare => 1 << 10,
         Permission::SubscribeStreams => 1 << 11,
+        Permission::MentionEveryone => 1 << 12,
     }
 }
 
@@ -125,10 +129,12 @@ mod tests {
         assert!(everyone.contains(Permission::CreateMessage));
         assert!(everyone.contains(Permission::SubscribeStreams));
         assert!(!everyone.contains(Permission::DeleteMessage));
+        assert!(!everyone.contains(Permission::MentionEveryone));
 
         let moderator = default_moderator_permissions();
         assert!(moderator.contains(Permission::ManageMemberRoles));
         assert!(moderator.contains(Permission::ManageIpBans));
+        assert!(moderator.contains(Permission::MentionEveryone));
         assert!(!moderator.contains(Permission::ManageWorkspaceRoles));
 
         let owner = all_permissions();
@@ -139,6 +145,7 @@ mod tests {
         assert!(member.contains(Permission::CreateMessage));
         assert!(member.contains(Permission::SubscribeStreams));
         assert!(!member.contains(Permission::DeleteMessage));
+        assert!(!member.contains(Permission::MentionEveryone));
     }
 
     #[test]
@@ -1,8 +1,13 @@
+mod close_code;
 mod connection_disconnect_followups;
 mod connection_runtime;
+mod guild_directory_search;
 mod hydration_runtime;
 pub mod livekit_sync;
+mod message_history;
+mod message_idempotency;
 mod message_record;
+mod outbound_queue;
 mod search_query_run;
 mod search_reconciliation_plan;
 mod search_runtime;
@@ -11,26 +16,22 @@ mod voice_sync_dispatch;
 use std::{
     collections::{HashSet, VecDeque},
     net::SocketAddr,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
     time::Duration,
 };
 
 use axum::{
     extract::{
         connect_info::ConnectInfo,
-        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        ws::{Message, WebSocket, WebSocketUpgrade},
         Extension, Query, State,
     },
-    http::HeaderMap,
+    http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderMap},
     response::IntoResponse,
 };
-use filament_core::Permission;
+use filament_core::{Permission, UserId};
 use filament_protocol::parse_envelope;
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{mpsc, oneshot, watch};
 use ulid::Ulid;
 use uuid::Uuid;
 
@@ -40,28 +41,46 @@ mod presence_subscribe;
 mod voice_registration;
 mod voice_registry;
 
+use close_code::gateway_close_frame;
 pub(crate) use connection_runtime::{
-    add_subscription, broadcast_channel_event, broadcast_guild_event, broadcast_user_event,
-    handle_presence_subscribe, handle_voice_subscribe, register_voice_participant_from_token,
-    remove_connection, remove_voice_participant_for_channel,
-    update_voice_participant_audio_state_for_channel,
+    add_subscription, broadcast_channel_event, broadcast_guild_event,
+    broadcast_guild_event_to_users, broadcast_user_event, connection_subscription_keys,
+    guild_listener_user_ids, handle_presence_subscribe, handle_voice_subscribe,
+    register_voice_participant_from_token, remove_connection, remove_subscription,
+    remove_voice_participant_for_channel, reserve_user_connection,
+    update_voice_participant_audio_state_for_channel, user_connection_count,
 };
+pub(crate) use guild_directory_search::{run_guild_directory_search, sync_guild_directory_entry};
 use ingress_command::{
     allow_gateway_ingress, classify_ingress_command_parse_error, decode_gateway_ingress_message,
-    execute_message_create_command, execute_subscribe_command, parse_gateway_ingress_command,
+    execute_history_request_command, execute_message_create_command, execute_set_filters_command,
+    execute_subscribe_command, parse_gateway_ingress_command, revalidate_connection_subscriptions,
     GatewayAttachmentIds, GatewayIngressCommand, GatewayIngressMessageDecode,
     GatewayMessageContent, IngressCommandParseClassification,
 };
+pub(crate) use message_history::load_message_history;
+pub(crate) use message_idempotency::{
+    claim_message_idempotency, complete_message_idempotency, parse_idempotency_key,
+    release_message_idempotency, IdempotencyClaim,
+};
+pub(crate) use message_record::history_cursor_end;
 use message_record::{
     append_message_record, bind_message_attachments_in_memory, build_db_created_message_response,
     build_in_memory_message_record, build_message_response_from_record,
+    ensure_channel_message_capacity,
 };
-pub(crate) use search_query_run::run_search_query;
-pub(crate) use search_reconciliation_plan::plan_search_reconciliation;
+#[cfg(test)]
+pub(crate) use outbound_queue::unbudgeted_outbound_queue;
+pub(crate) use outbound_queue::{outbound_queue, OutboundBufferBudget, OutboundSender};
+pub(crate) use search_query_run::{
+    run_search_query, search_facet_channel_ids, search_query_timeout_for_guild,
+};
+pub(crate) use search_reconciliation_plan::{reconcile_guild_search_index, reindex_guild_message};
 pub(crate) use search_runtime::{
-    collect_all_indexed_messages, collect_indexed_messages_for_guild, enqueue_search_operation,
-    ensure_search_bootstrapped, hydrate_messages_by_id, indexed_message_from_response,
-    init_search_service, validate_search_query,
+    attach_author_usernames, collect_all_indexed_messages, collect_indexed_messages_page_for_guild,
+    enqueue_search_operation, ensure_search_bootstrapped, hydrate_messages_by_id,
+    indexed_message_from_response, init_search_service, mark_search_guilds_bootstrapped,
+    search_service, validate_search_query,
 };
 
 #[allow(dead_code)]
@@ -71,21 +90,26 @@ pub(crate) fn build_search_schema() -> (tantivy::schema::Schema, super::core::Se
 
 use super::{
     auth::{
-        authenticate_with_token, bearer_token, channel_key, extract_client_ip, now_unix,
-        validate_message_content, ClientIp,
+        apply_message_content_policy, authenticate_with_token, bearer_token, channel_key,
+        extract_client_ip, now_unix, track_message_created, validate_message_content, ClientIp,
+    },
+    core::{
+        AppState, AuthContext, ConnectionControl, ConnectionPresence, SearchOperation,
+        WELCOME_MESSAGE_CONTENT,
     },
-    core::{AppState, AuthContext, ConnectionControl, ConnectionPresence, SearchOperation},
     domain::{
-        attachments_for_message_in_memory, bind_message_attachments_db,
-        channel_permission_snapshot, fetch_attachments_for_message_db, parse_attachment_ids,
-        reaction_summaries_from_users,
+        attachments_for_message_in_memory, bind_message_attachments_db, channel_is_locked,
+        channel_kind, channel_permission_snapshot, channel_readers, encode_message_content,
+        enforce_guild_mute, fetch_attachments_for_message_db, guild_mention_scope,
+        parse_attachment_ids, reaction_summaries_from_users, record_mention_notifications,
+        spawn_link_preview_fetch, MentionScope,
     },
     errors::AuthFailure,
     gateway_events::{self},
     metrics::{
         record_gateway_event_dropped, record_gateway_event_emitted,
         record_gateway_event_parse_rejected, record_gateway_event_serialize_error,
-        record_gateway_event_unknown_received, record_ws_disconnect,
+        record_gateway_event_unknown_received, record_rate_limit_hit, record_ws_disconnect,
     },
     types::{GatewayAuthQuery, MessageResponse},
 };
@@ -108,7 +132,7 @@ fn prepare_message_body(
 ) -> Result<PreparedMessageBody, AuthFailure> {
     if content.is_empty() {
         if !has_attachments {
-            return Err(AuthFailure::InvalidRequest);
+            return Err(AuthFailure::ContentEmpty);
         }
         return Ok(PreparedMessageBody {
             content,
@@ -138,7 +162,7 @@ fn prepare_prevalidated_message_body(content: String) -> PreparedMessageBody {
 }
 
 fn try_enqueue_ready_event(
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
     payload: String,
     max_gateway_event_bytes: usize,
 ) -> ReadyEnqueueResult {
@@ -171,6 +195,29 @@ fn ready_drop_metric_reason(result: &ReadyEnqueueResult) -> Option<&'static str>
     }
 }
 
+/// Subprotocol the server selects when a client authenticates through
+/// `Sec-WebSocket-Protocol`. Browsers fail the handshake unless the server
+/// echoes one of the offered protocols, so clients offer it next to the token.
+pub(crate) const GATEWAY_WS_PROTOCOL: &str = "filament.gateway.v1";
+/// Prefix of the `Sec-WebSocket-Protocol` entry that carries the access token.
+pub(crate) const GATEWAY_WS_TOKEN_PROTOCOL_PREFIX: &str = "filament.access_token.";
+/// How long the send task gets to flush the close frame after the read loop
+/// ends the connection.
+const GATEWAY_CLOSE_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn subprotocol_access_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|entry| entry.trim().strip_prefix(GATEWAY_WS_TOKEN_PROTOCOL_PREFIX))
+        .filter(|token| !token.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+/// Authenticates from the subprotocol token, then the bearer header, then the
+/// legacy `access_token` query parameter, which leaks into proxy logs.
 pub(crate) async fn gateway_ws(
     State(state): State<AppState>,
     ws: WebSocketUpgrade,
@@ -178,20 +225,29 @@ pub(crate) async fn gateway_ws(
     headers: HeaderMap,
     connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
 ) -> Result<impl IntoResponse, AuthFailure> {
-    let token = query
-        .access_token
+    let token = subprotocol_access_token(&headers)
         .or_else(|| bearer_token(&headers).map(ToOwned::to_owned))
+        .or(query.access_token)
         .ok_or(AuthFailure::Unauthorized)?;
     let auth = authenticate_with_token(&state, &token).await?;
+    // Fast path so most over-cap clients get a plain 429 instead of a socket
+    // that closes right away; `handle_gateway_connection` enforces the cap.
+    if user_connection_count(&state, auth.user_id).await
+        >= state.runtime.gateway_max_connections_per_user
+    {
+        return Err(AuthFailure::TooManyGatewayConnections);
+    }
     let client_ip = extract_client_ip(
         &state,
         &headers,
         connect_info.as_ref().map(|value| value.0 .0.ip()),
     );
 
-    Ok(ws.on_upgrade(move |socket| async move {
-        handle_gateway_connection(state, socket, auth, client_ip).await;
-    }))
+    Ok(ws
+        .protocols([GATEWAY_WS_PROTOCOL])
+        .on_upgrade(move |socket| async move {
+            handle_gateway_connection(state, socket, auth, client_ip).await;
+        }))
 }
 
 #[allow(clippy::too_many_lines)]
@@ -203,10 +259,24 @@ pub(crate) async fn handle_gateway_connection(
 ) {
     let connection_id = Uuid::new_v4();
     let (mut sink, mut stream) = socket.split();
-    let slow_consumer_disconnect = Arc::new(AtomicBool::new(false));
+    if !reserve_user_connection(&state, auth.user_id, connection_id).await {
+        tracing::warn!(
+            event = "gateway.connection.rejected",
+            user_id = %auth.user_id,
+            reject_reason = "too_many_connections"
+        );
+        record_rate_limit_hit("gateway", "too_many_connections");
+        record_ws_disconnect("too_many_connections");
+        let _ = sink
+            .send(Message::Close(gateway_close_frame("too_many_connections")))
+            .await;
+        return;
+    }
 
-    let (outbound_tx, mut outbound_rx) =
-        mpsc::channel::<String>(state.runtime.gateway_outbound_queue);
+    let (outbound_tx, mut outbound_rx) = outbound_queue(
+        state.runtime.gateway_outbound_queue,
+        &state.gateway_outbound_budget,
+    );
     state
         .realtime_registry
         .connection_senders()
@@ -232,14 +302,6 @@ pub(crate) async fn handle_gateway_connection(
                 guild_ids: HashSet::new(),
             },
         );
-    state
-        .realtime_registry
-        .user_connections()
-        .write()
-        .await
-        .entry(auth.user_id)
-        .or_default()
-        .insert(connection_id);
 
     let ready_event = match gateway_events::try_ready(auth.user_id) {
         Ok(event) => event,
@@ -253,6 +315,9 @@ pub(crate) async fn handle_gateway_connection(
             record_gateway_event_serialize_error("connection", gateway_events::READY_EVENT);
             record_ws_disconnect("ready_serialize_error");
             remove_connection(&state, connection_id).await;
+            let _ = sink
+                .send(Message::Close(gateway_close_frame("ready_serialize_error")))
+                .await;
             return;
         }
     };
@@ -273,12 +338,20 @@ pub(crate) async fn handle_gateway_connection(
         );
         record_ws_disconnect(reason);
         remove_connection(&state, connection_id).await;
+        let _ = sink.send(Message::Close(gateway_close_frame(reason))).await;
         return;
     }
     record_gateway_event_emitted("connection", ready_event.event_type);
 
-    let slow_consumer_disconnect_send = Arc::clone(&slow_consumer_disconnect);
-    let send_task = tokio::spawn(async move {
+    // The connection's auth context is fixed at upgrade, so cap how long it
+    // can be used before the client must reconnect and re-authenticate.
+    let reauth_deadline =
+        tokio::time::Instant::now() + state.runtime.gateway_max_connection_lifetime;
+    // The read loop hands its disconnect reason over so the close frame goes
+    // out on the same sink as every other outbound message.
+    let (close_tx, mut close_rx) = oneshot::channel::<&'static str>();
+    // Resolves with the reason when the server closed the socket itself.
+    let mut send_task = tokio::spawn(async move {
         let mut ping_interval = tokio::time::interval(Duration::from_secs(30));
         ping_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
@@ -286,30 +359,41 @@ pub(crate) async fn handle_gateway_connection(
             tokio::select! {
                 _ = ping_interval.tick() => {
                     if sink.send(Message::Ping(vec![].into())).await.is_err() {
-                        break;
+                        return None;
                     }
                 }
+                () = tokio::time::sleep_until(reauth_deadline) => {
+                    let _ = sink
+                        .send(Message::Close(gateway_close_frame("reauth_required")))
+                        .await;
+                    return Some("reauth_required");
+                }
                 control_change = control_rx.changed() => {
-                    if control_change.is_ok() && *control_rx.borrow() == ConnectionControl::Close {
-                        slow_consumer_disconnect_send.store(true, Ordering::Relaxed);
-                        record_ws_disconnect("slow_consumer");
-                        let _ = sink
-                            .send(Message::Close(Some(CloseFrame {
-                                code: 1008,
-                                reason: "slow_consumer".into(),
-                            })))
-                            .await;
-                        break;
+                    if control_change.is_err() {
+                        continue;
                     }
+                    let reason = match *control_rx.borrow() {
+                        ConnectionControl::Open => continue,
+                        ConnectionControl::Close => "slow_consumer",
+                        ConnectionControl::AdminClose => "admin_closed",
+                    };
+                    let _ = sink.send(Message::Close(gateway_close_frame(reason))).await;
+                    return Some(reason);
+                }
+                reason = &mut close_rx => {
+                    if let Some(frame) = reason.ok().and_then(gateway_close_frame) {
+                        let _ = sink.send(Message::Close(Some(frame))).await;
+                    }
+                    return None;
                 }
                 maybe_payload = outbound_rx.recv() => {
                     match maybe_payload {
                         Some(payload) => {
                             if sink.send(Message::Text(payload.into())).await.is_err() {
-                                break;
+                                return None;
                             }
                         }
-                        None => break,
+                        None => return None,
                     }
                 }
             }
@@ -318,7 +402,30 @@ pub(crate) async fn handle_gateway_connection(
 
     let mut ingress = VecDeque::new();
     let mut disconnect_reason = "connection_closed";
-    while let Some(incoming) = stream.next().await {
+    let mut send_task_finished = false;
+    let revalidate_period = state.runtime.gateway_subscription_revalidate_interval;
+    let mut revalidate_interval = tokio::time::interval_at(
+        tokio::time::Instant::now() + revalidate_period,
+        revalidate_period,
+    );
+    revalidate_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        let incoming = tokio::select! {
+            incoming = stream.next() => incoming,
+            _ = revalidate_interval.tick() => {
+                revalidate_connection_subscriptions(&state, connection_id, auth.user_id, client_ip)
+                    .await;
+                continue;
+            }
+            server_close = &mut send_task => {
+                send_task_finished = true;
+                disconnect_reason = server_close.ok().flatten().unwrap_or("socket_error");
+                break;
+            }
+        };
+        let Some(incoming) = incoming else {
+            break;
+        };
         let Ok(message) = incoming else {
             disconnect_reason = "socket_error";
             break;
@@ -382,18 +489,47 @@ pub(crate) async fn handle_gateway_connection(
                 }
             }
             GatewayIngressCommand::MessageCreate(request) => {
-                if let Err(reason) =
-                    execute_message_create_command(&state, &auth, client_ip, request).await
+                if let Err(reason) = execute_message_create_command(
+                    &state,
+                    connection_id,
+                    &auth,
+                    client_ip,
+                    request,
+                    &outbound_tx,
+                )
+                .await
+                {
+                    disconnect_reason = reason;
+                    break;
+                }
+            }
+            GatewayIngressCommand::HistoryRequest(request) => {
+                if let Err(reason) = execute_history_request_command(
+                    &state,
+                    connection_id,
+                    auth.user_id,
+                    client_ip,
+                    request,
+                    &outbound_tx,
+                )
+                .await
                 {
                     disconnect_reason = reason;
                     break;
                 }
             }
+            GatewayIngressCommand::SetFilters(request) => {
+                execute_set_filters_command(&state, connection_id, request).await;
+            }
         }
     }
 
-    if !slow_consumer_disconnect.load(Ordering::Relaxed) {
-        record_ws_disconnect(disconnect_reason);
+    record_ws_disconnect(disconnect_reason);
+    if !send_task_finished
+        && close_tx.send(disconnect_reason).is_ok()
+        && gateway_close_frame(disconnect_reason).is_some()
+    {
+        let _ = tokio::time::timeout(GATEWAY_CLOSE_FLUSH_TIMEOUT, &mut send_task).await;
     }
     remove_connection(&state, connection_id).await;
     send_task.abort();
@@ -409,6 +545,7 @@ pub(crate) async fn create_message_internal(
     attachment_ids: Vec<String>,
 ) -> Result<MessageResponse, AuthFailure> {
     let attachment_ids = parse_attachment_ids(attachment_ids)?;
+    let content = apply_message_content_policy(&state.runtime, content)?;
     let prepared = prepare_message_body(content, !attachment_ids.is_empty())?;
     create_message_internal_prepared(
         state,
@@ -431,7 +568,8 @@ pub(crate) async fn create_message_internal_from_ingress_validated(
     attachment_ids: GatewayAttachmentIds,
 ) -> Result<MessageResponse, AuthFailure> {
     let attachment_ids = attachment_ids.into_vec();
-    let prepared = prepare_prevalidated_message_body(content.into_string());
+    let content = apply_message_content_policy(&state.runtime, content.into_string())?;
+    let prepared = prepare_prevalidated_message_body(content);
     create_message_internal_prepared(
         state,
         auth,
@@ -453,7 +591,9 @@ async fn emit_message_create_and_index(
     guild_id: &str,
     channel_id: &str,
     response: &MessageResponse,
+    mention_scope: Option<MentionScope>,
 ) -> Result<(), AuthFailure> {
+    track_message_created(state, guild_id, &response.author_id).await;
     if let Ok(event) = gateway_events::try_message_create(response) {
         broadcast_channel_event(state, &channel_key(guild_id, channel_id), &event).await;
     } else {
@@ -464,9 +604,73 @@ async fn emit_message_create_and_index(
             "dropped message_create outbound event because serialization failed"
         );
     }
-    enqueue_search_operation(state, message_upsert_operation(response), true).await
+    if let Some(scope) = mention_scope {
+        match gateway_events::try_message_mention(guild_id, channel_id, &response.message_id, scope)
+        {
+            Ok(event) => {
+                // Only members who can read the channel learn that it mentioned them.
+                let listeners = guild_listener_user_ids(state, guild_id).await;
+                match channel_readers(state, guild_id, channel_id, listeners).await {
+                    Ok(readers) => {
+                        broadcast_guild_event_to_users(state, guild_id, &readers, &event).await;
+                    }
+                    Err(error) => tracing::warn!(
+                        event = "gateway.message_mention.audience_failed",
+                        guild_id,
+                        channel_id,
+                        error = %error
+                    ),
+                }
+            }
+            Err(error) => {
+                record_gateway_event_serialize_error(
+                    "guild",
+                    gateway_events::MESSAGE_MENTION_EVENT,
+                );
+                tracing::warn!(
+                    event = "gateway.message_mention.serialize_failed",
+                    guild_id,
+                    channel_id,
+                    error = %error
+                );
+            }
+        }
+        // The message is already committed, so a failed inbox write is logged
+        // rather than failing the create.
+        let recorded = match UserId::try_from(response.author_id.clone()) {
+            Ok(author_id) => {
+                record_mention_notifications(
+                    state,
+                    guild_id,
+                    channel_id,
+                    &response.message_id,
+                    author_id,
+                    scope,
+                )
+                .await
+            }
+            Err(_) => Err(AuthFailure::Internal),
+        };
+        if let Err(error) = recorded {
+            tracing::error!(
+                event = "notifications.mention.record_failed",
+                guild_id,
+                channel_id,
+                message_id = %response.message_id,
+                error = %error
+            );
+        }
+    }
+    spawn_link_preview_fetch(state, &response.markdown_tokens);
+    enqueue_search_operation(
+        state,
+        message_upsert_operation(response),
+        state.runtime.search_sync_index_writes,
+    )
+    .await
 }
 
+#[allow(clippy::too_many_lines)]
 async fn create_message_internal_prepared(
     state: &AppState,
     auth: &AuthContext,
@@ -481,20 +685,46 @@ async fn create_message_internal_prepared(
     if !permissions.contains(Permission::CreateMessage) {
         return Err(AuthFailure::Forbidden);
     }
+    if !permissions.contains(Permission::DeleteMessage)
+        && !permissions.contains(Permission::ManageChannelOverrides)
+        && channel_is_locked(state, guild_id, channel_id).await?
+    {
+        return Err(AuthFailure::Forbidden);
+    }
+    if !channel_kind(state, guild_id, channel_id)
+        .await?
+        .holds_messages()
+    {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    enforce_guild_mute(state, guild_id, auth.user_id).await?;
+    // Authors without `mention_everyone` may still post the text; it just
+    // does not notify the guild.
+    let mention_scope = guild_mention_scope(&markdown_tokens)
+        .filter(|_| permissions.contains(Permission::MentionEveryone));
 
     if let Some(pool) = &state.db_pool {
         let message_id = Ulid::new().to_string();
         let created_at_unix = now_unix();
+        let stored = encode_message_content(
+            &content,
+            &message_id,
+            state.runtime.message_content_compression,
+            state.message_content_cipher.as_deref(),
+        )?;
         let mut tx = pool.begin().await.map_err(|_| AuthFailure::Internal)?;
         sqlx::query(
-            "INSERT INTO messages (message_id, guild_id, channel_id, author_id, content, created_at_unix)
-             VALUES ($1, $2, $3, $4, $5, $6)",
+            "INSERT INTO messages
+                (message_id, guild_id, channel_id, author_id, content, content_zstd, content_sealed, created_at_unix)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
         )
         .bind(&message_id)
         .bind(guild_id)
         .bind(channel_id)
         .bind(auth.user_id.to_string())
-        .bind(&content)
+        .bind(&stored.content)
+        .bind(&stored.content_zstd)
+        .bind(&stored.content_sealed)
         .bind(created_at_unix)
         .execute(&mut *tx)
         .await
@@ -513,6 +743,7 @@ async fn create_message_internal_prepared(
             guild_id,
             channel_id,
             auth.user_id,
+            state.runtime.max_message_attachment_total_bytes,
         )
         .await?;
         let attachments =
@@ -530,7 +761,8 @@ async fn create_message_internal_prepared(
             created_at_unix,
         );
 
-        emit_message_create_and_index(state, guild_id, channel_id, &response).await?;
+        emit_message_create_and_index(state, guild_id, channel_id, &response, mention_scope)
+            .await?;
         return Ok(response);
     }
 
@@ -544,21 +776,37 @@ async fn create_message_internal_prepared(
         attachment_ids.clone(),
         created_at_unix,
     );
-    if !attachment_ids.is_empty() {
-        let mut attachments = state.attachments.write().await;
-        bind_message_attachments_in_memory(
-            &mut attachments,
-            &attachment_ids,
-            &message_id,
+    {
+        // Holding the guilds lock across the capacity check, the binding and
+        // the append keeps a full channel from leaving attachments bound to a
+        // message that is never stored.
+        let mut guilds = state.membership_store.guilds().write().await;
+        if !attachment_ids.is_empty() {
+            ensure_channel_message_capacity(
+                &guilds,
+                guild_id,
+                channel_id,
+                state.runtime.in_memory_max_channel_messages,
+            )?;
+            let mut attachments = state.attachments.write().await;
+            bind_message_attachments_in_memory(
+                &mut attachments,
+                &attachment_ids,
+                &message_id,
+                guild_id,
+                channel_id,
+                auth.user_id,
+                state.runtime.max_message_attachment_total_bytes,
+            )?;
+        }
+        append_message_record(
+            &mut guilds,
             guild_id,
             channel_id,
-            auth.user_id,
+            record.clone(),
+            state.runtime.in_memory_max_channel_messages,
         )?;
     }
-    {
-        let mut guilds = state.membership_store.guilds().write().await;
-        append_message_record(&mut guilds, guild_id, channel_id, record.clone())?;
-    }
 
     let attachments = attachments_for_message_in_memory(state, &record.attachment_ids).await?;
     let response = build_message_response_from_record(
@@ -569,25 +817,162 @@ async fn create_message_internal_prepared(
         reaction_summaries_from_users(&record.reactions, None),
     );
 
-    emit_message_create_and_index(state, guild_id, channel_id, &response).await?;
+    emit_message_create_and_index(state, guild_id, channel_id, &response, mention_scope).await?;
 
     Ok(response)
 }
 
+/// Posts a system welcome message for `user_id` when the guild has welcome
+/// messages turned on. The join has already happened, so failures are logged
+/// instead of returned.
+pub(crate) async fn post_welcome_message(state: &AppState, guild_id: &str, user_id: UserId) {
+    if let Err(error) = try_post_welcome_message(state, guild_id, user_id).await {
+        tracing::warn!(
+            event = "guild.welcome_message.failed",
+            guild_id,
+            user_id = %user_id,
+            error = %error
+        );
+    }
+}
+
+async fn try_post_welcome_message(
+    state: &AppState,
+    guild_id: &str,
+    user_id: UserId,
+) -> Result<(), AuthFailure> {
+    let content = String::from(WELCOME_MESSAGE_CONTENT);
+    let markdown_tokens = filament_core::tokenize_markdown(&content);
+    let message_id = Ulid::new().to_string();
+    let created_at_unix = now_unix();
+
+    let (channel_id, response) = if let Some(pool) = &state.db_pool {
+        let channel_id = sqlx::query_scalar::<_, Option<String>>(
+            "SELECT system_channel_id FROM guilds WHERE guild_id = $1 AND welcome_messages",
+        )
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .flatten();
+        let Some(channel_id) = channel_id else {
+            return Ok(());
+        };
+        let stored = encode_message_content(
+            &content,
+            &message_id,
+            state.runtime.message_content_compression,
+            state.message_content_cipher.as_deref(),
+        )?;
+        sqlx::query(
+            "INSERT INTO messages
+                (message_id, guild_id, channel_id, author_id, content, content_zstd, content_sealed, created_at_unix, system)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, TRUE)",
+        )
+        .bind(&message_id)
+        .bind(guild_id)
+        .bind(&channel_id)
+        .bind(user_id.to_string())
+        .bind(&stored.content)
+        .bind(&stored.content_zstd)
+        .bind(&stored.content_sealed)
+        .bind(created_at_unix)
+        .execute(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let mut response = build_db_created_message_response(
+            message_id,
+            guild_id,
+            &channel_id,
+            user_id,
+            content,
+            markdown_tokens,
+            Vec::new(),
+            created_at_unix,
+        );
+        response.system = true;
+        (channel_id, response)
+    } else {
+        let mut guilds = state.membership_store.guilds().write().await;
+        let channel_id = guilds
+            .get(guild_id)
+            .filter(|guild| guild.welcome_messages)
+            .and_then(|guild| guild.system_channel_id.clone());
+        let Some(channel_id) = channel_id else {
+            return Ok(());
+        };
+        let mut record = build_in_memory_message_record(
+            message_id,
+            user_id,
+            content,
+            markdown_tokens,
+            Vec::new(),
+            created_at_unix,
+        );
+        record.system = true;
+        append_message_record(
+            &mut guilds,
+            guild_id,
+            &channel_id,
+            record.clone(),
+            state.runtime.in_memory_max_channel_messages,
+        )?;
+        drop(guilds);
+        let response = build_message_response_from_record(
+            &record,
+            guild_id,
+            &channel_id,
+            Vec::new(),
+            Vec::new(),
+        );
+        (channel_id, response)
+    };
+
+    emit_message_create_and_index(state, guild_id, &channel_id, &response, None).await
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::server::realtime::unbudgeted_outbound_queue;
     use filament_core::MarkdownToken;
-    use tokio::sync::mpsc;
+
+    use axum::http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderMap, HeaderValue};
 
     use super::{
         message_upsert_operation, ready_drop_metric_reason, ready_error_reason,
-        try_enqueue_ready_event, ReadyEnqueueResult,
+        subprotocol_access_token, try_enqueue_ready_event, ReadyEnqueueResult,
     };
     use crate::server::{core::SearchOperation, types::MessageResponse};
 
+    #[test]
+    fn subprotocol_access_token_reads_prefixed_entry() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static("filament.gateway.v1, filament.access_token.v4.local.abc"),
+        );
+
+        assert_eq!(
+            subprotocol_access_token(&headers).as_deref(),
+            Some("v4.local.abc")
+        );
+    }
+
+    #[test]
+    fn subprotocol_access_token_ignores_missing_or_empty_token() {
+        let mut headers = HeaderMap::new();
+        assert!(subprotocol_access_token(&headers).is_none());
+
+        headers.insert(
+            SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static("filament.gateway.v1, filament.access_token."),
+        );
+        assert!(subprotocol_access_token(&headers).is_none());
+    }
+
     #[test]
     fn ready_enqueue_returns_enqueued_when_sender_has_capacity() {
-        let (tx, _rx) = mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_ready_event(&tx, String::from("payload"), 1024);
 
@@ -596,7 +981,7 @@ mod tests {
 
     #[test]
     fn ready_enqueue_returns_full_when_sender_is_full() {
-        let (tx, _rx) = mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         tx.try_send(String::from("first"))
             .expect("first send should fill queue");
 
@@ -607,7 +992,7 @@ mod tests {
 
     #[test]
     fn ready_enqueue_returns_closed_when_sender_is_closed() {
-        let (tx, rx) = mpsc::channel::<String>(1);
+        let (tx, rx) = unbudgeted_outbound_queue(1);
         drop(rx);
 
         let result = try_enqueue_ready_event(&tx, String::from("payload"), 1024);
@@ -617,7 +1002,7 @@ mod tests {
 
     #[test]
     fn ready_enqueue_returns_oversized_when_payload_exceeds_limit() {
-        let (tx, _rx) = mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_ready_event(&tx, String::from("payload"), 3);
 
@@ -674,7 +1059,13 @@ mod tests {
             }],
             attachments: Vec::new(),
             reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
             created_at_unix: 42,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
         };
 
         let op = message_upsert_operation(&response);
@@ -695,7 +1086,7 @@ mod tests {
         let result = super::prepare_message_body(String::new(), false);
         assert!(matches!(
             result,
-            Err(crate::server::errors::AuthFailure::InvalidRequest)
+            Err(crate::server::errors::AuthFailure::ContentEmpty)
         ));
     }
 
@@ -724,7 +1115,7 @@ mod tests {
 
         assert!(matches!(
             result,
-            Err(crate::server::errors::AuthFailure::InvalidRequest)
+            Err(crate::server::errors::AuthFailure::ContentTooLong { limit: 2000 })
         ));
     }
 
@@ -0,0 +1,123 @@
+use axum::extract::ws::CloseFrame;
+
+/// Close codes the gateway sends with every server-initiated disconnect. The
+/// numeric values are part of the client contract and must not be reused; the
+/// close frame's reason carries the specific `disconnect_reason` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GatewayCloseCode {
+    /// The server failed to build or queue an event it owed the client.
+    InternalError,
+    ReauthRequired,
+    TooManyConnections,
+    SlowConsumer,
+    EventTooLarge,
+    IngressRateLimited,
+    InvalidEnvelope,
+    /// A known event type arrived with a payload that failed validation.
+    InvalidPayload,
+    UnknownEvent,
+    IpBanned,
+    MessageRejected,
+    AdminClosed,
+}
+
+impl GatewayCloseCode {
+    pub(crate) const fn code(self) -> u16 {
+        match self {
+            Self::InternalError => 1011,
+            Self::ReauthRequired => 4001,
+            Self::TooManyConnections => 4002,
+            Self::SlowConsumer => 4003,
+            Self::EventTooLarge => 4004,
+            Self::IngressRateLimited => 4005,
+            Self::InvalidEnvelope => 4006,
+            Self::InvalidPayload => 4007,
+            Self::UnknownEvent => 4008,
+            Self::IpBanned => 4009,
+            Self::MessageRejected => 4010,
+            Self::AdminClosed => 4011,
+        }
+    }
+
+    /// Maps a `disconnect_reason` to its close code. Returns `None` for
+    /// disconnects the client caused (`client_close`, `socket_error`, ...),
+    /// where there is no open socket left to send a close frame on.
+    pub(crate) fn for_disconnect_reason(reason: &str) -> Option<Self> {
+        let code = match reason {
+            "ready_serialize_error"
+            | "outbound_serialize_error"
+            | "outbound_queue_full"
+            | "outbound_queue_closed"
+            | "outbound_payload_too_large" => Self::InternalError,
+            "reauth_required" => Self::ReauthRequired,
+            "too_many_connections" => Self::TooManyConnections,
+            "slow_consumer" => Self::SlowConsumer,
+            "event_too_large" => Self::EventTooLarge,
+            "ingress_rate_limited" => Self::IngressRateLimited,
+            "invalid_envelope" => Self::InvalidEnvelope,
+            "invalid_subscribe_payload"
+            | "invalid_message_create_payload"
+            | "invalid_history_request_payload"
+            | "invalid_set_filters_payload" => Self::InvalidPayload,
+            "unknown_event" => Self::UnknownEvent,
+            "ip_banned" => Self::IpBanned,
+            "message_rejected" => Self::MessageRejected,
+            "admin_closed" => Self::AdminClosed,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+pub(crate) fn gateway_close_frame(reason: &'static str) -> Option<CloseFrame> {
+    GatewayCloseCode::for_disconnect_reason(reason).map(|code| CloseFrame {
+        code: code.code(),
+        reason: reason.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{gateway_close_frame, GatewayCloseCode};
+
+    #[test]
+    fn close_codes_are_unique() {
+        let codes = [
+            GatewayCloseCode::InternalError,
+            GatewayCloseCode::ReauthRequired,
+            GatewayCloseCode::TooManyConnections,
+            GatewayCloseCode::SlowConsumer,
+            GatewayCloseCode::EventTooLarge,
+            GatewayCloseCode::IngressRateLimited,
+            GatewayCloseCode::InvalidEnvelope,
+            GatewayCloseCode::InvalidPayload,
+            GatewayCloseCode::UnknownEvent,
+            GatewayCloseCode::IpBanned,
+            GatewayCloseCode::MessageRejected,
+            GatewayCloseCode::AdminClosed,
+        ];
+        let unique: HashSet<u16> = codes.iter().map(|code| code.code()).collect();
+        assert_eq!(unique.len(), codes.len());
+    }
+
+    #[test]
+    fn close_frame_keeps_specific_reason() {
+        let frame = gateway_close_frame("invalid_message_create_payload")
+            .expect("payload rejection should close with a frame");
+        assert_eq!(frame.code, 4007);
+        assert_eq!(frame.reason.as_str(), "invalid_message_create_payload");
+        assert_eq!(
+            gateway_close_frame("reauth_required").map(|frame| frame.code),
+            Some(4001)
+        );
+    }
+
+    #[test]
+    fn client_caused_disconnects_send_no_frame() {
+        for reason in ["client_close", "socket_error", "connection_closed"] {
+            assert!(gateway_close_frame(reason).is_none());
+        }
+    }
+}
@@ -1,18 +1,22 @@
-use std::{collections::HashMap, future::Future, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    time::Instant,
+};
 
 use filament_core::UserId;
-use tokio::sync::{mpsc, watch};
+use tokio::sync::watch;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
 use crate::server::{
-    auth::{now_unix, release_media_subscribe_leases_for_user},
+    auth::{channel_key, now_unix, release_media_subscribe_leases_for_user},
     core::{
         AppState, VoiceStreamKind, MAX_TRACKED_VOICE_CHANNELS,
         MAX_TRACKED_VOICE_PARTICIPANTS_PER_CHANNEL,
     },
     core::{
-        ConnectionControl, ConnectionPresence, GuildConnectionIndex, Subscriptions,
+        ChannelKey, ConnectionControl, ConnectionPresence, GuildConnectionIndex, Subscriptions,
         UserConnectionIndex,
     },
     errors::AuthFailure,
@@ -26,7 +30,7 @@ use super::{
     },
     fanout_dispatch::{
         connection_ids_for_user, dispatch_channel_payload, dispatch_guild_payload,
-        dispatch_user_payload,
+        dispatch_user_payload, SlowConsumers,
     },
     presence_subscribe::{
         apply_presence_subscribe, build_presence_subscribe_events, dispatch_presence_sync_event,
@@ -42,6 +46,7 @@ use super::{
         collect_voice_snapshots, dispatch_voice_sync_event, try_build_voice_subscribe_sync_event,
         voice_channel_key, voice_sync_reject_reason,
     },
+    OutboundSender,
 };
 
 const REALTIME_DISPATCH_TIMEOUT: Duration = Duration::from_millis(200);
@@ -60,7 +65,7 @@ fn signal_slow_connections_close(
 fn remove_connection_state(
     presence: &mut HashMap<Uuid, ConnectionPresence>,
     controls: &mut HashMap<Uuid, watch::Sender<ConnectionControl>>,
-    senders: &mut HashMap<Uuid, mpsc::Sender<String>>,
+    senders: &mut HashMap<Uuid, OutboundSender>,
     connection_id: Uuid,
 ) -> Option<ConnectionPresence> {
     let removed_presence = presence.remove(&connection_id);
@@ -69,6 +74,50 @@ fn remove_connection_state(
     removed_presence
 }
 
+/// Adds `connection_id` to the user's entry unless they already hold
+/// `max_connections`. Callers hold the index write lock across the check and
+/// the insert so concurrent upgrades cannot overshoot the cap.
+fn try_reserve_user_connection(
+    user_connections: &mut UserConnectionIndex,
+    user_id: UserId,
+    connection_id: Uuid,
+    max_connections: usize,
+) -> bool {
+    let connection_ids = user_connections.entry(user_id).or_default();
+    if connection_ids.len() >= max_connections {
+        if connection_ids.is_empty() {
+            user_connections.remove(&user_id);
+        }
+        return false;
+    }
+    connection_ids.insert(connection_id);
+    true
+}
+
+pub(crate) async fn reserve_user_connection(
+    state: &AppState,
+    user_id: UserId,
+    connection_id: Uuid,
+) -> bool {
+    let mut user_connections = state.realtime_registry.user_connections().write().await;
+    try_reserve_user_connection(
+        &mut user_connections,
+        user_id,
+        connection_id,
+        state.runtime.gateway_max_connections_per_user,
+    )
+}
+
+pub(crate) async fn user_connection_count(state: &AppState, user_id: UserId) -> usize {
+    state
+        .realtime_registry
+        .user_connections()
+        .read()
+        .await
+        .get(&user_id)
+        .map_or(0, std::collections::HashSet::len)
+}
+
 fn remove_connection_from_subscription_indexes(
     subscriptions: &mut Subscriptions,
     guild_connections: &mut GuildConnectionIndex,
@@ -89,31 +138,46 @@ fn remove_connection_from_subscription_indexes(
     });
 }
 
-fn guild_id_from_subscription_key(key: &str) -> Option<&str> {
-    let (guild_id, _channel_id) = key.split_once(':')?;
-    if guild_id.is_empty() {
-        return None;
-    }
-    Some(guild_id)
-}
-
 fn insert_connection_subscription(
     subscriptions: &mut Subscriptions,
     guild_connections: &mut GuildConnectionIndex,
     connection_id: Uuid,
-    key: String,
-    outbound_tx: mpsc::Sender<String>,
+    key: ChannelKey,
+    outbound_tx: OutboundSender,
 ) {
-    let guild_id = guild_id_from_subscription_key(&key).map(ToOwned::to_owned);
+    guild_connections
+        .entry(key.guild_id().to_owned())
+        .or_default()
+        .insert(connection_id);
     subscriptions
         .entry(key)
         .or_default()
         .insert(connection_id, outbound_tx);
-    if let Some(guild_id) = guild_id {
-        guild_connections
-            .entry(guild_id)
-            .or_default()
-            .insert(connection_id);
+}
+
+fn remove_connection_subscription(
+    subscriptions: &mut Subscriptions,
+    guild_connections: &mut GuildConnectionIndex,
+    connection_id: Uuid,
+    key: &ChannelKey,
+) {
+    if let Some(listeners) = subscriptions.get_mut(key) {
+        listeners.remove(&connection_id);
+        if listeners.is_empty() {
+            subscriptions.remove(key);
+        }
+    }
+    let still_subscribed_in_guild = subscriptions.iter().any(|(other, listeners)| {
+        other.guild_id() == key.guild_id() && listeners.contains_key(&connection_id)
+    });
+    if still_subscribed_in_guild {
+        return;
+    }
+    if let Some(connection_ids) = guild_connections.get_mut(key.guild_id()) {
+        connection_ids.remove(&connection_id);
+        if connection_ids.is_empty() {
+            guild_connections.remove(key.guild_id());
+        }
     }
 }
 
@@ -167,12 +231,29 @@ where
     }
 }
 
-pub(crate) async fn broadcast_channel_event(state: &AppState, key: &str, event: &GatewayEvent) {
+pub(crate) async fn broadcast_channel_event(
+    state: &AppState,
+    key: &ChannelKey,
+    event: &GatewayEvent,
+) {
     let delivered = with_realtime_dispatch_timeout("channel", event.event_type, async {
         let timing_enabled = std::env::var_os("FILAMENT_DEBUG_REQUEST_TIMINGS").is_some();
         let total_start = Instant::now();
-        let mut slow_connections = Vec::new();
         let mut subscriptions = state.realtime_registry.subscriptions().write().await;
+        let event_filters = state
+            .realtime_registry
+            .connection_event_filters()
+            .read()
+            .await;
+        let mut strikes = state
+            .realtime_registry
+            .slow_consumer_strikes()
+            .write()
+            .await;
+        let mut slow_consumers = SlowConsumers::new(
+            &mut strikes,
+            state.runtime.gateway_slow_consumer_max_strikes,
+        );
         let listener_count_before = subscriptions.get(key).map_or(0, HashMap::len);
         let dispatch_start = Instant::now();
         let delivered = dispatch_channel_payload(
@@ -181,9 +262,13 @@ pub(crate) async fn broadcast_channel_event(state: &AppState, key: &str, event:
             &event.payload,
             state.runtime.max_gateway_event_bytes,
             event.event_type,
-            &mut slow_connections,
+            &event_filters,
+            &mut slow_consumers,
         );
         let dispatch_ms = dispatch_start.elapsed().as_millis();
+        let slow_connections = slow_consumers.into_disconnects();
+        drop(strikes);
+        drop(event_filters);
         drop(subscriptions);
 
         let close_start = Instant::now();
@@ -192,7 +277,7 @@ pub(crate) async fn broadcast_channel_event(state: &AppState, key: &str, event:
         if timing_enabled {
             tracing::info!(
                 event = "debug.gateway.broadcast_channel_event.timing",
-                key,
+                key = %key,
                 event_type = event.event_type,
                 listener_count_before,
                 delivered,
@@ -210,10 +295,36 @@ pub(crate) async fn broadcast_channel_event(state: &AppState, key: &str, event:
 }
 
 pub(crate) async fn broadcast_guild_event(state: &AppState, guild_id: &str, event: &GatewayEvent) {
+    // Fanout only walks this guild's connections; skip the write locks
+    // entirely when nobody is listening.
+    let has_listeners = state
+        .realtime_registry
+        .guild_connections()
+        .read()
+        .await
+        .get(guild_id)
+        .is_some_and(|connection_ids| !connection_ids.is_empty());
+    if !has_listeners {
+        return;
+    }
+
     let delivered = with_realtime_dispatch_timeout("guild", event.event_type, async {
-        let mut slow_connections = Vec::new();
         let mut guild_connections = state.realtime_registry.guild_connections().write().await;
         let mut senders = state.realtime_registry.connection_senders().write().await;
+        let event_filters = state
+            .realtime_registry
+            .connection_event_filters()
+            .read()
+            .await;
+        let mut strikes = state
+            .realtime_registry
+            .slow_consumer_strikes()
+            .write()
+            .await;
+        let mut slow_consumers = SlowConsumers::new(
+            &mut strikes,
+            state.runtime.gateway_slow_consumer_max_strikes,
+        );
         let delivered = dispatch_guild_payload(
             &mut guild_connections,
             &mut senders,
@@ -221,8 +332,12 @@ pub(crate) async fn broadcast_guild_event(state: &AppState, guild_id: &str, even
             &event.payload,
             state.runtime.max_gateway_event_bytes,
             event.event_type,
-            &mut slow_connections,
+            &event_filters,
+            &mut slow_consumers,
         );
+        let slow_connections = slow_consumers.into_disconnects();
+        drop(strikes);
+        drop(event_filters);
         drop(senders);
         drop(guild_connections);
 
@@ -235,6 +350,85 @@ pub(crate) async fn broadcast_guild_event(state: &AppState, guild_id: &str, even
     }
 }
 
+/// Users with at least one gateway connection watching `guild_id`.
+pub(crate) async fn guild_listener_user_ids(state: &AppState, guild_id: &str) -> HashSet<UserId> {
+    let guild_connections = state.realtime_registry.guild_connections().read().await;
+    let presence = state.realtime_registry.connection_presence().read().await;
+    guild_connections
+        .get(guild_id)
+        .into_iter()
+        .flatten()
+        .filter_map(|connection_id| presence.get(connection_id))
+        .map(|entry| entry.user_id)
+        .collect()
+}
+
+/// Guild fanout limited to connections owned by `user_ids`, for events only
+/// some members may see.
+pub(crate) async fn broadcast_guild_event_to_users(
+    state: &AppState,
+    guild_id: &str,
+    user_ids: &HashSet<UserId>,
+    event: &GatewayEvent,
+) {
+    let connection_ids: Vec<Uuid> = {
+        let guild_connections = state.realtime_registry.guild_connections().read().await;
+        let presence = state.realtime_registry.connection_presence().read().await;
+        guild_connections
+            .get(guild_id)
+            .into_iter()
+            .flatten()
+            .filter(|connection_id| {
+                presence
+                    .get(*connection_id)
+                    .is_some_and(|entry| user_ids.contains(&entry.user_id))
+            })
+            .copied()
+            .collect()
+    };
+    if should_skip_user_broadcast(&connection_ids) {
+        return;
+    }
+
+    let delivered = with_realtime_dispatch_timeout("guild", event.event_type, async {
+        let mut senders = state.realtime_registry.connection_senders().write().await;
+        let event_filters = state
+            .realtime_registry
+            .connection_event_filters()
+            .read()
+            .await;
+        let mut strikes = state
+            .realtime_registry
+            .slow_consumer_strikes()
+            .write()
+            .await;
+        let mut slow_consumers = SlowConsumers::new(
+            &mut strikes,
+            state.runtime.gateway_slow_consumer_max_strikes,
+        );
+        let delivered = dispatch_user_payload(
+            &mut senders,
+            &connection_ids,
+            &event.payload,
+            state.runtime.max_gateway_event_bytes,
+            event.event_type,
+            &event_filters,
+            &mut slow_consumers,
+        );
+        let slow_connections = slow_consumers.into_disconnects();
+        drop(strikes);
+        drop(event_filters);
+        drop(senders);
+
+        close_slow_connections(state, slow_connections).await;
+        delivered
+    })
+    .await;
+    if let Some(delivered) = delivered {
+        emit_gateway_delivery_metrics("guild", event.event_type, delivered);
+    }
+}
+
 fn should_skip_user_broadcast(connection_ids: &[Uuid]) -> bool {
     connection_ids.is_empty()
 }
@@ -258,16 +452,33 @@ pub(crate) async fn broadcast_user_event(state: &AppState, user_id: UserId, even
     }
 
     let delivered = with_realtime_dispatch_timeout("user", event.event_type, async {
-        let mut slow_connections = Vec::new();
         let mut senders = state.realtime_registry.connection_senders().write().await;
+        let event_filters = state
+            .realtime_registry
+            .connection_event_filters()
+            .read()
+            .await;
+        let mut strikes = state
+            .realtime_registry
+            .slow_consumer_strikes()
+            .write()
+            .await;
+        let mut slow_consumers = SlowConsumers::new(
+            &mut strikes,
+            state.runtime.gateway_slow_consumer_max_strikes,
+        );
         let delivered = dispatch_user_payload(
             &mut senders,
             &connection_ids,
             &event.payload,
             state.runtime.max_gateway_event_bytes,
             event.event_type,
-            &mut slow_connections,
+            &event_filters,
+            &mut slow_consumers,
         );
+        let slow_connections = slow_consumers.into_disconnects();
+        drop(strikes);
+        drop(event_filters);
         drop(senders);
 
         close_slow_connections(state, slow_connections).await;
@@ -392,8 +603,7 @@ pub(crate) async fn update_voice_participant_audio_state_for_channel(
         )
     };
 
-    let Some((channel_subscription_key, participant, changed_muted, changed_deafened)) = updated
-    else {
+    let Some((_voice_key, participant, changed_muted, changed_deafened)) = updated else {
         return;
     };
     let event = match gateway_events::try_voice_participant_update(
@@ -425,14 +635,14 @@ pub(crate) async fn update_voice_participant_audio_state_for_channel(
             return;
         }
     };
-    broadcast_channel_event(state, &channel_subscription_key, &event).await;
+    broadcast_channel_event(state, &channel_key(guild_id, channel_id), &event).await;
 }
 
 pub(crate) async fn handle_voice_subscribe(
     state: &AppState,
     guild_id: &str,
     channel_id: &str,
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
 ) {
     prune_expired_voice_participants(state, now_unix()).await;
     let key = voice_channel_key(guild_id, channel_id);
@@ -493,7 +703,7 @@ pub(crate) async fn handle_presence_subscribe(
     connection_id: Uuid,
     user_id: UserId,
     guild_id: &str,
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
 ) {
     let result = {
         let mut presence = state.realtime_registry.connection_presence().write().await;
@@ -546,8 +756,8 @@ pub(crate) async fn handle_presence_subscribe(
 pub(crate) async fn add_subscription(
     state: &AppState,
     connection_id: Uuid,
-    key: String,
-    outbound_tx: mpsc::Sender<String>,
+    key: ChannelKey,
+    outbound_tx: OutboundSender,
 ) {
     let mut subscriptions = state.realtime_registry.subscriptions().write().await;
     let mut guild_connections = state.realtime_registry.guild_connections().write().await;
@@ -560,11 +770,49 @@ pub(crate) async fn add_subscription(
     );
 }
 
+pub(crate) async fn remove_subscription(state: &AppState, connection_id: Uuid, key: &ChannelKey) {
+    let mut subscriptions = state.realtime_registry.subscriptions().write().await;
+    let mut guild_connections = state.realtime_registry.guild_connections().write().await;
+    remove_connection_subscription(
+        &mut subscriptions,
+        &mut guild_connections,
+        connection_id,
+        key,
+    );
+}
+
+pub(crate) async fn connection_subscription_keys(
+    state: &AppState,
+    connection_id: Uuid,
+) -> Vec<ChannelKey> {
+    state
+        .realtime_registry
+        .subscriptions()
+        .read()
+        .await
+        .iter()
+        .filter(|(_, listeners)| listeners.contains_key(&connection_id))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
 pub(crate) async fn remove_connection(state: &AppState, connection_id: Uuid) {
     let removed_presence = {
         let mut presence = state.realtime_registry.connection_presence().write().await;
         let mut controls = state.realtime_registry.connection_controls().write().await;
         let mut senders = state.realtime_registry.connection_senders().write().await;
+        state
+            .realtime_registry
+            .slow_consumer_strikes()
+            .write()
+            .await
+            .remove(&connection_id);
+        state
+            .realtime_registry
+            .connection_event_filters()
+            .write()
+            .await
+            .remove(&connection_id);
         remove_connection_state(&mut presence, &mut controls, &mut senders, connection_id)
     };
 
@@ -615,27 +863,62 @@ pub(crate) async fn remove_connection(state: &AppState, connection_id: Uuid) {
 mod tests {
     use std::collections::{HashMap, HashSet};
 
+    use crate::server::realtime::unbudgeted_outbound_queue;
     use filament_core::UserId;
-    use tokio::sync::mpsc;
     use tokio::sync::watch;
     use tokio::time::Duration;
     use uuid::Uuid;
 
     use super::{
-        emit_gateway_delivery_metrics, guild_id_from_subscription_key,
-        insert_connection_subscription, presence_event_scope,
+        emit_gateway_delivery_metrics, insert_connection_subscription, presence_event_scope,
         remove_connection_from_subscription_indexes, remove_connection_state,
-        should_skip_user_broadcast, signal_slow_connections_close, with_realtime_dispatch_timeout,
-        REALTIME_DISPATCH_TIMEOUT,
+        remove_connection_subscription, should_skip_user_broadcast, signal_slow_connections_close,
+        try_reserve_user_connection, with_realtime_dispatch_timeout, REALTIME_DISPATCH_TIMEOUT,
     };
     use crate::server::{
         core::{
-            ConnectionControl, ConnectionPresence, GuildConnectionIndex, Subscriptions,
+            ChannelKey, ConnectionControl, ConnectionPresence, GuildConnectionIndex, Subscriptions,
             UserConnectionIndex,
         },
         gateway_events,
     };
 
+    #[test]
+    fn try_reserve_user_connection_enforces_per_user_cap() {
+        let user = UserId::new();
+        let other = UserId::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let mut user_connections: UserConnectionIndex = HashMap::new();
+
+        assert!(try_reserve_user_connection(
+            &mut user_connections,
+            user,
+            first,
+            1
+        ));
+        assert!(!try_reserve_user_connection(
+            &mut user_connections,
+            user,
+            second,
+            1
+        ));
+        assert!(try_reserve_user_connection(
+            &mut user_connections,
+            other,
+            second,
+            1
+        ));
+        assert_eq!(user_connections[&user], HashSet::from([first]));
+        assert!(!try_reserve_user_connection(
+            &mut user_connections,
+            UserId::new(),
+            Uuid::new_v4(),
+            0
+        ));
+        assert_eq!(user_connections.len(), 2);
+    }
+
     #[test]
     fn should_skip_user_broadcast_when_no_targets() {
         assert!(should_skip_user_broadcast(&[]));
@@ -678,18 +961,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn guild_id_from_subscription_key_parses_valid_shape() {
-        assert_eq!(guild_id_from_subscription_key("g-1:c-1"), Some("g-1"));
-    }
-
-    #[test]
-    fn guild_id_from_subscription_key_rejects_invalid_shape() {
-        assert_eq!(guild_id_from_subscription_key(""), None);
-        assert_eq!(guild_id_from_subscription_key(":c-1"), None);
-        assert_eq!(guild_id_from_subscription_key("g-1"), None);
-    }
-
     #[test]
     fn closes_only_requested_connections_with_registered_controls() {
         let first = Uuid::new_v4();
@@ -723,7 +994,7 @@ mod tests {
         let (control_tx, _control_rx) = watch::channel(ConnectionControl::Open);
         let mut controls = HashMap::new();
         controls.insert(connection_id, control_tx);
-        let (sender_tx, _sender_rx) = mpsc::channel::<String>(1);
+        let (sender_tx, _sender_rx) = unbudgeted_outbound_queue(1);
         let mut senders = HashMap::new();
         senders.insert(connection_id, sender_tx);
 
@@ -745,7 +1016,7 @@ mod tests {
         let (control_tx, _control_rx) = watch::channel(ConnectionControl::Open);
         let mut controls = HashMap::new();
         controls.insert(connection_id, control_tx);
-        let (sender_tx, _sender_rx) = mpsc::channel::<String>(1);
+        let (sender_tx, _sender_rx) = unbudgeted_outbound_queue(1);
         let mut senders = HashMap::new();
         senders.insert(connection_id, sender_tx);
 
@@ -767,12 +1038,18 @@ mod tests {
         let keep = Uuid::new_v4();
         let target_user = UserId::new();
         let mixed_user = UserId::new();
-        let (target_tx, _) = mpsc::channel::<String>(1);
-        let (keep_tx, _) = mpsc::channel::<String>(1);
+        let (target_tx, _) = unbudgeted_outbound_queue(1);
+        let (keep_tx, _) = unbudgeted_outbound_queue(1);
 
         let mut subscriptions: Subscriptions = HashMap::from([
-            (String::from("g1:c1"), HashMap::from([(target, target_tx)])),
-            (String::from("g1:c2"), HashMap::from([(keep, keep_tx)])),
+            (
+                ChannelKey::new("g1", "c1"),
+                HashMap::from([(target, target_tx)]),
+            ),
+            (
+                ChannelKey::new("g1", "c2"),
+                HashMap::from([(keep, keep_tx)]),
+            ),
         ]);
         let mut guild_connections: GuildConnectionIndex = HashMap::from([
             (String::from("g1"), HashSet::from([target, keep])),
@@ -790,8 +1067,8 @@ mod tests {
             target,
         );
 
-        assert!(!subscriptions.contains_key("g1:c1"));
-        assert!(subscriptions.contains_key("g1:c2"));
+        assert!(!subscriptions.contains_key(&ChannelKey::new("g1", "c1")));
+        assert!(subscriptions.contains_key(&ChannelKey::new("g1", "c2")));
         assert!(!guild_connections.contains_key("g2"));
         assert!(guild_connections
             .get("g1")
@@ -809,11 +1086,11 @@ mod tests {
         let target = Uuid::new_v4();
         let keep = Uuid::new_v4();
         let mixed_user = UserId::new();
-        let (target_tx, _) = mpsc::channel::<String>(1);
-        let (keep_tx, _) = mpsc::channel::<String>(1);
+        let (target_tx, _) = unbudgeted_outbound_queue(1);
+        let (keep_tx, _) = unbudgeted_outbound_queue(1);
 
         let mut subscriptions: Subscriptions = HashMap::from([(
-            String::from("g1:c1"),
+            ChannelKey::new("g1", "c1"),
             HashMap::from([(target, target_tx), (keep, keep_tx)]),
         )]);
         let mut guild_connections: GuildConnectionIndex =
@@ -829,7 +1106,7 @@ mod tests {
         );
 
         let listeners = subscriptions
-            .get("g1:c1")
+            .get(&ChannelKey::new("g1", "c1"))
             .expect("entry should be retained for remaining listeners");
         assert_eq!(listeners.len(), 1);
         assert!(listeners.contains_key(&keep));
@@ -846,7 +1123,7 @@ mod tests {
     #[test]
     fn insert_connection_subscription_indexes_guild_from_valid_key() {
         let connection_id = Uuid::new_v4();
-        let (tx, _rx) = mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         let mut subscriptions = HashMap::new();
         let mut guild_connections = GuildConnectionIndex::new();
 
@@ -854,12 +1131,12 @@ mod tests {
             &mut subscriptions,
             &mut guild_connections,
             connection_id,
-            String::from("guild:channel"),
+            ChannelKey::new("guild", "channel"),
             tx,
         );
 
         let listeners = subscriptions
-            .get("guild:channel")
+            .get(&ChannelKey::new("guild", "channel"))
             .expect("listener map should exist");
         assert_eq!(listeners.len(), 1);
         assert!(listeners.contains_key(&connection_id));
@@ -870,22 +1147,86 @@ mod tests {
     }
 
     #[test]
-    fn insert_connection_subscription_rejects_invalid_guild_index_key_shape() {
-        let connection_id = Uuid::new_v4();
-        let (tx, _rx) = mpsc::channel::<String>(1);
+    fn insert_connection_subscription_keeps_colon_bearing_ids_distinct() {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let (first_tx, _first_rx) = unbudgeted_outbound_queue(1);
+        let (second_tx, _second_rx) = unbudgeted_outbound_queue(1);
         let mut subscriptions = HashMap::new();
         let mut guild_connections = GuildConnectionIndex::new();
 
         insert_connection_subscription(
             &mut subscriptions,
             &mut guild_connections,
-            connection_id,
-            String::from("invalid-key"),
+            first,
+            ChannelKey::new("g", "x:c"),
+            first_tx,
+        );
+        insert_connection_subscription(
+            &mut subscriptions,
+            &mut guild_connections,
+            second,
+            ChannelKey::new("g:x", "c"),
+            second_tx,
+        );
+
+        assert_eq!(subscriptions.len(), 2);
+        assert_eq!(
+            guild_connections.get("g").expect("guild should be indexed"),
+            &HashSet::from([first])
+        );
+        assert_eq!(
+            guild_connections
+                .get("g:x")
+                .expect("guild should be indexed"),
+            &HashSet::from([second])
+        );
+    }
+
+    #[test]
+    fn remove_connection_subscription_keeps_guild_index_while_other_channels_remain() {
+        let connection_id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let (tx, _rx) = unbudgeted_outbound_queue(4);
+        let mut subscriptions = HashMap::new();
+        let mut guild_connections = GuildConnectionIndex::new();
+        for key in [
+            ChannelKey::new("guild", "first"),
+            ChannelKey::new("guild", "second"),
+        ] {
+            insert_connection_subscription(
+                &mut subscriptions,
+                &mut guild_connections,
+                connection_id,
+                key,
+                tx.clone(),
+            );
+        }
+        insert_connection_subscription(
+            &mut subscriptions,
+            &mut guild_connections,
+            other,
+            ChannelKey::new("guild", "first"),
             tx,
         );
 
-        assert!(subscriptions.contains_key("invalid-key"));
-        assert!(guild_connections.is_empty());
+        remove_connection_subscription(
+            &mut subscriptions,
+            &mut guild_connections,
+            connection_id,
+            &ChannelKey::new("guild", "first"),
+        );
+        assert!(!subscriptions[&ChannelKey::new("guild", "first")].contains_key(&connection_id));
+        assert!(guild_connections["guild"].contains(&connection_id));
+
+        remove_connection_subscription(
+            &mut subscriptions,
+            &mut guild_connections,
+            connection_id,
+            &ChannelKey::new("guild", "second"),
+        );
+        assert!(!subscriptions.contains_key(&ChannelKey::new("guild", "second")));
+        assert_eq!(guild_connections["guild"], HashSet::from([other]));
     }
 
     #[test]
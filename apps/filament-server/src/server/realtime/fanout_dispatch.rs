@@ -5,18 +5,85 @@ use tokio::sync::mpsc;
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::server::core::{GuildConnectionIndex, Subscriptions, UserConnectionIndex};
+use crate::server::core::{
+    ChannelKey, ConnectionEventFilters, GuildConnectionIndex, Subscriptions, UserConnectionIndex,
+};
 use crate::server::metrics::{
     record_gateway_event_dropped, record_gateway_event_oversized_outbound,
 };
 
+use super::OutboundSender;
+
+/// Tracks consecutive full-queue hits per connection during one fanout pass.
+///
+/// A full outbound queue only drops the current event until a connection has
+/// accumulated `max_strikes` consecutive full-queue hits; any successful
+/// enqueue clears its strikes. Connections that exhaust the grace are
+/// collected for a `ConnectionControl::Close` signal.
+pub(crate) struct SlowConsumers<'a> {
+    strikes: &'a mut HashMap<Uuid, u32>,
+    max_strikes: u32,
+    disconnects: Vec<Uuid>,
+}
+
+impl<'a> SlowConsumers<'a> {
+    pub(crate) fn new(strikes: &'a mut HashMap<Uuid, u32>, max_strikes: u32) -> Self {
+        Self {
+            strikes,
+            max_strikes: max_strikes.max(1),
+            disconnects: Vec::new(),
+        }
+    }
+
+    fn record_delivered(&mut self, connection_id: &Uuid) {
+        if !self.strikes.is_empty() {
+            self.strikes.remove(connection_id);
+        }
+    }
+
+    /// Returns the strike count after this hit and whether the grace is exhausted.
+    fn record_full(&mut self, connection_id: Uuid) -> (u32, bool) {
+        let strikes = self.strikes.entry(connection_id).or_insert(0);
+        *strikes = strikes.saturating_add(1);
+        let strikes = *strikes;
+        if strikes < self.max_strikes {
+            return (strikes, false);
+        }
+        self.strikes.remove(&connection_id);
+        self.disconnects.push(connection_id);
+        (strikes, true)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn disconnects(&self) -> &[Uuid] {
+        &self.disconnects
+    }
+
+    pub(crate) fn into_disconnects(self) -> Vec<Uuid> {
+        self.disconnects
+    }
+}
+
+/// Returns whether the connection set a `set_filters` list that excludes this
+/// event type. Filtered connections are skipped without touching their strikes.
+fn filtered_out(
+    event_filters: &ConnectionEventFilters,
+    connection_id: &Uuid,
+    event_type: &'static str,
+) -> bool {
+    event_filters
+        .get(connection_id)
+        .is_some_and(|event_types| !event_types.contains(event_type))
+}
+
 pub(crate) fn dispatch_gateway_payload(
-    listeners: &mut HashMap<Uuid, mpsc::Sender<String>>,
+    listeners: &mut HashMap<Uuid, OutboundSender>,
     payload: &str,
     max_payload_bytes: usize,
     event_type: &'static str,
     scope: &'static str,
-    slow_connections: &mut Vec<Uuid>,
+    event_filters: &ConnectionEventFilters,
+    slow_consumers: &mut SlowConsumers<'_>,
 ) -> usize {
     if payload.len() > max_payload_bytes {
         record_gateway_event_oversized_outbound(scope, event_type);
@@ -32,9 +99,13 @@ pub(crate) fn dispatch_gateway_payload(
     }
 
     let mut delivered = 0usize;
-    listeners.retain(
-        |connection_id, sender| match sender.try_send(payload.to_owned()) {
+    listeners.retain(|connection_id, sender| {
+        if filtered_out(event_filters, connection_id, event_type) {
+            return true;
+        }
+        match sender.try_send(payload.to_owned()) {
             Ok(()) => {
+                slow_consumers.record_delivered(connection_id);
                 delivered += 1;
                 true
             }
@@ -51,28 +122,31 @@ pub(crate) fn dispatch_gateway_payload(
             }
             Err(mpsc::error::TrySendError::Full(_)) => {
                 record_gateway_event_dropped(scope, event_type, "full_queue");
+                let (strikes, exhausted) = slow_consumers.record_full(*connection_id);
                 warn!(
                     event = "gateway.fanout_dispatch.full_queue",
                     scope,
                     event_type,
                     connection_id = %connection_id,
+                    strikes,
+                    disconnect = exhausted,
                     "dropped outbound payload for full websocket queue"
                 );
-                slow_connections.push(*connection_id);
-                false
+                !exhausted
             }
-        },
-    );
+        }
+    });
     delivered
 }
 
 pub(crate) fn dispatch_channel_payload(
     subscriptions: &mut Subscriptions,
-    key: &str,
+    key: &ChannelKey,
     payload: &str,
     max_payload_bytes: usize,
     event_type: &'static str,
-    slow_connections: &mut Vec<Uuid>,
+    event_filters: &ConnectionEventFilters,
+    slow_consumers: &mut SlowConsumers<'_>,
 ) -> usize {
     let mut delivered = 0usize;
     if let Some(listeners) = subscriptions.get_mut(key) {
@@ -82,7 +156,8 @@ pub(crate) fn dispatch_channel_payload(
             max_payload_bytes,
             event_type,
             "channel",
-            slow_connections,
+            event_filters,
+            slow_consumers,
         );
         if listeners.is_empty() {
             subscriptions.remove(key);
@@ -91,14 +166,16 @@ pub(crate) fn dispatch_channel_payload(
     delivered
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn dispatch_guild_payload(
     guild_connections: &mut GuildConnectionIndex,
-    senders: &mut HashMap<Uuid, mpsc::Sender<String>>,
+    senders: &mut HashMap<Uuid, OutboundSender>,
     guild_id: &str,
     payload: &str,
     max_payload_bytes: usize,
     event_type: &'static str,
-    slow_connections: &mut Vec<Uuid>,
+    event_filters: &ConnectionEventFilters,
+    slow_consumers: &mut SlowConsumers<'_>,
 ) -> usize {
     if payload.len() > max_payload_bytes {
         record_gateway_event_oversized_outbound("guild", event_type);
@@ -125,9 +202,15 @@ pub(crate) fn dispatch_guild_payload(
             stale_connections.push(*connection_id);
             continue;
         };
+        if filtered_out(event_filters, connection_id, event_type) {
+            continue;
+        }
 
         match sender.try_send(payload.to_owned()) {
-            Ok(()) => delivered += 1,
+            Ok(()) => {
+                slow_consumers.record_delivered(connection_id);
+                delivered += 1;
+            }
             Err(mpsc::error::TrySendError::Closed(_)) => {
                 record_gateway_event_dropped("guild", event_type, "closed");
                 warn!(
@@ -141,15 +224,19 @@ pub(crate) fn dispatch_guild_payload(
             }
             Err(mpsc::error::TrySendError::Full(_)) => {
                 record_gateway_event_dropped("guild", event_type, "full_queue");
+                let (strikes, exhausted) = slow_consumers.record_full(*connection_id);
                 warn!(
                     event = "gateway.guild_fanout.full_queue",
                     event_type,
                     guild_id,
                     connection_id = %connection_id,
+                    strikes,
+                    disconnect = exhausted,
                     "dropped outbound payload for full websocket queue in guild fanout"
                 );
-                slow_connections.push(*connection_id);
-                stale_connections.push(*connection_id);
+                if exhausted {
+                    stale_connections.push(*connection_id);
+                }
             }
         }
     }
@@ -176,12 +263,13 @@ pub(crate) fn connection_ids_for_user(
 }
 
 pub(crate) fn dispatch_user_payload(
-    senders: &mut HashMap<Uuid, mpsc::Sender<String>>,
+    senders: &mut HashMap<Uuid, OutboundSender>,
     connection_ids: &[Uuid],
     payload: &str,
     max_payload_bytes: usize,
     event_type: &'static str,
-    slow_connections: &mut Vec<Uuid>,
+    event_filters: &ConnectionEventFilters,
+    slow_consumers: &mut SlowConsumers<'_>,
 ) -> usize {
     if payload.len() > max_payload_bytes {
         record_gateway_event_oversized_outbound("user", event_type);
@@ -201,8 +289,14 @@ pub(crate) fn dispatch_user_payload(
         let Some(sender) = senders.get(connection_id) else {
             continue;
         };
+        if filtered_out(event_filters, connection_id, event_type) {
+            continue;
+        }
         match sender.try_send(payload.to_owned()) {
-            Ok(()) => delivered += 1,
+            Ok(()) => {
+                slow_consumers.record_delivered(connection_id);
+                delivered += 1;
+            }
             Err(mpsc::error::TrySendError::Closed(_)) => {
                 record_gateway_event_dropped("user", event_type, "closed");
                 warn!(
@@ -215,14 +309,18 @@ pub(crate) fn dispatch_user_payload(
             }
             Err(mpsc::error::TrySendError::Full(_)) => {
                 record_gateway_event_dropped("user", event_type, "full_queue");
+                let (strikes, exhausted) = slow_consumers.record_full(*connection_id);
                 warn!(
                     event = "gateway.user_fanout.full_queue",
                     event_type,
                     connection_id = %connection_id,
+                    strikes,
+                    disconnect = exhausted,
                     "dropped outbound payload for full websocket queue in user fanout"
                 );
-                slow_connections.push(*connection_id);
-                senders.remove(connection_id);
+                if exhausted {
+                    senders.remove(connection_id);
+                }
             }
         }
     }
@@ -234,24 +332,26 @@ pub(crate) fn dispatch_user_payload(
 mod tests {
     use std::collections::{HashMap, HashSet};
 
+    use crate::server::realtime::unbudgeted_outbound_queue;
     use filament_core::UserId;
-    use tokio::sync::mpsc;
     use uuid::Uuid;
 
+    use crate::server::core::ChannelKey;
     use crate::server::metrics::{metrics_state, GATEWAY_DROP_REASON_OVERSIZED_OUTBOUND};
 
     use super::{
         connection_ids_for_user, dispatch_channel_payload, dispatch_gateway_payload,
-        dispatch_guild_payload, dispatch_user_payload,
+        dispatch_guild_payload, dispatch_user_payload, SlowConsumers,
     };
 
     #[tokio::test]
     async fn delivers_to_open_listeners_and_keeps_them_registered() {
         let connection_id = Uuid::new_v4();
-        let (sender, mut receiver) = mpsc::channel::<String>(1);
+        let (sender, mut receiver) = unbudgeted_outbound_queue(1);
         let mut listeners = HashMap::new();
         listeners.insert(connection_id, sender);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_gateway_payload(
             &mut listeners,
@@ -259,11 +359,12 @@ mod tests {
             "payload".len(),
             "message_create",
             "channel",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 1);
-        assert!(slow_connections.is_empty());
+        assert!(slow_connections.disconnects().is_empty());
         assert!(listeners.contains_key(&connection_id));
         assert_eq!(receiver.recv().await.as_deref(), Some("payload"));
     }
@@ -274,19 +375,20 @@ mod tests {
         let full_id = Uuid::new_v4();
         let closed_id = Uuid::new_v4();
 
-        let (keep_sender, _keep_receiver) = mpsc::channel::<String>(2);
-        let (full_sender, mut full_receiver) = mpsc::channel::<String>(1);
+        let (keep_sender, _keep_receiver) = unbudgeted_outbound_queue(2);
+        let (full_sender, mut full_receiver) = unbudgeted_outbound_queue(1);
         full_sender
             .try_send(String::from("occupied"))
             .expect("queue should accept first message");
-        let (closed_sender, closed_receiver) = mpsc::channel::<String>(1);
+        let (closed_sender, closed_receiver) = unbudgeted_outbound_queue(1);
         drop(closed_receiver);
 
         let mut listeners = HashMap::new();
         listeners.insert(keep_id, keep_sender);
         listeners.insert(full_id, full_sender);
         listeners.insert(closed_id, closed_sender);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_gateway_payload(
             &mut listeners,
@@ -294,11 +396,12 @@ mod tests {
             "payload".len(),
             "message_create",
             "channel",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 1);
-        assert_eq!(slow_connections, vec![full_id]);
+        assert_eq!(slow_connections.disconnects(), [full_id]);
         assert!(listeners.contains_key(&keep_id));
         assert!(!listeners.contains_key(&full_id));
         assert!(!listeners.contains_key(&closed_id));
@@ -310,6 +413,68 @@ mod tests {
         assert_eq!(drained, "occupied");
     }
 
+    #[tokio::test]
+    async fn full_listener_is_kept_until_slow_consumer_strikes_are_exhausted() {
+        let full_id = Uuid::new_v4();
+        let (full_sender, mut full_receiver) = unbudgeted_outbound_queue(1);
+        full_sender
+            .try_send(String::from("occupied"))
+            .expect("queue should accept first message");
+        let mut listeners = HashMap::from([(full_id, full_sender)]);
+        let mut strikes = HashMap::new();
+
+        for _ in 0..2 {
+            let mut slow_connections = SlowConsumers::new(&mut strikes, 3);
+            let delivered = dispatch_gateway_payload(
+                &mut listeners,
+                "payload",
+                "payload".len(),
+                "message_create",
+                "channel",
+                &HashMap::new(),
+                &mut slow_connections,
+            );
+            assert_eq!(delivered, 0);
+            assert!(slow_connections.disconnects().is_empty());
+            assert!(listeners.contains_key(&full_id));
+        }
+        assert_eq!(strikes.get(&full_id).copied(), Some(2));
+
+        assert_eq!(full_receiver.recv().await.as_deref(), Some("occupied"));
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 3);
+        let delivered = dispatch_gateway_payload(
+            &mut listeners,
+            "drained",
+            "drained".len(),
+            "message_create",
+            "channel",
+            &HashMap::new(),
+            &mut slow_connections,
+        );
+        assert_eq!(delivered, 1);
+        assert!(strikes.is_empty());
+
+        for strike in 1..=3 {
+            let mut slow_connections = SlowConsumers::new(&mut strikes, 3);
+            dispatch_gateway_payload(
+                &mut listeners,
+                "payload",
+                "payload".len(),
+                "message_create",
+                "channel",
+                &HashMap::new(),
+                &mut slow_connections,
+            );
+            if strike < 3 {
+                assert!(slow_connections.disconnects().is_empty());
+            } else {
+                assert_eq!(slow_connections.disconnects(), [full_id]);
+            }
+        }
+        assert!(!listeners.contains_key(&full_id));
+        assert!(strikes.is_empty());
+    }
+
     #[tokio::test]
     async fn rejects_oversized_outbound_payload_before_enqueue() {
         if let Ok(mut counters) = metrics_state().gateway_events_dropped.lock() {
@@ -317,9 +482,10 @@ mod tests {
         }
 
         let connection_id = Uuid::new_v4();
-        let (sender, mut receiver) = mpsc::channel::<String>(1);
+        let (sender, mut receiver) = unbudgeted_outbound_queue(1);
         let mut listeners = HashMap::from([(connection_id, sender)]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
         let payload = "payload";
 
         let delivered = dispatch_gateway_payload(
@@ -328,11 +494,12 @@ mod tests {
             payload.len() - 1,
             "message_create",
             "channel",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 0);
-        assert!(slow_connections.is_empty());
+        assert!(slow_connections.disconnects().is_empty());
         assert!(listeners.contains_key(&connection_id));
         assert!(receiver.try_recv().is_err());
 
@@ -359,15 +526,16 @@ mod tests {
         let event_type = "message_create_reason_test";
         let scope = "channel_reason_test";
 
-        let (full_sender, mut full_receiver) = mpsc::channel::<String>(1);
+        let (full_sender, mut full_receiver) = unbudgeted_outbound_queue(1);
         full_sender
             .try_send(String::from("occupied"))
             .expect("queue should accept first message");
-        let (closed_sender, closed_receiver) = mpsc::channel::<String>(1);
+        let (closed_sender, closed_receiver) = unbudgeted_outbound_queue(1);
         drop(closed_receiver);
 
         let mut listeners = HashMap::from([(full_id, full_sender), (closed_id, closed_sender)]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_gateway_payload(
             &mut listeners,
@@ -375,11 +543,12 @@ mod tests {
             "payload".len(),
             event_type,
             scope,
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 0);
-        assert_eq!(slow_connections, vec![full_id]);
+        assert_eq!(slow_connections.disconnects(), [full_id]);
         assert_eq!(full_receiver.recv().await.as_deref(), Some("occupied"));
         assert!(listeners.is_empty());
 
@@ -404,29 +573,31 @@ mod tests {
     #[tokio::test]
     async fn dispatch_channel_payload_delivers_and_prunes_empty_key() {
         let keep_id = Uuid::new_v4();
-        let (keep_sender, mut keep_receiver) = mpsc::channel::<String>(1);
+        let (keep_sender, mut keep_receiver) = unbudgeted_outbound_queue(1);
 
         let mut subscriptions = HashMap::from([(
-            String::from("g1:c1"),
+            ChannelKey::new("g1", "c1"),
             HashMap::from([(keep_id, keep_sender)]),
         )]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_channel_payload(
             &mut subscriptions,
-            "g1:c1",
+            &ChannelKey::new("g1", "c1"),
             "payload",
             "payload".len(),
             "message_create",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 1);
-        assert!(slow_connections.is_empty());
+        assert!(slow_connections.disconnects().is_empty());
         assert_eq!(keep_receiver.recv().await.as_deref(), Some("payload"));
 
         let listeners = subscriptions
-            .get("g1:c1")
+            .get(&ChannelKey::new("g1", "c1"))
             .expect("channel key should remain when listener is active");
         assert!(listeners.contains_key(&keep_id));
     }
@@ -436,33 +607,35 @@ mod tests {
         let full_id = Uuid::new_v4();
         let closed_id = Uuid::new_v4();
 
-        let (full_sender, mut full_receiver) = mpsc::channel::<String>(1);
+        let (full_sender, mut full_receiver) = unbudgeted_outbound_queue(1);
         full_sender
             .try_send(String::from("occupied"))
             .expect("queue should fill");
 
-        let (closed_sender, closed_receiver) = mpsc::channel::<String>(1);
+        let (closed_sender, closed_receiver) = unbudgeted_outbound_queue(1);
         drop(closed_receiver);
 
         let mut subscriptions = HashMap::from([(
-            String::from("g1:c1"),
+            ChannelKey::new("g1", "c1"),
             HashMap::from([(full_id, full_sender), (closed_id, closed_sender)]),
         )]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_channel_payload(
             &mut subscriptions,
-            "g1:c1",
+            &ChannelKey::new("g1", "c1"),
             "payload",
             "payload".len(),
             "message_create",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 0);
-        assert_eq!(slow_connections, vec![full_id]);
+        assert_eq!(slow_connections.disconnects(), [full_id]);
         assert_eq!(full_receiver.recv().await.as_deref(), Some("occupied"));
-        assert!(!subscriptions.contains_key("g1:c1"));
+        assert!(!subscriptions.contains_key(&ChannelKey::new("g1", "c1")));
     }
 
     #[tokio::test]
@@ -471,8 +644,8 @@ mod tests {
         let first_id = Uuid::new_v4();
         let second_id = Uuid::new_v4();
 
-        let (first_sender, mut first_receiver) = mpsc::channel::<String>(2);
-        let (second_sender, mut second_receiver) = mpsc::channel::<String>(2);
+        let (first_sender, mut first_receiver) = unbudgeted_outbound_queue(2);
+        let (second_sender, mut second_receiver) = unbudgeted_outbound_queue(2);
 
         let mut guild_connections = HashMap::from([
             (String::from("g-1"), HashSet::from([first_id, second_id])),
@@ -480,7 +653,8 @@ mod tests {
         ]);
         let mut senders = HashMap::from([(first_id, first_sender), (second_id, second_sender)]);
 
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
         let delivered = dispatch_guild_payload(
             &mut guild_connections,
             &mut senders,
@@ -488,11 +662,12 @@ mod tests {
             "payload",
             "payload".len(),
             "presence_update",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 2);
-        assert!(slow_connections.is_empty());
+        assert!(slow_connections.disconnects().is_empty());
         assert_eq!(first_receiver.recv().await.as_deref(), Some("payload"));
         assert_eq!(second_receiver.recv().await.as_deref(), Some("payload"));
         assert!(guild_connections.contains_key("g-2"));
@@ -504,12 +679,12 @@ mod tests {
         let full_id = Uuid::new_v4();
         let closed_id = Uuid::new_v4();
 
-        let (keep_sender, _keep_receiver) = mpsc::channel::<String>(2);
-        let (full_sender, mut full_receiver) = mpsc::channel::<String>(1);
+        let (keep_sender, _keep_receiver) = unbudgeted_outbound_queue(2);
+        let (full_sender, mut full_receiver) = unbudgeted_outbound_queue(1);
         full_sender
             .try_send(String::from("occupied"))
             .expect("queue should fill");
-        let (closed_sender, closed_receiver) = mpsc::channel::<String>(1);
+        let (closed_sender, closed_receiver) = unbudgeted_outbound_queue(1);
         drop(closed_receiver);
 
         let mut guild_connections = HashMap::from([(
@@ -522,7 +697,8 @@ mod tests {
             (closed_id, closed_sender),
         ]);
 
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
         let delivered = dispatch_guild_payload(
             &mut guild_connections,
             &mut senders,
@@ -530,11 +706,12 @@ mod tests {
             "payload",
             "payload".len(),
             "message_create",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 1);
-        assert_eq!(slow_connections, vec![full_id]);
+        assert_eq!(slow_connections.disconnects(), [full_id]);
 
         let listeners = guild_connections
             .get("g-1")
@@ -549,11 +726,12 @@ mod tests {
     #[tokio::test]
     async fn rejects_oversized_outbound_payload_before_guild_dispatch() {
         let connection_id = Uuid::new_v4();
-        let (sender, mut receiver) = mpsc::channel::<String>(1);
+        let (sender, mut receiver) = unbudgeted_outbound_queue(1);
         let mut guild_connections =
             HashMap::from([(String::from("g-1"), HashSet::from([connection_id]))]);
         let mut senders = HashMap::from([(connection_id, sender)]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
         let payload = "payload";
         let event_type = "message_create_oversized_reason_test";
         let key = (
@@ -575,11 +753,12 @@ mod tests {
             payload,
             payload.len() - 1,
             event_type,
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 0);
-        assert!(slow_connections.is_empty());
+        assert!(slow_connections.disconnects().is_empty());
         assert!(guild_connections
             .get("g-1")
             .expect("guild key should remain after oversized rejection")
@@ -619,17 +798,18 @@ mod tests {
                 )
             });
 
-        let (full_sender, mut full_receiver) = mpsc::channel::<String>(1);
+        let (full_sender, mut full_receiver) = unbudgeted_outbound_queue(1);
         full_sender
             .try_send(String::from("occupied"))
             .expect("queue should fill");
-        let (closed_sender, closed_receiver) = mpsc::channel::<String>(1);
+        let (closed_sender, closed_receiver) = unbudgeted_outbound_queue(1);
         drop(closed_receiver);
 
         let mut guild_connections =
             HashMap::from([(String::from("g-1"), HashSet::from([full_id, closed_id]))]);
         let mut senders = HashMap::from([(full_id, full_sender), (closed_id, closed_sender)]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_guild_payload(
             &mut guild_connections,
@@ -638,11 +818,12 @@ mod tests {
             "payload",
             "payload".len(),
             event_type,
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 0);
-        assert_eq!(slow_connections, vec![full_id]);
+        assert_eq!(slow_connections.disconnects(), [full_id]);
         assert!(!guild_connections.contains_key("g-1"));
         assert_eq!(full_receiver.recv().await.as_deref(), Some("occupied"));
 
@@ -658,7 +839,7 @@ mod tests {
     async fn prunes_missing_sender_for_target_guild_only() {
         let target_id = Uuid::new_v4();
         let other_id = Uuid::new_v4();
-        let (other_sender, _other_receiver) = mpsc::channel::<String>(1);
+        let (other_sender, _other_receiver) = unbudgeted_outbound_queue(1);
         let mut senders = HashMap::from([(other_id, other_sender)]);
         let mut guild_connections =
             HashMap::from([(String::from("g-target"), HashSet::from([target_id]))]);
@@ -667,7 +848,8 @@ mod tests {
         }
         guild_connections.insert(String::from("g-other"), HashSet::from([other_id]));
         let guild_count_before = guild_connections.len();
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_guild_payload(
             &mut guild_connections,
@@ -676,11 +858,12 @@ mod tests {
             "payload",
             "payload".len(),
             "presence_update",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 0);
-        assert!(slow_connections.is_empty());
+        assert!(slow_connections.disconnects().is_empty());
         assert!(!guild_connections.contains_key("g-target"));
         assert_eq!(guild_connections.len(), guild_count_before - 1);
         assert!(guild_connections
@@ -718,12 +901,12 @@ mod tests {
         let full_id = Uuid::new_v4();
         let closed_id = Uuid::new_v4();
 
-        let (keep_sender, _keep_receiver) = mpsc::channel::<String>(2);
-        let (full_sender, mut full_receiver) = mpsc::channel::<String>(1);
+        let (keep_sender, _keep_receiver) = unbudgeted_outbound_queue(2);
+        let (full_sender, mut full_receiver) = unbudgeted_outbound_queue(1);
         full_sender
             .try_send(String::from("occupied"))
             .expect("queue should fill");
-        let (closed_sender, closed_receiver) = mpsc::channel::<String>(1);
+        let (closed_sender, closed_receiver) = unbudgeted_outbound_queue(1);
         drop(closed_receiver);
 
         let mut senders = HashMap::from([
@@ -731,7 +914,8 @@ mod tests {
             (full_id, full_sender),
             (closed_id, closed_sender),
         ]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
 
         let delivered = dispatch_user_payload(
             &mut senders,
@@ -739,11 +923,12 @@ mod tests {
             "payload",
             "payload".len(),
             "presence_update",
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 1);
-        assert_eq!(slow_connections, vec![full_id]);
+        assert_eq!(slow_connections.disconnects(), [full_id]);
         assert!(senders.contains_key(&keep_id));
         assert!(!senders.contains_key(&full_id));
         assert!(!senders.contains_key(&closed_id));
@@ -753,9 +938,10 @@ mod tests {
     #[tokio::test]
     async fn user_fanout_rejects_oversized_payload_before_enqueue() {
         let connection_id = Uuid::new_v4();
-        let (sender, mut receiver) = mpsc::channel::<String>(1);
+        let (sender, mut receiver) = unbudgeted_outbound_queue(1);
         let mut senders = HashMap::from([(connection_id, sender)]);
-        let mut slow_connections = Vec::new();
+        let mut strikes = HashMap::new();
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 1);
         let payload = "payload";
         let event_type = "friend_request_update_oversized_reason_test";
         let key = (
@@ -776,11 +962,12 @@ mod tests {
             payload,
             payload.len() - 1,
             event_type,
+            &HashMap::new(),
             &mut slow_connections,
         );
 
         assert_eq!(delivered, 0);
-        assert!(slow_connections.is_empty());
+        assert!(slow_connections.disconnects().is_empty());
         assert!(senders.contains_key(&connection_id));
         assert!(receiver.try_recv().is_err());
 
@@ -790,4 +977,70 @@ mod tests {
             .expect("gateway dropped metrics mutex should not be poisoned");
         assert_eq!(dropped.get(&key).copied(), Some(before + 1));
     }
+
+    #[tokio::test]
+    async fn skips_connections_whose_filter_excludes_the_event_type() {
+        let filtered_id = Uuid::new_v4();
+        let open_id = Uuid::new_v4();
+        let (filtered_sender, mut filtered_receiver) = unbudgeted_outbound_queue(4);
+        let (open_sender, mut open_receiver) = unbudgeted_outbound_queue(4);
+        let event_filters = HashMap::from([(filtered_id, HashSet::from(["message_create"]))]);
+        let mut strikes = HashMap::from([(filtered_id, 1)]);
+        let mut slow_connections = SlowConsumers::new(&mut strikes, 3);
+
+        let key = ChannelKey::new("g", "c");
+        let mut subscriptions = HashMap::from([(
+            key.clone(),
+            HashMap::from([
+                (filtered_id, filtered_sender.clone()),
+                (open_id, open_sender.clone()),
+            ]),
+        )]);
+        let channel_delivered = dispatch_channel_payload(
+            &mut subscriptions,
+            &key,
+            "typing",
+            64,
+            "message_reaction",
+            &event_filters,
+            &mut slow_connections,
+        );
+        let mut guild_connections =
+            HashMap::from([(String::from("g"), HashSet::from([filtered_id, open_id]))]);
+        let mut senders = HashMap::from([(filtered_id, filtered_sender), (open_id, open_sender)]);
+        let guild_delivered = dispatch_guild_payload(
+            &mut guild_connections,
+            &mut senders,
+            "g",
+            "presence",
+            64,
+            "presence_update",
+            &event_filters,
+            &mut slow_connections,
+        );
+        let user_delivered = dispatch_user_payload(
+            &mut senders,
+            &[filtered_id, open_id],
+            "message",
+            64,
+            "message_create",
+            &event_filters,
+            &mut slow_connections,
+        );
+
+        assert_eq!(
+            (channel_delivered, guild_delivered, user_delivered),
+            (1, 1, 2)
+        );
+        assert!(subscriptions[&key].contains_key(&filtered_id));
+        assert!(guild_connections["g"].contains(&filtered_id));
+        assert_eq!(filtered_receiver.recv().await.as_deref(), Some("message"));
+        assert!(filtered_receiver.try_recv().is_err());
+        assert_eq!(open_receiver.recv().await.as_deref(), Some("typing"));
+        assert_eq!(open_receiver.recv().await.as_deref(), Some("presence"));
+        assert_eq!(open_receiver.recv().await.as_deref(), Some("message"));
+        assert!(slow_connections.disconnects().is_empty());
+        drop(slow_connections);
+        assert!(strikes.is_empty());
+    }
 }
@@ -0,0 +1,374 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use sqlx::Row;
+use tantivy::{
+    collector::TopDocs,
+    query::{BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, Query, TermQuery},
+    schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING},
+    TantivyDocument, Term,
+};
+
+use crate::server::{
+    core::{
+        AppState, GuildDirectoryFields, GuildDirectoryIndexState, GuildVisibility, IndexedGuild,
+        GUILD_DIRECTORY_WRITER_HEAP_BYTES,
+    },
+    db::visibility_to_i16,
+    errors::AuthFailure,
+};
+
+use super::{search_query_run::run_search_blocking_with_timeout, search_runtime::search_service};
+
+const NAME_BOOST: f32 = 4.0;
+const FUZZY_BOOST: f32 = 0.5;
+/// Shorter terms only prefix-match; one edit on a three-letter word matches
+/// too much of the directory to be useful.
+const MIN_FUZZY_TERM_CHARS: usize = 4;
+const MAX_GUILD_DIRECTORY_QUERY_TERMS: usize = 8;
+
+enum GuildDirectoryOperation {
+    Upsert(IndexedGuild),
+    Delete { guild_id: String },
+    Rebuild { docs: Vec<IndexedGuild> },
+}
+
+pub(crate) fn init_guild_directory_index() -> anyhow::Result<GuildDirectoryIndexState> {
+    let mut schema_builder = Schema::builder();
+    let guild_id = schema_builder.add_text_field("guild_id", STRING | STORED);
+    let text_options = TextOptions::default()
+        .set_indexing_options(TextFieldIndexing::default().set_tokenizer("default"));
+    let name = schema_builder.add_text_field("name", text_options.clone());
+    let description = schema_builder.add_text_field("description", text_options);
+    let index = tantivy::Index::create_in_ram(schema_builder.build());
+    let reader = index
+        .reader()
+        .map_err(|e| anyhow!("guild directory reader init failed: {e}"))?;
+    Ok(GuildDirectoryIndexState {
+        index,
+        reader,
+        fields: GuildDirectoryFields {
+            guild_id,
+            name,
+            description,
+        },
+        write_lock: std::sync::Mutex::new(()),
+    })
+}
+
+fn add_guild_doc(
+    directory: &GuildDirectoryIndexState,
+    writer: &tantivy::IndexWriter,
+    guild: IndexedGuild,
+) -> anyhow::Result<()> {
+    writer.delete_term(Term::from_field_text(
+        directory.fields.guild_id,
+        &guild.guild_id,
+    ));
+    let mut doc = TantivyDocument::default();
+    doc.add_text(directory.fields.guild_id, guild.guild_id);
+    doc.add_text(directory.fields.name, guild.name);
+    if let Some(description) = guild.description {
+        doc.add_text(directory.fields.description, description);
+    }
+    writer.add_document(doc)?;
+    Ok(())
+}
+
+fn apply_guild_directory_operation(
+    directory: &GuildDirectoryIndexState,
+    op: GuildDirectoryOperation,
+) -> anyhow::Result<()> {
+    let _guard = directory
+        .write_lock
+        .lock()
+        .map_err(|_| anyhow!("guild directory write lock poisoned"))?;
+    let mut writer = directory
+        .index
+        .writer_with_num_threads(1, GUILD_DIRECTORY_WRITER_HEAP_BYTES)?;
+    match op {
+        GuildDirectoryOperation::Upsert(guild) => add_guild_doc(directory, &writer, guild)?,
+        GuildDirectoryOperation::Delete { guild_id } => {
+            writer.delete_term(Term::from_field_text(directory.fields.guild_id, &guild_id));
+        }
+        GuildDirectoryOperation::Rebuild { docs } => {
+            writer.delete_all_documents()?;
+            for guild in docs {
+                add_guild_doc(directory, &writer, guild)?;
+            }
+        }
+    }
+    writer.commit()?;
+    directory.reader.reload()?;
+    Ok(())
+}
+
+/// Every query term must match a guild's name or description, exactly or
+/// within one edit as a prefix. Exact and name matches score higher.
+fn build_guild_directory_query(
+    directory: &GuildDirectoryIndexState,
+    raw_query: &str,
+) -> anyhow::Result<Option<BooleanQuery>> {
+    let mut analyzer = directory.index.tokenizer_for_field(directory.fields.name)?;
+    let mut stream = analyzer.token_stream(raw_query);
+    let mut terms: Vec<String> = Vec::new();
+    while stream.advance() {
+        let text = &stream.token().text;
+        if terms.len() < MAX_GUILD_DIRECTORY_QUERY_TERMS && !terms.contains(text) {
+            terms.push(text.clone());
+        }
+    }
+    if terms.is_empty() {
+        return Ok(None);
+    }
+
+    let fields = [
+        (directory.fields.name, NAME_BOOST),
+        (directory.fields.description, 1.0),
+    ];
+    let clauses = terms
+        .iter()
+        .map(|text| {
+            let distance = u8::from(text.chars().count() >= MIN_FUZZY_TERM_CHARS);
+            let mut alternatives: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+            for (field, boost) in fields {
+                let term = Term::from_field_text(field, text);
+                alternatives.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        Box::new(TermQuery::new(term.clone(), IndexRecordOption::WithFreqs)),
+                        boost,
+                    )),
+                ));
+                alternatives.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        Box::new(FuzzyTermQuery::new_prefix(term, distance, true)),
+                        boost * FUZZY_BOOST,
+                    )),
+                ));
+            }
+            (
+                Occur::Must,
+                Box::new(BooleanQuery::new(alternatives)) as Box<dyn Query>,
+            )
+        })
+        .collect();
+    Ok(Some(BooleanQuery::new(clauses)))
+}
+
+/// Returns ids of indexed guilds matching `raw_query`, best match first.
+fn search_guild_directory(
+    directory: &GuildDirectoryIndexState,
+    raw_query: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<String>> {
+    let Some(query) = build_guild_directory_query(directory, raw_query)? else {
+        return Ok(Vec::new());
+    };
+    let searcher = directory.reader.searcher();
+    let hits = searcher.search(&query, &TopDocs::with_limit(limit))?;
+    let mut guild_ids = Vec::with_capacity(hits.len());
+    for (_score, address) in hits {
+        let doc = searcher.doc::<TantivyDocument>(address)?;
+        if let Some(guild_id) = doc
+            .get_first(directory.fields.guild_id)
+            .and_then(|value| value.as_str())
+        {
+            guild_ids.push(guild_id.to_owned());
+        }
+    }
+    Ok(guild_ids)
+}
+
+async fn collect_public_guilds(state: &AppState) -> Result<Vec<IndexedGuild>, AuthFailure> {
+    if let Some(pool) = &state.db_pool {
+        let rows = sqlx::query(
+            "SELECT guild_id, name, description
+             FROM guilds
+             WHERE visibility = $1",
+        )
+        .bind(visibility_to_i16(GuildVisibility::Public))
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+        let mut guilds = Vec::with_capacity(rows.len());
+        for row in rows {
+            guilds.push(IndexedGuild {
+                guild_id: row.try_get("guild_id").map_err(|_| AuthFailure::Internal)?,
+                name: row.try_get("name").map_err(|_| AuthFailure::Internal)?,
+                description: row
+                    .try_get("description")
+                    .map_err(|_| AuthFailure::Internal)?,
+            });
+        }
+        return Ok(guilds);
+    }
+
+    let guilds = state.membership_store.guilds().read().await;
+    Ok(guilds
+        .iter()
+        .filter(|(_, guild)| guild.visibility == GuildVisibility::Public)
+        .map(|(guild_id, guild)| IndexedGuild {
+            guild_id: guild_id.clone(),
+            name: guild.name.clone(),
+            description: guild.description.clone(),
+        })
+        .collect())
+}
+
+async fn apply_guild_directory_operation_blocking(
+    state: &AppState,
+    op: GuildDirectoryOperation,
+) -> Result<(), AuthFailure> {
+    let directory = Arc::clone(&search_service(state)?.guild_directory);
+    tokio::task::spawn_blocking(move || apply_guild_directory_operation(&directory, op))
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .map_err(|error| {
+            tracing::warn!(event = "guild_directory.index.write_failed", error = %error);
+            AuthFailure::Internal
+        })
+}
+
+async fn ensure_guild_directory_bootstrapped(state: &AppState) -> Result<(), AuthFailure> {
+    state
+        .guild_directory_bootstrapped
+        .get_or_try_init(|| async move {
+            let docs = collect_public_guilds(state).await?;
+            apply_guild_directory_operation_blocking(
+                state,
+                GuildDirectoryOperation::Rebuild { docs },
+            )
+            .await
+        })
+        .await?;
+    Ok(())
+}
+
+/// Mirrors a guild's directory entry after it changes: `Some` while the guild
+/// is public, `None` once it is not. Before the first directory search the
+/// index is built from storage, so there is nothing to keep in sync yet.
+pub(crate) async fn sync_guild_directory_entry(
+    state: &AppState,
+    guild_id: &str,
+    entry: Option<IndexedGuild>,
+) {
+    if state.guild_directory_bootstrapped.get().is_none() {
+        return;
+    }
+    let op = match entry {
+        Some(guild) => GuildDirectoryOperation::Upsert(guild),
+        None => GuildDirectoryOperation::Delete {
+            guild_id: guild_id.to_owned(),
+        },
+    };
+    // A failed write leaves a stale entry; results are re-checked against
+    // storage, so it can only affect ranking.
+    let _ = apply_guild_directory_operation_blocking(state, op).await;
+}
+
+/// Ranks public guild ids for a directory query. Callers must re-check
+/// visibility against storage, since the index can briefly lag behind it.
+pub(crate) async fn run_guild_directory_search(
+    state: &AppState,
+    raw_query: &str,
+    limit: usize,
+) -> Result<Vec<String>, AuthFailure> {
+    ensure_guild_directory_bootstrapped(state).await?;
+    let directory = Arc::clone(&search_service(state)?.guild_directory);
+    let raw_query = raw_query.to_owned();
+    let permit = Arc::clone(&state.search_query_permits)
+        .try_acquire_owned()
+        .map_err(|_| AuthFailure::RateLimited)?;
+    run_search_blocking_with_timeout(state.runtime.search_query_timeout, move || {
+        let _permit = permit;
+        search_guild_directory(&directory, &raw_query, limit).map_err(|error| {
+            tracing::warn!(event = "guild_directory.search.failed", error = %error);
+            AuthFailure::Internal
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_guild_directory_operation, init_guild_directory_index, search_guild_directory,
+        GuildDirectoryOperation,
+    };
+    use crate::server::core::{GuildDirectoryIndexState, IndexedGuild};
+
+    fn guild(guild_id: &str, name: &str, description: Option<&str>) -> IndexedGuild {
+        IndexedGuild {
+            guild_id: guild_id.to_owned(),
+            name: name.to_owned(),
+            description: description.map(ToOwned::to_owned),
+        }
+    }
+
+    fn directory_with_guilds() -> GuildDirectoryIndexState {
+        let directory = init_guild_directory_index().expect("index should build");
+        apply_guild_directory_operation(
+            &directory,
+            GuildDirectoryOperation::Rebuild {
+                docs: vec![
+                    guild("g-rust", "Rustaceans", Some("Systems programming chat")),
+                    guild(
+                        "g-garden",
+                        "Garden Club",
+                        Some("Plants, soil and rust fungus"),
+                    ),
+                    guild("g-chess", "Chess Lounge", None),
+                ],
+            },
+        )
+        .expect("rebuild should apply");
+        directory
+    }
+
+    #[test]
+    fn directory_search_ranks_name_matches_first() {
+        let directory = directory_with_guilds();
+
+        let ids = search_guild_directory(&directory, "rust", 10).expect("search should run");
+
+        assert_eq!(ids.first().map(String::as_str), Some("g-rust"));
+        assert!(ids.contains(&String::from("g-garden")));
+        assert!(!ids.contains(&String::from("g-chess")));
+    }
+
+    #[test]
+    fn directory_search_tolerates_typos_and_prefixes() {
+        let directory = directory_with_guilds();
+
+        assert_eq!(
+            search_guild_directory(&directory, "chses", 10).expect("search should run"),
+            ["g-chess"]
+        );
+        assert_eq!(
+            search_guild_directory(&directory, "gard", 10).expect("search should run"),
+            ["g-garden"]
+        );
+    }
+
+    #[test]
+    fn directory_search_requires_every_term_and_honors_deletes() {
+        let directory = directory_with_guilds();
+        assert!(search_guild_directory(&directory, "chess plants", 10)
+            .expect("search should run")
+            .is_empty());
+
+        apply_guild_directory_operation(
+            &directory,
+            GuildDirectoryOperation::Delete {
+                guild_id: String::from("g-chess"),
+            },
+        )
+        .expect("delete should apply");
+
+        assert!(search_guild_directory(&directory, "chess", 10)
+            .expect("search should run")
+            .is_empty());
+    }
+}
@@ -2,13 +2,27 @@ use std::collections::HashMap;
 
 use crate::server::{
     core::GuildRecord,
-    domain::reaction_summaries_from_users,
+    domain::{
+        decode_message_content, reaction_summaries_from_users, MessageContentCipher,
+        StoredMessageContent,
+    },
     errors::AuthFailure,
     types::{AttachmentResponse, MessageResponse, ReactionResponse},
 };
 use filament_core::tokenize_markdown;
 
-type HydratedMessageRow = (String, String, String, String, String, i64);
+type HydratedMessageRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<Vec<u8>>,
+    Option<Vec<u8>>,
+    i64,
+    Option<i64>,
+    bool,
+);
 
 pub(crate) fn collect_hydrated_in_request_order(
     by_id: HashMap<String, MessageResponse>,
@@ -44,9 +58,30 @@ pub(crate) fn apply_hydration_attachments(
     }
 }
 
-fn map_hydrated_rows(rows: Vec<HydratedMessageRow>) -> HashMap<String, MessageResponse> {
+fn map_hydrated_rows(
+    rows: Vec<HydratedMessageRow>,
+    cipher: Option<&MessageContentCipher>,
+) -> Result<HashMap<String, MessageResponse>, AuthFailure> {
     let mut by_id = HashMap::with_capacity(rows.len());
-    for (message_id, guild_id, channel_id, author_id, content, created_at_unix) in rows {
+    for (
+        message_id,
+        guild_id,
+        channel_id,
+        author_id,
+        content,
+        content_zstd,
+        content_sealed,
+        created_at_unix,
+        edited_at_unix,
+        system,
+    ) in rows
+    {
+        let stored = StoredMessageContent {
+            content,
+            content_zstd,
+            content_sealed,
+        };
+        let content = decode_message_content(stored, &message_id, cipher)?;
         by_id.insert(
             message_id.clone(),
             MessageResponse {
@@ -58,24 +93,33 @@ fn map_hydrated_rows(rows: Vec<HydratedMessageRow>) -> HashMap<String, MessageRe
                 content,
                 attachments: Vec::new(),
                 reactions: Vec::new(),
+                embeds: Vec::new(),
+                author_username: None,
                 created_at_unix,
+                deleted: false,
+                system,
+                score: None,
+                edited_at_unix,
             },
         );
     }
-    by_id
+    Ok(by_id)
 }
 
 pub(super) async fn collect_hydrated_messages_db(
     pool: &sqlx::PgPool,
+    cipher: Option<&MessageContentCipher>,
     guild_id: &str,
     channel_id: Option<&str>,
     message_ids: &[String],
 ) -> Result<HashMap<String, MessageResponse>, AuthFailure> {
     let rows = if let Some(channel_id) = channel_id {
         sqlx::query_as::<_, HydratedMessageRow>(
-            "SELECT message_id, guild_id, channel_id, author_id, content, created_at_unix
+            "SELECT message_id, guild_id, channel_id, author_id, content, content_zstd,
+                    content_sealed, created_at_unix, edited_at_unix, system
              FROM messages
-             WHERE guild_id = $1 AND channel_id = $2 AND message_id = ANY($3::text[])",
+             WHERE guild_id = $1 AND channel_id = $2 AND message_id = ANY($3::text[])
+               AND deleted_at_unix IS NULL",
         )
         .bind(guild_id)
         .bind(channel_id)
@@ -85,9 +129,11 @@ pub(super) async fn collect_hydrated_messages_db(
         .map_err(|_| AuthFailure::Internal)?
     } else {
         sqlx::query_as::<_, HydratedMessageRow>(
-            "SELECT message_id, guild_id, channel_id, author_id, content, created_at_unix
+            "SELECT message_id, guild_id, channel_id, author_id, content, content_zstd,
+                    content_sealed, created_at_unix, edited_at_unix, system
              FROM messages
-             WHERE guild_id = $1 AND message_id = ANY($2::text[])",
+             WHERE guild_id = $1 AND message_id = ANY($2::text[])
+               AND deleted_at_unix IS NULL",
         )
         .bind(guild_id)
         .bind(message_ids)
@@ -96,7 +142,7 @@ pub(super) async fn collect_hydrated_messages_db(
         .map_err(|_| AuthFailure::Internal)?
     };
 
-    Ok(map_hydrated_rows(rows))
+    map_hydrated_rows(rows, cipher)
 }
 
 pub(super) fn collect_hydrated_messages_in_memory(
@@ -110,7 +156,11 @@ pub(super) fn collect_hydrated_messages_in_memory(
             .channels
             .get(channel_id)
             .ok_or(AuthFailure::NotFound)?;
-        for message in &channel.messages {
+        for message in channel
+            .messages
+            .iter()
+            .filter(|message| message.deleted_at_unix.is_none())
+        {
             by_id.insert(
                 message.id.clone(),
                 MessageResponse {
@@ -122,7 +172,13 @@ pub(super) fn collect_hydrated_messages_in_memory(
                     markdown_tokens: message.markdown_tokens.clone(),
                     attachments: Vec::new(),
                     reactions: reaction_summaries_from_users(&message.reactions, None),
+                    embeds: Vec::new(),
+                    author_username: None,
                     created_at_unix: message.created_at_unix,
+                    deleted: false,
+                    system: message.system,
+                    score: None,
+                    edited_at_unix: message.edited_at_unix,
                 },
             );
         }
@@ -130,7 +186,11 @@ pub(super) fn collect_hydrated_messages_in_memory(
     }
 
     for (channel_id, channel) in &guild.channels {
-        for message in &channel.messages {
+        for message in channel
+            .messages
+            .iter()
+            .filter(|message| message.deleted_at_unix.is_none())
+        {
             by_id.insert(
                 message.id.clone(),
                 MessageResponse {
@@ -142,7 +202,13 @@ pub(super) fn collect_hydrated_messages_in_memory(
                     markdown_tokens: message.markdown_tokens.clone(),
                     attachments: Vec::new(),
                     reactions: reaction_summaries_from_users(&message.reactions, None),
+                    embeds: Vec::new(),
+                    author_username: None,
                     created_at_unix: message.created_at_unix,
+                    deleted: false,
+                    system: message.system,
+                    score: None,
+                    edited_at_unix: message.edited_at_unix,
                 },
             );
         }
@@ -179,7 +245,13 @@ mod tests {
             content: content.to_owned(),
             attachments: Vec::new(),
             reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
             created_at_unix: 1,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
         }
     }
 
@@ -224,7 +296,13 @@ mod tests {
             }],
             attachments: Vec::new(),
             reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
             created_at_unix: 1,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
         }
     }
 
@@ -279,7 +357,13 @@ mod tests {
             }],
             attachments: Vec::new(),
             reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
             created_at_unix: 1,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
         }
     }
 
@@ -326,14 +410,22 @@ mod tests {
 
     #[test]
     fn map_hydrated_rows_maps_fields_and_tokenizes_content() {
-        let by_id = map_hydrated_rows(vec![(
-            String::from("m1"),
-            String::from("g1"),
-            String::from("c1"),
-            String::from("u1"),
-            String::from("hello **bold**"),
-            12,
-        )]);
+        let by_id = map_hydrated_rows(
+            vec![(
+                String::from("m1"),
+                String::from("g1"),
+                String::from("c1"),
+                String::from("u1"),
+                String::from("hello **bold**"),
+                None,
+                None,
+                12,
+                Some(15),
+                true,
+            )],
+            None,
+        )
+        .expect("rows should map");
 
         let message = by_id.get("m1").expect("mapped message should be present");
         assert_eq!(message.guild_id, "g1");
@@ -344,28 +436,42 @@ mod tests {
         assert!(message.attachments.is_empty());
         assert!(message.reactions.is_empty());
         assert_eq!(message.created_at_unix, 12);
+        assert_eq!(message.edited_at_unix, Some(15));
+        assert!(message.system);
     }
 
     #[test]
     fn map_hydrated_rows_overwrites_duplicate_message_ids_with_last_row() {
-        let by_id = map_hydrated_rows(vec![
-            (
-                String::from("m1"),
-                String::from("g1"),
-                String::from("c1"),
-                String::from("u1"),
-                String::from("old"),
-                10,
-            ),
-            (
-                String::from("m1"),
-                String::from("g1"),
-                String::from("c1"),
-                String::from("u1"),
-                String::from("new"),
-                11,
-            ),
-        ]);
+        let by_id = map_hydrated_rows(
+            vec![
+                (
+                    String::from("m1"),
+                    String::from("g1"),
+                    String::from("c1"),
+                    String::from("u1"),
+                    String::from("old"),
+                    None,
+                    None,
+                    10,
+                    None,
+                    false,
+                ),
+                (
+                    String::from("m1"),
+                    String::from("g1"),
+                    String::from("c1"),
+                    String::from("u1"),
+                    String::from("new"),
+                    None,
+                    None,
+                    11,
+                    None,
+                    false,
+                ),
+            ],
+            None,
+        )
+        .expect("rows should map");
 
         let message = by_id.get("m1").expect("mapped message should be present");
         assert_eq!(message.content, "new");
@@ -378,6 +484,11 @@ mod tests {
             visibility: GuildVisibility::Private,
             created_by_user_id: author,
             default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
             members: HashMap::from([(author, Role::Owner)]),
             banned_members: HashSet::new(),
             channels: HashMap::from([
@@ -386,6 +497,10 @@ mod tests {
                     ChannelRecord {
                         name: String::from("general"),
                         kind: ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
                         messages: vec![MessageRecord {
                             id: String::from("m1"),
                             author_id: author,
@@ -394,6 +509,9 @@ mod tests {
                             attachment_ids: Vec::new(),
                             created_at_unix: 11,
                             reactions: HashMap::new(),
+                            deleted_at_unix: None,
+                            edited_at_unix: None,
+                            system: false,
                         }],
                         role_overrides: HashMap::<Role, ChannelPermissionOverwrite>::new(),
                     },
@@ -403,6 +521,10 @@ mod tests {
                     ChannelRecord {
                         name: String::from("random"),
                         kind: ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
                         messages: vec![MessageRecord {
                             id: String::from("m2"),
                             author_id: author,
@@ -411,6 +533,9 @@ mod tests {
                             attachment_ids: Vec::new(),
                             created_at_unix: 12,
                             reactions: HashMap::new(),
+                            deleted_at_unix: None,
+                            edited_at_unix: None,
+                            system: false,
                         }],
                         role_overrides: HashMap::<Role, ChannelPermissionOverwrite>::new(),
                     },
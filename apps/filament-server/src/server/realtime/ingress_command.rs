@@ -1,11 +1,11 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
     time::{Duration, Instant},
 };
 
 use axum::extract::ws::Message;
 use filament_core::UserId;
-use filament_protocol::Envelope;
+use filament_protocol::{gateway_event_manifest, Envelope, GatewayEventScope};
 use serde::Deserialize;
 use serde_json::Value;
 use tokio::sync::mpsc;
@@ -14,15 +14,18 @@ use uuid::Uuid;
 
 use crate::server::{
     auth::{validate_message_content, ClientIp},
-    core::{AppState, AuthContext},
+    core::{AppState, AuthContext, ChannelKey},
     domain::{enforce_guild_ip_ban_for_request, parse_attachment_ids, user_can_write_channel},
     gateway_events,
     metrics::{record_gateway_event_dropped, record_gateway_event_emitted},
+    types::{HistoryQuery, HistorySort, MessageResponse},
 };
 
 use super::{
-    add_subscription, create_message_internal_from_ingress_validated, handle_presence_subscribe,
-    handle_voice_subscribe,
+    add_subscription, claim_message_idempotency, complete_message_idempotency,
+    connection_subscription_keys, create_message_internal_from_ingress_validated,
+    handle_presence_subscribe, handle_voice_subscribe, load_message_history, parse_idempotency_key,
+    release_message_idempotency, remove_subscription, IdempotencyClaim, OutboundSender,
 };
 
 #[derive(Debug, Deserialize)]
@@ -39,12 +42,30 @@ struct GatewayMessageCreateDto {
     channel_id: String,
     content: String,
     attachment_ids: Option<Vec<String>>,
+    nonce: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GatewayHistoryRequestDto {
+    guild_id: String,
+    channel_id: String,
+    limit: Option<usize>,
+    before: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GatewaySetFiltersDto {
+    event_types: Option<Vec<String>>,
 }
 
 #[derive(Debug)]
 pub(crate) enum GatewayIngressCommand {
     Subscribe(GatewaySubscribeCommand),
     MessageCreate(GatewayMessageCreateCommand),
+    HistoryRequest(GatewayHistoryRequestCommand),
+    SetFilters(GatewaySetFiltersCommand),
 }
 
 impl TryFrom<Envelope<Value>> for GatewayIngressCommand {
@@ -67,6 +88,20 @@ impl TryFrom<Envelope<Value>> for GatewayIngressCommand {
                         .map_err(|()| GatewayIngressCommandParseError::InvalidMessageCreatePayload)
                 })
                 .map(Self::MessageCreate),
+            "history_request" => serde_json::from_value::<GatewayHistoryRequestDto>(envelope.d)
+                .map_err(|_| GatewayIngressCommandParseError::InvalidHistoryRequestPayload)
+                .and_then(|history_request| {
+                    GatewayHistoryRequestCommand::try_from(history_request)
+                        .map_err(|()| GatewayIngressCommandParseError::InvalidHistoryRequestPayload)
+                })
+                .map(Self::HistoryRequest),
+            "set_filters" => serde_json::from_value::<GatewaySetFiltersDto>(envelope.d)
+                .map_err(|_| GatewayIngressCommandParseError::InvalidSetFiltersPayload)
+                .and_then(|set_filters| {
+                    GatewaySetFiltersCommand::try_from(set_filters)
+                        .map_err(|()| GatewayIngressCommandParseError::InvalidSetFiltersPayload)
+                })
+                .map(Self::SetFilters),
             _ => Err(GatewayIngressCommandParseError::UnknownEventType(
                 event_type,
             )),
@@ -122,10 +157,10 @@ pub(crate) struct GatewaySubscribeCommand {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct GatewaySubscriptionKey(String);
+pub(crate) struct GatewaySubscriptionKey(ChannelKey);
 
 impl GatewaySubscriptionKey {
-    pub(crate) fn into_string(self) -> String {
+    pub(crate) fn into_channel_key(self) -> ChannelKey {
         self.0
     }
 }
@@ -137,10 +172,9 @@ impl TryFrom<GatewaySubscribeDto> for GatewaySubscribeCommand {
         let guild_id = GatewayGuildId::try_from(value.guild_id)?;
         let channel_id = GatewayChannelId::try_from(value.channel_id)?;
         Ok(Self {
-            subscription_key: GatewaySubscriptionKey(format!(
-                "{}:{}",
+            subscription_key: GatewaySubscriptionKey(ChannelKey::new(
                 guild_id.as_str(),
-                channel_id.as_str()
+                channel_id.as_str(),
             )),
             guild_id,
             channel_id,
@@ -154,6 +188,7 @@ pub(crate) struct GatewayMessageCreateCommand {
     pub(crate) channel_id: GatewayChannelId,
     pub(crate) content: GatewayMessageContent,
     pub(crate) attachment_ids: GatewayAttachmentIds,
+    pub(crate) nonce: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -210,11 +245,73 @@ impl TryFrom<GatewayMessageCreateDto> for GatewayMessageCreateCommand {
         let attachment_ids =
             GatewayAttachmentIds::try_from(value.attachment_ids.unwrap_or_default())?;
         let content = GatewayMessageContent::try_from((value.content, !attachment_ids.is_empty()))?;
+        let nonce = value
+            .nonce
+            .as_deref()
+            .map(parse_idempotency_key)
+            .transpose()
+            .map_err(|_| ())?;
         Ok(Self {
             guild_id: GatewayGuildId::try_from(value.guild_id)?,
             channel_id: GatewayChannelId::try_from(value.channel_id)?,
             content,
             attachment_ids,
+            nonce,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GatewayHistoryRequestCommand {
+    pub(crate) guild_id: GatewayGuildId,
+    pub(crate) channel_id: GatewayChannelId,
+    pub(crate) limit: Option<usize>,
+    pub(crate) before: Option<String>,
+}
+
+impl TryFrom<GatewayHistoryRequestDto> for GatewayHistoryRequestCommand {
+    type Error = ();
+
+    fn try_from(value: GatewayHistoryRequestDto) -> Result<Self, Self::Error> {
+        if let Some(before) = value.before.as_deref() {
+            Ulid::from_string(before).map_err(|_| ())?;
+        }
+        Ok(Self {
+            guild_id: GatewayGuildId::try_from(value.guild_id)?,
+            channel_id: GatewayChannelId::try_from(value.channel_id)?,
+            limit: value.limit,
+            before: value.before,
+        })
+    }
+}
+
+/// Event types a connection wants fanned out to it; `None` clears the filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GatewaySetFiltersCommand {
+    pub(crate) event_types: Option<HashSet<&'static str>>,
+}
+
+impl TryFrom<GatewaySetFiltersDto> for GatewaySetFiltersCommand {
+    type Error = ();
+
+    fn try_from(value: GatewaySetFiltersDto) -> Result<Self, Self::Error> {
+        let Some(requested) = value.event_types else {
+            return Ok(Self { event_types: None });
+        };
+        let mut event_types = HashSet::with_capacity(requested.len());
+        for event_type in requested {
+            let entry = gateway_event_manifest()
+                .events
+                .iter()
+                .find(|entry| entry.event_type == event_type)
+                .ok_or(())?;
+            if entry.scope == GatewayEventScope::Connection {
+                return Err(());
+            }
+            event_types.insert(entry.event_type.as_str());
+        }
+        Ok(Self {
+            event_types: Some(event_types),
         })
     }
 }
@@ -223,6 +320,8 @@ impl TryFrom<GatewayMessageCreateDto> for GatewayMessageCreateCommand {
 pub(crate) enum GatewayIngressCommandParseError {
     InvalidSubscribePayload,
     InvalidMessageCreatePayload,
+    InvalidHistoryRequestPayload,
+    InvalidSetFiltersPayload,
     UnknownEventType(String),
 }
 
@@ -231,6 +330,8 @@ impl GatewayIngressCommandParseError {
         match self {
             Self::InvalidSubscribePayload => "invalid_subscribe_payload",
             Self::InvalidMessageCreatePayload => "invalid_message_create_payload",
+            Self::InvalidHistoryRequestPayload => "invalid_history_request_payload",
+            Self::InvalidSetFiltersPayload => "invalid_set_filters_payload",
             Self::UnknownEventType(_) => "unknown_event",
         }
     }
@@ -251,6 +352,12 @@ pub(crate) fn classify_ingress_command_parse_error(
         GatewayIngressCommandParseError::InvalidMessageCreatePayload => {
             IngressCommandParseClassification::ParseRejected("invalid_message_create_payload")
         }
+        GatewayIngressCommandParseError::InvalidHistoryRequestPayload => {
+            IngressCommandParseClassification::ParseRejected("invalid_history_request_payload")
+        }
+        GatewayIngressCommandParseError::InvalidSetFiltersPayload => {
+            IngressCommandParseClassification::ParseRejected("invalid_set_filters_payload")
+        }
         GatewayIngressCommandParseError::UnknownEventType(event_type) => {
             IngressCommandParseClassification::UnknownEventType(event_type)
         }
@@ -271,7 +378,7 @@ pub(crate) enum SubscribeAckEnqueueResult {
 }
 
 pub(crate) fn try_enqueue_subscribed_event(
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
     payload: String,
     max_gateway_event_bytes: usize,
 ) -> SubscribeAckEnqueueResult {
@@ -286,11 +393,15 @@ pub(crate) fn try_enqueue_subscribed_event(
     }
 }
 
+/// Creates a message from a gateway `message_create` and answers the sending
+/// connection with a `message_create_ack`, including for a replayed nonce.
 pub(crate) async fn execute_message_create_command(
     state: &AppState,
+    connection_id: Uuid,
     auth: &AuthContext,
     client_ip: ClientIp,
     request: GatewayMessageCreateCommand,
+    outbound_tx: &OutboundSender,
 ) -> Result<(), &'static str> {
     if enforce_guild_ip_ban_for_request(
         state,
@@ -305,7 +416,34 @@ pub(crate) async fn execute_message_create_command(
         return Err("ip_banned");
     }
 
-    if create_message_internal_from_ingress_validated(
+    if let Some(nonce) = request.nonce.as_deref() {
+        match claim_message_idempotency(
+            state,
+            auth.user_id,
+            nonce,
+            request.guild_id.as_str(),
+            request.channel_id.as_str(),
+        )
+        .await
+        {
+            // The original create was already broadcast to this channel; only
+            // the sender needs to hear about it again.
+            Ok(IdempotencyClaim::Replay(response)) => {
+                return send_message_create_ack(
+                    state,
+                    connection_id,
+                    auth.user_id,
+                    request.nonce.as_deref(),
+                    &response,
+                    outbound_tx,
+                );
+            }
+            Ok(IdempotencyClaim::Claimed | IdempotencyClaim::Untracked) => {}
+            Err(_) => return Err("message_rejected"),
+        }
+    }
+
+    let created = create_message_internal_from_ingress_validated(
         state,
         auth,
         request.guild_id.as_str(),
@@ -313,13 +451,82 @@ pub(crate) async fn execute_message_create_command(
         request.content,
         request.attachment_ids,
     )
-    .await
-    .is_err()
-    {
-        return Err("message_rejected");
+    .await;
+    if let Some(nonce) = request.nonce.as_deref() {
+        match &created {
+            Ok(response) => {
+                complete_message_idempotency(state, auth.user_id, nonce, response).await;
+            }
+            Err(_) => release_message_idempotency(state, auth.user_id, nonce).await,
+        }
     }
+    let Ok(response) = created else {
+        return Err("message_rejected");
+    };
 
-    Ok(())
+    send_message_create_ack(
+        state,
+        connection_id,
+        auth.user_id,
+        request.nonce.as_deref(),
+        &response,
+        outbound_tx,
+    )
+}
+
+fn send_message_create_ack(
+    state: &AppState,
+    connection_id: Uuid,
+    user_id: UserId,
+    nonce: Option<&str>,
+    message: &MessageResponse,
+    outbound_tx: &OutboundSender,
+) -> Result<(), &'static str> {
+    let ack_event = match gateway_events::try_message_create_ack(nonce, message) {
+        Ok(event) => event,
+        Err(error) => {
+            tracing::error!(
+                event = "gateway.message_create_ack.serialize_failed",
+                connection_id = %connection_id,
+                user_id = %user_id,
+                message_id = %message.message_id,
+                error = %error
+            );
+            record_gateway_event_dropped(
+                "connection",
+                gateway_events::MESSAGE_CREATE_ACK_EVENT,
+                "serialize_error",
+            );
+            return Ok(());
+        }
+    };
+    let enqueue_result = try_enqueue_subscribed_event(
+        outbound_tx,
+        ack_event.payload,
+        state.runtime.max_gateway_event_bytes,
+    );
+    if let Some(reason) = subscribe_ack_drop_metric_reason(&enqueue_result) {
+        record_gateway_event_dropped("connection", ack_event.event_type, reason);
+        tracing::warn!(
+            event = "gateway.message_create_ack.enqueue_rejected",
+            connection_id = %connection_id,
+            user_id = %user_id,
+            message_id = %message.message_id,
+            reason
+        );
+    }
+    match enqueue_result {
+        SubscribeAckEnqueueResult::Enqueued => {
+            record_gateway_event_emitted("connection", ack_event.event_type);
+            Ok(())
+        }
+        // The message is stored and broadcast either way; a client that misses
+        // the ack can reconcile from `message_create` or history.
+        SubscribeAckEnqueueResult::Oversized => Ok(()),
+        SubscribeAckEnqueueResult::Full | SubscribeAckEnqueueResult::Closed => {
+            Err(subscribe_ack_error_reason(&enqueue_result).unwrap_or("outbound_queue_closed"))
+        }
+    }
 }
 
 pub(crate) async fn execute_subscribe_command(
@@ -328,7 +535,7 @@ pub(crate) async fn execute_subscribe_command(
     user_id: UserId,
     client_ip: ClientIp,
     subscribe: GatewaySubscribeCommand,
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
 ) -> Result<(), &'static str> {
     let GatewaySubscribeCommand {
         guild_id,
@@ -360,7 +567,7 @@ pub(crate) async fn execute_subscribe_command(
     add_subscription(
         state,
         connection_id,
-        subscription_key.into_string(),
+        subscription_key.into_channel_key(),
         outbound_tx.clone(),
     )
     .await;
@@ -412,6 +619,159 @@ pub(crate) async fn execute_subscribe_command(
     Ok(())
 }
 
+/// Replaces the connection's event filter. Only fanout is filtered; replies to
+/// this connection's own commands are always delivered.
+pub(crate) async fn execute_set_filters_command(
+    state: &AppState,
+    connection_id: Uuid,
+    request: GatewaySetFiltersCommand,
+) {
+    let mut event_filters = state
+        .realtime_registry
+        .connection_event_filters()
+        .write()
+        .await;
+    match request.event_types {
+        Some(event_types) => {
+            event_filters.insert(connection_id, event_types);
+        }
+        None => {
+            event_filters.remove(&connection_id);
+        }
+    }
+}
+
+/// Answers a `history_request` with a `history` event on this connection.
+/// Access is checked exactly like `GET .../messages`; a rejected request is
+/// logged and dropped without closing the socket.
+pub(crate) async fn execute_history_request_command(
+    state: &AppState,
+    connection_id: Uuid,
+    user_id: UserId,
+    client_ip: ClientIp,
+    request: GatewayHistoryRequestCommand,
+    outbound_tx: &OutboundSender,
+) -> Result<(), &'static str> {
+    let guild_id = request.guild_id.as_str();
+    let channel_id = request.channel_id.as_str();
+    if enforce_guild_ip_ban_for_request(
+        state,
+        guild_id,
+        user_id,
+        client_ip,
+        "gateway.history_request",
+    )
+    .await
+    .is_err()
+    {
+        return Err("ip_banned");
+    }
+
+    let query = HistoryQuery {
+        limit: request.limit,
+        before: request.before,
+        include_deleted: false,
+        sort: HistorySort::Recent,
+    };
+    let history = match load_message_history(state, user_id, guild_id, channel_id, &query).await {
+        Ok(history) => history,
+        Err(error) => {
+            tracing::warn!(
+                event = "gateway.history_request.rejected",
+                connection_id = %connection_id,
+                user_id = %user_id,
+                guild_id,
+                channel_id,
+                reject_reason = %error,
+            );
+            return Ok(());
+        }
+    };
+
+    let history_event = match gateway_events::try_history(guild_id, channel_id, &history) {
+        Ok(event) => event,
+        Err(error) => {
+            tracing::error!(
+                event = "gateway.history.serialize_failed",
+                connection_id = %connection_id,
+                user_id = %user_id,
+                guild_id,
+                channel_id,
+                error = %error
+            );
+            record_gateway_event_dropped(
+                "connection",
+                gateway_events::HISTORY_EVENT,
+                "serialize_error",
+            );
+            return Ok(());
+        }
+    };
+    let enqueue_result = try_enqueue_subscribed_event(
+        outbound_tx,
+        history_event.payload,
+        state.runtime.max_gateway_event_bytes,
+    );
+    if let Some(reason) = subscribe_ack_drop_metric_reason(&enqueue_result) {
+        record_gateway_event_dropped("connection", history_event.event_type, reason);
+        tracing::warn!(
+            event = "gateway.history.enqueue_rejected",
+            connection_id = %connection_id,
+            user_id = %user_id,
+            guild_id,
+            channel_id,
+            reason
+        );
+    }
+    match enqueue_result {
+        SubscribeAckEnqueueResult::Enqueued => {
+            record_gateway_event_emitted("connection", history_event.event_type);
+            Ok(())
+        }
+        // Too many large messages for one frame: the client can page with a
+        // smaller limit or fall back to HTTP, so keep the session open.
+        SubscribeAckEnqueueResult::Oversized => Ok(()),
+        SubscribeAckEnqueueResult::Full | SubscribeAckEnqueueResult::Closed => {
+            Err(subscribe_ack_error_reason(&enqueue_result).unwrap_or("outbound_queue_closed"))
+        }
+    }
+}
+
+/// Re-runs the subscribe-time access checks for every channel this connection
+/// listens to and drops the ones the user has since lost (ban, kick, or a
+/// permission change), so revoked users stop receiving channel events.
+pub(crate) async fn revalidate_connection_subscriptions(
+    state: &AppState,
+    connection_id: Uuid,
+    user_id: UserId,
+    client_ip: ClientIp,
+) {
+    for key in connection_subscription_keys(state, connection_id).await {
+        let still_allowed = enforce_guild_ip_ban_for_request(
+            state,
+            key.guild_id(),
+            user_id,
+            client_ip,
+            "gateway.subscribe_revalidate",
+        )
+        .await
+        .is_ok()
+            && user_can_write_channel(state, user_id, key.guild_id(), key.channel_id()).await;
+        if still_allowed {
+            continue;
+        }
+        remove_subscription(state, connection_id, &key).await;
+        tracing::info!(
+            event = "gateway.subscription.revoked",
+            connection_id = %connection_id,
+            user_id = %user_id,
+            guild_id = key.guild_id(),
+            channel_id = key.channel_id(),
+            "gateway subscription dropped after access was revoked",
+        );
+    }
+}
+
 pub(crate) fn subscribe_ack_error_reason(
     result: &SubscribeAckEnqueueResult,
 ) -> Option<&'static str> {
@@ -497,7 +857,7 @@ pub(crate) fn allow_gateway_ingress(
 #[cfg(test)]
 mod tests {
     use std::{
-        collections::VecDeque,
+        collections::{HashSet, VecDeque},
         time::{Duration, Instant},
     };
 
@@ -512,8 +872,9 @@ mod tests {
         GatewayIngressCommandParseError, GatewayIngressMessageDecode,
         IngressCommandParseClassification, SubscribeAckEnqueueResult,
     };
+    use crate::server::core::ChannelKey;
+    use crate::server::realtime::unbudgeted_outbound_queue;
     use axum::extract::ws::Message;
-    use tokio::sync::mpsc;
 
     fn envelope(event_type: &str, payload: serde_json::Value) -> Envelope<serde_json::Value> {
         Envelope {
@@ -539,11 +900,13 @@ mod tests {
                 assert_eq!(subscribe.guild_id.as_str(), "01JYQ4V2YQ8B4FW9P51TE5Z1JK");
                 assert_eq!(subscribe.channel_id.as_str(), "01JYQ4V3E2BTRWCHKRHV9K8HXT");
                 assert_eq!(
-                    subscribe.subscription_key.into_string(),
-                    "01JYQ4V2YQ8B4FW9P51TE5Z1JK:01JYQ4V3E2BTRWCHKRHV9K8HXT"
+                    subscribe.subscription_key.into_channel_key(),
+                    ChannelKey::new("01JYQ4V2YQ8B4FW9P51TE5Z1JK", "01JYQ4V3E2BTRWCHKRHV9K8HXT")
                 );
             }
-            GatewayIngressCommand::MessageCreate(_) => {
+            GatewayIngressCommand::MessageCreate(_)
+            | GatewayIngressCommand::HistoryRequest(_)
+            | GatewayIngressCommand::SetFilters(_) => {
                 panic!("expected subscribe command");
             }
         }
@@ -591,7 +954,9 @@ mod tests {
                     vec![String::from("01JYQ4V3VW1TC0MCC4GY7Q4RPR")]
                 );
             }
-            GatewayIngressCommand::Subscribe(_) => {
+            GatewayIngressCommand::Subscribe(_)
+            | GatewayIngressCommand::HistoryRequest(_)
+            | GatewayIngressCommand::SetFilters(_) => {
                 panic!("expected message_create command");
             }
         }
@@ -624,7 +989,9 @@ mod tests {
                     ]
                 );
             }
-            GatewayIngressCommand::Subscribe(_) => {
+            GatewayIngressCommand::Subscribe(_)
+            | GatewayIngressCommand::HistoryRequest(_)
+            | GatewayIngressCommand::SetFilters(_) => {
                 panic!("expected message_create command");
             }
         }
@@ -651,6 +1018,38 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn message_create_command_carries_a_validated_nonce() {
+        let payload = |nonce: &str| {
+            envelope(
+                "message_create",
+                json!({
+                    "guild_id": "01JYQ4V2YQ8B4FW9P51TE5Z1JK",
+                    "channel_id": "01JYQ4V3E2BTRWCHKRHV9K8HXT",
+                    "content": "hello",
+                    "nonce": nonce
+                }),
+            )
+        };
+
+        match GatewayIngressCommand::try_from(payload("client-nonce-1"))
+            .expect("nonce should parse")
+        {
+            GatewayIngressCommand::MessageCreate(request) => {
+                assert_eq!(request.nonce.as_deref(), Some("client-nonce-1"));
+            }
+            GatewayIngressCommand::Subscribe(_)
+            | GatewayIngressCommand::HistoryRequest(_)
+            | GatewayIngressCommand::SetFilters(_) => {
+                panic!("expected message_create")
+            }
+        }
+        assert!(matches!(
+            GatewayIngressCommand::try_from(payload("has space")),
+            Err(GatewayIngressCommandParseError::InvalidMessageCreatePayload)
+        ));
+    }
+
     #[test]
     fn rejects_invalid_subscribe_payload() {
         let error = parse_gateway_ingress_command(envelope(
@@ -722,7 +1121,9 @@ mod tests {
             GatewayIngressCommand::MessageCreate(request) => {
                 assert!(request.attachment_ids.into_vec().is_empty());
             }
-            GatewayIngressCommand::Subscribe(_) => {
+            GatewayIngressCommand::Subscribe(_)
+            | GatewayIngressCommand::HistoryRequest(_)
+            | GatewayIngressCommand::SetFilters(_) => {
                 panic!("expected message_create command");
             }
         }
@@ -808,7 +1209,9 @@ mod tests {
                     vec![String::from("01JYQ4V3VW1TC0MCC4GY7Q4RPR")]
                 );
             }
-            GatewayIngressCommand::Subscribe(_) => {
+            GatewayIngressCommand::Subscribe(_)
+            | GatewayIngressCommand::HistoryRequest(_)
+            | GatewayIngressCommand::SetFilters(_) => {
                 panic!("expected message_create command");
             }
         }
@@ -832,6 +1235,98 @@ mod tests {
         assert_eq!(error.disconnect_reason(), "invalid_subscribe_payload");
     }
 
+    #[test]
+    fn parses_history_request_command() {
+        let command = parse_gateway_ingress_command(envelope(
+            "history_request",
+            json!({
+                "guild_id": "01JYQ4V2YQ8B4FW9P51TE5Z1JK",
+                "channel_id": "01JYQ4V3E2BTRWCHKRHV9K8HXT",
+                "limit": 25,
+                "before": "01JYQ4V3VW1TC0MCC4GY7Q4RPR"
+            }),
+        ))
+        .expect("history_request payload should parse");
+
+        let GatewayIngressCommand::HistoryRequest(request) = command else {
+            panic!("expected history_request command");
+        };
+        assert_eq!(request.guild_id.as_str(), "01JYQ4V2YQ8B4FW9P51TE5Z1JK");
+        assert_eq!(request.channel_id.as_str(), "01JYQ4V3E2BTRWCHKRHV9K8HXT");
+        assert_eq!(request.limit, Some(25));
+        assert_eq!(
+            request.before.as_deref(),
+            Some("01JYQ4V3VW1TC0MCC4GY7Q4RPR")
+        );
+    }
+
+    #[test]
+    fn rejects_history_request_with_invalid_cursor_or_unknown_fields() {
+        for payload in [
+            json!({
+                "guild_id": "01JYQ4V2YQ8B4FW9P51TE5Z1JK",
+                "channel_id": "01JYQ4V3E2BTRWCHKRHV9K8HXT",
+                "before": "not-a-ulid"
+            }),
+            json!({
+                "guild_id": "01JYQ4V2YQ8B4FW9P51TE5Z1JK",
+                "channel_id": "01JYQ4V3E2BTRWCHKRHV9K8HXT",
+                "include_deleted": true
+            }),
+        ] {
+            let error = parse_gateway_ingress_command(envelope("history_request", payload))
+                .expect_err("invalid history_request should fail");
+            assert!(matches!(
+                error,
+                GatewayIngressCommandParseError::InvalidHistoryRequestPayload
+            ));
+            assert_eq!(error.disconnect_reason(), "invalid_history_request_payload");
+        }
+    }
+
+    #[test]
+    fn parses_set_filters_command_and_clear() {
+        let command = parse_gateway_ingress_command(envelope(
+            "set_filters",
+            json!({"event_types": ["message_create", "message_create", "presence_update"]}),
+        ))
+        .expect("set_filters payload should parse");
+        let GatewayIngressCommand::SetFilters(request) = command else {
+            panic!("expected set_filters command");
+        };
+        assert_eq!(
+            request.event_types,
+            Some(HashSet::from(["message_create", "presence_update"]))
+        );
+
+        for payload in [json!({}), json!({"event_types": null})] {
+            let command = parse_gateway_ingress_command(envelope("set_filters", payload))
+                .expect("clearing set_filters payload should parse");
+            let GatewayIngressCommand::SetFilters(request) = command else {
+                panic!("expected set_filters command");
+            };
+            assert_eq!(request.event_types, None);
+        }
+    }
+
+    #[test]
+    fn rejects_set_filters_with_unknown_or_connection_events() {
+        for payload in [
+            json!({"event_types": ["typing_start"]}),
+            json!({"event_types": ["history"]}),
+            json!({"event_types": ["message_create"], "mode": "allow"}),
+            json!({"event_types": "message_create"}),
+        ] {
+            let error = parse_gateway_ingress_command(envelope("set_filters", payload))
+                .expect_err("invalid set_filters should fail");
+            assert!(matches!(
+                error,
+                GatewayIngressCommandParseError::InvalidSetFiltersPayload
+            ));
+            assert_eq!(error.disconnect_reason(), "invalid_set_filters_payload");
+        }
+    }
+
     #[test]
     fn rejects_unknown_event_type() {
         let error = parse_gateway_ingress_command(envelope("presence_sync", json!({})))
@@ -842,7 +1337,9 @@ mod tests {
                 assert_eq!(event_type, "presence_sync");
             }
             GatewayIngressCommandParseError::InvalidSubscribePayload
-            | GatewayIngressCommandParseError::InvalidMessageCreatePayload => {
+            | GatewayIngressCommandParseError::InvalidMessageCreatePayload
+            | GatewayIngressCommandParseError::InvalidHistoryRequestPayload
+            | GatewayIngressCommandParseError::InvalidSetFiltersPayload => {
                 panic!("expected unknown event type error")
             }
         }
@@ -1054,7 +1551,7 @@ mod tests {
 
     #[test]
     fn try_enqueue_subscribed_event_returns_enqueued_when_sender_has_capacity() {
-        let (tx, _rx) = mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_subscribed_event(&tx, String::from("payload"), 1024);
 
@@ -1063,7 +1560,7 @@ mod tests {
 
     #[test]
     fn try_enqueue_subscribed_event_returns_full_when_sender_is_full() {
-        let (tx, rx) = mpsc::channel::<String>(1);
+        let (tx, rx) = unbudgeted_outbound_queue(1);
         tx.try_send(String::from("first"))
             .expect("first send should fill queue");
 
@@ -1075,7 +1572,7 @@ mod tests {
 
     #[test]
     fn try_enqueue_subscribed_event_returns_closed_when_sender_is_closed() {
-        let (tx, rx) = mpsc::channel::<String>(1);
+        let (tx, rx) = unbudgeted_outbound_queue(1);
         drop(rx);
         let closed_result = try_enqueue_subscribed_event(&tx, String::from("third"), 1024);
         assert!(matches!(closed_result, SubscribeAckEnqueueResult::Closed));
@@ -1083,7 +1580,7 @@ mod tests {
 
     #[test]
     fn try_enqueue_subscribed_event_returns_oversized_when_payload_exceeds_limit() {
-        let (tx, _rx) = mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_subscribed_event(&tx, String::from("payload"), 3);
 
@@ -152,7 +152,10 @@ async fn user_lacks_subscribe_permission(
     {
         Ok((_, permissions)) => !permissions.contains(filament_core::Permission::SubscribeStreams),
         Err(err) => {
-            if !matches!(err, AuthFailure::Forbidden | AuthFailure::NotFound) {
+            if !matches!(
+                err,
+                AuthFailure::Forbidden | AuthFailure::NotFound | AuthFailure::NotGuildMember
+            ) {
                 tracing::warn!(
                     event = "voice.permission_reevaluate",
                     guild_id = %guild_id,
@@ -207,6 +210,11 @@ mod tests {
             visibility: GuildVisibility::Private,
             created_by_user_id: user_id,
             default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
             members: HashMap::new(),
             banned_members: HashSet::new(),
             channels: HashMap::new(),
@@ -217,6 +225,10 @@ mod tests {
             ChannelRecord {
                 name: String::from("voice"),
                 kind: ChannelKind::Voice,
+                locked: false,
+                retention_secs: None,
+                voting: false,
+                category_id: None,
                 messages: Vec::new(),
                 role_overrides,
             },
@@ -0,0 +1,221 @@
+use filament_core::{tokenize_markdown, Permission, UserId};
+use sqlx::Row;
+
+use crate::server::{
+    core::AppState,
+    domain::{
+        attach_message_embeds, attach_message_media, attach_message_reactions, attach_vote_scores,
+        attachment_map_for_messages_db, attachment_map_for_messages_in_memory,
+        channel_permission_snapshot, message_content_from_row, reaction_map_for_messages_db,
+        reaction_summaries_from_users,
+    },
+    errors::AuthFailure,
+    types::{HistoryQuery, HistorySort, MessageHistoryResponse, MessageResponse},
+};
+
+use super::history_cursor_end;
+
+/// Loads one page of channel history for `user_id`, enforcing the same limit
+/// and permission checks for REST and gateway `history_request` callers.
+#[allow(clippy::too_many_lines)]
+pub(crate) async fn load_message_history(
+    state: &AppState,
+    user_id: UserId,
+    guild_id: &str,
+    channel_id: &str,
+    query: &HistoryQuery,
+) -> Result<MessageHistoryResponse, AuthFailure> {
+    let limit = query.limit.unwrap_or(state.runtime.history_default_limit);
+    if limit == 0 || limit > state.runtime.history_max_limit {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    let (_, permissions) =
+        channel_permission_snapshot(state, user_id, guild_id, channel_id).await?;
+    if !permissions.contains(Permission::CreateMessage) {
+        return Err(AuthFailure::Forbidden);
+    }
+    if query.include_deleted && !permissions.contains(Permission::DeleteMessage) {
+        return Err(AuthFailure::Forbidden);
+    }
+
+    if let Some(pool) = &state.db_pool {
+        let voting: bool = sqlx::query(
+            "SELECT voting
+             FROM channels
+             WHERE guild_id = $1 AND channel_id = $2",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?
+        .ok_or(AuthFailure::NotFound)?
+        .try_get("voting")
+        .map_err(|_| AuthFailure::Internal)?;
+        if query.sort == HistorySort::Score && !voting {
+            return Err(AuthFailure::InvalidRequest);
+        }
+        let limit_i64 = i64::try_from(limit).map_err(|_| AuthFailure::InvalidRequest)?;
+        let rows = sqlx::query(
+            "SELECT message_id, author_id, content, content_zstd, content_sealed, created_at_unix,
+                    deleted_at_unix, edited_at_unix, system
+             FROM messages
+             WHERE guild_id = $1 AND channel_id = $2 AND ($3::text IS NULL OR message_id < $3)
+               AND ($5 OR deleted_at_unix IS NULL)
+             ORDER BY message_id DESC
+             LIMIT $4",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(query.before.clone())
+        .bind(limit_i64)
+        .bind(query.include_deleted)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal)?;
+
+        let mut messages = Vec::with_capacity(rows.len());
+        for row in rows {
+            let message_id: String = row
+                .try_get("message_id")
+                .map_err(|_| AuthFailure::Internal)?;
+            let author_id: String = row
+                .try_get("author_id")
+                .map_err(|_| AuthFailure::Internal)?;
+            let content = message_content_from_row(
+                &row,
+                &message_id,
+                state.message_content_cipher.as_deref(),
+            )?;
+            let created_at_unix: i64 = row
+                .try_get("created_at_unix")
+                .map_err(|_| AuthFailure::Internal)?;
+            let deleted_at_unix: Option<i64> = row
+                .try_get("deleted_at_unix")
+                .map_err(|_| AuthFailure::Internal)?;
+            let edited_at_unix: Option<i64> = row
+                .try_get("edited_at_unix")
+                .map_err(|_| AuthFailure::Internal)?;
+            let system: bool = row.try_get("system").map_err(|_| AuthFailure::Internal)?;
+            messages.push(MessageResponse {
+                message_id,
+                guild_id: guild_id.to_owned(),
+                channel_id: channel_id.to_owned(),
+                author_id,
+                content: content.clone(),
+                markdown_tokens: tokenize_markdown(&content),
+                attachments: Vec::new(),
+                reactions: Vec::new(),
+                embeds: Vec::new(),
+                author_username: None,
+                created_at_unix,
+                deleted: deleted_at_unix.is_some(),
+                system,
+                score: None,
+                edited_at_unix,
+            });
+        }
+        let message_ids: Vec<String> = messages
+            .iter()
+            .map(|message| message.message_id.clone())
+            .collect();
+        let attachment_map =
+            attachment_map_for_messages_db(pool, guild_id, Some(channel_id), &message_ids).await?;
+        let reaction_map = reaction_map_for_messages_db(
+            pool,
+            guild_id,
+            Some(channel_id),
+            &message_ids,
+            Some(user_id),
+        )
+        .await?;
+        attach_message_media(&mut messages, &attachment_map);
+        attach_message_reactions(&mut messages, &reaction_map);
+        attach_message_embeds(state, &mut messages).await?;
+        let next_before = messages.last().map(|message| message.message_id.clone());
+        if voting {
+            apply_vote_order(&mut messages, query.sort);
+        }
+        return Ok(MessageHistoryResponse {
+            messages,
+            next_before,
+        });
+    }
+
+    let guilds = state.membership_store.guilds().read().await;
+    let guild = guilds.get(guild_id).ok_or(AuthFailure::NotFound)?;
+    let channel = guild
+        .channels
+        .get(channel_id)
+        .ok_or(AuthFailure::NotFound)?;
+    let voting = channel.voting;
+    if query.sort == HistorySort::Score && !voting {
+        return Err(AuthFailure::InvalidRequest);
+    }
+
+    let mut messages = Vec::with_capacity(limit);
+    let end = history_cursor_end(&channel.messages, query.before.as_deref());
+
+    for message in channel.messages[..end].iter().rev() {
+        if message.deleted_at_unix.is_some() && !query.include_deleted {
+            continue;
+        }
+
+        if messages.len() >= limit {
+            break;
+        }
+
+        messages.push(MessageResponse {
+            message_id: message.id.clone(),
+            guild_id: guild_id.to_owned(),
+            channel_id: channel_id.to_owned(),
+            author_id: message.author_id.to_string(),
+            content: message.content.clone(),
+            markdown_tokens: message.markdown_tokens.clone(),
+            attachments: Vec::new(),
+            reactions: reaction_summaries_from_users(&message.reactions, Some(user_id)),
+            embeds: Vec::new(),
+            author_username: None,
+            created_at_unix: message.created_at_unix,
+            deleted: message.deleted_at_unix.is_some(),
+            system: message.system,
+            score: None,
+            edited_at_unix: message.edited_at_unix,
+        });
+    }
+
+    let message_ids: Vec<String> = messages
+        .iter()
+        .map(|message| message.message_id.clone())
+        .collect();
+    let attachment_map =
+        attachment_map_for_messages_in_memory(state, guild_id, Some(channel_id), &message_ids)
+            .await;
+    attach_message_media(&mut messages, &attachment_map);
+    drop(guilds);
+    attach_message_embeds(state, &mut messages).await?;
+
+    let next_before = messages.last().map(|message| message.message_id.clone());
+    if voting {
+        apply_vote_order(&mut messages, query.sort);
+    }
+
+    Ok(MessageHistoryResponse {
+        messages,
+        next_before,
+    })
+}
+
+/// Scores a voting channel's page and, for `sort=score`, reorders it by score.
+/// The cursor is taken beforehand, so pages still walk back by message id.
+fn apply_vote_order(messages: &mut [MessageResponse], sort: HistorySort) {
+    attach_vote_scores(messages);
+    if sort == HistorySort::Score {
+        messages.sort_by(|left, right| {
+            right
+                .score
+                .cmp(&left.score)
+                .then_with(|| right.message_id.cmp(&left.message_id))
+        });
+    }
+}
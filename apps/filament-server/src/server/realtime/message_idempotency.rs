@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use filament_core::UserId;
+
+use crate::server::{
+    auth::now_unix,
+    core::{
+        AppState, MessageIdempotencyRecord, MessageIdempotencyStore, MAX_IDEMPOTENCY_KEY_CHARS,
+        MAX_MESSAGE_IDEMPOTENCY_RECORDS, MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER,
+        MESSAGE_IDEMPOTENCY_TTL_SECS,
+    },
+    errors::AuthFailure,
+    types::MessageResponse,
+};
+
+/// Outcome of claiming an idempotency key before creating a message.
+#[derive(Debug)]
+pub(crate) enum IdempotencyClaim {
+    /// No live record existed; the caller must create the message and then
+    /// call [`complete_message_idempotency`] or [`release_message_idempotency`].
+    Claimed,
+    /// The key already produced this message.
+    Replay(Box<MessageResponse>),
+    /// The store, or the user's share of it, is full of live records that
+    /// cannot be evicted, so the request proceeds without duplicate
+    /// protection rather than failing.
+    Untracked,
+}
+
+/// Validates a client-supplied idempotency key: 1 to
+/// `MAX_IDEMPOTENCY_KEY_CHARS` visible ASCII characters.
+pub(crate) fn parse_idempotency_key(value: &str) -> Result<String, AuthFailure> {
+    if value.is_empty()
+        || value.len() > MAX_IDEMPOTENCY_KEY_CHARS
+        || !value.bytes().all(|byte| byte.is_ascii_graphic())
+    {
+        return Err(AuthFailure::InvalidRequest);
+    }
+    Ok(value.to_owned())
+}
+
+fn is_live(record: &MessageIdempotencyRecord, now: i64) -> bool {
+    now.saturating_sub(record.created_at_unix) < MESSAGE_IDEMPOTENCY_TTL_SECS
+}
+
+impl MessageIdempotencyStore {
+    /// Drops expired records for every user.
+    pub(crate) fn prune_expired(&mut self, now: i64) {
+        self.by_user.retain(|_, records| {
+            records.retain(|_, record| is_live(record, now));
+            !records.is_empty()
+        });
+        self.total = self.by_user.values().map(HashMap::len).sum();
+    }
+
+    /// Makes room for one more record from `user_id`. A user at their cap
+    /// first loses expired records, then their oldest completed one, so a
+    /// single busy user never turns off replay protection for anyone else.
+    fn make_room(&mut self, user_id: UserId, now: i64) -> bool {
+        if let Some(records) = self.by_user.get_mut(&user_id) {
+            if records.len() >= MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER {
+                let before = records.len();
+                records.retain(|_, record| is_live(record, now));
+                if records.len() >= MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER {
+                    let oldest = records
+                        .iter()
+                        .filter(|(_, record)| record.response.is_some())
+                        .min_by_key(|(_, record)| record.created_at_unix)
+                        .map(|(key, _)| key.clone());
+                    if let Some(oldest) = oldest {
+                        records.remove(&oldest);
+                    }
+                }
+                let full = records.len() >= MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER;
+                self.total -= before - records.len();
+                if full {
+                    return false;
+                }
+            }
+        }
+        if self.total >= MAX_MESSAGE_IDEMPOTENCY_RECORDS {
+            self.prune_expired(now);
+        }
+        self.total < MAX_MESSAGE_IDEMPOTENCY_RECORDS
+    }
+}
+
+/// Claims `key` for a message create by `user_id` in one channel. A key still
+/// in flight answers `IdempotencyConflict`; a key first used in another
+/// channel is rejected as an invalid request.
+pub(crate) async fn claim_message_idempotency(
+    state: &AppState,
+    user_id: UserId,
+    key: &str,
+    guild_id: &str,
+    channel_id: &str,
+) -> Result<IdempotencyClaim, AuthFailure> {
+    let now = now_unix();
+    let mut store = state.message_idempotency.write().await;
+    if let Some(record) = store
+        .by_user
+        .get(&user_id)
+        .and_then(|records| records.get(key))
+        .filter(|record| is_live(record, now))
+    {
+        if record.guild_id != guild_id || record.channel_id != channel_id {
+            return Err(AuthFailure::InvalidRequest);
+        }
+        return match &record.response {
+            Some(response) => Ok(IdempotencyClaim::Replay(Box::new(response.clone()))),
+            None => Err(AuthFailure::IdempotencyConflict),
+        };
+    }
+    if !store.make_room(user_id, now) {
+        return Ok(IdempotencyClaim::Untracked);
+    }
+    let replaced = store.by_user.entry(user_id).or_default().insert(
+        key.to_owned(),
+        MessageIdempotencyRecord {
+            guild_id: guild_id.to_owned(),
+            channel_id: channel_id.to_owned(),
+            created_at_unix: now,
+            response: None,
+        },
+    );
+    if replaced.is_none() {
+        store.total += 1;
+    }
+    Ok(IdempotencyClaim::Claimed)
+}
+
+/// Stores the created message so retries with the same key replay it.
+pub(crate) async fn complete_message_idempotency(
+    state: &AppState,
+    user_id: UserId,
+    key: &str,
+    response: &MessageResponse,
+) {
+    if let Some(record) = state
+        .message_idempotency
+        .write()
+        .await
+        .by_user
+        .get_mut(&user_id)
+        .and_then(|records| records.get_mut(key))
+    {
+        record.response = Some(response.clone());
+    }
+}
+
+/// Drops a claim whose create failed so the client can retry with the key.
+pub(crate) async fn release_message_idempotency(state: &AppState, user_id: UserId, key: &str) {
+    let mut store = state.message_idempotency.write().await;
+    let Some(records) = store.by_user.get_mut(&user_id) else {
+        return;
+    };
+    let removed = records.remove(key).is_some();
+    if records.is_empty() {
+        store.by_user.remove(&user_id);
+    }
+    if removed {
+        store.total -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use filament_core::UserId;
+
+    use super::{
+        claim_message_idempotency, complete_message_idempotency, parse_idempotency_key,
+        release_message_idempotency, IdempotencyClaim,
+    };
+    use crate::server::{
+        core::{
+            AppConfig, AppState, MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER,
+            MESSAGE_IDEMPOTENCY_TTL_SECS,
+        },
+        errors::AuthFailure,
+        types::MessageResponse,
+    };
+
+    fn response(message_id: &str) -> MessageResponse {
+        MessageResponse {
+            message_id: String::from(message_id),
+            guild_id: String::from("g1"),
+            channel_id: String::from("c1"),
+            author_id: String::from("u1"),
+            content: String::from("hello"),
+            markdown_tokens: Vec::new(),
+            attachments: Vec::new(),
+            reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
+            created_at_unix: 1,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
+        }
+    }
+
+    #[test]
+    fn parse_idempotency_key_accepts_visible_ascii_only() {
+        assert_eq!(
+            parse_idempotency_key("retry-01JYQ4V3VW").unwrap(),
+            "retry-01JYQ4V3VW"
+        );
+        let too_long = "k".repeat(65);
+        for value in ["", "has space", "caf\u{e9}", too_long.as_str()] {
+            assert!(matches!(
+                parse_idempotency_key(value),
+                Err(AuthFailure::InvalidRequest)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn claims_replay_completed_messages_and_reject_in_flight_keys() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let user_id = UserId::new();
+
+        assert!(matches!(
+            claim_message_idempotency(&state, user_id, "k1", "g1", "c1").await,
+            Ok(IdempotencyClaim::Claimed)
+        ));
+        assert!(matches!(
+            claim_message_idempotency(&state, user_id, "k1", "g1", "c1").await,
+            Err(AuthFailure::IdempotencyConflict)
+        ));
+        assert!(matches!(
+            claim_message_idempotency(&state, UserId::new(), "k1", "g1", "c1").await,
+            Ok(IdempotencyClaim::Claimed)
+        ));
+
+        complete_message_idempotency(&state, user_id, "k1", &response("m1")).await;
+        match claim_message_idempotency(&state, user_id, "k1", "g1", "c1").await {
+            Ok(IdempotencyClaim::Replay(replayed)) => assert_eq!(replayed.message_id, "m1"),
+            other => panic!("expected replay, got {other:?}"),
+        }
+        assert!(matches!(
+            claim_message_idempotency(&state, user_id, "k1", "g1", "c2").await,
+            Err(AuthFailure::InvalidRequest)
+        ));
+    }
+
+    #[tokio::test]
+    async fn released_and_expired_keys_can_be_claimed_again() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let user_id = UserId::new();
+
+        claim_message_idempotency(&state, user_id, "k1", "g1", "c1")
+            .await
+            .unwrap();
+        release_message_idempotency(&state, user_id, "k1").await;
+        assert!(matches!(
+            claim_message_idempotency(&state, user_id, "k1", "g1", "c1").await,
+            Ok(IdempotencyClaim::Claimed)
+        ));
+
+        complete_message_idempotency(&state, user_id, "k1", &response("m1")).await;
+        for records in state.message_idempotency.write().await.by_user.values_mut() {
+            for record in records.values_mut() {
+                record.created_at_unix -= MESSAGE_IDEMPOTENCY_TTL_SECS;
+            }
+        }
+        assert!(matches!(
+            claim_message_idempotency(&state, user_id, "k1", "g1", "c1").await,
+            Ok(IdempotencyClaim::Claimed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_full_user_evicts_only_their_own_oldest_completed_key() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let busy = UserId::new();
+        let other = UserId::new();
+
+        claim_message_idempotency(&state, other, "k1", "g1", "c1")
+            .await
+            .unwrap();
+        complete_message_idempotency(&state, other, "k1", &response("other")).await;
+        for index in 0..MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER {
+            let key = format!("k{index}");
+            claim_message_idempotency(&state, busy, &key, "g1", "c1")
+                .await
+                .unwrap();
+            complete_message_idempotency(&state, busy, &key, &response(&key)).await;
+        }
+        if let Some(record) = state
+            .message_idempotency
+            .write()
+            .await
+            .by_user
+            .get_mut(&busy)
+            .and_then(|records| records.get_mut("k0"))
+        {
+            record.created_at_unix -= 1;
+        }
+
+        assert!(matches!(
+            claim_message_idempotency(&state, busy, "overflow", "g1", "c1").await,
+            Ok(IdempotencyClaim::Claimed)
+        ));
+        assert!(matches!(
+            claim_message_idempotency(&state, busy, "k0", "g1", "c1").await,
+            Ok(IdempotencyClaim::Claimed)
+        ));
+        assert!(matches!(
+            claim_message_idempotency(&state, other, "k1", "g1", "c1").await,
+            Ok(IdempotencyClaim::Replay(_))
+        ));
+        let store = state.message_idempotency.read().await;
+        assert_eq!(
+            store.by_user[&busy].len(),
+            MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER
+        );
+        assert_eq!(store.total, MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER + 1);
+    }
+
+    #[tokio::test]
+    async fn a_user_with_only_in_flight_keys_is_left_untracked() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let busy = UserId::new();
+        for index in 0..MAX_MESSAGE_IDEMPOTENCY_RECORDS_PER_USER {
+            claim_message_idempotency(&state, busy, &format!("k{index}"), "g1", "c1")
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(
+            claim_message_idempotency(&state, busy, "overflow", "g1", "c1").await,
+            Ok(IdempotencyClaim::Untracked)
+        ));
+        assert!(matches!(
+            claim_message_idempotency(&state, UserId::new(), "k1", "g1", "c1").await,
+            Ok(IdempotencyClaim::Claimed)
+        ));
+    }
+}
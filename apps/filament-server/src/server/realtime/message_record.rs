@@ -24,6 +24,9 @@ pub(crate) fn build_in_memory_message_record(
         attachment_ids,
         created_at_unix,
         reactions: HashMap::new(),
+        deleted_at_unix: None,
+        edited_at_unix: None,
+        system: false,
     }
 }
 
@@ -47,7 +50,13 @@ pub(crate) fn build_db_created_message_response(
         markdown_tokens,
         attachments,
         reactions: Vec::new(),
+        embeds: Vec::new(),
+        author_username: None,
         created_at_unix,
+        deleted: false,
+        system: false,
+        score: None,
+        edited_at_unix: None,
     }
 }
 
@@ -67,7 +76,13 @@ pub(crate) fn build_message_response_from_record(
         markdown_tokens: record.markdown_tokens.clone(),
         attachments,
         reactions,
+        embeds: Vec::new(),
+        author_username: None,
         created_at_unix: record.created_at_unix,
+        deleted: false,
+        system: record.system,
+        score: None,
+        edited_at_unix: record.edited_at_unix,
     }
 }
 
@@ -78,38 +93,86 @@ pub(crate) fn bind_message_attachments_in_memory(
     guild_id: &str,
     channel_id: &str,
     owner_id: UserId,
+    max_total_bytes: u64,
 ) -> Result<(), AuthFailure> {
+    let mut total_bytes: u64 = 0;
     for attachment_id in attachment_ids {
-        let Some(attachment) = attachments.get_mut(attachment_id) else {
-            return Err(AuthFailure::InvalidRequest);
+        let Some(attachment) = attachments.get(attachment_id) else {
+            return Err(AuthFailure::AttachmentNotFound);
         };
-        if attachment.guild_id != guild_id
-            || attachment.channel_id != channel_id
-            || attachment.owner_id != owner_id
-            || attachment.message_id.is_some()
-        {
-            return Err(AuthFailure::InvalidRequest);
+        if attachment.guild_id != guild_id || attachment.owner_id != owner_id {
+            return Err(AuthFailure::AttachmentNotFound);
         }
-        attachment.message_id = Some(message_id.to_owned());
+        if attachment.channel_id != channel_id {
+            return Err(AuthFailure::AttachmentWrongChannel);
+        }
+        if attachment.message_id.is_some() {
+            return Err(AuthFailure::AttachmentAlreadyUsed);
+        }
+        total_bytes = total_bytes.saturating_add(attachment.size_bytes);
+    }
+    if total_bytes > max_total_bytes {
+        return Err(AuthFailure::MessageAttachmentsTooLarge);
+    }
+
+    for (position, attachment_id) in attachment_ids.iter().enumerate() {
+        if let Some(attachment) = attachments.get_mut(attachment_id) {
+            attachment.message_id = Some(message_id.to_owned());
+            attachment.message_position = Some(position);
+        }
+    }
+    Ok(())
+}
+
+/// Fails with `InMemoryCapacityReached` once the channel already holds
+/// `max_messages` records, tombstones included.
+pub(crate) fn ensure_channel_message_capacity(
+    guilds: &HashMap<String, GuildRecord>,
+    guild_id: &str,
+    channel_id: &str,
+    max_messages: usize,
+) -> Result<(), AuthFailure> {
+    let channel = guilds
+        .get(guild_id)
+        .and_then(|guild| guild.channels.get(channel_id))
+        .ok_or(AuthFailure::NotFound)?;
+    if channel.messages.len() >= max_messages {
+        return Err(AuthFailure::InMemoryCapacityReached);
     }
     Ok(())
 }
 
+/// Inserts `record` so the channel stays in ascending message id order, the
+/// same order the DB pages by. New ULIDs almost always land at the end.
 pub(crate) fn append_message_record(
     guilds: &mut HashMap<String, GuildRecord>,
     guild_id: &str,
     channel_id: &str,
     record: MessageRecord,
+    max_messages: usize,
 ) -> Result<(), AuthFailure> {
+    ensure_channel_message_capacity(guilds, guild_id, channel_id, max_messages)?;
     let guild = guilds.get_mut(guild_id).ok_or(AuthFailure::NotFound)?;
     let channel = guild
         .channels
         .get_mut(channel_id)
         .ok_or(AuthFailure::NotFound)?;
-    channel.messages.push(record);
+    let index = channel
+        .messages
+        .partition_point(|message| message.id < record.id);
+    channel.messages.insert(index, record);
     Ok(())
 }
 
+/// Returns the index one past the newest message strictly older than
+/// `before`, mirroring the DB's `message_id < $before`. The cursor does not
+/// have to name a message that still exists.
+pub(crate) fn history_cursor_end(messages: &[MessageRecord], before: Option<&str>) -> usize {
+    before.map_or(messages.len(), |before| {
+        messages.partition_point(|message| message.id.as_str() < before)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -119,7 +182,7 @@ mod tests {
     use super::{
         append_message_record, bind_message_attachments_in_memory,
         build_db_created_message_response, build_in_memory_message_record,
-        build_message_response_from_record,
+        build_message_response_from_record, history_cursor_end,
     };
     use crate::server::{
         core::{AttachmentRecord, ChannelRecord, GuildRecord, GuildVisibility, MessageRecord},
@@ -241,6 +304,7 @@ mod tests {
             sha256_hex: String::from("abc"),
             object_key: String::from("obj-1"),
             message_id: message_id.map(String::from),
+            message_position: None,
         }
     }
 
@@ -265,6 +329,7 @@ mod tests {
             "g1",
             "c1",
             owner_id,
+            u64::MAX,
         )
         .expect("attachments should bind");
 
@@ -284,8 +349,9 @@ mod tests {
             "g1",
             "c1",
             owner_id,
+            u64::MAX,
         );
-        assert!(matches!(result, Err(AuthFailure::InvalidRequest)));
+        assert!(matches!(result, Err(AuthFailure::AttachmentNotFound)));
 
         let different_owner = UserId::new();
         let mut attachments = HashMap::from([
@@ -306,8 +372,9 @@ mod tests {
             "g1",
             "c1",
             owner_id,
+            u64::MAX,
         );
-        assert!(matches!(owner_result, Err(AuthFailure::InvalidRequest)));
+        assert!(matches!(owner_result, Err(AuthFailure::AttachmentNotFound)));
 
         let bound_result = bind_message_attachments_in_memory(
             &mut attachments,
@@ -316,19 +383,84 @@ mod tests {
             "g1",
             "c1",
             owner_id,
+            u64::MAX,
         );
-        assert!(matches!(bound_result, Err(AuthFailure::InvalidRequest)));
+        assert!(matches!(
+            bound_result,
+            Err(AuthFailure::AttachmentAlreadyUsed)
+        ));
+
+        let mut attachments = HashMap::from([(
+            String::from("other-channel"),
+            attachment("other-channel", "g1", "c2", owner_id, None),
+        )]);
+        let channel_result = bind_message_attachments_in_memory(
+            &mut attachments,
+            &[String::from("other-channel")],
+            "m1",
+            "g1",
+            "c1",
+            owner_id,
+            u64::MAX,
+        );
+        assert!(matches!(
+            channel_result,
+            Err(AuthFailure::AttachmentWrongChannel)
+        ));
+    }
+
+    #[test]
+    fn bind_message_attachments_in_memory_rejects_total_over_limit_without_binding() {
+        let owner_id = UserId::new();
+        let mut attachments = HashMap::from([
+            (
+                String::from("a1"),
+                attachment("a1", "g1", "c1", owner_id, None),
+            ),
+            (
+                String::from("a2"),
+                attachment("a2", "g1", "c1", owner_id, None),
+            ),
+        ]);
+        let ids = [String::from("a1"), String::from("a2")];
+
+        let result = bind_message_attachments_in_memory(
+            &mut attachments,
+            &ids,
+            "m1",
+            "g1",
+            "c1",
+            owner_id,
+            23,
+        );
+        assert!(matches!(
+            result,
+            Err(AuthFailure::MessageAttachmentsTooLarge)
+        ));
+        assert!(attachments["a1"].message_id.is_none());
+        assert!(attachments["a2"].message_id.is_none());
+
+        bind_message_attachments_in_memory(&mut attachments, &ids, "m1", "g1", "c1", owner_id, 24)
+            .expect("attachments at the limit should bind");
+        assert_eq!(attachments["a2"].message_id.as_deref(), Some("m1"));
     }
 
     fn sample_record() -> MessageRecord {
+        record_with_id("m1")
+    }
+
+    fn record_with_id(id: &str) -> MessageRecord {
         MessageRecord {
-            id: String::from("m1"),
+            id: String::from(id),
             author_id: UserId::new(),
             content: String::from("hello"),
             markdown_tokens: Vec::new(),
             attachment_ids: Vec::new(),
             created_at_unix: 1,
             reactions: HashMap::new(),
+            deleted_at_unix: None,
+            edited_at_unix: None,
+            system: false,
         }
     }
 
@@ -340,6 +472,11 @@ mod tests {
             visibility: GuildVisibility::Private,
             created_by_user_id: UserId::new(),
             default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
             members: HashMap::new(),
             banned_members: std::collections::HashSet::new(),
             channels: HashMap::new(),
@@ -349,13 +486,17 @@ mod tests {
             ChannelRecord {
                 name: String::from("general"),
                 kind: filament_core::ChannelKind::Text,
+                locked: false,
+                retention_secs: None,
+                voting: false,
+                category_id: None,
                 messages: Vec::new(),
                 role_overrides: HashMap::new(),
             },
         );
         guilds.insert(String::from("g1"), guild);
 
-        append_message_record(&mut guilds, "g1", "c1", sample_record())
+        append_message_record(&mut guilds, "g1", "c1", sample_record(), usize::MAX)
             .expect("append should succeed");
 
         let channel = &guilds["g1"].channels["c1"];
@@ -363,11 +504,112 @@ mod tests {
         assert_eq!(channel.messages[0].id, "m1");
     }
 
+    #[test]
+    fn append_message_record_keeps_messages_in_id_order() {
+        let mut guilds = HashMap::from([(
+            String::from("g1"),
+            GuildRecord {
+                name: String::from("Guild"),
+                visibility: GuildVisibility::Private,
+                created_by_user_id: UserId::new(),
+                default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
+                members: HashMap::new(),
+                banned_members: std::collections::HashSet::new(),
+                channels: HashMap::from([(
+                    String::from("c1"),
+                    ChannelRecord {
+                        name: String::from("general"),
+                        kind: filament_core::ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
+                        messages: Vec::new(),
+                        role_overrides: HashMap::new(),
+                    },
+                )]),
+            },
+        )]);
+
+        for id in ["m2", "m4", "m1", "m3"] {
+            append_message_record(&mut guilds, "g1", "c1", record_with_id(id), usize::MAX)
+                .expect("append should succeed");
+        }
+
+        let ids: Vec<&str> = guilds["g1"].channels["c1"]
+            .messages
+            .iter()
+            .map(|message| message.id.as_str())
+            .collect();
+        assert_eq!(ids, ["m1", "m2", "m3", "m4"]);
+    }
+
+    #[test]
+    fn append_message_record_rejects_channel_at_capacity() {
+        let mut guilds = HashMap::from([(
+            String::from("g1"),
+            GuildRecord {
+                name: String::from("Guild"),
+                visibility: GuildVisibility::Private,
+                created_by_user_id: UserId::new(),
+                default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
+                members: HashMap::new(),
+                banned_members: std::collections::HashSet::new(),
+                channels: HashMap::from([(
+                    String::from("c1"),
+                    ChannelRecord {
+                        name: String::from("general"),
+                        kind: filament_core::ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
+                        messages: Vec::new(),
+                        role_overrides: HashMap::new(),
+                    },
+                )]),
+            },
+        )]);
+
+        for id in ["m1", "m2"] {
+            append_message_record(&mut guilds, "g1", "c1", record_with_id(id), 2)
+                .expect("append under the ceiling should succeed");
+        }
+        let error = append_message_record(&mut guilds, "g1", "c1", record_with_id("m3"), 2)
+            .expect_err("append at the ceiling should fail");
+        assert!(matches!(error, AuthFailure::InMemoryCapacityReached));
+        assert_eq!(guilds["g1"].channels["c1"].messages.len(), 2);
+    }
+
+    #[test]
+    fn history_cursor_end_matches_strictly_older_ids() {
+        let messages: Vec<MessageRecord> =
+            ["m1", "m3", "m5"].into_iter().map(record_with_id).collect();
+
+        assert_eq!(history_cursor_end(&messages, None), 3);
+        assert_eq!(history_cursor_end(&messages, Some("m5")), 2);
+        assert_eq!(history_cursor_end(&messages, Some("m4")), 2);
+        assert_eq!(history_cursor_end(&messages, Some("m1")), 0);
+        assert_eq!(history_cursor_end(&messages, Some("m0")), 0);
+        assert_eq!(history_cursor_end(&messages, Some("m9")), 3);
+    }
+
     #[test]
     fn append_message_record_rejects_unknown_guild_or_channel() {
         let mut guilds = HashMap::new();
-        let error = append_message_record(&mut guilds, "missing", "c1", sample_record())
-            .expect_err("missing guild should fail closed");
+        let error =
+            append_message_record(&mut guilds, "missing", "c1", sample_record(), usize::MAX)
+                .expect_err("missing guild should fail closed");
         assert!(matches!(error, AuthFailure::NotFound));
 
         let mut guild = GuildRecord {
@@ -375,6 +617,11 @@ mod tests {
             visibility: GuildVisibility::Private,
             created_by_user_id: UserId::new(),
             default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
             members: HashMap::new(),
             banned_members: std::collections::HashSet::new(),
             channels: HashMap::new(),
@@ -384,14 +631,19 @@ mod tests {
             ChannelRecord {
                 name: String::from("other"),
                 kind: filament_core::ChannelKind::Text,
+                locked: false,
+                retention_secs: None,
+                voting: false,
+                category_id: None,
                 messages: Vec::new(),
                 role_overrides: HashMap::new(),
             },
         );
         guilds.insert(String::from("g1"), guild);
 
-        let error = append_message_record(&mut guilds, "g1", "missing", sample_record())
-            .expect_err("missing channel should fail closed");
+        let error =
+            append_message_record(&mut guilds, "g1", "missing", sample_record(), usize::MAX)
+                .expect_err("missing channel should fail closed");
         assert!(matches!(error, AuthFailure::NotFound));
     }
 }
@@ -0,0 +1,220 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[cfg(test)]
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::server::metrics::{
+    record_gateway_outbound_bytes_buffered, record_gateway_outbound_bytes_released,
+};
+
+/// Server-wide cap on payload bytes sitting in gateway outbound queues.
+///
+/// Per-connection queues are bounded by message count only, so many slow
+/// sockets holding large events can still pile up memory. Every queue shares
+/// one budget; once it is spent, connections that already have a backlog get
+/// their sends rejected as if their queue were full, which feeds the regular
+/// slow-consumer strikes and closes the furthest-behind sockets first.
+#[derive(Debug)]
+pub(crate) struct OutboundBufferBudget {
+    buffered_bytes: AtomicUsize,
+    max_bytes: usize,
+}
+
+impl OutboundBufferBudget {
+    pub(crate) fn new(max_bytes: usize) -> Self {
+        Self {
+            buffered_bytes: AtomicUsize::new(0),
+            max_bytes,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes.load(Ordering::Acquire)
+    }
+
+    fn try_reserve(&self, bytes: usize, connection_backlogged: bool) -> bool {
+        let reserved =
+            self.buffered_bytes
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                    let next = current.saturating_add(bytes);
+                    // A caught-up connection may always take one event so an
+                    // exhausted budget only stalls sockets that are behind.
+                    (next <= self.max_bytes || !connection_backlogged).then_some(next)
+                });
+        if reserved.is_ok() {
+            record_gateway_outbound_bytes_buffered(bytes);
+        }
+        reserved.is_ok()
+    }
+
+    fn release(&self, bytes: usize) {
+        self.buffered_bytes.fetch_sub(bytes, Ordering::AcqRel);
+        record_gateway_outbound_bytes_released(bytes);
+    }
+}
+
+/// Sending half of a gateway connection's outbound queue.
+#[derive(Debug, Clone)]
+pub(crate) struct OutboundSender {
+    inner: mpsc::Sender<String>,
+    queued_bytes: Arc<AtomicUsize>,
+    budget: Arc<OutboundBufferBudget>,
+}
+
+impl OutboundSender {
+    pub(crate) fn try_send(&self, payload: String) -> Result<(), TrySendError<String>> {
+        let bytes = payload.len();
+        let backlogged = self.queued_bytes.load(Ordering::Acquire) > 0;
+        if !self.budget.try_reserve(bytes, backlogged) {
+            return Err(TrySendError::Full(payload));
+        }
+        self.queued_bytes.fetch_add(bytes, Ordering::AcqRel);
+        self.inner.try_send(payload).inspect_err(|_| {
+            self.queued_bytes.fetch_sub(bytes, Ordering::AcqRel);
+            self.budget.release(bytes);
+        })
+    }
+}
+
+/// Receiving half of a gateway connection's outbound queue. Dropping it
+/// returns any still-queued bytes to the shared budget.
+#[derive(Debug)]
+pub(crate) struct OutboundReceiver {
+    inner: mpsc::Receiver<String>,
+    queued_bytes: Arc<AtomicUsize>,
+    budget: Arc<OutboundBufferBudget>,
+}
+
+impl OutboundReceiver {
+    pub(crate) async fn recv(&mut self) -> Option<String> {
+        let payload = self.inner.recv().await?;
+        self.release(&payload);
+        Some(payload)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn try_recv(&mut self) -> Result<String, TryRecvError> {
+        let payload = self.inner.try_recv()?;
+        self.release(&payload);
+        Ok(payload)
+    }
+
+    fn release(&self, payload: &str) {
+        self.queued_bytes.fetch_sub(payload.len(), Ordering::AcqRel);
+        self.budget.release(payload.len());
+    }
+}
+
+impl Drop for OutboundReceiver {
+    fn drop(&mut self) {
+        self.inner.close();
+        while let Ok(payload) = self.inner.try_recv() {
+            self.release(&payload);
+        }
+    }
+}
+
+pub(crate) fn outbound_queue(
+    capacity: usize,
+    budget: &Arc<OutboundBufferBudget>,
+) -> (OutboundSender, OutboundReceiver) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let queued_bytes = Arc::new(AtomicUsize::new(0));
+    (
+        OutboundSender {
+            inner: tx,
+            queued_bytes: Arc::clone(&queued_bytes),
+            budget: Arc::clone(budget),
+        },
+        OutboundReceiver {
+            inner: rx,
+            queued_bytes,
+            budget: Arc::clone(budget),
+        },
+    )
+}
+
+/// Outbound queue with a budget large enough that only `capacity` applies.
+#[cfg(test)]
+pub(crate) fn unbudgeted_outbound_queue(capacity: usize) -> (OutboundSender, OutboundReceiver) {
+    outbound_queue(capacity, &Arc::new(OutboundBufferBudget::new(usize::MAX)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::sync::mpsc::error::TrySendError;
+
+    use super::{outbound_queue, OutboundBufferBudget};
+
+    #[tokio::test]
+    async fn queued_bytes_are_released_when_received() {
+        let budget = Arc::new(OutboundBufferBudget::new(64));
+        let (tx, mut rx) = outbound_queue(4, &budget);
+
+        tx.try_send(String::from("hello")).unwrap();
+        tx.try_send(String::from("world!")).unwrap();
+        assert_eq!(budget.buffered_bytes(), 11);
+
+        assert_eq!(rx.recv().await.as_deref(), Some("hello"));
+        assert_eq!(budget.buffered_bytes(), 6);
+        assert_eq!(rx.try_recv().unwrap(), "world!");
+        assert_eq!(budget.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn dropping_receiver_releases_pending_bytes() {
+        let budget = Arc::new(OutboundBufferBudget::new(64));
+        let (tx, rx) = outbound_queue(4, &budget);
+        tx.try_send(String::from("pending")).unwrap();
+        assert_eq!(budget.buffered_bytes(), 7);
+
+        drop(rx);
+        assert_eq!(budget.buffered_bytes(), 0);
+        assert!(matches!(
+            tx.try_send(String::from("late")),
+            Err(TrySendError::Closed(_))
+        ));
+        assert_eq!(budget.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn exhausted_budget_rejects_backlogged_connections_only() {
+        let budget = Arc::new(OutboundBufferBudget::new(10));
+        let (slow_tx, _slow_rx) = outbound_queue(8, &budget);
+        let (idle_tx, _idle_rx) = outbound_queue(8, &budget);
+
+        slow_tx.try_send(String::from("0123456789")).unwrap();
+        assert!(matches!(
+            slow_tx.try_send(String::from("next")),
+            Err(TrySendError::Full(_))
+        ));
+        assert_eq!(budget.buffered_bytes(), 10);
+
+        idle_tx.try_send(String::from("next")).unwrap();
+        assert_eq!(budget.buffered_bytes(), 14);
+        assert!(matches!(
+            idle_tx.try_send(String::from("again")),
+            Err(TrySendError::Full(_))
+        ));
+    }
+
+    #[test]
+    fn full_queue_rolls_back_reserved_bytes() {
+        let budget = Arc::new(OutboundBufferBudget::new(64));
+        let (tx, _rx) = outbound_queue(1, &budget);
+        tx.try_send(String::from("first")).unwrap();
+
+        assert!(matches!(
+            tx.try_send(String::from("second")),
+            Err(TrySendError::Full(_))
+        ));
+        assert_eq!(budget.buffered_bytes(), 5);
+    }
+}
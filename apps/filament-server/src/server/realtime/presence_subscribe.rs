@@ -10,6 +10,8 @@ use crate::server::{
     metrics::{record_gateway_event_dropped, record_gateway_event_emitted},
 };
 
+use super::OutboundSender;
+
 pub(crate) struct PresenceSubscribeResult {
     pub(crate) snapshot_user_ids: HashSet<String>,
     pub(crate) became_online: bool,
@@ -100,7 +102,7 @@ pub(crate) fn apply_presence_subscribe(
 }
 
 pub(crate) fn try_enqueue_presence_sync_event(
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
     payload: String,
     max_gateway_event_bytes: usize,
 ) -> PresenceSyncEnqueueResult {
@@ -126,7 +128,7 @@ pub(crate) fn presence_sync_dispatch_outcome(
 }
 
 pub(crate) fn dispatch_presence_sync_event(
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
     event: GatewayEvent,
     max_gateway_event_bytes: usize,
 ) -> PresenceSyncDispatchOutcome {
@@ -176,6 +178,7 @@ mod tests {
     use crate::server::core::ConnectionPresence;
     use crate::server::gateway_events;
     use crate::server::metrics::metrics_state;
+    use crate::server::realtime::unbudgeted_outbound_queue;
 
     #[test]
     fn inserts_guild_and_marks_first_online_subscription() {
@@ -248,7 +251,7 @@ mod tests {
 
     #[test]
     fn enqueue_presence_sync_event_reports_enqueued() {
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, mut rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_presence_sync_event(&tx, String::from("payload"), 1024);
 
@@ -259,7 +262,7 @@ mod tests {
 
     #[test]
     fn enqueue_presence_sync_event_reports_full() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         assert!(matches!(
             try_enqueue_presence_sync_event(&tx, String::from("first"), 1024),
             PresenceSyncEnqueueResult::Enqueued
@@ -272,7 +275,7 @@ mod tests {
 
     #[test]
     fn enqueue_presence_sync_event_reports_closed() {
-        let (tx, rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, rx) = unbudgeted_outbound_queue(1);
         drop(rx);
 
         let result = try_enqueue_presence_sync_event(&tx, String::from("payload"), 1024);
@@ -282,7 +285,7 @@ mod tests {
 
     #[test]
     fn enqueue_presence_sync_event_reports_oversized() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_presence_sync_event(&tx, String::from("payload"), 3);
 
@@ -311,7 +314,7 @@ mod tests {
 
     #[test]
     fn dispatch_presence_sync_event_returns_emitted_for_open_queue() {
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, mut rx) = unbudgeted_outbound_queue(1);
         let event = gateway_events::try_presence_sync("g-1", HashSet::new())
             .expect("presence_sync event should serialize");
         let expected_payload = event.payload.clone();
@@ -327,7 +330,7 @@ mod tests {
 
     #[test]
     fn dispatch_presence_sync_event_returns_full_for_full_queue() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         tx.try_send(String::from("occupied"))
             .expect("queue should be full");
         let event = gateway_events::try_presence_sync("g-1", HashSet::new())
@@ -340,7 +343,7 @@ mod tests {
 
     #[test]
     fn dispatch_presence_sync_event_returns_closed_for_closed_queue() {
-        let (tx, rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, rx) = unbudgeted_outbound_queue(1);
         drop(rx);
         let event = gateway_events::try_presence_sync("g-1", HashSet::new())
             .expect("presence_sync event should serialize");
@@ -355,7 +358,7 @@ mod tests {
 
     #[test]
     fn dispatch_presence_sync_event_returns_oversized_for_large_payload() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         let event = gateway_events::try_presence_sync("g-1", HashSet::new())
             .expect("presence_sync event should serialize");
 
@@ -380,7 +383,7 @@ mod tests {
             ))
             .copied()
             .unwrap_or(0);
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         let event = gateway_events::try_presence_sync("g-1", HashSet::new())
             .expect("presence_sync event should serialize");
 
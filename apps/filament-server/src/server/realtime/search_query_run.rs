@@ -1,13 +1,14 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
+use sqlx::Row;
 use tantivy::{
-    collector::TopDocs,
+    collector::{Count, TopDocs},
     query::{BooleanQuery, Occur, QueryParser, TermQuery},
     schema::{IndexRecordOption, Value},
-    TantivyDocument, Term,
+    DocAddress, Order, TantivyDocument, Term,
 };
 
-use crate::server::{core::AppState, errors::AuthFailure};
+use crate::server::{core::AppState, errors::AuthFailure, types::SearchSort};
 
 use super::search_runtime;
 
@@ -17,6 +18,7 @@ struct SearchQueryRunInput {
     channel_id: Option<String>,
     query: String,
     limit: usize,
+    sort: SearchSort,
 }
 
 fn build_search_query_run_input(
@@ -24,12 +26,14 @@ fn build_search_query_run_input(
     channel_id: Option<&str>,
     raw_query: &str,
     limit: usize,
+    sort: SearchSort,
 ) -> SearchQueryRunInput {
     SearchQueryRunInput {
         guild_id: guild_id.to_owned(),
         channel_id: channel_id.map(ToOwned::to_owned),
         query: search_runtime::normalize_search_query(raw_query),
         limit,
+        sort,
     }
 }
 
@@ -50,14 +54,12 @@ where
     .map_err(|_| AuthFailure::InvalidRequest)?
 }
 
-pub(crate) fn run_search_query_against_index(
+fn build_guild_search_query(
     search_state: &crate::server::core::SearchIndexState,
     guild_id: &str,
     channel_id: Option<&str>,
     raw_query: &str,
-    limit: usize,
-) -> Result<Vec<String>, AuthFailure> {
-    let searcher = search_state.reader.searcher();
+) -> Result<BooleanQuery, AuthFailure> {
     let parser = QueryParser::for_index(&search_state.index, vec![search_state.fields.content]);
     let parsed = parser
         .parse_query(raw_query)
@@ -82,13 +84,42 @@ pub(crate) fn run_search_query_against_index(
         ));
     }
 
-    let boolean_query = BooleanQuery::from(clauses);
-    let top_docs = searcher
-        .search(&boolean_query, &TopDocs::with_limit(limit))
-        .map_err(|_| AuthFailure::Internal)?;
+    Ok(BooleanQuery::from(clauses))
+}
 
-    let mut message_ids = Vec::with_capacity(top_docs.len());
-    for (_score, address) in top_docs {
+pub(crate) fn run_search_query_against_index(
+    search_state: &crate::server::core::SearchIndexState,
+    guild_id: &str,
+    channel_id: Option<&str>,
+    raw_query: &str,
+    limit: usize,
+    sort: SearchSort,
+) -> Result<Vec<String>, AuthFailure> {
+    let searcher = search_state.reader.searcher();
+    let boolean_query = build_guild_search_query(search_state, guild_id, channel_id, raw_query)?;
+    // `recent` ranks every match by the indexed timestamp rather than
+    // re-sorting the relevance top N, so newer low-scoring hits are not lost.
+    let addresses: Vec<DocAddress> = match sort {
+        SearchSort::Relevance => searcher
+            .search(&boolean_query, &TopDocs::with_limit(limit))
+            .map_err(|_| AuthFailure::Internal)?
+            .into_iter()
+            .map(|(_score, address)| address)
+            .collect(),
+        SearchSort::Recent => searcher
+            .search(
+                &boolean_query,
+                &TopDocs::with_limit(limit)
+                    .order_by_fast_field::<i64>("created_at_unix", Order::Desc),
+            )
+            .map_err(|_| AuthFailure::Internal)?
+            .into_iter()
+            .map(|(_created_at_unix, address)| address)
+            .collect(),
+    };
+
+    let mut message_ids = Vec::with_capacity(addresses.len());
+    for address in addresses {
         let Ok(doc) = searcher.doc::<TantivyDocument>(address) else {
             continue;
         };
@@ -103,47 +134,178 @@ pub(crate) fn run_search_query_against_index(
     Ok(message_ids)
 }
 
+/// Counts matches per channel with one `Count` pass each. Channels without a
+/// match are left out.
+pub(crate) fn count_search_matches_by_channel(
+    search_state: &crate::server::core::SearchIndexState,
+    guild_id: &str,
+    channel_ids: &[String],
+    raw_query: &str,
+) -> Result<BTreeMap<String, usize>, AuthFailure> {
+    let searcher = search_state.reader.searcher();
+    let mut facets = BTreeMap::new();
+    for channel_id in channel_ids {
+        let query = build_guild_search_query(search_state, guild_id, Some(channel_id), raw_query)?;
+        let count = searcher
+            .search(&query, &Count)
+            .map_err(|_| AuthFailure::Internal)?;
+        if count > 0 {
+            facets.insert(channel_id.clone(), count);
+        }
+    }
+    Ok(facets)
+}
+
+/// Lists the channels a faceted search counts: the requested channel alone,
+/// or every channel of the guild.
+pub(crate) async fn search_facet_channel_ids(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: Option<&str>,
+) -> Result<Vec<String>, AuthFailure> {
+    if let Some(channel_id) = channel_id {
+        return Ok(vec![channel_id.to_owned()]);
+    }
+    if let Some(pool) = &state.db_pool {
+        return sqlx::query_scalar::<_, String>(
+            "SELECT channel_id FROM channels WHERE guild_id = $1",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| AuthFailure::Internal);
+    }
+    Ok(state
+        .membership_store
+        .guilds()
+        .read()
+        .await
+        .get(guild_id)
+        .map(|guild| guild.channels.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Clamps a stored per-guild override to the configured maximum, falling back
+/// to the global timeout when the guild has none.
+fn effective_search_query_timeout(
+    override_ms: Option<u64>,
+    default_timeout: Duration,
+    max_timeout: Duration,
+) -> Duration {
+    override_ms.map_or(default_timeout, |millis| {
+        Duration::from_millis(millis).min(max_timeout)
+    })
+}
+
+/// Resolves the query timeout for one guild's index scans.
+pub(crate) async fn search_query_timeout_for_guild(
+    state: &AppState,
+    guild_id: &str,
+) -> Result<Duration, AuthFailure> {
+    let override_ms = if let Some(pool) = &state.db_pool {
+        let row = sqlx::query("SELECT search_query_timeout_ms FROM guilds WHERE guild_id = $1")
+            .bind(guild_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|_| AuthFailure::Internal)?;
+        match row {
+            Some(row) => row
+                .try_get::<Option<i64>, _>("search_query_timeout_ms")
+                .map_err(|_| AuthFailure::Internal)?
+                .and_then(|millis| u64::try_from(millis).ok()),
+            None => None,
+        }
+    } else {
+        state
+            .membership_store
+            .guilds()
+            .read()
+            .await
+            .get(guild_id)
+            .and_then(|guild| guild.search_query_timeout_ms)
+    };
+    Ok(effective_search_query_timeout(
+        override_ms,
+        state.runtime.search_query_timeout,
+        state.runtime.search_query_timeout_max,
+    ))
+}
+
+/// Runs a guild search. With `facet_channel_ids` set, per-channel match counts
+/// are computed in the same blocking task, under the same permit and timeout.
 pub(crate) async fn run_search_query(
     state: &AppState,
     guild_id: &str,
     channel_id: Option<&str>,
     raw_query: &str,
     limit: usize,
-) -> Result<Vec<String>, AuthFailure> {
-    let input = build_search_query_run_input(guild_id, channel_id, raw_query, limit);
-    let search_state = state.search.state.clone();
-    let timeout = state.runtime.search_query_timeout;
+    sort: SearchSort,
+    facet_channel_ids: Option<Vec<String>>,
+) -> Result<(Vec<String>, Option<BTreeMap<String, usize>>), AuthFailure> {
+    let input = build_search_query_run_input(guild_id, channel_id, raw_query, limit, sort);
+    let search_state = search_runtime::search_service(state)?.state.clone();
+    let timeout = search_query_timeout_for_guild(state, guild_id).await?;
+    // Each query occupies a blocking thread; shed load instead of queueing once
+    // every permit is taken. The permit moves into the task so it is held until
+    // the blocking work actually finishes, even if the timeout fires first.
+    let permit = Arc::clone(&state.search_query_permits)
+        .try_acquire_owned()
+        .map_err(|_| AuthFailure::RateLimited)?;
 
     run_search_blocking_with_timeout(timeout, move || {
-        run_search_query_against_index(
+        let _permit = permit;
+        let message_ids = run_search_query_against_index(
             &search_state,
             &input.guild_id,
             input.channel_id.as_deref(),
             &input.query,
             input.limit,
-        )
+            input.sort,
+        )?;
+        let facets = facet_channel_ids
+            .map(|channel_ids| {
+                count_search_matches_by_channel(
+                    &search_state,
+                    &input.guild_id,
+                    &channel_ids,
+                    &input.query,
+                )
+            })
+            .transpose()?;
+        Ok((message_ids, facets))
     })
     .await
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{thread, time::Duration};
+    use std::{sync::Arc, thread, time::Duration};
 
+    use axum::{http::StatusCode, response::IntoResponse};
     use tantivy::TantivyDocument;
 
     use crate::server::errors::AuthFailure;
-    use crate::server::{core::SearchIndexState, realtime::build_search_schema};
+    use crate::server::{
+        core::{AppConfig, AppState, SearchIndexState},
+        realtime::build_search_schema,
+        types::SearchSort,
+    };
 
     use super::{
-        build_search_query_run_input, run_search_blocking_with_timeout,
+        build_search_query_run_input, count_search_matches_by_channel,
+        effective_search_query_timeout, run_search_blocking_with_timeout, run_search_query,
         run_search_query_against_index, SearchQueryRunInput,
     };
 
     #[test]
     fn build_search_query_run_input_trims_and_copies_values() {
-        let input =
-            build_search_query_run_input("guild-1", Some("channel-9"), "  hello world  ", 17);
+        let input = build_search_query_run_input(
+            "guild-1",
+            Some("channel-9"),
+            "  hello world  ",
+            17,
+            SearchSort::Recent,
+        );
 
         assert_eq!(
             input,
@@ -152,13 +314,15 @@ mod tests {
                 channel_id: Some(String::from("channel-9")),
                 query: String::from("hello world"),
                 limit: 17,
+                sort: SearchSort::Recent,
             }
         );
     }
 
     #[test]
     fn build_search_query_run_input_handles_global_channel_scope() {
-        let input = build_search_query_run_input("guild-2", None, "query", 5);
+        let input =
+            build_search_query_run_input("guild-2", None, "query", 5, SearchSort::Relevance);
 
         assert_eq!(input.channel_id, None);
         assert_eq!(input.guild_id, "guild-2");
@@ -166,9 +330,28 @@ mod tests {
         assert_eq!(input.limit, 5);
     }
 
+    #[test]
+    fn guild_timeout_override_is_bounded_by_the_configured_max() {
+        let default_timeout = Duration::from_millis(200);
+        let max_timeout = Duration::from_millis(2_000);
+
+        assert_eq!(
+            effective_search_query_timeout(None, default_timeout, max_timeout),
+            default_timeout
+        );
+        assert_eq!(
+            effective_search_query_timeout(Some(50), default_timeout, max_timeout),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            effective_search_query_timeout(Some(10_000), default_timeout, max_timeout),
+            max_timeout
+        );
+    }
+
     #[tokio::test]
     async fn returns_task_result_before_timeout() {
-        let result = run_search_blocking_with_timeout(Duration::from_millis(100), || Ok(42_i32))
+        let result = run_search_blocking_with_timeout(Duration::from_secs(5), || Ok(42_i32))
             .await
             .expect("task should complete");
 
@@ -186,13 +369,56 @@ mod tests {
         assert!(matches!(result, Err(AuthFailure::InvalidRequest)));
     }
 
+    #[tokio::test]
+    async fn sheds_search_once_every_permit_is_taken() {
+        let state = AppState::new(&AppConfig {
+            search_max_concurrent_queries: 2,
+            ..AppConfig::default()
+        })
+        .expect("state should initialize");
+        let held = Arc::clone(&state.search_query_permits)
+            .try_acquire_many_owned(2)
+            .expect("both permits are free");
+
+        let rejected = run_search_query(
+            &state,
+            "guild-1",
+            None,
+            "hello",
+            10,
+            SearchSort::Relevance,
+            None,
+        )
+        .await
+        .expect_err("a third concurrent search should be shed");
+        assert!(matches!(rejected, AuthFailure::RateLimited));
+        let response = rejected.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(
+            response.headers().get("retry-after").is_none(),
+            "capacity rejections have no known delay"
+        );
+
+        drop(held);
+        let (message_ids, _) = run_search_query(
+            &state,
+            "guild-1",
+            None,
+            "hello",
+            10,
+            SearchSort::Relevance,
+            None,
+        )
+        .await
+        .expect("search runs once a permit frees up");
+        assert!(message_ids.is_empty());
+    }
+
     #[tokio::test]
     async fn maps_task_panic_to_internal_error() {
         let result: Result<i32, AuthFailure> =
-            run_search_blocking_with_timeout(Duration::from_millis(100), || {
-                panic!("simulated panic")
-            })
-            .await;
+            run_search_blocking_with_timeout(Duration::from_secs(5), || panic!("simulated panic"))
+                .await;
 
         assert!(matches!(result, Err(AuthFailure::Internal)));
     }
@@ -238,6 +464,7 @@ mod tests {
             index,
             reader,
             fields,
+            writer_heap_bytes: crate::server::core::DEFAULT_SEARCH_WRITER_HEAP_BYTES,
         }
     }
 
@@ -245,8 +472,9 @@ mod tests {
     fn run_search_query_filters_to_guild() {
         let search = search_state_with_docs();
 
-        let ids = run_search_query_against_index(&search, "g1", None, "rust", 10)
-            .expect("query should succeed");
+        let ids =
+            run_search_query_against_index(&search, "g1", None, "rust", 10, SearchSort::Relevance)
+                .expect("query should succeed");
 
         assert_eq!(ids.len(), 2);
         assert!(ids.iter().any(|id| id == "m1"));
@@ -258,9 +486,48 @@ mod tests {
     fn run_search_query_filters_to_channel_when_provided() {
         let search = search_state_with_docs();
 
-        let ids = run_search_query_against_index(&search, "g1", Some("c2"), "rust", 10)
-            .expect("query should succeed");
+        let ids = run_search_query_against_index(
+            &search,
+            "g1",
+            Some("c2"),
+            "rust",
+            10,
+            SearchSort::Relevance,
+        )
+        .expect("query should succeed");
 
         assert_eq!(ids, vec![String::from("m2")]);
     }
+
+    #[test]
+    fn run_search_query_recent_sort_orders_newest_first() {
+        let search = search_state_with_docs();
+
+        let ids =
+            run_search_query_against_index(&search, "g1", None, "rust", 10, SearchSort::Recent)
+                .expect("query should succeed");
+        assert_eq!(ids, vec![String::from("m2"), String::from("m1")]);
+
+        let newest =
+            run_search_query_against_index(&search, "g1", None, "rust", 1, SearchSort::Recent)
+                .expect("query should succeed");
+        assert_eq!(newest, vec![String::from("m2")]);
+    }
+
+    #[test]
+    fn count_search_matches_by_channel_skips_channels_without_matches() {
+        let search = search_state_with_docs();
+        let channel_ids = vec![String::from("c1"), String::from("c2"), String::from("c3")];
+
+        let facets = count_search_matches_by_channel(&search, "g1", &channel_ids, "rust")
+            .expect("query should succeed");
+        assert_eq!(facets.len(), 2);
+        assert_eq!(facets.get("c1"), Some(&1));
+        assert_eq!(facets.get("c2"), Some(&1));
+
+        let gateway_only = count_search_matches_by_channel(&search, "g1", &channel_ids, "gateway")
+            .expect("query should succeed");
+        assert_eq!(gateway_only.len(), 1);
+        assert_eq!(gateway_only.get("c1"), Some(&1));
+    }
 }
@@ -1,19 +1,24 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Bound};
 
 use tantivy::{
     collector::{Count, TopDocs},
-    query::TermQuery,
+    query::{BooleanQuery, Occur, Query, RangeQuery, TermQuery},
     schema::{IndexRecordOption, Value},
     TantivyDocument, Term,
 };
 
 use crate::server::{
-    core::{AppState, IndexedMessage, SearchIndexState},
+    core::{
+        AppState, IndexedMessage, SearchIndexState, SearchOperation, MAX_SEARCH_RECONCILE_DOCS,
+    },
     errors::AuthFailure,
 };
 
 use super::{
-    collect_indexed_messages_for_guild, search_query_run::run_search_blocking_with_timeout,
+    collect_indexed_messages_page_for_guild, enqueue_search_operation, hydrate_messages_by_id,
+    indexed_message_from_response,
+    search_query_run::{run_search_blocking_with_timeout, search_query_timeout_for_guild},
+    search_runtime::search_service,
 };
 
 pub(crate) fn build_search_reconciliation_plan(
@@ -44,32 +49,43 @@ pub(crate) fn compute_reconciliation(
     (upserts, delete_message_ids)
 }
 
+/// Message id range `(after_message_id, through_message_id]` of one guild;
+/// a missing bound leaves that side of the range open.
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct SearchIndexLookupInput {
     guild_id: String,
+    after_message_id: Option<String>,
+    through_message_id: Option<String>,
     max_docs: usize,
 }
 
-fn build_search_index_lookup_input(guild_id: &str, max_docs: usize) -> SearchIndexLookupInput {
+fn build_search_index_lookup_input(
+    guild_id: &str,
+    after_message_id: Option<&str>,
+    through_message_id: Option<&str>,
+    max_docs: usize,
+) -> SearchIndexLookupInput {
     SearchIndexLookupInput {
         guild_id: guild_id.to_owned(),
+        after_message_id: after_message_id.map(ToOwned::to_owned),
+        through_message_id: through_message_id.map(ToOwned::to_owned),
         max_docs,
     }
 }
 
 async fn collect_index_message_ids_for_guild(
     state: &AppState,
-    guild_id: &str,
-    max_docs: usize,
+    input: SearchIndexLookupInput,
 ) -> Result<HashSet<String>, AuthFailure> {
-    let input = build_search_index_lookup_input(guild_id, max_docs);
-    let search_state = state.search.state.clone();
-    let timeout = state.runtime.search_query_timeout;
+    let search_state = search_service(state)?.state.clone();
+    let timeout = search_query_timeout_for_guild(state, &input.guild_id).await?;
 
     run_search_blocking_with_timeout(timeout, move || {
         collect_index_message_ids_for_guild_from_index(
             &search_state,
             &input.guild_id,
+            input.after_message_id.as_deref(),
+            input.through_message_id.as_deref(),
             input.max_docs,
         )
     })
@@ -79,13 +95,30 @@ async fn collect_index_message_ids_for_guild(
 pub(crate) fn collect_index_message_ids_for_guild_from_index(
     search_state: &SearchIndexState,
     guild_id: &str,
+    after_message_id: Option<&str>,
+    through_message_id: Option<&str>,
     max_docs: usize,
 ) -> Result<HashSet<String>, AuthFailure> {
     let searcher = search_state.reader.searcher();
-    let guild_query = TermQuery::new(
+    let message_id_term =
+        |message_id: &str| Term::from_field_text(search_state.fields.message_id, message_id);
+    let lower =
+        after_message_id.map_or(Bound::Unbounded, |id| Bound::Excluded(message_id_term(id)));
+    let upper =
+        through_message_id.map_or(Bound::Unbounded, |id| Bound::Included(message_id_term(id)));
+    let guild_term_query: Box<dyn Query> = Box::new(TermQuery::new(
         Term::from_field_text(search_state.fields.guild_id, guild_id),
         IndexRecordOption::Basic,
-    );
+    ));
+    let guild_query: Box<dyn Query> =
+        if matches!((&lower, &upper), (Bound::Unbounded, Bound::Unbounded)) {
+            guild_term_query
+        } else {
+            Box::new(BooleanQuery::new(vec![
+                (Occur::Must, guild_term_query),
+                (Occur::Must, Box::new(RangeQuery::new(lower, upper))),
+            ]))
+        };
     let count = searcher
         .search(&guild_query, &Count)
         .map_err(|_| AuthFailure::Internal)?;
@@ -115,14 +148,145 @@ pub(crate) fn collect_index_message_ids_for_guild_from_index(
     Ok(message_ids)
 }
 
-pub(crate) async fn plan_search_reconciliation(
+/// Reconciles a guild's index against its messages one batch of message ids
+/// at a time, applying each batch before reading the next. Returns the total
+/// number of upserted and deleted documents.
+pub(crate) async fn reconcile_guild_search_index(
     state: &AppState,
     guild_id: &str,
-    max_docs: usize,
-) -> Result<(Vec<IndexedMessage>, Vec<String>), AuthFailure> {
-    let source_docs = collect_indexed_messages_for_guild(state, guild_id, max_docs).await?;
-    let index_ids = collect_index_message_ids_for_guild(state, guild_id, max_docs).await?;
-    Ok(build_search_reconciliation_plan(source_docs, index_ids))
+    batch_docs: usize,
+) -> Result<(usize, usize), AuthFailure> {
+    let batch_docs = batch_docs.max(1);
+    let mut after_message_id: Option<String> = None;
+    let mut upserted = 0;
+    let mut deleted = 0;
+
+    loop {
+        let source_docs = collect_indexed_messages_page_for_guild(
+            state,
+            guild_id,
+            after_message_id.as_deref(),
+            batch_docs,
+        )
+        .await?;
+        // A short page is the last one, so its range stays open-ended and
+        // also sweeps index entries past the newest message.
+        let through_message_id = if source_docs.len() < batch_docs {
+            None
+        } else {
+            source_docs.last().map(|doc| doc.message_id.clone())
+        };
+        let index_ids = collect_index_message_ids_for_guild(
+            state,
+            build_search_index_lookup_input(
+                guild_id,
+                after_message_id.as_deref(),
+                through_message_id.as_deref(),
+                MAX_SEARCH_RECONCILE_DOCS,
+            ),
+        )
+        .await?;
+
+        let (upserts, delete_message_ids) =
+            build_search_reconciliation_plan(source_docs, index_ids);
+        upserted += upserts.len();
+        deleted += delete_message_ids.len();
+        if !upserts.is_empty() || !delete_message_ids.is_empty() {
+            enqueue_search_operation(
+                state,
+                SearchOperation::Reconcile {
+                    upserts,
+                    delete_message_ids,
+                },
+                true,
+            )
+            .await?;
+        }
+
+        let Some(through_message_id) = through_message_id else {
+            return Ok((upserted, deleted));
+        };
+        after_message_id = Some(through_message_id);
+    }
+}
+
+pub(crate) fn index_contains_channel_message(
+    search_state: &SearchIndexState,
+    guild_id: &str,
+    channel_id: &str,
+    message_id: &str,
+) -> Result<bool, AuthFailure> {
+    let fields = search_state.fields;
+    let term_query = |field, value: &str| -> (Occur, Box<dyn Query>) {
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, value),
+                IndexRecordOption::Basic,
+            )),
+        )
+    };
+    let query = BooleanQuery::new(vec![
+        term_query(fields.message_id, message_id),
+        term_query(fields.guild_id, guild_id),
+        term_query(fields.channel_id, channel_id),
+    ]);
+    let count = search_state
+        .reader
+        .searcher()
+        .search(&query, &Count)
+        .map_err(|_| AuthFailure::Internal)?;
+    Ok(count > 0)
+}
+
+/// Re-indexes one message from its stored copy, or drops its index entry
+/// when the message is gone. Returns whether the message is now indexed.
+/// Only entries filed under the same guild and channel are ever removed.
+pub(crate) async fn reindex_guild_message(
+    state: &AppState,
+    guild_id: &str,
+    channel_id: &str,
+    message_id: &str,
+) -> Result<bool, AuthFailure> {
+    let messages =
+        hydrate_messages_by_id(state, guild_id, Some(channel_id), &[message_id.to_owned()]).await?;
+    if let Some(message) = messages.first() {
+        enqueue_search_operation(
+            state,
+            SearchOperation::Upsert(indexed_message_from_response(message)),
+            true,
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    let search_state = search_service(state)?.state.clone();
+    let timeout = search_query_timeout_for_guild(state, guild_id).await?;
+    let (lookup_guild_id, lookup_channel_id, lookup_message_id) = (
+        guild_id.to_owned(),
+        channel_id.to_owned(),
+        message_id.to_owned(),
+    );
+    let indexed = run_search_blocking_with_timeout(timeout, move || {
+        index_contains_channel_message(
+            &search_state,
+            &lookup_guild_id,
+            &lookup_channel_id,
+            &lookup_message_id,
+        )
+    })
+    .await?;
+    if indexed {
+        enqueue_search_operation(
+            state,
+            SearchOperation::Delete {
+                message_id: message_id.to_owned(),
+            },
+            true,
+        )
+        .await?;
+    }
+    Ok(false)
 }
 
 #[cfg(test)]
@@ -132,13 +296,18 @@ mod tests {
     use super::{
         build_search_index_lookup_input, build_search_reconciliation_plan,
         collect_index_message_ids_for_guild_from_index, compute_reconciliation,
+        index_contains_channel_message, reconcile_guild_search_index, reindex_guild_message,
         SearchIndexLookupInput,
     };
     use crate::server::{
-        core::{IndexedMessage, SearchIndexState},
-        realtime::build_search_schema,
+        core::{
+            AppConfig, AppState, ChannelRecord, GuildRecord, GuildVisibility, IndexedMessage,
+            MessageRecord, SearchIndexState, SearchOperation,
+        },
+        realtime::{build_search_schema, enqueue_search_operation, ensure_search_bootstrapped},
     };
-    use std::collections::HashSet;
+    use filament_core::{ChannelKind, Role, UserId};
+    use std::collections::{HashMap, HashSet};
 
     fn doc(id: &str) -> IndexedMessage {
         IndexedMessage {
@@ -188,12 +357,14 @@ mod tests {
 
     #[test]
     fn build_search_index_lookup_input_copies_values() {
-        let input = build_search_index_lookup_input("guild-1", 55);
+        let input = build_search_index_lookup_input("guild-1", Some("m1"), Some("m9"), 55);
 
         assert_eq!(
             input,
             SearchIndexLookupInput {
                 guild_id: String::from("guild-1"),
+                after_message_id: Some(String::from("m1")),
+                through_message_id: Some(String::from("m9")),
                 max_docs: 55,
             }
         );
@@ -201,9 +372,11 @@ mod tests {
 
     #[test]
     fn build_search_index_lookup_input_preserves_empty_guild_id() {
-        let input = build_search_index_lookup_input("", 1);
+        let input = build_search_index_lookup_input("", None, None, 1);
 
         assert_eq!(input.guild_id, "");
+        assert_eq!(input.after_message_id, None);
+        assert_eq!(input.through_message_id, None);
         assert_eq!(input.max_docs, 1);
     }
 
@@ -255,6 +428,7 @@ mod tests {
             index,
             reader,
             fields,
+            writer_heap_bytes: crate::server::core::DEFAULT_SEARCH_WRITER_HEAP_BYTES,
         }
     }
 
@@ -262,7 +436,7 @@ mod tests {
     fn collect_index_ids_returns_only_matching_guild_message_ids() {
         let search = search_state_with_docs();
 
-        let ids = collect_index_message_ids_for_guild_from_index(&search, "g1", 10)
+        let ids = collect_index_message_ids_for_guild_from_index(&search, "g1", None, None, 10)
             .expect("guild ids should be collected");
 
         assert_eq!(ids.len(), 2);
@@ -271,15 +445,297 @@ mod tests {
         assert!(!ids.contains("m3"));
     }
 
+    #[test]
+    fn collect_index_ids_honors_message_id_range() {
+        let search = search_state_with_docs();
+
+        let first =
+            collect_index_message_ids_for_guild_from_index(&search, "g1", None, Some("m1"), 10)
+                .expect("first range should be collected");
+        assert_eq!(first, HashSet::from([String::from("m1")]));
+
+        let rest =
+            collect_index_message_ids_for_guild_from_index(&search, "g1", Some("m1"), None, 10)
+                .expect("open-ended range should be collected");
+        assert_eq!(rest, HashSet::from([String::from("m2")]));
+
+        let bounded = collect_index_message_ids_for_guild_from_index(
+            &search,
+            "g2",
+            Some("m1"),
+            Some("m3"),
+            10,
+        )
+        .expect("bounded range should be collected");
+        assert_eq!(bounded, HashSet::from([String::from("m3")]));
+    }
+
     #[test]
     fn collect_index_ids_rejects_when_count_exceeds_cap() {
         let search = search_state_with_docs();
 
-        let result = collect_index_message_ids_for_guild_from_index(&search, "g1", 1);
+        let result = collect_index_message_ids_for_guild_from_index(&search, "g1", None, None, 1);
 
         assert!(matches!(
             result,
             Err(crate::server::errors::AuthFailure::InvalidRequest)
         ));
     }
+
+    #[tokio::test]
+    async fn reconcile_guild_search_index_spans_batches() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let author = UserId::new();
+        let messages = ["m1", "m2", "m3", "m4", "m5"]
+            .iter()
+            .map(|id| MessageRecord {
+                id: (*id).to_owned(),
+                author_id: author,
+                content: format!("content-{id}"),
+                markdown_tokens: Vec::new(),
+                attachment_ids: Vec::new(),
+                created_at_unix: 1,
+                reactions: HashMap::new(),
+                deleted_at_unix: None,
+                edited_at_unix: None,
+                system: false,
+            })
+            .collect();
+        state.membership_store.guilds().write().await.insert(
+            String::from("g1"),
+            GuildRecord {
+                name: String::from("Guild"),
+                visibility: GuildVisibility::Private,
+                created_by_user_id: author,
+                default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
+                members: HashMap::from([(author, Role::Owner)]),
+                banned_members: HashSet::new(),
+                channels: HashMap::from([(
+                    String::from("c1"),
+                    ChannelRecord {
+                        name: String::from("general"),
+                        kind: ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
+                        messages,
+                        role_overrides: HashMap::new(),
+                    },
+                )]),
+            },
+        );
+        enqueue_search_operation(
+            &state,
+            SearchOperation::Rebuild {
+                docs: vec![doc("m0"), doc("m2"), doc("m4"), doc("m9")],
+            },
+            true,
+        )
+        .await
+        .expect("index should be seeded");
+
+        let (upserted, deleted) = reconcile_guild_search_index(&state, "g1", 2)
+            .await
+            .expect("reconcile should succeed");
+        assert_eq!((upserted, deleted), (3, 2));
+
+        let ids = collect_index_message_ids_for_guild_from_index(
+            &state
+                .search
+                .as_ref()
+                .expect("search should be enabled")
+                .state,
+            "g1",
+            None,
+            None,
+            10,
+        )
+        .expect("guild ids should be collected");
+        let expected: HashSet<String> = ["m1", "m2", "m3", "m4", "m5"]
+            .iter()
+            .map(|id| (*id).to_owned())
+            .collect();
+        assert_eq!(ids, expected);
+
+        let (upserted, deleted) = reconcile_guild_search_index(&state, "g1", 2)
+            .await
+            .expect("second reconcile should succeed");
+        assert_eq!((upserted, deleted), (0, 0));
+    }
+
+    fn guild_with_messages(author: UserId, message_ids: &[&str]) -> GuildRecord {
+        GuildRecord {
+            name: String::from("Guild"),
+            visibility: GuildVisibility::Private,
+            created_by_user_id: author,
+            default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
+            members: HashMap::from([(author, Role::Owner)]),
+            banned_members: HashSet::new(),
+            channels: HashMap::from([(
+                String::from("c1"),
+                ChannelRecord {
+                    name: String::from("general"),
+                    kind: ChannelKind::Text,
+                    locked: false,
+                    retention_secs: None,
+                    voting: false,
+                    category_id: None,
+                    messages: message_ids
+                        .iter()
+                        .map(|id| MessageRecord {
+                            id: (*id).to_owned(),
+                            author_id: author,
+                            content: format!("content-{id}"),
+                            markdown_tokens: Vec::new(),
+                            attachment_ids: Vec::new(),
+                            created_at_unix: 1,
+                            reactions: HashMap::new(),
+                            deleted_at_unix: None,
+                            edited_at_unix: None,
+                            system: false,
+                        })
+                        .collect(),
+                    role_overrides: HashMap::new(),
+                },
+            )]),
+        }
+    }
+
+    #[tokio::test]
+    async fn ensure_search_bootstrapped_indexes_only_the_searched_guild_once() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let author = UserId::new();
+        {
+            let mut guilds = state.membership_store.guilds().write().await;
+            guilds.insert(
+                String::from("g1"),
+                guild_with_messages(author, &["m1", "m2"]),
+            );
+            guilds.insert(String::from("g2"), guild_with_messages(author, &["m3"]));
+        }
+        let index_ids = |guild_id: &str| {
+            collect_index_message_ids_for_guild_from_index(
+                &state
+                    .search
+                    .as_ref()
+                    .expect("search should be enabled")
+                    .state,
+                guild_id,
+                None,
+                None,
+                10,
+            )
+            .expect("guild ids should be collected")
+        };
+
+        ensure_search_bootstrapped(&state, "g1")
+            .await
+            .expect("bootstrap should succeed");
+        assert_eq!(
+            index_ids("g1"),
+            HashSet::from([String::from("m1"), String::from("m2")])
+        );
+        assert!(index_ids("g2").is_empty());
+
+        enqueue_search_operation(&state, SearchOperation::Upsert(doc("stale")), true)
+            .await
+            .expect("stale doc should be indexed");
+        ensure_search_bootstrapped(&state, "g1")
+            .await
+            .expect("second bootstrap should succeed");
+        assert!(index_ids("g1").contains("stale"));
+
+        ensure_search_bootstrapped(&state, "g2")
+            .await
+            .expect("bootstrap should succeed");
+        assert_eq!(index_ids("g2"), HashSet::from([String::from("m3")]));
+    }
+
+    #[tokio::test]
+    async fn reindex_guild_message_upserts_or_drops_only_its_own_entry() {
+        let state = AppState::new(&AppConfig::default()).expect("state initializes");
+        let author = UserId::new();
+        state.membership_store.guilds().write().await.insert(
+            String::from("g1"),
+            GuildRecord {
+                name: String::from("Guild"),
+                visibility: GuildVisibility::Private,
+                created_by_user_id: author,
+                default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
+                members: HashMap::from([(author, Role::Owner)]),
+                banned_members: HashSet::new(),
+                channels: HashMap::from([(
+                    String::from("c1"),
+                    ChannelRecord {
+                        name: String::from("general"),
+                        kind: ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
+                        messages: vec![MessageRecord {
+                            id: String::from("m1"),
+                            author_id: author,
+                            content: String::from("fresh content"),
+                            markdown_tokens: Vec::new(),
+                            attachment_ids: Vec::new(),
+                            created_at_unix: 1,
+                            reactions: HashMap::new(),
+                            deleted_at_unix: None,
+                            edited_at_unix: None,
+                            system: false,
+                        }],
+                        role_overrides: HashMap::new(),
+                    },
+                )]),
+            },
+        );
+        let mut foreign = doc("m3");
+        foreign.guild_id = String::from("g2");
+        enqueue_search_operation(
+            &state,
+            SearchOperation::Rebuild {
+                docs: vec![doc("m2"), foreign],
+            },
+            true,
+        )
+        .await
+        .expect("index should be seeded");
+        let search = &state
+            .search
+            .as_ref()
+            .expect("search should be enabled")
+            .state;
+
+        assert!(reindex_guild_message(&state, "g1", "c1", "m1")
+            .await
+            .expect("existing message should reindex"));
+        assert!(index_contains_channel_message(search, "g1", "c1", "m1").expect("lookup"));
+
+        assert!(!reindex_guild_message(&state, "g1", "c1", "m2")
+            .await
+            .expect("stale entry should be dropped"));
+        assert!(!index_contains_channel_message(search, "g1", "c1", "m2").expect("lookup"));
+
+        assert!(!reindex_guild_message(&state, "g1", "c1", "m3")
+            .await
+            .expect("foreign entry should be left alone"));
+        assert!(index_contains_channel_message(search, "g2", "c1", "m3").expect("lookup"));
+    }
 }
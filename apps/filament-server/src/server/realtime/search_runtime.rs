@@ -1,13 +1,22 @@
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
 
 use anyhow::anyhow;
+use filament_core::UserId;
 use tantivy::{
     schema::{NumericOptions, Schema, TextFieldIndexing, TextOptions, STORED, STRING},
     TantivyDocument, Term,
 };
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    oneshot,
+};
 
 use crate::server::{
+    auth_repository::{AuthPersistence, AuthRepository},
     core::{
         AppState, GuildRecord, IndexedMessage, SearchCommand, SearchFields, SearchIndexState,
         SearchOperation, SearchService, DEFAULT_SEARCH_RESULT_LIMIT, MAX_SEARCH_FUZZY,
@@ -15,19 +24,32 @@ use crate::server::{
     },
     domain::{
         attachment_map_for_messages_db, attachment_map_for_messages_in_memory,
-        reaction_map_for_messages_db,
+        decode_message_content, reaction_map_for_messages_db, MessageContentCipher,
+        StoredMessageContent,
     },
     errors::AuthFailure,
+    metrics::{record_search_index_op_dropped, record_search_worker_restart},
     types::{MessageResponse, SearchQuery},
 };
 
+use super::guild_directory_search::init_guild_directory_index;
 use super::hydration_runtime::{
     apply_hydration_attachments, collect_hydrated_in_request_order, collect_hydrated_messages_db,
     collect_hydrated_messages_in_memory, merge_hydration_maps,
 };
+use super::search_reconciliation_plan::reconcile_guild_search_index;
 
 const SEARCH_WORKER_BATCH_LIMIT: usize = 128;
-type IndexedMessageRow = (String, String, String, String, String, i64);
+type IndexedMessageRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<Vec<u8>>,
+    Option<Vec<u8>>,
+    i64,
+);
 
 pub(crate) async fn enqueue_search_command(
     tx: &mpsc::Sender<SearchCommand>,
@@ -59,10 +81,20 @@ pub(crate) async fn enqueue_search_command(
         }
         result
     } else {
-        let result = tx
-            .send(SearchCommand { op, ack: None })
-            .await
-            .map_err(|_| AuthFailure::Internal);
+        // Fire-and-forget writes never wait for queue space: when the worker
+        // falls behind the update is dropped and left for reconcile to repair.
+        let result = match tx.try_send(SearchCommand { op, ack: None }) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                record_search_index_op_dropped("queue_full");
+                tracing::warn!(
+                    event = "search.index.enqueue_dropped",
+                    reason = "queue_full"
+                );
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => Err(AuthFailure::Internal),
+        };
         if timing_enabled {
             tracing::info!(
                 event = "debug.search.enqueue_search_command.timing",
@@ -96,8 +128,10 @@ pub(crate) fn build_search_schema() -> (Schema, SearchFields) {
     let guild_id = schema_builder.add_text_field("guild_id", STRING | STORED);
     let channel_id = schema_builder.add_text_field("channel_id", STRING | STORED);
     let author_id = schema_builder.add_text_field("author_id", STRING | STORED);
-    let created_at_unix =
-        schema_builder.add_i64_field("created_at_unix", NumericOptions::default().set_stored());
+    let created_at_unix = schema_builder.add_i64_field(
+        "created_at_unix",
+        NumericOptions::default().set_stored().set_fast(),
+    );
     let content_options = TextOptions::default()
         .set_stored()
         .set_indexing_options(TextFieldIndexing::default().set_tokenizer("default"));
@@ -117,18 +151,35 @@ pub(crate) fn build_search_schema() -> (Schema, SearchFields) {
     )
 }
 
-fn indexed_messages_from_rows(rows: Vec<IndexedMessageRow>) -> Vec<IndexedMessage> {
+fn indexed_messages_from_rows(
+    rows: Vec<IndexedMessageRow>,
+    cipher: Option<&MessageContentCipher>,
+) -> Result<Vec<IndexedMessage>, AuthFailure> {
     rows.into_iter()
         .map(
-            |(message_id, guild_id, channel_id, author_id, content, created_at_unix)| {
-                IndexedMessage {
+            |(
+                message_id,
+                guild_id,
+                channel_id,
+                author_id,
+                content,
+                content_zstd,
+                content_sealed,
+                created_at_unix,
+            )| {
+                let stored = StoredMessageContent {
+                    content,
+                    content_zstd,
+                    content_sealed,
+                };
+                Ok(IndexedMessage {
+                    content: decode_message_content(stored, &message_id, cipher)?,
                     message_id,
                     guild_id,
                     channel_id,
                     author_id,
                     created_at_unix,
-                    content,
-                }
+                })
             },
         )
         .collect()
@@ -136,31 +187,23 @@ fn indexed_messages_from_rows(rows: Vec<IndexedMessageRow>) -> Vec<IndexedMessag
 
 pub(crate) fn collect_all_indexed_messages_rows(
     rows: Vec<IndexedMessageRow>,
-) -> Vec<IndexedMessage> {
-    indexed_messages_from_rows(rows)
+    cipher: Option<&MessageContentCipher>,
+) -> Result<Vec<IndexedMessage>, AuthFailure> {
+    indexed_messages_from_rows(rows, cipher)
 }
 
 pub(crate) fn collect_indexed_messages_for_guild_rows(
     rows: Vec<IndexedMessageRow>,
-) -> Vec<IndexedMessage> {
-    indexed_messages_from_rows(rows)
+    cipher: Option<&MessageContentCipher>,
+) -> Result<Vec<IndexedMessage>, AuthFailure> {
+    indexed_messages_from_rows(rows, cipher)
 }
 
-pub(crate) fn guild_collect_fetch_limit(max_docs: usize) -> Result<i64, AuthFailure> {
-    i64::try_from(max_docs.saturating_add(1)).map_err(|_| AuthFailure::InvalidRequest)
+pub(crate) fn guild_collect_page_limit(limit: usize) -> Result<i64, AuthFailure> {
+    i64::try_from(limit).map_err(|_| AuthFailure::InvalidRequest)
 }
 
-pub(crate) fn enforce_guild_collect_doc_cap(
-    row_count: usize,
-    max_docs: usize,
-) -> Result<(), AuthFailure> {
-    if row_count > max_docs {
-        return Err(AuthFailure::InvalidRequest);
-    }
-    Ok(())
-}
-
-pub(crate) fn init_search_service() -> anyhow::Result<SearchService> {
+pub(crate) fn init_search_service(writer_heap_bytes: usize) -> anyhow::Result<SearchService> {
     let (schema, fields) = build_search_schema();
     let index = tantivy::Index::create_in_ram(schema);
     let reader = index
@@ -170,22 +213,53 @@ pub(crate) fn init_search_service() -> anyhow::Result<SearchService> {
         index,
         reader,
         fields,
+        writer_heap_bytes,
     });
+    let guild_directory = Arc::new(init_guild_directory_index()?);
     let (tx, mut rx) = mpsc::channel::<SearchCommand>(SEARCH_INDEX_QUEUE_CAPACITY);
     let worker_state = state.clone();
     std::thread::Builder::new()
         .name(String::from("filament-search-index"))
         .spawn(move || {
+            supervise_search_worker(&mut rx, |batch| apply_search_batch(&worker_state, batch));
+        })
+        .map_err(|e| anyhow!("search worker spawn failed: {e}"))?;
+    Ok(SearchService {
+        tx,
+        state,
+        guild_directory,
+    })
+}
+
+/// Runs the index worker loop until every sender is gone.
+///
+/// A panic while applying a batch would otherwise drop `rx` and fail every
+/// later enqueue, so the loop is restarted on the same receiver. Callers
+/// waiting on the lost batch see their ack dropped; a reconcile repairs any
+/// index entries it missed.
+fn supervise_search_worker<F>(rx: &mut mpsc::Receiver<SearchCommand>, mut apply_batch: F)
+where
+    F: FnMut(Vec<SearchCommand>) -> anyhow::Result<()>,
+{
+    loop {
+        let run = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
             while let Some(command) = rx.blocking_recv() {
-                let batch = drain_search_batch(command, &mut rx, SEARCH_WORKER_BATCH_LIMIT);
-                let batch_result = apply_search_batch(&worker_state, batch);
-                if let Err(error) = batch_result {
+                let batch = drain_search_batch(command, rx, SEARCH_WORKER_BATCH_LIMIT);
+                if let Err(error) = apply_batch(batch) {
                     tracing::error!(event = "search.index.batch", error = %error);
                 }
             }
-        })
-        .map_err(|e| anyhow!("search worker spawn failed: {e}"))?;
-    Ok(SearchService { tx, state })
+        }));
+        if run.is_ok() {
+            return;
+        }
+        record_search_worker_restart("panic");
+        tracing::error!(
+            event = "search.worker.restart",
+            reason = "panic",
+            "search index worker panicked; restarting"
+        );
+    }
 }
 
 pub(crate) fn apply_search_batch(
@@ -213,7 +287,7 @@ where
     }
 
     let apply_result = (|| -> anyhow::Result<()> {
-        let mut writer = search.index.writer(50_000_000)?;
+        let mut writer = search.index.writer(search.writer_heap_bytes)?;
         for op in ops {
             apply_op(search, &mut writer, op);
         }
@@ -336,21 +410,29 @@ pub(crate) fn validate_search_query_limits(
     Ok(())
 }
 
-fn build_search_rebuild_operation(docs: Vec<IndexedMessage>) -> SearchOperation {
-    SearchOperation::Rebuild { docs }
-}
-
-fn map_collect_all_rows(rows: Vec<IndexedMessageRow>) -> Vec<IndexedMessage> {
-    collect_all_indexed_messages_rows(rows)
+fn map_collect_all_rows(
+    rows: Vec<IndexedMessageRow>,
+    cipher: Option<&MessageContentCipher>,
+) -> Result<Vec<IndexedMessage>, AuthFailure> {
+    collect_all_indexed_messages_rows(rows, cipher)
 }
 
+/// Copies every live message, giving up with `InMemoryCapacityReached` as
+/// soon as more than `max_docs` are found instead of cloning the rest.
 fn collect_all_indexed_messages_in_memory(
     guilds: &HashMap<String, GuildRecord>,
-) -> Vec<IndexedMessage> {
+    max_docs: usize,
+) -> Result<Vec<IndexedMessage>, AuthFailure> {
     let mut docs = Vec::new();
     for (guild_id, guild) in guilds {
         for (channel_id, channel) in &guild.channels {
             for message in &channel.messages {
+                if message.deleted_at_unix.is_some() {
+                    continue;
+                }
+                if docs.len() >= max_docs {
+                    return Err(AuthFailure::InMemoryCapacityReached);
+                }
                 docs.push(IndexedMessage {
                     message_id: message.id.clone(),
                     guild_id: guild_id.clone(),
@@ -362,32 +444,34 @@ fn collect_all_indexed_messages_in_memory(
             }
         }
     }
-    docs
-}
-
-fn map_collect_guild_rows(
-    rows: Vec<IndexedMessageRow>,
-    max_docs: usize,
-) -> Result<Vec<IndexedMessage>, AuthFailure> {
-    enforce_guild_collect_doc_cap(rows.len(), max_docs)?;
-    Ok(collect_indexed_messages_for_guild_rows(rows))
+    Ok(docs)
 }
 
-fn collect_indexed_messages_for_guild_in_memory(
+fn collect_indexed_messages_page_for_guild_in_memory(
     guilds: &HashMap<String, GuildRecord>,
     guild_id: &str,
-    max_docs: usize,
+    after_message_id: Option<&str>,
+    limit: usize,
 ) -> Result<Vec<IndexedMessage>, AuthFailure> {
     let Some(guild) = guilds.get(guild_id) else {
         return Err(AuthFailure::NotFound);
     };
 
+    // Channel messages are kept in id order, so each channel can skip straight
+    // past the cursor and stop after `limit` live messages instead of
+    // copying the whole guild.
     let mut docs = Vec::new();
     for (channel_id, channel) in &guild.channels {
-        for message in &channel.messages {
-            if docs.len() >= max_docs {
-                return Err(AuthFailure::InvalidRequest);
-            }
+        let start = after_message_id.map_or(0, |after| {
+            channel
+                .messages
+                .partition_point(|message| message.id.as_str() <= after)
+        });
+        let live = channel.messages[start..]
+            .iter()
+            .filter(|message| message.deleted_at_unix.is_none())
+            .take(limit);
+        for message in live {
             docs.push(IndexedMessage {
                 message_id: message.id.clone(),
                 guild_id: guild_id.to_owned(),
@@ -398,6 +482,8 @@ fn collect_indexed_messages_for_guild_in_memory(
             });
         }
     }
+    docs.sort_by(|a, b| a.message_id.cmp(&b.message_id));
+    docs.truncate(limit);
     Ok(docs)
 }
 
@@ -420,25 +506,73 @@ fn effective_search_limit(requested_limit: Option<usize>, default_limit: usize)
     requested_limit.unwrap_or(default_limit)
 }
 
-pub(crate) async fn ensure_search_bootstrapped(state: &AppState) -> Result<(), AuthFailure> {
-    state
-        .search_bootstrapped
+/// Loads `guild_id`'s messages into the index the first time the guild is
+/// searched. Each guild is reconciled on its own, one page at a time, so the
+/// first search on a large instance does not wait for every guild.
+pub(crate) async fn ensure_search_bootstrapped(
+    state: &AppState,
+    guild_id: &str,
+) -> Result<(), AuthFailure> {
+    search_service(state)?;
+    let existing = state
+        .search_bootstrapped_guilds
+        .read()
+        .await
+        .get(guild_id)
+        .cloned();
+    let bootstrapped = match existing {
+        Some(bootstrapped) => bootstrapped,
+        None => state
+            .search_bootstrapped_guilds
+            .write()
+            .await
+            .entry(guild_id.to_owned())
+            .or_default()
+            .clone(),
+    };
+    bootstrapped
         .get_or_try_init(|| async move {
-            let docs = collect_all_indexed_messages(state).await?;
-            let rebuild = build_search_rebuild_operation(docs);
-            enqueue_search_operation(state, rebuild, true).await?;
-            Ok(())
+            let (upserted, _) = reconcile_guild_search_index(
+                state,
+                guild_id,
+                state.runtime.search_reconcile_batch_docs,
+            )
+            .await?;
+            tracing::info!(event = "search.bootstrap.guild", guild_id, docs = upserted);
+            Ok::<(), AuthFailure>(())
         })
         .await?;
     Ok(())
 }
 
+/// Records guilds that a rebuild or reconcile has already brought up to
+/// date, so their first search skips the bootstrap pass.
+pub(crate) async fn mark_search_guilds_bootstrapped<I>(state: &AppState, guild_ids: I)
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut guilds = state.search_bootstrapped_guilds.write().await;
+    for guild_id in guild_ids {
+        let _ = guilds.entry(guild_id).or_default().set(());
+    }
+}
+
+/// Returns the search service, or `NotFound` when search is disabled.
+pub(crate) fn search_service(state: &AppState) -> Result<&SearchService, AuthFailure> {
+    state.search.as_ref().ok_or(AuthFailure::NotFound)
+}
+
+/// Queues an index write. A no-op when search is disabled, so message write
+/// paths need no checks of their own.
 pub(crate) async fn enqueue_search_operation(
     state: &AppState,
     op: SearchOperation,
     wait_for_apply: bool,
 ) -> Result<(), AuthFailure> {
-    enqueue_search_command(&state.search.tx, op, wait_for_apply).await
+    let Some(search) = &state.search else {
+        return Ok(());
+    };
+    enqueue_search_command(&search.tx, op, wait_for_apply).await
 }
 
 pub(crate) async fn collect_all_indexed_messages(
@@ -446,43 +580,55 @@ pub(crate) async fn collect_all_indexed_messages(
 ) -> Result<Vec<IndexedMessage>, AuthFailure> {
     if let Some(pool) = &state.db_pool {
         let rows = sqlx::query_as::<_, IndexedMessageRow>(
-            "SELECT message_id, guild_id, channel_id, author_id, content, created_at_unix
-             FROM messages",
+            "SELECT message_id, guild_id, channel_id, author_id, content, content_zstd,
+                    content_sealed, created_at_unix
+             FROM messages
+             WHERE deleted_at_unix IS NULL",
         )
         .fetch_all(pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
-        return Ok(map_collect_all_rows(rows));
+        return map_collect_all_rows(rows, state.message_content_cipher.as_deref());
     }
 
     let guilds = state.membership_store.guilds().read().await;
-    Ok(collect_all_indexed_messages_in_memory(&guilds))
+    collect_all_indexed_messages_in_memory(&guilds, state.runtime.in_memory_max_rebuild_docs)
 }
 
-pub(crate) async fn collect_indexed_messages_for_guild(
+/// Returns up to `limit` of a guild's messages with ids after
+/// `after_message_id`, in message id order, so callers can page through
+/// guilds of any size.
+pub(crate) async fn collect_indexed_messages_page_for_guild(
     state: &AppState,
     guild_id: &str,
-    max_docs: usize,
+    after_message_id: Option<&str>,
+    limit: usize,
 ) -> Result<Vec<IndexedMessage>, AuthFailure> {
     if let Some(pool) = &state.db_pool {
-        let limit = guild_collect_fetch_limit(max_docs)?;
+        let limit = guild_collect_page_limit(limit)?;
         let rows = sqlx::query_as::<_, IndexedMessageRow>(
-            "SELECT message_id, guild_id, channel_id, author_id, content, created_at_unix
+            "SELECT message_id, guild_id, channel_id, author_id, content, content_zstd,
+                    content_sealed, created_at_unix
              FROM messages
-             WHERE guild_id = $1
-             ORDER BY created_at_unix DESC
-             LIMIT $2",
+             WHERE guild_id = $1 AND ($2::text IS NULL OR message_id > $2)
+               AND deleted_at_unix IS NULL
+             ORDER BY message_id ASC
+             LIMIT $3",
         )
         .bind(guild_id)
+        .bind(after_message_id)
         .bind(limit)
         .fetch_all(pool)
         .await
         .map_err(|_| AuthFailure::Internal)?;
-        return map_collect_guild_rows(rows, max_docs);
+        return collect_indexed_messages_for_guild_rows(
+            rows,
+            state.message_content_cipher.as_deref(),
+        );
     }
 
     let guilds = state.membership_store.guilds().read().await;
-    collect_indexed_messages_for_guild_in_memory(&guilds, guild_id, max_docs)
+    collect_indexed_messages_page_for_guild_in_memory(&guilds, guild_id, after_message_id, limit)
 }
 
 pub(crate) async fn hydrate_messages_by_id(
@@ -496,8 +642,14 @@ pub(crate) async fn hydrate_messages_by_id(
     }
 
     if let Some(pool) = &state.db_pool {
-        let mut by_id =
-            collect_hydrated_messages_db(pool, guild_id, channel_id, message_ids).await?;
+        let mut by_id = collect_hydrated_messages_db(
+            pool,
+            state.message_content_cipher.as_deref(),
+            guild_id,
+            channel_id,
+            message_ids,
+        )
+        .await?;
         let message_ids_ordered: Vec<String> = message_ids.to_vec();
         let attachment_map =
             attachment_map_for_messages_db(pool, guild_id, channel_id, &message_ids_ordered)
@@ -518,6 +670,39 @@ pub(crate) async fn hydrate_messages_by_id(
     Ok(collect_hydrated_in_request_order(by_id, message_ids))
 }
 
+/// Fills in `author_username` on hydrated messages with one batched lookup.
+/// Authors whose accounts no longer exist are left without a username.
+pub(crate) async fn attach_author_usernames(
+    state: &AppState,
+    messages: &mut [MessageResponse],
+) -> Result<(), AuthFailure> {
+    let mut author_ids = Vec::new();
+    let mut seen = HashSet::new();
+    for message in messages.iter() {
+        let Ok(author_id) = UserId::try_from(message.author_id.clone()) else {
+            continue;
+        };
+        if seen.insert(author_id) {
+            author_ids.push(author_id);
+        }
+    }
+    if author_ids.is_empty() {
+        return Ok(());
+    }
+
+    let users = AuthRepository::from_state(state)
+        .lookup_users(&author_ids)
+        .await?;
+    let usernames: HashMap<String, String> = users
+        .into_iter()
+        .map(|user| (user.user_id, user.username))
+        .collect();
+    for message in messages {
+        message.author_username = usernames.get(&message.author_id).cloned();
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::{HashMap, HashSet};
@@ -534,13 +719,13 @@ mod tests {
     use tokio::sync::{mpsc, oneshot};
 
     use super::{
-        apply_search_batch_with_ack, apply_search_operation, build_search_rebuild_operation,
-        build_search_schema, collect_all_indexed_messages_in_memory,
-        collect_all_indexed_messages_rows, collect_indexed_messages_for_guild_in_memory,
-        collect_indexed_messages_for_guild_rows, drain_search_batch, effective_search_limit,
-        enforce_guild_collect_doc_cap, enqueue_search_command, guild_collect_fetch_limit,
-        indexed_message_from_response, map_collect_all_rows, map_collect_guild_rows,
-        normalize_search_query, validate_search_query_limits, validate_search_query_with_limits,
+        apply_search_batch_with_ack, apply_search_operation, build_search_schema,
+        collect_all_indexed_messages_in_memory, collect_all_indexed_messages_rows,
+        collect_indexed_messages_for_guild_rows, collect_indexed_messages_page_for_guild_in_memory,
+        drain_search_batch, effective_search_limit, enqueue_search_command,
+        guild_collect_page_limit, indexed_message_from_response, map_collect_all_rows,
+        normalize_search_query, supervise_search_worker, validate_search_query_limits,
+        validate_search_query_with_limits,
     };
     use crate::server::{
         core::{
@@ -548,7 +733,8 @@ mod tests {
             SearchCommand, SearchOperation,
         },
         errors::AuthFailure,
-        types::{MessageResponse, SearchQuery},
+        metrics::metrics_state,
+        types::{MessageResponse, SearchQuery, SearchSort},
     };
 
     fn search_state() -> Arc<crate::server::core::SearchIndexState> {
@@ -559,20 +745,10 @@ mod tests {
             index,
             reader,
             fields,
+            writer_heap_bytes: crate::server::core::DEFAULT_SEARCH_WRITER_HEAP_BYTES,
         })
     }
 
-    fn sample_doc(id: &str) -> IndexedMessage {
-        IndexedMessage {
-            message_id: id.to_owned(),
-            guild_id: String::from("g1"),
-            channel_id: String::from("c1"),
-            author_id: String::from("u1"),
-            created_at_unix: 1,
-            content: String::from("hello"),
-        }
-    }
-
     fn command(message_id: &str) -> SearchCommand {
         SearchCommand {
             op: SearchOperation::Delete {
@@ -591,6 +767,9 @@ mod tests {
 
     fn guild_with_messages(guild_id: &str, message_ids: &[&str]) -> HashMap<String, GuildRecord> {
         let author = UserId::new();
+        // Channels keep their messages in id order, like `append_message_record`.
+        let mut message_ids = message_ids.to_vec();
+        message_ids.sort_unstable();
         let messages = message_ids
             .iter()
             .map(|message_id| MessageRecord {
@@ -601,6 +780,9 @@ mod tests {
                 attachment_ids: Vec::new(),
                 created_at_unix: 1,
                 reactions: HashMap::new(),
+                deleted_at_unix: None,
+                edited_at_unix: None,
+                system: false,
             })
             .collect();
 
@@ -611,6 +793,11 @@ mod tests {
                 visibility: GuildVisibility::Private,
                 created_by_user_id: author,
                 default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
                 members: HashMap::from([(author, Role::Owner)]),
                 banned_members: HashSet::new(),
                 channels: HashMap::from([(
@@ -618,6 +805,10 @@ mod tests {
                     ChannelRecord {
                         name: String::from("general"),
                         kind: ChannelKind::Text,
+                        locked: false,
+                        retention_secs: None,
+                        voting: false,
+                        category_id: None,
                         messages,
                         role_overrides: HashMap::new(),
                     },
@@ -632,6 +823,10 @@ mod tests {
             q: String::from("  "),
             limit: Some(5),
             channel_id: None,
+            include_author_usernames: false,
+            sort: SearchSort::Relevance,
+            hydrate: None,
+            facets: false,
         };
 
         let result = validate_search_query_with_limits(&query, 20, 256, 50);
@@ -645,6 +840,10 @@ mod tests {
             q: String::from("hello"),
             limit: None,
             channel_id: Some(String::from("c1")),
+            include_author_usernames: false,
+            sort: SearchSort::Relevance,
+            hydrate: None,
+            facets: false,
         };
 
         let result = validate_search_query_with_limits(&query, 20, 256, 50);
@@ -692,30 +891,6 @@ mod tests {
         assert_eq!(effective_search_limit(Some(10), 25), 10);
     }
 
-    #[test]
-    fn build_search_rebuild_operation_wraps_docs() {
-        let op = build_search_rebuild_operation(vec![sample_doc("m1"), sample_doc("m2")]);
-
-        match op {
-            SearchOperation::Rebuild { docs } => {
-                assert_eq!(docs.len(), 2);
-                assert_eq!(docs[0].message_id, "m1");
-                assert_eq!(docs[1].message_id, "m2");
-            }
-            _ => panic!("expected rebuild operation"),
-        }
-    }
-
-    #[test]
-    fn build_search_rebuild_operation_supports_empty_docs() {
-        let op = build_search_rebuild_operation(Vec::new());
-
-        match op {
-            SearchOperation::Rebuild { docs } => assert!(docs.is_empty()),
-            _ => panic!("expected rebuild operation"),
-        }
-    }
-
     #[test]
     fn indexed_message_from_response_maps_all_fields() {
         let response = MessageResponse {
@@ -727,7 +902,13 @@ mod tests {
             markdown_tokens: Vec::new(),
             attachments: Vec::new(),
             reactions: Vec::new(),
+            embeds: Vec::new(),
+            author_username: None,
             created_at_unix: 42,
+            deleted: false,
+            system: false,
+            score: None,
+            edited_at_unix: None,
         };
 
         let indexed = indexed_message_from_response(&response);
@@ -768,33 +949,7 @@ mod tests {
 
     #[test]
     fn map_collect_all_rows_maps_messages_in_order() {
-        let docs = map_collect_all_rows(vec![
-            (
-                String::from("m1"),
-                String::from("g1"),
-                String::from("c1"),
-                String::from("u1"),
-                String::from("first"),
-                10,
-            ),
-            (
-                String::from("m2"),
-                String::from("g1"),
-                String::from("c2"),
-                String::from("u2"),
-                String::from("second"),
-                11,
-            ),
-        ]);
-
-        assert_eq!(docs.len(), 2);
-        assert_eq!(docs[0].message_id, "m1");
-        assert_eq!(docs[1].message_id, "m2");
-    }
-
-    #[test]
-    fn map_collect_guild_rows_fails_closed_when_rows_exceed_cap() {
-        let result = map_collect_guild_rows(
+        let docs = map_collect_all_rows(
             vec![
                 (
                     String::from("m1"),
@@ -802,43 +957,59 @@ mod tests {
                     String::from("c1"),
                     String::from("u1"),
                     String::from("first"),
+                    None,
+                    None,
                     10,
                 ),
                 (
                     String::from("m2"),
                     String::from("g1"),
-                    String::from("c1"),
-                    String::from("u1"),
-                    String::from("second"),
+                    String::from("c2"),
+                    String::from("u2"),
+                    String::new(),
+                    Some(zstd::bulk::compress(b"second", 3).expect("compression should succeed")),
+                    None,
                     11,
                 ),
             ],
-            1,
-        );
+            None,
+        )
+        .expect("rows should map");
 
-        assert!(matches!(result, Err(AuthFailure::InvalidRequest)));
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].message_id, "m1");
+        assert_eq!(docs[1].message_id, "m2");
+        assert_eq!(docs[1].content, "second");
     }
 
     #[test]
     fn collect_all_indexed_messages_rows_maps_all_fields() {
-        let docs = collect_all_indexed_messages_rows(vec![
-            (
-                String::from("m1"),
-                String::from("g1"),
-                String::from("c1"),
-                String::from("u1"),
-                String::from("hello"),
-                7,
-            ),
-            (
-                String::from("m2"),
-                String::from("g2"),
-                String::from("c2"),
-                String::from("u2"),
-                String::from("world"),
-                8,
-            ),
-        ]);
+        let docs = collect_all_indexed_messages_rows(
+            vec![
+                (
+                    String::from("m1"),
+                    String::from("g1"),
+                    String::from("c1"),
+                    String::from("u1"),
+                    String::from("hello"),
+                    None,
+                    None,
+                    7,
+                ),
+                (
+                    String::from("m2"),
+                    String::from("g2"),
+                    String::from("c2"),
+                    String::from("u2"),
+                    String::from("world"),
+                    None,
+                    None,
+                    8,
+                ),
+            ],
+            None,
+        )
+        .expect("rows should map");
 
         assert_eq!(docs.len(), 2);
         assert_eq!(docs[0].message_id, "m1");
@@ -851,41 +1022,41 @@ mod tests {
 
     #[test]
     fn collect_indexed_messages_for_guild_rows_preserves_row_order() {
-        let docs = collect_indexed_messages_for_guild_rows(vec![
-            (
-                String::from("newest"),
-                String::from("g1"),
-                String::from("c1"),
-                String::from("u1"),
-                String::from("new"),
-                11,
-            ),
-            (
-                String::from("older"),
-                String::from("g1"),
-                String::from("c1"),
-                String::from("u1"),
-                String::from("old"),
-                10,
-            ),
-        ]);
+        let docs = collect_indexed_messages_for_guild_rows(
+            vec![
+                (
+                    String::from("newest"),
+                    String::from("g1"),
+                    String::from("c1"),
+                    String::from("u1"),
+                    String::from("new"),
+                    None,
+                    None,
+                    11,
+                ),
+                (
+                    String::from("older"),
+                    String::from("g1"),
+                    String::from("c1"),
+                    String::from("u1"),
+                    String::from("old"),
+                    None,
+                    None,
+                    10,
+                ),
+            ],
+            None,
+        )
+        .expect("rows should map");
 
         let ids: Vec<&str> = docs.iter().map(|doc| doc.message_id.as_str()).collect();
         assert_eq!(ids, vec!["newest", "older"]);
     }
 
     #[test]
-    fn guild_collect_fetch_limit_adds_one_for_fail_closed_cap_check() {
-        let limit = guild_collect_fetch_limit(50).expect("valid max docs should convert");
-        assert_eq!(limit, 51);
-    }
-
-    #[test]
-    fn enforce_guild_collect_doc_cap_rejects_over_cap_results() {
-        assert!(enforce_guild_collect_doc_cap(2, 2).is_ok());
-        let error =
-            enforce_guild_collect_doc_cap(3, 2).expect_err("over-cap row count should fail closed");
-        assert!(matches!(error, AuthFailure::InvalidRequest));
+    fn guild_collect_page_limit_converts_page_size() {
+        let limit = guild_collect_page_limit(50).expect("valid page size should convert");
+        assert_eq!(limit, 50);
     }
 
     #[test]
@@ -904,6 +1075,52 @@ mod tests {
         assert_eq!(rx.try_recv().ok().as_ref().and_then(message_id), Some("m3"));
     }
 
+    #[test]
+    fn supervised_search_worker_restarts_after_a_panicking_batch() {
+        let (tx, mut rx) = mpsc::channel::<SearchCommand>(4);
+        let worker = std::thread::spawn(move || {
+            let mut batches = 0;
+            supervise_search_worker(&mut rx, |batch| {
+                batches += 1;
+                assert!(batches > 1, "simulated search worker failure");
+                for command in batch {
+                    if let Some(ack) = command.ack {
+                        let _ = ack.send(Ok(()));
+                    }
+                }
+                Ok(())
+            });
+            batches
+        });
+
+        let (first_ack, first_rx) = oneshot::channel();
+        tx.blocking_send(SearchCommand {
+            ack: Some(first_ack),
+            ..command("m1")
+        })
+        .expect("first command should queue");
+        assert!(first_rx.blocking_recv().is_err());
+
+        let (second_ack, second_rx) = oneshot::channel();
+        tx.blocking_send(SearchCommand {
+            ack: Some(second_ack),
+            ..command("m2")
+        })
+        .expect("worker should still accept commands after a panic");
+        assert!(matches!(second_rx.blocking_recv(), Ok(Ok(()))));
+
+        drop(tx);
+        assert_eq!(worker.join().expect("worker should exit cleanly"), 2);
+        let restarts = metrics_state()
+            .search_worker_restarts
+            .lock()
+            .expect("metrics lock")
+            .get("panic")
+            .copied()
+            .unwrap_or(0);
+        assert!(restarts >= 1);
+    }
+
     #[test]
     fn drain_search_batch_defaults_to_single_item_when_max_batch_is_zero() {
         let (tx, mut rx) = mpsc::channel::<SearchCommand>(4);
@@ -929,6 +1146,11 @@ mod tests {
                 visibility: GuildVisibility::Private,
                 created_by_user_id: author,
                 default_join_role_id: None,
+                description: None,
+                icon_attachment_id: None,
+                search_query_timeout_ms: None,
+                system_channel_id: None,
+                welcome_messages: false,
                 members: HashMap::from([(author, Role::Owner)]),
                 banned_members: HashSet::new(),
                 channels: HashMap::from([
@@ -937,6 +1159,10 @@ mod tests {
                         ChannelRecord {
                             name: String::from("general"),
                             kind: ChannelKind::Text,
+                            locked: false,
+                            retention_secs: None,
+                            voting: false,
+                            category_id: None,
                             messages: vec![MessageRecord {
                                 id: String::from("m1"),
                                 author_id: author,
@@ -945,6 +1171,9 @@ mod tests {
                                 attachment_ids: Vec::new(),
                                 created_at_unix: 10,
                                 reactions: HashMap::new(),
+                                deleted_at_unix: None,
+                                edited_at_unix: None,
+                                system: false,
                             }],
                             role_overrides: HashMap::new(),
                         },
@@ -954,6 +1183,10 @@ mod tests {
                         ChannelRecord {
                             name: String::from("random"),
                             kind: ChannelKind::Text,
+                            locked: false,
+                            retention_secs: None,
+                            voting: false,
+                            category_id: None,
                             messages: vec![MessageRecord {
                                 id: String::from("m2"),
                                 author_id: author,
@@ -962,6 +1195,9 @@ mod tests {
                                 attachment_ids: Vec::new(),
                                 created_at_unix: 11,
                                 reactions: HashMap::new(),
+                                deleted_at_unix: None,
+                                edited_at_unix: None,
+                                system: false,
                             }],
                             role_overrides: HashMap::new(),
                         },
@@ -970,7 +1206,8 @@ mod tests {
             },
         );
 
-        let docs = collect_all_indexed_messages_in_memory(&guilds);
+        let docs = collect_all_indexed_messages_in_memory(&guilds, 2)
+            .expect("both messages should fit the rebuild ceiling");
 
         assert_eq!(docs.len(), 2);
         assert!(docs.iter().any(|doc| {
@@ -988,33 +1225,64 @@ mod tests {
     }
 
     #[test]
-    fn collect_indexed_messages_for_guild_returns_not_found_for_missing_guild() {
-        let guilds = guild_with_messages("g1", &["m1"]);
+    fn collect_all_indexed_messages_rejects_stores_over_the_rebuild_ceiling() {
+        let guilds = guild_with_messages("g1", &["m1", "m2", "m3"]);
 
-        let result = collect_indexed_messages_for_guild_in_memory(&guilds, "missing", 10);
+        let result = collect_all_indexed_messages_in_memory(&guilds, 2);
 
-        assert!(matches!(result, Err(AuthFailure::NotFound)));
+        assert!(matches!(result, Err(AuthFailure::InMemoryCapacityReached)));
     }
 
     #[test]
-    fn collect_indexed_messages_for_guild_rejects_when_cap_is_exceeded() {
-        let guilds = guild_with_messages("g1", &["m1", "m2"]);
+    fn collect_indexed_messages_page_for_guild_returns_not_found_for_missing_guild() {
+        let guilds = guild_with_messages("g1", &["m1"]);
 
-        let result = collect_indexed_messages_for_guild_in_memory(&guilds, "g1", 1);
+        let result =
+            collect_indexed_messages_page_for_guild_in_memory(&guilds, "missing", None, 10);
 
-        assert!(matches!(result, Err(AuthFailure::InvalidRequest)));
+        assert!(matches!(result, Err(AuthFailure::NotFound)));
     }
 
     #[test]
-    fn collect_indexed_messages_for_guild_returns_all_messages_when_within_cap() {
-        let guilds = guild_with_messages("g1", &["m1", "m2"]);
-
-        let docs = collect_indexed_messages_for_guild_in_memory(&guilds, "g1", 2)
-            .expect("documents should be collected");
+    fn collect_indexed_messages_page_for_guild_pages_in_message_id_order() {
+        let guilds = guild_with_messages("g1", &["m3", "m1", "m4", "m2"]);
+
+        let first = collect_indexed_messages_page_for_guild_in_memory(&guilds, "g1", None, 2)
+            .expect("first page should be collected");
+        let first_ids: Vec<&str> = first.iter().map(|doc| doc.message_id.as_str()).collect();
+        assert_eq!(first_ids, vec!["m1", "m2"]);
+
+        let second =
+            collect_indexed_messages_page_for_guild_in_memory(&guilds, "g1", Some("m2"), 2)
+                .expect("second page should be collected");
+        let second_ids: Vec<&str> = second.iter().map(|doc| doc.message_id.as_str()).collect();
+        assert_eq!(second_ids, vec!["m3", "m4"]);
+
+        let last = collect_indexed_messages_page_for_guild_in_memory(&guilds, "g1", Some("m4"), 2)
+            .expect("empty page should be collected");
+        assert!(last.is_empty());
+    }
 
-        assert_eq!(docs.len(), 2);
-        assert!(docs.iter().any(|doc| doc.message_id == "m1"));
-        assert!(docs.iter().any(|doc| doc.message_id == "m2"));
+    #[test]
+    fn collect_indexed_messages_page_for_guild_skips_deleted_messages() {
+        let mut guilds = guild_with_messages("g1", &["m1", "m2", "m3", "m4", "m5"]);
+        let channel = guilds
+            .get_mut("g1")
+            .and_then(|guild| guild.channels.get_mut("c1"))
+            .expect("fixture channel should exist");
+        channel.messages[1].deleted_at_unix = Some(2);
+        channel.messages[2].deleted_at_unix = Some(2);
+
+        let first = collect_indexed_messages_page_for_guild_in_memory(&guilds, "g1", None, 2)
+            .expect("first page should be collected");
+        let first_ids: Vec<&str> = first.iter().map(|doc| doc.message_id.as_str()).collect();
+        assert_eq!(first_ids, vec!["m1", "m4"]);
+
+        let second =
+            collect_indexed_messages_page_for_guild_in_memory(&guilds, "g1", Some("m4"), 2)
+                .expect("second page should be collected");
+        let second_ids: Vec<&str> = second.iter().map(|doc| doc.message_id.as_str()).collect();
+        assert_eq!(second_ids, vec!["m5"]);
     }
 
     #[test]
@@ -1084,6 +1352,34 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn enqueue_search_command_drops_instead_of_blocking_when_queue_is_full() {
+        let (tx, mut rx) = mpsc::channel::<SearchCommand>(1);
+        tx.try_send(command("m1"))
+            .expect("first command should queue");
+
+        enqueue_search_command(&tx, command("m2").op, false)
+            .await
+            .expect("a full queue should not fail the write");
+
+        assert_eq!(rx.try_recv().ok().as_ref().and_then(message_id), Some("m1"));
+        assert!(rx.try_recv().is_err());
+        let dropped = metrics_state()
+            .search_index_ops_dropped
+            .lock()
+            .expect("metrics lock")
+            .get("queue_full")
+            .copied()
+            .unwrap_or(0);
+        assert!(dropped >= 1);
+
+        drop(rx);
+        assert!(matches!(
+            enqueue_search_command(&tx, command("m3").op, false).await,
+            Err(AuthFailure::Internal)
+        ));
+    }
+
     #[tokio::test]
     async fn enqueue_search_command_waits_for_ack_when_wait_is_true() {
         let (tx, mut rx) =
@@ -2,7 +2,7 @@ use filament_core::UserId;
 
 use crate::server::{
     auth::channel_key,
-    core::{AppState, VoiceParticipantsByChannel},
+    core::{AppState, ChannelKey, VoiceParticipantsByChannel},
     gateway_events::{self, GatewayEvent},
     metrics::record_gateway_event_dropped,
 };
@@ -61,7 +61,7 @@ fn build_voice_removal_events(
 fn plan_voice_removal_broadcasts(
     removals: Vec<VoiceParticipantRemoval>,
     event_at_unix: i64,
-) -> Result<Vec<(String, GatewayEvent)>, VoiceCleanupEventBuildError> {
+) -> Result<Vec<(ChannelKey, GatewayEvent)>, VoiceCleanupEventBuildError> {
     let mut planned = Vec::new();
     for removed in removals {
         let subscription_key = channel_key(&removed.guild_id, &removed.channel_id);
@@ -80,7 +80,7 @@ fn plan_voice_removal_broadcasts(
 fn expired_voice_removal_broadcasts(
     voice: &mut VoiceParticipantsByChannel,
     now_unix: i64,
-) -> Result<Vec<(String, GatewayEvent)>, VoiceCleanupEventBuildError> {
+) -> Result<Vec<(ChannelKey, GatewayEvent)>, VoiceCleanupEventBuildError> {
     let removed = take_expired_voice_participant_removals(voice, now_unix);
     plan_voice_removal_broadcasts(removed, now_unix)
 }
@@ -89,7 +89,7 @@ fn disconnected_user_voice_removal_broadcasts(
     voice: &mut VoiceParticipantsByChannel,
     user_id: UserId,
     disconnected_at_unix: i64,
-) -> Result<Vec<(String, GatewayEvent)>, VoiceCleanupEventBuildError> {
+) -> Result<Vec<(ChannelKey, GatewayEvent)>, VoiceCleanupEventBuildError> {
     let removed = remove_user_voice_participant_removals(voice, user_id);
     plan_voice_removal_broadcasts(removed, disconnected_at_unix)
 }
@@ -100,7 +100,7 @@ pub(crate) fn channel_user_voice_removal_broadcasts(
     channel_id: &str,
     user_id: UserId,
     removed_at_unix: i64,
-) -> Result<Vec<(String, GatewayEvent)>, VoiceCleanupEventBuildError> {
+) -> Result<Vec<(ChannelKey, GatewayEvent)>, VoiceCleanupEventBuildError> {
     let removed =
         remove_channel_user_voice_participant_removal(voice, guild_id, channel_id, user_id)
             .into_iter()
@@ -164,17 +164,17 @@ mod tests {
     use std::collections::{HashMap, HashSet};
 
     use crate::server::{
-        core::{VoiceParticipant, VoiceParticipantsByChannel, VoiceStreamKind},
+        core::{ChannelKey, VoiceParticipant, VoiceParticipantsByChannel, VoiceStreamKind},
         gateway_events::{
             self, GatewayEvent, VOICE_PARTICIPANT_LEAVE_EVENT, VOICE_STREAM_UNPUBLISH_EVENT,
         },
         realtime::voice_registry::VoiceParticipantRemoval,
     };
 
-    fn sample_planned() -> Vec<(String, GatewayEvent)> {
+    fn sample_planned() -> Vec<(ChannelKey, GatewayEvent)> {
         vec![
             (
-                String::from("voice:guild-1:channel-1"),
+                ChannelKey::new("guild-1", "channel-1"),
                 gateway_events::try_voice_participant_leave(
                     "guild-1",
                     "channel-1",
@@ -185,7 +185,7 @@ mod tests {
                 .expect("voice_participant_leave event should serialize"),
             ),
             (
-                String::from("voice:guild-1:channel-1"),
+                ChannelKey::new("guild-1", "channel-1"),
                 gateway_events::try_voice_participant_update(
                     "guild-1",
                     "channel-1",
@@ -203,7 +203,7 @@ mod tests {
         ]
     }
 
-    fn planned_event_count(planned: &[(String, GatewayEvent)]) -> usize {
+    fn planned_event_count(planned: &[(ChannelKey, GatewayEvent)]) -> usize {
         planned.len()
     }
 
@@ -274,9 +274,9 @@ mod tests {
             .expect("voice removal broadcasts should serialize");
 
         assert_eq!(planned.len(), 3);
-        assert_eq!(planned[0].0, "g1:c1");
-        assert_eq!(planned[1].0, "g1:c1");
-        assert_eq!(planned[2].0, "g1:c2");
+        assert_eq!(planned[0].0.to_string(), "g1:c1");
+        assert_eq!(planned[1].0.to_string(), "g1:c1");
+        assert_eq!(planned[2].0.to_string(), "g1:c2");
         assert_eq!(planned[0].1.event_type, VOICE_STREAM_UNPUBLISH_EVENT);
         assert_eq!(planned[1].1.event_type, VOICE_PARTICIPANT_LEAVE_EVENT);
         assert_eq!(planned[2].1.event_type, VOICE_PARTICIPANT_LEAVE_EVENT);
@@ -314,8 +314,8 @@ mod tests {
             .expect("expired cleanup events should serialize");
 
         assert_eq!(planned.len(), 2);
-        assert_eq!(planned[0].0, "g1:c1");
-        assert_eq!(planned[1].0, "g1:c1");
+        assert_eq!(planned[0].0.to_string(), "g1:c1");
+        assert_eq!(planned[1].0.to_string(), "g1:c1");
         assert_eq!(planned[0].1.event_type, VOICE_STREAM_UNPUBLISH_EVENT);
         assert_eq!(planned[1].1.event_type, VOICE_PARTICIPANT_LEAVE_EVENT);
         assert!(voice.is_empty());
@@ -338,8 +338,8 @@ mod tests {
                 .expect("disconnected cleanup events should serialize");
 
         assert_eq!(planned.len(), 2);
-        assert_eq!(planned[0].0, "g1:c1");
-        assert_eq!(planned[1].0, "g1:c1");
+        assert_eq!(planned[0].0.to_string(), "g1:c1");
+        assert_eq!(planned[1].0.to_string(), "g1:c1");
         assert_eq!(planned[0].1.event_type, VOICE_STREAM_UNPUBLISH_EVENT);
         assert_eq!(planned[1].1.event_type, VOICE_PARTICIPANT_LEAVE_EVENT);
         assert_eq!(voice["g1:c1"].len(), 1);
@@ -369,8 +369,8 @@ mod tests {
                 .expect("channel-scoped cleanup events should serialize");
 
         assert_eq!(planned.len(), 2);
-        assert_eq!(planned[0].0, "g2:c1");
-        assert_eq!(planned[1].0, "g2:c1");
+        assert_eq!(planned[0].0.to_string(), "g2:c1");
+        assert_eq!(planned[1].0.to_string(), "g2:c1");
         assert_eq!(planned[0].1.event_type, VOICE_STREAM_UNPUBLISH_EVENT);
         assert_eq!(planned[1].1.event_type, VOICE_PARTICIPANT_LEAVE_EVENT);
         assert_eq!(voice["g2:c1"].len(), 1);
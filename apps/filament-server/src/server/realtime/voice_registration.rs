@@ -4,7 +4,7 @@ use filament_core::UserId;
 
 use crate::server::{
     auth::channel_key,
-    core::{VoiceParticipant, VoiceParticipantsByChannel, VoiceStreamKind},
+    core::{ChannelKey, VoiceParticipant, VoiceParticipantsByChannel, VoiceStreamKind},
     errors::AuthFailure,
     gateway_events::{self, GatewayEvent},
 };
@@ -115,7 +115,7 @@ pub(crate) fn plan_voice_registration_events(
     user_id: UserId,
     identity: &str,
     event_at_unix: i64,
-) -> Result<Vec<(String, GatewayEvent)>, VoiceRegistrationEventBuildError> {
+) -> Result<Vec<(ChannelKey, GatewayEvent)>, VoiceRegistrationEventBuildError> {
     let mut planned = Vec::new();
 
     for (old_key, participant) in transition.removed {
@@ -226,7 +226,7 @@ mod tests {
 
     use super::{apply_voice_registration_transition, plan_voice_registration_events};
     use crate::server::{
-        core::{VoiceParticipant, VoiceParticipantsByChannel, VoiceStreamKind},
+        core::{ChannelKey, VoiceParticipant, VoiceParticipantsByChannel, VoiceStreamKind},
         errors::AuthFailure,
         gateway_events::{
             VOICE_PARTICIPANT_JOIN_EVENT, VOICE_PARTICIPANT_LEAVE_EVENT,
@@ -448,7 +448,10 @@ mod tests {
                 .expect("voice registration events should serialize");
 
         assert_eq!(planned.len(), 7);
-        let removed_key_events = planned.iter().filter(|(key, _)| key == "g1:c1").count();
+        let removed_key_events = planned
+            .iter()
+            .filter(|(key, _)| *key == ChannelKey::new("g1", "c1"))
+            .count();
         assert_eq!(removed_key_events, 3);
 
         let join_count = planned
@@ -6,6 +6,8 @@ use crate::server::metrics::{
     record_gateway_event_dropped, record_gateway_event_emitted, record_voice_sync_repair,
 };
 
+use super::OutboundSender;
+
 pub(crate) enum OutboundEnqueueResult {
     Enqueued,
     Closed,
@@ -61,7 +63,7 @@ pub(crate) fn collect_voice_snapshots(
 }
 
 pub(crate) fn try_enqueue_voice_sync_event(
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
     payload: String,
     max_gateway_event_bytes: usize,
 ) -> OutboundEnqueueResult {
@@ -96,7 +98,7 @@ pub(crate) fn try_build_voice_subscribe_sync_event(
 }
 
 pub(crate) fn dispatch_voice_sync_event(
-    outbound_tx: &mpsc::Sender<String>,
+    outbound_tx: &OutboundSender,
     event: GatewayEvent,
     max_gateway_event_bytes: usize,
 ) -> VoiceSyncDispatchOutcome {
@@ -144,6 +146,7 @@ mod tests {
     use crate::server::core::{VoiceParticipant, VoiceParticipantsByChannel, VoiceStreamKind};
     use crate::server::gateway_events::{self, VoiceParticipantSnapshot};
     use crate::server::metrics::metrics_state;
+    use crate::server::realtime::unbudgeted_outbound_queue;
 
     #[test]
     fn voice_channel_key_uses_guild_and_channel_namespace() {
@@ -271,7 +274,7 @@ mod tests {
 
     #[test]
     fn dispatch_voice_sync_event_returns_emitted_for_open_queue() {
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, mut rx) = unbudgeted_outbound_queue(1);
         let event = gateway_events::try_voice_participant_sync("g-1", "c-1", Vec::new(), 10)
             .expect("voice_participant_sync event should serialize");
         let expected_payload = event.payload.clone();
@@ -290,7 +293,7 @@ mod tests {
 
     #[test]
     fn enqueue_voice_sync_event_reports_enqueued() {
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, mut rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_voice_sync_event(&tx, String::from("payload"), 1024);
 
@@ -301,7 +304,7 @@ mod tests {
 
     #[test]
     fn enqueue_voice_sync_event_reports_full() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         assert!(matches!(
             try_enqueue_voice_sync_event(&tx, String::from("first"), 1024),
             OutboundEnqueueResult::Enqueued
@@ -314,7 +317,7 @@ mod tests {
 
     #[test]
     fn enqueue_voice_sync_event_reports_closed() {
-        let (tx, rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, rx) = unbudgeted_outbound_queue(1);
         drop(rx);
 
         let result = try_enqueue_voice_sync_event(&tx, String::from("payload"), 1024);
@@ -324,7 +327,7 @@ mod tests {
 
     #[test]
     fn enqueue_voice_sync_event_reports_oversized() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
 
         let result = try_enqueue_voice_sync_event(&tx, String::from("payload"), 3);
 
@@ -333,7 +336,7 @@ mod tests {
 
     #[test]
     fn dispatch_voice_sync_event_returns_full_for_full_queue() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         tx.try_send(String::from("occupied"))
             .expect("queue should be full");
         let event = gateway_events::try_voice_participant_sync("g-1", "c-1", Vec::new(), 10)
@@ -346,7 +349,7 @@ mod tests {
 
     #[test]
     fn dispatch_voice_sync_event_returns_closed_for_closed_queue() {
-        let (tx, rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, rx) = unbudgeted_outbound_queue(1);
         drop(rx);
         let event = gateway_events::try_voice_participant_sync("g-1", "c-1", Vec::new(), 10)
             .expect("voice_participant_sync event should serialize");
@@ -358,7 +361,7 @@ mod tests {
 
     #[test]
     fn dispatch_voice_sync_event_returns_oversized_for_large_payload() {
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         let event = gateway_events::try_voice_participant_sync("g-1", "c-1", Vec::new(), 10)
             .expect("voice_participant_sync event should serialize");
 
@@ -383,7 +386,7 @@ mod tests {
             ))
             .copied()
             .unwrap_or(0);
-        let (tx, _rx) = tokio::sync::mpsc::channel::<String>(1);
+        let (tx, _rx) = unbudgeted_outbound_queue(1);
         let event = gateway_events::try_voice_participant_sync("g-1", "c-1", Vec::new(), 10)
             .expect("voice_participant_sync event should serialize");
 
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     sync::Arc,
     time::Duration,
@@ -6,9 +7,12 @@ use std::{
 
 use anyhow::anyhow;
 use axum::{
+    body::Body,
     extract::ConnectInfo,
-    extract::DefaultBodyLimit,
-    http::{header::AUTHORIZATION, request::Request, HeaderName, StatusCode},
+    extract::{DefaultBodyLimit, MatchedPath, State},
+    http::{request::Request, HeaderMap, HeaderName, StatusCode},
+    middleware::{from_fn_with_state, Next},
+    response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
     Router,
 };
@@ -17,10 +21,9 @@ use pasetors::{
     claims::ClaimsValidationRules, keys::SymmetricKey, local, token::UntrustedToken, version4::V4,
     Local,
 };
-use tower::ServiceBuilder;
+use tower::{Layer, ServiceBuilder, ServiceExt};
 use tower_governor::{
     errors::GovernorError, governor::GovernorConfigBuilder, key_extractor::KeyExtractor,
-    GovernorLayer,
 };
 use tower_http::{
     request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
@@ -29,22 +32,43 @@ use tower_http::{
 };
 
 use super::{
-    auth::resolve_client_ip,
-    core::{AppConfig, AppState, MAX_LIVEKIT_TOKEN_TTL_SECS},
+    auth::{bearer_token, resolve_client_ip},
+    core::{
+        AppConfig, AppState, MAX_ATTACHMENT_KEY_PREFIX_CHARS, MAX_ATTACHMENT_KEY_SHARD_CHARS,
+        MAX_DELETED_MESSAGE_RETENTION_SECS, MAX_GATEWAY_MAX_CONNECTIONS_PER_USER,
+        MAX_GATEWAY_MAX_CONNECTION_LIFETIME_SECS, MAX_GATEWAY_SLOW_CONSUMER_MAX_STRIKES,
+        MAX_GATEWAY_SUBSCRIPTION_REVALIDATE_SECS, MAX_HISTORY_LIMIT, MAX_LIVEKIT_TOKEN_TTL_SECS,
+        MAX_MIME_SNIFF_BYTES, MAX_REFRESH_TOKEN_REUSE_GRACE_SECS, MAX_ROUTE_BODY_LIMIT_BYTES,
+        MAX_ROUTE_BODY_LIMIT_OVERRIDES, MAX_ROUTE_BODY_LIMIT_PATH_CHARS,
+        MAX_ROUTE_RATE_LIMIT_OVERRIDES, MAX_ROUTE_RATE_LIMIT_REQUESTS_PER_MINUTE,
+        MAX_SEARCH_QUERY_TIMEOUT_MILLIS, MAX_SEARCH_RECONCILE_DOCS, MAX_SEARCH_WRITER_HEAP_BYTES,
+        MIN_ADMIN_API_SECRET_CHARS, MIN_MIME_SNIFF_BYTES, MIN_SEARCH_WRITER_HEAP_BYTES,
+    },
     db::ensure_db_schema,
+    errors::AuthFailure,
     handlers::{
-        auth::{login, logout, lookup_users, me, refresh, register},
+        admin::{close_gateway_connection, list_gateway_connections, list_message_rates},
+        auth::{
+            export_me, login, logout, lookup_users, lookup_users_by_username, me, refresh,
+            register, token_status,
+        },
+        capabilities::get_capabilities,
         friends::{
-            accept_friend_request, create_friend_request, delete_friend_request,
+            accept_friend_request, create_friend_request, delete_friend_request, get_relationship,
             list_friend_requests, list_friends, remove_friend,
         },
         guilds::{
-            add_member, assign_guild_role, ban_member, create_channel, create_guild,
-            create_guild_role, delete_guild_role, join_public_guild, kick_member, list_guild_audit,
+            add_member, assign_guild_role, ban_member, create_channel, create_channel_batch,
+            create_guild, create_guild_role, delete_channel_role_override, delete_guild_role,
+            get_channel_access, get_guild, get_guild_system_channel, get_guild_tags,
+            join_public_guild, kick_member, list_channel_role_overrides, list_guild_audit,
             list_guild_channels, list_guild_ip_bans, list_guild_members, list_guild_roles,
-            list_guilds, list_public_guilds, remove_guild_ip_ban, reorder_guild_roles,
+            list_guilds, list_public_guilds, preview_channel_role_override, remove_guild_ip_ban,
+            reorder_guild_roles, reset_guild_channel_role_overrides,
             set_channel_permission_override, set_channel_role_override, unassign_guild_role,
-            update_guild, update_guild_default_join_role, update_guild_role, update_member_role,
+            update_channel, update_channel_category, update_channel_retention,
+            update_channel_voting, update_guild, update_guild_default_join_role, update_guild_role,
+            update_guild_system_channel, update_guild_tags, update_member_role,
             upsert_guild_ip_bans_by_user,
         },
         media::{
@@ -52,35 +76,50 @@ use super::{
             update_voice_participant_state, upload_attachment,
         },
         messages::{
-            add_reaction, create_message, delete_message, edit_message, get_channel_permissions,
-            get_messages, remove_reaction,
+            add_reaction, create_message, delete_message, edit_message, export_channel_messages,
+            get_channel_permissions, get_messages, get_recent_messages, move_message,
+            remove_reaction, restore_message,
         },
+        mutes::{mute_member, unmute_member},
+        notifications::{list_notifications, mark_notifications_read},
         profile::{
             download_user_avatar, download_user_banner, get_user_profile, update_my_profile,
             upload_my_avatar, upload_my_banner,
         },
-        search::{rebuild_search_index, reconcile_search_index, search_messages},
+        push::{create_push_subscription, remove_push_subscription},
+        reports::{list_guild_reports, report_message, report_user},
+        search::{
+            rebuild_all_search_indexes, rebuild_search_index, reconcile_search_index,
+            reindex_search_message, search_messages, update_guild_search_timeout,
+        },
     },
     realtime::gateway_ws,
     types::{echo, health, metrics, slow},
 };
 
-#[cfg(test)]
+/// Every `(method, path template)` the router registers. Route rate limit
+/// overrides are checked against these templates so a mistyped key fails at
+/// startup instead of never matching.
 pub(crate) const ROUTE_MANIFEST: &[(&str, &str)] = &[
     ("GET", "/health"),
     ("GET", "/metrics"),
     ("POST", "/echo"),
     ("GET", "/slow"),
+    ("GET", "/capabilities"),
     ("POST", "/auth/register"),
     ("POST", "/auth/login"),
     ("POST", "/auth/refresh"),
     ("POST", "/auth/logout"),
     ("GET", "/auth/me"),
+    ("GET", "/auth/me/export"),
+    ("GET", "/auth/token/status"),
     ("PATCH", "/users/me/profile"),
     ("GET", "/users/{user_id}/profile"),
     ("GET", "/users/{user_id}/avatar"),
     ("GET", "/users/{user_id}/banner"),
+    ("GET", "/users/{user_id}/relationship"),
     ("POST", "/users/lookup"),
+    ("POST", "/users/lookup-by-username"),
     ("GET", "/friends"),
     ("DELETE", "/friends/{friend_user_id}"),
     ("POST", "/friends/requests"),
@@ -89,7 +128,12 @@ pub(crate) const ROUTE_MANIFEST: &[(&str, &str)] = &[
     ("DELETE", "/friends/requests/{request_id}"),
     ("POST", "/guilds"),
     ("GET", "/guilds"),
+    ("GET", "/guilds/{guild_id}"),
     ("PATCH", "/guilds/{guild_id}"),
+    ("GET", "/guilds/{guild_id}/system-channel"),
+    ("PATCH", "/guilds/{guild_id}/system-channel"),
+    ("GET", "/guilds/{guild_id}/tags"),
+    ("PUT", "/guilds/{guild_id}/tags"),
     ("GET", "/guilds/public"),
     ("POST", "/guilds/{guild_id}/join"),
     ("GET", "/guilds/{guild_id}/audit"),
@@ -113,20 +157,41 @@ pub(crate) const ROUTE_MANIFEST: &[(&str, &str)] = &[
     ("DELETE", "/guilds/{guild_id}/ip-bans/{ban_id}"),
     ("POST", "/guilds/{guild_id}/channels"),
     ("GET", "/guilds/{guild_id}/channels"),
+    ("POST", "/guilds/{guild_id}/channels/batch"),
+    ("PATCH", "/guilds/{guild_id}/channels/{channel_id}"),
+    ("PATCH", "/guilds/{guild_id}/channels/{channel_id}/category"),
+    (
+        "PATCH",
+        "/guilds/{guild_id}/channels/{channel_id}/retention",
+    ),
+    ("PATCH", "/guilds/{guild_id}/channels/{channel_id}/voting"),
     (
         "GET",
         "/guilds/{guild_id}/channels/{channel_id}/permissions/self",
     ),
+    (
+        "GET",
+        "/guilds/{guild_id}/channels/{channel_id}/permissions/preview",
+    ),
+    ("GET", "/guilds/{guild_id}/channels/{channel_id}/access"),
+    ("GET", "/guilds/{guild_id}/channels/{channel_id}/overrides"),
     (
         "POST",
         "/guilds/{guild_id}/channels/{channel_id}/overrides/{role}",
     ),
+    (
+        "DELETE",
+        "/guilds/{guild_id}/channels/{channel_id}/overrides/{role}",
+    ),
+    ("POST", "/guilds/{guild_id}/permissions/reset"),
     (
         "POST",
         "/guilds/{guild_id}/channels/{channel_id}/permission-overrides/{target_kind}/{target_id}",
     ),
     ("POST", "/guilds/{guild_id}/channels/{channel_id}/messages"),
     ("GET", "/guilds/{guild_id}/channels/{channel_id}/messages"),
+    ("POST", "/guilds/{guild_id}/messages/recent"),
+    ("GET", "/guilds/{guild_id}/channels/{channel_id}/export"),
     (
         "PATCH",
         "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}",
@@ -135,6 +200,14 @@ pub(crate) const ROUTE_MANIFEST: &[(&str, &str)] = &[
         "DELETE",
         "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}",
     ),
+    (
+        "POST",
+        "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/restore",
+    ),
+    (
+        "POST",
+        "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/move",
+    ),
     (
         "POST",
         "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/reactions/{emoji}",
@@ -158,6 +231,25 @@ pub(crate) const ROUTE_MANIFEST: &[(&str, &str)] = &[
     ("GET", "/guilds/{guild_id}/search"),
     ("POST", "/guilds/{guild_id}/search/rebuild"),
     ("POST", "/guilds/{guild_id}/search/reconcile"),
+    ("PATCH", "/guilds/{guild_id}/search/timeout"),
+    ("POST", "/admin/search/rebuild"),
+    ("GET", "/admin/connections"),
+    ("GET", "/admin/message-rates"),
+    ("GET", "/notifications"),
+    ("POST", "/notifications/read"),
+    ("POST", "/push/subscriptions"),
+    ("DELETE", "/push/subscriptions/{subscription_id}"),
+    ("POST", "/admin/connections/{connection_id}/close"),
+    (
+        "POST",
+        "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/reindex",
+    ),
+    (
+        "POST",
+        "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/report",
+    ),
+    ("POST", "/users/{user_id}/report"),
+    ("GET", "/guilds/{guild_id}/reports"),
     (
         "GET",
         "/guilds/{guild_id}/channels/{channel_id}/attachments/{attachment_id}",
@@ -170,6 +262,8 @@ pub(crate) const ROUTE_MANIFEST: &[(&str, &str)] = &[
     ("PATCH", "/guilds/{guild_id}/members/{user_id}"),
     ("POST", "/guilds/{guild_id}/members/{user_id}/kick"),
     ("POST", "/guilds/{guild_id}/members/{user_id}/ban"),
+    ("POST", "/guilds/{guild_id}/members/{user_id}/mute"),
+    ("DELETE", "/guilds/{guild_id}/members/{user_id}/mute"),
     ("GET", "/gateway/ws"),
     (
         "POST",
@@ -195,34 +289,33 @@ impl TrustedClientIpKeyExtractor {
             token_key,
         }
     }
+
+    /// Returns the subject of a valid, unexpired bearer access token. Tokens
+    /// that fail to decrypt or validate fall back to IP keying, so a forged or
+    /// stale header cannot mint fresh per-user buckets.
+    fn authenticated_subject(&self, headers: &HeaderMap) -> Option<String> {
+        let token = bearer_token(headers)?;
+        let untrusted = UntrustedToken::<Local, V4>::try_from(token).ok()?;
+        let validation_rules = ClaimsValidationRules::new();
+        let trusted =
+            local::decrypt(&self.token_key, &untrusted, &validation_rules, None, None).ok()?;
+        trusted
+            .payload_claims()?
+            .get_claim("sub")?
+            .as_str()
+            .map(ToOwned::to_owned)
+    }
 }
 
+/// Keys authenticated requests on the token subject so users behind a shared
+/// NAT/CGNAT egress IP do not throttle each other; anonymous requests are keyed
+/// on the trusted-proxy-aware client IP.
 impl KeyExtractor for TrustedClientIpKeyExtractor {
     type Key = String;
 
     fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, GovernorError> {
-        if let Some(auth_header) = req.headers().get(AUTHORIZATION) {
-            if let Ok(auth_str) = auth_header.to_str() {
-                if let Some(token) = auth_str.strip_prefix("Bearer ") {
-                    if let Ok(untrusted) = UntrustedToken::<Local, V4>::try_from(token) {
-                        let validation_rules = ClaimsValidationRules::new();
-                        if let Ok(trusted) = local::decrypt(
-                            &self.token_key,
-                            &untrusted,
-                            &validation_rules,
-                            None,
-                            None,
-                        ) {
-                            if let Some(claims) = trusted.payload_claims() {
-                                if let Some(sub) = claims.get_claim("sub").and_then(|v| v.as_str())
-                                {
-                                    return Ok(format!("user:{sub}"));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        if let Some(subject) = self.authenticated_subject(req.headers()) {
+            return Ok(format!("user:{subject}"));
         }
 
         let peer_ip = req
@@ -237,6 +330,140 @@ impl KeyExtractor for TrustedClientIpKeyExtractor {
     }
 }
 
+/// Every bucket, global or per route, holds `requests_per_minute` tokens and
+/// regains one per period, so an override is comparable to the global limit.
+const RATE_LIMIT_REPLENISH_PERIOD: Duration = Duration::from_secs(60);
+
+type RouteLimitCheck = Arc<dyn Fn(&String) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+struct RouteLimit {
+    check: RouteLimitCheck,
+    requests_per_minute: u32,
+    /// One replenish period, which bounds how long a rejected caller must wait.
+    retry_after_secs: u64,
+}
+
+impl RouteLimit {
+    fn new(
+        route: &str,
+        requests_per_minute: u32,
+        key_extractor: &TrustedClientIpKeyExtractor,
+    ) -> anyhow::Result<Self> {
+        let period = RATE_LIMIT_REPLENISH_PERIOD;
+        let governor_config = GovernorConfigBuilder::default()
+            .period(period)
+            .burst_size(requests_per_minute)
+            .key_extractor(key_extractor.clone())
+            .finish()
+            .ok_or_else(|| anyhow!("invalid rate limit configuration for route {route}"))?;
+        let check: RouteLimitCheck =
+            Arc::new(move |key: &String| governor_config.limiter().check_key(key).is_ok());
+        Ok(Self {
+            check,
+            requests_per_minute,
+            retry_after_secs: period.as_secs() + u64::from(period.subsec_nanos() > 0),
+        })
+    }
+}
+
+/// The global governor plus per-route quotas, keyed by the trusted user/IP
+/// key so limits cannot be bypassed by rotating route parameters. Routes are
+/// matched on their axum path template (for example
+/// `/guilds/{guild_id}/search`). An override at or below the global limit is
+/// checked on top of the global bucket and can only tighten the route; an
+/// override above it replaces the global bucket for that route, up to
+/// `MAX_ROUTE_RATE_LIMIT_REQUESTS_PER_MINUTE`.
+#[derive(Clone)]
+struct RateLimits {
+    key_extractor: TrustedClientIpKeyExtractor,
+    global: RouteLimit,
+    routes: Arc<HashMap<String, RouteLimit>>,
+}
+
+impl RateLimits {
+    fn new(
+        global_requests_per_minute: u32,
+        overrides: &HashMap<String, u32>,
+        key_extractor: TrustedClientIpKeyExtractor,
+    ) -> anyhow::Result<Self> {
+        let global = RouteLimit::new("*", global_requests_per_minute, &key_extractor)?;
+        let mut routes = HashMap::with_capacity(overrides.len());
+        for (route, requests_per_minute) in overrides {
+            routes.insert(
+                route.clone(),
+                RouteLimit::new(route, *requests_per_minute, &key_extractor)?,
+            );
+        }
+        Ok(Self {
+            key_extractor,
+            global,
+            routes: Arc::new(routes),
+        })
+    }
+}
+
+/// Runs after routing so the matched path template is known; applied with
+/// `Router::layer` so unmatched paths still draw from the global bucket.
+async fn enforce_rate_limits(
+    State(limits): State<RateLimits>,
+    matched_path: Option<MatchedPath>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Ok(key) = limits.key_extractor.extract(&request) else {
+        return AuthFailure::RateLimited.into_response();
+    };
+    let route = matched_path
+        .as_ref()
+        .and_then(|path| limits.routes.get(path.as_str()));
+    let replaces_global =
+        route.is_some_and(|limit| limit.requests_per_minute > limits.global.requests_per_minute);
+    if !replaces_global && !(limits.global.check)(&key) {
+        return AuthFailure::RateLimitedRetryAfter(limits.global.retry_after_secs).into_response();
+    }
+    if let Some(limit) = route {
+        if !(limit.check)(&key) {
+            tracing::warn!(
+                event = "http.route_rate_limit",
+                route = matched_path.as_ref().map_or("", MatchedPath::as_str)
+            );
+            return AuthFailure::RateLimitedRetryAfter(limit.retry_after_secs).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Per-route JSON body limits keyed by axum path template. Matching requests
+/// get a route-specific `DefaultBodyLimit`, which replaces the global
+/// `max_body_bytes` cap for extractors on that route.
+#[derive(Clone)]
+struct RouteBodyLimits {
+    limits: Arc<HashMap<String, usize>>,
+}
+
+async fn apply_route_body_limit(
+    State(limits): State<RouteBodyLimits>,
+    matched_path: Option<MatchedPath>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(limit) = matched_path
+        .as_ref()
+        .and_then(|path| limits.limits.get(path.as_str()))
+    else {
+        return next.run(request).await;
+    };
+    match DefaultBodyLimit::max(*limit)
+        .layer(next)
+        .oneshot(request)
+        .await
+    {
+        Ok(response) => response,
+        Err(infallible) => match infallible {},
+    }
+}
+
 /// Build the axum router with global security middleware.
 ///
 /// # Errors
@@ -264,17 +491,182 @@ pub async fn build_router_with_db_bootstrap(config: &AppConfig) -> anyhow::Resul
     build_router_with_state(config, app_state)
 }
 
-fn validate_router_config(config: &AppConfig) -> anyhow::Result<()> {
-    if config.rate_limit_requests_per_minute == 0 {
+/// Overrides may tighten a route below the global limit or loosen it above,
+/// but never past `MAX_ROUTE_RATE_LIMIT_REQUESTS_PER_MINUTE`.
+fn validate_route_rate_limits(overrides: &HashMap<String, u32>) -> anyhow::Result<()> {
+    if overrides.len() > MAX_ROUTE_RATE_LIMIT_OVERRIDES {
         return Err(anyhow!(
-            "global rate limit must be at least 1 request per minute"
+            "route rate limit overrides cannot exceed {MAX_ROUTE_RATE_LIMIT_OVERRIDES} entries"
         ));
     }
-    if config.auth_route_requests_per_minute == 0 {
+    for (route, requests_per_minute) in overrides {
+        if !ROUTE_MANIFEST.iter().any(|(_, path)| path == route) {
+            return Err(anyhow!(
+                "route rate limit override key must be a registered route path template: {route:?}"
+            ));
+        }
+        if *requests_per_minute == 0
+            || *requests_per_minute > MAX_ROUTE_RATE_LIMIT_REQUESTS_PER_MINUTE
+        {
+            return Err(anyhow!(
+                "route rate limit override for {route} must be between 1 and {MAX_ROUTE_RATE_LIMIT_REQUESTS_PER_MINUTE} requests per minute"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Routes mounted with `DefaultBodyLimit::disable()` innermost, where a
+/// route body limit would never take effect.
+const UPLOAD_ROUTE_PATHS: [&str; 3] = [
+    "/guilds/{guild_id}/channels/{channel_id}/attachments",
+    "/users/me/profile/avatar",
+    "/users/me/profile/banner",
+];
+
+fn validate_route_body_limits(overrides: &HashMap<String, usize>) -> anyhow::Result<()> {
+    if overrides.len() > MAX_ROUTE_BODY_LIMIT_OVERRIDES {
         return Err(anyhow!(
-            "auth route rate limit must be at least 1 request per minute"
+            "route body limit overrides cannot exceed {MAX_ROUTE_BODY_LIMIT_OVERRIDES} entries"
+        ));
+    }
+    for (route, limit) in overrides {
+        if route.len() > MAX_ROUTE_BODY_LIMIT_PATH_CHARS
+            || !ROUTE_MANIFEST.iter().any(|(_, path)| path == route)
+        {
+            return Err(anyhow!(
+                "route body limit override key must be a registered route path template: {route:?}"
+            ));
+        }
+        if UPLOAD_ROUTE_PATHS.contains(&route.as_str()) {
+            return Err(anyhow!(
+                "route body limit override cannot target upload route {route}; uploads are bounded by the attachment and profile media limits"
+            ));
+        }
+        if *limit == 0 || *limit > MAX_ROUTE_BODY_LIMIT_BYTES {
+            return Err(anyhow!(
+                "route body limit override for {route} must be between 1 and {MAX_ROUTE_BODY_LIMIT_BYTES} bytes"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_search_config(config: &AppConfig) -> anyhow::Result<()> {
+    if config.search_requests_per_minute == 0 {
+        return Err(anyhow!(
+            "search rate limit must be at least 1 request per minute"
+        ));
+    }
+    if config.search_max_concurrent_queries == 0 {
+        return Err(anyhow!("search concurrency limit must be at least 1 query"));
+    }
+    if config.search_reconcile_batch_docs == 0
+        || config.search_reconcile_batch_docs > MAX_SEARCH_RECONCILE_DOCS
+    {
+        return Err(anyhow!(
+            "search reconcile batch size must be between 1 and {MAX_SEARCH_RECONCILE_DOCS}"
+        ));
+    }
+    if config.search_writer_heap_bytes < MIN_SEARCH_WRITER_HEAP_BYTES
+        || config.search_writer_heap_bytes > MAX_SEARCH_WRITER_HEAP_BYTES
+    {
+        return Err(anyhow!(
+            "search writer heap must be between {MIN_SEARCH_WRITER_HEAP_BYTES} and {MAX_SEARCH_WRITER_HEAP_BYTES} bytes"
+        ));
+    }
+    if config.search_query_timeout.is_zero()
+        || config.search_query_timeout_max < config.search_query_timeout
+        || config.search_query_timeout_max > Duration::from_millis(MAX_SEARCH_QUERY_TIMEOUT_MILLIS)
+    {
+        return Err(anyhow!(
+            "search query timeout max must be between the default search timeout and {MAX_SEARCH_QUERY_TIMEOUT_MILLIS} ms"
+        ));
+    }
+    Ok(())
+}
+
+fn validate_content_config(config: &AppConfig) -> anyhow::Result<()> {
+    if config.deleted_message_retention > Duration::from_secs(MAX_DELETED_MESSAGE_RETENTION_SECS) {
+        return Err(anyhow!(
+            "deleted message retention must be at most {MAX_DELETED_MESSAGE_RETENTION_SECS} seconds"
+        ));
+    }
+    if config.mime_sniff_bytes < MIN_MIME_SNIFF_BYTES
+        || config.mime_sniff_bytes > MAX_MIME_SNIFF_BYTES
+    {
+        return Err(anyhow!(
+            "mime sniff window must be between {MIN_MIME_SNIFF_BYTES} and {MAX_MIME_SNIFF_BYTES} bytes"
+        ));
+    }
+    if config.history_max_limit == 0 || config.history_max_limit > MAX_HISTORY_LIMIT {
+        return Err(anyhow!(
+            "history max limit must be between 1 and {MAX_HISTORY_LIMIT}"
+        ));
+    }
+    if config.history_default_limit == 0 || config.history_default_limit > config.history_max_limit
+    {
+        return Err(anyhow!(
+            "history default limit must be between 1 and the history max limit"
+        ));
+    }
+    if config.message_content_policy.max_combining_mark_percent > 100 {
+        return Err(anyhow!(
+            "message combining mark percent must be between 0 and 100"
+        ));
+    }
+    Ok(())
+}
+
+fn validate_attachment_config(config: &AppConfig) -> anyhow::Result<()> {
+    let prefix = &config.attachment_key_prefix;
+    let valid_prefix = !prefix.is_empty()
+        && prefix.len() <= MAX_ATTACHMENT_KEY_PREFIX_CHARS
+        && prefix.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+        });
+    if !valid_prefix {
+        return Err(anyhow!(
+            "attachment key prefix must be 1-{MAX_ATTACHMENT_KEY_PREFIX_CHARS} characters of `/`-separated [A-Za-z0-9_-] segments"
         ));
     }
+    if config.attachment_key_shard_chars > MAX_ATTACHMENT_KEY_SHARD_CHARS {
+        return Err(anyhow!(
+            "attachment key shard chars must be between 0 and {MAX_ATTACHMENT_KEY_SHARD_CHARS}"
+        ));
+    }
+    if config.max_message_attachment_total_bytes == 0 {
+        return Err(anyhow!(
+            "max message attachment total bytes must be at least 1"
+        ));
+    }
+    Ok(())
+}
+
+fn validate_tls_config(config: &AppConfig) -> anyhow::Result<()> {
+    match (&config.tls_cert_path, &config.tls_key_path) {
+        (None, None) | (Some(_), Some(_)) => Ok(()),
+        (Some(_), None) | (None, Some(_)) => Err(anyhow!(
+            "tls certificate and key paths must be set together"
+        )),
+    }
+}
+
+fn validate_admin_config(config: &AppConfig) -> anyhow::Result<()> {
+    if let Some(secret) = &config.admin_api_secret {
+        if secret.trim().chars().count() < MIN_ADMIN_API_SECRET_CHARS {
+            return Err(anyhow!(
+                "admin api secret must be at least {MIN_ADMIN_API_SECRET_CHARS} characters"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_gateway_config(config: &AppConfig) -> anyhow::Result<()> {
     if config.gateway_ingress_events_per_window == 0 {
         return Err(anyhow!(
             "gateway ingress rate limit must be at least 1 event per window"
@@ -283,12 +675,92 @@ fn validate_router_config(config: &AppConfig) -> anyhow::Result<()> {
     if config.gateway_ingress_window.is_zero() {
         return Err(anyhow!("gateway ingress window must be at least 1 second"));
     }
+    if config.gateway_slow_consumer_max_strikes == 0
+        || config.gateway_slow_consumer_max_strikes > MAX_GATEWAY_SLOW_CONSUMER_MAX_STRIKES
+    {
+        return Err(anyhow!(
+            "gateway slow consumer strikes must be between 1 and {MAX_GATEWAY_SLOW_CONSUMER_MAX_STRIKES}"
+        ));
+    }
+    if config.gateway_max_connection_lifetime.is_zero()
+        || config.gateway_max_connection_lifetime
+            > Duration::from_secs(MAX_GATEWAY_MAX_CONNECTION_LIFETIME_SECS)
+    {
+        return Err(anyhow!(
+            "gateway max connection lifetime must be between 1 and {MAX_GATEWAY_MAX_CONNECTION_LIFETIME_SECS} seconds"
+        ));
+    }
+    if config.gateway_max_connections_per_user == 0
+        || config.gateway_max_connections_per_user > MAX_GATEWAY_MAX_CONNECTIONS_PER_USER
+    {
+        return Err(anyhow!(
+            "gateway max connections per user must be between 1 and {MAX_GATEWAY_MAX_CONNECTIONS_PER_USER}"
+        ));
+    }
+    if config.gateway_subscription_revalidate_interval.is_zero()
+        || config.gateway_subscription_revalidate_interval
+            > Duration::from_secs(MAX_GATEWAY_SUBSCRIPTION_REVALIDATE_SECS)
+    {
+        return Err(anyhow!(
+            "gateway subscription revalidate interval must be between 1 and {MAX_GATEWAY_SUBSCRIPTION_REVALIDATE_SECS} seconds"
+        ));
+    }
     if config.max_gateway_event_bytes > filament_protocol::MAX_EVENT_BYTES {
         return Err(anyhow!(
             "gateway event limit cannot exceed protocol max of {} bytes",
             filament_protocol::MAX_EVENT_BYTES
         ));
     }
+    if config.gateway_outbound_buffer_max_bytes < config.max_gateway_event_bytes {
+        return Err(anyhow!(
+            "gateway outbound buffer budget must be at least the gateway event limit of {} bytes",
+            config.max_gateway_event_bytes
+        ));
+    }
+    Ok(())
+}
+
+fn validate_in_memory_config(config: &AppConfig) -> anyhow::Result<()> {
+    if config.in_memory_max_guilds == 0 {
+        return Err(anyhow!("in-memory max guilds must be at least 1 guild"));
+    }
+    if config.in_memory_max_channel_messages == 0 {
+        return Err(anyhow!(
+            "in-memory max channel messages must be at least 1 message"
+        ));
+    }
+    if config.in_memory_max_friend_requests == 0 {
+        return Err(anyhow!(
+            "in-memory max friend requests must be at least 1 request"
+        ));
+    }
+    if config.in_memory_max_total_friend_requests == 0 {
+        return Err(anyhow!(
+            "in-memory max total friend requests must be at least 1 request"
+        ));
+    }
+    if config.in_memory_max_rebuild_docs == 0 {
+        return Err(anyhow!(
+            "in-memory max rebuild docs must be at least 1 document"
+        ));
+    }
+    Ok(())
+}
+
+fn validate_router_config(config: &AppConfig) -> anyhow::Result<()> {
+    if config.rate_limit_requests_per_minute == 0 {
+        return Err(anyhow!(
+            "global rate limit must be at least 1 request per minute"
+        ));
+    }
+    validate_route_rate_limits(&config.route_rate_limits)?;
+    validate_route_body_limits(&config.route_body_limits)?;
+    if config.auth_route_requests_per_minute == 0 {
+        return Err(anyhow!(
+            "auth route rate limit must be at least 1 request per minute"
+        ));
+    }
+    validate_gateway_config(config)?;
     if config.media_token_requests_per_minute == 0 {
         return Err(anyhow!(
             "media token rate limit must be at least 1 request per minute"
@@ -299,6 +771,7 @@ fn validate_router_config(config: &AppConfig) -> anyhow::Result<()> {
             "media publish rate limit must be at least 1 request per minute"
         ));
     }
+    validate_search_config(config)?;
     if config.media_subscribe_token_cap_per_channel == 0 {
         return Err(anyhow!(
             "media subscribe token cap must be at least 1 active token"
@@ -309,6 +782,14 @@ fn validate_router_config(config: &AppConfig) -> anyhow::Result<()> {
             "max created guilds per user must be at least 1 guild"
         ));
     }
+    if config.max_friends_per_user == 0 {
+        return Err(anyhow!("max friends per user must be at least 1 friend"));
+    }
+    if config.max_incoming_friend_requests_per_user == 0 {
+        return Err(anyhow!(
+            "max incoming friend requests per user must be at least 1 request"
+        ));
+    }
     if config.directory_join_requests_per_minute_per_ip == 0 {
         return Err(anyhow!(
             "directory join per-ip rate limit must be at least 1 request per minute"
@@ -324,6 +805,11 @@ fn validate_router_config(config: &AppConfig) -> anyhow::Result<()> {
             "audit list limit max must be at least 1 record per request"
         ));
     }
+    if config.audit_log_memory_max_entries == 0 {
+        return Err(anyhow!(
+            "audit log memory max entries must be at least 1 record"
+        ));
+    }
     if config.guild_ip_ban_max_entries == 0 {
         return Err(anyhow!(
             "guild ip ban max entries must be at least 1 record"
@@ -342,6 +828,16 @@ fn validate_router_config(config: &AppConfig) -> anyhow::Result<()> {
             "livekit token ttl must be between 1 and {MAX_LIVEKIT_TOKEN_TTL_SECS} seconds"
         ));
     }
+    if config.refresh_token_reuse_grace > Duration::from_secs(MAX_REFRESH_TOKEN_REUSE_GRACE_SECS) {
+        return Err(anyhow!(
+            "refresh token reuse grace must be at most {MAX_REFRESH_TOKEN_REUSE_GRACE_SECS} seconds"
+        ));
+    }
+    validate_content_config(config)?;
+    validate_attachment_config(config)?;
+    validate_tls_config(config)?;
+    validate_admin_config(config)?;
+    validate_in_memory_config(config)?;
 
     Ok(())
 }
@@ -351,36 +847,54 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
     tokio::spawn(crate::server::realtime::livekit_sync::start_livekit_sync(
         app_state.clone(),
     ));
+    tokio::spawn(crate::server::domain::start_deleted_message_purge(
+        app_state.clone(),
+    ));
+    tokio::spawn(crate::server::domain::start_channel_retention_prune(
+        app_state.clone(),
+    ));
+    tokio::spawn(crate::server::domain::start_guild_mute_purge(
+        app_state.clone(),
+    ));
+    tokio::spawn(crate::server::domain::start_link_preview_prune(
+        app_state.clone(),
+    ));
+    tokio::spawn(crate::server::domain::start_audit_log_purge(
+        app_state.clone(),
+    ));
 
-    let governor_config = Arc::new(
-        GovernorConfigBuilder::default()
-            .period(Duration::from_secs(60))
-            .burst_size(config.rate_limit_requests_per_minute)
-            .key_extractor(TrustedClientIpKeyExtractor::new(
-                Arc::new(config.trusted_proxy_cidrs.clone()),
-                app_state.token_key.clone(),
-            ))
-            .finish()
-            .ok_or_else(|| anyhow!("invalid governor configuration"))?,
+    let key_extractor = TrustedClientIpKeyExtractor::new(
+        Arc::new(config.trusted_proxy_cidrs.clone()),
+        app_state.token_key.clone(),
     );
+    let rate_limits = RateLimits::new(
+        config.rate_limit_requests_per_minute,
+        &config.route_rate_limits,
+        key_extractor,
+    )?;
+    let route_body_limits = RouteBodyLimits {
+        limits: Arc::new(config.route_body_limits.clone()),
+    };
     let request_id_header = HeaderName::from_static("x-request-id");
-    let governor_layer = GovernorLayer::new(governor_config);
 
-    let routes = Router::new()
+    let mut routes = Router::new()
         .route("/health", get(health))
         .route("/metrics", get(metrics))
-        .route("/echo", post(echo))
-        .route("/slow", get(slow))
+        .route("/capabilities", get(get_capabilities))
         .route("/auth/register", post(register))
         .route("/auth/login", post(login))
         .route("/auth/refresh", post(refresh))
         .route("/auth/logout", post(logout))
         .route("/auth/me", get(me))
+        .route("/auth/me/export", get(export_me))
+        .route("/auth/token/status", get(token_status))
         .route("/users/me/profile", patch(update_my_profile))
         .route("/users/{user_id}/profile", get(get_user_profile))
         .route("/users/{user_id}/avatar", get(download_user_avatar))
         .route("/users/{user_id}/banner", get(download_user_banner))
+        .route("/users/{user_id}/relationship", get(get_relationship))
         .route("/users/lookup", post(lookup_users))
+        .route("/users/lookup-by-username", post(lookup_users_by_username))
         .route("/friends", get(list_friends))
         .route("/friends/{friend_user_id}", delete(remove_friend))
         .route(
@@ -396,7 +910,15 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
             delete(delete_friend_request),
         )
         .route("/guilds", post(create_guild).get(list_guilds))
-        .route("/guilds/{guild_id}", patch(update_guild))
+        .route("/guilds/{guild_id}", get(get_guild).patch(update_guild))
+        .route(
+            "/guilds/{guild_id}/system-channel",
+            get(get_guild_system_channel).patch(update_guild_system_channel),
+        )
+        .route(
+            "/guilds/{guild_id}/tags",
+            get(get_guild_tags).put(update_guild_tags),
+        )
         .route("/guilds/public", get(list_public_guilds))
         .route("/guilds/{guild_id}/join", post(join_public_guild))
         .route("/guilds/{guild_id}/audit", get(list_guild_audit))
@@ -433,13 +955,49 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
             "/guilds/{guild_id}/channels",
             post(create_channel).get(list_guild_channels),
         )
+        .route(
+            "/guilds/{guild_id}/channels/batch",
+            post(create_channel_batch),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}",
+            patch(update_channel),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/category",
+            patch(update_channel_category),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/retention",
+            patch(update_channel_retention),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/voting",
+            patch(update_channel_voting),
+        )
         .route(
             "/guilds/{guild_id}/channels/{channel_id}/permissions/self",
             get(get_channel_permissions),
         )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/permissions/preview",
+            get(preview_channel_role_override),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/access",
+            get(get_channel_access),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/overrides",
+            get(list_channel_role_overrides),
+        )
         .route(
             "/guilds/{guild_id}/channels/{channel_id}/overrides/{role}",
-            post(set_channel_role_override),
+            post(set_channel_role_override).delete(delete_channel_role_override),
+        )
+        .route(
+            "/guilds/{guild_id}/permissions/reset",
+            post(reset_guild_channel_role_overrides),
         )
         .route(
             "/guilds/{guild_id}/channels/{channel_id}/permission-overrides/{target_kind}/{target_id}",
@@ -449,10 +1007,26 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
             "/guilds/{guild_id}/channels/{channel_id}/messages",
             post(create_message).get(get_messages),
         )
+        .route(
+            "/guilds/{guild_id}/messages/recent",
+            post(get_recent_messages),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/export",
+            get(export_channel_messages),
+        )
         .route(
             "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}",
             patch(edit_message).delete(delete_message),
         )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/restore",
+            post(restore_message),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/move",
+            post(move_message),
+        )
         .route(
             "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/reactions/{emoji}",
             post(add_reaction).delete(remove_reaction),
@@ -478,6 +1052,34 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
             "/guilds/{guild_id}/search/reconcile",
             post(reconcile_search_index),
         )
+        .route(
+            "/guilds/{guild_id}/search/timeout",
+            patch(update_guild_search_timeout),
+        )
+        .route("/admin/search/rebuild", post(rebuild_all_search_indexes))
+        .route("/admin/connections", get(list_gateway_connections))
+        .route("/admin/message-rates", get(list_message_rates))
+        .route("/notifications", get(list_notifications))
+        .route("/notifications/read", post(mark_notifications_read))
+        .route("/push/subscriptions", post(create_push_subscription))
+        .route(
+            "/push/subscriptions/{subscription_id}",
+            delete(remove_push_subscription),
+        )
+        .route(
+            "/admin/connections/{connection_id}/close",
+            post(close_gateway_connection),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/reindex",
+            post(reindex_search_message),
+        )
+        .route(
+            "/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/report",
+            post(report_message),
+        )
+        .route("/users/{user_id}/report", post(report_user))
+        .route("/guilds/{guild_id}/reports", get(list_guild_reports))
         .route(
             "/guilds/{guild_id}/channels/{channel_id}/attachments/{attachment_id}",
             get(download_attachment).delete(delete_attachment),
@@ -492,8 +1094,16 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
             post(kick_member),
         )
         .route("/guilds/{guild_id}/members/{user_id}/ban", post(ban_member))
+        .route(
+            "/guilds/{guild_id}/members/{user_id}/mute",
+            post(mute_member).delete(unmute_member),
+        )
         .route("/gateway/ws", get(gateway_ws));
+    if config.enable_debug_routes {
+        routes = routes.route("/echo", post(echo)).route("/slow", get(slow));
+    }
 
+    // Keep in sync with `UPLOAD_ROUTE_PATHS`.
     let upload_route = Router::new()
         .route(
             "/guilds/{guild_id}/channels/{channel_id}/attachments",
@@ -505,6 +1115,11 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
 
     Ok(routes
         .merge(upload_route)
+        .route_layer(from_fn_with_state(
+            route_body_limits,
+            apply_route_body_limit,
+        ))
+        .layer(from_fn_with_state(rate_limits, enforce_rate_limits))
         .with_state(app_state)
         .layer(DefaultBodyLimit::max(config.max_body_bytes))
         .layer(
@@ -515,16 +1130,119 @@ fn build_router_with_state(config: &AppConfig, app_state: AppState) -> anyhow::R
                 .layer(TimeoutLayer::with_status_code(
                     StatusCode::REQUEST_TIMEOUT,
                     config.request_timeout,
-                ))
-                .layer(governor_layer),
+                )),
         ))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::build_router_with_db_bootstrap;
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+
+    use axum::{body::Body, http::Request};
+    use pasetors::{claims::Claims, keys::SymmetricKey, local, version4::V4};
+    use tower_governor::key_extractor::KeyExtractor;
+
+    use super::{
+        build_router_with_db_bootstrap, RateLimits, TrustedClientIpKeyExtractor, ROUTE_MANIFEST,
+        UPLOAD_ROUTE_PATHS,
+    };
     use crate::server::core::AppConfig;
 
+    fn access_token_for(key: &SymmetricKey<V4>, subject: &str) -> String {
+        let mut claims =
+            Claims::new_expires_in(&Duration::from_secs(60)).expect("claims should build");
+        claims.subject(subject).expect("subject should set");
+        local::encrypt(key, &claims, None, None).expect("token should encrypt")
+    }
+
+    fn request_with_bearer(token: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().uri("/guilds");
+        if let Some(token) = token {
+            builder = builder.header("authorization", format!("Bearer {token}"));
+        }
+        builder.body(Body::empty()).expect("request should build")
+    }
+
+    #[test]
+    fn governor_key_separates_authenticated_users_sharing_an_ip() {
+        let key = SymmetricKey::<V4>::from(&[7_u8; 32]).expect("key should build");
+        let extractor = TrustedClientIpKeyExtractor::new(
+            Arc::new(Vec::new()),
+            Arc::new(SymmetricKey::<V4>::from(key.as_bytes()).expect("key should copy")),
+        );
+
+        let alice = extractor
+            .extract(&request_with_bearer(Some(&access_token_for(&key, "alice"))))
+            .expect("key should extract");
+        let bob = extractor
+            .extract(&request_with_bearer(Some(&access_token_for(&key, "bob"))))
+            .expect("key should extract");
+        let anonymous = extractor
+            .extract(&request_with_bearer(None))
+            .expect("key should extract");
+
+        assert_eq!(alice, "user:alice");
+        assert_eq!(bob, "user:bob");
+        assert!(anonymous.starts_with("ip:"));
+    }
+
+    #[test]
+    fn governor_key_falls_back_to_ip_for_untrusted_tokens() {
+        let key = SymmetricKey::<V4>::from(&[7_u8; 32]).expect("key should build");
+        let foreign_key = SymmetricKey::<V4>::from(&[9_u8; 32]).expect("key should build");
+        let extractor = TrustedClientIpKeyExtractor::new(Arc::new(Vec::new()), Arc::new(key));
+
+        let forged = extractor
+            .extract(&request_with_bearer(Some(&access_token_for(
+                &foreign_key,
+                "mallory",
+            ))))
+            .expect("key should extract");
+        let garbage = extractor
+            .extract(&request_with_bearer(Some("not-a-token")))
+            .expect("key should extract");
+
+        assert!(forged.starts_with("ip:"));
+        assert!(garbage.starts_with("ip:"));
+    }
+
+    #[test]
+    fn upload_route_paths_are_registered_routes() {
+        for upload in UPLOAD_ROUTE_PATHS {
+            assert!(
+                ROUTE_MANIFEST.iter().any(|(_, path)| *path == upload),
+                "{upload} missing from the route manifest"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn route_override_one_above_global_refills_like_the_global_bucket() {
+        let extractor = TrustedClientIpKeyExtractor::new(
+            Arc::new(Vec::new()),
+            Arc::new(SymmetricKey::<V4>::from(&[7_u8; 32]).expect("key should build")),
+        );
+        let global_rpm = 5_000;
+        let overrides = HashMap::from([(String::from("/guilds"), global_rpm + 1)]);
+        let limits =
+            RateLimits::new(global_rpm, &overrides, extractor).expect("limits should build");
+        let route = &limits.routes["/guilds"];
+        assert_eq!(route.retry_after_secs, limits.global.retry_after_secs);
+
+        let key = String::from("ip:203.0.113.9");
+        for _ in 0..global_rpm {
+            assert!((limits.global.check)(&key));
+        }
+        assert!(!(limits.global.check)(&key));
+        for _ in 0..=global_rpm {
+            assert!((route.check)(&key));
+        }
+        assert!(!(route.check)(&key));
+        // A per-request period would have refilled several tokens by now.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!(route.check)(&key));
+    }
+
     #[tokio::test]
     async fn build_router_with_db_bootstrap_fails_fast_when_schema_init_fails() {
         let result = build_router_with_db_bootstrap(&AppConfig {
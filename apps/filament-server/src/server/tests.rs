@@ -4,25 +4,28 @@ mod tests {
     use super::super::{
         auth::{channel_key, hash_password},
         core::{
-            AppConfig, AppState, AuthContext, ChannelRecord, ConnectionControl, GuildRecord,
-            GuildVisibility, UserRecord, DEFAULT_MAX_GATEWAY_EVENT_BYTES,
+            AppConfig, AppState, AuthContext, ChannelRecord, ConnectionControl, ConnectionPresence,
+            GuildRecord, GuildVisibility, UserRecord, ACCESS_TOKEN_TTL_SECS,
+            DEFAULT_MAX_GATEWAY_EVENT_BYTES, MAX_USER_LOOKUP_IDS,
         },
         directory_contract::IpNetwork,
         gateway_events,
         realtime::{
             add_subscription, broadcast_channel_event, broadcast_guild_event, broadcast_user_event,
-            create_message_internal,
+            create_message_internal, unbudgeted_outbound_queue,
         },
         router::{build_router, ROUTE_MANIFEST},
         types::AuthResponse,
     };
     use axum::{body::Body, extract::connect_info::ConnectInfo, http::Request, http::StatusCode};
-    use filament_core::{ChannelKind, Role, UserId, Username};
+    use filament_core::{
+        ChannelKind, ChannelPermissionOverwrite, Permission, PermissionSet, Role, UserId, Username,
+    };
     use serde_json::{json, Value};
     use std::{collections::HashMap, net::SocketAddr, time::Duration};
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
     use tokio::net::TcpListener;
-    use tokio::sync::{mpsc, watch};
+    use tokio::sync::watch;
     use tower::ServiceExt;
     use uuid::Uuid;
 
@@ -408,4 +411,5 @@ mod tests {
     mod guilds;
     mod ip_ban;
     mod profile;
+    mod search;
 }
@@ -81,10 +81,10 @@ async fn guild_audit_endpoint_enforces_authz_and_returns_redacted_events() {
 
     let (outsider_status, outsider_payload) =
         list_guild_audit_for_test(&app, &outsider_auth, "203.0.113.224", &guild_id, None).await;
-    assert_eq!(outsider_status, StatusCode::FORBIDDEN);
+    assert_eq!(outsider_status, StatusCode::NOT_FOUND);
     assert_eq!(
         outsider_payload.expect("outsider denial payload")["error"],
-        "audit_access_denied"
+        "not_found"
     );
 
     let (unknown_status, unknown_payload) = list_guild_audit_for_test(
@@ -86,6 +86,55 @@ async fn auth_flow_register_login_me_refresh_logout_and_replay_detection() {
     );
 }
 
+#[tokio::test]
+async fn token_status_reports_subject_and_remaining_ttl() {
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let login_body = register_and_login(&app, "203.0.113.14").await;
+
+    let (status, me) = authed_json_request(
+        &app,
+        "GET",
+        String::from("/auth/me"),
+        &login_body.access_token,
+        "203.0.113.14",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, token) = authed_json_request(
+        &app,
+        "GET",
+        String::from("/auth/token/status"),
+        &login_body.access_token,
+        "203.0.113.14",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let token = token.unwrap();
+    assert_eq!(token["user_id"], me.unwrap()["user_id"]);
+    let expires_in_secs = token["expires_in_secs"].as_i64().unwrap();
+    assert!(expires_in_secs > ACCESS_TOKEN_TTL_SECS - 60);
+    assert!(expires_in_secs <= ACCESS_TOKEN_TTL_SECS);
+    assert!(token["expires_at_unix"].as_i64().unwrap() > expires_in_secs);
+
+    let (status, _) = authed_json_request(
+        &app,
+        "GET",
+        String::from("/auth/token/status"),
+        "not-a-token",
+        "203.0.113.14",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn register_requires_valid_hcaptcha_when_enabled() {
     let verify_url = spawn_hcaptcha_stub(false).await;
@@ -161,6 +210,43 @@ async fn register_accepts_valid_hcaptcha_when_enabled() {
     assert_eq!(response.status(), StatusCode::OK);
 }
 
+#[tokio::test]
+async fn captcha_verify_refuses_internal_https_destinations() {
+    let verifier = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let app = build_router(&AppConfig {
+        captcha_hcaptcha_site_key: Some(String::from("10000000-ffff-ffff-ffff-000000000001")),
+        captcha_hcaptcha_secret: Some(String::from("0x0000000000000000000000000000000000000000")),
+        captcha_verify_url: format!(
+            "https://127.0.0.1:{}/siteverify",
+            verifier.local_addr().unwrap().port()
+        ),
+        ..AppConfig::default()
+    })
+    .unwrap();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/auth/register")
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", "203.0.113.15")
+        .body(Body::from(
+            json!({
+                "username":"captcha_ssrf",
+                "password":"super-secure-password",
+                "captcha_token":"tok_111111111111111111111111111111111111"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let contacted = tokio::time::timeout(Duration::from_millis(100), verifier.accept()).await;
+    assert!(
+        contacted.is_err(),
+        "captcha verification must not connect to a loopback address"
+    );
+}
+
 #[tokio::test]
 async fn login_errors_do_not_enumerate_accounts() {
     let app = build_router(&AppConfig {
@@ -366,4 +452,205 @@ async fn metrics_endpoint_exposes_auth_and_rate_limit_counters() {
     assert!(metrics_text.contains("filament_gateway_events_unknown_received_total"));
     assert!(metrics_text.contains("filament_gateway_events_parse_rejected_total"));
     assert!(metrics_text.contains("filament_voice_sync_repairs_total"));
+    assert!(metrics_text.contains("filament_push_deliveries_total"));
+    assert!(metrics_text.contains("filament_channel_retention_pruned_total"));
+    assert!(metrics_text.contains("filament_search_worker_restarts_total"));
+    assert!(metrics_text.contains("filament_search_index_ops_dropped_total"));
+    assert!(metrics_text.contains("filament_messages_created_total"));
+    assert!(metrics_text.contains("filament_audit_log_entries_written_total"));
+    assert!(metrics_text.contains("filament_audit_log_entries_pruned_total"));
+    assert!(metrics_text.contains("filament_gateway_outbound_buffered_bytes"));
+}
+
+#[tokio::test]
+async fn lookup_by_username_resolves_ids_in_request_order() {
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let alice = register_and_login_as(&app, "alice_lookup", "203.0.113.61").await;
+    let bob = register_and_login_as(&app, "bob_lookup", "203.0.113.62").await;
+    let bob_id = user_id_from_me(&app, &bob, "203.0.113.62").await;
+
+    let (status, payload) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/users/lookup-by-username"),
+        &alice.access_token,
+        "203.0.113.61",
+        Some(json!({"usernames":["bob_lookup","ghost_lookup","bob_lookup","alice_lookup"]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let users = payload.unwrap()["users"].as_array().unwrap().clone();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0]["username"], "bob_lookup");
+    assert_eq!(users[0]["user_id"], bob_id);
+    assert_eq!(users[1]["username"], "alice_lookup");
+
+    let (me_status, me_payload) = authed_json_request(
+        &app,
+        "GET",
+        String::from("/auth/me"),
+        &bob.access_token,
+        "203.0.113.62",
+        None,
+    )
+    .await;
+    assert_eq!(me_status, StatusCode::OK);
+    let bob_created_at = me_payload.unwrap()["created_at_unix"].as_i64().unwrap();
+    assert!(bob_created_at > 0);
+    assert_eq!(users[0]["created_at_unix"], bob_created_at);
+
+    let too_many: Vec<String> = (0..=MAX_USER_LOOKUP_IDS)
+        .map(|index| format!("user_{index}"))
+        .collect();
+    for body in [
+        json!({"usernames":[]}),
+        json!({"usernames":too_many}),
+        json!({"usernames":["not a username"]}),
+    ] {
+        let (status, _) = authed_json_request(
+            &app,
+            "POST",
+            String::from("/users/lookup-by-username"),
+            &alice.access_token,
+            "203.0.113.61",
+            Some(body),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn capabilities_report_limits_and_optional_features_without_secrets() {
+    let capabilities = |app: axum::Router| async move {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/capabilities")
+                    .header("x-forwarded-for", "198.51.100.140")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice::<Value>(&body).unwrap()
+    };
+
+    let plain = capabilities(
+        build_router(&AppConfig {
+            max_attachment_bytes: 4096,
+            ..AppConfig::default()
+        })
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(plain["max_message_content_bytes"], 2000);
+    assert_eq!(plain["max_attachments_per_message"], 5);
+    assert_eq!(plain["max_attachment_bytes"], 4096);
+    assert_eq!(plain["channel_kinds"], json!(["text", "voice", "category"]));
+    assert!(plain["captcha"].is_null());
+    assert!(plain["push"].is_null());
+    assert_eq!(plain["voice_enabled"], false);
+    assert_eq!(plain["search_enabled"], true);
+
+    let configured = capabilities(
+        build_router(&AppConfig {
+            captcha_hcaptcha_site_key: Some(String::from("10000000-ffff-ffff-ffff-000000000001")),
+            captcha_hcaptcha_secret: Some(String::from(
+                "0x0000000000000000000000000000000000000000",
+            )),
+            livekit_api_key: Some(String::from("devkey")),
+            livekit_api_secret: Some(String::from("devsecret")),
+            search_enabled: false,
+            ..AppConfig::default()
+        })
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(configured["captcha"]["provider"], "hcaptcha");
+    assert_eq!(
+        configured["captcha"]["site_key"],
+        "10000000-ffff-ffff-ffff-000000000001"
+    );
+    assert_eq!(configured["voice_enabled"], true);
+    assert_eq!(configured["search_enabled"], false);
+    let rendered = configured.to_string();
+    assert!(!rendered.contains("0x0000000000000000000000000000000000000000"));
+    assert!(!rendered.contains("devsecret"));
+}
+
+#[tokio::test]
+async fn validation_errors_name_the_violated_limit() {
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let ip = "203.0.113.150";
+
+    for (password, error, limit) in [
+        ("short", "password_too_short", 12),
+        (&"p".repeat(129)[..], "password_too_long", 128),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/auth/register")
+                    .header("content-type", "application/json")
+                    .header("x-forwarded-for", ip)
+                    .body(Body::from(
+                        json!({"username":"limits_user","password":password}).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let payload: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(payload, json!({"error":error,"limit":limit}));
+    }
+
+    let auth = register_and_login(&app, ip).await;
+    let guild_id = create_guild_for_test(&app, &auth, ip).await;
+    let channel_id = create_channel_for_test(&app, &auth, ip, &guild_id).await;
+    let too_many_attachments: Vec<String> = (0..6).map(|_| ulid::Ulid::new().to_string()).collect();
+    for (body, expected) in [
+        (json!({"content":""}), json!({"error":"content_empty"})),
+        (
+            json!({"content":"a".repeat(2001)}),
+            json!({"error":"content_too_long","limit":2000}),
+        ),
+        (
+            json!({"content":"hi","attachment_ids":too_many_attachments}),
+            json!({"error":"too_many_attachments","limit":5}),
+        ),
+    ] {
+        let (status, payload) = authed_json_request(
+            &app,
+            "POST",
+            format!("/guilds/{guild_id}/channels/{channel_id}/messages"),
+            &auth.access_token,
+            ip,
+            Some(body),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(payload, Some(expected));
+    }
 }
@@ -133,3 +133,150 @@ async fn friendship_request_acceptance_and_list_management_work() {
         0
     );
 }
+
+async fn relationship_for_test(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    user_id: &str,
+) -> (StatusCode, Option<Value>) {
+    authed_json_request(
+        app,
+        "GET",
+        format!("/users/{user_id}/relationship"),
+        &auth.access_token,
+        ip,
+        None,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn relationship_reports_requests_and_friendships() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    let alice = register_and_login_as(&app, "alice_relation", "203.0.113.84").await;
+    let bob = register_and_login_as(&app, "bob_relation", "203.0.113.85").await;
+    let alice_user_id = user_id_from_me(&app, &alice, "203.0.113.84").await;
+    let bob_user_id = user_id_from_me(&app, &bob, "203.0.113.85").await;
+
+    let (status, payload) = relationship_for_test(&app, &alice, "203.0.113.84", &bob_user_id).await;
+    assert_eq!(status, StatusCode::OK);
+    let payload = payload.unwrap();
+    assert_eq!(payload["relationship"], "none");
+    assert!(payload["request_id"].is_null());
+
+    let request_id =
+        create_friend_request_for_test(&app, &alice, "203.0.113.84", &bob_user_id).await;
+    let (_, payload) = relationship_for_test(&app, &alice, "203.0.113.84", &bob_user_id).await;
+    assert_eq!(payload.unwrap()["relationship"], "outgoing_request");
+    let (_, payload) = relationship_for_test(&app, &bob, "203.0.113.85", &alice_user_id).await;
+    let payload = payload.unwrap();
+    assert_eq!(payload["relationship"], "incoming_request");
+    assert_eq!(payload["request_id"], request_id);
+
+    let (accept_status, _) = authed_json_request(
+        &app,
+        "POST",
+        format!("/friends/requests/{request_id}/accept"),
+        &bob.access_token,
+        "203.0.113.85",
+        None,
+    )
+    .await;
+    assert_eq!(accept_status, StatusCode::OK);
+    let (_, payload) = relationship_for_test(&app, &bob, "203.0.113.85", &alice_user_id).await;
+    assert_eq!(payload.unwrap()["relationship"], "friends");
+
+    let (self_status, _) =
+        relationship_for_test(&app, &alice, "203.0.113.84", &alice_user_id).await;
+    assert_eq!(self_status, StatusCode::BAD_REQUEST);
+    let (unknown_status, _) =
+        relationship_for_test(&app, &alice, "203.0.113.84", &ulid::Ulid::new().to_string()).await;
+    assert_eq!(unknown_status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn in_memory_friend_request_ceiling_applies_per_sender() {
+    let app = build_router(&AppConfig {
+        in_memory_max_friend_requests: 1,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let alice = register_and_login_as(&app, "alice_ceiling", "203.0.113.100").await;
+    let bob = register_and_login_as(&app, "bob_ceiling", "203.0.113.101").await;
+    let carol = register_and_login_as(&app, "carol_ceiling", "203.0.113.102").await;
+    let bob_user_id = user_id_from_me(&app, &bob, "203.0.113.101").await;
+    let carol_user_id = user_id_from_me(&app, &carol, "203.0.113.102").await;
+
+    let (first_request_status, _) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/friends/requests"),
+        &alice.access_token,
+        "203.0.113.100",
+        Some(json!({ "recipient_user_id": bob_user_id })),
+    )
+    .await;
+    assert_eq!(first_request_status, StatusCode::OK);
+    let (second_request_status, second_request_payload) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/friends/requests"),
+        &alice.access_token,
+        "203.0.113.100",
+        Some(json!({ "recipient_user_id": carol_user_id })),
+    )
+    .await;
+    assert_eq!(second_request_status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(second_request_payload.unwrap()["error"], "capacity_reached");
+    let (other_sender_status, _) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/friends/requests"),
+        &carol.access_token,
+        "203.0.113.102",
+        Some(json!({ "recipient_user_id": bob_user_id })),
+    )
+    .await;
+    assert_eq!(other_sender_status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn in_memory_total_friend_request_ceiling_applies_across_senders() {
+    let app = build_router(&AppConfig {
+        in_memory_max_friend_requests: 5,
+        in_memory_max_total_friend_requests: 2,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let alice = register_and_login_as(&app, "alice_total", "203.0.113.103").await;
+    let bob = register_and_login_as(&app, "bob_total", "203.0.113.104").await;
+    let carol = register_and_login_as(&app, "carol_total", "203.0.113.105").await;
+    let dave = register_and_login_as(&app, "dave_total", "203.0.113.106").await;
+    let alice_user_id = user_id_from_me(&app, &alice, "203.0.113.103").await;
+    let bob_user_id = user_id_from_me(&app, &bob, "203.0.113.104").await;
+
+    for (sender, ip) in [(&alice, "203.0.113.103"), (&carol, "203.0.113.105")] {
+        let (status, _) = authed_json_request(
+            &app,
+            "POST",
+            String::from("/friends/requests"),
+            &sender.access_token,
+            ip,
+            Some(json!({ "recipient_user_id": bob_user_id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (full_status, full_payload) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/friends/requests"),
+        &dave.access_token,
+        "203.0.113.106",
+        Some(json!({ "recipient_user_id": alice_user_id })),
+    )
+    .await;
+    assert_eq!(full_status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(full_payload.unwrap()["error"], "capacity_reached");
+}
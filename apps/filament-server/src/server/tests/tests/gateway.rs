@@ -19,6 +19,7 @@ async fn gateway_broadcasts_message_to_subscribed_connection() {
             password_hash: hash_password("super-secure-password").unwrap(),
             failed_logins: 0,
             locked_until_unix: None,
+            created_at_unix: 0,
         },
     );
     state
@@ -34,6 +35,11 @@ async fn gateway_broadcasts_message_to_subscribed_connection() {
         visibility: GuildVisibility::Private,
         created_by_user_id: user_id,
         default_join_role_id: None,
+        description: None,
+        icon_attachment_id: None,
+        search_query_timeout_ms: None,
+        system_channel_id: None,
+        welcome_messages: false,
         members: HashMap::new(),
         banned_members: std::collections::HashSet::new(),
         channels: HashMap::new(),
@@ -44,6 +50,10 @@ async fn gateway_broadcasts_message_to_subscribed_connection() {
         ChannelRecord {
             name: String::from("gateway-room"),
             kind: ChannelKind::Text,
+            locked: false,
+            retention_secs: None,
+            voting: false,
+            category_id: None,
             messages: Vec::new(),
             role_overrides: HashMap::new(),
         },
@@ -55,7 +65,7 @@ async fn gateway_broadcasts_message_to_subscribed_connection() {
         .await
         .insert(guild_id.clone(), guild);
 
-    let (tx, mut rx) = mpsc::channel::<String>(4);
+    let (tx, mut rx) = unbudgeted_outbound_queue(4);
     add_subscription(&state, Uuid::new_v4(), channel_key("g", "c"), tx).await;
 
     let auth = AuthContext {
@@ -80,11 +90,201 @@ async fn gateway_broadcasts_message_to_subscribed_connection() {
     assert_eq!(value["d"]["content"], "hello");
 }
 
+async fn insert_mention_guild(state: &AppState, owner_id: UserId, member_ids: &[UserId]) {
+    let mut guild = GuildRecord {
+        name: String::from("Mention Test"),
+        visibility: GuildVisibility::Private,
+        created_by_user_id: owner_id,
+        default_join_role_id: None,
+        description: None,
+        icon_attachment_id: None,
+        search_query_timeout_ms: None,
+        system_channel_id: None,
+        welcome_messages: false,
+        members: HashMap::new(),
+        banned_members: std::collections::HashSet::new(),
+        channels: HashMap::new(),
+    };
+    guild.members.insert(owner_id, Role::Owner);
+    for member_id in member_ids {
+        guild.members.insert(*member_id, Role::Member);
+    }
+    for channel_id in ["c-general", "c-other"] {
+        guild.channels.insert(
+            String::from(channel_id),
+            ChannelRecord {
+                name: String::from(channel_id),
+                kind: ChannelKind::Text,
+                locked: false,
+                retention_secs: None,
+                voting: false,
+                category_id: None,
+                messages: Vec::new(),
+                role_overrides: HashMap::new(),
+            },
+        );
+    }
+    state
+        .membership_store
+        .guilds()
+        .write()
+        .await
+        .insert(String::from("g"), guild);
+}
+
+/// Registers a connection for `user_id` watching guild `g` through `c-other`.
+async fn listen_to_mention_guild(
+    state: &AppState,
+    user_id: UserId,
+    tx: crate::server::realtime::OutboundSender,
+) {
+    let connection_id = Uuid::new_v4();
+    add_subscription(
+        state,
+        connection_id,
+        channel_key("g", "c-other"),
+        tx.clone(),
+    )
+    .await;
+    state
+        .realtime_registry
+        .connection_senders()
+        .write()
+        .await
+        .insert(connection_id, tx);
+    state
+        .realtime_registry
+        .connection_presence()
+        .write()
+        .await
+        .insert(
+            connection_id,
+            ConnectionPresence {
+                user_id,
+                guild_ids: std::collections::HashSet::from([String::from("g")]),
+            },
+        );
+}
+
+#[tokio::test]
+async fn guild_wide_mentions_fan_out_only_for_authors_with_mention_everyone() {
+    let state = AppState::new(&AppConfig::default()).unwrap();
+    let owner_id = UserId::new();
+    let member_id = UserId::new();
+    insert_mention_guild(&state, owner_id, &[member_id]).await;
+    let (tx, mut rx) = unbudgeted_outbound_queue(8);
+    listen_to_mention_guild(&state, member_id, tx).await;
+
+    let member = AuthContext {
+        user_id: member_id,
+        username: String::from("member_1"),
+    };
+    create_message_internal(
+        &state,
+        &member,
+        "g",
+        "c-general",
+        String::from("@everyone look"),
+        Vec::new(),
+    )
+    .await
+    .expect("members may still post the mention text");
+    let unpermitted = tokio::time::timeout(Duration::from_millis(25), rx.recv()).await;
+    assert!(
+        unpermitted.is_err(),
+        "mention without mention_everyone must not fan out"
+    );
+
+    let owner = AuthContext {
+        user_id: owner_id,
+        username: String::from("owner_1"),
+    };
+    let message = create_message_internal(
+        &state,
+        &owner,
+        "g",
+        "c-general",
+        String::from("@here standup"),
+        Vec::new(),
+    )
+    .await
+    .unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .expect("mention should fan out to the guild")
+        .unwrap();
+    let value: Value = serde_json::from_str(&event).unwrap();
+    assert_eq!(value["t"], "message_mention");
+    assert_eq!(value["d"]["channel_id"], "c-general");
+    assert_eq!(value["d"]["message_id"], message.message_id.as_str());
+    assert_eq!(value["d"]["scope"], "here");
+}
+
+#[tokio::test]
+async fn guild_wide_mentions_skip_members_who_cannot_read_the_channel() {
+    let state = AppState::new(&AppConfig::default()).unwrap();
+    let owner_id = UserId::new();
+    let reader_id = UserId::new();
+    let hidden_id = UserId::new();
+    insert_mention_guild(&state, owner_id, &[reader_id, hidden_id]).await;
+    let mut deny = PermissionSet::empty();
+    deny.insert(Permission::CreateMessage);
+    state
+        .membership_store
+        .guild_channel_permission_overrides()
+        .write()
+        .await
+        .entry(String::from("g"))
+        .or_default()
+        .entry(String::from("c-general"))
+        .or_default()
+        .member_overrides
+        .insert(
+            hidden_id,
+            ChannelPermissionOverwrite {
+                allow: PermissionSet::empty(),
+                deny,
+            },
+        );
+    let (reader_tx, mut reader_rx) = unbudgeted_outbound_queue(8);
+    listen_to_mention_guild(&state, reader_id, reader_tx).await;
+    let (hidden_tx, mut hidden_rx) = unbudgeted_outbound_queue(8);
+    listen_to_mention_guild(&state, hidden_id, hidden_tx).await;
+
+    let owner = AuthContext {
+        user_id: owner_id,
+        username: String::from("owner_1"),
+    };
+    create_message_internal(
+        &state,
+        &owner,
+        "g",
+        "c-general",
+        String::from("@here private standup"),
+        Vec::new(),
+    )
+    .await
+    .unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(1), reader_rx.recv())
+        .await
+        .expect("readers of the channel should get the mention")
+        .unwrap();
+    let value: Value = serde_json::from_str(&event).unwrap();
+    assert_eq!(value["t"], "message_mention");
+    let hidden = tokio::time::timeout(Duration::from_millis(25), hidden_rx.recv()).await;
+    assert!(
+        hidden.is_err(),
+        "members overridden out of the channel must not learn about the mention"
+    );
+}
+
 #[tokio::test]
 async fn channel_broadcast_targets_only_matching_subscription_key() {
     let state = AppState::new(&AppConfig::default()).unwrap();
-    let (tx_target, mut rx_target) = mpsc::channel::<String>(2);
-    let (tx_other, mut rx_other) = mpsc::channel::<String>(2);
+    let (tx_target, mut rx_target) = unbudgeted_outbound_queue(2);
+    let (tx_other, mut rx_other) = unbudgeted_outbound_queue(2);
     add_subscription(
         &state,
         Uuid::new_v4(),
@@ -119,8 +319,8 @@ async fn guild_broadcast_delivers_once_per_connection_and_skips_other_guilds() {
     let state = AppState::new(&AppConfig::default()).unwrap();
     let connection_id = Uuid::new_v4();
     let other_connection_id = Uuid::new_v4();
-    let (tx_target, mut rx_target) = mpsc::channel::<String>(4);
-    let (tx_other, mut rx_other) = mpsc::channel::<String>(4);
+    let (tx_target, mut rx_target) = unbudgeted_outbound_queue(4);
+    let (tx_other, mut rx_other) = unbudgeted_outbound_queue(4);
     add_subscription(
         &state,
         connection_id,
@@ -179,9 +379,9 @@ async fn user_broadcast_targets_only_requested_authenticated_user() {
     let connection_a1 = Uuid::new_v4();
     let connection_a2 = Uuid::new_v4();
     let connection_b = Uuid::new_v4();
-    let (tx_a1, mut rx_a1) = mpsc::channel::<String>(2);
-    let (tx_a2, mut rx_a2) = mpsc::channel::<String>(2);
-    let (tx_b, mut rx_b) = mpsc::channel::<String>(2);
+    let (tx_a1, mut rx_a1) = unbudgeted_outbound_queue(2);
+    let (tx_a2, mut rx_a2) = unbudgeted_outbound_queue(2);
+    let (tx_b, mut rx_b) = unbudgeted_outbound_queue(2);
 
     state
         .realtime_registry
@@ -234,12 +434,13 @@ async fn user_broadcast_targets_only_requested_authenticated_user() {
 async fn slow_consumer_signal_is_sent_when_outbound_queue_is_full() {
     let state = AppState::new(&AppConfig {
         gateway_outbound_queue: 1,
+        gateway_slow_consumer_max_strikes: 1,
         ..AppConfig::default()
     })
     .unwrap();
 
     let connection_id = Uuid::new_v4();
-    let (tx, _rx) = mpsc::channel::<String>(1);
+    let (tx, _rx) = unbudgeted_outbound_queue(1);
     let (control_tx, control_rx) = watch::channel(ConnectionControl::Open);
     state
         .realtime_registry
@@ -264,16 +465,62 @@ async fn slow_consumer_signal_is_sent_when_outbound_queue_is_full() {
     assert_eq!(*control_rx.borrow(), ConnectionControl::Close);
 }
 
+#[tokio::test]
+async fn slow_consumer_is_disconnected_only_after_consecutive_full_queue_strikes() {
+    let state = AppState::new(&AppConfig {
+        gateway_outbound_queue: 1,
+        gateway_slow_consumer_max_strikes: 3,
+        ..AppConfig::default()
+    })
+    .unwrap();
+
+    let connection_id = Uuid::new_v4();
+    let (tx, mut rx) = unbudgeted_outbound_queue(1);
+    let (control_tx, control_rx) = watch::channel(ConnectionControl::Open);
+    state
+        .realtime_registry
+        .connection_controls()
+        .write()
+        .await
+        .insert(connection_id, control_tx);
+    add_subscription(&state, connection_id, channel_key("g", "c"), tx.clone()).await;
+    let event =
+        gateway_events::try_subscribed("g", "c").expect("subscribed event should serialize");
+
+    tx.try_send(String::from("first")).unwrap();
+    for _ in 0..2 {
+        broadcast_channel_event(&state, &channel_key("g", "c"), &event).await;
+        assert_eq!(*control_rx.borrow(), ConnectionControl::Open);
+    }
+
+    assert_eq!(rx.recv().await.as_deref(), Some("first"));
+    broadcast_channel_event(&state, &channel_key("g", "c"), &event).await;
+    assert!(state
+        .realtime_registry
+        .slow_consumer_strikes()
+        .read()
+        .await
+        .is_empty());
+
+    for _ in 0..2 {
+        broadcast_channel_event(&state, &channel_key("g", "c"), &event).await;
+        assert_eq!(*control_rx.borrow(), ConnectionControl::Open);
+    }
+    broadcast_channel_event(&state, &channel_key("g", "c"), &event).await;
+    assert_eq!(*control_rx.borrow(), ConnectionControl::Close);
+}
+
 #[tokio::test]
 async fn slow_consumer_signal_is_sent_for_guild_fanout_when_outbound_queue_is_full() {
     let state = AppState::new(&AppConfig {
         gateway_outbound_queue: 1,
+        gateway_slow_consumer_max_strikes: 1,
         ..AppConfig::default()
     })
     .unwrap();
 
     let connection_id = Uuid::new_v4();
-    let (tx, _rx) = mpsc::channel::<String>(1);
+    let (tx, _rx) = unbudgeted_outbound_queue(1);
     let (control_tx, control_rx) = watch::channel(ConnectionControl::Open);
     state
         .realtime_registry
@@ -301,13 +548,14 @@ async fn slow_consumer_signal_is_sent_for_guild_fanout_when_outbound_queue_is_fu
 async fn slow_consumer_signal_is_sent_for_user_fanout_when_outbound_queue_is_full() {
     let state = AppState::new(&AppConfig {
         gateway_outbound_queue: 1,
+        gateway_slow_consumer_max_strikes: 1,
         ..AppConfig::default()
     })
     .unwrap();
 
     let user_id = UserId::new();
     let connection_id = Uuid::new_v4();
-    let (tx, _rx) = mpsc::channel::<String>(1);
+    let (tx, _rx) = unbudgeted_outbound_queue(1);
     let (control_tx, control_rx) = watch::channel(ConnectionControl::Open);
     state
         .realtime_registry
@@ -369,7 +617,7 @@ async fn fanout_benchmark_snapshot_channel_hot_path() {
     let mut receivers = Vec::with_capacity(listener_count);
     for _ in 0..listener_count {
         let connection_id = Uuid::new_v4();
-        let (tx, rx) = mpsc::channel::<String>(queue_capacity);
+        let (tx, rx) = unbudgeted_outbound_queue(queue_capacity);
         add_subscription(&state, connection_id, channel_key("g-bench", "c-bench"), tx).await;
         receivers.push(rx);
     }
@@ -402,7 +650,7 @@ async fn fanout_benchmark_snapshot_guild_hot_path() {
     let mut receivers = Vec::with_capacity(listener_count);
     for index in 0..listener_count {
         let connection_id = Uuid::new_v4();
-        let (tx, rx) = mpsc::channel::<String>(queue_capacity);
+        let (tx, rx) = unbudgeted_outbound_queue(queue_capacity);
         add_subscription(
             &state,
             connection_id,
@@ -451,7 +699,7 @@ async fn fanout_benchmark_snapshot_user_hot_path() {
         let mut senders = state.realtime_registry.connection_senders().write().await;
         for _ in 0..listener_count {
             let connection_id = Uuid::new_v4();
-            let (tx, rx) = mpsc::channel::<String>(queue_capacity);
+            let (tx, rx) = unbudgeted_outbound_queue(queue_capacity);
             senders.insert(connection_id, tx);
             connection_ids.insert(connection_id);
             receivers.push(rx);
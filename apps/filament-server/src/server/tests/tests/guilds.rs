@@ -155,7 +155,518 @@ async fn channel_permissions_endpoint_enforces_least_visibility() {
         &channel_id,
     )
     .await;
-    assert_eq!(stranger_status, StatusCode::FORBIDDEN);
+    assert_eq!(stranger_status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn channel_override_preview_resolves_without_persisting() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    let owner_auth = register_and_login_as(&app, "owner_pv", "203.0.113.77").await;
+    let member_auth = register_and_login_as(&app, "member_pv", "203.0.113.78").await;
+    let guild_id = create_guild_for_test(&app, &owner_auth, "203.0.113.77").await;
+    let channel_id = create_channel_for_test(&app, &owner_auth, "203.0.113.77", &guild_id).await;
+    let member_user_id = user_id_from_me(&app, &member_auth, "203.0.113.78").await;
+    add_member_for_test(
+        &app,
+        &owner_auth,
+        "203.0.113.77",
+        &guild_id,
+        &member_user_id,
+    )
+    .await;
+    let preview_uri = |query: &str| {
+        format!("/guilds/{guild_id}/channels/{channel_id}/permissions/preview?{query}")
+    };
+
+    let (status, payload) = authed_json_request(
+        &app,
+        "GET",
+        preview_uri("role=member&allow=publish_video&deny=create_message"),
+        &owner_auth.access_token,
+        "203.0.113.77",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let payload = payload.unwrap();
+    assert_eq!(payload["role"], "member");
+    assert_eq!(
+        payload["permissions"],
+        json!(["publish_video", "subscribe_streams"])
+    );
+
+    let (member_status, _) =
+        fetch_self_permissions_for_test(&app, &member_auth, "203.0.113.78", &guild_id, &channel_id)
+            .await;
+    assert_eq!(member_status, StatusCode::OK);
+
+    for query in [
+        "role=member&allow=create_message&deny=create_message",
+        "role=member&deny=not_a_permission",
+    ] {
+        let (status, _) = authed_json_request(
+            &app,
+            "GET",
+            preview_uri(query),
+            &owner_auth.access_token,
+            "203.0.113.77",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "query {query}");
+    }
+
+    let (member_preview_status, _) = authed_json_request(
+        &app,
+        "GET",
+        preview_uri("role=owner&deny=create_message"),
+        &member_auth.access_token,
+        "203.0.113.78",
+        None,
+    )
+    .await;
+    assert_eq!(member_preview_status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn channel_role_overrides_are_listed_with_permission_names() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    let owner_auth = register_and_login_as(&app, "owner_lo", "203.0.113.86").await;
+    let member_auth = register_and_login_as(&app, "member_lo", "203.0.113.87").await;
+    let guild_id = create_guild_for_test(&app, &owner_auth, "203.0.113.86").await;
+    let channel_id = create_channel_for_test(&app, &owner_auth, "203.0.113.86", &guild_id).await;
+    let member_user_id = user_id_from_me(&app, &member_auth, "203.0.113.87").await;
+    add_member_for_test(
+        &app,
+        &owner_auth,
+        "203.0.113.86",
+        &guild_id,
+        &member_user_id,
+    )
+    .await;
+    let overrides_uri = format!("/guilds/{guild_id}/channels/{channel_id}/overrides");
+
+    let (status, payload) = authed_json_request(
+        &app,
+        "GET",
+        overrides_uri.clone(),
+        &owner_auth.access_token,
+        "203.0.113.86",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload.unwrap(), json!({"overrides": []}));
+
+    deny_member_create_message_for_test(&app, &owner_auth, "203.0.113.86", &guild_id, &channel_id)
+        .await;
+    let (status, _) = authed_json_request(
+        &app,
+        "POST",
+        format!("{overrides_uri}/moderator"),
+        &owner_auth.access_token,
+        "203.0.113.86",
+        Some(json!({"allow":["publish_video"],"deny":[]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, payload) = authed_json_request(
+        &app,
+        "GET",
+        overrides_uri.clone(),
+        &owner_auth.access_token,
+        "203.0.113.86",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        payload.unwrap(),
+        json!({"overrides": [
+            {"role": "moderator", "allow": ["publish_video"], "deny": []},
+            {"role": "member", "allow": [], "deny": ["create_message"]},
+        ]})
+    );
+
+    let (member_status, _) = authed_json_request(
+        &app,
+        "GET",
+        overrides_uri,
+        &member_auth.access_token,
+        "203.0.113.87",
+        None,
+    )
+    .await;
+    assert_eq!(member_status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn deleting_channel_role_override_restores_base_permissions() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    let owner_auth = register_and_login_as(&app, "owner_do", "203.0.113.88").await;
+    let member_auth = register_and_login_as(&app, "member_do", "203.0.113.89").await;
+    let guild_id = create_guild_for_test(&app, &owner_auth, "203.0.113.88").await;
+    let channel_id = create_channel_for_test(&app, &owner_auth, "203.0.113.88", &guild_id).await;
+    let member_user_id = user_id_from_me(&app, &member_auth, "203.0.113.89").await;
+    add_member_for_test(
+        &app,
+        &owner_auth,
+        "203.0.113.88",
+        &guild_id,
+        &member_user_id,
+    )
+    .await;
+    deny_member_create_message_for_test(&app, &owner_auth, "203.0.113.88", &guild_id, &channel_id)
+        .await;
+    let override_uri = format!("/guilds/{guild_id}/channels/{channel_id}/overrides/member");
+
+    let (member_delete_status, _) = authed_json_request(
+        &app,
+        "DELETE",
+        override_uri.clone(),
+        &member_auth.access_token,
+        "203.0.113.89",
+        None,
+    )
+    .await;
+    assert_eq!(member_delete_status, StatusCode::FORBIDDEN);
+
+    let (status, payload) = authed_json_request(
+        &app,
+        "DELETE",
+        override_uri.clone(),
+        &owner_auth.access_token,
+        "203.0.113.88",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload.unwrap()["accepted"], true);
+
+    let (member_status, _) =
+        fetch_self_permissions_for_test(&app, &member_auth, "203.0.113.89", &guild_id, &channel_id)
+            .await;
+    assert_eq!(member_status, StatusCode::OK);
+
+    let (missing_status, _) = authed_json_request(
+        &app,
+        "DELETE",
+        override_uri,
+        &owner_auth.access_token,
+        "203.0.113.88",
+        None,
+    )
+    .await;
+    assert_eq!(missing_status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn owner_resets_all_channel_role_overrides_in_guild() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    let owner_auth = register_and_login_as(&app, "owner_reset", "203.0.113.160").await;
+    let member_auth = register_and_login_as(&app, "member_reset", "203.0.113.161").await;
+    let guild_id = create_guild_for_test(&app, &owner_auth, "203.0.113.160").await;
+    let first_channel =
+        create_channel_for_test(&app, &owner_auth, "203.0.113.160", &guild_id).await;
+    let second_channel =
+        create_channel_for_test(&app, &owner_auth, "203.0.113.160", &guild_id).await;
+    let member_user_id = user_id_from_me(&app, &member_auth, "203.0.113.161").await;
+    add_member_for_test(
+        &app,
+        &owner_auth,
+        "203.0.113.160",
+        &guild_id,
+        &member_user_id,
+    )
+    .await;
+    for channel_id in [&first_channel, &second_channel] {
+        deny_member_create_message_for_test(
+            &app,
+            &owner_auth,
+            "203.0.113.160",
+            &guild_id,
+            channel_id,
+        )
+        .await;
+    }
+    let reset_uri = format!("/guilds/{guild_id}/permissions/reset");
+
+    let (member_status, _) = authed_json_request(
+        &app,
+        "POST",
+        reset_uri.clone(),
+        &member_auth.access_token,
+        "203.0.113.161",
+        None,
+    )
+    .await;
+    assert_eq!(member_status, StatusCode::FORBIDDEN);
+
+    let (status, payload) = authed_json_request(
+        &app,
+        "POST",
+        reset_uri.clone(),
+        &owner_auth.access_token,
+        "203.0.113.160",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload.unwrap()["cleared"], 2);
+
+    for channel_id in [&first_channel, &second_channel] {
+        let (status, payload) = fetch_self_permissions_for_test(
+            &app,
+            &member_auth,
+            "203.0.113.161",
+            &guild_id,
+            channel_id,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(payload.unwrap()["permissions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|permission| permission == "create_message"));
+    }
+
+    let (_, overrides) = authed_json_request(
+        &app,
+        "GET",
+        format!("/guilds/{guild_id}/channels/{first_channel}/overrides"),
+        &owner_auth.access_token,
+        "203.0.113.160",
+        None,
+    )
+    .await;
+    assert_eq!(overrides.unwrap()["overrides"], json!([]));
+
+    let (_, repeat) = authed_json_request(
+        &app,
+        "POST",
+        reset_uri,
+        &owner_auth.access_token,
+        "203.0.113.160",
+        None,
+    )
+    .await;
+    assert_eq!(repeat.unwrap()["cleared"], 0);
+}
+
+#[tokio::test]
+#[allow(clippy::too_many_lines)]
+async fn channel_access_lists_roles_and_member_overrides() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    let owner_auth = register_and_login_as(&app, "owner_acc", "203.0.113.81").await;
+    let member_auth = register_and_login_as(&app, "member_acc", "203.0.113.82").await;
+    let stranger_auth = register_and_login_as(&app, "stranger_acc", "203.0.113.83").await;
+    let guild_id = create_guild_for_test(&app, &owner_auth, "203.0.113.81").await;
+    let channel_id = create_channel_for_test(&app, &owner_auth, "203.0.113.81", &guild_id).await;
+    let member_user_id = user_id_from_me(&app, &member_auth, "203.0.113.82").await;
+    add_member_for_test(
+        &app,
+        &owner_auth,
+        "203.0.113.81",
+        &guild_id,
+        &member_user_id,
+    )
+    .await;
+
+    let (_, roles) = authed_json_request(
+        &app,
+        "GET",
+        format!("/guilds/{guild_id}/roles"),
+        &owner_auth.access_token,
+        "203.0.113.81",
+        None,
+    )
+    .await;
+    let everyone_role_id = roles.unwrap()["roles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|role| role["name"] == "@everyone")
+        .and_then(|role| role["role_id"].as_str())
+        .unwrap()
+        .to_owned();
+    for (target, body) in [
+        (
+            format!("role/{everyone_role_id}"),
+            json!({"allow":[],"deny":["create_message"]}),
+        ),
+        (
+            format!("member/{member_user_id}"),
+            json!({"allow":["create_message"],"deny":[]}),
+        ),
+    ] {
+        let (status, _) = authed_json_request(
+            &app,
+            "POST",
+            format!("/guilds/{guild_id}/channels/{channel_id}/permission-overrides/{target}"),
+            &owner_auth.access_token,
+            "203.0.113.81",
+            Some(body),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "target {target}");
+    }
+
+    let access_uri = format!("/guilds/{guild_id}/channels/{channel_id}/access");
+    let (status, payload) = authed_json_request(
+        &app,
+        "GET",
+        access_uri.clone(),
+        &owner_auth.access_token,
+        "203.0.113.81",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let payload = payload.unwrap();
+    let role_access: Vec<(String, bool)> = payload["roles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|role| {
+            (
+                role["name"].as_str().unwrap().to_owned(),
+                role["can_access"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        role_access,
+        [
+            (String::from("workspace_owner"), true),
+            (String::from("moderator"), false),
+            (String::from("member"), false),
+            (String::from("@everyone"), false),
+        ]
+    );
+    let members = payload["members"].as_array().unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0]["user_id"], member_user_id.as_str());
+    assert_eq!(members[0]["can_access"], true);
+
+    let (member_status, _) = authed_json_request(
+        &app,
+        "GET",
+        access_uri.clone(),
+        &member_auth.access_token,
+        "203.0.113.82",
+        None,
+    )
+    .await;
+    assert_eq!(member_status, StatusCode::FORBIDDEN);
+    let (stranger_status, _) = authed_json_request(
+        &app,
+        "GET",
+        access_uri,
+        &stranger_auth.access_token,
+        "203.0.113.83",
+        None,
+    )
+    .await;
+    assert_eq!(stranger_status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn moderators_cannot_override_roles_or_members_above_them() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    let owner_auth = register_and_login_as(&app, "owner_ovr", "203.0.113.79").await;
+    let moderator_auth = register_and_login_as(&app, "moderator_ovr", "203.0.113.80").await;
+    let guild_id = create_guild_for_test(&app, &owner_auth, "203.0.113.79").await;
+    let channel_id = create_channel_for_test(&app, &owner_auth, "203.0.113.79", &guild_id).await;
+    let owner_user_id = user_id_from_me(&app, &owner_auth, "203.0.113.79").await;
+    let moderator_user_id = user_id_from_me(&app, &moderator_auth, "203.0.113.80").await;
+    add_member_for_test(
+        &app,
+        &owner_auth,
+        "203.0.113.79",
+        &guild_id,
+        &moderator_user_id,
+    )
+    .await;
+    let (promote_status, _) = authed_json_request(
+        &app,
+        "PATCH",
+        format!("/guilds/{guild_id}/members/{moderator_user_id}"),
+        &owner_auth.access_token,
+        "203.0.113.79",
+        Some(json!({"role":"moderator"})),
+    )
+    .await;
+    assert_eq!(promote_status, StatusCode::OK);
+
+    let (_, roles) = authed_json_request(
+        &app,
+        "GET",
+        format!("/guilds/{guild_id}/roles"),
+        &owner_auth.access_token,
+        "203.0.113.79",
+        None,
+    )
+    .await;
+    let roles = roles.unwrap();
+    let role_id = |name: &str| {
+        roles["roles"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|role| role["name"] == name)
+            .and_then(|role| role["role_id"].as_str())
+            .unwrap()
+            .to_owned()
+    };
+    let deny_create = json!({"allow":[],"deny":["create_message"]});
+
+    for (target, expected) in [
+        (
+            format!("role/{}", role_id("workspace_owner")),
+            StatusCode::FORBIDDEN,
+        ),
+        (
+            format!("role/{}", role_id("moderator")),
+            StatusCode::FORBIDDEN,
+        ),
+        (format!("member/{owner_user_id}"), StatusCode::FORBIDDEN),
+        (format!("role/{}", role_id("member")), StatusCode::OK),
+        (format!("role/{}", role_id("@everyone")), StatusCode::OK),
+    ] {
+        let (status, _) = authed_json_request(
+            &app,
+            "POST",
+            format!("/guilds/{guild_id}/channels/{channel_id}/permission-overrides/{target}"),
+            &moderator_auth.access_token,
+            "203.0.113.80",
+            Some(deny_create.clone()),
+        )
+        .await;
+        assert_eq!(status, expected, "target {target}");
+    }
+
+    let (legacy_status, _) = authed_json_request(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/channels/{channel_id}/overrides/owner"),
+        &moderator_auth.access_token,
+        "203.0.113.80",
+        Some(deny_create),
+    )
+    .await;
+    assert_eq!(legacy_status, StatusCode::FORBIDDEN);
+
+    let (owner_status, owner_permissions) =
+        fetch_self_permissions_for_test(&app, &owner_auth, "203.0.113.79", &guild_id, &channel_id)
+            .await;
+    assert_eq!(owner_status, StatusCode::OK);
+    assert!(owner_permissions.unwrap()["permissions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|permission| permission == "create_message"));
 }
 
 #[tokio::test]
@@ -238,7 +749,7 @@ async fn guild_and_channel_list_endpoints_are_member_scoped() {
         None,
     )
     .await;
-    assert_eq!(stranger_status, StatusCode::FORBIDDEN);
+    assert_eq!(stranger_status, StatusCode::NOT_FOUND);
 }
 
 #[tokio::test]
@@ -332,6 +843,168 @@ async fn create_guild_enforces_per_user_creation_limit() {
     assert_eq!(payload["error"], "guild_creation_limit_reached");
 }
 
+#[tokio::test]
+async fn guild_create_cooldown_spaces_out_creations_per_user() {
+    let app = build_router(&AppConfig {
+        guild_create_cooldown: Duration::from_secs(3600),
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let alice = register_and_login_as(&app, "alice_cooldown", "203.0.113.65").await;
+    let bob = register_and_login_as(&app, "bob_cooldown", "203.0.113.66").await;
+
+    let (first_status, _) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        &alice.access_token,
+        "203.0.113.65",
+        Some(json!({"name":"First"})),
+    )
+    .await;
+    assert_eq!(first_status, StatusCode::OK);
+
+    let second_create = Request::builder()
+        .method("POST")
+        .uri("/guilds")
+        .header("authorization", format!("Bearer {}", alice.access_token))
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", "203.0.113.65")
+        .body(Body::from(json!({"name":"Second"}).to_string()))
+        .unwrap();
+    let second_response = app.clone().oneshot(second_create).await.unwrap();
+    assert_eq!(second_response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = second_response
+        .headers()
+        .get("retry-after")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .unwrap();
+    assert!((1..=3600).contains(&retry_after));
+    let body = axum::body::to_bytes(second_response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let payload: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(payload["error"], "rate_limited");
+
+    let (other_user_status, _) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        &bob.access_token,
+        "203.0.113.66",
+        Some(json!({"name":"Unrelated"})),
+    )
+    .await;
+    assert_eq!(other_user_status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn in_memory_ceilings_reject_new_guilds_and_messages() {
+    let app = build_router(&AppConfig {
+        in_memory_max_guilds: 1,
+        in_memory_max_channel_messages: 2,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let alice = register_and_login_as(&app, "alice_ceiling", "203.0.113.100").await;
+    let bob = register_and_login_as(&app, "bob_ceiling", "203.0.113.101").await;
+
+    let (guild_status, guild_payload) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        &alice.access_token,
+        "203.0.113.100",
+        Some(json!({"name":"Only Guild"})),
+    )
+    .await;
+    assert_eq!(guild_status, StatusCode::OK);
+    let guild_id = guild_payload.unwrap()["guild_id"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+
+    let (full_status, full_payload) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        &bob.access_token,
+        "203.0.113.101",
+        Some(json!({"name":"One Too Many"})),
+    )
+    .await;
+    assert_eq!(full_status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(full_payload.unwrap()["error"], "capacity_reached");
+
+    let (channel_status, channel_payload) = authed_json_request(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        &alice.access_token,
+        "203.0.113.100",
+        Some(json!({"name":"general-chat"})),
+    )
+    .await;
+    assert_eq!(channel_status, StatusCode::OK);
+    let channel_id = channel_payload.unwrap()["channel_id"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    for (content, expected) in [
+        ("one", StatusCode::OK),
+        ("two", StatusCode::OK),
+        ("three", StatusCode::SERVICE_UNAVAILABLE),
+    ] {
+        let (status, _) = authed_json_request(
+            &app,
+            "POST",
+            format!("/guilds/{guild_id}/channels/{channel_id}/messages"),
+            &alice.access_token,
+            "203.0.113.100",
+            Some(json!({ "content": content })),
+        )
+        .await;
+        assert_eq!(status, expected);
+    }
+}
+
+#[tokio::test]
+async fn min_account_age_blocks_new_accounts_from_guilds_and_friend_requests() {
+    let app = build_router(&AppConfig {
+        min_account_age_for_guild_create: Duration::from_secs(3600),
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let alice = register_and_login_as(&app, "alice_fresh", "203.0.113.74").await;
+    let bob = register_and_login_as(&app, "bob_fresh", "203.0.113.75").await;
+    let bob_user_id = user_id_from_me(&app, &bob, "203.0.113.75").await;
+
+    let (guild_status, guild_payload) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        &alice.access_token,
+        "203.0.113.74",
+        Some(json!({"name":"Too Soon"})),
+    )
+    .await;
+    assert_eq!(guild_status, StatusCode::FORBIDDEN);
+    assert_eq!(guild_payload.unwrap()["error"], "account_too_new");
+
+    let (friend_status, friend_payload) = authed_json_request(
+        &app,
+        "POST",
+        String::from("/friends/requests"),
+        &alice.access_token,
+        "203.0.113.74",
+        Some(json!({ "recipient_user_id": bob_user_id })),
+    )
+    .await;
+    assert_eq!(friend_status, StatusCode::FORBIDDEN);
+    assert_eq!(friend_payload.unwrap()["error"], "account_too_new");
+}
+
 #[test]
 fn invalid_postgres_url_is_rejected() {
     let result = build_router(&AppConfig {
@@ -350,6 +1023,112 @@ fn zero_created_guild_limit_is_rejected() {
     assert!(result.is_err());
 }
 
+#[test]
+fn out_of_range_gateway_max_connection_lifetime_is_rejected() {
+    for lifetime in [Duration::ZERO, Duration::from_secs(7 * 24 * 60 * 60 + 1)] {
+        let result = build_router(&AppConfig {
+            gateway_max_connection_lifetime: lifetime,
+            ..AppConfig::default()
+        });
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn out_of_range_gateway_max_connections_per_user_is_rejected() {
+    for max_connections in [0, 257] {
+        let result = build_router(&AppConfig {
+            gateway_max_connections_per_user: max_connections,
+            ..AppConfig::default()
+        });
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn short_admin_api_secret_is_rejected() {
+    let result = build_router(&AppConfig {
+        admin_api_secret: Some(String::from("   too-short   ")),
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn out_of_range_gateway_subscription_revalidate_interval_is_rejected() {
+    for interval in [Duration::ZERO, Duration::from_secs(60 * 60 + 1)] {
+        let result = build_router(&AppConfig {
+            gateway_subscription_revalidate_interval: interval,
+            ..AppConfig::default()
+        });
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn invalid_attachment_key_layout_is_rejected() {
+    for prefix in [
+        "",
+        "/attachments",
+        "attachments/",
+        "a//b",
+        "../escape",
+        "key prefix",
+    ] {
+        let result = build_router(&AppConfig {
+            attachment_key_prefix: String::from(prefix),
+            ..AppConfig::default()
+        });
+        assert!(result.is_err(), "prefix {prefix:?} should be rejected");
+    }
+    let result = build_router(&AppConfig {
+        attachment_key_shard_chars: 5,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn tls_cert_and_key_paths_must_be_set_together() {
+    let cert_only = build_router(&AppConfig {
+        tls_cert_path: Some(std::path::PathBuf::from("/tmp/cert.pem")),
+        ..AppConfig::default()
+    });
+    let key_only = build_router(&AppConfig {
+        tls_key_path: Some(std::path::PathBuf::from("/tmp/key.pem")),
+        ..AppConfig::default()
+    });
+    assert!(cert_only.is_err());
+    assert!(key_only.is_err());
+}
+
+#[test]
+fn zero_max_message_attachment_total_bytes_is_rejected() {
+    let result = build_router(&AppConfig {
+        max_message_attachment_total_bytes: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn zero_max_friends_per_user_is_rejected() {
+    let result = build_router(&AppConfig {
+        max_friends_per_user: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn zero_max_incoming_friend_requests_per_user_is_rejected() {
+    let result = build_router(&AppConfig {
+        max_incoming_friend_requests_per_user: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
 #[test]
 fn zero_directory_join_per_ip_limit_is_rejected() {
     let result = build_router(&AppConfig {
@@ -377,6 +1156,60 @@ fn zero_audit_list_limit_max_is_rejected() {
     assert!(result.is_err());
 }
 
+#[test]
+fn zero_audit_log_memory_max_entries_is_rejected() {
+    let result = build_router(&AppConfig {
+        audit_log_memory_max_entries: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn zero_in_memory_max_guilds_is_rejected() {
+    let result = build_router(&AppConfig {
+        in_memory_max_guilds: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn zero_in_memory_max_channel_messages_is_rejected() {
+    let result = build_router(&AppConfig {
+        in_memory_max_channel_messages: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn zero_in_memory_max_friend_requests_is_rejected() {
+    let result = build_router(&AppConfig {
+        in_memory_max_friend_requests: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn zero_in_memory_max_total_friend_requests_is_rejected() {
+    let result = build_router(&AppConfig {
+        in_memory_max_total_friend_requests: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn zero_in_memory_max_rebuild_docs_is_rejected() {
+    let result = build_router(&AppConfig {
+        in_memory_max_rebuild_docs: 0,
+        ..AppConfig::default()
+    });
+    assert!(result.is_err());
+}
+
 #[test]
 fn zero_guild_ip_ban_max_entries_is_rejected() {
     let result = build_router(&AppConfig {
@@ -97,10 +97,10 @@ async fn guild_ip_ban_endpoints_add_list_remove_and_redact_payloads() {
 
     let (outsider_list_status, outsider_list_payload) =
         list_guild_ip_bans_for_test(&app, &outsider_auth, "203.0.113.243", &guild_id, None).await;
-    assert_eq!(outsider_list_status, StatusCode::FORBIDDEN);
+    assert_eq!(outsider_list_status, StatusCode::NOT_FOUND);
     assert_eq!(
         outsider_list_payload.expect("outsider list payload")["error"],
-        "forbidden"
+        "not_found"
     );
 
     let (remove_status, remove_payload) =
@@ -79,7 +79,12 @@ async fn profile_avatar_upload_and_download_round_trip() {
         0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
     ];
 
-    let app = build_router(&AppConfig::default()).unwrap();
+    let attachments = tempfile::tempdir().unwrap();
+    let app = build_router(&AppConfig {
+        attachment_root: attachments.path().to_path_buf(),
+        ..AppConfig::default()
+    })
+    .unwrap();
     let auth = register_and_login_as(&app, "avatar_owner", "203.0.113.142").await;
     let user_id = user_id_from_me(&app, &auth, "203.0.113.142").await;
 
@@ -143,7 +148,12 @@ async fn profile_banner_upload_and_download_round_trip() {
         0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
     ];
 
-    let app = build_router(&AppConfig::default()).unwrap();
+    let attachments = tempfile::tempdir().unwrap();
+    let app = build_router(&AppConfig {
+        attachment_root: attachments.path().to_path_buf(),
+        ..AppConfig::default()
+    })
+    .unwrap();
     let auth = register_and_login_as(&app, "banner_owner", "203.0.113.143").await;
     let user_id = user_id_from_me(&app, &auth, "203.0.113.143").await;
 
@@ -188,7 +198,9 @@ async fn profile_banner_upload_and_download_round_trip() {
 
 #[tokio::test]
 async fn profile_banner_upload_rejects_oversized_payload() {
+    let attachments = tempfile::tempdir().unwrap();
     let app = build_router(&AppConfig {
+        attachment_root: attachments.path().to_path_buf(),
         max_profile_banner_bytes: 8,
         ..AppConfig::default()
     })
@@ -217,7 +229,12 @@ async fn profile_banner_upload_requires_auth_and_matching_mime() {
         0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
     ];
 
-    let app = build_router(&AppConfig::default()).unwrap();
+    let attachments = tempfile::tempdir().unwrap();
+    let app = build_router(&AppConfig {
+        attachment_root: attachments.path().to_path_buf(),
+        ..AppConfig::default()
+    })
+    .unwrap();
     let auth = register_and_login_as(&app, "banner_guard", "203.0.113.145").await;
 
     let unauth_upload = Request::builder()
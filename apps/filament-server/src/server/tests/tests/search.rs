@@ -0,0 +1,174 @@
+use super::*;
+use crate::server::{
+    auth::issue_tokens,
+    errors::AuthFailure,
+    handlers::{
+        admin::ADMIN_SECRET_HEADER,
+        search::{rebuild_all_search_indexes, update_guild_search_timeout},
+    },
+    realtime::search_query_timeout_for_guild,
+    types::{GuildPath, UpdateSearchTimeoutRequest},
+};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+
+const ADMIN_SECRET: &str = "search-admin-secret-for-unit-tests";
+
+async fn bearer_headers_for_new_user(
+    state: &AppState,
+    user_id: UserId,
+    username: &str,
+) -> HeaderMap {
+    let username = Username::try_from(username.to_owned()).unwrap();
+    state.users.write().await.insert(
+        username.as_str().to_owned(),
+        UserRecord {
+            id: user_id,
+            username: username.clone(),
+            about_markdown: String::new(),
+            avatar: None,
+            avatar_version: 0,
+            banner: None,
+            banner_version: 0,
+            password_hash: hash_password("super-secure-password").unwrap(),
+            failed_logins: 0,
+            locked_until_unix: None,
+            created_at_unix: 0,
+        },
+    );
+    state
+        .user_ids
+        .write()
+        .await
+        .insert(user_id.to_string(), username.as_str().to_owned());
+
+    let (access_token, _, _) = issue_tokens(state, user_id, username.as_str(), "session").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "authorization",
+        format!("Bearer {access_token}").parse().unwrap(),
+    );
+    headers
+}
+
+#[tokio::test]
+async fn global_search_rebuild_requires_the_admin_api_secret() {
+    let server_owner = UserId::new();
+    let state = AppState::new(&AppConfig {
+        server_owner_user_id: Some(server_owner),
+        admin_api_secret: Some(String::from(ADMIN_SECRET)),
+        ..AppConfig::default()
+    })
+    .unwrap();
+
+    let owner_headers = bearer_headers_for_new_user(&state, server_owner, "operator_1").await;
+    let error = rebuild_all_search_indexes(State(state.clone()), owner_headers)
+        .await
+        .expect_err("a bearer token alone should not authorize a rebuild");
+    assert!(matches!(error, AuthFailure::Unauthorized));
+
+    let mut wrong_secret = HeaderMap::new();
+    wrong_secret.insert(ADMIN_SECRET_HEADER, "not-the-admin-secret".parse().unwrap());
+    let error = rebuild_all_search_indexes(State(state.clone()), wrong_secret)
+        .await
+        .expect_err("a wrong admin secret should be rejected");
+    assert!(matches!(error, AuthFailure::Unauthorized));
+
+    let mut admin_headers = HeaderMap::new();
+    admin_headers.insert(ADMIN_SECRET_HEADER, ADMIN_SECRET.parse().unwrap());
+    let status = rebuild_all_search_indexes(State(state.clone()), admin_headers)
+        .await
+        .expect("admin rebuild should succeed");
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn global_search_rebuild_is_hidden_without_an_admin_api_secret() {
+    let server_owner = UserId::new();
+    let state = AppState::new(&AppConfig {
+        server_owner_user_id: Some(server_owner),
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let headers = bearer_headers_for_new_user(&state, server_owner, "operator_2").await;
+    let error = rebuild_all_search_indexes(State(state), headers)
+        .await
+        .expect_err("rebuild should not exist without an admin secret");
+    assert!(matches!(error, AuthFailure::NotFound));
+}
+
+#[tokio::test]
+async fn guild_search_timeout_override_is_owner_only_and_bounded() {
+    let server_owner = UserId::new();
+    let state = AppState::new(&AppConfig {
+        server_owner_user_id: Some(server_owner),
+        search_query_timeout: Duration::from_millis(200),
+        search_query_timeout_max: Duration::from_millis(1_000),
+        ..AppConfig::default()
+    })
+    .unwrap();
+    state.membership_store.guilds().write().await.insert(
+        String::from("g1"),
+        GuildRecord {
+            name: String::from("Search Timeout"),
+            visibility: GuildVisibility::Private,
+            created_by_user_id: server_owner,
+            default_join_role_id: None,
+            description: None,
+            icon_attachment_id: None,
+            search_query_timeout_ms: None,
+            system_channel_id: None,
+            welcome_messages: false,
+            members: HashMap::from([(server_owner, Role::Owner)]),
+            banned_members: std::collections::HashSet::new(),
+            channels: HashMap::new(),
+        },
+    );
+    let update = |headers: HeaderMap, guild_id: &str, timeout_ms: Option<u64>| {
+        update_guild_search_timeout(
+            State(state.clone()),
+            headers,
+            Path(GuildPath {
+                guild_id: guild_id.to_owned(),
+            }),
+            Json(UpdateSearchTimeoutRequest { timeout_ms }),
+        )
+    };
+
+    let member_headers = bearer_headers_for_new_user(&state, UserId::new(), "member_3").await;
+    let error = update(member_headers, "g1", Some(500))
+        .await
+        .expect_err("non-owner update should be rejected");
+    assert!(matches!(error, AuthFailure::Forbidden));
+
+    let owner_headers = bearer_headers_for_new_user(&state, server_owner, "operator_3").await;
+    for timeout_ms in [0, 1_001] {
+        let error = update(owner_headers.clone(), "g1", Some(timeout_ms))
+            .await
+            .expect_err("out-of-range override should be rejected");
+        assert!(matches!(error, AuthFailure::InvalidRequest));
+    }
+    let error = update(owner_headers.clone(), "missing", Some(500))
+        .await
+        .expect_err("unknown guild should be rejected");
+    assert!(matches!(error, AuthFailure::NotFound));
+
+    let Json(response) = update(owner_headers.clone(), "g1", Some(750))
+        .await
+        .expect("owner update should succeed");
+    assert_eq!(response.timeout_ms, Some(750));
+    assert_eq!(response.effective_timeout_ms, 750);
+    assert_eq!(
+        search_query_timeout_for_guild(&state, "g1").await.unwrap(),
+        Duration::from_millis(750)
+    );
+
+    let Json(cleared) = update(owner_headers, "g1", None)
+        .await
+        .expect("clearing the override should succeed");
+    assert_eq!(cleared.timeout_ms, None);
+    assert_eq!(cleared.effective_timeout_ms, 200);
+}
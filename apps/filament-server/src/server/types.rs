@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 use axum::{
     http::{header::CONTENT_TYPE, StatusCode},
@@ -61,6 +61,71 @@ pub(crate) async fn slow() -> Json<HealthResponse> {
     Json(HealthResponse { status: "ok" })
 }
 
+#[derive(Debug, Serialize)]
+pub(crate) struct CaptchaCapability {
+    pub(crate) provider: &'static str,
+    pub(crate) site_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct CapabilitiesResponse {
+    pub(crate) max_message_content_bytes: usize,
+    pub(crate) max_attachments_per_message: usize,
+    pub(crate) max_attachment_bytes: usize,
+    pub(crate) max_message_attachment_total_bytes: u64,
+    pub(crate) user_attachment_quota_bytes: u64,
+    pub(crate) max_profile_avatar_bytes: usize,
+    pub(crate) max_profile_banner_bytes: usize,
+    pub(crate) history_default_limit: usize,
+    pub(crate) history_max_limit: usize,
+    pub(crate) search_query_max_chars: usize,
+    pub(crate) channel_kinds: Vec<ChannelKind>,
+    pub(crate) captcha: Option<CaptchaCapability>,
+    pub(crate) voice_enabled: bool,
+    pub(crate) search_enabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AdminConnectionResponse {
+    pub(crate) connection_id: String,
+    pub(crate) user_id: String,
+    pub(crate) guild_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AdminConnectionsResponse {
+    pub(crate) connections: Vec<AdminConnectionResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AdminConnectionPath {
+    pub(crate) connection_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AdminMessageRatesQuery {
+    pub(crate) limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AdminMessageRateResponse {
+    pub(crate) user_id: String,
+    pub(crate) messages: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AdminGuildMessageRateResponse {
+    pub(crate) guild_id: String,
+    pub(crate) messages: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AdminMessageRatesResponse {
+    pub(crate) window_secs: i64,
+    pub(crate) users: Vec<AdminMessageRateResponse>,
+    pub(crate) guilds: Vec<AdminGuildMessageRateResponse>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct RegisterRequest {
@@ -99,6 +164,12 @@ pub(crate) struct AuthError {
     pub(crate) error: &'static str,
 }
 
+#[derive(Debug, Serialize)]
+pub(crate) struct LimitError {
+    pub(crate) error: &'static str,
+    pub(crate) limit: usize,
+}
+
 #[derive(Debug, Serialize)]
 pub(crate) struct MeResponse {
     pub(crate) user_id: String,
@@ -107,6 +178,14 @@ pub(crate) struct MeResponse {
     pub(crate) about_markdown_tokens: Vec<MarkdownToken>,
     pub(crate) avatar_version: i64,
     pub(crate) banner_version: i64,
+    pub(crate) created_at_unix: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TokenStatusResponse {
+    pub(crate) user_id: String,
+    pub(crate) expires_at_unix: i64,
+    pub(crate) expires_in_secs: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -126,17 +205,41 @@ pub(crate) struct UserProfileResponse {
     pub(crate) banner_version: i64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RelationshipStatus {
+    None,
+    Friends,
+    IncomingRequest,
+    OutgoingRequest,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RelationshipResponse {
+    pub(crate) user_id: String,
+    pub(crate) relationship: RelationshipStatus,
+    /// Set for pending requests so clients can accept or cancel directly.
+    pub(crate) request_id: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct UserLookupRequest {
     pub(crate) user_ids: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UserLookupByUsernameRequest {
+    pub(crate) usernames: Vec<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub(crate) struct UserLookupItem {
     pub(crate) user_id: String,
     pub(crate) username: String,
     pub(crate) avatar_version: i64,
+    pub(crate) created_at_unix: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -191,6 +294,8 @@ pub(crate) struct FriendshipRequestCreateResponse {
 pub(crate) struct CreateGuildRequest {
     pub(crate) name: String,
     pub(crate) visibility: Option<GuildVisibility>,
+    /// Overrides the server's `create_default_guild_channels` setting.
+    pub(crate) default_channels: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -198,6 +303,10 @@ pub(crate) struct CreateGuildRequest {
 pub(crate) struct UpdateGuildRequest {
     pub(crate) name: Option<String>,
     pub(crate) visibility: Option<GuildVisibility>,
+    /// An empty string clears the description.
+    pub(crate) description: Option<String>,
+    /// An empty string clears the icon.
+    pub(crate) icon_attachment_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -205,6 +314,24 @@ pub(crate) struct GuildResponse {
     pub(crate) guild_id: String,
     pub(crate) name: String,
     pub(crate) visibility: GuildVisibility,
+    pub(crate) description: Option<String>,
+    pub(crate) icon_attachment_id: Option<String>,
+    /// Channels created together with the guild; only set on create.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) channels: Vec<ChannelResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct GuildDetailResponse {
+    pub(crate) guild_id: String,
+    pub(crate) name: String,
+    pub(crate) visibility: GuildVisibility,
+    pub(crate) description: Option<String>,
+    pub(crate) icon_attachment_id: Option<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) member_count: u64,
+    /// The caller's role in the guild.
+    pub(crate) role: Role,
 }
 
 #[derive(Debug, Serialize)]
@@ -217,6 +344,37 @@ pub(crate) struct GuildListResponse {
 pub(crate) struct CreateChannelRequest {
     pub(crate) name: String,
     pub(crate) kind: Option<ChannelKind>,
+    pub(crate) category_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CreateChannelBatchRequest {
+    pub(crate) channels: Vec<CreateChannelRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UpdateChannelRequest {
+    pub(crate) locked: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UpdateChannelRetentionRequest {
+    pub(crate) retention_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UpdateChannelVotingRequest {
+    pub(crate) voting: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UpdateChannelCategoryRequest {
+    pub(crate) category_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -224,6 +382,11 @@ pub(crate) struct ChannelResponse {
     pub(crate) channel_id: String,
     pub(crate) name: String,
     pub(crate) kind: ChannelKind,
+    pub(crate) locked: bool,
+    pub(crate) retention_secs: Option<u64>,
+    pub(crate) voting: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) category_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -237,6 +400,45 @@ pub(crate) struct ChannelPermissionsResponse {
     pub(crate) permissions: Vec<Permission>,
 }
 
+#[derive(Debug, Serialize)]
+pub(crate) struct ChannelRoleOverrideResponse {
+    pub(crate) role: Role,
+    pub(crate) allow: Vec<Permission>,
+    pub(crate) deny: Vec<Permission>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ChannelRoleOverrideListResponse {
+    pub(crate) overrides: Vec<ChannelRoleOverrideResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ChannelRoleOverrideResetResponse {
+    pub(crate) cleared: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ChannelRoleAccessResponse {
+    pub(crate) role_id: String,
+    pub(crate) name: String,
+    pub(crate) position: i32,
+    pub(crate) can_access: bool,
+    pub(crate) permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ChannelMemberAccessResponse {
+    pub(crate) user_id: String,
+    pub(crate) can_access: bool,
+    pub(crate) permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ChannelAccessResponse {
+    pub(crate) roles: Vec<ChannelRoleAccessResponse>,
+    pub(crate) members: Vec<ChannelMemberAccessResponse>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct CreateMessageRequest {
@@ -244,10 +446,20 @@ pub(crate) struct CreateMessageRequest {
     pub(crate) attachment_ids: Option<Vec<String>>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MoveMessageRequest {
+    /// Target channel in the same guild.
+    pub(crate) channel_id: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct EditMessageRequest {
     pub(crate) content: String,
+    /// Rejects the edit with `409` unless the stored `edited_at_unix` still
+    /// matches; `0` expects a message that was never edited.
+    pub(crate) expected_edited_at_unix: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -279,6 +491,31 @@ pub(crate) struct ReorderGuildRolesRequest {
     pub(crate) role_ids: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UpdateGuildSystemChannelRequest {
+    pub(crate) system_channel_id: Option<String>,
+    pub(crate) welcome_messages: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct GuildSystemChannelResponse {
+    pub(crate) system_channel_id: Option<String>,
+    pub(crate) welcome_messages: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UpdateGuildTagsRequest {
+    /// Replaces every tag on the guild; an empty list clears them.
+    pub(crate) tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct GuildTagsResponse {
+    pub(crate) tags: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct UpdateGuildDefaultJoinRoleRequest {
@@ -321,11 +558,61 @@ pub(crate) struct MessageResponse {
     pub(crate) guild_id: String,
     pub(crate) channel_id: String,
     pub(crate) author_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) author_username: Option<String>,
     pub(crate) content: String,
     pub(crate) markdown_tokens: Vec<MarkdownToken>,
     pub(crate) attachments: Vec<AttachmentResponse>,
     pub(crate) reactions: Vec<ReactionResponse>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) embeds: Vec<EmbedResponse>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) deleted: bool,
+    /// Posted by the server (e.g. a welcome message) on the author's behalf.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) system: bool,
+    pub(crate) created_at_unix: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) edited_at_unix: Option<i64>,
+    /// Net vote score; only set on history from voting channels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) score: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ExportedMessage {
+    pub(crate) message_id: String,
+    pub(crate) guild_id: String,
+    pub(crate) channel_id: String,
+    pub(crate) author_id: String,
+    pub(crate) content: String,
     pub(crate) created_at_unix: i64,
+    pub(crate) attachments: Vec<AttachmentResponse>,
+    pub(crate) reactions: Vec<ReactionResponse>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum UserExportRecord {
+    Account {
+        user_id: String,
+        username: String,
+        exported_at_unix: i64,
+    },
+    Friend(FriendRecordResponse),
+    Attachment(AttachmentResponse),
+    Message(ExportedMessage),
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub(crate) struct EmbedResponse {
+    pub(crate) url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) image_url: Option<String>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -366,6 +653,20 @@ pub(crate) struct MessageHistoryResponse {
     pub(crate) next_before: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RecentMessagesRequest {
+    pub(crate) channel_ids: Vec<String>,
+    pub(crate) limit: Option<usize>,
+}
+
+/// Newest-first messages keyed by channel id; channels the caller cannot
+/// read are left out.
+#[derive(Debug, Serialize)]
+pub(crate) struct RecentMessagesResponse {
+    pub(crate) channels: BTreeMap<String, Vec<MessageResponse>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct GuildPath {
     pub(crate) guild_id: String,
@@ -457,10 +758,42 @@ pub(crate) struct ReactionPath {
     pub(crate) emoji: String,
 }
 
+/// Comma-separated permission names, e.g. `allow=create_message,publish_video`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ChannelOverridePreviewQuery {
+    pub(crate) role: Role,
+    #[serde(default)]
+    pub(crate) allow: String,
+    #[serde(default)]
+    pub(crate) deny: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct HistoryQuery {
     pub(crate) limit: Option<usize>,
     pub(crate) before: Option<String>,
+    #[serde(default)]
+    pub(crate) include_deleted: bool,
+    #[serde(default)]
+    pub(crate) sort: HistorySort,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum HistorySort {
+    #[default]
+    Recent,
+    /// Orders each page by net vote score; voting channels only.
+    Score,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SearchSort {
+    #[default]
+    Relevance,
+    Recent,
 }
 
 #[derive(Debug, Deserialize)]
@@ -468,12 +801,27 @@ pub(crate) struct SearchQuery {
     pub(crate) q: String,
     pub(crate) limit: Option<usize>,
     pub(crate) channel_id: Option<String>,
+    #[serde(default)]
+    pub(crate) include_author_usernames: bool,
+    #[serde(default)]
+    pub(crate) sort: SearchSort,
+    /// `Some(false)` returns ids only and skips message hydration.
+    pub(crate) hydrate: Option<bool>,
+    /// Adds per-channel match counts to the response.
+    #[serde(default)]
+    pub(crate) facets: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct PublicGuildListQuery {
     pub(crate) q: Option<String>,
+    /// Only guilds carrying this tag.
+    pub(crate) tag: Option<String>,
     pub(crate) limit: Option<usize>,
+    /// Cursor from `next_before`: guilds created before it.
+    pub(crate) before: Option<String>,
+    /// Cursor from `next_after`: guilds created after it.
+    pub(crate) after: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -481,11 +829,16 @@ pub(crate) struct PublicGuildListItem {
     pub(crate) guild_id: String,
     pub(crate) name: String,
     pub(crate) visibility: GuildVisibility,
+    pub(crate) description: Option<String>,
+    pub(crate) icon_attachment_id: Option<String>,
+    pub(crate) tags: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub(crate) struct PublicGuildListResponse {
     pub(crate) guilds: Vec<PublicGuildListItem>,
+    pub(crate) next_before: Option<String>,
+    pub(crate) next_after: Option<String>,
 }
 
 #[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
@@ -572,7 +925,10 @@ pub(crate) struct GuildRoleListResponse {
 #[derive(Debug, Serialize)]
 pub(crate) struct SearchResponse {
     pub(crate) message_ids: Vec<String>,
-    pub(crate) messages: Vec<MessageResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) messages: Option<Vec<MessageResponse>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) facets: Option<BTreeMap<String, usize>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -581,6 +937,137 @@ pub(crate) struct SearchReconcileResponse {
     pub(crate) deleted: usize,
 }
 
+#[derive(Debug, Serialize)]
+pub(crate) struct SearchReindexResponse {
+    pub(crate) indexed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReportMessageRequest {
+    pub(crate) reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReportUserRequest {
+    pub(crate) guild_id: String,
+    pub(crate) reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NotificationListQuery {
+    pub(crate) limit: Option<usize>,
+    pub(crate) before: Option<String>,
+    #[serde(default)]
+    pub(crate) unread_only: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct NotificationResponse {
+    pub(crate) notification_id: String,
+    pub(crate) kind: &'static str,
+    pub(crate) guild_id: String,
+    pub(crate) channel_id: String,
+    pub(crate) message_id: String,
+    pub(crate) author_id: String,
+    pub(crate) scope: String,
+    pub(crate) created_at_unix: i64,
+    pub(crate) read: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct NotificationListResponse {
+    pub(crate) notifications: Vec<NotificationResponse>,
+    pub(crate) unread_count: u64,
+    pub(crate) next_before: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MarkNotificationsReadRequest {
+    pub(crate) notification_ids: Vec<String>,
+}
+
+/// Shape of the browser's `PushSubscription.toJSON()`, so clients can post it
+/// unchanged; `expirationTime` and other extra fields are ignored.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PushSubscriptionRequest {
+    pub(crate) endpoint: String,
+    pub(crate) keys: PushSubscriptionKeys,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PushSubscriptionKeys {
+    pub(crate) p256dh: String,
+    pub(crate) auth: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PushSubscriptionResponse {
+    pub(crate) subscription_id: String,
+    pub(crate) endpoint: String,
+    pub(crate) created_at_unix: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PushSubscriptionPath {
+    pub(crate) subscription_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReportListQuery {
+    pub(crate) limit: Option<usize>,
+    pub(crate) before: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ReportResponse {
+    pub(crate) report_id: String,
+    pub(crate) guild_id: String,
+    pub(crate) reporter_user_id: String,
+    pub(crate) target_user_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) channel_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) message_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) message_content: Option<String>,
+    pub(crate) reason: String,
+    pub(crate) created_at_unix: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ReportListResponse {
+    pub(crate) reports: Vec<ReportResponse>,
+    pub(crate) next_before: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MuteMemberRequest {
+    pub(crate) duration_secs: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct MemberMuteResponse {
+    pub(crate) guild_id: String,
+    pub(crate) user_id: String,
+    pub(crate) expires_at_unix: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct UpdateSearchTimeoutRequest {
+    pub(crate) timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SearchTimeoutResponse {
+    pub(crate) timeout_ms: Option<u64>,
+    pub(crate) effective_timeout_ms: u64,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct VoiceTokenRequest {
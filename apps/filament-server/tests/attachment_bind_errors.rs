@@ -0,0 +1,186 @@
+mod common;
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use ulid::Ulid;
+
+use common::{
+    attachment_root, create_guild_with_channel, in_memory_app_with, parse_json_body,
+    postgres_app_with, register_and_login, send_json, AuthResponse,
+};
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        attachment_root: attachment_root("attachment-bind"),
+        ..common::test_config()
+    }
+}
+
+async fn upload_text(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    filename: &str,
+) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("{channel_uri}/attachments?filename={filename}"))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", "text/plain")
+        .header("x-forwarded-for", ip)
+        .body(Body::from(filename.to_owned()))
+        .expect("upload request should build");
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("upload should execute");
+    assert_eq!(response.status(), StatusCode::OK);
+    let uploaded: Value = parse_json_body(response).await;
+    uploaded["attachment_id"].as_str().unwrap().to_owned()
+}
+
+async fn create_message(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    attachment_ids: &[&str],
+) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        format!("{channel_uri}/messages"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({"content":"bind","attachment_ids":attachment_ids})),
+    )
+    .await
+}
+
+async fn assert_bind_error(response: axum::response::Response, expected: &str) {
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = parse_json_body(response).await;
+    assert_eq!(body["error"], expected);
+}
+
+async fn setup_gallery(
+    app: &axum::Router,
+    prefix: &str,
+    ip: &str,
+) -> (AuthResponse, String, String) {
+    let owner = register_and_login(app, prefix, ip).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, ip, "Gallery Guild", "gallery").await;
+    let channel_uri = format!("/guilds/{guild_id}/channels/{channel_id}");
+    (owner, guild_id, channel_uri)
+}
+
+async fn bind_missing_and_foreign(app: &axum::Router) {
+    let ip = "203.0.113.212";
+    let (owner, _, channel_uri) = setup_gallery(app, "binder", ip).await;
+    let (stranger, _, stranger_channel_uri) = setup_gallery(app, "stranger", ip).await;
+    let foreign = upload_text(app, &stranger, ip, &stranger_channel_uri, "foreign.txt").await;
+
+    let missing = Ulid::new().to_string();
+    assert_bind_error(
+        create_message(app, &owner, ip, &channel_uri, &[&missing]).await,
+        "attachment_not_found",
+    )
+    .await;
+    // Another user's attachment reads as missing rather than revealing it.
+    assert_bind_error(
+        create_message(app, &owner, ip, &channel_uri, &[&foreign]).await,
+        "attachment_not_found",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn unknown_and_foreign_attachments_are_not_found() {
+    bind_missing_and_foreign(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_unknown_and_foreign_attachments_are_not_found() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    bind_missing_and_foreign(&app).await;
+}
+
+async fn bind_from_another_channel(app: &axum::Router) {
+    let ip = "203.0.113.213";
+    let (owner, guild_id, channel_uri) = setup_gallery(app, "binder", ip).await;
+    let other_channel = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        ip,
+        Some(json!({"name":"elsewhere"})),
+    )
+    .await;
+    assert_eq!(other_channel.status(), StatusCode::OK);
+    let other_channel: Value = parse_json_body(other_channel).await;
+    let other_channel_uri = format!(
+        "/guilds/{guild_id}/channels/{}",
+        other_channel["channel_id"].as_str().unwrap()
+    );
+    let fresh = upload_text(app, &owner, ip, &channel_uri, "fresh.txt").await;
+    let misplaced = upload_text(app, &owner, ip, &other_channel_uri, "misplaced.txt").await;
+
+    assert_bind_error(
+        create_message(app, &owner, ip, &channel_uri, &[&fresh, &misplaced]).await,
+        "attachment_wrong_channel",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn attachments_uploaded_to_another_channel_are_rejected() {
+    bind_from_another_channel(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_attachments_uploaded_to_another_channel_are_rejected() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    bind_from_another_channel(&app).await;
+}
+
+async fn bind_already_used(app: &axum::Router) {
+    let ip = "203.0.113.214";
+    let (owner, _, channel_uri) = setup_gallery(app, "binder", ip).await;
+    let fresh = upload_text(app, &owner, ip, &channel_uri, "fresh.txt").await;
+    let spare = upload_text(app, &owner, ip, &channel_uri, "spare.txt").await;
+
+    let bound = create_message(app, &owner, ip, &channel_uri, &[&fresh]).await;
+    assert_eq!(bound.status(), StatusCode::OK);
+    assert_bind_error(
+        create_message(app, &owner, ip, &channel_uri, &[&spare, &fresh]).await,
+        "attachment_already_used",
+    )
+    .await;
+
+    // The failed request bound nothing, so the spare is still free to use.
+    let retried = create_message(app, &owner, ip, &channel_uri, &[&spare]).await;
+    assert_eq!(retried.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn used_attachments_are_rejected_without_binding_the_rest() {
+    bind_already_used(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_used_attachments_are_rejected_without_binding_the_rest() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    bind_already_used(&app).await;
+}
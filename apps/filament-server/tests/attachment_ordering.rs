@@ -0,0 +1,106 @@
+mod common;
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{
+    attachment_root, create_guild_with_channel, in_memory_app_with, parse_json_body,
+    postgres_app_with, register_and_login, send_json, AuthResponse,
+};
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        attachment_root: attachment_root("attachment-order"),
+        ..common::test_config()
+    }
+}
+
+async fn upload_text(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    filename: &str,
+) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("{channel_uri}/attachments?filename={filename}"))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", "text/plain")
+        .header("x-forwarded-for", ip)
+        .body(Body::from(filename.to_owned()))
+        .expect("upload request should build");
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("upload should execute");
+    assert_eq!(response.status(), StatusCode::OK);
+    let uploaded: Value = parse_json_body(response).await;
+    uploaded["attachment_id"].as_str().unwrap().to_owned()
+}
+
+fn attachment_ids(message: &Value) -> Vec<String> {
+    message["attachments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|attachment| attachment["attachment_id"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+async fn post_gallery_in_author_order(app: &axum::Router) {
+    let ip = "203.0.113.211";
+    let owner = register_and_login(app, "gallery", ip).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, ip, "Gallery Guild", "gallery").await;
+    let channel_uri = format!("/guilds/{guild_id}/channels/{channel_id}");
+
+    let mut uploaded = Vec::new();
+    for filename in ["first.txt", "second.txt", "third.txt"] {
+        uploaded.push(upload_text(app, &owner, ip, &channel_uri, filename).await);
+    }
+    // Newest upload first, so id order and author order disagree.
+    let listed: Vec<String> = uploaded.iter().rev().cloned().collect();
+
+    let created = send_json(
+        app,
+        "POST",
+        format!("{channel_uri}/messages"),
+        Some(&owner.access_token),
+        ip,
+        Some(json!({"content":"gallery","attachment_ids":listed})),
+    )
+    .await;
+    assert_eq!(created.status(), StatusCode::OK);
+    let created: Value = parse_json_body(created).await;
+    assert_eq!(attachment_ids(&created), listed);
+
+    let history = send_json(
+        app,
+        "GET",
+        format!("{channel_uri}/messages"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(history.status(), StatusCode::OK);
+    let history: Value = parse_json_body(history).await;
+    assert_eq!(attachment_ids(&history["messages"][0]), listed);
+}
+
+#[tokio::test]
+async fn message_attachments_keep_author_order() {
+    post_gallery_in_author_order(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_message_attachments_keep_author_order() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    post_gallery_in_author_order(&app).await;
+}
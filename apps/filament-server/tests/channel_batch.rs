@@ -0,0 +1,162 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_guild, in_memory_app, parse_json_body, postgres_app, register_and_login, send_json,
+    user_id, AuthResponse,
+};
+
+async fn create_batch(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    channels: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels/batch"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({"channels":channels})),
+    )
+    .await
+}
+
+async fn listed_names(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+) -> Vec<String> {
+    let listed = send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    listed["channels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|channel| channel["name"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+async fn create_batch_in_order(app: &axum::Router) {
+    let ip = "203.0.113.221";
+    let owner = register_and_login(app, "batch_owner", ip).await;
+    let guild_id = create_guild(app, &owner, ip, json!({"name":"Batch Guild"})).await;
+
+    let names = ["zeta", "alpha", "lobby", "middle"];
+    let created = create_batch(
+        app,
+        &owner,
+        ip,
+        &guild_id,
+        json!([
+            {"name":names[0]},
+            {"name":names[1],"kind":"voice"},
+            {"name":names[2]},
+            {"name":names[3],"kind":"text"},
+        ]),
+    )
+    .await;
+    assert_eq!(created.status(), StatusCode::OK);
+    let created: Value = parse_json_body(created).await;
+    let created = created["channels"].as_array().unwrap();
+    assert_eq!(created.len(), names.len());
+    for (channel, name) in created.iter().zip(names) {
+        assert_eq!(channel["name"], name);
+        assert_eq!(channel["locked"], false);
+    }
+    assert_eq!(created[1]["kind"], "voice");
+    assert_eq!(created[0]["kind"], "text");
+    assert_eq!(listed_names(app, &owner, ip, &guild_id).await, names);
+}
+
+#[tokio::test]
+async fn channel_batches_create_channels_in_request_order() {
+    create_batch_in_order(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_channel_batches_create_channels_in_request_order() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    create_batch_in_order(&app).await;
+}
+
+async fn reject_invalid_batches(app: &axum::Router) {
+    let ip = "203.0.113.222";
+    let owner = register_and_login(app, "batch_owner", ip).await;
+    let guild_id = create_guild(app, &owner, ip, json!({"name":"Batch Guild"})).await;
+
+    let oversized: Vec<Value> = (0..51)
+        .map(|index| json!({"name":format!("bulk-{index}")}))
+        .collect();
+    for invalid in [
+        json!([]),
+        Value::from(oversized),
+        json!([{"name":"valid-first"}, {"name":""}]),
+    ] {
+        let rejected = create_batch(app, &owner, ip, &guild_id, invalid).await;
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    }
+    assert!(listed_names(app, &owner, ip, &guild_id).await.is_empty());
+}
+
+#[tokio::test]
+async fn invalid_channel_batches_create_nothing() {
+    reject_invalid_batches(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_invalid_channel_batches_create_nothing() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    reject_invalid_batches(&app).await;
+}
+
+#[tokio::test]
+async fn channel_batches_require_channel_management() {
+    let app = in_memory_app();
+    let (owner_ip, member_ip) = ("203.0.113.223", "203.0.113.224");
+    let owner = register_and_login(&app, "batch_owner", owner_ip).await;
+    let member = register_and_login(&app, "batch_member", member_ip).await;
+    let member_user_id = user_id(&app, &member, member_ip).await;
+    let guild_id = create_guild(&app, &owner, owner_ip, json!({"name":"Batch Guild"})).await;
+    let added = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        owner_ip,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+
+    let forbidden = create_batch(
+        &app,
+        &member,
+        member_ip,
+        &guild_id,
+        json!([{"name":"sneaky"}]),
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    assert!(listed_names(&app, &owner, owner_ip, &guild_id)
+        .await
+        .is_empty());
+}
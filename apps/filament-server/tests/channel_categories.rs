@@ -0,0 +1,335 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild, in_memory_app, parse_json_body, post_message, postgres_app, register_and_login,
+    send_json, user_id, AuthResponse,
+};
+
+async fn create_channel(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    body: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&auth.access_token),
+        ip,
+        Some(body),
+    )
+    .await
+}
+
+async fn created_id(response: axum::response::Response) -> String {
+    assert_eq!(response.status(), StatusCode::OK);
+    let created: Value = parse_json_body(response).await;
+    created["channel_id"].as_str().unwrap().to_owned()
+}
+
+async fn set_category(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    channel_id: &str,
+    category_id: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}/channels/{channel_id}/category"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({"category_id":category_id})),
+    )
+    .await
+}
+
+async fn listed_categories(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+) -> Vec<(String, Value)> {
+    let listed = send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    listed["channels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|channel| {
+            (
+                channel["name"].as_str().unwrap().to_owned(),
+                channel.get("category_id").cloned().unwrap_or(Value::Null),
+            )
+        })
+        .collect()
+}
+
+const OWNER_IP: &str = "203.0.113.231";
+const MEMBER_IP: &str = "203.0.113.232";
+
+struct CategoryGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    guild_id: String,
+    category_id: String,
+    text_id: String,
+}
+
+/// Creates a guild with a `Community` category and an uncategorized `lobby`.
+async fn setup_category_guild(app: &axum::Router) -> CategoryGuild {
+    let owner = register_and_login(app, "cat_owner", OWNER_IP).await;
+    let member = register_and_login(app, "cat_member", MEMBER_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let guild_id = create_guild(app, &owner, OWNER_IP, json!({"name":"Category Guild"})).await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+
+    let category_id = created_id(
+        create_channel(
+            app,
+            &owner,
+            OWNER_IP,
+            &guild_id,
+            json!({"name":"Community","kind":"category"}),
+        )
+        .await,
+    )
+    .await;
+    let text_id =
+        created_id(create_channel(app, &owner, OWNER_IP, &guild_id, json!({"name":"lobby"})).await)
+            .await;
+    CategoryGuild {
+        owner,
+        member,
+        guild_id,
+        category_id,
+        text_id,
+    }
+}
+
+async fn create_categorized_channels(app: &axum::Router) {
+    let guild = setup_category_guild(app).await;
+    let category_id = &guild.category_id;
+
+    let child = create_channel(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        json!({"name":"announcements","category_id":category_id}),
+    )
+    .await;
+    assert_eq!(child.status(), StatusCode::OK);
+    let child: Value = parse_json_body(child).await;
+    assert_eq!(child["category_id"], category_id.as_str());
+
+    let batch = send_json(
+        app,
+        "POST",
+        format!("/guilds/{}/channels/batch", guild.guild_id),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        Some(json!({"channels":[
+            {"name":"voice-hangout","kind":"voice","category_id":category_id},
+            {"name":"off-topic"},
+        ]})),
+    )
+    .await;
+    assert_eq!(batch.status(), StatusCode::OK);
+    let batch: Value = parse_json_body(batch).await;
+    assert_eq!(batch["channels"][0]["category_id"], category_id.as_str());
+    assert!(batch["channels"][1].get("category_id").is_none());
+
+    let category = Value::from(category_id.as_str());
+    assert_eq!(
+        listed_categories(app, &guild.owner, OWNER_IP, &guild.guild_id).await,
+        vec![
+            (String::from("Community"), Value::Null),
+            (String::from("lobby"), Value::Null),
+            (String::from("announcements"), category.clone()),
+            (String::from("voice-hangout"), category),
+            (String::from("off-topic"), Value::Null),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn channels_can_be_created_under_a_category_singly_and_in_batches() {
+    create_categorized_channels(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_channels_can_be_created_under_a_category_singly_and_in_batches() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    create_categorized_channels(&app).await;
+}
+
+async fn move_in_and_out_of_category(app: &axum::Router) {
+    let guild = setup_category_guild(app).await;
+
+    let moved = set_category(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &guild.text_id,
+        json!(guild.category_id),
+    )
+    .await;
+    assert_eq!(moved.status(), StatusCode::OK);
+    let moved: Value = parse_json_body(moved).await;
+    assert_eq!(moved["category_id"], guild.category_id.as_str());
+    assert_eq!(
+        listed_categories(app, &guild.owner, OWNER_IP, &guild.guild_id).await,
+        vec![
+            (String::from("Community"), Value::Null),
+            (
+                String::from("lobby"),
+                Value::from(guild.category_id.as_str())
+            ),
+        ]
+    );
+
+    let cleared = set_category(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &guild.text_id,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(cleared.status(), StatusCode::OK);
+    let cleared: Value = parse_json_body(cleared).await;
+    assert!(cleared.get("category_id").is_none());
+}
+
+#[tokio::test]
+async fn channels_move_into_and_out_of_categories() {
+    move_in_and_out_of_category(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_channels_move_into_and_out_of_categories() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    move_in_and_out_of_category(&app).await;
+}
+
+#[tokio::test]
+async fn channels_cannot_be_created_under_categories_text_channels_or_unknown_parents() {
+    let app = in_memory_app();
+    let guild = setup_category_guild(&app).await;
+    for bad_category in [
+        json!({"name":"nested","kind":"category","category_id":guild.category_id}),
+        json!({"name":"under-text","category_id":guild.text_id}),
+        json!({"name":"under-missing","category_id":Ulid::new().to_string()}),
+    ] {
+        let rejected =
+            create_channel(&app, &guild.owner, OWNER_IP, &guild.guild_id, bad_category).await;
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn category_changes_require_a_manager_a_known_channel_and_a_real_parent() {
+    let app = in_memory_app();
+    let guild = setup_category_guild(&app).await;
+
+    let forbidden = set_category(
+        &app,
+        &guild.member,
+        MEMBER_IP,
+        &guild.guild_id,
+        &guild.text_id,
+        json!(guild.category_id),
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    let self_parent = set_category(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &guild.text_id,
+        json!(guild.text_id),
+    )
+    .await;
+    assert_eq!(self_parent.status(), StatusCode::BAD_REQUEST);
+    let missing = set_category(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &Ulid::new().to_string(),
+        json!(guild.category_id),
+    )
+    .await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn categories_do_not_accept_posted_or_moved_messages() {
+    let app = in_memory_app();
+    let guild = setup_category_guild(&app).await;
+    let (guild_id, category_id, text_id) = (&guild.guild_id, &guild.category_id, &guild.text_id);
+
+    let category_post = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/channels/{category_id}/messages"),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        Some(json!({"content":"into the category"})),
+    )
+    .await;
+    assert_eq!(category_post.status(), StatusCode::BAD_REQUEST);
+    let message_id = post_message(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        guild_id,
+        text_id,
+        "stays in the lobby",
+    )
+    .await;
+    let category_move = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/channels/{text_id}/messages/{message_id}/move"),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        Some(json!({"channel_id":category_id})),
+    )
+    .await;
+    assert_eq!(category_move.status(), StatusCode::BAD_REQUEST);
+}
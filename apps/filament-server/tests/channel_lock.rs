@@ -0,0 +1,179 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+const OWNER_IP: &str = "203.0.113.201";
+const MODERATOR_IP: &str = "203.0.113.202";
+const MEMBER_IP: &str = "203.0.113.203";
+
+struct LockGuild {
+    owner: AuthResponse,
+    moderator: AuthResponse,
+    member: AuthResponse,
+    guild_id: String,
+    channel_id: String,
+}
+
+impl LockGuild {
+    fn channel_uri(&self) -> String {
+        format!("/guilds/{}/channels/{}", self.guild_id, self.channel_id)
+    }
+}
+
+async fn setup_lock_guild(app: &axum::Router) -> LockGuild {
+    let owner = register_and_login(app, "lock_owner", OWNER_IP).await;
+    let moderator = register_and_login(app, "lock_mod", MODERATOR_IP).await;
+    let member = register_and_login(app, "lock_member", MEMBER_IP).await;
+    let moderator_user_id = user_id(app, &moderator, MODERATOR_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, OWNER_IP, "Lock Guild", "lock-chat").await;
+    for user_id in [&moderator_user_id, &member_user_id] {
+        let added = send_json(
+            app,
+            "POST",
+            format!("/guilds/{guild_id}/members/{user_id}"),
+            Some(&owner.access_token),
+            OWNER_IP,
+            None,
+        )
+        .await;
+        assert_eq!(added.status(), StatusCode::OK);
+    }
+    let promoted = send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}/members/{moderator_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        Some(json!({"role":"moderator"})),
+    )
+    .await;
+    assert_eq!(promoted.status(), StatusCode::OK);
+    LockGuild {
+        owner,
+        moderator,
+        member,
+        guild_id,
+        channel_id,
+    }
+}
+
+async fn set_locked(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: String,
+    locked: bool,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PATCH",
+        channel_uri,
+        Some(&auth.access_token),
+        ip,
+        Some(json!({"locked":locked})),
+    )
+    .await
+}
+
+async fn member_post_status(app: &axum::Router, guild: &LockGuild) -> StatusCode {
+    send_json(
+        app,
+        "POST",
+        format!("{}/messages", guild.channel_uri()),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        Some(json!({"content":"hello"})),
+    )
+    .await
+    .status()
+}
+
+async fn lock_and_unlock_channel(app: &axum::Router) {
+    let guild = setup_lock_guild(app).await;
+
+    let locked = set_locked(app, &guild.owner, OWNER_IP, guild.channel_uri(), true).await;
+    assert_eq!(locked.status(), StatusCode::OK);
+    let locked: Value = parse_json_body(locked).await;
+    assert_eq!(locked["channel_id"], guild.channel_id.as_str());
+    assert_eq!(locked["locked"], true);
+
+    let listed = send_json(
+        app,
+        "GET",
+        format!("/guilds/{}/channels", guild.guild_id),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    assert_eq!(listed["channels"][0]["locked"], true);
+
+    assert_eq!(member_post_status(app, &guild).await, StatusCode::FORBIDDEN);
+    for (auth, ip) in [(&guild.moderator, MODERATOR_IP), (&guild.owner, OWNER_IP)] {
+        post_message(app, auth, ip, &guild.guild_id, &guild.channel_id, "hello").await;
+    }
+    let history = send_json(
+        app,
+        "GET",
+        format!("{}/messages", guild.channel_uri()),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(history.status(), StatusCode::OK);
+
+    let unlocked = set_locked(
+        app,
+        &guild.moderator,
+        MODERATOR_IP,
+        guild.channel_uri(),
+        false,
+    )
+    .await;
+    assert_eq!(unlocked.status(), StatusCode::OK);
+    assert_eq!(member_post_status(app, &guild).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn locked_channels_admit_only_moderators_until_unlocked() {
+    lock_and_unlock_channel(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_locked_channels_admit_only_moderators_until_unlocked() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    lock_and_unlock_channel(&app).await;
+}
+
+#[tokio::test]
+async fn channel_lock_requires_moderator_and_existing_channel() {
+    let app = in_memory_app();
+    let guild = setup_lock_guild(&app).await;
+
+    let forbidden = set_locked(&app, &guild.member, MEMBER_IP, guild.channel_uri(), true).await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    let missing = set_locked(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        format!("/guilds/{}/channels/{}", guild.guild_id, Ulid::new()),
+        true,
+    )
+    .await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(member_post_status(&app, &guild).await, StatusCode::OK);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+async fn set_retention(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    retention_secs: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PATCH",
+        format!("{channel_uri}/retention"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({"retention_secs":retention_secs})),
+    )
+    .await
+}
+
+async fn listed_retention(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+) -> Value {
+    let listed = send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    listed["channels"][0]["retention_secs"].clone()
+}
+
+const OWNER_IP: &str = "203.0.113.211";
+const MEMBER_IP: &str = "203.0.113.212";
+
+struct RetentionGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    guild_id: String,
+    channel_id: String,
+    channel_uri: String,
+}
+
+async fn setup_retention_guild(app: &axum::Router) -> RetentionGuild {
+    let owner = register_and_login(app, "ret_owner", OWNER_IP).await;
+    let member = register_and_login(app, "ret_member", MEMBER_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, OWNER_IP, "Retention Guild", "retention-chat").await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+    let channel_uri = format!("/guilds/{guild_id}/channels/{channel_id}");
+    RetentionGuild {
+        owner,
+        member,
+        guild_id,
+        channel_id,
+        channel_uri,
+    }
+}
+
+async fn set_and_clear_retention(app: &axum::Router) {
+    let guild = setup_retention_guild(app).await;
+    assert_eq!(
+        listed_retention(app, &guild.owner, OWNER_IP, &guild.guild_id).await,
+        Value::Null
+    );
+
+    let updated = set_retention(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.channel_uri,
+        json!(86_400),
+    )
+    .await;
+    assert_eq!(updated.status(), StatusCode::OK);
+    let updated: Value = parse_json_body(updated).await;
+    assert_eq!(updated["channel_id"], guild.channel_id.as_str());
+    assert_eq!(updated["retention_secs"], 86_400);
+    assert_eq!(updated["locked"], false);
+    assert_eq!(
+        listed_retention(app, &guild.owner, OWNER_IP, &guild.guild_id).await,
+        86_400
+    );
+    post_message(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &guild.channel_id,
+        "fresh message",
+    )
+    .await;
+
+    let cleared = set_retention(app, &guild.owner, OWNER_IP, &guild.channel_uri, Value::Null).await;
+    assert_eq!(cleared.status(), StatusCode::OK);
+    let cleared: Value = parse_json_body(cleared).await;
+    assert_eq!(cleared["retention_secs"], Value::Null);
+    assert_eq!(
+        listed_retention(app, &guild.owner, OWNER_IP, &guild.guild_id).await,
+        Value::Null
+    );
+}
+
+#[tokio::test]
+async fn channel_retention_can_be_set_and_cleared() {
+    set_and_clear_retention(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_channel_retention_can_be_set_and_cleared() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    set_and_clear_retention(&app).await;
+}
+
+#[tokio::test]
+async fn channel_retention_rejects_members_bad_windows_and_unknown_channels() {
+    let app = in_memory_app();
+    let guild = setup_retention_guild(&app).await;
+
+    let forbidden = set_retention(
+        &app,
+        &guild.member,
+        MEMBER_IP,
+        &guild.channel_uri,
+        json!(3600),
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    for out_of_range in [json!(0), json!(59), json!(315_360_001)] {
+        let rejected = set_retention(
+            &app,
+            &guild.owner,
+            OWNER_IP,
+            &guild.channel_uri,
+            out_of_range,
+        )
+        .await;
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    }
+    let missing = set_retention(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &format!("/guilds/{}/channels/{}", guild.guild_id, Ulid::new()),
+        json!(3600),
+    )
+    .await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        listed_retention(&app, &guild.owner, OWNER_IP, &guild.guild_id).await,
+        Value::Null
+    );
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, postgres_app, register_and_login,
+    send_json, user_id, AuthResponse,
+};
+
+async fn list_overrides(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    overrides_uri: &str,
+) -> Value {
+    let listed = send_json(
+        app,
+        "GET",
+        overrides_uri.to_owned(),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    parse_json_body(listed).await
+}
+
+const OWNER_IP: &str = "203.0.113.251";
+const MEMBER_IP: &str = "203.0.113.252";
+
+struct OverrideGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    guild_id: String,
+    overrides_uri: String,
+}
+
+async fn setup_override_guild(app: &axum::Router) -> OverrideGuild {
+    let owner = register_and_login(app, "override_owner", OWNER_IP).await;
+    let member = register_and_login(app, "override_member", MEMBER_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, OWNER_IP, "Override Guild", "override-chat").await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+    let overrides_uri = format!("/guilds/{guild_id}/channels/{channel_id}/overrides");
+    OverrideGuild {
+        owner,
+        member,
+        guild_id,
+        overrides_uri,
+    }
+}
+
+async fn deny_posting(app: &axum::Router, guild: &OverrideGuild, role: &str) {
+    let denied = send_json(
+        app,
+        "POST",
+        format!("{}/{role}", guild.overrides_uri),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        Some(json!({"allow":[],"deny":["create_message"]})),
+    )
+    .await;
+    assert_eq!(denied.status(), StatusCode::OK);
+}
+
+async fn delete_member_override(
+    app: &axum::Router,
+    guild: &OverrideGuild,
+    auth: &AuthResponse,
+    ip: &str,
+) -> StatusCode {
+    send_json(
+        app,
+        "DELETE",
+        format!("{}/member", guild.overrides_uri),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await
+    .status()
+}
+
+async fn delete_override(app: &axum::Router) {
+    let guild = setup_override_guild(app).await;
+    deny_posting(app, &guild, "member").await;
+    assert_eq!(
+        list_overrides(app, &guild.owner, OWNER_IP, &guild.overrides_uri).await,
+        json!({"overrides": [{"role": "member", "allow": [], "deny": ["create_message"]}]})
+    );
+
+    assert_eq!(
+        delete_member_override(app, &guild, &guild.owner, OWNER_IP).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        list_overrides(app, &guild.owner, OWNER_IP, &guild.overrides_uri).await,
+        json!({"overrides": []})
+    );
+    assert_eq!(
+        delete_member_override(app, &guild, &guild.owner, OWNER_IP).await,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn channel_role_overrides_can_be_deleted_once() {
+    delete_override(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_channel_role_overrides_can_be_deleted_once() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    delete_override(&app).await;
+}
+
+#[tokio::test]
+async fn members_cannot_delete_channel_role_overrides() {
+    let app = in_memory_app();
+    let guild = setup_override_guild(&app).await;
+    deny_posting(&app, &guild, "member").await;
+
+    assert_eq!(
+        delete_member_override(&app, &guild, &guild.member, MEMBER_IP).await,
+        StatusCode::FORBIDDEN
+    );
+    let listed = list_overrides(&app, &guild.owner, OWNER_IP, &guild.overrides_uri).await;
+    assert_eq!(listed["overrides"].as_array().unwrap().len(), 1);
+}
+
+async fn reset_overrides(app: &axum::Router) {
+    let guild = setup_override_guild(app).await;
+    for role in ["member", "moderator"] {
+        deny_posting(app, &guild, role).await;
+    }
+
+    let reset = send_json(
+        app,
+        "POST",
+        format!("/guilds/{}/permissions/reset", guild.guild_id),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(reset.status(), StatusCode::OK);
+    let reset: Value = parse_json_body(reset).await;
+    assert_eq!(reset, json!({"cleared": 2}));
+    assert_eq!(
+        list_overrides(app, &guild.owner, OWNER_IP, &guild.overrides_uri).await,
+        json!({"overrides": []})
+    );
+}
+
+#[tokio::test]
+async fn owners_can_reset_every_channel_role_override() {
+    reset_overrides(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_owners_can_reset_every_channel_role_override() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    reset_overrides(&app).await;
+}
+
+#[tokio::test]
+async fn members_cannot_reset_channel_role_overrides() {
+    let app = in_memory_app();
+    let guild = setup_override_guild(&app).await;
+    deny_posting(&app, &guild, "member").await;
+
+    let member_reset = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{}/permissions/reset", guild.guild_id),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(member_reset.status(), StatusCode::FORBIDDEN);
+    let listed = list_overrides(&app, &guild.owner, OWNER_IP, &guild.overrides_uri).await;
+    assert_eq!(listed["overrides"].as_array().unwrap().len(), 1);
+}
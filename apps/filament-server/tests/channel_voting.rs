@@ -0,0 +1,297 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+async fn set_voting(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    voting: bool,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PATCH",
+        format!("{channel_uri}/voting"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({"voting":voting})),
+    )
+    .await
+}
+
+async fn react(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    message_id: &str,
+    emoji: &str,
+) {
+    let reacted = send_json(
+        app,
+        "POST",
+        format!("{channel_uri}/messages/{message_id}/reactions/{emoji}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(reacted.status(), StatusCode::OK);
+}
+
+async fn history(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    query: &str,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "GET",
+        format!("{channel_uri}/messages{query}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await
+}
+
+fn ids_and_scores(history: &Value) -> Vec<(String, Value)> {
+    history["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| {
+            (
+                message["message_id"].as_str().unwrap().to_owned(),
+                message.get("score").cloned().unwrap_or(Value::Null),
+            )
+        })
+        .collect()
+}
+
+const UP: &str = "%F0%9F%91%8D";
+const DOWN: &str = "%F0%9F%91%8E";
+const SMILE: &str = "%F0%9F%98%80";
+
+const OWNER_IP: &str = "203.0.113.221";
+const FIRST_IP: &str = "203.0.113.222";
+const SECOND_IP: &str = "203.0.113.223";
+
+struct VotingGuild {
+    owner: AuthResponse,
+    first: AuthResponse,
+    guild_id: String,
+    channel_uri: String,
+    top: String,
+    worst: String,
+    even: String,
+}
+
+/// Posts three answers that net +2, -1, and 0 votes; the smile on the last
+/// one is not a vote.
+async fn setup_voting_guild(app: &axum::Router) -> VotingGuild {
+    let owner = register_and_login(app, "vote_owner", OWNER_IP).await;
+    let first = register_and_login(app, "vote_first", FIRST_IP).await;
+    let second = register_and_login(app, "vote_second", SECOND_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, OWNER_IP, "Voting Guild", "questions").await;
+    for (member, ip) in [(&first, FIRST_IP), (&second, SECOND_IP)] {
+        let member_user_id = user_id(app, member, ip).await;
+        let added = send_json(
+            app,
+            "POST",
+            format!("/guilds/{guild_id}/members/{member_user_id}"),
+            Some(&owner.access_token),
+            OWNER_IP,
+            None,
+        )
+        .await;
+        assert_eq!(added.status(), StatusCode::OK);
+    }
+    let channel_uri = format!("/guilds/{guild_id}/channels/{channel_id}");
+
+    let top = post_message(app, &owner, OWNER_IP, &guild_id, &channel_id, "best answer").await;
+    let worst = post_message(
+        app,
+        &owner,
+        OWNER_IP,
+        &guild_id,
+        &channel_id,
+        "wrong answer",
+    )
+    .await;
+    let even = post_message(
+        app,
+        &owner,
+        OWNER_IP,
+        &guild_id,
+        &channel_id,
+        "so-so answer",
+    )
+    .await;
+    react(app, &first, FIRST_IP, &channel_uri, &top, UP).await;
+    react(app, &second, SECOND_IP, &channel_uri, &top, UP).await;
+    react(app, &first, FIRST_IP, &channel_uri, &worst, DOWN).await;
+    react(app, &owner, OWNER_IP, &channel_uri, &even, UP).await;
+    react(app, &first, FIRST_IP, &channel_uri, &even, DOWN).await;
+    react(app, &second, SECOND_IP, &channel_uri, &even, SMILE).await;
+    VotingGuild {
+        owner,
+        first,
+        guild_id,
+        channel_uri,
+        top,
+        worst,
+        even,
+    }
+}
+
+async fn enable_voting(app: &axum::Router, guild: &VotingGuild) {
+    let enabled = set_voting(app, &guild.owner, OWNER_IP, &guild.channel_uri, true).await;
+    assert_eq!(enabled.status(), StatusCode::OK);
+    let enabled: Value = parse_json_body(enabled).await;
+    assert_eq!(enabled["voting"], true);
+    assert_eq!(enabled["locked"], false);
+}
+
+async fn member_history(app: &axum::Router, guild: &VotingGuild, query: &str) -> Value {
+    let response = history(app, &guild.first, FIRST_IP, &guild.channel_uri, query).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    parse_json_body(response).await
+}
+
+fn assert_unscored(history: &Value) {
+    assert!(ids_and_scores(history)
+        .iter()
+        .all(|(_, score)| score.is_null()));
+}
+
+async fn score_recent_history(app: &axum::Router) {
+    let guild = setup_voting_guild(app).await;
+    enable_voting(app, &guild).await;
+
+    let listed = send_json(
+        app,
+        "GET",
+        format!("/guilds/{}/channels", guild.guild_id),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    assert_eq!(listed["channels"][0]["voting"], true);
+
+    let recent = member_history(app, &guild, "").await;
+    assert_eq!(
+        ids_and_scores(&recent),
+        vec![
+            (guild.even.clone(), json!(0)),
+            (guild.worst.clone(), json!(-1)),
+            (guild.top.clone(), json!(2)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn voting_channels_score_messages_by_up_and_down_reactions() {
+    score_recent_history(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_voting_channels_score_messages_by_up_and_down_reactions() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    score_recent_history(&app).await;
+}
+
+async fn rank_history_by_score(app: &axum::Router) {
+    let guild = setup_voting_guild(app).await;
+    enable_voting(app, &guild).await;
+
+    let ranked = member_history(app, &guild, "?sort=score").await;
+    assert_eq!(
+        ids_and_scores(&ranked),
+        vec![
+            (guild.top.clone(), json!(2)),
+            (guild.even.clone(), json!(0)),
+            (guild.worst.clone(), json!(-1)),
+        ]
+    );
+    assert_eq!(ranked["next_before"], guild.top.as_str());
+    let page = member_history(app, &guild, "?sort=score&limit=2").await;
+    assert_eq!(
+        ids_and_scores(&page),
+        vec![
+            (guild.even.clone(), json!(0)),
+            (guild.worst.clone(), json!(-1))
+        ]
+    );
+    assert_eq!(page["next_before"], guild.worst.as_str());
+}
+
+#[tokio::test]
+async fn score_sorted_history_ranks_recent_messages_by_score() {
+    rank_history_by_score(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_score_sorted_history_ranks_recent_messages_by_score() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    rank_history_by_score(&app).await;
+}
+
+#[tokio::test]
+async fn channels_without_voting_hide_scores_and_refuse_score_sorting() {
+    let app = in_memory_app();
+    let guild = setup_voting_guild(&app).await;
+
+    assert_unscored(&member_history(&app, &guild, "").await);
+    let unsorted = history(
+        &app,
+        &guild.first,
+        FIRST_IP,
+        &guild.channel_uri,
+        "?sort=score",
+    )
+    .await;
+    assert_eq!(unsorted.status(), StatusCode::BAD_REQUEST);
+
+    enable_voting(&app, &guild).await;
+    let disabled = set_voting(&app, &guild.owner, OWNER_IP, &guild.channel_uri, false).await;
+    assert_eq!(disabled.status(), StatusCode::OK);
+    assert_unscored(&member_history(&app, &guild, "").await);
+}
+
+#[tokio::test]
+async fn voting_toggle_requires_a_moderator_and_an_existing_channel() {
+    let app = in_memory_app();
+    let guild = setup_voting_guild(&app).await;
+
+    let forbidden = set_voting(&app, &guild.first, FIRST_IP, &guild.channel_uri, true).await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    let missing = set_voting(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &format!("/guilds/{}/channels/{}", guild.guild_id, Ulid::new()),
+        true,
+    )
+    .await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_unscored(&member_history(&app, &guild, "").await);
+}
@@ -0,0 +1,216 @@
+//! Fixtures shared by the integration test binaries. Each binary compiles
+//! its own copy and uses a different subset, hence the `dead_code` allowance.
+#![allow(dead_code)]
+
+use std::{env, path::PathBuf, time::Duration};
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use filament_server::{build_router, build_router_with_db_bootstrap, AppConfig};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use ulid::Ulid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AuthResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct MeResponse {
+    user_id: String,
+}
+
+/// Limits loose enough that a whole matrix of requests from one binary stays
+/// under the rate limiters.
+pub fn test_config() -> AppConfig {
+    AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 500,
+        auth_route_requests_per_minute: 200,
+        ..AppConfig::default()
+    }
+}
+
+/// A fresh attachment directory per app, so binaries never share files.
+pub fn attachment_root(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("filament-test-{name}-{}", Ulid::new()))
+}
+
+pub fn in_memory_app() -> axum::Router {
+    in_memory_app_with(&test_config())
+}
+
+pub fn in_memory_app_with(config: &AppConfig) -> axum::Router {
+    build_router(config).expect("router should build")
+}
+
+pub async fn postgres_app() -> Option<axum::Router> {
+    postgres_app_with(test_config()).await
+}
+
+/// Builds a Postgres-backed app from `config`, or returns `None` so the
+/// caller can skip when `FILAMENT_TEST_DATABASE_URL` is unset.
+pub async fn postgres_app_with(config: AppConfig) -> Option<axum::Router> {
+    let Ok(database_url) = env::var("FILAMENT_TEST_DATABASE_URL") else {
+        eprintln!("skipping postgres-backed cases: FILAMENT_TEST_DATABASE_URL is unset");
+        return None;
+    };
+    Some(
+        build_router_with_db_bootstrap(&AppConfig {
+            database_url: Some(database_url),
+            ..config
+        })
+        .await
+        .expect("router should build"),
+    )
+}
+
+pub async fn parse_json_body<T: DeserializeOwned>(response: axum::response::Response) -> T {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should be readable");
+    serde_json::from_slice(&body).expect("response body should be valid json")
+}
+
+pub async fn send_json(
+    app: &axum::Router,
+    method: &str,
+    uri: String,
+    access_token: Option<&str>,
+    ip: &str,
+    body: Option<Value>,
+) -> axum::response::Response {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("x-forwarded-for", ip);
+    if let Some(access_token) = access_token {
+        request = request.header("authorization", format!("Bearer {access_token}"));
+    }
+    let body = match body {
+        Some(value) => {
+            request = request.header("content-type", "application/json");
+            Body::from(value.to_string())
+        }
+        None => Body::empty(),
+    };
+    app.clone()
+        .oneshot(request.body(body).expect("request should build"))
+        .await
+        .expect("request should execute")
+}
+
+/// Registers a user named `prefix` plus a random 17-character suffix and
+/// logs them in. Usernames cap at 32 bytes, so keep `prefix` to 15.
+pub async fn register_and_login(app: &axum::Router, prefix: &str, ip: &str) -> AuthResponse {
+    let suffix = Ulid::new().to_string().to_lowercase();
+    let username = format!("{prefix}_{}", &suffix[..16]);
+    let credentials = json!({"username":username,"password":"super-secure-password"});
+    let register = send_json(
+        app,
+        "POST",
+        String::from("/auth/register"),
+        None,
+        ip,
+        Some(credentials.clone()),
+    )
+    .await;
+    assert_eq!(register.status(), StatusCode::OK);
+    let login = send_json(
+        app,
+        "POST",
+        String::from("/auth/login"),
+        None,
+        ip,
+        Some(credentials),
+    )
+    .await;
+    assert_eq!(login.status(), StatusCode::OK);
+    parse_json_body(login).await
+}
+
+pub async fn user_id(app: &axum::Router, auth: &AuthResponse, ip: &str) -> String {
+    let me = send_json(
+        app,
+        "GET",
+        String::from("/auth/me"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(me.status(), StatusCode::OK);
+    let me: MeResponse = parse_json_body(me).await;
+    me.user_id
+}
+
+/// Creates a guild from `body` (at least `{"name": ...}`) and returns its id.
+pub async fn create_guild(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    body: Value,
+) -> String {
+    let guild = send_json(
+        app,
+        "POST",
+        String::from("/guilds"),
+        Some(&owner.access_token),
+        ip,
+        Some(body),
+    )
+    .await;
+    assert_eq!(guild.status(), StatusCode::OK);
+    let guild_json: Value = parse_json_body(guild).await;
+    guild_json["guild_id"].as_str().unwrap().to_owned()
+}
+
+pub async fn create_guild_with_channel(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    guild_name: &str,
+    channel_name: &str,
+) -> (String, String) {
+    let guild_id = create_guild(app, owner, ip, json!({"name":guild_name})).await;
+    let channel = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        ip,
+        Some(json!({"name":channel_name})),
+    )
+    .await;
+    assert_eq!(channel.status(), StatusCode::OK);
+    let channel_json: Value = parse_json_body(channel).await;
+    let channel_id = channel_json["channel_id"].as_str().unwrap().to_owned();
+    (guild_id, channel_id)
+}
+
+/// Posts `content` to a channel, asserts it was accepted, and returns the
+/// new message id.
+pub async fn post_message(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    channel_id: &str,
+    content: &str,
+) -> String {
+    let posted = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels/{channel_id}/messages"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({"content":content})),
+    )
+    .await;
+    assert_eq!(posted.status(), StatusCode::OK);
+    let posted_json: Value = parse_json_body(posted).await;
+    posted_json["message_id"].as_str().unwrap().to_owned()
+}
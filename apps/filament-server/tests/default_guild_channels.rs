@@ -0,0 +1,165 @@
+mod common;
+
+use axum::http::StatusCode;
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+
+use common::{
+    in_memory_app_with, parse_json_body, post_message, postgres_app_with, register_and_login,
+    send_json, test_config, AuthResponse,
+};
+
+fn defaults_config() -> AppConfig {
+    AppConfig {
+        create_default_guild_channels: true,
+        create_default_guild_voice_channel: true,
+        ..test_config()
+    }
+}
+
+/// Creates a guild and returns the full response, which is the only place
+/// the default channels are reported.
+async fn create_guild_json(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    body: Value,
+) -> Value {
+    let guild = send_json(
+        app,
+        "POST",
+        String::from("/guilds"),
+        Some(&owner.access_token),
+        ip,
+        Some(body),
+    )
+    .await;
+    assert_eq!(guild.status(), StatusCode::OK);
+    parse_json_body(guild).await
+}
+
+async fn listed_channels(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+) -> Value {
+    let listed = send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    listed["channels"].clone()
+}
+
+fn names_and_kinds(channels: &Value) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = channels
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|channel| {
+            (
+                channel["name"].as_str().unwrap().to_owned(),
+                channel["kind"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+async fn create_configured_defaults(app: &axum::Router) {
+    let ip = "203.0.113.231";
+    let owner = register_and_login(app, "defaults", ip).await;
+
+    let guild = create_guild_json(app, &owner, ip, json!({"name":"Ready Guild"})).await;
+    let guild_id = guild["guild_id"].as_str().unwrap().to_owned();
+    let expected = vec![
+        (String::from("general"), String::from("text")),
+        (String::from("voice"), String::from("voice")),
+    ];
+    assert_eq!(names_and_kinds(&guild["channels"]), expected);
+    let listed = listed_channels(app, &owner, ip, &guild_id).await;
+    assert_eq!(names_and_kinds(&listed), expected);
+
+    let general_id = guild["channels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|channel| channel["name"] == "general")
+        .and_then(|channel| channel["channel_id"].as_str())
+        .unwrap()
+        .to_owned();
+    post_message(app, &owner, ip, &guild_id, &general_id, "hello").await;
+}
+
+#[tokio::test]
+async fn configured_defaults_give_new_guilds_general_and_voice_channels() {
+    create_configured_defaults(&in_memory_app_with(&defaults_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_configured_defaults_give_new_guilds_general_and_voice_channels() {
+    let Some(app) = postgres_app_with(defaults_config()).await else {
+        return;
+    };
+    create_configured_defaults(&app).await;
+}
+
+#[tokio::test]
+async fn guilds_can_opt_out_of_configured_defaults() {
+    let app = in_memory_app_with(&defaults_config());
+    let ip = "203.0.113.233";
+    let owner = register_and_login(&app, "bare", ip).await;
+
+    let bare = create_guild_json(
+        &app,
+        &owner,
+        ip,
+        json!({"name":"Bare Guild","default_channels":false}),
+    )
+    .await;
+    assert!(bare.get("channels").is_none());
+    let bare_id = bare["guild_id"].as_str().unwrap();
+    assert_eq!(listed_channels(&app, &owner, ip, bare_id).await, json!([]));
+}
+
+async fn request_defaults(app: &axum::Router) {
+    let ip = "203.0.113.232";
+    let owner = register_and_login(app, "requested", ip).await;
+
+    let plain = create_guild_json(app, &owner, ip, json!({"name":"Plain Guild"})).await;
+    assert!(plain.get("channels").is_none());
+
+    let requested = create_guild_json(
+        app,
+        &owner,
+        ip,
+        json!({"name":"Asked Guild","default_channels":true}),
+    )
+    .await;
+    assert_eq!(
+        names_and_kinds(&requested["channels"]),
+        vec![(String::from("general"), String::from("text"))]
+    );
+    assert_eq!(requested["channels"][0]["locked"], false);
+}
+
+#[tokio::test]
+async fn guilds_can_request_a_general_channel_when_defaults_are_off() {
+    request_defaults(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_guilds_can_request_a_general_channel_when_defaults_are_off() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    request_defaults(&app).await;
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use axum::http::StatusCode;
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+
+use common::{
+    in_memory_app_with, parse_json_body, postgres_app_with, register_and_login, send_json, user_id,
+    AuthResponse,
+};
+
+struct TestUser {
+    auth: AuthResponse,
+    user_id: String,
+}
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        max_friends_per_user: 1,
+        max_incoming_friend_requests_per_user: 2,
+        ..common::test_config()
+    }
+}
+
+async fn register_user(app: &axum::Router, prefix: &str, ip: &str) -> TestUser {
+    let auth = register_and_login(app, prefix, ip).await;
+    let user_id = user_id(app, &auth, ip).await;
+    TestUser { auth, user_id }
+}
+
+async fn request_friendship(
+    app: &axum::Router,
+    sender: &TestUser,
+    recipient: &TestUser,
+    ip: &str,
+) -> String {
+    let response = send_json(
+        app,
+        "POST",
+        String::from("/friends/requests"),
+        Some(&sender.auth.access_token),
+        ip,
+        Some(json!({"recipient_user_id": recipient.user_id})),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = parse_json_body(response).await;
+    body["request_id"].as_str().unwrap().to_owned()
+}
+
+async fn accept(
+    app: &axum::Router,
+    recipient: &TestUser,
+    ip: &str,
+    request_id: &str,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        format!("/friends/requests/{request_id}/accept"),
+        Some(&recipient.auth.access_token),
+        ip,
+        None,
+    )
+    .await
+}
+
+struct CappedFriends {
+    alice: TestUser,
+    bob: TestUser,
+    carol: TestUser,
+}
+
+/// Registers three users and makes the first two friends, filling both of
+/// their one-friend caps.
+async fn setup_capped_friends(app: &axum::Router, ip: &str) -> CappedFriends {
+    let alice = register_user(app, "fl_alice", ip).await;
+    let bob = register_user(app, "fl_bob", ip).await;
+    let carol = register_user(app, "fl_carol", ip).await;
+    let first = request_friendship(app, &alice, &bob, ip).await;
+    assert_eq!(accept(app, &bob, ip, &first).await.status(), StatusCode::OK);
+    CappedFriends { alice, bob, carol }
+}
+
+async fn accept_past_friend_caps(app: &axum::Router) {
+    let ip = "203.0.113.214";
+    let CappedFriends { alice, bob, carol } = setup_capped_friends(app, ip).await;
+
+    let over_sender_cap = request_friendship(app, &carol, &alice, ip).await;
+    let rejected = accept(app, &alice, ip, &over_sender_cap).await;
+    assert_eq!(rejected.status(), StatusCode::FORBIDDEN);
+    let rejected: Value = parse_json_body(rejected).await;
+    assert_eq!(rejected["error"], "friend_limit_reached");
+
+    let over_recipient_cap = request_friendship(app, &carol, &bob, ip).await;
+    let rejected = accept(app, &bob, ip, &over_recipient_cap).await;
+    assert_eq!(rejected.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn accepting_past_either_users_friend_cap_is_rejected() {
+    accept_past_friend_caps(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_accepting_past_either_users_friend_cap_is_rejected() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    accept_past_friend_caps(&app).await;
+}
+
+#[tokio::test]
+async fn rejected_accepts_leave_the_request_pending() {
+    let app = in_memory_app_with(&test_config());
+    let ip = "203.0.113.215";
+    let CappedFriends { alice, carol, .. } = setup_capped_friends(&app, ip).await;
+    let request_id = request_friendship(&app, &carol, &alice, ip).await;
+    assert_eq!(
+        accept(&app, &alice, ip, &request_id).await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    let relationship = send_json(
+        &app,
+        "GET",
+        format!("/users/{}/relationship", carol.user_id),
+        Some(&alice.auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(relationship.status(), StatusCode::OK);
+    let relationship: Value = parse_json_body(relationship).await;
+    assert_eq!(relationship["relationship"], "incoming_request");
+    assert_eq!(relationship["request_id"], request_id.as_str());
+
+    let pending = send_json(
+        &app,
+        "GET",
+        String::from("/friends/requests"),
+        Some(&alice.auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(pending.status(), StatusCode::OK);
+    let pending: Value = parse_json_body(pending).await;
+    assert_eq!(pending["incoming"][0]["request_id"], request_id.as_str());
+}
+
+#[tokio::test]
+async fn removing_a_friend_frees_room_under_the_cap() {
+    let app = in_memory_app_with(&test_config());
+    let ip = "203.0.113.216";
+    let CappedFriends { alice, bob, carol } = setup_capped_friends(&app, ip).await;
+    let request_id = request_friendship(&app, &carol, &alice, ip).await;
+    assert_eq!(
+        accept(&app, &alice, ip, &request_id).await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    let removed = send_json(
+        &app,
+        "DELETE",
+        format!("/friends/{}", bob.user_id),
+        Some(&alice.auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(removed.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        accept(&app, &alice, ip, &request_id).await.status(),
+        StatusCode::OK
+    );
+}
+
+struct CrowdedInbox {
+    target: TestUser,
+    second: TestUser,
+    third: TestUser,
+    cancellable_request_id: String,
+}
+
+/// Fills `target`'s two-request incoming cap from two senders.
+async fn setup_crowded_inbox(app: &axum::Router, ip: &str) -> CrowdedInbox {
+    let target = register_user(app, "fl_target", ip).await;
+    let first = register_user(app, "fl_first", ip).await;
+    let second = register_user(app, "fl_second", ip).await;
+    let third = register_user(app, "fl_third", ip).await;
+    request_friendship(app, &first, &target, ip).await;
+    let cancellable_request_id = request_friendship(app, &second, &target, ip).await;
+    CrowdedInbox {
+        target,
+        second,
+        third,
+        cancellable_request_id,
+    }
+}
+
+async fn request_into_full_inbox(app: &axum::Router) {
+    let ip = "203.0.113.217";
+    let inbox = setup_crowded_inbox(app, ip).await;
+
+    let rejected = send_json(
+        app,
+        "POST",
+        String::from("/friends/requests"),
+        Some(&inbox.third.auth.access_token),
+        ip,
+        Some(json!({"recipient_user_id": inbox.target.user_id})),
+    )
+    .await;
+    assert_eq!(rejected.status(), StatusCode::FORBIDDEN);
+    let rejected: Value = parse_json_body(rejected).await;
+    assert_eq!(rejected["error"], "incoming_friend_request_limit_reached");
+}
+
+#[tokio::test]
+async fn friend_requests_past_the_recipients_incoming_cap_are_rejected() {
+    request_into_full_inbox(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_friend_requests_past_the_recipients_incoming_cap_are_rejected() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    request_into_full_inbox(&app).await;
+}
+
+#[tokio::test]
+async fn users_at_the_incoming_cap_can_still_send_requests() {
+    let app = in_memory_app_with(&test_config());
+    let ip = "203.0.113.218";
+    let inbox = setup_crowded_inbox(&app, ip).await;
+    request_friendship(&app, &inbox.target, &inbox.third, ip).await;
+}
+
+#[tokio::test]
+async fn cancelling_a_pending_request_frees_an_incoming_slot() {
+    let app = in_memory_app_with(&test_config());
+    let ip = "203.0.113.219";
+    let inbox = setup_crowded_inbox(&app, ip).await;
+
+    let cancel = send_json(
+        &app,
+        "DELETE",
+        format!("/friends/requests/{}", inbox.cancellable_request_id),
+        Some(&inbox.second.auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(cancel.status(), StatusCode::NO_CONTENT);
+    request_friendship(&app, &inbox.third, &inbox.target, ip).await;
+}
@@ -173,6 +173,14 @@ fn test_app() -> axum::Router {
 
 fn test_app_with_max_gateway_event_bytes(max_gateway_event_bytes: usize) -> axum::Router {
     build_router(&AppConfig {
+        max_gateway_event_bytes,
+        ..test_app_config()
+    })
+    .expect("router should build")
+}
+
+fn test_app_config() -> AppConfig {
+    AppConfig {
         max_body_bytes: 1024 * 32,
         request_timeout: Duration::from_secs(2),
         rate_limit_requests_per_minute: 200,
@@ -180,10 +188,8 @@ fn test_app_with_max_gateway_event_bytes(max_gateway_event_bytes: usize) -> axum
         gateway_ingress_events_per_window: 20,
         gateway_ingress_window: Duration::from_secs(10),
         gateway_outbound_queue: 256,
-        max_gateway_event_bytes,
         ..AppConfig::default()
-    })
-    .expect("router should build")
+    }
 }
 
 async fn next_text_event(
@@ -538,6 +544,784 @@ async fn websocket_handshake_and_message_flow_work_over_network() {
     server.abort();
 }
 
+#[tokio::test]
+async fn websocket_handshake_authenticates_with_subprotocol_token() {
+    let app = test_app();
+    let auth = register_and_login(&app, "203.0.113.45").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut ws_request = format!("ws://{addr}/gateway/ws")
+        .into_client_request()
+        .expect("websocket request should build");
+    ws_request.headers_mut().insert(
+        "sec-websocket-protocol",
+        http::HeaderValue::from_str(&format!(
+            "filament.gateway.v1, filament.access_token.{}",
+            auth.access_token
+        ))
+        .expect("protocol header should be valid"),
+    );
+    ws_request.headers_mut().insert(
+        "x-forwarded-for",
+        http::HeaderValue::from_static("203.0.113.45"),
+    );
+
+    let (mut socket, response) = connect_async(ws_request)
+        .await
+        .expect("websocket handshake should succeed");
+    assert_eq!(
+        response
+            .headers()
+            .get("sec-websocket-protocol")
+            .and_then(|value| value.to_str().ok()),
+        Some("filament.gateway.v1")
+    );
+    let _ = next_event_of_type(&mut socket, "ready").await;
+
+    let mut bad_request = format!("ws://{addr}/gateway/ws")
+        .into_client_request()
+        .expect("websocket request should build");
+    bad_request.headers_mut().insert(
+        "sec-websocket-protocol",
+        http::HeaderValue::from_static("filament.gateway.v1, filament.access_token.invalid"),
+    );
+    assert!(connect_async(bad_request).await.is_err());
+
+    socket
+        .close(None)
+        .await
+        .expect("socket close should succeed");
+    server.abort();
+}
+
+#[tokio::test]
+async fn websocket_is_closed_for_reauth_after_max_lifetime() {
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        gateway_max_connection_lifetime: Duration::from_secs(1),
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "203.0.113.46").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut ws_request = format!("ws://{addr}/gateway/ws?access_token={}", auth.access_token)
+        .into_client_request()
+        .expect("websocket request should build");
+    ws_request.headers_mut().insert(
+        "x-forwarded-for",
+        http::HeaderValue::from_static("203.0.113.46"),
+    );
+    let (mut socket, _response) = connect_async(ws_request)
+        .await
+        .expect("websocket handshake should succeed");
+    let _ = next_event_of_type(&mut socket, "ready").await;
+
+    let close_frame = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Close(frame))) => return frame,
+                Some(Ok(_)) => {}
+                other => panic!("expected close frame, got {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("server should close the connection")
+    .expect("close frame should carry a reason");
+    assert_eq!(u16::from(close_frame.code), 4001);
+    assert_eq!(close_frame.reason.as_str(), "reauth_required");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn websocket_upgrade_is_rejected_past_per_user_connection_cap() {
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        gateway_max_connections_per_user: 1,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "203.0.113.47").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let ws_request = || {
+        let mut request = format!("ws://{addr}/gateway/ws?access_token={}", auth.access_token)
+            .into_client_request()
+            .expect("websocket request should build");
+        request.headers_mut().insert(
+            "x-forwarded-for",
+            http::HeaderValue::from_static("203.0.113.47"),
+        );
+        request
+    };
+    let (mut socket, _response) = connect_async(ws_request())
+        .await
+        .expect("first websocket handshake should succeed");
+    let _ = next_event_of_type(&mut socket, "ready").await;
+
+    match connect_async(ws_request()).await {
+        Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status().as_u16(), 429);
+        }
+        other => panic!("expected 429 handshake rejection, got {other:?}"),
+    }
+
+    socket
+        .close(None)
+        .await
+        .expect("socket close should succeed");
+    server.abort();
+}
+
+#[tokio::test]
+async fn invalid_envelope_is_closed_with_structured_close_code() {
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "203.0.113.48").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut ws_request = format!("ws://{addr}/gateway/ws?access_token={}", auth.access_token)
+        .into_client_request()
+        .expect("websocket request should build");
+    ws_request.headers_mut().insert(
+        "x-forwarded-for",
+        http::HeaderValue::from_static("203.0.113.48"),
+    );
+    let (mut socket, _response) = connect_async(ws_request)
+        .await
+        .expect("websocket handshake should succeed");
+    let _ = next_event_of_type(&mut socket, "ready").await;
+
+    socket
+        .send(Message::Text("not json".into()))
+        .await
+        .expect("invalid envelope should send");
+
+    let close_frame = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Close(frame))) => return frame,
+                Some(Ok(_)) => {}
+                other => panic!("expected close frame, got {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("server should close the connection")
+    .expect("close frame should carry a reason");
+    assert_eq!(u16::from(close_frame.code), 4006);
+    assert_eq!(close_frame.reason.as_str(), "invalid_envelope");
+
+    server.abort();
+}
+
+async fn post_channel_message(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel: &ChannelRef,
+    content: &str,
+) {
+    let create_message = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/guilds/{}/channels/{}/messages",
+            channel.guild_id, channel.channel_id
+        ))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", ip)
+        .body(Body::from(json!({ "content": content }).to_string()))
+        .expect("create message request should build");
+    let message_response = app
+        .clone()
+        .oneshot(create_message)
+        .await
+        .expect("create message request should execute");
+    assert_eq!(message_response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn banned_member_subscription_is_dropped_on_revalidation() {
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        gateway_subscription_revalidate_interval: Duration::from_secs(1),
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let ip = "203.0.113.47";
+    let owner = register_and_login_as(&app, "revalidate_owner", ip).await;
+    let member = register_and_login_as(&app, "revalidate_member", ip).await;
+    let member_id = user_id_from_me(&app, &member, ip).await;
+    let channel = create_channel_context(&app, &owner, ip).await;
+    add_member(&app, &owner.access_token, ip, &channel.guild_id, &member_id).await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server_app = app.clone();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, server_app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut ws_request = format!(
+        "ws://{addr}/gateway/ws?access_token={}",
+        member.access_token
+    )
+    .into_client_request()
+    .expect("websocket request should build");
+    ws_request
+        .headers_mut()
+        .insert("x-forwarded-for", http::HeaderValue::from_static(ip));
+    let (mut socket, _response) = connect_async(ws_request)
+        .await
+        .expect("websocket handshake should succeed");
+    let _ = next_event_of_type(&mut socket, "ready").await;
+    subscribe_to_channel(&mut socket, &channel).await;
+
+    post_channel_message(&app, &owner, ip, &channel, "before ban").await;
+    let delivered = next_event_of_type(&mut socket, "message_create").await;
+    assert_eq!(delivered["d"]["content"], "before ban");
+
+    let ban_member = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/guilds/{}/members/{member_id}/ban",
+            channel.guild_id
+        ))
+        .header("authorization", format!("Bearer {}", owner.access_token))
+        .header("x-forwarded-for", ip)
+        .body(Body::empty())
+        .expect("ban member request should build");
+    let ban_member_response = app
+        .clone()
+        .oneshot(ban_member)
+        .await
+        .expect("ban member request should execute");
+    assert_eq!(ban_member_response.status(), StatusCode::OK);
+
+    tokio::time::sleep(Duration::from_millis(2_500)).await;
+    post_channel_message(&app, &owner, ip, &channel, "after ban").await;
+    assert!(
+        maybe_next_event_of_type(&mut socket, "message_create", Duration::from_millis(500))
+            .await
+            .is_none(),
+        "banned member should no longer receive channel messages"
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn admin_can_list_and_force_close_gateway_connections() {
+    const ADMIN_SECRET: &str = "test-admin-secret-0123456789abcdef";
+    let app = build_router(&AppConfig {
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        admin_api_secret: Some(String::from(ADMIN_SECRET)),
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "203.0.113.49").await;
+    let user_id = user_id_from_me(&app, &auth, "203.0.113.49").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server_app = app.clone();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, server_app)
+            .await
+            .expect("server should run without errors");
+    });
+    let mut socket = connect_gateway_socket(addr, &auth, "203.0.113.49").await;
+
+    let admin_request = |method: &str, uri: &str, secret: Option<&str>| {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("x-forwarded-for", "203.0.113.50");
+        if let Some(secret) = secret {
+            builder = builder.header("x-filament-admin-secret", secret);
+        }
+        builder
+            .body(Body::empty())
+            .expect("admin request should build")
+    };
+
+    let unauthorized = app
+        .clone()
+        .oneshot(admin_request(
+            "GET",
+            "/admin/connections",
+            Some("wrong-secret"),
+        ))
+        .await
+        .expect("admin list request should execute");
+    assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+    let listed = app
+        .clone()
+        .oneshot(admin_request(
+            "GET",
+            "/admin/connections",
+            Some(ADMIN_SECRET),
+        ))
+        .await
+        .expect("admin list request should execute");
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    let connections = listed["connections"]
+        .as_array()
+        .expect("connections should be an array");
+    assert_eq!(connections.len(), 1);
+    assert_eq!(connections[0]["user_id"], user_id);
+    let connection_id = connections[0]["connection_id"]
+        .as_str()
+        .expect("connection id should be a string")
+        .to_owned();
+
+    let closed = app
+        .clone()
+        .oneshot(admin_request(
+            "POST",
+            &format!("/admin/connections/{connection_id}/close"),
+            Some(ADMIN_SECRET),
+        ))
+        .await
+        .expect("admin close request should execute");
+    assert_eq!(closed.status(), StatusCode::NO_CONTENT);
+
+    let close_frame = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Close(frame))) => return frame,
+                Some(Ok(_)) => {}
+                other => panic!("expected close frame, got {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("server should close the connection")
+    .expect("close frame should carry a reason");
+    assert_eq!(u16::from(close_frame.code), 4011);
+    assert_eq!(close_frame.reason.as_str(), "admin_closed");
+
+    let unknown = app
+        .clone()
+        .oneshot(admin_request(
+            "POST",
+            &format!("/admin/connections/{}/close", uuid::Uuid::new_v4()),
+            Some(ADMIN_SECRET),
+        ))
+        .await
+        .expect("admin close request should execute");
+    assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn admin_connection_endpoints_are_hidden_without_configured_secret() {
+    let app = test_app();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/admin/connections")
+                .header("x-forwarded-for", "203.0.113.51")
+                .header("x-filament-admin-secret", "anything")
+                .body(Body::empty())
+                .expect("admin request should build"),
+        )
+        .await
+        .expect("admin list request should execute");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+async fn connect_gateway_socket(
+    addr: SocketAddr,
+    auth: &AuthResponse,
+    ip: &'static str,
+) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>> {
+    let mut ws_request = format!("ws://{addr}/gateway/ws?access_token={}", auth.access_token)
+        .into_client_request()
+        .expect("websocket request should build");
+    ws_request
+        .headers_mut()
+        .insert("x-forwarded-for", http::HeaderValue::from_static(ip));
+    let (mut socket, _response) = connect_async(ws_request)
+        .await
+        .expect("websocket handshake should succeed");
+    let _ = next_event_of_type(&mut socket, "ready").await;
+    socket
+}
+
+async fn send_history_request(
+    socket: &mut tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >,
+    payload: Value,
+) {
+    let request = json!({"v": 1, "t": "history_request", "d": payload});
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await
+        .expect("history_request should send");
+}
+
+#[tokio::test]
+async fn history_request_pages_channel_history_over_gateway() {
+    let app = test_app();
+    let ip = "203.0.113.48";
+    let owner = register_and_login_as(&app, "history_owner", ip).await;
+    let outsider = register_and_login_as(&app, "history_outsider", ip).await;
+    let channel = create_channel_context(&app, &owner, ip).await;
+    for content in ["first", "second", "third"] {
+        post_channel_message(&app, &owner, ip, &channel, content).await;
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server_app = app.clone();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, server_app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut socket = connect_gateway_socket(addr, &owner, ip).await;
+    send_history_request(
+        &mut socket,
+        json!({"guild_id": channel.guild_id, "channel_id": channel.channel_id, "limit": 0}),
+    )
+    .await;
+    send_history_request(
+        &mut socket,
+        json!({"guild_id": channel.guild_id, "channel_id": channel.channel_id, "limit": 2}),
+    )
+    .await;
+    let page = next_event_of_type(&mut socket, "history").await;
+    assert_eq!(page["d"]["guild_id"], channel.guild_id);
+    assert_eq!(page["d"]["channel_id"], channel.channel_id);
+    let messages = page["d"]["messages"]
+        .as_array()
+        .expect("history messages should be an array");
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["content"], "third");
+    assert_eq!(messages[1]["content"], "second");
+    assert_eq!(page["d"]["next_before"], messages[1]["message_id"]);
+
+    send_history_request(
+        &mut socket,
+        json!({
+            "guild_id": channel.guild_id,
+            "channel_id": channel.channel_id,
+            "before": page["d"]["next_before"],
+        }),
+    )
+    .await;
+    let older = next_event_of_type(&mut socket, "history").await;
+    let older_messages = older["d"]["messages"]
+        .as_array()
+        .expect("history messages should be an array");
+    assert_eq!(older_messages.len(), 1);
+    assert_eq!(older_messages[0]["content"], "first");
+
+    let mut outsider_socket = connect_gateway_socket(addr, &outsider, ip).await;
+    send_history_request(
+        &mut outsider_socket,
+        json!({"guild_id": channel.guild_id, "channel_id": channel.channel_id}),
+    )
+    .await;
+    assert!(
+        maybe_next_event_of_type(&mut outsider_socket, "history", Duration::from_millis(500))
+            .await
+            .is_none()
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn message_create_is_acknowledged_to_the_sending_connection() {
+    let app = test_app();
+    let ip = "203.0.113.52";
+    let owner = register_and_login_as(&app, "ack_owner", ip).await;
+    let channel = create_channel_context(&app, &owner, ip).await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut sender = connect_gateway_socket(addr, &owner, ip).await;
+    let mut watcher = connect_gateway_socket(addr, &owner, ip).await;
+    subscribe_to_channel(&mut watcher, &channel).await;
+
+    let message_create = json!({
+        "v": 1,
+        "t": "message_create",
+        "d": {
+            "guild_id": channel.guild_id,
+            "channel_id": channel.channel_id,
+            "content": "optimistic hello",
+            "nonce": "draft-1"
+        }
+    });
+    sender
+        .send(Message::Text(message_create.to_string().into()))
+        .await
+        .expect("message create event should send");
+    let ack = next_event_of_type(&mut sender, "message_create_ack").await;
+    assert_eq!(ack["d"]["nonce"], "draft-1");
+    assert_eq!(ack["d"]["content"], "optimistic hello");
+    assert!(ack["d"]["created_at_unix"].as_i64().is_some());
+    let message_id = ack["d"]["message_id"].clone();
+    assert!(message_id.is_string());
+
+    let broadcast = next_event_of_type(&mut watcher, "message_create").await;
+    assert_eq!(broadcast["d"]["message_id"], message_id);
+    assert!(maybe_next_event_of_type(
+        &mut watcher,
+        "message_create_ack",
+        Duration::from_millis(300)
+    )
+    .await
+    .is_none());
+
+    sender
+        .send(Message::Text(message_create.to_string().into()))
+        .await
+        .expect("replayed message create event should send");
+    let replay_ack = next_event_of_type(&mut sender, "message_create_ack").await;
+    assert_eq!(replay_ack["d"]["nonce"], "draft-1");
+    assert_eq!(replay_ack["d"]["message_id"], message_id);
+
+    let without_nonce = json!({
+        "v": 1,
+        "t": "message_create",
+        "d": {
+            "guild_id": channel.guild_id,
+            "channel_id": channel.channel_id,
+            "content": "no nonce"
+        }
+    });
+    sender
+        .send(Message::Text(without_nonce.to_string().into()))
+        .await
+        .expect("message create event should send");
+    let plain_ack = next_event_of_type(&mut sender, "message_create_ack").await;
+    assert!(plain_ack["d"]["nonce"].is_null());
+    assert_eq!(plain_ack["d"]["content"], "no nonce");
+    assert_ne!(plain_ack["d"]["message_id"], message_id);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn message_create_into_a_category_is_rejected() {
+    let app = test_app();
+    let ip = "203.0.113.53";
+    let owner = register_and_login_as(&app, "category_gateway_owner", ip).await;
+    let channel = create_channel_context(&app, &owner, ip).await;
+    let create_category = Request::builder()
+        .method("POST")
+        .uri(format!("/guilds/{}/channels", channel.guild_id))
+        .header("authorization", format!("Bearer {}", owner.access_token))
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", ip)
+        .body(Body::from(
+            json!({"name":"Community","kind":"category"}).to_string(),
+        ))
+        .expect("create category request should build");
+    let response = app
+        .clone()
+        .oneshot(create_category)
+        .await
+        .expect("create category request should execute");
+    assert_eq!(response.status(), StatusCode::OK);
+    let category: Value = parse_json_body(response).await;
+    let category_id = category["channel_id"]
+        .as_str()
+        .expect("category id should exist")
+        .to_owned();
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut socket = connect_gateway_socket(addr, &owner, ip).await;
+    let message_create = json!({
+        "v": 1,
+        "t": "message_create",
+        "d": {
+            "guild_id": channel.guild_id,
+            "channel_id": category_id,
+            "content": "into the category"
+        }
+    });
+    socket
+        .send(Message::Text(message_create.to_string().into()))
+        .await
+        .expect("message create event should send");
+
+    let close_frame = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Close(frame))) => return frame,
+                Some(Ok(_)) => {}
+                other => panic!("expected close frame, got {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("server should close the connection")
+    .expect("close frame should carry a reason");
+    assert_eq!(u16::from(close_frame.code), 4010);
+    assert_eq!(close_frame.reason.as_str(), "message_rejected");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn set_filters_limits_fanout_to_listed_event_types() {
+    let app = test_app();
+    let ip = "203.0.113.49";
+    let owner = register_and_login_as(&app, "filter_owner", ip).await;
+    let channel = create_channel_context(&app, &owner, ip).await;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener addr should be readable");
+    let server_app = app.clone();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, server_app)
+            .await
+            .expect("server should run without errors");
+    });
+
+    let mut filtered = connect_gateway_socket(addr, &owner, ip).await;
+    let mut unfiltered = connect_gateway_socket(addr, &owner, ip).await;
+    let set_filters = json!({"v": 1, "t": "set_filters", "d": {"event_types": ["message_update"]}});
+    filtered
+        .send(Message::Text(set_filters.to_string().into()))
+        .await
+        .expect("set_filters should send");
+    subscribe_to_channel(&mut filtered, &channel).await;
+    subscribe_to_channel(&mut unfiltered, &channel).await;
+
+    post_channel_message(&app, &owner, ip, &channel, "filtered out").await;
+    let delivered = next_event_of_type(&mut unfiltered, "message_create").await;
+    assert_eq!(delivered["d"]["content"], "filtered out");
+    assert!(
+        maybe_next_event_of_type(&mut filtered, "message_create", Duration::from_millis(500))
+            .await
+            .is_none()
+    );
+
+    let clear_filters = json!({"v": 1, "t": "set_filters", "d": {"event_types": null}});
+    filtered
+        .send(Message::Text(clear_filters.to_string().into()))
+        .await
+        .expect("set_filters should send");
+    send_history_request(
+        &mut filtered,
+        json!({"guild_id": channel.guild_id, "channel_id": channel.channel_id, "limit": 1}),
+    )
+    .await;
+    let _ = next_event_of_type(&mut filtered, "history").await;
+
+    post_channel_message(&app, &owner, ip, &channel, "delivered again").await;
+    let delivered = next_event_of_type(&mut filtered, "message_create").await;
+    assert_eq!(delivered["d"]["content"], "delivered again");
+
+    server.abort();
+}
+
 #[tokio::test]
 async fn gateway_ingress_rejections_and_unknown_events_are_counted_in_metrics() {
     let app = test_app();
@@ -2031,7 +2815,12 @@ async fn websocket_voice_participant_sync_repairs_and_disconnect_cleanup() {
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn websocket_profile_and_friendship_events_sync_across_sessions() {
-    let app = test_app();
+    let attachments = tempfile::tempdir().expect("temp dir should be created");
+    let app = build_router(&AppConfig {
+        attachment_root: attachments.path().to_path_buf(),
+        ..test_app_config()
+    })
+    .expect("router should build");
     let app_http = app.clone();
 
     let alice = register_and_login_as(&app_http, "phase5_alice", "203.0.113.120").await;
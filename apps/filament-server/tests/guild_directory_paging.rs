@@ -0,0 +1,226 @@
+mod common;
+
+use axum::http::StatusCode;
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild, in_memory_app_with, parse_json_body, postgres_app_with, register_and_login,
+    send_json, AuthResponse,
+};
+
+const OWNER_IP: &str = "203.0.113.220";
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        max_created_guilds_per_user: 10,
+        ..common::test_config()
+    }
+}
+
+async fn put_tags(app: &axum::Router, auth: &AuthResponse, ip: &str, guild_id: &str, tags: Value) {
+    let response = send_json(
+        app,
+        "PUT",
+        format!("/guilds/{guild_id}/tags"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({ "tags": tags })),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn list_page(app: &axum::Router, auth: &AuthResponse, ip: &str, query: &str) -> Value {
+    let response = send_json(
+        app,
+        "GET",
+        format!("/guilds/public?{query}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    parse_json_body(response).await
+}
+
+fn guild_ids(page: &Value) -> Vec<String> {
+    page["guilds"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|guild| guild["guild_id"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+fn cursor(page: &Value, field: &str) -> String {
+    page[field].as_str().unwrap().to_owned()
+}
+
+struct PagingDirectory {
+    owner: AuthResponse,
+    tag: String,
+}
+
+impl PagingDirectory {
+    async fn page(&self, app: &axum::Router, params: &str) -> Value {
+        list_page(
+            app,
+            &self.owner,
+            OWNER_IP,
+            &format!("tag={}&{params}", self.tag),
+        )
+        .await
+    }
+
+    async fn all_ids(&self, app: &axum::Router) -> Vec<String> {
+        let everything = self.page(app, "limit=50").await;
+        let all_ids = guild_ids(&everything);
+        assert_eq!(all_ids.len(), 5);
+        all_ids
+    }
+}
+
+async fn setup_paging_directory(app: &axum::Router) -> PagingDirectory {
+    let owner = register_and_login(app, "paging_owner", OWNER_IP).await;
+    // The Postgres database is shared between runs, so scope every listing to
+    // a tag only this run's guilds carry.
+    let tag = format!("paging-{}", &Ulid::new().to_string().to_lowercase()[..12]);
+    for (name, visibility) in [
+        ("Paging 0", "public"),
+        ("Paging 1", "public"),
+        ("Paging 2", "public"),
+        ("Paging 3", "public"),
+        ("Paging 4", "public"),
+        ("Paging hidden", "private"),
+    ] {
+        let guild_id = create_guild(
+            app,
+            &owner,
+            OWNER_IP,
+            json!({"name": name, "visibility": visibility}),
+        )
+        .await;
+        put_tags(app, &owner, OWNER_IP, &guild_id, json!([tag])).await;
+    }
+    PagingDirectory { owner, tag }
+}
+
+async fn page_backward_with_before_cursors(app: &axum::Router) {
+    let directory = setup_paging_directory(app).await;
+    let all_ids = directory.all_ids(app).await;
+
+    let first = directory.page(app, "limit=2").await;
+    assert_eq!(guild_ids(&first), all_ids[..2]);
+    assert!(first["next_after"].is_null());
+    let second = directory
+        .page(
+            app,
+            &format!("limit=2&before={}", cursor(&first, "next_before")),
+        )
+        .await;
+    assert_eq!(guild_ids(&second), all_ids[2..4]);
+    let third = directory
+        .page(
+            app,
+            &format!("limit=2&before={}", cursor(&second, "next_before")),
+        )
+        .await;
+    assert_eq!(guild_ids(&third), all_ids[4..]);
+    assert!(third["next_before"].is_null());
+}
+
+async fn page_forward_with_after_cursors(app: &axum::Router) {
+    let directory = setup_paging_directory(app).await;
+    let all_ids = directory.all_ids(app).await;
+
+    let mut last = directory.page(app, "limit=2").await;
+    while let Some(before) = last["next_before"].as_str() {
+        last = directory
+            .page(app, &format!("limit=2&before={before}"))
+            .await;
+    }
+    assert_eq!(guild_ids(&last), all_ids[4..]);
+
+    let back = directory
+        .page(
+            app,
+            &format!("limit=2&after={}", cursor(&last, "next_after")),
+        )
+        .await;
+    assert_eq!(guild_ids(&back), all_ids[2..4]);
+    assert!(!back["next_before"].is_null());
+    let front = directory
+        .page(
+            app,
+            &format!("limit=2&after={}", cursor(&back, "next_after")),
+        )
+        .await;
+    assert_eq!(guild_ids(&front), all_ids[..2]);
+    assert!(front["next_after"].is_null());
+}
+
+#[tokio::test]
+async fn public_guilds_page_backward_with_before_cursors() {
+    page_backward_with_before_cursors(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_public_guilds_page_backward_with_before_cursors() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    page_backward_with_before_cursors(&app).await;
+}
+
+#[tokio::test]
+async fn public_guilds_page_forward_with_after_cursors() {
+    page_forward_with_after_cursors(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_public_guilds_page_forward_with_after_cursors() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    page_forward_with_after_cursors(&app).await;
+}
+
+#[tokio::test]
+async fn a_page_covering_every_public_guild_has_no_cursors() {
+    let app = in_memory_app_with(&test_config());
+    let directory = setup_paging_directory(&app).await;
+
+    let everything = directory.page(&app, "limit=50").await;
+    assert_eq!(guild_ids(&everything).len(), 5);
+    assert!(everything["next_before"].is_null());
+    assert!(everything["next_after"].is_null());
+}
+
+#[tokio::test]
+async fn conflicting_or_malformed_cursors_are_rejected() {
+    let app = in_memory_app_with(&test_config());
+    let directory = setup_paging_directory(&app).await;
+    let first = directory.page(&app, "limit=2").await;
+    let before = cursor(&first, "next_before");
+    let tag = &directory.tag;
+
+    for query in [
+        format!("tag={tag}&before={before}&after={before}"),
+        format!("tag={tag}&before=not-a-cursor"),
+        format!("tag={tag}&after=12_not-a-ulid"),
+    ] {
+        let rejected = send_json(
+            &app,
+            "GET",
+            format!("/guilds/public?{query}"),
+            Some(&directory.owner.access_token),
+            OWNER_IP,
+            None,
+        )
+        .await;
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST, "{query}");
+    }
+}
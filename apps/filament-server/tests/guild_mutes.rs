@@ -0,0 +1,289 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+const OWNER_IP: &str = "203.0.113.191";
+const MODERATOR_IP: &str = "203.0.113.192";
+const MEMBER_IP: &str = "203.0.113.193";
+
+struct MuteGuild {
+    owner: AuthResponse,
+    moderator: AuthResponse,
+    member: AuthResponse,
+    owner_user_id: String,
+    member_user_id: String,
+    guild_id: String,
+    channel_id: String,
+}
+
+impl MuteGuild {
+    fn mute_uri(&self, user_id: &str) -> String {
+        format!("/guilds/{}/members/{user_id}/mute", self.guild_id)
+    }
+
+    fn messages_uri(&self) -> String {
+        format!(
+            "/guilds/{}/channels/{}/messages",
+            self.guild_id, self.channel_id
+        )
+    }
+}
+
+async fn setup_mute_guild(app: &axum::Router) -> MuteGuild {
+    let owner = register_and_login(app, "mute_owner", OWNER_IP).await;
+    let moderator = register_and_login(app, "mute_mod", MODERATOR_IP).await;
+    let member = register_and_login(app, "mute_member", MEMBER_IP).await;
+    let owner_user_id = user_id(app, &owner, OWNER_IP).await;
+    let moderator_user_id = user_id(app, &moderator, MODERATOR_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, OWNER_IP, "Mute Guild", "mute-chat").await;
+    for user_id in [&moderator_user_id, &member_user_id] {
+        let added = send_json(
+            app,
+            "POST",
+            format!("/guilds/{guild_id}/members/{user_id}"),
+            Some(&owner.access_token),
+            OWNER_IP,
+            None,
+        )
+        .await;
+        assert_eq!(added.status(), StatusCode::OK);
+    }
+    let promoted = send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}/members/{moderator_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        Some(json!({"role":"moderator"})),
+    )
+    .await;
+    assert_eq!(promoted.status(), StatusCode::OK);
+    MuteGuild {
+        owner,
+        moderator,
+        member,
+        owner_user_id,
+        member_user_id,
+        guild_id,
+        channel_id,
+    }
+}
+
+async fn member_post_status(app: &axum::Router, guild: &MuteGuild) -> StatusCode {
+    send_json(
+        app,
+        "POST",
+        guild.messages_uri(),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        Some(json!({"content":"hello"})),
+    )
+    .await
+    .status()
+}
+
+async fn mute_then_unmute_member(app: &axum::Router) {
+    let guild = setup_mute_guild(app).await;
+    let message_id = post_message(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &guild.channel_id,
+        "hello",
+    )
+    .await;
+    let reaction_uri = format!(
+        "{}/{message_id}/reactions/%F0%9F%91%8D",
+        guild.messages_uri()
+    );
+
+    let muted = send_json(
+        app,
+        "POST",
+        guild.mute_uri(&guild.member_user_id),
+        Some(&guild.moderator.access_token),
+        MODERATOR_IP,
+        Some(json!({"duration_secs":600})),
+    )
+    .await;
+    assert_eq!(muted.status(), StatusCode::OK);
+    let muted: Value = parse_json_body(muted).await;
+    assert_eq!(muted["user_id"], guild.member_user_id.as_str());
+    assert!(muted["expires_at_unix"].as_i64().unwrap() > 0);
+
+    assert_eq!(member_post_status(app, &guild).await, StatusCode::FORBIDDEN);
+    let blocked_reaction = send_json(
+        app,
+        "POST",
+        reaction_uri.clone(),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(blocked_reaction.status(), StatusCode::FORBIDDEN);
+    let history = send_json(
+        app,
+        "GET",
+        guild.messages_uri(),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(history.status(), StatusCode::OK);
+
+    let unmuted = send_json(
+        app,
+        "DELETE",
+        guild.mute_uri(&guild.member_user_id),
+        Some(&guild.moderator.access_token),
+        MODERATOR_IP,
+        None,
+    )
+    .await;
+    assert_eq!(unmuted.status(), StatusCode::OK);
+    let unmuted_again = send_json(
+        app,
+        "DELETE",
+        guild.mute_uri(&guild.member_user_id),
+        Some(&guild.moderator.access_token),
+        MODERATOR_IP,
+        None,
+    )
+    .await;
+    assert_eq!(unmuted_again.status(), StatusCode::NOT_FOUND);
+    assert_eq!(member_post_status(app, &guild).await, StatusCode::OK);
+    let allowed_reaction = send_json(
+        app,
+        "POST",
+        reaction_uri,
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(allowed_reaction.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn mute_blocks_posting_and_reactions_until_lifted() {
+    mute_then_unmute_member(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_mute_blocks_posting_and_reactions_until_lifted() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    mute_then_unmute_member(&app).await;
+}
+
+#[tokio::test]
+async fn mutes_reject_unauthorized_actors_bad_durations_and_non_members() {
+    let app = in_memory_app();
+    let guild = setup_mute_guild(&app).await;
+    let outsider = register_and_login(&app, "mute_out", MEMBER_IP).await;
+    let outsider_user_id = user_id(&app, &outsider, MEMBER_IP).await;
+
+    for (auth, ip, target, body, expected) in [
+        (
+            &guild.member,
+            MEMBER_IP,
+            &guild.owner_user_id,
+            json!({"duration_secs":600}),
+            StatusCode::FORBIDDEN,
+        ),
+        (
+            &guild.moderator,
+            MODERATOR_IP,
+            &guild.owner_user_id,
+            json!({"duration_secs":600}),
+            StatusCode::FORBIDDEN,
+        ),
+        (
+            &guild.moderator,
+            MODERATOR_IP,
+            &guild.member_user_id,
+            json!({"duration_secs":0}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            &guild.moderator,
+            MODERATOR_IP,
+            &guild.member_user_id,
+            json!({"duration_secs":60 * 24 * 60 * 60}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            &guild.moderator,
+            MODERATOR_IP,
+            &outsider_user_id,
+            json!({"duration_secs":600}),
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let response = send_json(
+            &app,
+            "POST",
+            guild.mute_uri(target),
+            Some(&auth.access_token),
+            ip,
+            Some(body),
+        )
+        .await;
+        assert_eq!(response.status(), expected, "muting {target}");
+    }
+    assert_eq!(member_post_status(&app, &guild).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn mute_and_unmute_are_recorded_in_the_audit_log() {
+    let app = in_memory_app();
+    let guild = setup_mute_guild(&app).await;
+    for method in ["POST", "DELETE"] {
+        let response = send_json(
+            &app,
+            method,
+            guild.mute_uri(&guild.member_user_id),
+            Some(&guild.moderator.access_token),
+            MODERATOR_IP,
+            (method == "POST").then(|| json!({"duration_secs":600})),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let audit = send_json(
+        &app,
+        "GET",
+        format!(
+            "/guilds/{}/audit?action_prefix=moderation.&limit=10",
+            guild.guild_id
+        ),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(audit.status(), StatusCode::OK);
+    let audit: Value = parse_json_body(audit).await;
+    let mut actions: Vec<&str> = audit["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|event| event["target_user_id"] == guild.member_user_id.as_str())
+        .map(|event| event["action"].as_str().unwrap())
+        .collect();
+    actions.sort_unstable();
+    assert_eq!(actions, ["moderation.mute", "moderation.unmute"]);
+}
@@ -0,0 +1,370 @@
+mod common;
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use ulid::Ulid;
+
+use common::{
+    attachment_root, create_guild_with_channel, in_memory_app_with, parse_json_body,
+    postgres_app_with, register_and_login, send_json, user_id, AuthResponse,
+};
+
+const GIF_1X1: &[u8] = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xff\xff\xff!\xf9\x04\x01\x00\x00\x00\x00,\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02D\x01\x00;";
+
+const DETAIL_OWNER_IP: &str = "203.0.113.218";
+const DETAIL_MEMBER_IP: &str = "203.0.113.219";
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        attachment_root: attachment_root("guild-profile"),
+        ..common::test_config()
+    }
+}
+
+async fn upload(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    filename: &str,
+    content_type: &str,
+    body: &[u8],
+) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("{channel_uri}/attachments?filename={filename}"))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", content_type)
+        .header("x-forwarded-for", ip)
+        .body(Body::from(body.to_vec()))
+        .expect("upload request should build");
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("upload should execute");
+    assert_eq!(response.status(), StatusCode::OK);
+    let uploaded: Value = parse_json_body(response).await;
+    uploaded["attachment_id"].as_str().unwrap().to_owned()
+}
+
+async fn patch_guild(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    body: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}"),
+        Some(&auth.access_token),
+        ip,
+        Some(body),
+    )
+    .await
+}
+
+async fn search_public_guilds(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    query: &str,
+) -> Vec<Value> {
+    let response = send_json(
+        app,
+        "GET",
+        format!("/guilds/public?q={query}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = parse_json_body(response).await;
+    body["guilds"].as_array().unwrap().clone()
+}
+
+async fn setup_profile_guild(app: &axum::Router, ip: &str) -> (AuthResponse, String, String) {
+    let owner = register_and_login(app, "profile", ip).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, ip, "Profile Guild", "profile-chat").await;
+    let channel_uri = format!("/guilds/{guild_id}/channels/{channel_id}");
+    (owner, guild_id, channel_uri)
+}
+
+async fn update_and_clear_profile(app: &axum::Router) {
+    let ip = "203.0.113.212";
+    let (owner, guild_id, channel_uri) = setup_profile_guild(app, ip).await;
+    let icon_id = upload(
+        app,
+        &owner,
+        ip,
+        &channel_uri,
+        "icon.gif",
+        "image/gif",
+        GIF_1X1,
+    )
+    .await;
+
+    let updated = patch_guild(
+        app,
+        &owner,
+        ip,
+        &guild_id,
+        json!({
+            "visibility": "public",
+            "description": "  Friendly folks\nwho like filament  ",
+            "icon_attachment_id": icon_id,
+        }),
+    )
+    .await;
+    assert_eq!(updated.status(), StatusCode::OK);
+    let updated: Value = parse_json_body(updated).await;
+    assert_eq!(updated["description"], "Friendly folks\nwho like filament");
+    assert_eq!(updated["icon_attachment_id"], icon_id.as_str());
+
+    let public = search_public_guilds(app, &owner, ip, "profile").await;
+    let listed = public
+        .iter()
+        .find(|guild| guild["guild_id"] == guild_id.as_str())
+        .expect("public guild should be listed");
+    assert_eq!(listed["description"], "Friendly folks\nwho like filament");
+    assert_eq!(listed["icon_attachment_id"], icon_id.as_str());
+
+    let cleared = patch_guild(
+        app,
+        &owner,
+        ip,
+        &guild_id,
+        json!({"description": "", "icon_attachment_id": ""}),
+    )
+    .await;
+    assert_eq!(cleared.status(), StatusCode::OK);
+    let guilds = send_json(
+        app,
+        "GET",
+        String::from("/guilds"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(guilds.status(), StatusCode::OK);
+    let guilds: Value = parse_json_body(guilds).await;
+    let mine = guilds["guilds"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|guild| guild["guild_id"] == guild_id.as_str())
+        .expect("guild should be listed");
+    assert!(mine["description"].is_null());
+    assert!(mine["icon_attachment_id"].is_null());
+    assert_eq!(mine["visibility"], "public");
+}
+
+#[tokio::test]
+async fn guild_profiles_update_and_clear_description_and_icon() {
+    update_and_clear_profile(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_guild_profiles_update_and_clear_description_and_icon() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    update_and_clear_profile(&app).await;
+}
+
+#[tokio::test]
+async fn guild_profiles_reject_non_image_icons_unknown_icons_and_long_descriptions() {
+    let app = in_memory_app_with(&test_config());
+    let ip = "203.0.113.213";
+    let (owner, guild_id, channel_uri) = setup_profile_guild(&app, ip).await;
+    let notes_id = upload(
+        &app,
+        &owner,
+        ip,
+        &channel_uri,
+        "notes.txt",
+        "text/plain",
+        b"notes",
+    )
+    .await;
+
+    for body in [
+        json!({"icon_attachment_id": notes_id}),
+        json!({"icon_attachment_id": Ulid::new().to_string()}),
+        json!({"description": "x".repeat(301)}),
+    ] {
+        let rejected = patch_guild(&app, &owner, ip, &guild_id, body.clone()).await;
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST, "{body}");
+    }
+}
+
+#[tokio::test]
+async fn public_guild_search_matches_misspelled_descriptions() {
+    let app = in_memory_app_with(&test_config());
+    let ip = "203.0.113.214";
+    let (owner, guild_id, _) = setup_profile_guild(&app, ip).await;
+    let updated = patch_guild(
+        &app,
+        &owner,
+        ip,
+        &guild_id,
+        json!({"visibility": "public", "description": "Friendly folks"}),
+    )
+    .await;
+    assert_eq!(updated.status(), StatusCode::OK);
+
+    let fuzzy = search_public_guilds(&app, &owner, ip, "frendly").await;
+    assert!(fuzzy
+        .iter()
+        .any(|guild| guild["guild_id"] == guild_id.as_str()));
+}
+
+async fn get_guild(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await
+}
+
+struct DetailGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    member_user_id: String,
+    guild_id: String,
+}
+
+async fn setup_detail_guild(app: &axum::Router) -> DetailGuild {
+    let owner = register_and_login(app, "detail_owner", DETAIL_OWNER_IP).await;
+    let member = register_and_login(app, "detail_member", DETAIL_MEMBER_IP).await;
+    let member_user_id = user_id(app, &member, DETAIL_MEMBER_IP).await;
+    let (guild_id, _) = create_guild_with_channel(
+        app,
+        &owner,
+        DETAIL_OWNER_IP,
+        "Profile Guild",
+        "profile-chat",
+    )
+    .await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        DETAIL_OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+    DetailGuild {
+        owner,
+        member,
+        member_user_id,
+        guild_id,
+    }
+}
+
+async fn fetch_guild_detail(app: &axum::Router) {
+    let guild = setup_detail_guild(app).await;
+    let patched = patch_guild(
+        app,
+        &guild.owner,
+        DETAIL_OWNER_IP,
+        &guild.guild_id,
+        json!({"description":"All about detail"}),
+    )
+    .await;
+    assert_eq!(patched.status(), StatusCode::OK);
+
+    let as_owner = get_guild(app, &guild.owner, DETAIL_OWNER_IP, &guild.guild_id).await;
+    assert_eq!(as_owner.status(), StatusCode::OK);
+    let as_owner: Value = parse_json_body(as_owner).await;
+    assert_eq!(as_owner["guild_id"], guild.guild_id.as_str());
+    assert_eq!(as_owner["name"], "Profile Guild");
+    assert_eq!(as_owner["visibility"], "private");
+    assert_eq!(as_owner["description"], "All about detail");
+    assert!(as_owner["icon_attachment_id"].is_null());
+    assert_eq!(as_owner["tags"], json!([]));
+    assert_eq!(as_owner["member_count"], 2);
+    assert_eq!(as_owner["role"], "owner");
+
+    let as_member = get_guild(app, &guild.member, DETAIL_MEMBER_IP, &guild.guild_id).await;
+    assert_eq!(as_member.status(), StatusCode::OK);
+    let as_member: Value = parse_json_body(as_member).await;
+    assert_eq!(as_member["member_count"], 2);
+    assert_eq!(as_member["role"], "member");
+}
+
+#[tokio::test]
+async fn guild_detail_reports_profile_role_and_member_count() {
+    fetch_guild_detail(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_guild_detail_reports_profile_role_and_member_count() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    fetch_guild_detail(&app).await;
+}
+
+#[tokio::test]
+async fn guild_detail_is_not_found_for_outsiders_and_unknown_guilds() {
+    let app = in_memory_app_with(&test_config());
+    let guild = setup_detail_guild(&app).await;
+    let outsider_ip = "203.0.113.233";
+    let outsider = register_and_login(&app, "detail_outsider", outsider_ip).await;
+
+    let hidden = get_guild(&app, &outsider, outsider_ip, &guild.guild_id).await;
+    assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+    let missing = get_guild(
+        &app,
+        &guild.owner,
+        DETAIL_OWNER_IP,
+        &Ulid::new().to_string(),
+    )
+    .await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn banned_members_lose_guild_detail_and_leave_the_member_count() {
+    let app = in_memory_app_with(&test_config());
+    let guild = setup_detail_guild(&app).await;
+
+    let banned = send_json(
+        &app,
+        "POST",
+        format!(
+            "/guilds/{}/members/{}/ban",
+            guild.guild_id, guild.member_user_id
+        ),
+        Some(&guild.owner.access_token),
+        DETAIL_OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(banned.status(), StatusCode::OK);
+    let after_ban = get_guild(&app, &guild.member, DETAIL_MEMBER_IP, &guild.guild_id).await;
+    assert_eq!(after_ban.status(), StatusCode::NOT_FOUND);
+    let recount = get_guild(&app, &guild.owner, DETAIL_OWNER_IP, &guild.guild_id).await;
+    assert_eq!(recount.status(), StatusCode::OK);
+    let recount: Value = parse_json_body(recount).await;
+    assert_eq!(recount["member_count"], 1);
+}
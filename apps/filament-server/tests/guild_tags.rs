@@ -0,0 +1,297 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild, in_memory_app, parse_json_body, postgres_app, register_and_login, send_json,
+    AuthResponse,
+};
+
+const OWNER_IP: &str = "203.0.113.215";
+const MEMBER_IP: &str = "203.0.113.216";
+const OUTSIDER_IP: &str = "203.0.113.217";
+
+struct TagGuilds {
+    owner: AuthResponse,
+    member: AuthResponse,
+    outsider: AuthResponse,
+    tagged: String,
+    untagged: String,
+    private: String,
+    /// Postgres runs share one database, so filters use a tag no earlier run used.
+    unique_tag: String,
+}
+
+async fn setup_tag_guilds(app: &axum::Router) -> TagGuilds {
+    let owner = register_and_login(app, "tag_owner", OWNER_IP).await;
+    let member = register_and_login(app, "tag_member", MEMBER_IP).await;
+    let outsider = register_and_login(app, "tag_outsider", OUTSIDER_IP).await;
+    let suffix = Ulid::new().to_string().to_lowercase();
+    let unique_tag = format!("run-{}", &suffix[..12]);
+
+    let tagged = create_guild(
+        app,
+        &owner,
+        OWNER_IP,
+        json!({"name":"Tagged Lounge","visibility":"public"}),
+    )
+    .await;
+    let untagged = create_guild(
+        app,
+        &owner,
+        OWNER_IP,
+        json!({"name":"Untagged Lounge","visibility":"public"}),
+    )
+    .await;
+    let private = create_guild(
+        app,
+        &owner,
+        OWNER_IP,
+        json!({"name":"Private Lounge","visibility":"private"}),
+    )
+    .await;
+
+    let joined = send_json(
+        app,
+        "POST",
+        format!("/guilds/{tagged}/join"),
+        Some(&member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(joined.status(), StatusCode::OK);
+    TagGuilds {
+        owner,
+        member,
+        outsider,
+        tagged,
+        untagged,
+        private,
+        unique_tag,
+    }
+}
+
+async fn put_tags(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    tags: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PUT",
+        format!("/guilds/{guild_id}/tags"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({ "tags": tags })),
+    )
+    .await
+}
+
+async fn list_public_guilds(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    query: &str,
+) -> Vec<Value> {
+    let response = send_json(
+        app,
+        "GET",
+        format!("/guilds/public?{query}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = parse_json_body(response).await;
+    body["guilds"].as_array().unwrap().clone()
+}
+
+fn guild_ids(guilds: &[Value]) -> Vec<&str> {
+    guilds
+        .iter()
+        .map(|guild| guild["guild_id"].as_str().unwrap())
+        .collect()
+}
+
+async fn filter_public_guilds_by_tag(app: &axum::Router) {
+    let guilds = setup_tag_guilds(app).await;
+    let unique = guilds.unique_tag.as_str();
+
+    let updated = put_tags(
+        app,
+        &guilds.owner,
+        OWNER_IP,
+        &guilds.tagged,
+        json!([" Tech ", unique, "tech"]),
+    )
+    .await;
+    assert_eq!(updated.status(), StatusCode::OK);
+    let updated: Value = parse_json_body(updated).await;
+    let expected = json!([unique, "tech"]);
+    assert_eq!(updated["tags"], expected);
+    assert_eq!(
+        put_tags(
+            app,
+            &guilds.owner,
+            OWNER_IP,
+            &guilds.private,
+            json!([unique])
+        )
+        .await
+        .status(),
+        StatusCode::OK
+    );
+
+    let outsider = &guilds.outsider;
+    let filtered = list_public_guilds(app, outsider, OUTSIDER_IP, &format!("tag={unique}")).await;
+    assert_eq!(guild_ids(&filtered), vec![guilds.tagged.as_str()]);
+    assert_eq!(filtered[0]["tags"], expected);
+    let upper = list_public_guilds(
+        app,
+        outsider,
+        OUTSIDER_IP,
+        &format!("tag={}", unique.to_uppercase()),
+    )
+    .await;
+    assert_eq!(guild_ids(&upper), vec![guilds.tagged.as_str()]);
+    let named = list_public_guilds(
+        app,
+        outsider,
+        OUTSIDER_IP,
+        &format!("q=lounge&tag={unique}"),
+    )
+    .await;
+    assert_eq!(guild_ids(&named), vec![guilds.tagged.as_str()]);
+    let no_match = list_public_guilds(
+        app,
+        outsider,
+        OUTSIDER_IP,
+        &format!("q=untagged&tag={unique}"),
+    )
+    .await;
+    assert!(no_match.is_empty());
+
+    let cleared = put_tags(app, &guilds.owner, OWNER_IP, &guilds.tagged, json!([])).await;
+    assert_eq!(cleared.status(), StatusCode::OK);
+    let cleared: Value = parse_json_body(cleared).await;
+    assert_eq!(cleared["tags"], json!([]));
+    assert!(
+        list_public_guilds(app, outsider, OUTSIDER_IP, &format!("tag={unique}"))
+            .await
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn tag_filter_lists_only_public_guilds_carrying_the_tag() {
+    filter_public_guilds_by_tag(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_tag_filter_lists_only_public_guilds_carrying_the_tag() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    filter_public_guilds_by_tag(&app).await;
+}
+
+#[tokio::test]
+async fn guild_tags_reject_invalid_lists_and_non_owners() {
+    let app = in_memory_app();
+    let guilds = setup_tag_guilds(&app).await;
+
+    for invalid in [
+        json!(["two words"]),
+        json!(["-leading"]),
+        json!([""]),
+        json!(["a".repeat(25)]),
+        json!(["a", "b", "c", "d", "e", "f"]),
+    ] {
+        let rejected = put_tags(&app, &guilds.owner, OWNER_IP, &guilds.tagged, invalid).await;
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    }
+    let forbidden = put_tags(
+        &app,
+        &guilds.member,
+        MEMBER_IP,
+        &guilds.tagged,
+        json!(["tech"]),
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn guild_tags_are_readable_by_members_but_not_outsiders() {
+    let app = in_memory_app();
+    let guilds = setup_tag_guilds(&app).await;
+    let updated = put_tags(
+        &app,
+        &guilds.owner,
+        OWNER_IP,
+        &guilds.tagged,
+        json!(["tech"]),
+    )
+    .await;
+    assert_eq!(updated.status(), StatusCode::OK);
+
+    let fetched = send_json(
+        &app,
+        "GET",
+        format!("/guilds/{}/tags", guilds.tagged),
+        Some(&guilds.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(fetched.status(), StatusCode::OK);
+    let fetched: Value = parse_json_body(fetched).await;
+    assert_eq!(fetched["tags"], json!(["tech"]));
+    let hidden = send_json(
+        &app,
+        "GET",
+        format!("/guilds/{}/tags", guilds.tagged),
+        Some(&guilds.outsider.access_token),
+        OUTSIDER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn untagged_public_guilds_are_listed_with_no_tags() {
+    let app = in_memory_app();
+    let guilds = setup_tag_guilds(&app).await;
+
+    let by_name =
+        list_public_guilds(&app, &guilds.outsider, OUTSIDER_IP, "q=untagged%20lounge").await;
+    let untagged = by_name
+        .iter()
+        .find(|guild| guild["guild_id"] == guilds.untagged.as_str())
+        .expect("untagged guild should be listed");
+    assert_eq!(untagged["tags"], json!([]));
+}
+
+#[tokio::test]
+async fn malformed_tag_filters_are_rejected() {
+    let app = in_memory_app();
+    let outsider = register_and_login(&app, "tag_outsider", OUTSIDER_IP).await;
+
+    let bad_filter = send_json(
+        &app,
+        "GET",
+        String::from("/guilds/public?tag=not%20a%20tag"),
+        Some(&outsider.access_token),
+        OUTSIDER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(bad_filter.status(), StatusCode::BAD_REQUEST);
+}
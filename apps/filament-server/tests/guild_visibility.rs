@@ -0,0 +1,289 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+async fn create_guild_with_message(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+) -> (String, String, String) {
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, owner, ip, "Visibility Guild", "visibility-chat").await;
+    let message_id = post_message(
+        app,
+        owner,
+        ip,
+        &guild_id,
+        &channel_id,
+        "hidden from outsiders",
+    )
+    .await;
+    (guild_id, channel_id, message_id)
+}
+
+fn outsider_probes(
+    guild_id: &str,
+    channel_id: &str,
+    message_id: &str,
+) -> Vec<(&'static str, String, Option<Value>)> {
+    let channel_uri = format!("/guilds/{guild_id}/channels/{channel_id}");
+    vec![
+        ("GET", format!("/guilds/{guild_id}/channels"), None),
+        ("GET", format!("/guilds/{guild_id}/members"), None),
+        ("GET", format!("/guilds/{guild_id}/audit"), None),
+        ("GET", format!("/guilds/{guild_id}/ip-bans"), None),
+        ("GET", format!("/guilds/{guild_id}/search?q=hidden"), None),
+        (
+            "PATCH",
+            format!("/guilds/{guild_id}"),
+            Some(json!({"name":"Renamed"})),
+        ),
+        ("GET", format!("{channel_uri}/messages"), None),
+        (
+            "POST",
+            format!("{channel_uri}/messages"),
+            Some(json!({"content":"hello?"})),
+        ),
+        (
+            "POST",
+            format!("{channel_uri}/messages/{message_id}/reactions/%F0%9F%91%8D"),
+            None,
+        ),
+        (
+            "DELETE",
+            format!("{channel_uri}/messages/{message_id}"),
+            None,
+        ),
+    ]
+}
+
+async fn assert_outsiders_cannot_tell_guilds_exist(app: &axum::Router, ip: &'static str) {
+    let owner = register_and_login(app, "vis_owner", ip).await;
+    let outsider = register_and_login(app, "vis_outsider", ip).await;
+    let (guild_id, channel_id, message_id) = create_guild_with_message(app, &owner, ip).await;
+    let missing_guild_id = Ulid::new().to_string();
+
+    let existing = outsider_probes(&guild_id, &channel_id, &message_id);
+    let missing = outsider_probes(&missing_guild_id, &channel_id, &message_id);
+    for ((method, uri, body), (_, missing_uri, missing_body)) in existing.into_iter().zip(missing) {
+        let response = send_json(
+            app,
+            method,
+            uri.clone(),
+            Some(&outsider.access_token),
+            ip,
+            body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{method} {uri}");
+        let existing_json: Value = parse_json_body(response).await;
+        let response = send_json(
+            app,
+            method,
+            missing_uri,
+            Some(&outsider.access_token),
+            ip,
+            missing_body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{method} {uri}");
+        let missing_json: Value = parse_json_body(response).await;
+        assert_eq!(existing_json, missing_json, "{method} {uri}");
+    }
+
+    let metrics = send_json(app, "GET", String::from("/metrics"), None, ip, None).await;
+    assert_eq!(metrics.status(), StatusCode::OK);
+    let metrics_body = axum::body::to_bytes(metrics.into_body(), usize::MAX)
+        .await
+        .expect("metrics body should be readable");
+    let metrics_text = String::from_utf8(metrics_body.to_vec()).expect("metrics are utf-8");
+    assert!(metrics_text.contains("filament_auth_failures_total{reason=\"not_guild_member\"}"));
+}
+
+async fn assert_members_see_forbidden_not_missing(app: &axum::Router, ip: &'static str) {
+    let owner = register_and_login(app, "vis_owner", ip).await;
+    let member = register_and_login(app, "vis_member", ip).await;
+    let (guild_id, channel_id, message_id) = create_guild_with_message(app, &owner, ip).await;
+    let member_id = user_id(app, &member, ip).await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_id}"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+
+    let channel_uri = format!("/guilds/{guild_id}/channels/{channel_id}");
+    for (method, uri, body) in [
+        ("GET", format!("/guilds/{guild_id}/audit"), None),
+        ("GET", format!("/guilds/{guild_id}/ip-bans"), None),
+        (
+            "PATCH",
+            format!("/guilds/{guild_id}"),
+            Some(json!({"name":"Renamed"})),
+        ),
+        (
+            "DELETE",
+            format!("{channel_uri}/messages/{message_id}"),
+            None,
+        ),
+    ] {
+        let response = send_json(
+            app,
+            method,
+            uri.clone(),
+            Some(&member.access_token),
+            ip,
+            body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{method} {uri}");
+    }
+
+    let missing_channel = send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}/channels/{}/messages", Ulid::new()),
+        Some(&member.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(missing_channel.status(), StatusCode::NOT_FOUND);
+}
+
+async fn assert_only_owner_changes_visibility(app: &axum::Router, ip: &'static str) {
+    let owner = register_and_login(app, "vis_owner", ip).await;
+    let admin = register_and_login(app, "vis_admin", ip).await;
+    let (guild_id, _, _) = create_guild_with_message(app, &owner, ip).await;
+    let admin_id = user_id(app, &admin, ip).await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{admin_id}"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+    let role = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/roles"),
+        Some(&owner.access_token),
+        ip,
+        Some(json!({"name":"admins","permissions":["manage_roles"],"position":90})),
+    )
+    .await;
+    assert_eq!(role.status(), StatusCode::OK);
+    let role: Value = parse_json_body(role).await;
+    let role_id = role["role_id"].as_str().unwrap();
+    let assigned = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/roles/{role_id}/members/{admin_id}"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(assigned.status(), StatusCode::OK);
+
+    let make_public = json!({"visibility":"public"});
+    let denied = send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}"),
+        Some(&admin.access_token),
+        ip,
+        Some(make_public.clone()),
+    )
+    .await;
+    assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+    let renamed = send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}"),
+        Some(&admin.access_token),
+        ip,
+        Some(json!({"name":"Renamed","visibility":"private"})),
+    )
+    .await;
+    assert_eq!(renamed.status(), StatusCode::OK);
+
+    let published = send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}"),
+        Some(&owner.access_token),
+        ip,
+        Some(make_public),
+    )
+    .await;
+    assert_eq!(published.status(), StatusCode::OK);
+    let published: Value = parse_json_body(published).await;
+    assert_eq!(published["visibility"], "public");
+
+    let audit = send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}/audit?action_prefix=guild.update"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(audit.status(), StatusCode::OK);
+    let audit: Value = parse_json_body(audit).await;
+    assert_eq!(audit["events"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn outsiders_get_the_same_not_found_for_existing_and_missing_guilds() {
+    assert_outsiders_cannot_tell_guilds_exist(&in_memory_app(), "203.0.113.181").await;
+}
+
+#[tokio::test]
+async fn postgres_outsiders_get_the_same_not_found_for_existing_and_missing_guilds() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    assert_outsiders_cannot_tell_guilds_exist(&app, "203.0.113.181").await;
+}
+
+#[tokio::test]
+async fn members_get_forbidden_rather_than_not_found() {
+    assert_members_see_forbidden_not_missing(&in_memory_app(), "203.0.113.182").await;
+}
+
+#[tokio::test]
+async fn postgres_members_get_forbidden_rather_than_not_found() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    assert_members_see_forbidden_not_missing(&app, "203.0.113.182").await;
+}
+
+#[tokio::test]
+async fn only_the_owner_can_change_guild_visibility() {
+    assert_only_owner_changes_visibility(&in_memory_app(), "203.0.113.183").await;
+}
+
+#[tokio::test]
+async fn postgres_only_the_owner_can_change_guild_visibility() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    assert_only_owner_changes_visibility(&app, "203.0.113.183").await;
+}
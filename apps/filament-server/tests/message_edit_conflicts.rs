@@ -0,0 +1,140 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, AuthResponse,
+};
+
+async fn create_message(app: &axum::Router, owner: &AuthResponse, ip: &str) -> String {
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, owner, ip, "Edit Guild", "edit-chat").await;
+    let message_id = post_message(app, owner, ip, &guild_id, &channel_id, "original").await;
+    format!("/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}")
+}
+
+async fn latest_message(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    message_uri: &str,
+) -> Value {
+    let history_uri = message_uri.rsplit_once('/').unwrap().0;
+    let history = send_json(
+        app,
+        "GET",
+        history_uri.to_owned(),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(history.status(), StatusCode::OK);
+    let history: Value = parse_json_body(history).await;
+    history["messages"][0].clone()
+}
+
+async fn edit(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    message_uri: &str,
+    body: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PATCH",
+        message_uri.to_owned(),
+        Some(&auth.access_token),
+        ip,
+        Some(body),
+    )
+    .await
+}
+
+async fn edit_with_expected_versions(app: &axum::Router) {
+    let ip = "203.0.113.231";
+    let owner = register_and_login(app, "edit_owner", ip).await;
+    let message_uri = create_message(app, &owner, ip).await;
+
+    let first = edit(
+        app,
+        &owner,
+        ip,
+        &message_uri,
+        json!({"content":"first edit","expected_edited_at_unix":0}),
+    )
+    .await;
+    assert_eq!(first.status(), StatusCode::OK);
+    let first: Value = parse_json_body(first).await;
+    let first_version = first["edited_at_unix"]
+        .as_i64()
+        .expect("edited message should carry edited_at_unix");
+
+    let stale = edit(
+        app,
+        &owner,
+        ip,
+        &message_uri,
+        json!({"content":"lost update","expected_edited_at_unix":0}),
+    )
+    .await;
+    assert_eq!(stale.status(), StatusCode::CONFLICT);
+    let stale: Value = parse_json_body(stale).await;
+    assert_eq!(stale["error"], "conflict");
+
+    let second = edit(
+        app,
+        &owner,
+        ip,
+        &message_uri,
+        json!({"content":"second edit","expected_edited_at_unix":first_version}),
+    )
+    .await;
+    assert_eq!(second.status(), StatusCode::OK);
+    let second: Value = parse_json_body(second).await;
+    assert!(second["edited_at_unix"].as_i64().unwrap() > first_version);
+    assert_eq!(
+        latest_message(app, &owner, ip, &message_uri).await["content"],
+        "second edit"
+    );
+}
+
+#[tokio::test]
+async fn edits_against_a_stale_version_conflict() {
+    edit_with_expected_versions(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_edits_against_a_stale_version_conflict() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    edit_with_expected_versions(&app).await;
+}
+
+#[tokio::test]
+async fn edits_without_an_expected_version_always_apply() {
+    let app = in_memory_app();
+    let ip = "203.0.113.232";
+    let owner = register_and_login(&app, "edit_owner", ip).await;
+    let message_uri = create_message(&app, &owner, ip).await;
+    let original = latest_message(&app, &owner, ip, &message_uri).await;
+    assert_eq!(original.get("edited_at_unix"), None);
+
+    let mut last_version = 0;
+    for content in ["first word", "last word"] {
+        let edited = edit(&app, &owner, ip, &message_uri, json!({"content":content})).await;
+        assert_eq!(edited.status(), StatusCode::OK);
+        let edited: Value = parse_json_body(edited).await;
+        let version = edited["edited_at_unix"].as_i64().unwrap();
+        assert!(version > last_version);
+        last_version = version;
+    }
+
+    let latest = latest_message(&app, &owner, ip, &message_uri).await;
+    assert_eq!(latest["content"], "last word");
+    assert_eq!(latest["edited_at_unix"], last_version);
+}
@@ -0,0 +1,308 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{in_memory_app, parse_json_body, register_and_login, send_json};
+
+#[tokio::test]
+#[allow(clippy::too_many_lines)]
+async fn channel_export_streams_history_for_moderators_only() {
+    let app = in_memory_app();
+    let owner = register_and_login(&app, "export_owner", "203.0.113.141").await;
+    let member = register_and_login(&app, "export_member", "203.0.113.142").await;
+
+    let me = send_json(
+        &app,
+        "GET",
+        String::from("/auth/me"),
+        Some(&member.access_token),
+        "203.0.113.142",
+        None,
+    )
+    .await;
+    let me_json: Value = parse_json_body(me).await;
+    let member_id = me_json["user_id"].as_str().unwrap().to_owned();
+
+    let guild = send_json(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        Some(&owner.access_token),
+        "203.0.113.141",
+        Some(json!({"name":"Export Guild"})),
+    )
+    .await;
+    assert_eq!(guild.status(), StatusCode::OK);
+    let guild_json: Value = parse_json_body(guild).await;
+    let guild_id = guild_json["guild_id"].as_str().unwrap().to_owned();
+    let channel = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        "203.0.113.141",
+        Some(json!({"name":"export-chat"})),
+    )
+    .await;
+    assert_eq!(channel.status(), StatusCode::OK);
+    let channel_json: Value = parse_json_body(channel).await;
+    let channel_id = channel_json["channel_id"].as_str().unwrap().to_owned();
+
+    let add_member = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_id}"),
+        Some(&owner.access_token),
+        "203.0.113.141",
+        None,
+    )
+    .await;
+    assert_eq!(add_member.status(), StatusCode::OK);
+
+    for content in ["first", "second", "third"] {
+        let created = send_json(
+            &app,
+            "POST",
+            format!("/guilds/{guild_id}/channels/{channel_id}/messages"),
+            Some(&owner.access_token),
+            "203.0.113.141",
+            Some(json!({"content":content})),
+        )
+        .await;
+        assert_eq!(created.status(), StatusCode::OK);
+    }
+
+    let export_uri = format!("/guilds/{guild_id}/channels/{channel_id}/export");
+    let forbidden = send_json(
+        &app,
+        "GET",
+        export_uri.clone(),
+        Some(&member.access_token),
+        "203.0.113.142",
+        None,
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+
+    let export = send_json(
+        &app,
+        "GET",
+        export_uri.clone(),
+        Some(&owner.access_token),
+        "203.0.113.141",
+        None,
+    )
+    .await;
+    assert_eq!(export.status(), StatusCode::OK);
+    assert_eq!(
+        export.headers()["content-type"].to_str().unwrap(),
+        "application/x-ndjson"
+    );
+    assert_eq!(
+        export.headers()["content-disposition"].to_str().unwrap(),
+        format!("attachment; filename=\"channel-{channel_id}.ndjson\"")
+    );
+    let body = axum::body::to_bytes(export.into_body(), usize::MAX)
+        .await
+        .expect("export body should be readable");
+    let lines: Vec<Value> = std::str::from_utf8(&body)
+        .expect("export should be utf-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be json"))
+        .collect();
+    let contents: Vec<&str> = lines
+        .iter()
+        .map(|line| line["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(contents, ["first", "second", "third"]);
+    assert!(lines
+        .windows(2)
+        .all(|pair| pair[0]["message_id"].as_str() < pair[1]["message_id"].as_str()));
+    assert!(lines[0].get("markdown_tokens").is_none());
+    assert!(lines[0]["attachments"].is_array());
+    assert!(lines[0]["reactions"].is_array());
+
+    let second = send_json(
+        &app,
+        "GET",
+        export_uri.clone(),
+        Some(&owner.access_token),
+        "203.0.113.141",
+        None,
+    )
+    .await;
+    assert_eq!(second.status(), StatusCode::OK);
+    let limited = send_json(
+        &app,
+        "GET",
+        export_uri,
+        Some(&owner.access_token),
+        "203.0.113.141",
+        None,
+    )
+    .await;
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+#[allow(clippy::too_many_lines)]
+async fn personal_export_streams_account_friends_and_authored_messages() {
+    let app = in_memory_app();
+    let alice = register_and_login(&app, "export_alice", "203.0.113.151").await;
+    let bob = register_and_login(&app, "export_bob", "203.0.113.152").await;
+    let me = send_json(
+        &app,
+        "GET",
+        String::from("/auth/me"),
+        Some(&bob.access_token),
+        "203.0.113.152",
+        None,
+    )
+    .await;
+    let me_json: Value = parse_json_body(me).await;
+    let bob_id = me_json["user_id"].as_str().unwrap().to_owned();
+
+    let friend_request = send_json(
+        &app,
+        "POST",
+        String::from("/friends/requests"),
+        Some(&alice.access_token),
+        "203.0.113.151",
+        Some(json!({"recipient_user_id":bob_id})),
+    )
+    .await;
+    assert_eq!(friend_request.status(), StatusCode::OK);
+    let friend_request_json: Value = parse_json_body(friend_request).await;
+    let request_id = friend_request_json["request_id"].as_str().unwrap();
+    let accepted = send_json(
+        &app,
+        "POST",
+        format!("/friends/requests/{request_id}/accept"),
+        Some(&bob.access_token),
+        "203.0.113.152",
+        None,
+    )
+    .await;
+    assert!(accepted.status().is_success());
+
+    let guild = send_json(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        Some(&alice.access_token),
+        "203.0.113.151",
+        Some(json!({"name":"Personal Export Guild"})),
+    )
+    .await;
+    let guild_json: Value = parse_json_body(guild).await;
+    let guild_id = guild_json["guild_id"].as_str().unwrap().to_owned();
+    let channel = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&alice.access_token),
+        "203.0.113.151",
+        Some(json!({"name":"personal-export"})),
+    )
+    .await;
+    let channel_json: Value = parse_json_body(channel).await;
+    let channel_id = channel_json["channel_id"].as_str().unwrap().to_owned();
+    let add_member = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{bob_id}"),
+        Some(&alice.access_token),
+        "203.0.113.151",
+        None,
+    )
+    .await;
+    assert_eq!(add_member.status(), StatusCode::OK);
+
+    for (auth, ip, content) in [
+        (&alice, "203.0.113.151", "alice one"),
+        (&bob, "203.0.113.152", "bob one"),
+        (&alice, "203.0.113.151", "alice two"),
+    ] {
+        let created = send_json(
+            &app,
+            "POST",
+            format!("/guilds/{guild_id}/channels/{channel_id}/messages"),
+            Some(&auth.access_token),
+            ip,
+            Some(json!({"content":content})),
+        )
+        .await;
+        assert_eq!(created.status(), StatusCode::OK);
+    }
+
+    let export = send_json(
+        &app,
+        "GET",
+        String::from("/auth/me/export"),
+        Some(&alice.access_token),
+        "203.0.113.151",
+        None,
+    )
+    .await;
+    assert_eq!(export.status(), StatusCode::OK);
+    assert_eq!(
+        export.headers()["content-type"].to_str().unwrap(),
+        "application/x-ndjson"
+    );
+    let body = axum::body::to_bytes(export.into_body(), usize::MAX)
+        .await
+        .expect("export body should be readable");
+    let records: Vec<Value> = std::str::from_utf8(&body)
+        .expect("export should be utf-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be json"))
+        .collect();
+    assert_eq!(records[0]["kind"], "account");
+    let alice_me = send_json(
+        &app,
+        "GET",
+        String::from("/auth/me"),
+        Some(&alice.access_token),
+        "203.0.113.151",
+        None,
+    )
+    .await;
+    let alice_me: Value = parse_json_body(alice_me).await;
+    assert_eq!(records[0]["username"], alice_me["username"]);
+    let friends: Vec<&Value> = records
+        .iter()
+        .filter(|record| record["kind"] == "friend")
+        .collect();
+    assert_eq!(friends.len(), 1);
+    assert_eq!(friends[0]["user_id"], bob_id.as_str());
+    let messages: Vec<&str> = records
+        .iter()
+        .filter(|record| record["kind"] == "message")
+        .map(|record| record["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(messages, ["alice one", "alice two"]);
+
+    let limited = send_json(
+        &app,
+        "GET",
+        String::from("/auth/me/export"),
+        Some(&alice.access_token),
+        "203.0.113.151",
+        None,
+    )
+    .await;
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(limited.headers().contains_key("retry-after"));
+
+    let other_user = send_json(
+        &app,
+        "GET",
+        String::from("/auth/me/export"),
+        Some(&bob.access_token),
+        "203.0.113.152",
+        None,
+    )
+    .await;
+    assert_eq!(other_user.status(), StatusCode::OK);
+}
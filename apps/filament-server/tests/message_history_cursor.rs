@@ -0,0 +1,280 @@
+mod common;
+
+use axum::http::StatusCode;
+use filament_server::{build_router, AppConfig};
+use serde_json::{json, Value};
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, test_config, AuthResponse,
+};
+
+struct HistoryContext {
+    auth: AuthResponse,
+    ip: &'static str,
+    guild_id: String,
+    channel_id: String,
+}
+
+async fn history_context(app: &axum::Router, ip: &'static str) -> HistoryContext {
+    let auth = register_and_login(app, "cursor", ip).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &auth, ip, "Cursor Guild", "cursor-chat").await;
+    HistoryContext {
+        auth,
+        ip,
+        guild_id,
+        channel_id,
+    }
+}
+
+impl HistoryContext {
+    fn messages_uri(&self) -> String {
+        format!(
+            "/guilds/{}/channels/{}/messages",
+            self.guild_id, self.channel_id
+        )
+    }
+}
+
+async fn create_messages(app: &axum::Router, ctx: &HistoryContext, count: usize) -> Vec<String> {
+    let mut message_ids = Vec::with_capacity(count);
+    for index in 0..count {
+        message_ids.push(
+            post_message(
+                app,
+                &ctx.auth,
+                ctx.ip,
+                &ctx.guild_id,
+                &ctx.channel_id,
+                &format!("message {index}"),
+            )
+            .await,
+        );
+    }
+    message_ids
+}
+
+async fn history_page(
+    app: &axum::Router,
+    ctx: &HistoryContext,
+    limit: usize,
+    before: Option<&str>,
+) -> (Vec<String>, Option<String>) {
+    let uri = match before {
+        Some(before) => format!("{}?limit={limit}&before={before}", ctx.messages_uri()),
+        None => format!("{}?limit={limit}", ctx.messages_uri()),
+    };
+    let response = send_json(app, "GET", uri, Some(&ctx.auth.access_token), ctx.ip, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let page: Value = parse_json_body(response).await;
+    let ids = page["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message["message_id"].as_str().unwrap().to_owned())
+        .collect();
+    (ids, page["next_before"].as_str().map(str::to_owned))
+}
+
+fn newest_first(message_ids: &[String]) -> Vec<String> {
+    let mut sorted = message_ids.to_vec();
+    sorted.sort();
+    sorted.reverse();
+    sorted
+}
+
+async fn assert_pages_walk_history_once_in_id_order(app: &axum::Router, ip: &'static str) {
+    let ctx = history_context(app, ip).await;
+    let message_ids = create_messages(app, &ctx, 7).await;
+
+    let mut walked = Vec::new();
+    let mut before = None;
+    loop {
+        let (ids, next_before) = history_page(app, &ctx, 3, before.as_deref()).await;
+        assert!(ids.len() <= 3);
+        assert_eq!(next_before.as_ref(), ids.last());
+        walked.extend(ids);
+        match next_before {
+            Some(next) => before = Some(next),
+            None => break,
+        }
+    }
+
+    assert_eq!(walked, newest_first(&message_ids));
+}
+
+async fn assert_edits_do_not_move_cursor_pages(app: &axum::Router, ip: &'static str) {
+    let ctx = history_context(app, ip).await;
+    let message_ids = create_messages(app, &ctx, 5).await;
+    let expected = newest_first(&message_ids);
+
+    let (first_page, next_before) = history_page(app, &ctx, 2, None).await;
+    assert_eq!(first_page, expected[..2]);
+
+    for message_id in [&expected[0], &expected[3]] {
+        let edited = send_json(
+            app,
+            "PATCH",
+            format!("{}/{message_id}", ctx.messages_uri()),
+            Some(&ctx.auth.access_token),
+            ctx.ip,
+            Some(json!({"content":"edited"})),
+        )
+        .await;
+        assert_eq!(edited.status(), StatusCode::OK);
+    }
+
+    let (second_page, _) = history_page(app, &ctx, 2, next_before.as_deref()).await;
+    assert_eq!(second_page, expected[2..4]);
+    let (replayed_first_page, _) = history_page(app, &ctx, 2, None).await;
+    assert_eq!(replayed_first_page, first_page);
+}
+
+async fn assert_cursor_need_not_name_a_message(app: &axum::Router, ip: &'static str) {
+    let ctx = history_context(app, ip).await;
+    let message_ids = create_messages(app, &ctx, 3).await;
+    let expected = newest_first(&message_ids);
+
+    let (from_future, _) = history_page(app, &ctx, 10, Some("7ZZZZZZZZZZZZZZZZZZZZZZZZZ")).await;
+    assert_eq!(from_future, expected);
+    let (from_past, next_before) =
+        history_page(app, &ctx, 10, Some("00000000000000000000000000")).await;
+    assert!(from_past.is_empty());
+    assert!(next_before.is_none());
+}
+
+async fn assert_deleted_cursor_still_pages_older_messages(app: &axum::Router, ip: &'static str) {
+    let ctx = history_context(app, ip).await;
+    let message_ids = create_messages(app, &ctx, 5).await;
+    let expected = newest_first(&message_ids);
+
+    let (first_page, next_before) = history_page(app, &ctx, 2, None).await;
+    assert_eq!(first_page, expected[..2]);
+    let cursor = next_before.expect("first page should carry a cursor");
+    let deleted = send_json(
+        app,
+        "DELETE",
+        format!("{}/{cursor}", ctx.messages_uri()),
+        Some(&ctx.auth.access_token),
+        ctx.ip,
+        None,
+    )
+    .await;
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+
+    let (second_page, next_before) = history_page(app, &ctx, 2, Some(&cursor)).await;
+    assert_eq!(second_page, expected[2..4]);
+    let (third_page, _) = history_page(app, &ctx, 2, next_before.as_deref()).await;
+    assert_eq!(third_page, expected[4..]);
+}
+
+#[tokio::test]
+async fn cursor_pages_walk_history_once_newest_first() {
+    assert_pages_walk_history_once_in_id_order(&in_memory_app(), "203.0.113.171").await;
+}
+
+#[tokio::test]
+async fn postgres_cursor_pages_walk_history_once_newest_first() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    assert_pages_walk_history_once_in_id_order(&app, "203.0.113.171").await;
+}
+
+#[tokio::test]
+async fn edits_do_not_move_messages_between_cursor_pages() {
+    assert_edits_do_not_move_cursor_pages(&in_memory_app(), "203.0.113.172").await;
+}
+
+#[tokio::test]
+async fn postgres_edits_do_not_move_messages_between_cursor_pages() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    assert_edits_do_not_move_cursor_pages(&app, "203.0.113.172").await;
+}
+
+#[tokio::test]
+async fn cursors_need_not_name_an_existing_message() {
+    assert_cursor_need_not_name_a_message(&in_memory_app(), "203.0.113.173").await;
+}
+
+#[tokio::test]
+async fn postgres_cursors_need_not_name_an_existing_message() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    assert_cursor_need_not_name_a_message(&app, "203.0.113.173").await;
+}
+
+#[tokio::test]
+async fn deleted_cursor_messages_still_page_older_history() {
+    assert_deleted_cursor_still_pages_older_messages(&in_memory_app(), "203.0.113.174").await;
+}
+
+#[tokio::test]
+async fn postgres_deleted_cursor_messages_still_page_older_history() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    assert_deleted_cursor_still_pages_older_messages(&app, "203.0.113.174").await;
+}
+
+#[tokio::test]
+async fn history_limits_are_validated_at_startup() {
+    for (history_default_limit, history_max_limit) in [(0, 100), (20, 0), (20, 501), (50, 40)] {
+        assert!(build_router(&AppConfig {
+            history_default_limit,
+            history_max_limit,
+            ..test_config()
+        })
+        .is_err());
+    }
+    assert!(build_router(&AppConfig {
+        history_default_limit: 200,
+        history_max_limit: 500,
+        ..test_config()
+    })
+    .is_ok());
+}
+
+#[tokio::test]
+async fn configured_history_limits_shape_pages() {
+    let app = build_router(&AppConfig {
+        history_default_limit: 2,
+        history_max_limit: 3,
+        ..test_config()
+    })
+    .expect("router should build");
+    let ctx = history_context(&app, "203.0.113.175").await;
+    let message_ids = create_messages(&app, &ctx, 4).await;
+    let expected = newest_first(&message_ids);
+
+    let default_page = send_json(
+        &app,
+        "GET",
+        ctx.messages_uri(),
+        Some(&ctx.auth.access_token),
+        ctx.ip,
+        None,
+    )
+    .await;
+    assert_eq!(default_page.status(), StatusCode::OK);
+    let default_page: Value = parse_json_body(default_page).await;
+    assert_eq!(default_page["messages"].as_array().unwrap().len(), 2);
+
+    let (max_page, _) = history_page(&app, &ctx, 3, None).await;
+    assert_eq!(max_page, expected[..3]);
+
+    let over_max = send_json(
+        &app,
+        "GET",
+        format!("{}?limit=4", ctx.messages_uri()),
+        Some(&ctx.auth.access_token),
+        ctx.ip,
+        None,
+    )
+    .await;
+    assert_eq!(over_max.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, register_and_login,
+    send_json, AuthResponse,
+};
+
+const AUTHOR_IP: &str = "203.0.113.181";
+
+async fn post_with_idempotency_key(
+    app: &axum::Router,
+    uri: &str,
+    auth: &AuthResponse,
+    idempotency_key: &str,
+    content: &str,
+) -> axum::response::Response {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", AUTHOR_IP)
+        .header("idempotency-key", idempotency_key)
+        .body(Body::from(json!({"content":content}).to_string()))
+        .expect("request should build");
+    app.clone()
+        .oneshot(request)
+        .await
+        .expect("request should execute")
+}
+
+#[tokio::test]
+async fn retried_message_create_with_idempotency_key_replays_original() {
+    let app = in_memory_app();
+    let auth = register_and_login(&app, "idem_author", AUTHOR_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(&app, &auth, AUTHOR_IP, "Retry Guild", "retries").await;
+    let messages_uri = format!("/guilds/{guild_id}/channels/{channel_id}/messages");
+
+    let first = post_with_idempotency_key(&app, &messages_uri, &auth, "send-1", "hello").await;
+    assert_eq!(first.status(), StatusCode::OK);
+    let first: Value = parse_json_body(first).await;
+    let retry = post_with_idempotency_key(&app, &messages_uri, &auth, "send-1", "hello").await;
+    assert_eq!(retry.status(), StatusCode::OK);
+    let retry: Value = parse_json_body(retry).await;
+    assert_eq!(retry["message_id"], first["message_id"]);
+
+    let other_key = post_with_idempotency_key(&app, &messages_uri, &auth, "send-2", "hello").await;
+    assert_eq!(other_key.status(), StatusCode::OK);
+    post_message(&app, &auth, AUTHOR_IP, &guild_id, &channel_id, "hello").await;
+    let invalid_key =
+        post_with_idempotency_key(&app, &messages_uri, &auth, "has space", "hello").await;
+    assert_eq!(invalid_key.status(), StatusCode::BAD_REQUEST);
+
+    let history = send_json(
+        &app,
+        "GET",
+        messages_uri,
+        Some(&auth.access_token),
+        AUTHOR_IP,
+        None,
+    )
+    .await;
+    assert_eq!(history.status(), StatusCode::OK);
+    let history: Value = parse_json_body(history).await;
+    assert_eq!(history["messages"].as_array().unwrap().len(), 3);
+}
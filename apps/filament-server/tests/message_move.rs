@@ -0,0 +1,288 @@
+mod common;
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use ulid::Ulid;
+
+use common::{
+    attachment_root, create_guild, in_memory_app_with, parse_json_body, postgres_app_with,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        search_sync_index_writes: true,
+        attachment_root: attachment_root("message-move"),
+        ..common::test_config()
+    }
+}
+
+async fn create_channel(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    name: &str,
+) -> String {
+    let channel = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        ip,
+        Some(json!({"name":name})),
+    )
+    .await;
+    assert_eq!(channel.status(), StatusCode::OK);
+    let channel_json: Value = parse_json_body(channel).await;
+    channel_json["channel_id"].as_str().unwrap().to_owned()
+}
+
+async fn upload_text(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel_uri: &str,
+    filename: &str,
+) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("{channel_uri}/attachments?filename={filename}"))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", "text/plain")
+        .header("x-forwarded-for", ip)
+        .body(Body::from(filename.to_owned()))
+        .expect("upload request should build");
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("upload should execute");
+    assert_eq!(response.status(), StatusCode::OK);
+    let uploaded: Value = parse_json_body(response).await;
+    uploaded["attachment_id"].as_str().unwrap().to_owned()
+}
+
+async fn history_ids(app: &axum::Router, auth: &AuthResponse, ip: &str, uri: &str) -> Vec<String> {
+    let history = send_json(
+        app,
+        "GET",
+        format!("{uri}/messages"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(history.status(), StatusCode::OK);
+    let history: Value = parse_json_body(history).await;
+    history["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message["message_id"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+const OWNER_IP: &str = "203.0.113.241";
+const MEMBER_IP: &str = "203.0.113.242";
+
+struct MoveGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    guild_id: String,
+    source_id: String,
+    target_id: String,
+    target_uri: String,
+    source_uri: String,
+    attachment_id: String,
+    message_id: String,
+}
+
+impl MoveGuild {
+    async fn move_to(
+        &self,
+        app: &axum::Router,
+        auth: &AuthResponse,
+        ip: &str,
+        channel_id: &str,
+    ) -> axum::response::Response {
+        send_json(
+            app,
+            "POST",
+            format!("{}/messages/{}/move", self.source_uri, self.message_id),
+            Some(&auth.access_token),
+            ip,
+            Some(json!({"channel_id":channel_id})),
+        )
+        .await
+    }
+}
+
+/// Has a member post a message with an attachment to `general`, which the
+/// owner then reacts to.
+async fn setup_move_guild(app: &axum::Router) -> MoveGuild {
+    let owner = register_and_login(app, "mover", OWNER_IP).await;
+    let member = register_and_login(app, "move_member", MEMBER_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let guild_id = create_guild(app, &owner, OWNER_IP, json!({"name":"Move Guild"})).await;
+    let source_id = create_channel(app, &owner, OWNER_IP, &guild_id, "general").await;
+    let target_id = create_channel(app, &owner, OWNER_IP, &guild_id, "off-topic").await;
+    let source_uri = format!("/guilds/{guild_id}/channels/{source_id}");
+    let target_uri = format!("/guilds/{guild_id}/channels/{target_id}");
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+
+    let attachment_id = upload_text(app, &member, MEMBER_IP, &source_uri, "cat.txt").await;
+    let created = send_json(
+        app,
+        "POST",
+        format!("{source_uri}/messages"),
+        Some(&member.access_token),
+        MEMBER_IP,
+        Some(json!({"content":"pictures of my cat","attachment_ids":[attachment_id]})),
+    )
+    .await;
+    assert_eq!(created.status(), StatusCode::OK);
+    let created: Value = parse_json_body(created).await;
+    let message_id = created["message_id"].as_str().unwrap().to_owned();
+    let reacted = send_json(
+        app,
+        "POST",
+        format!("{source_uri}/messages/{message_id}/reactions/%F0%9F%91%8D"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(reacted.status(), StatusCode::OK);
+    MoveGuild {
+        owner,
+        member,
+        guild_id,
+        source_id,
+        target_id,
+        target_uri,
+        source_uri,
+        attachment_id,
+        message_id,
+    }
+}
+
+async fn move_between_channels(app: &axum::Router) {
+    let guild = setup_move_guild(app).await;
+
+    let moved = guild
+        .move_to(app, &guild.owner, OWNER_IP, &guild.target_id)
+        .await;
+    assert_eq!(moved.status(), StatusCode::OK);
+    let moved: Value = parse_json_body(moved).await;
+    assert_eq!(moved["channel_id"], guild.target_id);
+    assert_eq!(moved["content"], "pictures of my cat");
+    assert_eq!(
+        moved["attachments"][0]["attachment_id"],
+        guild.attachment_id
+    );
+    assert_eq!(moved["reactions"][0]["count"], 1);
+
+    assert!(history_ids(app, &guild.owner, OWNER_IP, &guild.source_uri)
+        .await
+        .is_empty());
+    assert_eq!(
+        history_ids(app, &guild.owner, OWNER_IP, &guild.target_uri).await,
+        vec![guild.message_id.clone()]
+    );
+    let download = send_json(
+        app,
+        "GET",
+        format!("{}/attachments/{}", guild.target_uri, guild.attachment_id),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(download.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn moved_messages_keep_their_attachments_and_reactions() {
+    move_between_channels(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_moved_messages_keep_their_attachments_and_reactions() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    move_between_channels(&app).await;
+}
+
+#[tokio::test]
+async fn moved_messages_are_searchable_in_their_new_channel() {
+    let app = in_memory_app_with(&test_config());
+    let guild = setup_move_guild(&app).await;
+    let moved = guild
+        .move_to(&app, &guild.owner, OWNER_IP, &guild.target_id)
+        .await;
+    assert_eq!(moved.status(), StatusCode::OK);
+
+    let search = send_json(
+        &app,
+        "GET",
+        format!(
+            "/guilds/{}/search?q=cat&channel_id={}",
+            guild.guild_id, guild.target_id
+        ),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(search.status(), StatusCode::OK);
+    let search: Value = parse_json_body(search).await;
+    assert_eq!(search["message_ids"], json!([guild.message_id]));
+}
+
+#[tokio::test]
+async fn moves_require_a_moderator_and_a_different_existing_channel() {
+    let app = in_memory_app_with(&test_config());
+    let guild = setup_move_guild(&app).await;
+
+    let by_member = guild
+        .move_to(&app, &guild.member, MEMBER_IP, &guild.target_id)
+        .await;
+    assert_eq!(by_member.status(), StatusCode::FORBIDDEN);
+    let same_channel = guild
+        .move_to(&app, &guild.owner, OWNER_IP, &guild.source_id)
+        .await;
+    assert_eq!(same_channel.status(), StatusCode::BAD_REQUEST);
+    let unknown_target = guild
+        .move_to(&app, &guild.owner, OWNER_IP, &Ulid::new().to_string())
+        .await;
+    assert_eq!(unknown_target.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        history_ids(&app, &guild.owner, OWNER_IP, &guild.source_uri).await,
+        vec![guild.message_id.clone()]
+    );
+}
+
+#[tokio::test]
+async fn moving_an_already_moved_message_is_not_found() {
+    let app = in_memory_app_with(&test_config());
+    let guild = setup_move_guild(&app).await;
+    for expected in [StatusCode::OK, StatusCode::NOT_FOUND] {
+        let moved = guild
+            .move_to(&app, &guild.owner, OWNER_IP, &guild.target_id)
+            .await;
+        assert_eq!(moved.status(), expected);
+    }
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{
+    create_guild, in_memory_app_with, parse_json_body, post_message, postgres_app_with,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+const ADMIN_SECRET: &str = "message-rates-admin-secret-0123456789";
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        admin_api_secret: Some(String::from(ADMIN_SECRET)),
+        ..common::test_config()
+    }
+}
+
+async fn create_channel(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+) -> String {
+    let channel = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        ip,
+        Some(json!({"name":"rate-chat"})),
+    )
+    .await;
+    assert_eq!(channel.status(), StatusCode::OK);
+    let channel_json: Value = parse_json_body(channel).await;
+    channel_json["channel_id"].as_str().unwrap().to_owned()
+}
+
+async fn message_rates(
+    app: &axum::Router,
+    query: &str,
+    secret: Option<&str>,
+) -> axum::response::Response {
+    let mut request = Request::builder()
+        .method("GET")
+        .uri(format!("/admin/message-rates{query}"))
+        .header("x-forwarded-for", "203.0.113.230");
+    if let Some(secret) = secret {
+        request = request.header("x-filament-admin-secret", secret);
+    }
+    app.clone()
+        .oneshot(request.body(Body::empty()).expect("request should build"))
+        .await
+        .expect("request should execute")
+}
+
+/// User ids of the guild's two posters, plus the guild they posted in.
+struct RateGuild {
+    owner: String,
+    member: String,
+    guild_id: String,
+}
+
+/// Posts three messages as the owner and one as a member.
+async fn setup_rate_guild(app: &axum::Router) -> RateGuild {
+    let (owner_ip, member_ip) = ("203.0.113.231", "203.0.113.232");
+    let owner = register_and_login(app, "rate_owner", owner_ip).await;
+    let member = register_and_login(app, "rate_member", member_ip).await;
+    let owner_user_id = user_id(app, &owner, owner_ip).await;
+    let member_user_id = user_id(app, &member, member_ip).await;
+    let guild_id = create_guild(
+        app,
+        &owner,
+        owner_ip,
+        json!({"name":"Rate Guild","visibility":"private"}),
+    )
+    .await;
+    let channel_id = create_channel(app, &owner, owner_ip, &guild_id).await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        owner_ip,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+
+    for content in ["one", "two", "three"] {
+        post_message(app, &owner, owner_ip, &guild_id, &channel_id, content).await;
+    }
+    post_message(app, &member, member_ip, &guild_id, &channel_id, "hi").await;
+    RateGuild {
+        owner: owner_user_id,
+        member: member_user_id,
+        guild_id,
+    }
+}
+
+async fn rank_recent_posters(app: &axum::Router) {
+    let guild = setup_rate_guild(app).await;
+    let rates = message_rates(app, "", Some(ADMIN_SECRET)).await;
+    assert_eq!(rates.status(), StatusCode::OK);
+    let rates: Value = parse_json_body(rates).await;
+    assert_eq!(rates["window_secs"], 60);
+    assert_eq!(
+        rates["users"],
+        json!([
+            {"user_id": guild.owner, "messages": 3},
+            {"user_id": guild.member, "messages": 1},
+        ])
+    );
+    assert_eq!(
+        rates["guilds"],
+        json!([{"guild_id": guild.guild_id, "messages": 4}])
+    );
+}
+
+#[tokio::test]
+async fn admin_message_rates_rank_users_and_guilds_in_the_window() {
+    rank_recent_posters(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_admin_message_rates_rank_users_and_guilds_in_the_window() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    rank_recent_posters(&app).await;
+}
+
+#[tokio::test]
+async fn admin_message_rates_honor_the_limit() {
+    let app = in_memory_app_with(&test_config());
+    let guild = setup_rate_guild(&app).await;
+    let top = message_rates(&app, "?limit=1", Some(ADMIN_SECRET)).await;
+    assert_eq!(top.status(), StatusCode::OK);
+    let top: Value = parse_json_body(top).await;
+    assert_eq!(top["users"].as_array().map(Vec::len), Some(1));
+    assert_eq!(top["users"][0]["user_id"], guild.owner);
+}
+
+#[tokio::test]
+async fn admin_message_rates_reject_bad_limits_and_missing_secrets() {
+    let app = in_memory_app_with(&test_config());
+    for query in ["?limit=0", "?limit=101"] {
+        let invalid = message_rates(&app, query, Some(ADMIN_SECRET)).await;
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST, "{query}");
+    }
+    let missing_secret = message_rates(&app, "", None).await;
+    assert_eq!(missing_secret.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn public_metrics_export_only_the_aggregate_message_count() {
+    let app = in_memory_app_with(&test_config());
+    let guild = setup_rate_guild(&app).await;
+    let metrics = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/metrics")
+                .header("x-forwarded-for", "203.0.113.230")
+                .body(Body::empty())
+                .expect("request should build"),
+        )
+        .await
+        .expect("request should execute");
+    let metrics = axum::body::to_bytes(metrics.into_body(), usize::MAX)
+        .await
+        .expect("metrics body should be readable");
+    let metrics = String::from_utf8_lossy(&metrics);
+    let created_total = metrics
+        .lines()
+        .find_map(|line| line.strip_prefix("filament_messages_created_total "))
+        .and_then(|value| value.parse::<u64>().ok())
+        .expect("aggregate message counter should be exported");
+    assert!(created_total >= 4);
+    assert!(!metrics.contains(&guild.guild_id));
+    assert!(!metrics.contains(&guild.owner));
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use filament_server::AppConfig;
+use serde_json::{json, Value};
+
+use common::{in_memory_app_with, parse_json_body, register_and_login, send_json, AuthResponse};
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        deleted_message_retention: Duration::from_secs(3600),
+        ..common::test_config()
+    }
+}
+
+async fn history_contents(
+    app: &axum::Router,
+    uri: String,
+    auth: &AuthResponse,
+    ip: &str,
+) -> Vec<(String, bool)> {
+    let response = send_json(app, "GET", uri, Some(&auth.access_token), ip, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let page: Value = parse_json_body(response).await;
+    page["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| {
+            (
+                message["content"].as_str().unwrap().to_owned(),
+                message
+                    .get("deleted")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+#[allow(clippy::too_many_lines)]
+async fn deleted_messages_are_tombstoned_and_restorable_by_moderators() {
+    let app = in_memory_app_with(&test_config());
+    let owner = register_and_login(&app, "tomb_owner", "203.0.113.161").await;
+    let member = register_and_login(&app, "tomb_member", "203.0.113.162").await;
+    let me = send_json(
+        &app,
+        "GET",
+        String::from("/auth/me"),
+        Some(&member.access_token),
+        "203.0.113.162",
+        None,
+    )
+    .await;
+    let me_json: Value = parse_json_body(me).await;
+    let member_id = me_json["user_id"].as_str().unwrap().to_owned();
+
+    let guild = send_json(
+        &app,
+        "POST",
+        String::from("/guilds"),
+        Some(&owner.access_token),
+        "203.0.113.161",
+        Some(json!({"name":"Tombstone Guild"})),
+    )
+    .await;
+    let guild_json: Value = parse_json_body(guild).await;
+    let guild_id = guild_json["guild_id"].as_str().unwrap().to_owned();
+    let channel = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        "203.0.113.161",
+        Some(json!({"name":"tombstones"})),
+    )
+    .await;
+    let channel_json: Value = parse_json_body(channel).await;
+    let channel_id = channel_json["channel_id"].as_str().unwrap().to_owned();
+    let add_member = send_json(
+        &app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_id}"),
+        Some(&owner.access_token),
+        "203.0.113.161",
+        None,
+    )
+    .await;
+    assert_eq!(add_member.status(), StatusCode::OK);
+
+    let messages_uri = format!("/guilds/{guild_id}/channels/{channel_id}/messages");
+    let mut message_ids = Vec::new();
+    for content in ["keep", "remove"] {
+        let created = send_json(
+            &app,
+            "POST",
+            messages_uri.clone(),
+            Some(&member.access_token),
+            "203.0.113.162",
+            Some(json!({"content":content})),
+        )
+        .await;
+        assert_eq!(created.status(), StatusCode::OK);
+        let created_json: Value = parse_json_body(created).await;
+        message_ids.push(created_json["message_id"].as_str().unwrap().to_owned());
+    }
+    let removed_uri = format!("{messages_uri}/{}", message_ids[1]);
+
+    let deleted = send_json(
+        &app,
+        "DELETE",
+        removed_uri.clone(),
+        Some(&member.access_token),
+        "203.0.113.162",
+        None,
+    )
+    .await;
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        history_contents(&app, messages_uri.clone(), &member, "203.0.113.162").await,
+        [(String::from("keep"), false)]
+    );
+
+    let include_deleted_uri = format!("{messages_uri}?include_deleted=true");
+    let forbidden = send_json(
+        &app,
+        "GET",
+        include_deleted_uri.clone(),
+        Some(&member.access_token),
+        "203.0.113.162",
+        None,
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    let mut with_deleted =
+        history_contents(&app, include_deleted_uri, &owner, "203.0.113.161").await;
+    with_deleted.sort();
+    assert_eq!(
+        with_deleted,
+        [
+            (String::from("keep"), false),
+            (String::from("remove"), true)
+        ]
+    );
+
+    let edit = send_json(
+        &app,
+        "PATCH",
+        removed_uri.clone(),
+        Some(&member.access_token),
+        "203.0.113.162",
+        Some(json!({"content":"edited"})),
+    )
+    .await;
+    assert_eq!(edit.status(), StatusCode::NOT_FOUND);
+    let deleted_again = send_json(
+        &app,
+        "DELETE",
+        removed_uri.clone(),
+        Some(&member.access_token),
+        "203.0.113.162",
+        None,
+    )
+    .await;
+    assert_eq!(deleted_again.status(), StatusCode::NOT_FOUND);
+
+    let restore_uri = format!("{removed_uri}/restore");
+    let member_restore = send_json(
+        &app,
+        "POST",
+        restore_uri.clone(),
+        Some(&member.access_token),
+        "203.0.113.162",
+        None,
+    )
+    .await;
+    assert_eq!(member_restore.status(), StatusCode::FORBIDDEN);
+    let restored = send_json(
+        &app,
+        "POST",
+        restore_uri.clone(),
+        Some(&owner.access_token),
+        "203.0.113.161",
+        None,
+    )
+    .await;
+    assert_eq!(restored.status(), StatusCode::OK);
+    let restored_json: Value = parse_json_body(restored).await;
+    assert_eq!(restored_json["content"], "remove");
+    assert!(restored_json.get("deleted").is_none());
+    let mut history = history_contents(&app, messages_uri, &member, "203.0.113.162").await;
+    history.sort();
+    assert_eq!(
+        history,
+        [
+            (String::from("keep"), false),
+            (String::from("remove"), false)
+        ]
+    );
+
+    let restore_live = send_json(
+        &app,
+        "POST",
+        restore_uri,
+        Some(&owner.access_token),
+        "203.0.113.161",
+        None,
+    )
+    .await;
+    assert_eq!(restore_live.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+const OWNER_IP: &str = "203.0.113.191";
+const MEMBER_IP: &str = "203.0.113.192";
+const OUTSIDER_IP: &str = "203.0.113.193";
+
+struct InboxGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    outsider: AuthResponse,
+    owner_user_id: String,
+    guild_id: String,
+    channel_id: String,
+}
+
+impl InboxGuild {
+    async fn post(&self, app: &axum::Router, content: &str) -> String {
+        post_message(
+            app,
+            &self.owner,
+            OWNER_IP,
+            &self.guild_id,
+            &self.channel_id,
+            content,
+        )
+        .await
+    }
+
+    /// Mentions everyone and returns the id of the member's notification.
+    async fn mention_member(&self, app: &axum::Router) -> String {
+        self.post(app, "@everyone while you were away").await;
+        let inbox = list_inbox(app, &self.member, MEMBER_IP, "").await;
+        inbox["notifications"][0]["notification_id"]
+            .as_str()
+            .unwrap()
+            .to_owned()
+    }
+}
+
+async fn setup_inbox_guild(app: &axum::Router) -> InboxGuild {
+    let owner = register_and_login(app, "inbox_owner", OWNER_IP).await;
+    let member = register_and_login(app, "inbox_member", MEMBER_IP).await;
+    let outsider = register_and_login(app, "inbox_out", OUTSIDER_IP).await;
+    let owner_user_id = user_id(app, &owner, OWNER_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, OWNER_IP, "Inbox Guild", "inbox-chat").await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+    InboxGuild {
+        owner,
+        member,
+        outsider,
+        owner_user_id,
+        guild_id,
+        channel_id,
+    }
+}
+
+async fn list_inbox(app: &axum::Router, auth: &AuthResponse, ip: &str, query: &str) -> Value {
+    let response = send_json(
+        app,
+        "GET",
+        format!("/notifications{query}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    parse_json_body(response).await
+}
+
+async fn mark_read(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    notification_ids: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        String::from("/notifications/read"),
+        Some(&auth.access_token),
+        ip,
+        Some(json!({ "notification_ids": notification_ids })),
+    )
+    .await
+}
+
+async fn deliver_everyone_mention(app: &axum::Router) {
+    let guild = setup_inbox_guild(app).await;
+
+    // Nobody has a gateway connection, so `@here` reaches no one.
+    guild.post(app, "@here anyone?").await;
+    guild.post(app, "no mention").await;
+    let message_id = guild.post(app, "@everyone while you were away").await;
+
+    let inbox = list_inbox(app, &guild.member, MEMBER_IP, "").await;
+    assert_eq!(inbox["unread_count"], 1);
+    let notifications = inbox["notifications"].as_array().unwrap();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["kind"], "mention");
+    assert_eq!(notifications[0]["scope"], "everyone");
+    assert_eq!(notifications[0]["guild_id"], guild.guild_id.as_str());
+    assert_eq!(notifications[0]["channel_id"], guild.channel_id.as_str());
+    assert_eq!(notifications[0]["message_id"], message_id.as_str());
+    assert_eq!(notifications[0]["author_id"], guild.owner_user_id.as_str());
+    assert_eq!(notifications[0]["read"], false);
+
+    let owner_inbox = list_inbox(app, &guild.owner, OWNER_IP, "").await;
+    assert_eq!(owner_inbox["unread_count"], 0);
+    assert!(owner_inbox["notifications"].as_array().unwrap().is_empty());
+    let outsider_inbox = list_inbox(app, &guild.outsider, OUTSIDER_IP, "").await;
+    assert!(outsider_inbox["notifications"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+async fn mark_notification_read(app: &axum::Router) {
+    let guild = setup_inbox_guild(app).await;
+    let notification_id = guild.mention_member(app).await;
+
+    let marked = mark_read(app, &guild.member, MEMBER_IP, json!([notification_id])).await;
+    assert_eq!(marked.status(), StatusCode::NO_CONTENT);
+    let inbox = list_inbox(app, &guild.member, MEMBER_IP, "").await;
+    assert_eq!(inbox["unread_count"], 0);
+    assert_eq!(inbox["notifications"][0]["read"], true);
+    let unread = list_inbox(app, &guild.member, MEMBER_IP, "?unread_only=true").await;
+    assert!(unread["notifications"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn everyone_mentions_reach_only_other_members_inboxes() {
+    deliver_everyone_mention(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_everyone_mentions_reach_only_other_members_inboxes() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    deliver_everyone_mention(&app).await;
+}
+
+#[tokio::test]
+async fn read_notifications_leave_the_unread_count_and_filter() {
+    mark_notification_read(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_read_notifications_leave_the_unread_count_and_filter() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    mark_notification_read(&app).await;
+}
+
+#[tokio::test]
+async fn marking_another_users_notification_is_ignored() {
+    let app = in_memory_app();
+    let guild = setup_inbox_guild(&app).await;
+    let notification_id = guild.mention_member(&app).await;
+
+    let foreign_mark =
+        mark_read(&app, &guild.outsider, OUTSIDER_IP, json!([notification_id])).await;
+    assert_eq!(foreign_mark.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        list_inbox(&app, &guild.member, MEMBER_IP, "").await["unread_count"],
+        1
+    );
+}
+
+#[tokio::test]
+async fn notification_requests_reject_bad_limits_cursors_and_ids() {
+    let app = in_memory_app();
+    let member = register_and_login(&app, "inbox_member", MEMBER_IP).await;
+
+    for query in ["?limit=0", "?limit=101", "?before=not-a-ulid"] {
+        let response = send_json(
+            &app,
+            "GET",
+            format!("/notifications{query}"),
+            Some(&member.access_token),
+            MEMBER_IP,
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{query}");
+    }
+    for ids in [json!([]), json!(["not-a-ulid"])] {
+        let response = mark_read(&app, &member, MEMBER_IP, ids.clone()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{ids}");
+    }
+}
+
+#[tokio::test]
+async fn notifications_require_authentication() {
+    let app = in_memory_app();
+    let anonymous = send_json(
+        &app,
+        "GET",
+        String::from("/notifications"),
+        None,
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+}
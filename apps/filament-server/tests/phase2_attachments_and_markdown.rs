@@ -252,6 +252,306 @@ async fn attachment_upload_rejects_payloads_over_configured_limit() {
     assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
 }
 
+#[tokio::test]
+async fn attachment_upload_uses_configured_sharded_key_layout() {
+    let root = attachment_root();
+    let app = build_router(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        attachment_root: root.clone(),
+        attachment_key_prefix: String::from("blobs/uploads"),
+        attachment_key_shard_chars: 3,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "phase2_sharded", "203.0.113.78").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.78").await;
+
+    let upload = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/guilds/{}/channels/{}/attachments?filename=sharded.gif",
+            channel.guild_id, channel.channel_id
+        ))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", "image/gif")
+        .header("x-forwarded-for", "203.0.113.78")
+        .body(Body::from(GIF_1X1.to_vec()))
+        .expect("upload request should build");
+    let upload_response = app.clone().oneshot(upload).await.unwrap();
+    assert_eq!(upload_response.status(), StatusCode::OK);
+    let uploaded_json: Value = parse_json_body(upload_response).await;
+    let attachment_id = uploaded_json["attachment_id"].as_str().unwrap().to_owned();
+
+    let shard = &attachment_id[attachment_id.len() - 3..];
+    let stored = root
+        .join("blobs")
+        .join("uploads")
+        .join(shard)
+        .join(&attachment_id);
+    assert_eq!(
+        std::fs::read(stored).expect("object should be sharded"),
+        GIF_1X1
+    );
+
+    let download = Request::builder()
+        .method("GET")
+        .uri(format!(
+            "/guilds/{}/channels/{}/attachments/{}",
+            channel.guild_id, channel.channel_id, attachment_id
+        ))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("x-forwarded-for", "203.0.113.78")
+        .body(Body::empty())
+        .expect("download request should build");
+    let download_response = app.oneshot(download).await.unwrap();
+    assert_eq!(download_response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn attachment_download_rejects_corrupted_object_when_verification_enabled() {
+    let root = attachment_root();
+    let app = build_router(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        attachment_root: root.clone(),
+        attachment_key_shard_chars: 0,
+        attachment_verify_integrity: true,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "phase2_integrity", "203.0.113.79").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.79").await;
+
+    let upload = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/guilds/{}/channels/{}/attachments?filename=intact.gif",
+            channel.guild_id, channel.channel_id
+        ))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("content-type", "image/gif")
+        .header("x-forwarded-for", "203.0.113.79")
+        .body(Body::from(GIF_1X1.to_vec()))
+        .expect("upload request should build");
+    let upload_response = app.clone().oneshot(upload).await.unwrap();
+    assert_eq!(upload_response.status(), StatusCode::OK);
+    let uploaded_json: Value = parse_json_body(upload_response).await;
+    let attachment_id = uploaded_json["attachment_id"].as_str().unwrap().to_owned();
+
+    let download = || {
+        Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/guilds/{}/channels/{}/attachments/{}",
+                channel.guild_id, channel.channel_id, attachment_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("x-forwarded-for", "203.0.113.79")
+            .body(Body::empty())
+            .expect("download request should build")
+    };
+    let intact_response = app.clone().oneshot(download()).await.unwrap();
+    assert_eq!(intact_response.status(), StatusCode::OK);
+
+    let mut corrupted = GIF_1X1.to_vec();
+    corrupted[10] ^= 0xff;
+    std::fs::write(root.join("attachments").join(&attachment_id), corrupted)
+        .expect("object should be overwritable");
+
+    let corrupted_response = app.oneshot(download()).await.unwrap();
+    assert_eq!(
+        corrupted_response.status(),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+    let error_json: Value = parse_json_body(corrupted_response).await;
+    assert_eq!(error_json["error"], "attachment_integrity_mismatch");
+}
+
+#[tokio::test]
+async fn attachment_upload_returns_sanitized_filename_with_extension() {
+    let app = test_app();
+
+    for (username, encoded_name, expected) in [
+        ("phase2_names_bidi", "evil%E2%80%AEfig.exe", "evilfig.exe"),
+        ("phase2_names_bare", "holiday%E2%80%8B", "holiday.gif"),
+    ] {
+        // Each upload uses its own account to stay under the tiny test quota.
+        let auth = register_and_login(&app, username, "203.0.113.79").await;
+        let channel = create_channel_context(&app, &auth, "203.0.113.79").await;
+        let upload = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/guilds/{}/channels/{}/attachments?filename={encoded_name}",
+                channel.guild_id, channel.channel_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("content-type", "image/gif")
+            .header("x-forwarded-for", "203.0.113.79")
+            .body(Body::from(GIF_1X1.to_vec()))
+            .expect("upload request should build");
+        let response = app.clone().oneshot(upload).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let uploaded_json: Value = parse_json_body(response).await;
+        assert_eq!(uploaded_json["filename"], expected);
+    }
+}
+
+#[tokio::test]
+async fn attachment_upload_accepts_declared_text_and_rejects_svg() {
+    let app = test_app();
+    let auth = register_and_login(&app, "phase2_text", "203.0.113.80").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.80").await;
+
+    let mut statuses = Vec::new();
+    for (filename, content_type, body) in [
+        (
+            "drawing.svg",
+            "image/svg+xml",
+            &b"<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>"[..],
+        ),
+        ("notes.txt", "text/plain", &b"<svg onload=alert(1)>"[..]),
+        ("notes", "text/plain; charset=utf-8", &b"meeting notes"[..]),
+    ] {
+        let upload = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/guilds/{}/channels/{}/attachments?filename={filename}",
+                channel.guild_id, channel.channel_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("content-type", content_type)
+            .header("x-forwarded-for", "203.0.113.80")
+            .body(Body::from(body.to_vec()))
+            .expect("upload request should build");
+        let response = app.clone().oneshot(upload).await.unwrap();
+        let status = response.status();
+        if status == StatusCode::OK {
+            let uploaded_json: Value = parse_json_body(response).await;
+            assert_eq!(uploaded_json["mime_type"], "text/plain");
+            assert_eq!(uploaded_json["filename"], "notes.txt");
+        }
+        statuses.push(status);
+    }
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::BAD_REQUEST,
+            StatusCode::BAD_REQUEST,
+            StatusCode::OK
+        ]
+    );
+}
+
+#[test]
+fn mime_sniff_window_is_bounded() {
+    for mime_sniff_bytes in [511, 1024 * 1024 + 1] {
+        assert!(build_router(&AppConfig {
+            mime_sniff_bytes,
+            ..AppConfig::default()
+        })
+        .is_err());
+    }
+}
+
+#[tokio::test]
+async fn attachment_upload_requires_declared_type_for_ambiguous_archives() {
+    let app = test_app();
+    let auth = register_and_login(&app, "phase2_zip", "203.0.113.81").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.81").await;
+    let zip_header = b"PK\x03\x04\x14\0\0\0\x08\0".to_vec();
+
+    let mut statuses = Vec::new();
+    for content_type in [None, Some("application/zip")] {
+        let mut upload = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/guilds/{}/channels/{}/attachments?filename=archive.zip",
+                channel.guild_id, channel.channel_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("x-forwarded-for", "203.0.113.81");
+        if let Some(content_type) = content_type {
+            upload = upload.header("content-type", content_type);
+        }
+        let upload = upload
+            .body(Body::from(zip_header.clone()))
+            .expect("upload request should build");
+        statuses.push(app.clone().oneshot(upload).await.unwrap().status());
+    }
+    assert_eq!(statuses, [StatusCode::BAD_REQUEST, StatusCode::OK]);
+}
+
+#[tokio::test]
+async fn message_creation_rejects_attachments_over_combined_size_limit() {
+    let app = build_router(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        attachment_root: attachment_root(),
+        max_message_attachment_total_bytes: 64,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "phase2_total_size", "203.0.113.80").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.80").await;
+
+    let mut attachment_ids = Vec::new();
+    for name in ["first.gif", "second.gif"] {
+        let upload = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/guilds/{}/channels/{}/attachments?filename={name}",
+                channel.guild_id, channel.channel_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("content-type", "image/gif")
+            .header("x-forwarded-for", "203.0.113.80")
+            .body(Body::from(GIF_1X1.to_vec()))
+            .expect("upload request should build");
+        let upload_response = app.clone().oneshot(upload).await.unwrap();
+        assert_eq!(upload_response.status(), StatusCode::OK);
+        let uploaded_json: Value = parse_json_body(upload_response).await;
+        attachment_ids.push(uploaded_json["attachment_id"].as_str().unwrap().to_owned());
+    }
+
+    let create_message = |ids: &[String]| {
+        Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/guilds/{}/channels/{}/messages",
+                channel.guild_id, channel.channel_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("content-type", "application/json")
+            .header("x-forwarded-for", "203.0.113.80")
+            .body(Body::from(
+                json!({"content": "", "attachment_ids": ids}).to_string(),
+            ))
+            .expect("message request should build")
+    };
+
+    let over_limit = app
+        .clone()
+        .oneshot(create_message(&attachment_ids))
+        .await
+        .unwrap();
+    assert_eq!(over_limit.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let error_json: Value = parse_json_body(over_limit).await;
+    assert_eq!(error_json["error"], "message_attachments_too_large");
+
+    let within_limit = app
+        .oneshot(create_message(&attachment_ids[..1]))
+        .await
+        .unwrap();
+    assert_eq!(within_limit.status(), StatusCode::OK);
+}
+
 #[tokio::test]
 async fn message_creation_binds_attachments_and_deletes_media_on_message_delete() {
     let app = test_app();
@@ -463,5 +763,5 @@ async fn moderation_routes_enforce_membership_state() {
         .body(Body::from(json!({"content":"hi"}).to_string()))
         .expect("message request should build");
     let target_message_response = app.oneshot(target_message).await.unwrap();
-    assert_eq!(target_message_response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(target_message_response.status(), StatusCode::NOT_FOUND);
 }
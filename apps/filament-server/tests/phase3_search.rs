@@ -23,6 +23,7 @@ fn test_app() -> axum::Router {
         request_timeout: Duration::from_secs(2),
         rate_limit_requests_per_minute: 200,
         auth_route_requests_per_minute: 200,
+        search_sync_index_writes: true,
         ..AppConfig::default()
     })
     .expect("router should build")
@@ -336,8 +337,187 @@ async fn search_query_abuse_limits_are_enforced() {
         .header("x-forwarded-for", "203.0.113.81")
         .body(Body::empty())
         .expect("field-query search request should build");
-    let field_query_response = app.oneshot(field_query_request).await.unwrap();
+    let field_query_response = app.clone().oneshot(field_query_request).await.unwrap();
     assert_eq!(field_query_response.status(), StatusCode::BAD_REQUEST);
+
+    for (sort, expected) in [
+        ("recent", StatusCode::OK),
+        ("relevance", StatusCode::OK),
+        ("oldest", StatusCode::BAD_REQUEST),
+    ] {
+        let sort_request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/guilds/{}/search?q=hello&sort={sort}",
+                channel.guild_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("x-forwarded-for", "203.0.113.81")
+            .body(Body::empty())
+            .expect("sorted search request should build");
+        let sort_response = app.clone().oneshot(sort_request).await.unwrap();
+        assert_eq!(sort_response.status(), expected, "sort {sort}");
+    }
+}
+
+#[tokio::test]
+async fn search_includes_author_usernames_only_when_requested() {
+    let app = test_app();
+    let auth = register_and_login(&app, "phase3_author_names", "203.0.113.83").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.83").await;
+    let _ = create_message(
+        &app,
+        &auth,
+        &channel,
+        "203.0.113.83",
+        "phase3 author lookup needle",
+    )
+    .await;
+
+    let plain = search(&app, &auth, &channel.guild_id, "needle").await;
+    assert!(plain["messages"][0].get("author_username").is_none());
+
+    let with_names = search(
+        &app,
+        &auth,
+        &channel.guild_id,
+        "needle&include_author_usernames=true",
+    )
+    .await;
+    assert_eq!(
+        with_names["messages"][0]["author_username"],
+        "phase3_author_names"
+    );
+}
+
+#[tokio::test]
+async fn search_returns_ids_only_when_hydration_is_disabled() {
+    let app = test_app();
+    let auth = register_and_login(&app, "phase3_ids_only", "203.0.113.84").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.84").await;
+    let message_id = create_message(
+        &app,
+        &auth,
+        &channel,
+        "203.0.113.84",
+        "phase3 lazy hydration needle",
+    )
+    .await;
+
+    let ids_only = search(&app, &auth, &channel.guild_id, "needle&hydrate=false").await;
+    assert_eq!(ids_only["message_ids"], json!([message_id]));
+    assert!(ids_only.get("messages").is_none());
+
+    let hydrated = search(&app, &auth, &channel.guild_id, "needle&hydrate=true").await;
+    assert_eq!(hydrated["messages"][0]["message_id"], message_id);
+}
+
+#[tokio::test]
+async fn search_reports_channel_facets_only_when_requested() {
+    let app = test_app();
+    let auth = register_and_login(&app, "phase3_facets", "203.0.113.87").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.87").await;
+    for content in [
+        "phase3 facet needle one",
+        "phase3 facet needle two",
+        "phase3 facet haystack",
+    ] {
+        let _ = create_message(&app, &auth, &channel, "203.0.113.87", content).await;
+    }
+
+    let plain = search(&app, &auth, &channel.guild_id, "needle").await;
+    assert!(plain.get("facets").is_none());
+
+    let faceted = search(&app, &auth, &channel.guild_id, "needle&facets=true&limit=1").await;
+    assert_eq!(faceted["message_ids"].as_array().unwrap().len(), 1);
+    assert_eq!(faceted["facets"], json!({ channel.channel_id.clone(): 2 }));
+}
+
+#[tokio::test]
+async fn queued_index_writes_become_searchable_without_sync_apply() {
+    let app = build_router(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        search_requests_per_minute: 100,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "phase3_async_index", "203.0.113.86").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.86").await;
+    let _ = search(&app, &auth, &channel.guild_id, "warmup").await;
+    let message_id = create_message(
+        &app,
+        &auth,
+        &channel,
+        "203.0.113.86",
+        "phase3 queued needle",
+    )
+    .await;
+
+    let mut found = false;
+    for _ in 0..40 {
+        let result = search(&app, &auth, &channel.guild_id, "queued").await;
+        if result["message_ids"] == json!([message_id]) {
+            found = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    assert!(found, "queued index write should be applied");
+}
+
+#[tokio::test]
+async fn search_routes_are_not_found_when_search_is_disabled() {
+    let app = build_router(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        search_enabled: false,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "phase3_search_off", "203.0.113.85").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.85").await;
+    let _ = create_message(
+        &app,
+        &auth,
+        &channel,
+        "203.0.113.85",
+        "phase3 unindexed needle",
+    )
+    .await;
+
+    for (method, uri) in [
+        (
+            "GET",
+            format!("/guilds/{}/search?q=needle", channel.guild_id),
+        ),
+        (
+            "POST",
+            format!("/guilds/{}/search/reconcile", channel.guild_id),
+        ),
+        (
+            "POST",
+            format!("/guilds/{}/search/rebuild", channel.guild_id),
+        ),
+    ] {
+        let request = Request::builder()
+            .method(method)
+            .uri(&uri)
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("x-forwarded-for", "203.0.113.85")
+            .body(Body::empty())
+            .expect("search request should build");
+        let response = app
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("search request should execute");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{method} {uri}");
+    }
 }
 
 #[tokio::test]
@@ -358,3 +538,78 @@ async fn search_reconcile_reports_noop_when_index_is_consistent() {
     assert_eq!(reconcile_json["upserted"], 0);
     assert_eq!(reconcile_json["deleted"], 0);
 }
+
+#[tokio::test]
+async fn search_requests_are_rate_limited_per_user_and_guild() {
+    let app = build_router(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        search_requests_per_minute: 2,
+        ..AppConfig::default()
+    })
+    .expect("router should build");
+    let auth = register_and_login(&app, "phase3_search_rate", "203.0.113.80").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.80").await;
+
+    let _ = search(&app, &auth, &channel.guild_id, "needle").await;
+    let _ = search(&app, &auth, &channel.guild_id, "needle").await;
+
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!("/guilds/{}/search?q=needle", channel.guild_id))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("x-forwarded-for", "203.0.113.80")
+        .body(Body::empty())
+        .expect("search request should build");
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response
+        .headers()
+        .get("retry-after")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .expect("rate-limited search should carry retry-after");
+    assert!((1..=60).contains(&retry_after));
+    let payload: Value = parse_json_body(response).await;
+    assert_eq!(payload["error"], "rate_limited");
+}
+
+#[test]
+fn zero_search_limits_are_rejected() {
+    assert!(build_router(&AppConfig {
+        search_requests_per_minute: 0,
+        ..AppConfig::default()
+    })
+    .is_err());
+    assert!(build_router(&AppConfig {
+        search_max_concurrent_queries: 0,
+        ..AppConfig::default()
+    })
+    .is_err());
+    assert!(build_router(&AppConfig {
+        search_reconcile_batch_docs: 0,
+        ..AppConfig::default()
+    })
+    .is_err());
+}
+
+#[test]
+fn search_index_limits_are_bounded() {
+    assert!(build_router(&AppConfig {
+        search_reconcile_batch_docs: 1_000_001,
+        ..AppConfig::default()
+    })
+    .is_err());
+    assert!(build_router(&AppConfig {
+        search_writer_heap_bytes: 1_000_000,
+        ..AppConfig::default()
+    })
+    .is_err());
+    assert!(build_router(&AppConfig {
+        search_writer_heap_bytes: 2_000_000_000,
+        ..AppConfig::default()
+    })
+    .is_err());
+}
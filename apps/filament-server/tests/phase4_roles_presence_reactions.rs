@@ -330,6 +330,115 @@ async fn roles_overrides_and_reactions_enforce_privilege_boundaries() {
     assert_eq!(reaction_deleted_json["count"], 0);
 }
 
+async fn history_reactions(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    channel: &ChannelRef,
+) -> Vec<Value> {
+    let history = Request::builder()
+        .method("GET")
+        .uri(format!(
+            "/guilds/{}/channels/{}/messages",
+            channel.guild_id, channel.channel_id
+        ))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("x-forwarded-for", ip)
+        .body(Body::empty())
+        .expect("history request should build");
+    let history_response = app.clone().oneshot(history).await.unwrap();
+    assert_eq!(history_response.status(), StatusCode::OK);
+    let history_json: Value = parse_json_body(history_response).await;
+    history_json["messages"][0]["reactions"]
+        .as_array()
+        .expect("reactions should be an array")
+        .clone()
+}
+
+fn reaction_view(reactions: &[Value]) -> Vec<(String, u64, bool)> {
+    reactions
+        .iter()
+        .map(|reaction| {
+            (
+                reaction["emoji"].as_str().unwrap().to_owned(),
+                reaction["count"].as_u64().unwrap(),
+                reaction["reacted_by_me"].as_bool().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn message_history_marks_reactions_by_the_viewer() {
+    let app = test_app();
+    let owner = register_and_login(&app, "phase4_viewer_owner", "203.0.113.94").await;
+    let member = register_and_login(&app, "phase4_viewer_member", "203.0.113.95").await;
+    let member_id = user_id_from_me(&app, &member, "203.0.113.95").await;
+    let channel = create_channel_context(&app, &owner, "203.0.113.94", "Viewer Guild").await;
+
+    let add_member = Request::builder()
+        .method("POST")
+        .uri(format!("/guilds/{}/members/{member_id}", channel.guild_id))
+        .header("authorization", format!("Bearer {}", owner.access_token))
+        .header("x-forwarded-for", "203.0.113.94")
+        .body(Body::empty())
+        .expect("add member request should build");
+    let add_member_response = app.clone().oneshot(add_member).await.unwrap();
+    assert_eq!(add_member_response.status(), StatusCode::OK);
+
+    let message = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/guilds/{}/channels/{}/messages",
+            channel.guild_id, channel.channel_id
+        ))
+        .header("authorization", format!("Bearer {}", owner.access_token))
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", "203.0.113.94")
+        .body(Body::from(json!({"content":"react to me"}).to_string()))
+        .expect("message request should build");
+    let message_response = app.clone().oneshot(message).await.unwrap();
+    assert_eq!(message_response.status(), StatusCode::OK);
+    let message_json: Value = parse_json_body(message_response).await;
+    let message_id = message_json["message_id"].as_str().unwrap().to_owned();
+
+    for (auth, ip, emoji) in [
+        (&owner, "203.0.113.94", "%F0%9F%91%8D"),
+        (&member, "203.0.113.95", "%F0%9F%91%8D"),
+        (&member, "203.0.113.95", "%F0%9F%8E%89"),
+    ] {
+        let reaction = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/guilds/{}/channels/{}/messages/{message_id}/reactions/{emoji}",
+                channel.guild_id, channel.channel_id
+            ))
+            .header("authorization", format!("Bearer {}", auth.access_token))
+            .header("x-forwarded-for", ip)
+            .body(Body::empty())
+            .expect("reaction request should build");
+        let reaction_response = app.clone().oneshot(reaction).await.unwrap();
+        assert_eq!(reaction_response.status(), StatusCode::OK);
+    }
+
+    let owner_view = history_reactions(&app, &owner, "203.0.113.94", &channel).await;
+    assert_eq!(
+        reaction_view(&owner_view),
+        [
+            (String::from("\u{1f389}"), 1, false),
+            (String::from("\u{1f44d}"), 2, true),
+        ]
+    );
+    let member_view = history_reactions(&app, &member, "203.0.113.95", &channel).await;
+    assert_eq!(
+        reaction_view(&member_view),
+        [
+            (String::from("\u{1f389}"), 1, true),
+            (String::from("\u{1f44d}"), 2, true),
+        ]
+    );
+}
+
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn presence_events_do_not_leak_across_guilds() {
@@ -714,6 +714,11 @@ async fn postgres_backed_user_lookup_batch_endpoint_returns_valid_users() {
     assert_eq!(users.len(), 2);
     assert_eq!(users[0]["username"], alice_username);
     assert_eq!(users[1]["username"], bob_username);
+    assert!(users[0]["created_at_unix"].as_i64().unwrap() > 0);
+    assert_eq!(
+        users[0]["created_at_unix"],
+        alice_profile["created_at_unix"]
+    );
 
     let unauthorized_request = Request::builder()
         .method("POST")
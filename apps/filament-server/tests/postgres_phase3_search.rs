@@ -32,6 +32,7 @@ async fn test_app(database_url: String) -> axum::Router {
         request_timeout: Duration::from_secs(2),
         rate_limit_requests_per_minute: 200,
         auth_route_requests_per_minute: 200,
+        search_sync_index_writes: true,
         database_url: Some(database_url),
         ..AppConfig::default()
     })
@@ -267,6 +268,8 @@ async fn postgres_search_reconcile_repairs_missing_and_orphan_docs() {
     let before_ids = extract_ids(&before_mutation, "message_ids");
     assert!(before_ids.contains(&baseline_id));
     assert!(before_ids.contains(&orphan_id));
+    let faceted = search(&app, &auth, &channel.guild_id, "needle&facets=true").await;
+    assert_eq!(faceted["facets"][&channel.channel_id], 2);
 
     let missing_id = Ulid::new().to_string();
     let created_at_unix = i64::try_from(
@@ -315,3 +318,259 @@ async fn postgres_search_reconcile_repairs_missing_and_orphan_docs() {
     assert!(!repaired_message_ids.contains(&orphan_id));
     assert!(repaired_hydrated_ids.contains(&missing_id));
 }
+
+async fn channel_history(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    channel: &ChannelRef,
+    ip: &str,
+) -> Value {
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!(
+            "/guilds/{}/channels/{}/messages?limit=10",
+            channel.guild_id, channel.channel_id
+        ))
+        .header("authorization", format!("Bearer {}", auth.access_token))
+        .header("x-forwarded-for", ip)
+        .body(Body::empty())
+        .expect("history request should build");
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("history request should execute");
+    assert_eq!(response.status(), StatusCode::OK);
+    parse_json_body(response).await
+}
+
+/// Inserts a plaintext row the way servers wrote messages before
+/// `content_zstd` existed.
+async fn insert_legacy_message(
+    db_pool: &PgPool,
+    channel: &ChannelRef,
+    author_id: &str,
+    content: &str,
+) -> String {
+    let message_id = Ulid::new().to_string();
+    let created_at_unix = i64::try_from(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time should be valid")
+            .as_secs(),
+    )
+    .expect("timestamp should fit i64");
+    sqlx::query(
+        "INSERT INTO messages (message_id, guild_id, channel_id, author_id, content, created_at_unix)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(&message_id)
+    .bind(&channel.guild_id)
+    .bind(&channel.channel_id)
+    .bind(author_id)
+    .bind(content)
+    .bind(created_at_unix)
+    .execute(db_pool)
+    .await
+    .expect("legacy message insert should succeed");
+    message_id
+}
+
+#[tokio::test]
+async fn postgres_compressed_message_content_round_trips_through_history_and_search() {
+    let Some(database_url) = postgres_url() else {
+        eprintln!("skipping postgres-backed search test: FILAMENT_TEST_DATABASE_URL is unset");
+        return;
+    };
+
+    let app = build_router_with_db_bootstrap(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        search_sync_index_writes: true,
+        database_url: Some(database_url.clone()),
+        message_content_compression: true,
+        ..AppConfig::default()
+    })
+    .await
+    .expect("router should build");
+    let db_pool = PgPool::connect(&database_url)
+        .await
+        .expect("postgres pool should connect");
+
+    let suffix = Ulid::new().to_string().to_lowercase();
+    let username = format!("pg_zstd_{}", &suffix[..16]);
+    let auth = register_and_login(&app, &username, "203.0.113.92").await;
+    let author_id = current_user_id(&app, &auth, "203.0.113.92").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.92").await;
+
+    let long_content = format!(
+        "zstdneedle {}",
+        "compressed message content survives the round trip. ".repeat(8)
+    );
+    let compressed_id = create_message(&app, &auth, &channel, "203.0.113.92", &long_content).await;
+    let short_id = create_message(&app, &auth, &channel, "203.0.113.92", "zstdneedle short").await;
+
+    let stored: (String, Option<Vec<u8>>) =
+        sqlx::query_as("SELECT content, content_zstd FROM messages WHERE message_id = $1")
+            .bind(&compressed_id)
+            .fetch_one(&db_pool)
+            .await
+            .expect("compressed row should exist");
+    assert!(stored.0.is_empty());
+    assert!(stored
+        .1
+        .is_some_and(|bytes| bytes.len() < long_content.len()));
+
+    let bootstrapped = search(&app, &auth, &channel.guild_id, "zstdneedle").await;
+    let hydrated = bootstrapped["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|message| message["message_id"] == compressed_id.as_str())
+        .expect("compressed message should hydrate");
+    assert_eq!(hydrated["content"], long_content.as_str());
+
+    let legacy_id = insert_legacy_message(
+        &db_pool,
+        &channel,
+        &author_id,
+        "zstdneedle legacy plaintext row",
+    )
+    .await;
+
+    let history_json = channel_history(&app, &auth, &channel, "203.0.113.92").await;
+    let content_by_id = |id: &str| {
+        history_json["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|message| message["message_id"] == id)
+            .and_then(|message| message["content"].as_str().map(ToOwned::to_owned))
+    };
+    assert_eq!(
+        content_by_id(&compressed_id).as_deref(),
+        Some(long_content.as_str())
+    );
+    assert_eq!(
+        content_by_id(&short_id).as_deref(),
+        Some("zstdneedle short")
+    );
+    assert_eq!(
+        content_by_id(&legacy_id).as_deref(),
+        Some("zstdneedle legacy plaintext row")
+    );
+
+    let reconcile_result = reconcile(&app, &auth, &channel.guild_id).await;
+    assert_eq!(reconcile_result["upserted"], 1);
+
+    let results = search(&app, &auth, &channel.guild_id, "zstdneedle").await;
+    let message_ids = extract_ids(&results, "message_ids");
+    assert!(message_ids.contains(&compressed_id));
+    assert!(message_ids.contains(&short_id));
+    assert!(message_ids.contains(&legacy_id));
+}
+
+#[tokio::test]
+async fn postgres_sealed_message_content_round_trips_through_history_and_search() {
+    let Some(database_url) = postgres_url() else {
+        eprintln!("skipping postgres-backed search test: FILAMENT_TEST_DATABASE_URL is unset");
+        return;
+    };
+
+    let app = build_router_with_db_bootstrap(&AppConfig {
+        max_body_bytes: 1024 * 64,
+        request_timeout: Duration::from_secs(2),
+        rate_limit_requests_per_minute: 200,
+        auth_route_requests_per_minute: 200,
+        search_sync_index_writes: true,
+        database_url: Some(database_url.clone()),
+        message_content_compression: true,
+        message_content_encryption_key: Some(String::from(
+            "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc",
+        )),
+        ..AppConfig::default()
+    })
+    .await
+    .expect("router should build");
+    let db_pool = PgPool::connect(&database_url)
+        .await
+        .expect("postgres pool should connect");
+
+    let suffix = Ulid::new().to_string().to_lowercase();
+    let username = format!("pg_sealed_{}", &suffix[..16]);
+    let auth = register_and_login(&app, &username, "203.0.113.98").await;
+    let author_id = current_user_id(&app, &auth, "203.0.113.98").await;
+    let channel = create_channel_context(&app, &auth, "203.0.113.98").await;
+
+    let long_content = format!(
+        "sealedneedle {}",
+        "sealed message content survives the round trip. ".repeat(8)
+    );
+    let sealed_id = create_message(&app, &auth, &channel, "203.0.113.98", &long_content).await;
+    let short_id =
+        create_message(&app, &auth, &channel, "203.0.113.98", "sealedneedle short").await;
+
+    let stored: (String, Option<Vec<u8>>, Option<Vec<u8>>) = sqlx::query_as(
+        "SELECT content, content_zstd, content_sealed FROM messages WHERE message_id = $1",
+    )
+    .bind(&short_id)
+    .fetch_one(&db_pool)
+    .await
+    .expect("sealed row should exist");
+    assert!(stored.0.is_empty());
+    assert!(stored.1.is_none());
+    let sealed = stored.2.expect("sealed column should be populated");
+    assert!(!sealed
+        .windows(b"sealedneedle".len())
+        .any(|window| window == b"sealedneedle"));
+
+    let bootstrapped = search(&app, &auth, &channel.guild_id, "sealedneedle").await;
+    let hydrated = bootstrapped["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|message| message["message_id"] == sealed_id.as_str())
+        .expect("sealed message should hydrate");
+    assert_eq!(hydrated["content"], long_content.as_str());
+
+    let legacy_id = insert_legacy_message(
+        &db_pool,
+        &channel,
+        &author_id,
+        "sealedneedle legacy plaintext row",
+    )
+    .await;
+
+    let history_json = channel_history(&app, &auth, &channel, "203.0.113.98").await;
+    let content_by_id = |id: &str| {
+        history_json["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|message| message["message_id"] == id)
+            .and_then(|message| message["content"].as_str().map(ToOwned::to_owned))
+    };
+    assert_eq!(
+        content_by_id(&sealed_id).as_deref(),
+        Some(long_content.as_str())
+    );
+    assert_eq!(
+        content_by_id(&short_id).as_deref(),
+        Some("sealedneedle short")
+    );
+    assert_eq!(
+        content_by_id(&legacy_id).as_deref(),
+        Some("sealedneedle legacy plaintext row")
+    );
+
+    let reconcile_result = reconcile(&app, &auth, &channel.guild_id).await;
+    assert_eq!(reconcile_result["upserted"], 1);
+
+    let results = search(&app, &auth, &channel.guild_id, "sealedneedle").await;
+    let message_ids = extract_ids(&results, "message_ids");
+    assert!(message_ids.contains(&sealed_id));
+    assert!(message_ids.contains(&short_id));
+    assert!(message_ids.contains(&legacy_id));
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    in_memory_app, parse_json_body, postgres_app, register_and_login, send_json, AuthResponse,
+};
+
+// Client keys from RFC 8291 Appendix A.
+const CLIENT_P256DH: &str =
+    "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4";
+const CLIENT_AUTH: &str = "BTBZMqHH6r4Tts7J_aSIgg";
+
+fn subscription(endpoint: &str) -> Value {
+    json!({
+        "endpoint": endpoint,
+        "expirationTime": null,
+        "keys": {"p256dh": CLIENT_P256DH, "auth": CLIENT_AUTH},
+    })
+}
+
+fn unique_endpoint() -> String {
+    format!(
+        "https://push.filament.example/send/{}",
+        Ulid::new().to_string().to_lowercase()
+    )
+}
+
+async fn subscribe(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    body: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        String::from("/push/subscriptions"),
+        Some(&auth.access_token),
+        ip,
+        Some(body),
+    )
+    .await
+}
+
+async fn delete_subscription(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    subscription_id: &str,
+) -> StatusCode {
+    send_json(
+        app,
+        "DELETE",
+        format!("/push/subscriptions/{subscription_id}"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await
+    .status()
+}
+
+async fn register_refresh_and_delete(app: &axum::Router) {
+    let ip = "203.0.113.201";
+    let owner = register_and_login(app, "push_owner", ip).await;
+    let endpoint = unique_endpoint();
+
+    let created = subscribe(app, &owner, ip, subscription(&endpoint)).await;
+    assert_eq!(created.status(), StatusCode::OK);
+    let created: Value = parse_json_body(created).await;
+    let subscription_id = created["subscription_id"].as_str().unwrap().to_owned();
+    assert_eq!(created["endpoint"], endpoint);
+
+    let refreshed = subscribe(app, &owner, ip, subscription(&endpoint)).await;
+    assert_eq!(refreshed.status(), StatusCode::OK);
+    let refreshed: Value = parse_json_body(refreshed).await;
+    assert_eq!(refreshed["subscription_id"], subscription_id.as_str());
+
+    assert_eq!(
+        delete_subscription(app, &owner, ip, &subscription_id).await,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        delete_subscription(app, &owner, ip, &subscription_id).await,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn subscriptions_register_refresh_and_delete() {
+    register_refresh_and_delete(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_subscriptions_register_refresh_and_delete() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    register_refresh_and_delete(&app).await;
+}
+
+#[tokio::test]
+async fn subscriptions_reject_unsafe_endpoints_and_malformed_keys() {
+    let app = in_memory_app();
+    let ip = "203.0.113.202";
+    let owner = register_and_login(&app, "push_invalid", ip).await;
+
+    for rejected in [
+        subscription("http://push.filament.example/send/plain"),
+        subscription("https://127.0.0.1/send/loopback"),
+        subscription("https://push.filament.example:8443/send/port"),
+        json!({
+            "endpoint": "https://push.filament.example/send/short-key",
+            "keys": {"p256dh": CLIENT_AUTH, "auth": CLIENT_AUTH},
+        }),
+        json!({"endpoint": "https://push.filament.example/send/no-keys"}),
+    ] {
+        let response = subscribe(&app, &owner, ip, rejected).await;
+        assert!(
+            response.status() == StatusCode::BAD_REQUEST
+                || response.status() == StatusCode::UNPROCESSABLE_ENTITY,
+            "unexpected status {}",
+            response.status()
+        );
+    }
+}
+
+#[tokio::test]
+async fn subscriptions_can_only_be_deleted_by_their_owner() {
+    let app = in_memory_app();
+    let (owner_ip, other_ip) = ("203.0.113.203", "203.0.113.204");
+    let owner = register_and_login(&app, "push_owner", owner_ip).await;
+    let other = register_and_login(&app, "push_other", other_ip).await;
+
+    let created = subscribe(&app, &owner, owner_ip, subscription(&unique_endpoint())).await;
+    assert_eq!(created.status(), StatusCode::OK);
+    let created: Value = parse_json_body(created).await;
+    let subscription_id = created["subscription_id"].as_str().unwrap();
+
+    assert_eq!(
+        delete_subscription(&app, &other, other_ip, subscription_id).await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        delete_subscription(&app, &owner, owner_ip, "not-a-ulid").await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        delete_subscription(&app, &owner, owner_ip, subscription_id).await,
+        StatusCode::NO_CONTENT
+    );
+}
+
+#[tokio::test]
+async fn subscriptions_require_authentication() {
+    let app = in_memory_app();
+    let response = send_json(
+        &app,
+        "POST",
+        String::from("/push/subscriptions"),
+        None,
+        "203.0.113.205",
+        Some(subscription(&unique_endpoint())),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn capabilities_do_not_advertise_push() {
+    let app = in_memory_app();
+    let capabilities = send_json(
+        &app,
+        "GET",
+        String::from("/capabilities"),
+        None,
+        "203.0.113.206",
+        None,
+    )
+    .await;
+    assert_eq!(capabilities.status(), StatusCode::OK);
+    let capabilities: Value = parse_json_body(capabilities).await;
+    assert!(capabilities.get("push").is_none());
+}
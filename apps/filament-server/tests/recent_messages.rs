@@ -0,0 +1,214 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild, in_memory_app, parse_json_body, post_message, postgres_app, register_and_login,
+    send_json, user_id, AuthResponse,
+};
+
+async fn create_channel(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+) -> String {
+    let channel = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        ip,
+        Some(json!({"name":"recent-chat"})),
+    )
+    .await;
+    assert_eq!(channel.status(), StatusCode::OK);
+    let channel_json: Value = parse_json_body(channel).await;
+    channel_json["channel_id"].as_str().unwrap().to_owned()
+}
+
+async fn recent_messages(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    body: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/messages/recent"),
+        Some(&auth.access_token),
+        ip,
+        Some(body),
+    )
+    .await
+}
+
+fn contents(messages: &Value) -> Vec<&str> {
+    messages
+        .as_array()
+        .expect("channel messages should be an array")
+        .iter()
+        .map(|message| message["content"].as_str().unwrap())
+        .collect()
+}
+
+const OWNER_IP: &str = "203.0.113.221";
+const MEMBER_IP: &str = "203.0.113.222";
+
+struct RecentGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    guild_id: String,
+    general: String,
+    random: String,
+    staff: String,
+}
+
+/// Builds a guild with three posts in `general`, none in `random`, and one
+/// in `staff`, which the member is denied.
+async fn setup_recent_guild(app: &axum::Router) -> RecentGuild {
+    let owner = register_and_login(app, "recent_owner", OWNER_IP).await;
+    let member = register_and_login(app, "recent_member", MEMBER_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let guild_id = create_guild(app, &owner, OWNER_IP, json!({"name":"Recent Guild"})).await;
+    let general = create_channel(app, &owner, OWNER_IP, &guild_id).await;
+    let random = create_channel(app, &owner, OWNER_IP, &guild_id).await;
+    let staff = create_channel(app, &owner, OWNER_IP, &guild_id).await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+    let denied = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels/{staff}/permission-overrides/member/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        Some(json!({"allow":[],"deny":["create_message"]})),
+    )
+    .await;
+    assert_eq!(denied.status(), StatusCode::OK);
+
+    for content in ["one", "two", "three"] {
+        post_message(app, &owner, OWNER_IP, &guild_id, &general, content).await;
+    }
+    post_message(app, &owner, OWNER_IP, &guild_id, &staff, "staff only").await;
+    RecentGuild {
+        owner,
+        member,
+        guild_id,
+        general,
+        random,
+        staff,
+    }
+}
+
+impl RecentGuild {
+    /// Asks for every channel plus an unknown id and a duplicate.
+    fn batch_body(&self) -> Value {
+        json!({
+            "channel_ids":[self.general, self.random, self.staff, Ulid::new().to_string(), self.general],
+            "limit":2,
+        })
+    }
+}
+
+async fn fetch_member_recent_messages(app: &axum::Router) {
+    let guild = setup_recent_guild(app).await;
+    let response = recent_messages(
+        app,
+        &guild.member,
+        MEMBER_IP,
+        &guild.guild_id,
+        guild.batch_body(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: Value = parse_json_body(response).await;
+    let channels = response["channels"]
+        .as_object()
+        .expect("channels should be an object");
+    assert_eq!(channels.len(), 2);
+    assert_eq!(contents(&channels[&guild.general]), ["three", "two"]);
+    assert!(contents(&channels[&guild.random]).is_empty());
+}
+
+#[tokio::test]
+async fn recent_messages_return_the_newest_posts_of_readable_channels() {
+    fetch_member_recent_messages(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_recent_messages_return_the_newest_posts_of_readable_channels() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    fetch_member_recent_messages(&app).await;
+}
+
+#[tokio::test]
+async fn owners_see_recent_messages_in_channels_denied_to_members() {
+    let app = in_memory_app();
+    let guild = setup_recent_guild(&app).await;
+    let owner_view = recent_messages(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        guild.batch_body(),
+    )
+    .await;
+    assert_eq!(owner_view.status(), StatusCode::OK);
+    let owner_view: Value = parse_json_body(owner_view).await;
+    assert_eq!(
+        contents(&owner_view["channels"][&guild.staff]),
+        ["staff only"]
+    );
+}
+
+#[tokio::test]
+async fn recent_messages_are_not_found_for_outsiders() {
+    let app = in_memory_app();
+    let guild = setup_recent_guild(&app).await;
+    let outsider = register_and_login(&app, "recent_out", MEMBER_IP).await;
+    let outsider_view = recent_messages(
+        &app,
+        &outsider,
+        MEMBER_IP,
+        &guild.guild_id,
+        json!({"channel_ids":[guild.general]}),
+    )
+    .await;
+    assert_eq!(outsider_view.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn recent_messages_reject_malformed_batches() {
+    let app = in_memory_app();
+    let guild = setup_recent_guild(&app).await;
+    let general = &guild.general;
+    let too_many: Vec<String> = (0..51).map(|_| Ulid::new().to_string()).collect();
+    for invalid in [
+        json!({"channel_ids":[]}),
+        json!({"channel_ids":too_many}),
+        json!({"channel_ids":[general], "limit":0}),
+        json!({"channel_ids":[general], "limit":21}),
+        json!({"channel_ids":["not-a-channel"]}),
+        json!({"channel_ids":[general], "before":general}),
+    ] {
+        let rejected =
+            recent_messages(&app, &guild.member, MEMBER_IP, &guild.guild_id, invalid).await;
+        assert!(rejected.status().is_client_error());
+        assert_ne!(rejected.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use filament_server::{build_router, AppConfig};
+use serde_json::json;
+
+use common::{
+    in_memory_app_with, parse_json_body, postgres_app_with, register_and_login, send_json,
+    AuthResponse,
+};
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        refresh_token_reuse_grace: Duration::from_secs(30),
+        ..common::test_config()
+    }
+}
+
+async fn refresh(app: &axum::Router, ip: &str, refresh_token: &str) -> axum::response::Response {
+    send_json(
+        app,
+        "POST",
+        String::from("/auth/refresh"),
+        None,
+        ip,
+        Some(json!({"refresh_token":refresh_token})),
+    )
+    .await
+}
+
+async fn refreshed(app: &axum::Router, ip: &str, refresh_token: &str) -> AuthResponse {
+    let response = refresh(app, ip, refresh_token).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    parse_json_body(response).await
+}
+
+async fn retry_lost_refresh(app: &axum::Router) {
+    let ip = "203.0.113.213";
+    let login = register_and_login(app, "retry", ip).await;
+    let lost = refreshed(app, ip, &login.refresh_token).await;
+
+    // The client never saw `lost` and retries with the token it still holds.
+    let retried = refreshed(app, ip, &login.refresh_token).await;
+    assert_ne!(retried.refresh_token, lost.refresh_token);
+    assert!(!retried.access_token.is_empty());
+}
+
+#[tokio::test]
+async fn retried_refreshes_within_the_grace_window_get_a_fresh_pair() {
+    retry_lost_refresh(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_retried_refreshes_within_the_grace_window_get_a_fresh_pair() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    retry_lost_refresh(&app).await;
+}
+
+async fn replay_after_retry(app: &axum::Router) {
+    let ip = "203.0.113.214";
+    let login = register_and_login(app, "retry", ip).await;
+    refreshed(app, ip, &login.refresh_token).await;
+    let retried = refreshed(app, ip, &login.refresh_token).await;
+
+    // The retry superseded the first pair, so the login token is no longer
+    // the immediately previous one and presenting it again is a replay.
+    let replayed = refresh(app, ip, &login.refresh_token).await;
+    assert_eq!(replayed.status(), StatusCode::UNAUTHORIZED);
+    let after_replay = refresh(app, ip, &retried.refresh_token).await;
+    assert_eq!(after_replay.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn second_retries_of_a_refresh_token_revoke_the_session() {
+    replay_after_retry(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_second_retries_of_a_refresh_token_revoke_the_session() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    replay_after_retry(&app).await;
+}
+
+async fn use_superseded_pair(app: &axum::Router) {
+    let ip = "203.0.113.215";
+    let login = register_and_login(app, "retry", ip).await;
+    let superseded = refreshed(app, ip, &login.refresh_token).await;
+    let extra = refreshed(app, ip, &login.refresh_token).await;
+
+    // A grace retry mints a further pair instead of handing back the one it
+    // supersedes. When the holder of the superseded pair refreshes, that is a
+    // replay and the retry's pair is revoked with the rest of the session.
+    let replayed = refresh(app, ip, &superseded.refresh_token).await;
+    assert_eq!(replayed.status(), StatusCode::UNAUTHORIZED);
+    let extra_after_revoke = refresh(app, ip, &extra.refresh_token).await;
+    assert_eq!(extra_after_revoke.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn refreshing_a_pair_superseded_by_a_retry_revokes_the_session() {
+    use_superseded_pair(&in_memory_app_with(&test_config())).await;
+}
+
+#[tokio::test]
+async fn postgres_refreshing_a_pair_superseded_by_a_retry_revokes_the_session() {
+    let Some(app) = postgres_app_with(test_config()).await else {
+        return;
+    };
+    use_superseded_pair(&app).await;
+}
+
+#[test]
+fn refresh_reuse_grace_is_bounded() {
+    assert!(build_router(&AppConfig {
+        refresh_token_reuse_grace: Duration::from_secs(61),
+        ..AppConfig::default()
+    })
+    .is_err());
+}
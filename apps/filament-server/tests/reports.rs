@@ -0,0 +1,242 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_guild_with_channel, in_memory_app, parse_json_body, post_message, postgres_app,
+    register_and_login, send_json, user_id, AuthResponse,
+};
+
+const OWNER_IP: &str = "203.0.113.181";
+const MEMBER_IP: &str = "203.0.113.182";
+const OUTSIDER_IP: &str = "203.0.113.183";
+
+struct ReportGuild {
+    owner: AuthResponse,
+    member: AuthResponse,
+    owner_user_id: String,
+    guild_id: String,
+    message_id: String,
+    message_report_uri: String,
+}
+
+async fn setup_report_guild(app: &axum::Router) -> ReportGuild {
+    let owner = register_and_login(app, "rpt_owner", OWNER_IP).await;
+    let member = register_and_login(app, "rpt_member", MEMBER_IP).await;
+    let owner_user_id = user_id(app, &owner, OWNER_IP).await;
+    let member_user_id = user_id(app, &member, MEMBER_IP).await;
+    let (guild_id, channel_id) =
+        create_guild_with_channel(app, &owner, OWNER_IP, "Report Guild", "report-chat").await;
+    let message_id = post_message(
+        app,
+        &owner,
+        OWNER_IP,
+        &guild_id,
+        &channel_id,
+        "reported content",
+    )
+    .await;
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{member_user_id}"),
+        Some(&owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+    let message_report_uri =
+        format!("/guilds/{guild_id}/channels/{channel_id}/messages/{message_id}/report");
+    ReportGuild {
+        owner,
+        member,
+        owner_user_id,
+        guild_id,
+        message_id,
+        message_report_uri,
+    }
+}
+
+async fn list_reports(app: &axum::Router, guild: &ReportGuild, query: &str) -> Value {
+    let page = send_json(
+        app,
+        "GET",
+        format!("/guilds/{}/reports?{query}", guild.guild_id),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(page.status(), StatusCode::OK);
+    parse_json_body(page).await
+}
+
+async fn file_and_page_reports(app: &axum::Router) {
+    let guild = setup_report_guild(app).await;
+
+    let message_report = send_json(
+        app,
+        "POST",
+        guild.message_report_uri.clone(),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        Some(json!({"reason":"spam link"})),
+    )
+    .await;
+    assert_eq!(message_report.status(), StatusCode::OK);
+    let message_report: Value = parse_json_body(message_report).await;
+    assert_eq!(
+        message_report["target_user_id"],
+        guild.owner_user_id.as_str()
+    );
+    assert_eq!(message_report["message_id"], guild.message_id.as_str());
+    assert_eq!(message_report["message_content"], "reported content");
+    assert_eq!(message_report["reason"], "spam link");
+
+    let user_report = send_json(
+        app,
+        "POST",
+        format!("/users/{}/report", guild.owner_user_id),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        Some(json!({"guild_id":guild.guild_id,"reason":"harassment"})),
+    )
+    .await;
+    assert_eq!(user_report.status(), StatusCode::OK);
+    let user_report: Value = parse_json_body(user_report).await;
+    assert!(user_report.get("message_id").is_none());
+
+    let first_page = list_reports(app, &guild, "limit=1").await;
+    assert_eq!(first_page["reports"].as_array().unwrap().len(), 1);
+    let next_before = first_page["next_before"].as_str().unwrap();
+    let second_page = list_reports(app, &guild, &format!("limit=1&before={next_before}")).await;
+    let mut listed = [&first_page, &second_page]
+        .iter()
+        .flat_map(|page| page["reports"].as_array().unwrap().clone())
+        .map(|report| report["report_id"].as_str().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    listed.sort();
+    let mut created = vec![
+        message_report["report_id"].as_str().unwrap().to_owned(),
+        user_report["report_id"].as_str().unwrap().to_owned(),
+    ];
+    created.sort();
+    assert_eq!(listed, created);
+    assert!(second_page["next_before"].is_null());
+}
+
+#[tokio::test]
+async fn message_and_user_reports_are_listed_for_moderators_page_by_page() {
+    file_and_page_reports(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_message_and_user_reports_are_listed_for_moderators_page_by_page() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    file_and_page_reports(&app).await;
+}
+
+#[tokio::test]
+async fn reports_reject_blank_reasons_self_reports_and_outsiders() {
+    let app = in_memory_app();
+    let guild = setup_report_guild(&app).await;
+    let outsider = register_and_login(&app, "rpt_out", OUTSIDER_IP).await;
+    let outsider_user_id = user_id(&app, &outsider, OUTSIDER_IP).await;
+    let user_report_uri = |user_id: &str| format!("/users/{user_id}/report");
+
+    for (auth, ip, uri, body, expected) in [
+        (
+            &guild.member,
+            MEMBER_IP,
+            guild.message_report_uri.clone(),
+            json!({"reason":"   "}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            &guild.owner,
+            OWNER_IP,
+            guild.message_report_uri.clone(),
+            json!({"reason":"my own message"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            &outsider,
+            OUTSIDER_IP,
+            guild.message_report_uri.clone(),
+            json!({"reason":"spam"}),
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            &guild.member,
+            MEMBER_IP,
+            user_report_uri(&outsider_user_id),
+            json!({"guild_id":guild.guild_id,"reason":"not here"}),
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            &outsider,
+            OUTSIDER_IP,
+            user_report_uri(&guild.owner_user_id),
+            json!({"guild_id":guild.guild_id,"reason":"probe"}),
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let response = send_json(
+            &app,
+            "POST",
+            uri.clone(),
+            Some(&auth.access_token),
+            ip,
+            Some(body),
+        )
+        .await;
+        assert_eq!(response.status(), expected, "uri {uri}");
+    }
+    let reports = list_reports(&app, &guild, "limit=10").await;
+    assert!(reports["reports"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn only_moderators_can_list_reports() {
+    let app = in_memory_app();
+    let guild = setup_report_guild(&app).await;
+    let member_list = send_json(
+        &app,
+        "GET",
+        format!("/guilds/{}/reports", guild.guild_id),
+        Some(&guild.member.access_token),
+        MEMBER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(member_list.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn reporters_are_rate_limited_per_window() {
+    let app = in_memory_app();
+    let guild = setup_report_guild(&app).await;
+    for expected in [
+        StatusCode::OK,
+        StatusCode::OK,
+        StatusCode::OK,
+        StatusCode::OK,
+        StatusCode::OK,
+        StatusCode::TOO_MANY_REQUESTS,
+    ] {
+        let response = send_json(
+            &app,
+            "POST",
+            guild.message_report_uri.clone(),
+            Some(&guild.member.access_token),
+            MEMBER_IP,
+            Some(json!({"reason":"again"})),
+        )
+        .await;
+        assert_eq!(response.status(), expected);
+    }
+}
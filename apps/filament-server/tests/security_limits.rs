@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
 use axum::{body::Body, http::Request, http::StatusCode};
@@ -10,6 +11,7 @@ async fn rejects_body_over_limit() {
         max_body_bytes: 32,
         request_timeout: Duration::from_secs(1),
         rate_limit_requests_per_minute: 60,
+        enable_debug_routes: true,
         ..AppConfig::default()
     };
     let app = build_router(&config).unwrap();
@@ -34,6 +36,7 @@ async fn times_out_slow_requests() {
         max_body_bytes: 1024,
         request_timeout: Duration::from_millis(20),
         rate_limit_requests_per_minute: 60,
+        enable_debug_routes: true,
         ..AppConfig::default()
     };
     let app = build_router(&config).unwrap();
@@ -76,3 +79,271 @@ async fn rate_limits_per_client_ip() {
     assert_eq!(second.status(), StatusCode::OK);
     assert_eq!(third.status(), StatusCode::TOO_MANY_REQUESTS);
 }
+
+#[tokio::test]
+async fn route_rate_limit_override_applies_only_to_configured_route() {
+    let config = AppConfig {
+        max_body_bytes: 1024,
+        request_timeout: Duration::from_secs(1),
+        rate_limit_requests_per_minute: 60,
+        route_rate_limits: HashMap::from([(String::from("/health"), 1)]),
+        ..AppConfig::default()
+    };
+    let app = build_router(&config).unwrap();
+
+    let request = |uri: &str| {
+        Request::builder()
+            .method("GET")
+            .uri(uri)
+            .header("x-forwarded-for", "198.51.100.10")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let first = app.clone().oneshot(request("/health")).await.unwrap();
+    let second = app.clone().oneshot(request("/health")).await.unwrap();
+    let other_route = app.oneshot(request("/metrics")).await.unwrap();
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        second
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok()),
+        Some("60")
+    );
+    assert_eq!(other_route.status(), StatusCode::OK);
+}
+
+#[test]
+fn invalid_route_rate_limit_overrides_are_rejected() {
+    for (route, limit) in [
+        ("/health", 0),
+        ("health", 10),
+        ("/guilds/{id}/channels", 10),
+        ("/not-a-route", 10),
+    ] {
+        let result = build_router(&AppConfig {
+            route_rate_limits: HashMap::from([(String::from(route), limit)]),
+            ..AppConfig::default()
+        });
+        assert!(result.is_err(), "{route}={limit} should be rejected");
+    }
+}
+
+#[tokio::test]
+async fn route_rate_limit_overrides_are_capped() {
+    let with_override = |limit| {
+        build_router(&AppConfig {
+            rate_limit_requests_per_minute: 60,
+            route_rate_limits: HashMap::from([(String::from("/guilds/{guild_id}/search"), limit)]),
+            ..AppConfig::default()
+        })
+    };
+
+    assert!(with_override(60).is_ok());
+    assert!(with_override(6_000).is_ok());
+    assert!(
+        with_override(6_001).is_err(),
+        "overrides above the hard ceiling should be rejected"
+    );
+}
+
+#[tokio::test]
+async fn looser_route_rate_limit_override_replaces_the_global_limit() {
+    let config = AppConfig {
+        max_body_bytes: 1024,
+        request_timeout: Duration::from_secs(1),
+        rate_limit_requests_per_minute: 2,
+        route_rate_limits: HashMap::from([(String::from("/health"), 4)]),
+        ..AppConfig::default()
+    };
+    let app = build_router(&config).unwrap();
+
+    let request = |uri: &str| {
+        Request::builder()
+            .method("GET")
+            .uri(uri)
+            .header("x-forwarded-for", "198.51.100.12")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    for attempt in 0..4 {
+        let response = app.clone().oneshot(request("/health")).await.unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::OK,
+            "request {attempt} should fit the looser route limit"
+        );
+    }
+    let fifth = app.clone().oneshot(request("/health")).await.unwrap();
+    assert_eq!(fifth.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let first_metrics = app.clone().oneshot(request("/metrics")).await.unwrap();
+    let second_metrics = app.clone().oneshot(request("/metrics")).await.unwrap();
+    let third_metrics = app.oneshot(request("/metrics")).await.unwrap();
+    assert_eq!(first_metrics.status(), StatusCode::OK);
+    assert_eq!(second_metrics.status(), StatusCode::OK);
+    assert_eq!(
+        third_metrics.status(),
+        StatusCode::TOO_MANY_REQUESTS,
+        "other routes keep the global limit"
+    );
+}
+
+#[tokio::test]
+async fn unmatched_paths_still_draw_from_the_global_limit() {
+    let config = AppConfig {
+        max_body_bytes: 1024,
+        request_timeout: Duration::from_secs(1),
+        rate_limit_requests_per_minute: 1,
+        ..AppConfig::default()
+    };
+    let app = build_router(&config).unwrap();
+
+    let request = |uri: &str| {
+        Request::builder()
+            .method("GET")
+            .uri(uri)
+            .header("x-forwarded-for", "198.51.100.13")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let first = app
+        .clone()
+        .oneshot(request("/no-such-route"))
+        .await
+        .unwrap();
+    let second = app.oneshot(request("/no-such-route")).await.unwrap();
+    assert_eq!(first.status(), StatusCode::NOT_FOUND);
+    assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn global_limit_still_applies_to_routes_with_an_override() {
+    let config = AppConfig {
+        max_body_bytes: 1024,
+        request_timeout: Duration::from_secs(1),
+        rate_limit_requests_per_minute: 2,
+        route_rate_limits: HashMap::from([(String::from("/health"), 2)]),
+        ..AppConfig::default()
+    };
+    let app = build_router(&config).unwrap();
+
+    let request = |uri: &str| {
+        Request::builder()
+            .method("GET")
+            .uri(uri)
+            .header("x-forwarded-for", "198.51.100.11")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let metrics = app.clone().oneshot(request("/metrics")).await.unwrap();
+    let first_health = app.clone().oneshot(request("/health")).await.unwrap();
+    let second_health = app.oneshot(request("/health")).await.unwrap();
+
+    assert_eq!(metrics.status(), StatusCode::OK);
+    assert_eq!(first_health.status(), StatusCode::OK);
+    assert_eq!(
+        second_health.status(),
+        StatusCode::TOO_MANY_REQUESTS,
+        "the override has budget left, but the shared global bucket does not"
+    );
+}
+
+#[tokio::test]
+async fn route_body_limit_override_replaces_global_limit_for_route() {
+    let payload = r#"{"message":"this payload is definitely too large"}"#;
+    let request = || {
+        Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .header("content-type", "application/json")
+            .header("x-forwarded-for", "203.0.113.11")
+            .body(Body::from(payload))
+            .unwrap()
+    };
+
+    let raised = build_router(&AppConfig {
+        max_body_bytes: 32,
+        request_timeout: Duration::from_secs(1),
+        route_body_limits: HashMap::from([(String::from("/echo"), 1024)]),
+        enable_debug_routes: true,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let response = raised.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let tightened = build_router(&AppConfig {
+        max_body_bytes: 1024,
+        request_timeout: Duration::from_secs(1),
+        route_body_limits: HashMap::from([(String::from("/echo"), 16)]),
+        enable_debug_routes: true,
+        ..AppConfig::default()
+    })
+    .unwrap();
+    let response = tightened.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn debug_routes_are_not_mounted_by_default() {
+    let app = build_router(&AppConfig::default()).unwrap();
+    for (method, uri) in [("POST", "/echo"), ("GET", "/slow")] {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("x-forwarded-for", "203.0.113.12")
+            .body(Body::from(r#"{"message":"hi"}"#))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{method} {uri}");
+    }
+}
+
+#[test]
+fn invalid_route_body_limit_overrides_are_rejected() {
+    for (route, limit) in [("/echo", 0), ("echo", 1024), ("/echo", 64 * 1024 * 1024)] {
+        let result = build_router(&AppConfig {
+            route_body_limits: HashMap::from([(String::from(route), limit)]),
+            ..AppConfig::default()
+        });
+        assert!(result.is_err(), "{route}={limit} should be rejected");
+    }
+}
+
+#[test]
+fn route_body_limit_overrides_must_name_registered_non_upload_routes() {
+    let rejection = |route: &str| {
+        build_router(&AppConfig {
+            route_body_limits: HashMap::from([(String::from(route), 1024)]),
+            ..AppConfig::default()
+        })
+        .err()
+        .map(|error| error.to_string())
+        .unwrap_or_default()
+    };
+
+    for route in ["/not/a/route", "/guilds/{id}/search", &"/a".repeat(200)] {
+        assert!(
+            rejection(route).contains("registered route path template"),
+            "{route} should be rejected as unregistered"
+        );
+    }
+    for route in [
+        "/guilds/{guild_id}/channels/{channel_id}/attachments",
+        "/users/me/profile/avatar",
+        "/users/me/profile/banner",
+    ] {
+        assert!(
+            rejection(route).contains("upload route"),
+            "{route} should be rejected as an upload route"
+        );
+    }
+}
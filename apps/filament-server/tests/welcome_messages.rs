@@ -0,0 +1,330 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use ulid::Ulid;
+
+use common::{
+    create_guild, in_memory_app, parse_json_body, post_message, postgres_app, register_and_login,
+    send_json, user_id, AuthResponse,
+};
+
+async fn create_channel(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    body: Value,
+) -> String {
+    let channel = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/channels"),
+        Some(&owner.access_token),
+        ip,
+        Some(body),
+    )
+    .await;
+    assert_eq!(channel.status(), StatusCode::OK);
+    let channel_json: Value = parse_json_body(channel).await;
+    channel_json["channel_id"].as_str().unwrap().to_owned()
+}
+
+async fn set_system_channel(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    body: Value,
+) -> axum::response::Response {
+    send_json(
+        app,
+        "PATCH",
+        format!("/guilds/{guild_id}/system-channel"),
+        Some(&auth.access_token),
+        ip,
+        Some(body),
+    )
+    .await
+}
+
+async fn add_member(
+    app: &axum::Router,
+    owner: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    user_id: &str,
+) {
+    let added = send_json(
+        app,
+        "POST",
+        format!("/guilds/{guild_id}/members/{user_id}"),
+        Some(&owner.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(added.status(), StatusCode::OK);
+}
+
+async fn history(
+    app: &axum::Router,
+    auth: &AuthResponse,
+    ip: &str,
+    guild_id: &str,
+    channel_id: &str,
+) -> Vec<Value> {
+    let listed = send_json(
+        app,
+        "GET",
+        format!("/guilds/{guild_id}/channels/{channel_id}/messages"),
+        Some(&auth.access_token),
+        ip,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed: Value = parse_json_body(listed).await;
+    listed["messages"].as_array().unwrap().clone()
+}
+
+const OWNER_IP: &str = "203.0.113.241";
+const MEMBER_IP: &str = "203.0.113.242";
+const JOINER_IP: &str = "203.0.113.243";
+
+struct WelcomeGuild {
+    owner: AuthResponse,
+    guild_id: String,
+    welcome_id: String,
+    voice_id: String,
+}
+
+async fn setup_welcome_guild(app: &axum::Router) -> WelcomeGuild {
+    let owner = register_and_login(app, "welcome_owner", OWNER_IP).await;
+    let guild_id = create_guild(
+        app,
+        &owner,
+        OWNER_IP,
+        json!({"name":"Welcome Guild","visibility":"public"}),
+    )
+    .await;
+    let welcome_id =
+        create_channel(app, &owner, OWNER_IP, &guild_id, json!({"name":"welcome"})).await;
+    let voice_id = create_channel(
+        app,
+        &owner,
+        OWNER_IP,
+        &guild_id,
+        json!({"name":"lounge","kind":"voice"}),
+    )
+    .await;
+    WelcomeGuild {
+        owner,
+        guild_id,
+        welcome_id,
+        voice_id,
+    }
+}
+
+async fn set_welcome_messages(app: &axum::Router, guild: &WelcomeGuild, enabled: bool) {
+    let updated = set_system_channel(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        json!({"system_channel_id":guild.welcome_id,"welcome_messages":enabled}),
+    )
+    .await;
+    assert_eq!(updated.status(), StatusCode::OK);
+    let updated: Value = parse_json_body(updated).await;
+    assert_eq!(updated["system_channel_id"], guild.welcome_id.as_str());
+    assert_eq!(updated["welcome_messages"], enabled);
+}
+
+async fn welcome_history(app: &axum::Router, guild: &WelcomeGuild) -> Vec<Value> {
+    history(
+        app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &guild.welcome_id,
+    )
+    .await
+}
+
+async fn welcome_joiners(app: &axum::Router) {
+    let guild = setup_welcome_guild(app).await;
+    let joiner = register_and_login(app, "welcome_joiner", JOINER_IP).await;
+    let late = register_and_login(app, "welcome_late", MEMBER_IP).await;
+    let joiner_user_id = user_id(app, &joiner, JOINER_IP).await;
+    let late_user_id = user_id(app, &late, MEMBER_IP).await;
+    set_welcome_messages(app, &guild, true).await;
+
+    let directory_join = send_json(
+        app,
+        "POST",
+        format!("/guilds/{}/join", guild.guild_id),
+        Some(&joiner.access_token),
+        JOINER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(directory_join.status(), StatusCode::OK);
+    add_member(app, &guild.owner, OWNER_IP, &guild.guild_id, &late_user_id).await;
+
+    let messages = welcome_history(app, &guild).await;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["author_id"], late_user_id.as_str());
+    assert_eq!(messages[1]["author_id"], joiner_user_id.as_str());
+    for message in &messages {
+        assert_eq!(message["system"], true);
+        assert_eq!(message["content"], "joined the guild");
+    }
+}
+
+#[tokio::test]
+async fn joins_post_welcome_messages_to_the_system_channel() {
+    welcome_joiners(&in_memory_app()).await;
+}
+
+#[tokio::test]
+async fn postgres_joins_post_welcome_messages_to_the_system_channel() {
+    let Some(app) = postgres_app().await else {
+        return;
+    };
+    welcome_joiners(&app).await;
+}
+
+#[tokio::test]
+async fn guilds_start_without_welcome_messages() {
+    let app = in_memory_app();
+    let guild = setup_welcome_guild(&app).await;
+    let member = register_and_login(&app, "welcome_member", MEMBER_IP).await;
+    let member_user_id = user_id(&app, &member, MEMBER_IP).await;
+
+    let settings = send_json(
+        &app,
+        "GET",
+        format!("/guilds/{}/system-channel", guild.guild_id),
+        Some(&guild.owner.access_token),
+        OWNER_IP,
+        None,
+    )
+    .await;
+    assert_eq!(settings.status(), StatusCode::OK);
+    let settings: Value = parse_json_body(settings).await;
+    assert_eq!(
+        settings,
+        json!({"system_channel_id":null,"welcome_messages":false})
+    );
+    add_member(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &member_user_id,
+    )
+    .await;
+    assert!(welcome_history(&app, &guild).await.is_empty());
+}
+
+#[tokio::test]
+async fn system_channel_settings_require_a_manager_and_an_existing_text_channel() {
+    let app = in_memory_app();
+    let guild = setup_welcome_guild(&app).await;
+    let member = register_and_login(&app, "welcome_member", MEMBER_IP).await;
+    let member_user_id = user_id(&app, &member, MEMBER_IP).await;
+    add_member(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &member_user_id,
+    )
+    .await;
+
+    let forbidden = set_system_channel(
+        &app,
+        &member,
+        MEMBER_IP,
+        &guild.guild_id,
+        json!({"system_channel_id":guild.welcome_id,"welcome_messages":true}),
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    for invalid in [
+        json!({"system_channel_id":null,"welcome_messages":true}),
+        json!({"system_channel_id":guild.voice_id,"welcome_messages":true}),
+        json!({"system_channel_id":Ulid::new().to_string(),"welcome_messages":true}),
+    ] {
+        let rejected =
+            set_system_channel(&app, &guild.owner, OWNER_IP, &guild.guild_id, invalid).await;
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn welcome_messages_cannot_be_edited_and_member_posts_are_not_system_messages() {
+    let app = in_memory_app();
+    let guild = setup_welcome_guild(&app).await;
+    let joiner = register_and_login(&app, "welcome_joiner", JOINER_IP).await;
+    let joiner_user_id = user_id(&app, &joiner, JOINER_IP).await;
+    set_welcome_messages(&app, &guild, true).await;
+    add_member(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &joiner_user_id,
+    )
+    .await;
+
+    let messages = welcome_history(&app, &guild).await;
+    let message_id = messages[0]["message_id"].as_str().unwrap();
+    let edit = send_json(
+        &app,
+        "PATCH",
+        format!(
+            "/guilds/{}/channels/{}/messages/{message_id}",
+            guild.guild_id, guild.welcome_id
+        ),
+        Some(&joiner.access_token),
+        JOINER_IP,
+        Some(json!({"content":"rewritten"})),
+    )
+    .await;
+    assert_eq!(edit.status(), StatusCode::FORBIDDEN);
+
+    post_message(
+        &app,
+        &joiner,
+        JOINER_IP,
+        &guild.guild_id,
+        &guild.welcome_id,
+        "hello",
+    )
+    .await;
+    let messages = welcome_history(&app, &guild).await;
+    assert_eq!(messages[0]["content"], "hello");
+    assert!(messages[0].get("system").is_none());
+}
+
+#[tokio::test]
+async fn disabled_welcome_messages_stop_posting_on_join() {
+    let app = in_memory_app();
+    let guild = setup_welcome_guild(&app).await;
+    let member = register_and_login(&app, "welcome_member", MEMBER_IP).await;
+    let member_user_id = user_id(&app, &member, MEMBER_IP).await;
+    set_welcome_messages(&app, &guild, true).await;
+    set_welcome_messages(&app, &guild, false).await;
+
+    add_member(
+        &app,
+        &guild.owner,
+        OWNER_IP,
+        &guild.guild_id,
+        &member_user_id,
+    )
+    .await;
+    assert!(welcome_history(&app, &guild).await.is_empty());
+}
@@ -183,6 +183,8 @@ impl TryFrom<String> for ChannelName {
 pub enum ChannelKind {
     Text,
     Voice,
+    /// Groups other channels; it holds no messages of its own.
+    Category,
 }
 
 impl ChannelKind {
@@ -191,8 +193,15 @@ impl ChannelKind {
         match self {
             Self::Text => "text",
             Self::Voice => "voice",
+            Self::Category => "category",
         }
     }
+
+    /// Whether messages can be posted to or moved into this kind of channel.
+    #[must_use]
+    pub const fn holds_messages(self) -> bool {
+        !matches!(self, Self::Category)
+    }
 }
 
 impl TryFrom<String> for ChannelKind {
@@ -202,6 +211,7 @@ impl TryFrom<String> for ChannelKind {
         match value.as_str() {
             "text" => Ok(Self::Text),
             "voice" => Ok(Self::Voice),
+            "category" => Ok(Self::Category),
             _ => Err(DomainError::InvalidChannelKind),
         }
     }
@@ -268,6 +278,7 @@ pub enum Permission {
     PublishVideo,
     PublishScreenShare,
     SubscribeStreams,
+    MentionEveryone,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -335,6 +346,14 @@ pub fn can_moderate_member_legacy(actor: Role, target: Role) -> bool {
     !matches!(target, Role::Owner) && role_rank(actor) > role_rank(target)
 }
 
+/// Channel overrides may only target roles at or below the actor, so a
+/// moderator cannot deny permissions to the owner role.
+#[must_use]
+pub fn can_override_role_legacy(actor: Role, target: Role) -> bool {
+    has_permission_legacy(actor, Permission::ManageChannelOverrides)
+        && role_rank(actor) >= role_rank(target)
+}
+
 #[must_use]
 pub fn base_permissions_legacy(role: Role) -> PermissionSet {
     let mut set = PermissionSet::empty();
@@ -352,6 +371,7 @@ pub fn base_permissions_legacy(role: Role) -> PermissionSet {
             set.insert(Permission::PublishVideo);
             set.insert(Permission::PublishScreenShare);
             set.insert(Permission::SubscribeStreams);
+            set.insert(Permission::MentionEveryone);
         }
         Role::Moderator => {
             set.insert(Permission::ManageMemberRoles);
@@ -363,6 +383,7 @@ pub fn base_permissions_legacy(role: Role) -> PermissionSet {
             set.insert(Permission::PublishVideo);
             set.insert(Permission::PublishScreenShare);
             set.insert(Permission::SubscribeStreams);
+            set.insert(Permission::MentionEveryone);
         }
         Role::Member => {
             set.insert(Permission::CreateMessage);
@@ -481,6 +502,7 @@ fn permission_mask(permission: Permission) -> u64 {
         Permission::PublishVideo => 1 << 9,
         Permission::PublishScreenShare => 1 << 10,
         Permission::SubscribeStreams => 1 << 11,
+        Permission::MentionEveryone => 1 << 12,
     }
 }
 
@@ -871,10 +893,10 @@ fn validate_profile_about(value: &str) -> Result<(), DomainError> {
 mod tests {
     use super::{
         apply_channel_overwrite_legacy, base_permissions_legacy, can_assign_role_legacy,
-        can_moderate_member_legacy, has_permission_legacy, project_name, role_rank,
-        tokenize_markdown, ChannelKind, ChannelName, ChannelPermissionOverwrite, DomainError,
-        GuildName, LiveKitIdentity, LiveKitRoomName, MarkdownToken, Permission, PermissionSet,
-        ProfileAbout, Role, UserId, Username,
+        can_moderate_member_legacy, can_override_role_legacy, has_permission_legacy, project_name,
+        role_rank, tokenize_markdown, ChannelKind, ChannelName, ChannelPermissionOverwrite,
+        DomainError, GuildName, LiveKitIdentity, LiveKitRoomName, MarkdownToken, Permission,
+        PermissionSet, ProfileAbout, Role, UserId, Username,
     };
 
     #[test]
@@ -918,6 +940,11 @@ mod tests {
         let voice = ChannelKind::try_from(String::from("voice")).unwrap();
         assert_eq!(text.as_str(), "text");
         assert_eq!(voice.as_str(), "voice");
+        let category = ChannelKind::try_from(String::from("category")).unwrap();
+        assert_eq!(category.as_str(), "category");
+        assert!(text.holds_messages());
+        assert!(voice.holds_messages());
+        assert!(!category.holds_messages());
         assert_eq!(
             ChannelKind::try_from(String::from("video")).unwrap_err(),
             DomainError::InvalidChannelKind
@@ -1009,6 +1036,14 @@ mod tests {
             Role::Member,
             Permission::PublishScreenShare
         ));
+        assert!(has_permission_legacy(
+            Role::Moderator,
+            Permission::MentionEveryone
+        ));
+        assert!(!has_permission_legacy(
+            Role::Member,
+            Permission::MentionEveryone
+        ));
         assert!(has_permission_legacy(
             Role::Member,
             Permission::CreateMessage
@@ -1048,6 +1083,10 @@ mod tests {
             Role::Member,
             Role::Owner
         ));
+        assert!(can_override_role_legacy(Role::Owner, Role::Owner));
+        assert!(can_override_role_legacy(Role::Owner, Role::Member));
+        assert!(!can_override_role_legacy(Role::Moderator, Role::Owner));
+        assert!(!can_override_role_legacy(Role::Member, Role::Member));
         assert!(can_moderate_member_legacy(Role::Owner, Role::Moderator));
         assert!(can_moderate_member_legacy(Role::Moderator, Role::Member));
         assert!(!can_moderate_member_legacy(